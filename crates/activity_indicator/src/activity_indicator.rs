@@ -11,7 +11,7 @@ use gpui::{
 use language::{
     LanguageRegistry, LanguageServerBinaryStatus, LanguageServerId, LanguageServerName,
 };
-use project::{LanguageServerProgress, Project};
+use project::{GitRemoteOperation, LanguageServerProgress, Project};
 use smallvec::SmallVec;
 use std::{cmp::Reverse, fmt::Write, sync::Arc, time::Duration};
 use ui::{prelude::*, ContextMenu};
@@ -219,6 +219,36 @@ impl ActivityIndicator {
             };
         }
 
+        // Show any git fetch, pull or push that is running.
+        let mut git_operations = self.project.read(cx).pending_git_remote_operations();
+        if let Some(operation) = git_operations.next() {
+            let mut message = match operation {
+                GitRemoteOperation::Fetch => "Fetching...",
+                GitRemoteOperation::Pull => "Pulling...",
+                GitRemoteOperation::Push => "Pushing...",
+            }
+            .to_string();
+            let additional_operation_count = git_operations.count();
+            if additional_operation_count > 0 {
+                write!(&mut message, " + {} more", additional_operation_count).unwrap();
+            }
+
+            return Content {
+                icon: Some(
+                    Icon::new(IconName::ArrowCircle)
+                        .size(IconSize::Small)
+                        .with_animation(
+                            "arrow-circle",
+                            Animation::new(Duration::from_secs(2)).repeat(),
+                            |icon, delta| icon.transform(Transformation::rotate(percentage(delta))),
+                        )
+                        .into_any_element(),
+                ),
+                message,
+                on_click: None,
+            };
+        }
+
         // Show any language server installation info.
         let mut downloading = SmallVec::<[_; 3]>::new();
         let mut checking_for_update = SmallVec::<[_; 3]>::new();
@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use collections::HashMap;
+use futures::channel::{mpsc, oneshot};
+use gpui::{BackgroundExecutor, Task};
+use tempfile::TempDir;
+
+/// A prompt for a password or other credential, from a program run with an [`AskPassSession`].
+pub struct AskPassPrompt {
+    pub prompt: String,
+    response: oneshot::Sender<String>,
+}
+
+impl AskPassPrompt {
+    /// Answers the prompt. Dropping the prompt without answering it cancels the authentication.
+    pub fn respond(self, response: String) {
+        self.response.send(response).ok();
+    }
+}
+
+/// Where the prompts of an [`AskPassSession`] are sent to be answered.
+#[derive(Clone)]
+pub struct AskPassDelegate(mpsc::UnboundedSender<AskPassPrompt>);
+
+impl AskPassDelegate {
+    pub fn new() -> (Self, mpsc::UnboundedReceiver<AskPassPrompt>) {
+        let (tx, rx) = mpsc::unbounded();
+        (Self(tx), rx)
+    }
+
+    /// Asks for a credential, returning `None` if the prompt was dismissed.
+    pub async fn ask(&self, prompt: String) -> Option<String> {
+        let (response, rx) = oneshot::channel();
+        self.0
+            .unbounded_send(AskPassPrompt { prompt, response })
+            .ok()?;
+        rx.await.ok()
+    }
+}
+
+/// A temporary askpass program for `ssh` and `git`, which forwards their prompts for passwords
+/// to an [`AskPassDelegate`] over a domain socket, for as long as the session is alive.
+pub struct AskPassSession {
+    script_path: PathBuf,
+    _temp_dir: TempDir,
+    _listener: Task<()>,
+}
+
+impl AskPassSession {
+    #[cfg(not(unix))]
+    pub async fn new(_executor: &BackgroundExecutor, _delegate: AskPassDelegate) -> Result<Self> {
+        Err(anyhow::anyhow!("askpass is not supported on this platform"))
+    }
+
+    #[cfg(unix)]
+    pub async fn new(executor: &BackgroundExecutor, delegate: AskPassDelegate) -> Result<Self> {
+        use anyhow::Context as _;
+        use futures::{io::BufReader, AsyncBufReadExt as _, AsyncWriteExt as _};
+        use smol::{fs::unix::PermissionsExt as _, net::unix::UnixListener};
+        use util::ResultExt as _;
+
+        let temp_dir = tempfile::Builder::new().prefix("zed-askpass").tempdir()?;
+
+        // Create a domain socket listener to handle requests from the askpass program.
+        let askpass_socket = temp_dir.path().join("askpass.sock");
+        let listener =
+            UnixListener::bind(&askpass_socket).context("failed to create askpass socket")?;
+
+        let listener = executor.spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = Vec::new();
+                let mut reader = BufReader::new(&mut stream);
+                if reader.read_until(b'\0', &mut buffer).await.is_err() {
+                    buffer.clear();
+                }
+                let prompt = String::from_utf8_lossy(&buffer)
+                    .trim_end_matches('\0')
+                    .to_string();
+                if let Some(response) = delegate.ask(prompt).await {
+                    stream.write_all(response.as_bytes()).await.log_err();
+                }
+            }
+        });
+
+        // Create an askpass script that communicates back to this process.
+        let askpass_script = format!(
+            "{shebang}\n{print_args} | nc -U {askpass_socket} 2> /dev/null \n",
+            askpass_socket = askpass_socket.display(),
+            print_args = "printf '%s\\0' \"$@\"",
+            shebang = "#!/bin/sh",
+        );
+        let script_path = temp_dir.path().join("askpass.sh");
+        smol::fs::write(&script_path, askpass_script).await?;
+        smol::fs::set_permissions(&script_path, std::fs::Permissions::from_mode(0o755)).await?;
+
+        Ok(Self {
+            script_path,
+            _temp_dir: temp_dir,
+            _listener: listener,
+        })
+    }
+
+    pub fn script_path(&self) -> &Path {
+        &self.script_path
+    }
+
+    /// The environment variables that make `git`, and the `ssh` it runs, prompt through this
+    /// session instead of the terminal.
+    pub fn env(&self) -> HashMap<String, String> {
+        let script_path = self.script_path.to_string_lossy().into_owned();
+        HashMap::from_iter([
+            ("GIT_ASKPASS".to_string(), script_path.clone()),
+            ("SSH_ASKPASS".to_string(), script_path),
+            ("SSH_ASKPASS_REQUIRE".to_string(), "force".to_string()),
+        ])
+    }
+}
@@ -504,6 +504,48 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_read_only_project_request::<proto::InlayHints>,
             ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSemanticTokens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareCallHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetIncomingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetOutgoingCalls>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::PrepareTypeHierarchy>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSupertypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetSubtypes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetFoldingRanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::ResolveCodeLens>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetDocumentLinks>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::ResolveDocumentLink>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetDocumentColors>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetColorPresentations>,
+            ))
             .add_request_handler(user_handler(
                 forward_read_only_project_request::<proto::OpenBufferByPath>,
             ))
@@ -525,6 +567,9 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::ApplyCodeAction>,
             ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ExecuteCodeLens>,
+            ))
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::PrepareRename>,
             ))
@@ -534,6 +579,9 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::ReloadBuffers>,
             ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ReopenBuffer>,
+            ))
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::FormatBuffers>,
             ))
@@ -561,6 +609,48 @@ impl Server {
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::BlameBuffer>,
             ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetGitStagingStatus>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::StageGitPaths>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::UnstageGitPaths>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::SetGitIndexText>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::UnstageGitRows>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::CommitGitChanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::StashGitChanges>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::ApplyGitStash>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::DropGitStash>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetGitStashes>,
+            ))
+            .add_request_handler(user_handler(
+                forward_mutating_project_request::<proto::RunGitRemoteOperation>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetGitLog>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetGitCommitDiff>,
+            ))
+            .add_request_handler(user_handler(
+                forward_read_only_project_request::<proto::GetGitRevisionDiff>,
+            ))
             .add_request_handler(user_handler(
                 forward_mutating_project_request::<proto::MultiLspQuery>,
             ))
@@ -573,6 +663,9 @@ impl Server {
             .add_message_handler(create_buffer_for_peer)
             .add_request_handler(update_buffer)
             .add_message_handler(broadcast_project_message_from_host::<proto::RefreshInlayHints>)
+            .add_message_handler(
+                broadcast_project_message_from_host::<proto::RefreshSemanticTokens>,
+            )
             .add_message_handler(broadcast_project_message_from_host::<proto::UpdateBufferFile>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferReloaded>)
             .add_message_handler(broadcast_project_message_from_host::<proto::BufferSaved>)
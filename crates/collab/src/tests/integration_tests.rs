@@ -12,7 +12,13 @@ use client::{User, RECEIVE_TIMEOUT};
 use collections::{HashMap, HashSet};
 use fs::{FakeFs, Fs as _, RemoveOptions};
 use futures::{channel::mpsc, StreamExt as _};
-use git::repository::GitFileStatus;
+use git::{
+    log::{CommitEntry, CommitFileDiff, LogQuery},
+    repository::GitFileStatus,
+    stash::StashEntry,
+    status::GitChangeKind,
+    Oid,
+};
 use gpui::{
     px, size, AppContext, BackgroundExecutor, Model, Modifiers, MouseButton, MouseDownEvent,
     TestAppContext, UpdateGlobal,
@@ -28,8 +34,8 @@ use live_kit_client::MacOSDisplay;
 use lsp::LanguageServerId;
 use parking_lot::Mutex;
 use project::{
-    search::SearchQuery, DiagnosticSummary, FormatTrigger, HoverBlockKind, Project, ProjectPath,
-    SearchResult,
+    search::SearchQuery, DiagnosticSummary, FormatTrigger, GitOperation, GitRemoteOperation,
+    HoverBlockKind, Project, ProjectPath, SearchResult,
 };
 use rand::prelude::*;
 use serde_json::json;
@@ -2992,6 +2998,520 @@ async fn test_git_status_sync(
     });
 }
 
+#[gpui::test]
+async fn test_git_staging_and_commit(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+                "b.txt": "b",
+            }),
+        )
+        .await;
+    client_a.fs().set_status_for_repo_via_git_operation(
+        Path::new("/dir/.git"),
+        &[
+            (Path::new("a.txt"), GitFileStatus::Modified),
+            (Path::new("b.txt"), GitFileStatus::Added),
+        ],
+    );
+
+    let (project_local, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let (worktree_id, work_directory_id) = project_remote.read_with(cx_b, |project, cx| {
+        let worktree = project.visible_worktrees(cx).next().unwrap();
+        let snapshot = worktree.read(cx).snapshot();
+        let (_, repository) = snapshot.repositories().next().unwrap();
+        (snapshot.id(), repository.work_directory_id())
+    });
+    let staging_status = |project: &Model<Project>, cx: &mut TestAppContext| {
+        let status = project.update(cx, |project, cx| {
+            project.git_staging_status(worktree_id, work_directory_id, cx)
+        });
+        async move {
+            status
+                .await
+                .unwrap()
+                .entries
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.repo_path.to_string_lossy().into_owned(),
+                        entry.index_status,
+                        entry.worktree_status,
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        staging_status(&project_remote, cx_b).await,
+        [
+            ("a.txt".to_string(), None, Some(GitChangeKind::Modified)),
+            ("b.txt".to_string(), None, Some(GitChangeKind::Untracked)),
+        ]
+    );
+
+    // The guest stages a file on the host's repository.
+    project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::Stage(vec![Path::new("a.txt").into()]),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        staging_status(&project_local, cx_a).await,
+        [
+            ("a.txt".to_string(), Some(GitChangeKind::Modified), None),
+            ("b.txt".to_string(), None, Some(GitChangeKind::Untracked)),
+        ]
+    );
+
+    // Then commits it.
+    project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::Commit {
+                    message: "Update a.txt".to_string(),
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        staging_status(&project_remote, cx_b).await,
+        [("b.txt".to_string(), None, Some(GitChangeKind::Untracked))]
+    );
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(state.commit_messages, ["Update a.txt"]);
+        });
+
+    // Committing without staged changes fails on the host and the error reaches the guest.
+    let result = project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::Commit {
+                    message: "Nothing".to_string(),
+                },
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_git_remote_operations_and_stash(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a",
+            }),
+        )
+        .await;
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            state.branch_name = Some("main".to_string());
+        });
+    client_a.fs().set_status_for_repo_via_git_operation(
+        Path::new("/dir/.git"),
+        &[(Path::new("a.txt"), GitFileStatus::Modified)],
+    );
+
+    let (project_local, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let (worktree_id, work_directory_id) = project_remote.read_with(cx_b, |project, cx| {
+        let worktree = project.visible_worktrees(cx).next().unwrap();
+        let snapshot = worktree.read(cx).snapshot();
+        let (_, repository) = snapshot.repositories().next().unwrap();
+        (snapshot.id(), repository.work_directory_id())
+    });
+
+    // The guest's fetch and push run on the host, and are pending until they're done.
+    for operation in [GitRemoteOperation::Fetch, GitRemoteOperation::Push] {
+        let task = project_remote.update(cx_b, |project, cx| {
+            project.run_git_remote_operation(worktree_id, work_directory_id, operation, None, cx)
+        });
+        project_remote.read_with(cx_b, |project, _| {
+            assert_eq!(
+                project.pending_git_remote_operations().collect::<Vec<_>>(),
+                [operation]
+            );
+        });
+        task.await.unwrap();
+        project_remote.read_with(cx_b, |project, _| {
+            assert_eq!(project.pending_git_remote_operations().count(), 0);
+        });
+    }
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(state.remote_operations, ["fetch", "push"]);
+        });
+
+    // The guest stashes the host's changes and lists the stash.
+    project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::StashSave {
+                    message: Some("before pulling".to_string()),
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    let stashes = project_remote
+        .update(cx_b, |project, cx| {
+            project.git_stashes(worktree_id, work_directory_id, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        stashes,
+        [StashEntry {
+            index: 0,
+            message: "On main: before pulling".to_string(),
+        }]
+    );
+
+    // Stashing again fails, as there are no changes left.
+    let result = project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::StashSave { message: None },
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+
+    // Popping the entry removes it from the stash.
+    project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::StashApply {
+                    index: 0,
+                    pop: true,
+                },
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            assert_eq!(state.stashes, []);
+        });
+    let result = project_remote
+        .update(cx_b, |project, cx| {
+            project.run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::StashDrop { index: 0 },
+                cx,
+            )
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_git_log_and_commit_diff(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a\nb\n",
+                "b.txt": "b",
+            }),
+        )
+        .await;
+    let commit = |sha: &str, parents: &[&str], message: &str| CommitEntry {
+        sha: sha.repeat(40).parse().unwrap(),
+        parents: parents
+            .iter()
+            .map(|parent| parent.repeat(40).parse().unwrap())
+            .collect(),
+        author_name: "Jane Doe".to_string(),
+        author_email: "jane@example.com".to_string(),
+        timestamp: 1700000000,
+        message: message.to_string(),
+    };
+    let second_commit = commit("2", &["1"], "Update a.txt\n\nDetails");
+    let first_commit = commit("1", &[], "Initial commit");
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            state.log = vec![second_commit.clone(), first_commit.clone()];
+            state.commit_diffs.insert(
+                second_commit.sha,
+                vec![CommitFileDiff {
+                    path: Path::new("a.txt").into(),
+                    old_text: Some("a\n".to_string()),
+                    new_text: Some("a\nb\n".to_string()),
+                }],
+            );
+            state.commit_diffs.insert(
+                first_commit.sha,
+                vec![
+                    CommitFileDiff {
+                        path: Path::new("a.txt").into(),
+                        old_text: None,
+                        new_text: Some("a\n".to_string()),
+                    },
+                    CommitFileDiff {
+                        path: Path::new("b.txt").into(),
+                        old_text: None,
+                        new_text: Some("b".to_string()),
+                    },
+                ],
+            );
+        });
+
+    let (project_local, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let (worktree_id, work_directory_id) = project_remote.read_with(cx_b, |project, cx| {
+        let worktree = project.visible_worktrees(cx).next().unwrap();
+        let snapshot = worktree.read(cx).snapshot();
+        let (_, repository) = snapshot.repositories().next().unwrap();
+        (snapshot.id(), repository.work_directory_id())
+    });
+    let log = |query: LogQuery, cx: &mut TestAppContext| {
+        project_remote.update(cx, |project, cx| {
+            project.git_log(worktree_id, work_directory_id, query, cx)
+        })
+    };
+
+    let log_page = log(
+        LogQuery {
+            limit: 10,
+            ..Default::default()
+        },
+        cx_b,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        log_page.commits,
+        [second_commit.clone(), first_commit.clone()]
+    );
+    assert_eq!(log_page.commits[0].subject(), "Update a.txt");
+
+    let log_page = log(
+        LogQuery {
+            path: Some(Path::new("b.txt").into()),
+            limit: 10,
+            ..Default::default()
+        },
+        cx_b,
+    )
+    .await
+    .unwrap();
+    assert_eq!(log_page.commits, [first_commit.clone()]);
+
+    let log_page = log(
+        LogQuery {
+            skip: 1,
+            limit: 10,
+            ..Default::default()
+        },
+        cx_b,
+    )
+    .await
+    .unwrap();
+    assert_eq!(log_page.commits, [first_commit.clone()]);
+
+    let diff = project_remote
+        .update(cx_b, |project, cx| {
+            project.git_commit_diff(worktree_id, work_directory_id, second_commit.sha, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        diff,
+        [CommitFileDiff {
+            path: Path::new("a.txt").into(),
+            old_text: Some("a\n".to_string()),
+            new_text: Some("a\nb\n".to_string()),
+        }]
+    );
+
+    let unknown_sha = "3".repeat(40).parse::<Oid>().unwrap();
+    let result = project_remote
+        .update(cx_b, |project, cx| {
+            project.git_commit_diff(worktree_id, work_directory_id, unknown_sha, cx)
+        })
+        .await;
+    assert!(result.is_err());
+}
+
+#[gpui::test]
+async fn test_git_revision_diff(
+    executor: BackgroundExecutor,
+    cx_a: &mut TestAppContext,
+    cx_b: &mut TestAppContext,
+) {
+    let mut server = TestServer::start(executor.clone()).await;
+    let client_a = server.create_client(cx_a, "user_a").await;
+    let client_b = server.create_client(cx_b, "user_b").await;
+    server
+        .create_room(&mut [(&client_a, cx_a), (&client_b, cx_b)])
+        .await;
+    let active_call_a = cx_a.read(ActiveCall::global);
+
+    client_a
+        .fs()
+        .insert_tree(
+            "/dir",
+            json!({
+                ".git": {},
+                "a.txt": "a\nb\n",
+            }),
+        )
+        .await;
+    let changes = vec![
+        CommitFileDiff {
+            path: Path::new("a.txt").into(),
+            old_text: Some("a\n".to_string()),
+            new_text: Some("a\nb\n".to_string()),
+        },
+        CommitFileDiff {
+            path: Path::new("removed.txt").into(),
+            old_text: Some("removed".to_string()),
+            new_text: None,
+        },
+    ];
+    client_a
+        .fs()
+        .with_git_state(Path::new("/dir/.git"), false, |state| {
+            state
+                .revision_diffs
+                .insert("main".to_string(), changes.clone());
+        });
+
+    let (project_local, _) = client_a.build_local_project("/dir", cx_a).await;
+    let project_id = active_call_a
+        .update(cx_a, |call, cx| {
+            call.share_project(project_local.clone(), cx)
+        })
+        .await
+        .unwrap();
+    let project_remote = client_b.build_dev_server_project(project_id, cx_b).await;
+    executor.run_until_parked();
+
+    let (worktree_id, work_directory_id) = project_remote.read_with(cx_b, |project, cx| {
+        let worktree = project.visible_worktrees(cx).next().unwrap();
+        let snapshot = worktree.read(cx).snapshot();
+        let (_, repository) = snapshot.repositories().next().unwrap();
+        (snapshot.id(), repository.work_directory_id())
+    });
+
+    let diff = project_remote
+        .update(cx_b, |project, cx| {
+            project.git_revision_diff(worktree_id, work_directory_id, "main".into(), true, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(diff, changes);
+
+    let result = project_remote
+        .update(cx_b, |project, cx| {
+            project.git_revision_diff(worktree_id, work_directory_id, "unknown".into(), false, cx)
+        })
+        .await;
+    assert!(result.is_err());
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_fs_operations(
     executor: BackgroundExecutor,
@@ -3526,7 +4046,12 @@ async fn test_buffer_reloading(
     let new_contents = Rope::from("d\ne\nf");
     client_a
         .fs()
-        .save("/dir/a.txt".as_ref(), &new_contents, LineEnding::Windows)
+        .save(
+            "/dir/a.txt".as_ref(),
+            &new_contents,
+            LineEnding::Windows,
+            Default::default(),
+        )
         .await
         .unwrap();
 
@@ -4293,6 +4818,7 @@ async fn test_reloading_buffer_manually(
             "/a/a.rs".as_ref(),
             &Rope::from("let seven = 7;"),
             LineEnding::Unix,
+            Default::default(),
         )
         .await
         .unwrap();
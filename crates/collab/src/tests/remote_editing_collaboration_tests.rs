@@ -2,6 +2,9 @@ use crate::tests::TestServer;
 use call::ActiveCall;
 use fs::{FakeFs, Fs as _};
 use gpui::{Context as _, TestAppContext};
+use http_client::FakeHttpClient;
+use language::LanguageRegistry;
+use node_runtime::FakeNodeRuntime;
 use remote::SshSession;
 use remote_server::HeadlessProject;
 use serde_json::json;
@@ -43,8 +46,17 @@ async fn test_sharing_an_ssh_remote_project(
 
     // User A connects to the remote project via SSH.
     server_cx.update(HeadlessProject::init);
-    let _headless_project =
-        server_cx.new_model(|cx| HeadlessProject::new(server_ssh, remote_fs.clone(), cx));
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let _headless_project = server_cx.new_model(|cx| {
+        HeadlessProject::new(
+            server_ssh,
+            remote_fs.clone(),
+            FakeHttpClient::with_404_response(),
+            FakeNodeRuntime::new(),
+            languages,
+            cx,
+        )
+    });
 
     let (project_a, worktree_id) = client_a
         .build_ssh_project("/code/project1", client_ssh, cx_a)
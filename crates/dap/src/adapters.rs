@@ -0,0 +1,362 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fmt,
+    net::{Ipv4Addr, TcpListener},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use async_trait::async_trait;
+use collections::{BTreeMap, HashMap};
+use gpui::{AppContext, Global, ReadGlobal};
+use language::LspAdapterDelegate;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use task::{DebugAdapterConfig, DebugRequestType, ResolvedTask};
+
+use crate::{DebugAdapterBinary, TcpArguments};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct DebugAdapterName(pub Arc<str>);
+
+impl fmt::Display for DebugAdapterName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<&str> for DebugAdapterName {
+    fn from(name: &str) -> Self {
+        Self(name.into())
+    }
+}
+
+/// A task to debug, resolved from a [`task::TaskTemplate`] with a debug configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugTaskDefinition {
+    pub label: String,
+    pub config: DebugAdapterConfig,
+    /// The program to launch under the debugger.
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    pub env: HashMap<String, String>,
+}
+
+impl DebugTaskDefinition {
+    /// Returns the debug definition of a resolved task, if its template has a debug configuration.
+    pub fn from_resolved_task(task: &ResolvedTask) -> Option<Self> {
+        let config = task.original_task().debug.clone()?;
+        let spawn = task.resolved.as_ref()?;
+        Some(Self {
+            label: spawn.label.clone(),
+            config,
+            program: spawn.command.clone(),
+            args: spawn.args.clone(),
+            cwd: spawn.cwd.clone(),
+            env: spawn.env.clone(),
+        })
+    }
+}
+
+/// Knows how to start a kind of debug adapter, and how to ask it to debug a task.
+#[async_trait(?Send)]
+pub trait DebugAdapter: 'static + Send + Sync {
+    fn name(&self) -> DebugAdapterName;
+
+    /// Returns the binary to start the debug adapter with.
+    async fn get_binary(
+        &self,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        config: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary>;
+
+    /// Returns the arguments of the `launch` or `attach` request debugging the given task.
+    fn request_args(&self, task: &DebugTaskDefinition) -> Value {
+        let mut args = match &task.config.request {
+            DebugRequestType::Launch => json!({
+                "program": task.program,
+                "args": task.args,
+                "cwd": task.cwd,
+                "env": task.env,
+            }),
+            DebugRequestType::Attach { process_id } => json!({ "processId": process_id }),
+        };
+        merge_initialize_args(&mut args, task);
+        args
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    fn as_fake(&self) -> Option<&FakeDebugAdapterEntry> {
+        None
+    }
+}
+
+/// Lets the user's `initialize_args` override the arguments Zed derives from the task.
+fn merge_initialize_args(args: &mut Value, task: &DebugTaskDefinition) {
+    if let Some(initialize_args) = task.config.initialize_args.clone() {
+        util::merge_json_value_into(initialize_args, args);
+    }
+}
+
+#[derive(Default)]
+struct GlobalDapRegistry(Arc<DapRegistry>);
+
+impl Global for GlobalDapRegistry {}
+
+/// The debug adapters available to start debug sessions with, built in or provided by extensions.
+#[derive(Default)]
+pub struct DapRegistry {
+    adapters: RwLock<BTreeMap<DebugAdapterName, Arc<dyn DebugAdapter>>>,
+}
+
+impl DapRegistry {
+    /// Returns the global [`DapRegistry`].
+    pub fn global(cx: &AppContext) -> Arc<Self> {
+        GlobalDapRegistry::global(cx).0.clone()
+    }
+
+    /// Returns the global [`DapRegistry`].
+    ///
+    /// Inserts a default [`DapRegistry`] if one does not yet exist.
+    pub fn default_global(cx: &mut AppContext) -> Arc<Self> {
+        cx.default_global::<GlobalDapRegistry>().0.clone()
+    }
+
+    /// Registers the provided [`DebugAdapter`], replacing any adapter with the same name.
+    pub fn add_adapter(&self, adapter: Arc<dyn DebugAdapter>) {
+        self.adapters.write().insert(adapter.name(), adapter);
+    }
+
+    pub fn remove_adapter(&self, name: &DebugAdapterName) {
+        self.adapters.write().remove(name);
+    }
+
+    /// Returns the [`DebugAdapter`] with the given name.
+    pub fn adapter(&self, name: &str) -> Option<Arc<dyn DebugAdapter>> {
+        self.adapters
+            .read()
+            .get(&DebugAdapterName(name.into()))
+            .cloned()
+    }
+
+    /// Returns the names of registered [`DebugAdapter`]s.
+    pub fn adapter_names(&self) -> Vec<DebugAdapterName> {
+        self.adapters.read().keys().cloned().collect()
+    }
+
+    /// Registers a fake debug adapter, returning a stream of the [`crate::FakeDebugAdapter`]s
+    /// started for debug sessions using it.
+    ///
+    /// The initializer is called on each fake before the debug session sends it any request.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn register_fake_adapter(
+        &self,
+        name: &str,
+        capabilities: crate::Capabilities,
+        initializer: impl 'static + Send + Sync + Fn(&crate::FakeDebugAdapter),
+    ) -> futures::channel::mpsc::UnboundedReceiver<crate::FakeDebugAdapter> {
+        let (fakes_tx, fakes_rx) = futures::channel::mpsc::unbounded();
+        self.add_adapter(Arc::new(FakeDebugAdapterEntry {
+            name: name.into(),
+            capabilities,
+            initializer: Box::new(initializer),
+            fakes_tx,
+        }));
+        fakes_rx
+    }
+}
+
+/// A debug adapter registered via [`DapRegistry::register_fake_adapter`].
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeDebugAdapterEntry {
+    name: DebugAdapterName,
+    pub capabilities: crate::Capabilities,
+    #[allow(clippy::type_complexity)]
+    pub initializer: Box<dyn 'static + Send + Sync + Fn(&crate::FakeDebugAdapter)>,
+    pub fakes_tx: futures::channel::mpsc::UnboundedSender<crate::FakeDebugAdapter>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+#[async_trait(?Send)]
+impl DebugAdapter for FakeDebugAdapterEntry {
+    fn name(&self) -> DebugAdapterName {
+        self.name.clone()
+    }
+
+    async fn get_binary(
+        &self,
+        _: Arc<dyn LspAdapterDelegate>,
+        _: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary> {
+        unreachable!("fake debug adapters are not started from a binary")
+    }
+
+    fn as_fake(&self) -> Option<&FakeDebugAdapterEntry> {
+        Some(self)
+    }
+}
+
+/// Registers the debug adapters built into Zed.
+pub fn init(cx: &mut AppContext) {
+    let registry = DapRegistry::default_global(cx);
+    registry.add_adapter(Arc::new(LldbDebugAdapter));
+    registry.add_adapter(Arc::new(PythonDebugAdapter));
+    registry.add_adapter(Arc::new(GoDebugAdapter));
+}
+
+/// Returns the path of the first of the given commands found in PATH.
+async fn which_any(delegate: &dyn LspAdapterDelegate, commands: &[&str]) -> Option<PathBuf> {
+    for command in commands {
+        if let Some(path) = delegate.which(OsStr::new(command)).await {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Debugs native programs with `lldb-dap`, formerly known as `lldb-vscode`.
+struct LldbDebugAdapter;
+
+#[async_trait(?Send)]
+impl DebugAdapter for LldbDebugAdapter {
+    fn name(&self) -> DebugAdapterName {
+        "lldb".into()
+    }
+
+    async fn get_binary(
+        &self,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary> {
+        let command = which_any(delegate.as_ref(), &["lldb-dap", "lldb-vscode"])
+            .await
+            .context("lldb-dap not found in PATH")?;
+        Ok(DebugAdapterBinary {
+            command,
+            arguments: Vec::new(),
+            env: None,
+            connection: None,
+        })
+    }
+
+    fn request_args(&self, task: &DebugTaskDefinition) -> Value {
+        let mut args = match &task.config.request {
+            DebugRequestType::Launch => json!({
+                "program": task.program,
+                "args": task.args,
+                "cwd": task.cwd,
+                "env": task
+                    .env
+                    .iter()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect::<Vec<_>>(),
+            }),
+            DebugRequestType::Attach { process_id } => json!({ "pid": process_id }),
+        };
+        merge_initialize_args(&mut args, task);
+        args
+    }
+}
+
+/// Debugs Python programs with `debugpy`, which needs to be installed in the Python environment found in PATH.
+struct PythonDebugAdapter;
+
+#[async_trait(?Send)]
+impl DebugAdapter for PythonDebugAdapter {
+    fn name(&self) -> DebugAdapterName {
+        "python".into()
+    }
+
+    async fn get_binary(
+        &self,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary> {
+        let command = which_any(delegate.as_ref(), &["python3", "python"])
+            .await
+            .context("python not found in PATH")?;
+        Ok(DebugAdapterBinary {
+            command,
+            arguments: vec!["-m".into(), "debugpy.adapter".into()],
+            env: None,
+            connection: None,
+        })
+    }
+
+    fn request_args(&self, task: &DebugTaskDefinition) -> Value {
+        let mut args = match &task.config.request {
+            DebugRequestType::Launch => json!({
+                "program": task.program,
+                "args": task.args,
+                "cwd": task.cwd,
+                "env": task.env,
+                // Zed cannot run the program in a terminal on behalf of the adapter.
+                "console": "internalConsole",
+            }),
+            DebugRequestType::Attach { process_id } => json!({ "processId": process_id }),
+        };
+        merge_initialize_args(&mut args, task);
+        args
+    }
+}
+
+/// Debugs Go programs with Delve, which talks DAP through a socket.
+struct GoDebugAdapter;
+
+#[async_trait(?Send)]
+impl DebugAdapter for GoDebugAdapter {
+    fn name(&self) -> DebugAdapterName {
+        "go".into()
+    }
+
+    async fn get_binary(
+        &self,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary> {
+        let command = delegate
+            .which(OsStr::new("dlv"))
+            .await
+            .context("dlv not found in PATH")?;
+        let host = Ipv4Addr::LOCALHOST;
+        let port = TcpListener::bind((host, 0))
+            .and_then(|listener| listener.local_addr())
+            .map_err(|error| anyhow!("failed to find a free port for dlv: {error}"))?
+            .port();
+        Ok(DebugAdapterBinary {
+            command,
+            arguments: vec![
+                OsString::from("dap"),
+                OsString::from("--listen"),
+                OsString::from(format!("{host}:{port}")),
+            ],
+            env: None,
+            connection: Some(TcpArguments {
+                host,
+                port,
+                timeout: None,
+            }),
+        })
+    }
+
+    fn request_args(&self, task: &DebugTaskDefinition) -> Value {
+        let mut args = match &task.config.request {
+            DebugRequestType::Launch => json!({
+                "mode": "debug",
+                "program": task.program,
+                "args": task.args,
+                "cwd": task.cwd,
+                "env": task.env,
+            }),
+            DebugRequestType::Attach { process_id } => json!({
+                "mode": "local",
+                "processId": process_id,
+            }),
+        };
+        merge_initialize_args(&mut args, task);
+        args
+    }
+}
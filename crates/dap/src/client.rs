@@ -0,0 +1,668 @@
+use std::{
+    fmt,
+    future::Future,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering::SeqCst},
+        Arc, Weak,
+    },
+    time::Duration,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use futures::{channel::oneshot, AsyncBufRead, AsyncBufReadExt as _, AsyncWrite};
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use parking_lot::{Mutex, RwLock};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use smol::{channel, process::Child};
+use util::{ResultExt, TryFutureExt};
+
+use crate::{
+    events::Event,
+    requests::{Disconnect, Initialize, Request},
+    transport::{read_message, write_message, Message, RequestMessage, ResponseMessage, Transport},
+    Capabilities, DebugAdapterBinary, DisconnectArguments, InitializeRequestArguments,
+};
+
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Handles an event (`None`) or a request (`Some(seq)`) sent by the other side.
+type MessageHandler = Box<dyn Send + FnMut(Option<u64>, Value, AsyncAppContext)>;
+type ResponseHandler = Box<dyn Send + FnOnce(Result<Value>)>;
+
+/// Identifies a running debug adapter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct DebugAdapterClientId(pub usize);
+
+impl fmt::Display for DebugAdapterClientId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A connection to a running debug adapter.
+pub struct DebugAdapterClient {
+    id: DebugAdapterClientId,
+    name: Arc<str>,
+    next_seq: Arc<AtomicU64>,
+    outbound_tx: channel::Sender<Message>,
+    capabilities: RwLock<Capabilities>,
+    handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+    response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+    executor: BackgroundExecutor,
+    #[allow(clippy::type_complexity)]
+    io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
+    process: Mutex<Option<Child>>,
+}
+
+/// Handle to a debug adapter event or request subscription.
+#[must_use]
+pub struct Subscription {
+    name: &'static str,
+    handlers: Option<Weak<Mutex<HashMap<&'static str, MessageHandler>>>>,
+}
+
+impl DebugAdapterClient {
+    /// Starts a debug adapter process and connects to it.
+    pub async fn start(
+        id: DebugAdapterClientId,
+        name: Arc<str>,
+        binary: &DebugAdapterBinary,
+        working_dir: &Path,
+        cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let transport =
+            Transport::start(binary, working_dir, cx.background_executor().clone()).await?;
+        Ok(Self::new_internal(
+            id,
+            name,
+            transport.input,
+            transport.output,
+            transport.logs,
+            Some(transport.process),
+            cx,
+        ))
+    }
+
+    fn new_internal<Input, Output>(
+        id: DebugAdapterClientId,
+        name: Arc<str>,
+        input: Input,
+        output: Output,
+        logs: Vec<(&'static str, Box<dyn AsyncBufRead + Unpin + Send>)>,
+        process: Option<Child>,
+        cx: AsyncAppContext,
+    ) -> Self
+    where
+        Input: AsyncBufRead + Unpin + Send + 'static,
+        Output: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (outbound_tx, outbound_rx) = channel::unbounded::<Message>();
+        let next_seq = Arc::new(AtomicU64::new(1));
+        let handlers = Arc::new(Mutex::new(HashMap::<_, MessageHandler>::default()));
+        let response_handlers =
+            Arc::new(Mutex::new(Some(HashMap::<_, ResponseHandler>::default())));
+
+        let input_task = cx.spawn({
+            let handlers = handlers.clone();
+            let response_handlers = response_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
+            let next_seq = next_seq.clone();
+            let name = name.clone();
+            move |cx| async move {
+                let log_tasks = logs
+                    .into_iter()
+                    .map(|(stream_name, stream)| {
+                        cx.background_executor().spawn(Self::handle_log(
+                            stream,
+                            stream_name,
+                            name.clone(),
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                let result = Self::handle_input(
+                    input,
+                    handlers,
+                    response_handlers.clone(),
+                    outbound_tx,
+                    next_seq,
+                    cx,
+                )
+                .await;
+                // Fail the requests still waiting for a response.
+                response_handlers.lock().take();
+                drop(log_tasks);
+                result.log_err()
+            }
+        });
+        let output_task = cx
+            .background_executor()
+            .spawn(Self::handle_output(output, outbound_rx).log_err());
+
+        Self {
+            id,
+            name,
+            next_seq,
+            outbound_tx,
+            capabilities: Default::default(),
+            handlers,
+            response_handlers,
+            executor: cx.background_executor().clone(),
+            io_tasks: Mutex::new(Some((input_task, output_task))),
+            process: Mutex::new(process),
+        }
+    }
+
+    async fn handle_input<Input>(
+        mut input: Input,
+        handlers: Arc<Mutex<HashMap<&'static str, MessageHandler>>>,
+        response_handlers: Arc<Mutex<Option<HashMap<u64, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<Message>,
+        next_seq: Arc<AtomicU64>,
+        cx: AsyncAppContext,
+    ) -> Result<()>
+    where
+        Input: AsyncBufRead + Unpin,
+    {
+        let mut buffer = Vec::new();
+        loop {
+            let Some(message) = read_message(&mut input, &mut buffer).await? else {
+                continue;
+            };
+            match message {
+                Message::Response(response) => {
+                    let handler = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&response.request_seq));
+                    if let Some(handler) = handler {
+                        if response.success {
+                            handler(Ok(response.body));
+                        } else {
+                            handler(Err(anyhow!(
+                                "{}",
+                                response
+                                    .message
+                                    .unwrap_or_else(|| format!("{} failed", response.command))
+                            )));
+                        }
+                    }
+                }
+                Message::Event(event) => {
+                    if let Some(handler) = handlers.lock().get_mut(event.event.as_str()) {
+                        handler(None, event.body, cx.clone());
+                    } else {
+                        log::info!("unhandled debug adapter event {}", event.event);
+                    }
+                }
+                Message::Request(request) => {
+                    if let Some(handler) = handlers.lock().get_mut(request.command.as_str()) {
+                        handler(Some(request.seq), request.arguments, cx.clone());
+                    } else {
+                        outbound_tx
+                            .try_send(Message::Response(ResponseMessage {
+                                seq: next_seq.fetch_add(1, SeqCst),
+                                request_seq: request.seq,
+                                success: false,
+                                message: Some(format!("unsupported request {}", request.command)),
+                                command: request.command,
+                                body: Value::Null,
+                            }))
+                            .ok();
+                    }
+                }
+            }
+        }
+    }
+
+    async fn handle_log(
+        mut stream: Box<dyn AsyncBufRead + Unpin + Send>,
+        stream_name: &'static str,
+        name: Arc<str>,
+    ) {
+        let mut line = String::new();
+        while stream.read_line(&mut line).await.unwrap_or(0) > 0 {
+            log::debug!("{name} debug adapter {stream_name}: {}", line.trim_end());
+            line.clear();
+        }
+    }
+
+    async fn handle_output<Output>(
+        mut output: Output,
+        outbound_rx: channel::Receiver<Message>,
+    ) -> Result<()>
+    where
+        Output: AsyncWrite + Unpin,
+    {
+        while let Ok(message) = outbound_rx.recv().await {
+            write_message(&mut output, &message)
+                .await
+                .context("failed to write to debug adapter")?;
+        }
+        Ok(())
+    }
+
+    /// Sends the `initialize` request, returning the capabilities of the debug adapter.
+    pub async fn initialize(&self) -> Result<Capabilities> {
+        let capabilities = self
+            .request::<Initialize>(InitializeRequestArguments {
+                client_id: Some("zed".into()),
+                client_name: Some("Zed".into()),
+                adapter_id: self.name.to_string(),
+                locale: Some("en-us".into()),
+                lines_start_at1: Some(true),
+                columns_start_at1: Some(true),
+                path_format: Some("path".into()),
+                supports_variable_type: Some(true),
+                supports_variable_paging: Some(false),
+                supports_run_in_terminal_request: Some(false),
+                supports_progress_reporting: Some(false),
+            })
+            .await?;
+        *self.capabilities.write() = capabilities.clone();
+        Ok(capabilities)
+    }
+
+    pub fn id(&self) -> DebugAdapterClientId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.read().clone()
+    }
+
+    /// Sends a request to the debug adapter, resolving with its response.
+    pub fn request<R: Request>(
+        &self,
+        arguments: R::Arguments,
+    ) -> impl 'static + Future<Output = Result<R::Response>> {
+        let seq = self.next_seq.fetch_add(1, SeqCst);
+        let (tx, rx) = oneshot::channel();
+        let handle_response = self
+            .response_handlers
+            .lock()
+            .as_mut()
+            .ok_or_else(|| anyhow!("debug adapter shut down"))
+            .map(|handlers| {
+                handlers.insert(
+                    seq,
+                    Box::new(move |response| {
+                        tx.send(response).ok();
+                    }),
+                );
+            });
+        let send = serde_json::to_value(arguments)
+            .map_err(anyhow::Error::from)
+            .and_then(|arguments| {
+                self.outbound_tx
+                    .try_send(Message::Request(RequestMessage {
+                        seq,
+                        command: R::COMMAND.into(),
+                        arguments,
+                    }))
+                    .context("failed to send request to debug adapter")
+            });
+
+        async move {
+            handle_response?;
+            send?;
+            let body = rx
+                .await
+                .map_err(|_| anyhow!("debug adapter shut down before responding"))??;
+            deserialize_body(body).with_context(|| format!("invalid {} response", R::COMMAND))
+        }
+    }
+
+    /// Registers a handler for the events of the given type.
+    pub fn on_event<E, F>(&self, mut f: F) -> Subscription
+    where
+        E: Event,
+        F: 'static + Send + FnMut(E::Body, AsyncAppContext),
+    {
+        self.on_message(
+            E::EVENT,
+            Box::new(move |_, body, cx| {
+                if let Some(body) = deserialize_body(body).log_err() {
+                    f(body, cx);
+                }
+            }),
+        )
+    }
+
+    /// Registers a handler for the requests of the given type, sent by the other side.
+    pub fn on_request<R, F, Fut>(&self, mut f: F) -> Subscription
+    where
+        R: Request,
+        F: 'static + Send + FnMut(R::Arguments, AsyncAppContext) -> Fut,
+        Fut: 'static + Future<Output = Result<R::Response>>,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        let next_seq = self.next_seq.clone();
+        self.on_message(
+            R::COMMAND,
+            Box::new(move |request_seq, arguments, cx| {
+                let Some(request_seq) = request_seq else {
+                    return;
+                };
+                let response =
+                    deserialize_body(arguments).map(|arguments| f(arguments, cx.clone()));
+                let outbound_tx = outbound_tx.clone();
+                let next_seq = next_seq.clone();
+                cx.foreground_executor()
+                    .spawn(async move {
+                        let response = match response {
+                            Ok(response) => response.await,
+                            Err(error) => Err(error),
+                        };
+                        let (success, message, body) =
+                            match response.and_then(|body| Ok(serde_json::to_value(body)?)) {
+                                Ok(body) => (true, None, body),
+                                Err(error) => (false, Some(error.to_string()), Value::Null),
+                            };
+                        outbound_tx
+                            .try_send(Message::Response(ResponseMessage {
+                                seq: next_seq.fetch_add(1, SeqCst),
+                                request_seq,
+                                success,
+                                command: R::COMMAND.into(),
+                                message,
+                                body,
+                            }))
+                            .ok();
+                    })
+                    .detach();
+            }),
+        )
+    }
+
+    fn on_message(&self, name: &'static str, handler: MessageHandler) -> Subscription {
+        let prev_handler = self.handlers.lock().insert(name, handler);
+        assert!(
+            prev_handler.is_none(),
+            "registered multiple handlers for the same DAP message"
+        );
+        Subscription {
+            name,
+            handlers: Some(Arc::downgrade(&self.handlers)),
+        }
+    }
+
+    /// Removes a handler registered via [`Self::on_event`] or [`Self::on_request`].
+    pub fn remove_handler(&self, name: &str) {
+        self.handlers.lock().remove(name);
+    }
+
+    /// Sends an event to the other side, as debug adapters do.
+    #[cfg(any(test, feature = "test-support"))]
+    fn send_event<E: Event>(&self, body: E::Body) -> Result<()> {
+        self.outbound_tx
+            .try_send(Message::Event(crate::transport::EventMessage {
+                seq: self.next_seq.fetch_add(1, SeqCst),
+                event: E::EVENT.into(),
+                body: serde_json::to_value(body)?,
+            }))?;
+        Ok(())
+    }
+
+    /// Asks the debug adapter to end the debug session, terminating the debuggee, then stops the adapter.
+    pub fn shutdown(&self) -> Option<impl 'static + Send + Future<Output = ()>> {
+        let io_tasks = self.io_tasks.lock().take()?;
+        let process = self.process.lock().take();
+        let disconnect = self.request::<Disconnect>(DisconnectArguments {
+            restart: Some(false),
+            terminate_debuggee: Some(true),
+        });
+        let timer = self.executor.timer(DISCONNECT_TIMEOUT);
+        let name = self.name.clone();
+        Some(async move {
+            smol::future::or(
+                async {
+                    disconnect
+                        .await
+                        .with_context(|| format!("{name} debug adapter failed to disconnect"))
+                        .log_err();
+                },
+                async {
+                    timer.await;
+                    log::warn!(
+                        "{name} debug adapter did not disconnect within {DISCONNECT_TIMEOUT:?}"
+                    );
+                },
+            )
+            .await;
+            drop(io_tasks);
+            if let Some(mut process) = process {
+                process.kill().log_err();
+            }
+        })
+    }
+}
+
+/// Deserializes the body of a message, which adapters may send as an empty object rather than omit.
+fn deserialize_body<T: DeserializeOwned>(body: Value) -> Result<T> {
+    match serde_json::from_value(body.clone()) {
+        Ok(body) => Ok(body),
+        Err(error) => {
+            if body.as_object().is_some_and(|body| body.is_empty()) {
+                if let Ok(body) = serde_json::from_value(Value::Null) {
+                    return Ok(body);
+                }
+            }
+            Err(error.into())
+        }
+    }
+}
+
+impl Drop for DebugAdapterClient {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown() {
+            self.executor.spawn(shutdown).detach();
+        }
+    }
+}
+
+impl fmt::Debug for DebugAdapterClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DebugAdapterClient")
+            .field("id", &self.id.0)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Subscription {
+    /// Detaching a subscription handle prevents it from unsubscribing on drop.
+    pub fn detach(mut self) {
+        self.handlers.take();
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(handlers) = self
+            .handlers
+            .as_ref()
+            .and_then(|handlers| handlers.upgrade())
+        {
+            handlers.lock().remove(self.name);
+        }
+    }
+}
+
+/// Mock debug adapter for use in tests.
+#[cfg(any(test, feature = "test-support"))]
+#[derive(Clone)]
+pub struct FakeDebugAdapter {
+    pub adapter: Arc<DebugAdapterClient>,
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl FakeDebugAdapter {
+    /// Constructs a fake debug adapter, returning it along with the client connected to it.
+    pub fn new(
+        id: DebugAdapterClientId,
+        name: Arc<str>,
+        capabilities: Capabilities,
+        cx: AsyncAppContext,
+    ) -> (DebugAdapterClient, FakeDebugAdapter) {
+        let (adapter_input, client_output) = async_pipe::pipe();
+        let (client_input, adapter_output) = async_pipe::pipe();
+
+        let client = DebugAdapterClient::new_internal(
+            id,
+            name.clone(),
+            futures::io::BufReader::new(client_input),
+            client_output,
+            Vec::new(),
+            None,
+            cx.clone(),
+        );
+        let fake = FakeDebugAdapter {
+            adapter: Arc::new(DebugAdapterClient::new_internal(
+                id,
+                name,
+                futures::io::BufReader::new(adapter_input),
+                adapter_output,
+                Vec::new(),
+                None,
+                cx,
+            )),
+        };
+        fake.handle_request::<Initialize, _, _>(move |_, _| {
+            let capabilities = capabilities.clone();
+            async move { Ok(capabilities) }
+        });
+        fake.handle_request::<Disconnect, _, _>(|_, _| async move { Ok(()) });
+
+        (client, fake)
+    }
+
+    /// Registers a handler for a specific kind of request, replacing any existing handler for it.
+    pub fn handle_request<R, F, Fut>(
+        &self,
+        mut handler: F,
+    ) -> futures::channel::mpsc::UnboundedReceiver<()>
+    where
+        R: 'static + Request,
+        F: 'static + Send + FnMut(R::Arguments, AsyncAppContext) -> Fut,
+        Fut: 'static + Send + Future<Output = Result<R::Response>>,
+    {
+        let (responded_tx, responded_rx) = futures::channel::mpsc::unbounded();
+        self.adapter.remove_handler(R::COMMAND);
+        self.adapter
+            .on_request::<R, _, _>(move |arguments, cx| {
+                let result = handler(arguments, cx.clone());
+                let responded_tx = responded_tx.clone();
+                let executor = cx.background_executor().clone();
+                async move {
+                    executor.simulate_random_delay().await;
+                    let result = result.await;
+                    responded_tx.unbounded_send(()).ok();
+                    result
+                }
+            })
+            .detach();
+        responded_rx
+    }
+
+    /// Sends an event to the client.
+    pub fn send_event<E: Event>(&self, body: E::Body) {
+        self.adapter.send_event::<E>(body).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        events::{Initialized, Stopped},
+        requests::{Launch, Threads},
+        StoppedEventBody, Thread, ThreadsResponse,
+    };
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use serde_json::json;
+
+    #[ctor::ctor]
+    fn init_logger() {
+        if std::env::var("RUST_LOG").is_ok() {
+            env_logger::init();
+        }
+    }
+
+    #[gpui::test]
+    async fn test_fake(cx: &mut TestAppContext) {
+        let (client, fake) = FakeDebugAdapter::new(
+            DebugAdapterClientId(0),
+            "the-adapter".into(),
+            Capabilities {
+                supports_configuration_done_request: Some(true),
+                ..Default::default()
+            },
+            cx.to_async(),
+        );
+
+        let (events_tx, mut events_rx) = futures::channel::mpsc::unbounded();
+        client
+            .on_event::<Initialized, _>({
+                let events_tx = events_tx.clone();
+                move |_, _| {
+                    events_tx.unbounded_send("initialized".to_string()).ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<Stopped, _>(move |body, _| {
+                events_tx.unbounded_send(body.reason).ok();
+            })
+            .detach();
+
+        let capabilities = client.initialize().await.unwrap();
+        assert_eq!(capabilities.supports_configuration_done_request, Some(true));
+        assert_eq!(client.capabilities(), capabilities);
+
+        let fake_adapter = fake.clone();
+        let mut launched = fake.handle_request::<Launch, _, _>(move |arguments, _| {
+            assert_eq!(arguments, json!({ "program": "/dir/a.out" }));
+            fake_adapter.send_event::<Initialized>(());
+            async move { Ok(()) }
+        });
+        client
+            .request::<Launch>(json!({ "program": "/dir/a.out" }))
+            .await
+            .unwrap();
+        launched.next().await.unwrap();
+        assert_eq!(events_rx.next().await.unwrap(), "initialized");
+
+        fake.send_event::<Stopped>(StoppedEventBody {
+            reason: "breakpoint".into(),
+            description: None,
+            thread_id: Some(1),
+            all_threads_stopped: Some(true),
+            text: None,
+        });
+        assert_eq!(events_rx.next().await.unwrap(), "breakpoint");
+
+        fake.handle_request::<Threads, _, _>(|_, _| async move {
+            Ok(ThreadsResponse {
+                threads: vec![Thread {
+                    id: 1,
+                    name: "main".into(),
+                }],
+            })
+        });
+        let threads = client.request::<Threads>(()).await.unwrap();
+        assert_eq!(threads.threads[0].name, "main");
+
+        // Requests the adapter has no handler for fail rather than hang.
+        fake.adapter.remove_handler(Threads::COMMAND);
+        assert!(client.request::<Threads>(()).await.is_err());
+    }
+}
@@ -0,0 +1,34 @@
+//! A client for the [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol),
+//! through which Zed drives debuggers.
+
+pub mod adapters;
+mod client;
+pub mod transport;
+mod types;
+
+pub use client::*;
+pub use types::*;
+
+use collections::HashMap;
+use serde::Deserialize;
+use std::{ffi::OsString, net::Ipv4Addr, path::PathBuf};
+
+/// A launchable debug adapter.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DebugAdapterBinary {
+    pub command: PathBuf,
+    pub arguments: Vec<OsString>,
+    pub env: Option<HashMap<String, String>>,
+    /// Where to connect to the adapter, for adapters which listen on a socket rather than
+    /// talking through their stdio.
+    pub connection: Option<TcpArguments>,
+}
+
+/// The socket a debug adapter listens on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TcpArguments {
+    pub host: Ipv4Addr,
+    pub port: u16,
+    /// How long to wait for the adapter to listen, in milliseconds.
+    pub timeout: Option<u64>,
+}
@@ -0,0 +1,255 @@
+use std::{net::SocketAddr, path::Path, process::Stdio, time::Duration};
+
+use anyhow::{anyhow, Context as _, Result};
+use futures::{AsyncBufRead, AsyncBufReadExt as _, AsyncReadExt as _, AsyncWrite, AsyncWriteExt};
+use gpui::BackgroundExecutor;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use smol::{
+    net::TcpStream,
+    process::{self, Child},
+};
+
+#[cfg(target_os = "windows")]
+use smol::process::windows::CommandExt;
+
+use crate::{DebugAdapterBinary, TcpArguments};
+
+const CONTENT_LEN_HEADER: &str = "Content-Length: ";
+const HEADER_DELIMITER: &[u8; 4] = b"\r\n\r\n";
+const DEFAULT_TCP_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A Debug Adapter Protocol message, in either direction.
+///
+/// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Base_Protocol_ProtocolMessage)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Message {
+    Request(RequestMessage),
+    Response(ResponseMessage),
+    Event(EventMessage),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMessage {
+    pub seq: u64,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub arguments: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseMessage {
+    pub seq: u64,
+    pub request_seq: u64,
+    pub success: bool,
+    pub command: String,
+    /// The error, when the request did not succeed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventMessage {
+    pub seq: u64,
+    pub event: String,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub body: Value,
+}
+
+/// The streams to talk to a debug adapter through.
+pub(crate) struct Transport {
+    pub input: Box<dyn AsyncBufRead + Unpin + Send>,
+    pub output: Box<dyn AsyncWrite + Unpin + Send>,
+    /// The output of the adapter that is not part of the protocol, by the name of its stream:
+    /// its stderr, and its stdout when messages go through a socket.
+    pub logs: Vec<(&'static str, Box<dyn AsyncBufRead + Unpin + Send>)>,
+    pub process: Child,
+}
+
+impl Transport {
+    /// Starts the debug adapter process, talking to it through its stdio or, when the binary
+    /// specifies a TCP connection, through a socket once the adapter listens on it.
+    pub async fn start(
+        binary: &DebugAdapterBinary,
+        working_dir: &Path,
+        executor: BackgroundExecutor,
+    ) -> Result<Self> {
+        log::info!(
+            "starting debug adapter. binary path: {:?}, working directory: {:?}, args: {:?}",
+            binary.command,
+            working_dir,
+            binary.arguments
+        );
+
+        let mut command = process::Command::new(&binary.command);
+        command
+            .current_dir(working_dir)
+            .args(&binary.arguments)
+            .envs(binary.env.clone().unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        #[cfg(windows)]
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+        let mut process = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn debug adapter. path: {:?}, working directory: {:?}, args: {:?}",
+                binary.command, working_dir, binary.arguments
+            )
+        })?;
+
+        let mut logs = Vec::new();
+        if let Some(stderr) = process.stderr.take() {
+            logs.push((
+                "stderr",
+                Box::new(futures::io::BufReader::new(stderr))
+                    as Box<dyn AsyncBufRead + Unpin + Send>,
+            ));
+        }
+        match &binary.connection {
+            None => {
+                let stdin = process.stdin.take().unwrap();
+                let stdout = process.stdout.take().unwrap();
+                Ok(Self {
+                    input: Box::new(futures::io::BufReader::new(stdout)),
+                    output: Box::new(stdin),
+                    logs,
+                    process,
+                })
+            }
+            Some(connection) => {
+                // The adapter's stdout is only logged, so that it never fills up the pipe.
+                if let Some(stdout) = process.stdout.take() {
+                    logs.push(("stdout", Box::new(futures::io::BufReader::new(stdout))));
+                }
+                let stream = Self::connect(connection, executor).await?;
+                let (input, output) = stream.split();
+                Ok(Self {
+                    input: Box::new(futures::io::BufReader::new(input)),
+                    output: Box::new(output),
+                    logs,
+                    process,
+                })
+            }
+        }
+    }
+
+    /// Connects to a debug adapter, retrying until it listens on its port or the connection times out.
+    async fn connect(connection: &TcpArguments, executor: BackgroundExecutor) -> Result<TcpStream> {
+        let address = SocketAddr::new(connection.host.into(), connection.port);
+        let timeout = connection
+            .timeout
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_TCP_CONNECTION_TIMEOUT);
+        let connect = async {
+            loop {
+                match TcpStream::connect(address).await {
+                    Ok(stream) => return Ok(stream),
+                    Err(_) => executor.timer(Duration::from_millis(100)).await,
+                };
+            }
+        };
+        let timeout = async {
+            executor.timer(timeout).await;
+            Err(anyhow!(
+                "timed out connecting to debug adapter at {address}"
+            ))
+        };
+        smol::future::or(connect, timeout).await
+    }
+}
+
+/// Reads the next message sent through the given stream, returning `None` for messages which
+/// cannot be deserialized, so that they can be skipped.
+pub(crate) async fn read_message<Input>(
+    input: &mut Input,
+    buffer: &mut Vec<u8>,
+) -> Result<Option<Message>>
+where
+    Input: AsyncBufRead + Unpin,
+{
+    buffer.clear();
+    loop {
+        if buffer.len() >= HEADER_DELIMITER.len()
+            && buffer[(buffer.len() - HEADER_DELIMITER.len())..] == HEADER_DELIMITER[..]
+        {
+            break;
+        }
+        if input.read_until(b'\n', buffer).await? == 0 {
+            return Err(anyhow!("cannot read DAP message headers"));
+        }
+    }
+
+    let headers = std::str::from_utf8(buffer)?;
+    let message_len = headers
+        .split('\n')
+        .find_map(|line| line.strip_prefix(CONTENT_LEN_HEADER))
+        .ok_or_else(|| anyhow!("invalid DAP message header {headers:?}"))?
+        .trim_end()
+        .parse()?;
+
+    buffer.resize(message_len, 0);
+    input.read_exact(buffer).await?;
+    let message = std::str::from_utf8(buffer)?;
+    log::trace!("incoming message: {message}");
+    match serde_json::from_str(message) {
+        Ok(message) => Ok(Some(message)),
+        Err(error) => {
+            log::warn!("failed to deserialize DAP message: {error}\n{message}");
+            Ok(None)
+        }
+    }
+}
+
+/// Writes a message to the given stream.
+pub(crate) async fn write_message<Output>(output: &mut Output, message: &Message) -> Result<()>
+where
+    Output: AsyncWrite + Unpin,
+{
+    let message = serde_json::to_string(message)?;
+    log::trace!("outgoing message: {message}");
+    output
+        .write_all(format!("{CONTENT_LEN_HEADER}{}\r\n\r\n", message.len()).as_bytes())
+        .await?;
+    output.write_all(message.as_bytes()).await?;
+    output.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[gpui::test]
+    async fn test_read_message() {
+        let mut buffer = Vec::new();
+        let mut input = futures::io::BufReader::new(
+            b"Content-Length: 46\r\n\r\n{\"seq\":1,\"type\":\"event\",\"event\":\"initialized\"}Content-Type: application/json\r\nContent-Length: 63\r\n\r\n{\"seq\":2,\"type\":\"request\",\"command\":\"threads\",\"arguments\":null}"
+                as &[u8],
+        );
+        assert_eq!(
+            read_message(&mut input, &mut buffer).await.unwrap(),
+            Some(Message::Event(EventMessage {
+                seq: 1,
+                event: "initialized".into(),
+                body: Value::Null,
+            }))
+        );
+        assert_eq!(
+            read_message(&mut input, &mut buffer).await.unwrap(),
+            Some(Message::Request(RequestMessage {
+                seq: 2,
+                command: "threads".into(),
+                arguments: Value::Null,
+            }))
+        );
+        assert!(read_message(&mut input, &mut buffer).await.is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! The subset of the Debug Adapter Protocol types that Zed uses.
+//!
+//! [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification)
+
+use serde::{Deserialize, Serialize};
+
+/// Arguments of the `initialize` request, describing the capabilities of the client.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeRequestArguments {
+    #[serde(rename = "clientID", skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+    #[serde(rename = "adapterID")]
+    pub adapter_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines_start_at1: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns_start_at1: Option<bool>,
+    /// Either `path` or `uri`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_variable_type: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_variable_paging: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_run_in_terminal_request: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_progress_reporting: Option<bool>,
+}
+
+/// The features a debug adapter supports, as reported in the response to the `initialize` request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Capabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_configuration_done_request: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_function_breakpoints: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_conditional_breakpoints: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_hit_conditional_breakpoints: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_evaluate_for_hovers: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_step_back: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_set_variable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_restart_request: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_terminate_request: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_log_points: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_single_thread_execution_requests: Option<bool>,
+}
+
+/// A source file, identified by its path or, for sources without a file, by a reference.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Source {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_reference: Option<i64>,
+}
+
+/// A breakpoint requested by the client.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceBreakpoint {
+    /// The 1-based line of the breakpoint.
+    pub line: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hit_condition: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_message: Option<String>,
+}
+
+/// A breakpoint as set by the debug adapter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breakpoint {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+    /// Whether the adapter could set the breakpoint.
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBreakpointsArguments {
+    pub source: Source,
+    /// All the breakpoints of the source, replacing the ones previously set.
+    pub breakpoints: Option<Vec<SourceBreakpoint>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_modified: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetBreakpointsResponse {
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Thread {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadsResponse {
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceArguments {
+    pub thread_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_frame: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub levels: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackFrame {
+    pub id: u64,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Source>,
+    /// The 1-based line of the frame, or 0 when the frame has no source.
+    pub line: u64,
+    pub column: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_column: Option<u64>,
+    /// Either `normal`, `label` or `subtle`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StackTraceResponse {
+    pub stack_frames: Vec<StackFrame>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_frames: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesArguments {
+    pub frame_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scope {
+    pub name: String,
+    pub variables_reference: u64,
+    /// Whether retrieving the variables of the scope is expensive, e.g. for global variables.
+    #[serde(default)]
+    pub expensive: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presentation_hint: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopesResponse {
+    pub scopes: Vec<Scope>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesArguments {
+    pub variables_reference: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub count: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    /// A reference to retrieve the children of the variable with, or 0 if it has none.
+    #[serde(default)]
+    pub variables_reference: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluate_name: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariablesResponse {
+    pub variables: Vec<Variable>,
+}
+
+/// The context in which an expression is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EvaluateArgumentsContext {
+    Watch,
+    Repl,
+    Hover,
+    Clipboard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateArguments {
+    pub expression: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<EvaluateArgumentsContext>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateResponse {
+    pub result: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(default)]
+    pub variables_reference: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueArguments {
+    pub thread_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinueResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+/// Arguments of the `next`, `stepIn` and `stepOut` requests.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepArguments {
+    pub thread_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub single_thread: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PauseArguments {
+    pub thread_id: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisconnectArguments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminate_debuggee: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateArguments {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedEventBody {
+    /// Why the execution stopped, e.g. `step`, `breakpoint`, `exception` or `pause`.
+    pub reason: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_threads_stopped: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContinuedEventBody {
+    pub thread_id: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_threads_continued: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitedEventBody {
+    pub exit_code: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminatedEventBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThreadEventBody {
+    /// Either `started` or `exited`.
+    pub reason: String,
+    pub thread_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputEventBody {
+    /// Either `console`, `important`, `stdout`, `stderr` or `telemetry`; `console` when missing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub output: String,
+}
+
+/// Requests sent from the client to the debug adapter.
+pub mod requests {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+
+    /// A Debug Adapter Protocol request.
+    pub trait Request {
+        type Arguments: DeserializeOwned + Serialize + Send + 'static;
+        type Response: DeserializeOwned + Serialize + Send + 'static;
+        const COMMAND: &'static str;
+    }
+
+    macro_rules! request {
+        ($name:ident, $command:literal, $arguments:ty, $response:ty) => {
+            #[derive(Debug)]
+            pub enum $name {}
+
+            impl Request for $name {
+                type Arguments = $arguments;
+                type Response = $response;
+                const COMMAND: &'static str = $command;
+            }
+        };
+    }
+
+    request!(
+        Initialize,
+        "initialize",
+        InitializeRequestArguments,
+        Capabilities
+    );
+    // The arguments of `launch` and `attach` are specific to each debug adapter.
+    request!(Launch, "launch", serde_json::Value, ());
+    request!(Attach, "attach", serde_json::Value, ());
+    request!(ConfigurationDone, "configurationDone", (), ());
+    request!(
+        SetBreakpoints,
+        "setBreakpoints",
+        SetBreakpointsArguments,
+        SetBreakpointsResponse
+    );
+    request!(Threads, "threads", (), ThreadsResponse);
+    request!(
+        StackTrace,
+        "stackTrace",
+        StackTraceArguments,
+        StackTraceResponse
+    );
+    request!(Scopes, "scopes", ScopesArguments, ScopesResponse);
+    request!(
+        Variables,
+        "variables",
+        VariablesArguments,
+        VariablesResponse
+    );
+    request!(Evaluate, "evaluate", EvaluateArguments, EvaluateResponse);
+    request!(Continue, "continue", ContinueArguments, ContinueResponse);
+    request!(Next, "next", StepArguments, ());
+    request!(StepIn, "stepIn", StepArguments, ());
+    request!(StepOut, "stepOut", StepArguments, ());
+    request!(Pause, "pause", PauseArguments, ());
+    request!(Disconnect, "disconnect", DisconnectArguments, ());
+    request!(Terminate, "terminate", TerminateArguments, ());
+}
+
+/// Events sent from the debug adapter to the client.
+pub mod events {
+    use serde::{de::DeserializeOwned, Serialize};
+
+    use super::*;
+
+    /// A Debug Adapter Protocol event.
+    pub trait Event {
+        type Body: DeserializeOwned + Serialize + Send + 'static;
+        const EVENT: &'static str;
+    }
+
+    macro_rules! event {
+        ($name:ident, $event:literal, $body:ty) => {
+            #[derive(Debug)]
+            pub enum $name {}
+
+            impl Event for $name {
+                type Body = $body;
+                const EVENT: &'static str = $event;
+            }
+        };
+    }
+
+    event!(Initialized, "initialized", ());
+    event!(Stopped, "stopped", StoppedEventBody);
+    event!(Continued, "continued", ContinuedEventBody);
+    event!(Exited, "exited", ExitedEventBody);
+    event!(Terminated, "terminated", Option<TerminatedEventBody>);
+    event!(Thread, "thread", ThreadEventBody);
+    event!(Output, "output", OutputEventBody);
+}
@@ -0,0 +1,1066 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use collections::{HashMap, HashSet};
+use dap::{DebugAdapterClientId, EvaluateArgumentsContext, OutputEventBody, Scope, StackFrame};
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use futures::future;
+use gpui::{
+    Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, ModelContext, Stateful, Subscription, Task, View, WeakView,
+};
+use language::Point;
+use project::{
+    dap_store::{
+        DapStore, DapStoreEvent, DebugSession, DebugSessionEvent, DebugSessionStatus, StepKind,
+    },
+    Fs,
+};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+use crate::{
+    debug_panel_settings::{DebugPanelDockPosition, DebugPanelSettings},
+    Continue, Pause, StepInto, StepOut, StepOver, Stop, ToggleFocus,
+};
+
+const DEBUG_PANEL_KEY: &str = "DebugPanel";
+
+/// The type of the row highlight marking the line of the selected stack frame.
+enum DebugStackFrameLine {}
+
+/// What the panel shows of a debug session, fetched whenever its debuggee stops.
+struct SessionState {
+    id: DebugAdapterClientId,
+    session: Model<DebugSession>,
+    stack_frames: Vec<StackFrame>,
+    selected_frame: Option<u64>,
+    scopes: Vec<Scope>,
+    /// The fetched children of scopes and variables, by their variables reference.
+    variables: HashMap<u64, Vec<dap::Variable>>,
+    expanded: HashSet<u64>,
+    watch_results: HashMap<String, Result<String, String>>,
+    stack_frames_task: Task<()>,
+    scopes_task: Task<()>,
+    watches_task: Task<()>,
+    variable_tasks: HashMap<u64, Task<()>>,
+    _subscription: Subscription,
+}
+
+impl SessionState {
+    fn new(session: Model<DebugSession>, cx: &mut ViewContext<DebugPanel>) -> Self {
+        Self {
+            id: session.read(cx).id(),
+            _subscription: cx.subscribe(&session, DebugPanel::handle_session_event),
+            session,
+            stack_frames: Vec::new(),
+            selected_frame: None,
+            scopes: Vec::new(),
+            variables: HashMap::default(),
+            expanded: HashSet::default(),
+            watch_results: HashMap::default(),
+            stack_frames_task: Task::ready(()),
+            scopes_task: Task::ready(()),
+            watches_task: Task::ready(()),
+            variable_tasks: HashMap::default(),
+        }
+    }
+
+    /// Forgets everything fetched while the debuggee was stopped.
+    fn clear(&mut self) {
+        self.stack_frames.clear();
+        self.selected_frame = None;
+        self.clear_frame();
+    }
+
+    fn clear_frame(&mut self) {
+        self.scopes.clear();
+        self.variables.clear();
+        self.expanded.clear();
+        self.watch_results.clear();
+        self.scopes_task = Task::ready(());
+        self.watches_task = Task::ready(());
+        self.variable_tasks.clear();
+    }
+}
+
+pub struct DebugPanel {
+    workspace: WeakView<Workspace>,
+    dap_store: Model<DapStore>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    active: bool,
+    focus_handle: FocusHandle,
+    pending_serialization: Task<Option<()>>,
+    sessions: Vec<SessionState>,
+    active_session: Option<DebugAdapterClientId>,
+    watch_expressions: Vec<String>,
+    watch_editor: View<Editor>,
+    console_editor: View<Editor>,
+    /// The editor showing the line of the selected stack frame.
+    highlighted_editor: Option<WeakView<Editor>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDebugPanel {
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+}
+
+impl DebugPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(DEBUG_PANEL_KEY) })
+            .await
+            .context("loading debug panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedDebugPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.height = serialized_panel.height.map(|px| px.round());
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let workspace_handle = workspace.weak_handle();
+        let dap_store = workspace.project().read(cx).dap_store().clone();
+        let fs = workspace.app_state().fs.clone();
+        cx.new_view(|cx| {
+            let watch_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Add a watch expression", cx);
+                editor
+            });
+            let console_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Evaluate an expression", cx);
+                editor
+            });
+            let subscription = cx.subscribe(&dap_store, Self::handle_dap_store_event);
+            let mut this = Self {
+                workspace: workspace_handle,
+                dap_store: dap_store.clone(),
+                fs,
+                width: None,
+                height: None,
+                active: false,
+                focus_handle: cx.focus_handle(),
+                pending_serialization: Task::ready(None),
+                sessions: Vec::new(),
+                active_session: None,
+                watch_expressions: Vec::new(),
+                watch_editor,
+                console_editor,
+                highlighted_editor: None,
+                _subscriptions: vec![subscription],
+            };
+            for session in dap_store.read(cx).sessions().cloned().collect::<Vec<_>>() {
+                this.add_session(session, cx);
+            }
+            this
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let height = self.height;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        DEBUG_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedDebugPanel { width, height })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn handle_dap_store_event(
+        &mut self,
+        _: Model<DapStore>,
+        event: &DapStoreEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            DapStoreEvent::SessionStarted(session) => {
+                self.add_session(session.clone(), cx);
+                // The panel is being updated, so it can only be opened once the event is handled.
+                let workspace = self.workspace.clone();
+                WindowContext::defer(cx, move |cx| {
+                    workspace
+                        .update(cx, |workspace, cx| workspace.open_panel::<Self>(cx))
+                        .ok();
+                });
+            }
+            DapStoreEvent::SessionEnded(id) => {
+                self.sessions.retain(|state| state.id != *id);
+                if self.active_session == Some(*id) {
+                    self.active_session = self.sessions.last().map(|state| state.id);
+                    self.clear_stack_frame_highlight(cx);
+                }
+                cx.notify();
+            }
+            DapStoreEvent::BreakpointsChanged(_) => {}
+        }
+    }
+
+    fn add_session(&mut self, session: Model<DebugSession>, cx: &mut ViewContext<Self>) {
+        let state = SessionState::new(session, cx);
+        self.active_session = Some(state.id);
+        self.sessions.push(state);
+        cx.notify();
+    }
+
+    fn handle_session_event(
+        &mut self,
+        session: Model<DebugSession>,
+        event: &DebugSessionEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let id = session.read(cx).id();
+        let is_stopped = matches!(
+            session.read(cx).status(),
+            DebugSessionStatus::Stopped { .. }
+        );
+        match event {
+            DebugSessionEvent::StatusChanged => {
+                if is_stopped {
+                    self.select_session(id, cx);
+                    self.load_stack_frames(id, cx);
+                } else {
+                    if let Some(state) = self.session_state_mut(id) {
+                        state.clear();
+                    }
+                    if self.active_session == Some(id) {
+                        self.clear_stack_frame_highlight(cx);
+                    }
+                }
+            }
+            // Adapters may report stops without a thread, in which case the stack can only be
+            // fetched for the first thread once the threads are known.
+            DebugSessionEvent::ThreadsChanged => {
+                if is_stopped
+                    && self
+                        .session_state(id)
+                        .map_or(false, |state| state.stack_frames.is_empty())
+                {
+                    self.load_stack_frames(id, cx);
+                }
+            }
+            DebugSessionEvent::Output | DebugSessionEvent::Terminated => {}
+        }
+        cx.notify();
+    }
+
+    fn session_state(&self, id: DebugAdapterClientId) -> Option<&SessionState> {
+        self.sessions.iter().find(|state| state.id == id)
+    }
+
+    fn session_state_mut(&mut self, id: DebugAdapterClientId) -> Option<&mut SessionState> {
+        self.sessions.iter_mut().find(|state| state.id == id)
+    }
+
+    fn active_session_state(&self) -> Option<&SessionState> {
+        self.session_state(self.active_session?)
+    }
+
+    fn select_session(&mut self, id: DebugAdapterClientId, cx: &mut ViewContext<Self>) {
+        if self.active_session == Some(id) {
+            return;
+        }
+        self.active_session = Some(id);
+        self.clear_stack_frame_highlight(cx);
+        let selected_frame = self.session_state(id).and_then(|state| {
+            state
+                .stack_frames
+                .iter()
+                .find(|frame| Some(frame.id) == state.selected_frame)
+                .cloned()
+        });
+        if let Some(frame) = selected_frame {
+            self.show_stack_frame(&frame, cx);
+        }
+        cx.notify();
+    }
+
+    fn load_stack_frames(&mut self, id: DebugAdapterClientId, cx: &mut ViewContext<Self>) {
+        let Some(state) = self.session_state_mut(id) else {
+            return;
+        };
+        let Some(thread_id) = state.session.read(cx).current_thread_id() else {
+            return;
+        };
+        let stack_frames = state.session.read(cx).stack_frames(thread_id);
+        state.stack_frames_task = cx.spawn(|this, mut cx| async move {
+            let stack_frames = stack_frames.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                let top_frame = stack_frames.first().map(|frame| frame.id);
+                if let Some(state) = this.session_state_mut(id) {
+                    state.stack_frames = stack_frames;
+                }
+                if let Some(frame_id) = top_frame {
+                    this.select_stack_frame(id, frame_id, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn select_stack_frame(
+        &mut self,
+        id: DebugAdapterClientId,
+        frame_id: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(state) = self.session_state_mut(id) else {
+            return;
+        };
+        state.clear_frame();
+        state.selected_frame = Some(frame_id);
+        let frame = state
+            .stack_frames
+            .iter()
+            .find(|frame| frame.id == frame_id)
+            .cloned();
+        let scopes = state.session.read(cx).scopes(frame_id);
+        state.scopes_task = cx.spawn(|this, mut cx| async move {
+            let scopes = scopes.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                // The first scope which is cheap to fetch, usually the locals, starts expanded.
+                let expanded_scope = scopes
+                    .iter()
+                    .find(|scope| !scope.expensive)
+                    .map(|scope| scope.variables_reference);
+                if let Some(state) = this.session_state_mut(id) {
+                    state.scopes = scopes;
+                }
+                if let Some(variables_reference) = expanded_scope {
+                    this.toggle_variables(id, variables_reference, cx);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+
+        self.evaluate_watches(id, cx);
+        if let Some(frame) = frame {
+            self.show_stack_frame(&frame, cx);
+        }
+        cx.notify();
+    }
+
+    fn toggle_variables(
+        &mut self,
+        id: DebugAdapterClientId,
+        variables_reference: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(state) = self.session_state_mut(id) else {
+            return;
+        };
+        cx.notify();
+        if !state.expanded.insert(variables_reference) {
+            state.expanded.remove(&variables_reference);
+            return;
+        }
+        if state.variables.contains_key(&variables_reference)
+            || state.variable_tasks.contains_key(&variables_reference)
+        {
+            return;
+        }
+
+        let variables = state.session.read(cx).variables(variables_reference);
+        let task = cx.spawn(|this, mut cx| async move {
+            let variables = variables.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                if let Some(state) = this.session_state_mut(id) {
+                    state.variable_tasks.remove(&variables_reference);
+                    state.variables.insert(variables_reference, variables);
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+        state.variable_tasks.insert(variables_reference, task);
+    }
+
+    fn evaluate_watches(&mut self, id: DebugAdapterClientId, cx: &mut ViewContext<Self>) {
+        let Some(state) = self.sessions.iter_mut().find(|state| state.id == id) else {
+            return;
+        };
+        let Some(frame_id) = state.selected_frame else {
+            return;
+        };
+        let session = state.session.read(cx);
+        let evaluations = self
+            .watch_expressions
+            .iter()
+            .map(|expression| {
+                let evaluation = session.evaluate(
+                    expression.clone(),
+                    Some(frame_id),
+                    EvaluateArgumentsContext::Watch,
+                );
+                let expression = expression.clone();
+                async move { (expression, evaluation.await) }
+            })
+            .collect::<Vec<_>>();
+        state.watches_task = cx.spawn(|this, mut cx| async move {
+            let results = future::join_all(evaluations).await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(state) = this.session_state_mut(id) {
+                    state.watch_results = results
+                        .into_iter()
+                        .map(|(expression, result)| {
+                            let result = result
+                                .map(|response| response.result)
+                                .map_err(|error| error.to_string());
+                            (expression, result)
+                        })
+                        .collect();
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    /// Opens the source of the stack frame, and highlights its line.
+    fn show_stack_frame(&mut self, frame: &StackFrame, cx: &mut ViewContext<Self>) {
+        self.clear_stack_frame_highlight(cx);
+        let Some(path) = frame.source.as_ref().and_then(|source| source.path.clone()) else {
+            return;
+        };
+        let Ok(open_task) = self.workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(PathBuf::from(path), false, cx)
+        }) else {
+            return;
+        };
+        let row = frame.line.saturating_sub(1) as u32;
+        cx.spawn(|this, mut cx| async move {
+            let item = open_task.await?;
+            let editor = item
+                .downcast::<Editor>()
+                .context("stack frame source is not opened in an editor")?;
+            editor.update(&mut cx, |editor, cx| {
+                let position = editor
+                    .buffer()
+                    .read(cx)
+                    .snapshot(cx)
+                    .anchor_before(Point::new(row, 0));
+                let color = cx.theme().colors().editor_highlighted_line_background;
+                editor.highlight_rows::<DebugStackFrameLine>(
+                    position..=position,
+                    Some(color),
+                    true,
+                    cx,
+                );
+            })?;
+            this.update(&mut cx, |this, _| {
+                this.highlighted_editor = Some(editor.downgrade());
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn clear_stack_frame_highlight(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self
+            .highlighted_editor
+            .take()
+            .and_then(|editor| editor.upgrade())
+        {
+            editor.update(cx, |editor, cx| {
+                editor.clear_row_highlights::<DebugStackFrameLine>();
+                cx.notify();
+            });
+        }
+    }
+
+    fn run_session_command(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+        command: impl FnOnce(
+            &mut DebugSession,
+            &mut ModelContext<DebugSession>,
+        ) -> Task<anyhow::Result<()>>,
+    ) {
+        if let Some(state) = self.active_session_state() {
+            state
+                .session
+                .clone()
+                .update(cx, command)
+                .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn continue_debugging(&mut self, _: &Continue, cx: &mut ViewContext<Self>) {
+        self.run_session_command(cx, |session, cx| session.resume(cx));
+    }
+
+    pub fn pause(&mut self, _: &Pause, cx: &mut ViewContext<Self>) {
+        self.run_session_command(cx, |session, cx| session.pause(cx));
+    }
+
+    pub fn step_over(&mut self, _: &StepOver, cx: &mut ViewContext<Self>) {
+        self.run_session_command(cx, |session, cx| session.step(StepKind::Over, cx));
+    }
+
+    pub fn step_into(&mut self, _: &StepInto, cx: &mut ViewContext<Self>) {
+        self.run_session_command(cx, |session, cx| session.step(StepKind::In, cx));
+    }
+
+    pub fn step_out(&mut self, _: &StepOut, cx: &mut ViewContext<Self>) {
+        self.run_session_command(cx, |session, cx| session.step(StepKind::Out, cx));
+    }
+
+    pub fn stop(&mut self, _: &Stop, cx: &mut ViewContext<Self>) {
+        if let Some(id) = self.active_session {
+            self.dap_store
+                .update(cx, |dap_store, cx| dap_store.stop_session(id, cx));
+        }
+    }
+
+    fn add_watch(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.watch_editor.read(cx).text(cx).trim().to_owned();
+        if expression.is_empty() {
+            return;
+        }
+        self.watch_editor.update(cx, |editor, cx| editor.clear(cx));
+        if !self.watch_expressions.contains(&expression) {
+            self.watch_expressions.push(expression);
+        }
+        if let Some(id) = self.active_session {
+            self.evaluate_watches(id, cx);
+        }
+        cx.notify();
+    }
+
+    fn remove_watch(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        if ix < self.watch_expressions.len() {
+            self.watch_expressions.remove(ix);
+            cx.notify();
+        }
+    }
+
+    /// Evaluates the console input in the selected stack frame, printing it and its result
+    /// to the console.
+    fn evaluate_console_input(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.console_editor.read(cx).text(cx);
+        if expression.trim().is_empty() {
+            return;
+        }
+        let Some(state) = self.active_session_state() else {
+            return;
+        };
+        let session = state.session.clone();
+        let frame_id = state.selected_frame;
+        self.console_editor
+            .update(cx, |editor, cx| editor.clear(cx));
+
+        let evaluation = session.update(cx, |session, cx| {
+            session.push_output(
+                OutputEventBody {
+                    category: Some("console".into()),
+                    output: format!("> {expression}\n"),
+                },
+                cx,
+            );
+            session.evaluate(expression, frame_id, EvaluateArgumentsContext::Repl)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let output = match evaluation.await {
+                Ok(response) => OutputEventBody {
+                    category: Some("console".into()),
+                    output: format!("{}\n", response.result),
+                },
+                Err(error) => OutputEventBody {
+                    category: Some("stderr".into()),
+                    output: format!("{error}\n"),
+                },
+            };
+            session.update(&mut cx, |session, cx| session.push_output(output, cx))
+        })
+        .detach_and_log_err(cx);
+
+        // The evaluation may have changed the state of the debuggee.
+        if let Some(id) = self.active_session {
+            self.evaluate_watches(id, cx);
+        }
+    }
+
+    fn render_command_button(
+        &self,
+        id: &'static str,
+        icon: IconName,
+        tooltip: &'static str,
+        action: &dyn Action,
+        enabled: bool,
+    ) -> IconButton {
+        let tooltip_action = action.boxed_clone();
+        let action = action.boxed_clone();
+        IconButton::new(id, icon)
+            .icon_size(IconSize::Small)
+            .disabled(!enabled)
+            .tooltip(move |cx| Tooltip::for_action(tooltip, &*tooltip_action, cx))
+            .on_click(move |_, cx| cx.dispatch_action(action.boxed_clone()))
+    }
+
+    fn render_toolbar(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let status = self
+            .active_session_state()
+            .map(|state| state.session.read(cx).status().clone());
+        let is_stopped = matches!(status, Some(DebugSessionStatus::Stopped { .. }));
+        let is_running = matches!(status, Some(DebugSessionStatus::Running));
+        let status_label = match &status {
+            Some(DebugSessionStatus::Starting) => "Starting".to_owned(),
+            Some(DebugSessionStatus::Running) => "Running".to_owned(),
+            Some(DebugSessionStatus::Stopped { reason, .. }) => format!("Paused on {reason}"),
+            Some(DebugSessionStatus::Exited) => "Exited".to_owned(),
+            None => String::new(),
+        };
+
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(self.sessions.iter().map(|state| {
+                let id = state.id;
+                Button::new(
+                    ("debug-session", id.0),
+                    state.session.read(cx).label().to_owned(),
+                )
+                .label_size(LabelSize::Small)
+                .selected(self.active_session == Some(id))
+                .on_click(cx.listener(move |this, _, cx| this.select_session(id, cx)))
+            }))
+            .child(
+                Label::new(status_label)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(div().flex_1())
+            .child(self.render_command_button(
+                "debug-continue",
+                IconName::Play,
+                "Continue",
+                &Continue,
+                is_stopped,
+            ))
+            .child(self.render_command_button(
+                "debug-pause",
+                IconName::DebugPause,
+                "Pause",
+                &Pause,
+                is_running,
+            ))
+            .child(self.render_command_button(
+                "debug-step-over",
+                IconName::DebugStepOver,
+                "Step Over",
+                &StepOver,
+                is_stopped,
+            ))
+            .child(self.render_command_button(
+                "debug-step-into",
+                IconName::DebugStepInto,
+                "Step Into",
+                &StepInto,
+                is_stopped,
+            ))
+            .child(self.render_command_button(
+                "debug-step-out",
+                IconName::DebugStepOut,
+                "Step Out",
+                &StepOut,
+                is_stopped,
+            ))
+            .child(self.render_command_button(
+                "debug-stop",
+                IconName::Stop,
+                "Stop",
+                &Stop,
+                status.is_some(),
+            ))
+    }
+
+    fn render_section_header(&self, title: &'static str, cx: &ViewContext<Self>) -> Div {
+        h_flex()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+    }
+
+    fn render_call_stack(&self, state: &SessionState, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        let session = state.session.read(cx);
+        let current_thread_id = session.current_thread_id();
+        let id = state.id;
+        v_flex()
+            .id("debug-call-stack")
+            .flex_1()
+            .h_full()
+            .overflow_y_scroll()
+            .child(self.render_section_header("Threads", cx))
+            .children(session.threads().iter().map(|thread| {
+                ListItem::new(("debug-thread", thread.id as usize))
+                    .selected(current_thread_id == Some(thread.id))
+                    .child(Label::new(thread.name.clone()).size(LabelSize::Small))
+            }))
+            .child(self.render_section_header("Call Stack", cx))
+            .children(state.stack_frames.iter().map(|frame| {
+                let frame_id = frame.id;
+                let location = frame
+                    .source
+                    .as_ref()
+                    .and_then(|source| source.name.clone().or_else(|| source.path.clone()))
+                    .map(|name| format!("{name}:{}", frame.line));
+                ListItem::new(("debug-stack-frame", frame.id as usize))
+                    .selected(state.selected_frame == Some(frame.id))
+                    .on_click(
+                        cx.listener(move |this, _, cx| this.select_stack_frame(id, frame_id, cx)),
+                    )
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(Label::new(frame.name.clone()).size(LabelSize::Small))
+                            .children(location.map(|location| {
+                                Label::new(location)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                            })),
+                    )
+            }))
+    }
+
+    fn render_variables(&self, state: &SessionState, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        let mut rows = Vec::new();
+        for scope in &state.scopes {
+            rows.push(self.render_variable_row(
+                state,
+                rows.len(),
+                scope.name.clone(),
+                None,
+                scope.variables_reference,
+                0,
+                cx,
+            ));
+            self.push_variable_rows(
+                state,
+                scope.variables_reference,
+                1,
+                &mut Vec::new(),
+                &mut rows,
+                cx,
+            );
+        }
+
+        v_flex()
+            .id("debug-variables")
+            .flex_1()
+            .h_full()
+            .overflow_y_scroll()
+            .child(self.render_section_header("Variables", cx))
+            .children(rows)
+            .child(self.render_section_header("Watch", cx))
+            .children(
+                self.watch_expressions
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, expression)| {
+                        let result = match state.watch_results.get(expression) {
+                            Some(Ok(value)) => Label::new(value.clone()).color(Color::Muted),
+                            Some(Err(error)) => Label::new(error.clone()).color(Color::Error),
+                            None => Label::new("not available").color(Color::Muted),
+                        };
+                        ListItem::new(("debug-watch", ix))
+                            .child(
+                                h_flex()
+                                    .gap_2()
+                                    .child(Label::new(expression.clone()).size(LabelSize::Small))
+                                    .child(result.size(LabelSize::Small).single_line()),
+                            )
+                            .end_slot(
+                                IconButton::new(("debug-remove-watch", ix), IconName::Close)
+                                    .icon_size(IconSize::XSmall)
+                                    .tooltip(|cx| Tooltip::text("Remove Watch", cx))
+                                    .on_click(
+                                        cx.listener(move |this, _, cx| this.remove_watch(ix, cx)),
+                                    ),
+                            )
+                    }),
+            )
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .on_action(cx.listener(Self::add_watch))
+                    .child(self.watch_editor.clone()),
+            )
+    }
+
+    /// Renders the fetched children of an expanded scope or variable, skipping the ones
+    /// referencing an ancestor so that cyclic structures don't expand forever.
+    fn push_variable_rows(
+        &self,
+        state: &SessionState,
+        variables_reference: u64,
+        depth: usize,
+        ancestors: &mut Vec<u64>,
+        rows: &mut Vec<AnyElement>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !state.expanded.contains(&variables_reference)
+            || ancestors.contains(&variables_reference)
+        {
+            return;
+        }
+        let Some(variables) = state.variables.get(&variables_reference) else {
+            return;
+        };
+        ancestors.push(variables_reference);
+        for variable in variables {
+            rows.push(self.render_variable_row(
+                state,
+                rows.len(),
+                variable.name.clone(),
+                Some(variable.value.clone()),
+                variable.variables_reference,
+                depth,
+                cx,
+            ));
+            if variable.variables_reference > 0 {
+                self.push_variable_rows(
+                    state,
+                    variable.variables_reference,
+                    depth + 1,
+                    ancestors,
+                    rows,
+                    cx,
+                );
+            }
+        }
+        ancestors.pop();
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_variable_row(
+        &self,
+        state: &SessionState,
+        ix: usize,
+        name: String,
+        value: Option<String>,
+        variables_reference: u64,
+        depth: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let id = state.id;
+        let is_expandable = variables_reference > 0;
+        ListItem::new(("debug-variable", ix))
+            .indent_level(depth)
+            .indent_step_size(px(12.))
+            .toggle(is_expandable.then(|| state.expanded.contains(&variables_reference)))
+            .on_toggle(
+                cx.listener(move |this, _, cx| this.toggle_variables(id, variables_reference, cx)),
+            )
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(name).size(LabelSize::Small))
+                    .children(value.map(|value| {
+                        Label::new(value)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line()
+                    })),
+            )
+            .into_any_element()
+    }
+
+    fn render_console(&self, state: &SessionState, cx: &mut ViewContext<Self>) -> Div {
+        let output = state.session.read(cx).output();
+        v_flex()
+            .flex_1()
+            .h_full()
+            .child(self.render_section_header("Console", cx))
+            .child(
+                v_flex()
+                    .id("debug-console-output")
+                    .flex_1()
+                    .px_2()
+                    .overflow_y_scroll()
+                    .children(output.iter().map(|output| {
+                        let color = match output.category.as_deref() {
+                            Some("stderr") => Color::Error,
+                            Some("console") => Color::Muted,
+                            _ => Color::Default,
+                        };
+                        Label::new(output.output.trim_end_matches('\n').to_owned())
+                            .size(LabelSize::Small)
+                            .color(color)
+                    })),
+            )
+            .child(
+                div()
+                    .px_2()
+                    .py_1()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .on_action(cx.listener(Self::evaluate_console_input))
+                    .child(self.console_editor.clone()),
+            )
+    }
+}
+
+impl Panel for DebugPanel {
+    fn persistent_name() -> &'static str {
+        "Debug Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match DebugPanelSettings::get_global(cx).dock {
+            DebugPanelDockPosition::Left => DockPosition::Left,
+            DebugPanelDockPosition::Bottom => DockPosition::Bottom,
+            DebugPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebugPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left => DebugPanelDockPosition::Left,
+                    DockPosition::Bottom => DebugPanelDockPosition::Bottom,
+                    DockPosition::Right => DebugPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = DebugPanelSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or(settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or(settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        DebugPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Debug)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Debug Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _: &WindowContext) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, _: &mut ViewContext<Self>) {
+        self.active = active;
+    }
+}
+
+impl FocusableView for DebugPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for DebugPanel {}
+
+impl Render for DebugPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let panel = v_flex()
+            .id("debug-panel")
+            .size_full()
+            .key_context("DebugPanel")
+            .track_focus(&self.focus_handle);
+
+        let Some(state) = self.active_session_state() else {
+            return panel.child(v_flex().justify_center().size_full().child(
+                h_flex().justify_center().child(Label::new(
+                    "Run a task with a debug configuration to start debugging",
+                )),
+            ));
+        };
+
+        panel.child(self.render_toolbar(cx)).child(
+            h_flex()
+                .flex_1()
+                .w_full()
+                .items_start()
+                .child(self.render_call_stack(state, cx))
+                .child(
+                    div()
+                        .h_full()
+                        .border_l_1()
+                        .border_color(cx.theme().colors().border),
+                )
+                .child(self.render_variables(state, cx))
+                .child(
+                    div()
+                        .h_full()
+                        .border_l_1()
+                        .border_color(cx.theme().colors().border),
+                )
+                .child(self.render_console(state, cx)),
+        )
+    }
+}
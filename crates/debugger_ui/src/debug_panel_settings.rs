@@ -0,0 +1,53 @@
+use gpui::Pixels;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugPanelDockPosition {
+    Left,
+    Bottom,
+    Right,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct DebugPanelSettings {
+    pub button: bool,
+    pub dock: DebugPanelDockPosition,
+    pub default_width: Pixels,
+    pub default_height: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DebugPanelSettingsContent {
+    /// Whether to show the debugger panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// The position of the debugger panel.
+    ///
+    /// Default: bottom
+    pub dock: Option<DebugPanelDockPosition>,
+    /// Default width (in pixels) of the debugger panel when docked to the left or right.
+    ///
+    /// Default: 400
+    pub default_width: Option<f32>,
+    /// Default height (in pixels) of the debugger panel when docked to the bottom.
+    ///
+    /// Default: 320
+    pub default_height: Option<f32>,
+}
+
+impl Settings for DebugPanelSettings {
+    const KEY: Option<&'static str> = Some("debugger");
+
+    type FileContent = DebugPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
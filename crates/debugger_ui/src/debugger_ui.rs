@@ -0,0 +1,57 @@
+mod debug_panel;
+mod debug_panel_settings;
+
+use debug_panel_settings::DebugPanelSettings;
+use gpui::{actions, Action, AppContext, ViewContext};
+use settings::Settings;
+use workspace::Workspace;
+
+pub use debug_panel::DebugPanel;
+
+actions!(
+    debugger,
+    [
+        ToggleFocus,
+        Continue,
+        Pause,
+        StepOver,
+        StepInto,
+        StepOut,
+        Stop
+    ]
+);
+
+pub fn init_settings(cx: &mut AppContext) {
+    DebugPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+    dap::adapters::init(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<DebugPanel>(cx);
+        });
+        register_session_action(workspace, DebugPanel::continue_debugging);
+        register_session_action(workspace, DebugPanel::pause);
+        register_session_action(workspace, DebugPanel::step_over);
+        register_session_action(workspace, DebugPanel::step_into);
+        register_session_action(workspace, DebugPanel::step_out);
+        register_session_action(workspace, DebugPanel::stop);
+    })
+    .detach();
+}
+
+/// Lets the action drive the active debug session from anywhere in the workspace,
+/// not only from within the debugger panel.
+fn register_session_action<A: Action>(
+    workspace: &mut Workspace,
+    handler: fn(&mut DebugPanel, &A, &mut ViewContext<DebugPanel>),
+) {
+    workspace.register_action(move |workspace, action: &A, cx| {
+        if let Some(panel) = workspace.panel::<DebugPanel>(cx) {
+            panel.update(cx, |panel, cx| handler(panel, action, cx));
+        }
+    });
+}
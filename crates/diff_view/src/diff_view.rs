@@ -0,0 +1,795 @@
+mod text_diff;
+
+use std::{
+    any::{Any, TypeId},
+    mem,
+    ops::Range,
+    time::Duration,
+};
+
+use anyhow::Result;
+use collections::HashSet;
+use editor::{
+    display_map::{BlockDisposition, BlockProperties, BlockStyle, CustomBlockId},
+    scroll::Autoscroll,
+    Editor, EditorEvent,
+};
+use gpui::{
+    actions, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, HighlightStyle, Hsla,
+    Model, Subscription, Task, View,
+};
+use language::{Buffer, Capability, Point};
+use project::{Project, ProjectPath};
+use text::BufferSnapshot;
+use ui::prelude::*;
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, ItemHandle as _},
+    ItemNavHistory, Workspace,
+};
+
+use crate::text_diff::{changed_words, line_hunks, LineHunk};
+
+actions!(
+    diff_view,
+    [CompareWithClipboard, OpenGitDiff, NextHunk, PreviousHunk]
+);
+
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(SplitDiffView::register).detach();
+}
+
+/// Rows changed in one of the sides of a [`SplitDiffView`].
+struct ChangedRows;
+
+/// Words changed within the changed rows of one of the sides of a [`SplitDiffView`].
+struct ChangedWords;
+
+/// A side-by-side comparison of two buffers, with the old one on the left and the new one on
+/// the right. Changed rows are highlighted on both sides and padded so that unchanged rows stay
+/// aligned, and both sides scroll together.
+pub struct SplitDiffView {
+    focus_handle: FocusHandle,
+    old_title: SharedString,
+    new_title: SharedString,
+    old_buffer: Model<Buffer>,
+    new_buffer: Model<Buffer>,
+    old_editor: View<Editor>,
+    new_editor: View<Editor>,
+    /// Whether the old buffer holds the new buffer's git diff base, and follows its changes.
+    follows_diff_base: bool,
+    hunks: Vec<LineHunk>,
+    old_padding: HashSet<CustomBlockId>,
+    new_padding: HashSet<CustomBlockId>,
+    update_task: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// What to show on one side of the view: its changed rows and words, and the padding rows that
+/// stand in for the rows that only exist on the other side.
+#[derive(Default)]
+struct SideDiff {
+    changed_rows: Vec<Range<u32>>,
+    changed_words: Vec<Range<Point>>,
+    /// Rows of padding to insert above the given row.
+    padding: Vec<(u32, u32)>,
+}
+
+impl SplitDiffView {
+    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(Self::compare_with_clipboard);
+        workspace.register_action(Self::open_git_diff);
+    }
+
+    /// Compares the active file with the text in the clipboard.
+    fn compare_with_clipboard(
+        workspace: &mut Workspace,
+        _: &CompareWithClipboard,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(buffer) = active_buffer(workspace, cx) else {
+            return;
+        };
+        let Some(clipboard) = cx.read_from_clipboard() else {
+            return;
+        };
+        let old_buffer = local_copy(&buffer, clipboard.text().clone(), cx);
+        let title = buffer_title(&buffer, cx);
+        Self::open(
+            workspace,
+            old_buffer,
+            buffer,
+            "Clipboard".into(),
+            title,
+            false,
+            cx,
+        );
+    }
+
+    /// Compares the active file with its git diff base, the version of it in the index.
+    fn open_git_diff(workspace: &mut Workspace, _: &OpenGitDiff, cx: &mut ViewContext<Workspace>) {
+        let Some(buffer) = active_buffer(workspace, cx) else {
+            return;
+        };
+        let Some(diff_base) = buffer.read(cx).diff_base().map(|text| text.to_string()) else {
+            return;
+        };
+        let old_buffer = local_copy(&buffer, diff_base, cx);
+        let title = buffer_title(&buffer, cx);
+        let old_title = format!("{title} (Index)").into();
+        Self::open(workspace, old_buffer, buffer, old_title, title, true, cx);
+    }
+
+    /// Opens the files at the given project paths and compares them.
+    pub fn compare_project_paths(
+        workspace: &mut Workspace,
+        old_path: ProjectPath,
+        new_path: ProjectPath,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let (old_buffer, new_buffer) = workspace.project().update(cx, |project, cx| {
+            (
+                project.open_buffer(old_path, cx),
+                project.open_buffer(new_path, cx),
+            )
+        });
+        cx.spawn(|workspace, mut cx| async move {
+            let old_buffer = old_buffer.await?;
+            let new_buffer = new_buffer.await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let old_title = buffer_title(&old_buffer, cx);
+                let new_title = buffer_title(&new_buffer, cx);
+                Self::open(
+                    workspace, old_buffer, new_buffer, old_title, new_title, false, cx,
+                );
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn open(
+        workspace: &mut Workspace,
+        old_buffer: Model<Buffer>,
+        new_buffer: Model<Buffer>,
+        old_title: SharedString,
+        new_title: SharedString,
+        follows_diff_base: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let view = cx.new_view(|cx| {
+            Self::new(
+                old_buffer,
+                new_buffer,
+                old_title,
+                new_title,
+                follows_diff_base,
+                project,
+                cx,
+            )
+        });
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    pub fn new(
+        old_buffer: Model<Buffer>,
+        new_buffer: Model<Buffer>,
+        old_title: SharedString,
+        new_title: SharedString,
+        follows_diff_base: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let old_editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_buffer(old_buffer.clone(), Some(project.clone()), cx);
+            editor.set_read_only(true);
+            configure_editor(&mut editor, cx);
+            editor
+        });
+        let new_editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_buffer(new_buffer.clone(), Some(project), cx);
+            configure_editor(&mut editor, cx);
+            editor
+        });
+
+        let focus_handle = cx.focus_handle();
+        cx.on_focus_in(&focus_handle, |this, cx| {
+            if this.focus_handle.is_focused(cx) {
+                this.new_editor.focus_handle(cx).focus(cx);
+            }
+        })
+        .detach();
+
+        let subscriptions = vec![
+            cx.subscribe(&old_buffer, |this, _, event, cx| {
+                this.handle_buffer_event(event, cx)
+            }),
+            cx.subscribe(&new_buffer, |this, _, event, cx| {
+                if let language::Event::DiffBaseChanged = event {
+                    this.update_diff_base(cx);
+                }
+                this.handle_buffer_event(event, cx)
+            }),
+            cx.subscribe(&old_editor, |this, editor, event, cx| {
+                this.handle_editor_event(editor, event, cx)
+            }),
+            cx.subscribe(&new_editor, |this, editor, event: &EditorEvent, cx| {
+                this.handle_editor_event(editor, event, cx);
+                cx.emit(event.clone());
+            }),
+        ];
+
+        let mut this = Self {
+            focus_handle,
+            old_title,
+            new_title,
+            old_buffer,
+            new_buffer,
+            old_editor,
+            new_editor,
+            follows_diff_base,
+            hunks: Vec::new(),
+            old_padding: HashSet::default(),
+            new_padding: HashSet::default(),
+            update_task: Task::ready(()),
+            _subscriptions: subscriptions,
+        };
+        this.schedule_update(false, cx);
+        this
+    }
+
+    fn handle_buffer_event(&mut self, event: &language::Event, cx: &mut ViewContext<Self>) {
+        if let language::Event::Edited | language::Event::Reloaded = event {
+            self.schedule_update(true, cx);
+        }
+    }
+
+    /// Keeps the vertical scroll positions of both sides the same, which keeps them aligned.
+    fn handle_editor_event(
+        &mut self,
+        editor: View<Editor>,
+        event: &EditorEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let EditorEvent::ScrollPositionChanged { local: true, .. } = event {
+            let other = if editor == self.old_editor {
+                &self.new_editor
+            } else {
+                &self.old_editor
+            };
+            let position = editor.update(cx, |editor, cx| editor.scroll_position(cx));
+            other.update(cx, |other, cx| {
+                let mut other_position = other.scroll_position(cx);
+                if other_position.y != position.y {
+                    other_position.y = position.y;
+                    other.set_scroll_position(other_position, cx);
+                }
+            });
+        }
+    }
+
+    fn update_diff_base(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.follows_diff_base {
+            return;
+        }
+        let diff_base = self
+            .new_buffer
+            .read(cx)
+            .diff_base()
+            .map(|text| text.to_string());
+        self.old_buffer.update(cx, |buffer, cx| {
+            buffer.set_text(diff_base.unwrap_or_default(), cx);
+        });
+    }
+
+    fn schedule_update(&mut self, debounce: bool, cx: &mut ViewContext<Self>) {
+        let old_snapshot = self.old_buffer.read(cx).text_snapshot();
+        let new_snapshot = self.new_buffer.read(cx).text_snapshot();
+        self.update_task = cx.spawn(|this, mut cx| async move {
+            if debounce {
+                cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            }
+            let (hunks, old_side, new_side) = cx
+                .background_executor()
+                .spawn(async move { compute_diff(&old_snapshot, &new_snapshot) })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                this.hunks = hunks;
+                let git_colors = cx.theme().status().git();
+                apply_side_diff(
+                    &this.old_editor,
+                    &mut this.old_padding,
+                    old_side,
+                    git_colors.deleted,
+                    cx,
+                );
+                apply_side_diff(
+                    &this.new_editor,
+                    &mut this.new_padding,
+                    new_side,
+                    git_colors.created,
+                    cx,
+                );
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    fn next_hunk(&mut self, _: &NextHunk, cx: &mut ViewContext<Self>) {
+        self.go_to_hunk(true, cx);
+    }
+
+    fn previous_hunk(&mut self, _: &PreviousHunk, cx: &mut ViewContext<Self>) {
+        self.go_to_hunk(false, cx);
+    }
+
+    /// Moves the cursors of both sides to the start of the next or previous hunk, relative to
+    /// the cursor of the focused side.
+    fn go_to_hunk(&mut self, forward: bool, cx: &mut ViewContext<Self>) {
+        let old_focused = self.old_editor.focus_handle(cx).is_focused(cx);
+        let editor = if old_focused {
+            &self.old_editor
+        } else {
+            &self.new_editor
+        };
+        let cursor_row = editor.update(cx, |editor, cx| {
+            editor.selections.newest::<Point>(cx).head().row
+        });
+        let hunk_row = |hunk: &LineHunk| {
+            if old_focused {
+                hunk.old_rows.start
+            } else {
+                hunk.new_rows.start
+            }
+        };
+        let hunk = if forward {
+            self.hunks.iter().find(|hunk| hunk_row(hunk) > cursor_row)
+        } else {
+            self.hunks
+                .iter()
+                .rev()
+                .find(|hunk| hunk_row(hunk) < cursor_row)
+        };
+        let Some(hunk) = hunk.cloned() else {
+            return;
+        };
+        for (editor, row) in [
+            (&self.old_editor, hunk.old_rows.start),
+            (&self.new_editor, hunk.new_rows.start),
+        ] {
+            editor.update(cx, |editor, cx| {
+                let position = Point::new(row, 0);
+                editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                    selections.select_ranges([position..position])
+                });
+            });
+        }
+    }
+
+    fn render_title(&self, title: &SharedString, cx: &ViewContext<Self>) -> impl IntoElement {
+        h_flex()
+            .w_1_2()
+            .px_2()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(title.clone()).size(LabelSize::Small))
+    }
+}
+
+fn configure_editor(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    // Wrapped lines would take up a different number of rows on each side.
+    editor.set_soft_wrap_mode(language::language_settings::SoftWrap::None, cx);
+    editor.set_show_git_diff_gutter(false, cx);
+}
+
+fn active_buffer(workspace: &Workspace, cx: &AppContext) -> Option<Model<Buffer>> {
+    workspace
+        .active_item_as::<Editor>(cx)?
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .as_singleton()
+}
+
+fn buffer_title(buffer: &Model<Buffer>, cx: &AppContext) -> SharedString {
+    match buffer.read(cx).file() {
+        Some(file) => file.file_name(cx).to_string_lossy().into_owned().into(),
+        None => "untitled".into(),
+    }
+}
+
+/// Creates a read-only buffer with the given text, in the language of the given buffer.
+fn local_copy(buffer: &Model<Buffer>, text: String, cx: &mut AppContext) -> Model<Buffer> {
+    let buffer = buffer.read(cx);
+    let language = buffer.language().cloned();
+    let language_registry = buffer.language_registry();
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        if let Some(language_registry) = language_registry {
+            buffer.set_language_registry(language_registry);
+        }
+        buffer.set_language(language, cx);
+        buffer
+    })
+}
+
+fn compute_diff(
+    old_snapshot: &BufferSnapshot,
+    new_snapshot: &BufferSnapshot,
+) -> (Vec<LineHunk>, SideDiff, SideDiff) {
+    let old_text = old_snapshot.text();
+    let new_text = new_snapshot.text();
+    let hunks = line_hunks(&old_text, &new_text);
+    let mut old_side = SideDiff::default();
+    let mut new_side = SideDiff::default();
+    for hunk in &hunks {
+        old_side.changed_rows.push(hunk.old_rows.clone());
+        new_side.changed_rows.push(hunk.new_rows.clone());
+        if hunk.old_len() < hunk.new_len() {
+            old_side
+                .padding
+                .push((hunk.old_rows.end, hunk.new_len() - hunk.old_len()));
+        } else if hunk.new_len() < hunk.old_len() {
+            new_side
+                .padding
+                .push((hunk.new_rows.end, hunk.old_len() - hunk.new_len()));
+        }
+
+        if hunk.old_len() > 0 && hunk.new_len() > 0 {
+            let old_range = row_range_to_offsets(old_snapshot, &hunk.old_rows);
+            let new_range = row_range_to_offsets(new_snapshot, &hunk.new_rows);
+            let (deleted, inserted) =
+                changed_words(&old_text[old_range.clone()], &new_text[new_range.clone()]);
+            old_side
+                .changed_words
+                .extend(deleted.into_iter().map(|range| {
+                    old_snapshot.offset_to_point(old_range.start + range.start)
+                        ..old_snapshot.offset_to_point(old_range.start + range.end)
+                }));
+            new_side
+                .changed_words
+                .extend(inserted.into_iter().map(|range| {
+                    new_snapshot.offset_to_point(new_range.start + range.start)
+                        ..new_snapshot.offset_to_point(new_range.start + range.end)
+                }));
+        }
+    }
+    (hunks, old_side, new_side)
+}
+
+fn row_range_to_offsets(snapshot: &BufferSnapshot, rows: &Range<u32>) -> Range<usize> {
+    let start = snapshot.point_to_offset(Point::new(rows.start, 0));
+    let end = if rows.end > snapshot.max_point().row {
+        snapshot.len()
+    } else {
+        snapshot.point_to_offset(Point::new(rows.end, 0))
+    };
+    start..end
+}
+
+fn apply_side_diff(
+    editor: &View<Editor>,
+    padding_blocks: &mut HashSet<CustomBlockId>,
+    side: SideDiff,
+    color: Hsla,
+    cx: &mut WindowContext,
+) {
+    let mut row_color = color;
+    row_color.fade_out(0.8);
+    let mut word_color = color;
+    word_color.fade_out(0.5);
+    editor.update(cx, |editor, cx| {
+        editor.remove_blocks(mem::take(padding_blocks), None, cx);
+        editor.clear_row_highlights::<ChangedRows>();
+
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let max_point = snapshot.max_point();
+        for rows in side.changed_rows {
+            if rows.is_empty() {
+                continue;
+            }
+            let start = snapshot.anchor_before(Point::new(rows.start, 0));
+            let end = snapshot.anchor_before(Point::new(rows.end - 1, 0));
+            editor.highlight_rows::<ChangedRows>(start..=end, Some(row_color), false, cx);
+        }
+
+        let blocks = side.padding.into_iter().map(|(row, height)| {
+            let (position, disposition) = if row <= max_point.row {
+                (Point::new(row, 0), BlockDisposition::Above)
+            } else {
+                (max_point, BlockDisposition::Below)
+            };
+            BlockProperties {
+                position: snapshot.anchor_after(position),
+                height,
+                style: BlockStyle::Flex,
+                render: Box::new(|cx| {
+                    div()
+                        .size_full()
+                        .bg(cx.theme().colors().editor_subheader_background)
+                        .into_any_element()
+                }),
+                disposition,
+            }
+        });
+        *padding_blocks = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+
+        let word_ranges = side
+            .changed_words
+            .into_iter()
+            .map(|range| snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end))
+            .collect();
+        editor.highlight_text::<ChangedWords>(
+            word_ranges,
+            HighlightStyle {
+                background_color: Some(word_color),
+                ..Default::default()
+            },
+            cx,
+        );
+    });
+}
+
+impl EventEmitter<EditorEvent> for SplitDiffView {}
+
+impl FocusableView for SplitDiffView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for SplitDiffView {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(format!("{} ↔ {}", self.old_title, self.new_title).into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::Split))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("split diff view")
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.new_editor
+            .update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.new_editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.new_editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        self.new_editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.new_buffer.read(cx).is_dirty()
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.new_buffer.read(cx).has_conflict()
+    }
+
+    fn can_save(&self, cx: &AppContext) -> bool {
+        self.new_buffer.read(cx).file().is_some()
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        self.new_editor.save(format, project, cx)
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: ProjectPath,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.new_editor.reload(project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.new_editor.to_any())
+        } else {
+            None
+        }
+    }
+}
+
+impl Render for SplitDiffView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let border_color = cx.theme().colors().border;
+        v_flex()
+            .key_context("SplitDiffView")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::next_hunk))
+            .on_action(cx.listener(Self::previous_hunk))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .w_full()
+                    .child(self.render_title(&self.old_title, cx))
+                    .child(div().h_full().w_px().bg(border_color))
+                    .child(self.render_title(&self.new_title, cx)),
+            )
+            .child(
+                h_flex()
+                    .size_full()
+                    .child(div().h_full().w_1_2().child(self.old_editor.clone()))
+                    .child(div().h_full().w_px().bg(border_color))
+                    .child(div().h_full().w_1_2().child(self.new_editor.clone())),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualContext as _, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use workspace::Workspace;
+
+    #[gpui::test]
+    async fn test_split_diff_view(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "old.txt": "one\ntwo\nthree\nfour\nfive\nsix\n",
+                "new.txt": "one\n2\nthree\nthree and a half\nthree and three quarters\nfour\nsix\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = project.update(cx, |project, cx| {
+            project.worktrees(cx).next().unwrap().read(cx).id()
+        });
+
+        workspace.update(cx, |workspace, cx| {
+            SplitDiffView::compare_project_paths(
+                workspace,
+                (worktree_id, "old.txt").into(),
+                (worktree_id, "new.txt").into(),
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        let view = workspace.update(cx, |workspace, cx| {
+            workspace.active_item_as::<SplitDiffView>(cx).unwrap()
+        });
+
+        let (old_editor, new_editor) = view.update(cx, |view, cx| {
+            assert_eq!(
+                view.hunks,
+                [
+                    LineHunk {
+                        old_rows: 1..2,
+                        new_rows: 1..2,
+                    },
+                    LineHunk {
+                        old_rows: 3..3,
+                        new_rows: 3..5,
+                    },
+                    LineHunk {
+                        old_rows: 4..5,
+                        new_rows: 6..6,
+                    },
+                ]
+            );
+            assert_eq!(
+                view.tab_content_text(cx).as_deref(),
+                Some("old.txt ↔ new.txt")
+            );
+            (view.old_editor.clone(), view.new_editor.clone())
+        });
+        // Padding rows keep the unchanged rows aligned.
+        let old_text = old_editor.update(cx, |editor, cx| editor.display_text(cx));
+        let new_text = new_editor.update(cx, |editor, cx| editor.display_text(cx));
+        assert_eq!(old_text, "one\ntwo\nthree\n\n\nfour\nfive\nsix\n");
+        assert_eq!(
+            new_text,
+            "one\n2\nthree\nthree and a half\nthree and three quarters\nfour\n\nsix\n"
+        );
+
+        // Hunk navigation moves both sides to the corresponding rows.
+        cx.focus_view(&new_editor);
+        cx.dispatch_action(NextHunk);
+        cx.dispatch_action(NextHunk);
+        let cursor_row = |editor: &View<Editor>, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                editor.selections.newest::<Point>(cx).head().row
+            })
+        };
+        assert_eq!(cursor_row(&old_editor, cx), 3);
+        assert_eq!(cursor_row(&new_editor, cx), 3);
+        cx.dispatch_action(PreviousHunk);
+        assert_eq!(cursor_row(&old_editor, cx), 1);
+        assert_eq!(cursor_row(&new_editor, cx), 1);
+
+        // The diff is updated when the new side is edited.
+        new_editor.update(cx, |editor, cx| {
+            editor.buffer().update(cx, |buffer, cx| {
+                buffer.edit([(Point::new(1, 0)..Point::new(1, 1), "two")], None, cx)
+            });
+        });
+        cx.executor().advance_clock(UPDATE_DEBOUNCE);
+        cx.run_until_parked();
+        view.update(cx, |view, _| {
+            assert_eq!(
+                view.hunks,
+                [
+                    LineHunk {
+                        old_rows: 3..3,
+                        new_rows: 3..5,
+                    },
+                    LineHunk {
+                        old_rows: 4..5,
+                        new_rows: 6..6,
+                    },
+                ]
+            );
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            crate::init(cx);
+        });
+    }
+}
@@ -0,0 +1,149 @@
+use std::ops::Range;
+
+use similar::{ChangeTag, TextDiff};
+
+/// A run of changed lines, as the rows it spans in the old and in the new text.
+/// One of the ranges is empty when lines were only inserted or only deleted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineHunk {
+    pub old_rows: Range<u32>,
+    pub new_rows: Range<u32>,
+}
+
+impl LineHunk {
+    pub fn old_len(&self) -> u32 {
+        self.old_rows.end - self.old_rows.start
+    }
+
+    pub fn new_len(&self) -> u32 {
+        self.new_rows.end - self.new_rows.start
+    }
+}
+
+/// Compares two texts line by line, returning the runs of lines that differ between them.
+pub fn line_hunks(old: &str, new: &str) -> Vec<LineHunk> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut hunks = Vec::<LineHunk>::new();
+    let mut old_row = 0;
+    let mut new_row = 0;
+    for change in diff.iter_all_changes() {
+        let (old_rows, new_rows) = match change.tag() {
+            ChangeTag::Equal => {
+                old_row += 1;
+                new_row += 1;
+                continue;
+            }
+            ChangeTag::Delete => {
+                old_row += 1;
+                (old_row - 1..old_row, new_row..new_row)
+            }
+            ChangeTag::Insert => {
+                new_row += 1;
+                (old_row..old_row, new_row - 1..new_row)
+            }
+        };
+        match hunks.last_mut() {
+            Some(hunk)
+                if hunk.old_rows.end == old_rows.start && hunk.new_rows.end == new_rows.start =>
+            {
+                hunk.old_rows.end = old_rows.end;
+                hunk.new_rows.end = new_rows.end;
+            }
+            _ => hunks.push(LineHunk { old_rows, new_rows }),
+        }
+    }
+    hunks
+}
+
+/// Compares two texts word by word, returning the byte ranges of the words that were deleted
+/// from the old text and inserted into the new one. Adjacent words are merged into one range.
+pub fn changed_words(old: &str, new: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let diff = TextDiff::from_words(old, new);
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    let mut old_offset = 0;
+    let mut new_offset = 0;
+    for change in diff.iter_all_changes() {
+        let len = change.value().len();
+        match change.tag() {
+            ChangeTag::Equal => {
+                old_offset += len;
+                new_offset += len;
+            }
+            ChangeTag::Delete => {
+                push_range(&mut deleted, old_offset..old_offset + len);
+                old_offset += len;
+            }
+            ChangeTag::Insert => {
+                push_range(&mut inserted, new_offset..new_offset + len);
+                new_offset += len;
+            }
+        }
+    }
+    (deleted, inserted)
+}
+
+fn push_range(ranges: &mut Vec<Range<usize>>, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_hunks() {
+        let old = "one\ntwo\nthree\nfour\nfive\n";
+        let new = "one\n2\nthree\nfour\nfour and a half\nfive\nsix\n";
+        assert_eq!(
+            line_hunks(old, new),
+            [
+                LineHunk {
+                    old_rows: 1..2,
+                    new_rows: 1..2,
+                },
+                LineHunk {
+                    old_rows: 4..4,
+                    new_rows: 4..5,
+                },
+                LineHunk {
+                    old_rows: 5..5,
+                    new_rows: 6..7,
+                },
+            ]
+        );
+
+        assert_eq!(
+            line_hunks("a\nb\nc\n", "a\n"),
+            [LineHunk {
+                old_rows: 1..3,
+                new_rows: 1..1,
+            }]
+        );
+        assert_eq!(line_hunks("same\n", "same\n"), []);
+    }
+
+    #[test]
+    fn test_changed_words() {
+        let old = "let value = compute(a, b);";
+        let new = "let result = compute(a, c);";
+        let (deleted, inserted) = changed_words(old, new);
+        assert_eq!(
+            deleted
+                .into_iter()
+                .map(|range| &old[range])
+                .collect::<Vec<_>>(),
+            ["value", "b);"]
+        );
+        assert_eq!(
+            inserted
+                .into_iter()
+                .map(|range| &new[range])
+                .collect::<Vec<_>>(),
+            ["result", "c);"]
+        );
+    }
+}
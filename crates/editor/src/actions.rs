@@ -163,9 +163,12 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptBoth,
         AcceptPartialCopilotSuggestion,
         AcceptInlineCompletion,
+        AcceptOurs,
         AcceptPartialInlineCompletion,
+        AcceptTheirs,
         AddSelectionAbove,
         AddSelectionBelow,
         Backspace,
@@ -206,6 +209,8 @@ gpui::actions!(
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
+        FoldAllComments,
+        FoldAllImports,
         FoldSelectedRanges,
         Format,
         GoToDefinition,
@@ -253,6 +258,7 @@ gpui::actions!(
         PageDown,
         PageUp,
         Paste,
+        PickColor,
         PreviousInlineCompletion,
         Redo,
         RedoSelection,
@@ -291,9 +297,11 @@ gpui::actions!(
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
         Tab,
         TabPrev,
         ToggleAutoSignatureHelp,
+        ToggleBreakpoint,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleSelectionMenu,
@@ -309,6 +317,7 @@ gpui::actions!(
         UnfoldLines,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
     ]
 );
 
@@ -0,0 +1,101 @@
+use gpui::AppContext;
+use language::{Bias, Point, ToPoint as _};
+use multi_buffer::{Anchor, MultiBufferRow, ToPoint as _};
+use ui::{prelude::*, IconButtonShape, Tooltip, ViewContext};
+
+use crate::{
+    display_map::{DisplayRow, ToDisplayPoint},
+    DisplayPoint, Editor, EditorSnapshot, ToggleBreakpoint,
+};
+
+impl Editor {
+    pub fn toggle_breakpoint(&mut self, _: &ToggleBreakpoint, cx: &mut ViewContext<Self>) {
+        let head = self.selections.newest_anchor().head();
+        self.toggle_breakpoint_at(head, cx);
+    }
+
+    /// Adds a breakpoint on the line of the given position, or removes the one already there.
+    fn toggle_breakpoint_at(&mut self, position: Anchor, cx: &mut ViewContext<Self>) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some((buffer, buffer_position)) =
+            self.buffer.read(cx).text_anchor_for_position(position, cx)
+        else {
+            return;
+        };
+        let row = buffer_position.to_point(buffer.read(cx)).row;
+        let dap_store = project.read(cx).dap_store().clone();
+        dap_store.update(cx, |dap_store, cx| {
+            dap_store.toggle_breakpoint(&buffer, row, cx)
+        });
+    }
+
+    /// Returns the display rows of the breakpoints set in the editor's buffers, skipping folded ones.
+    pub(crate) fn breakpoint_display_rows(
+        &self,
+        snapshot: &EditorSnapshot,
+        cx: &AppContext,
+    ) -> Vec<DisplayRow> {
+        let Some(project) = self.project.as_ref() else {
+            return Vec::new();
+        };
+        let dap_store = project.read(cx).dap_store().read(cx);
+        let multi_buffer = self.buffer.read(cx);
+        let mut display_rows = Vec::new();
+        for buffer in multi_buffer.all_buffers() {
+            let buffer_snapshot = buffer.read(cx);
+            let rows = dap_store.breakpoint_rows(buffer_snapshot.remote_id(), buffer_snapshot);
+            if rows.is_empty() {
+                continue;
+            }
+            for (excerpt_id, excerpt_range) in multi_buffer.excerpts_for_buffer(&buffer, cx) {
+                let context_start = excerpt_range.context.start.to_point(buffer_snapshot);
+                let context_end = excerpt_range.context.end.to_point(buffer_snapshot);
+                for row in &rows {
+                    if *row < context_start.row || *row > context_end.row {
+                        continue;
+                    }
+                    let position =
+                        buffer_snapshot.anchor_after(Point::new(*row, 0).max(context_start));
+                    let Some(anchor) = snapshot
+                        .buffer_snapshot
+                        .anchor_in_excerpt(excerpt_id, position)
+                    else {
+                        continue;
+                    };
+                    let multi_buffer_row = anchor.to_point(&snapshot.buffer_snapshot).row;
+                    if snapshot.is_line_folded(MultiBufferRow(multi_buffer_row)) {
+                        continue;
+                    }
+                    display_rows.push(anchor.to_display_point(snapshot).row());
+                }
+            }
+        }
+        display_rows.sort_unstable();
+        display_rows.dedup();
+        display_rows
+    }
+
+    pub(crate) fn render_breakpoint_indicator(
+        &self,
+        row: DisplayRow,
+        cx: &mut ViewContext<Self>,
+    ) -> IconButton {
+        IconButton::new(
+            ("breakpoint_indicator", row.0 as usize),
+            IconName::DebugBreakpoint,
+        )
+        .shape(IconButtonShape::Square)
+        .icon_size(IconSize::XSmall)
+        .icon_color(Color::Error)
+        .tooltip(|cx| Tooltip::for_action("Remove breakpoint", &ToggleBreakpoint, cx))
+        .on_click(cx.listener(move |editor, _, cx| {
+            let snapshot = editor.snapshot(cx);
+            let position = snapshot
+                .display_snapshot
+                .display_point_to_anchor(DisplayPoint::new(row, 0), Bias::Left);
+            editor.toggle_breakpoint_at(position, cx);
+        }))
+    }
+}
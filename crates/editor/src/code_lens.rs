@@ -0,0 +1,375 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use collections::{HashMap, HashSet};
+use gpui::{Model, WeakView};
+use language::{Buffer, Point, ToOffset, ToPoint};
+use project::CodeLens;
+use settings::Settings;
+use text::{Bias, BufferId};
+use ui::{prelude::*, ViewContext};
+use util::ResultExt;
+
+use crate::{
+    display_map::{BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, RenderBlock},
+    Editor, EditorMode, EditorSettings,
+};
+
+pub(super) const CODE_LENS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// The code lenses displayed for one of the editor's buffers.
+pub(super) struct BufferCodeLens {
+    buffer: Model<Buffer>,
+    /// The version of the buffer the lenses were fetched for, `None` once they are outdated.
+    version: Option<clock::Global>,
+    pub(super) blocks: Vec<CodeLensBlock>,
+}
+
+/// The lenses of a line, displayed in a single block above it.
+pub(super) struct CodeLensBlock {
+    id: CustomBlockId,
+    position: text::Anchor,
+    pub(super) lenses: Vec<CodeLens>,
+    /// Whether the commands of the lenses were requested, which only happens once the block
+    /// is scrolled into view.
+    resolved: bool,
+}
+
+/// Requests the code lenses of the editor's buffers that changed since they were last
+/// displayed, and replaces the blocks of those buffers.
+///
+/// `invalidate` refetches the lenses of every buffer, for when they may have changed without
+/// the buffers being edited.
+pub(super) fn refresh_code_lens(
+    editor: &mut Editor,
+    invalidate: bool,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    if !EditorSettings::get_global(cx).code_lens {
+        editor.code_lens_task = None;
+        let buffer_ids = editor.code_lens.keys().copied().collect::<Vec<_>>();
+        for buffer_id in buffer_ids {
+            remove_buffer_code_lens(editor, buffer_id, cx);
+        }
+        return None;
+    }
+    let project = editor.project.clone()?;
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    let buffer_ids = buffers
+        .iter()
+        .map(|buffer| buffer.read(cx).remote_id())
+        .collect::<HashSet<_>>();
+    let removed_buffer_ids = editor
+        .code_lens
+        .keys()
+        .filter(|buffer_id| !buffer_ids.contains(buffer_id))
+        .copied()
+        .collect::<Vec<_>>();
+    for buffer_id in removed_buffer_ids {
+        remove_buffer_code_lens(editor, buffer_id, cx);
+    }
+    if invalidate {
+        for code_lens in editor.code_lens.values_mut() {
+            code_lens.version = None;
+        }
+    }
+
+    let outdated_buffers = buffers
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            editor
+                .code_lens
+                .get(&buffer.remote_id())
+                .and_then(|code_lens| code_lens.version.as_ref())
+                != Some(&buffer.version())
+        })
+        .collect::<Vec<_>>();
+    if outdated_buffers.is_empty() {
+        return None;
+    }
+
+    editor.code_lens_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(CODE_LENS_DEBOUNCE_TIMEOUT)
+            .await;
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                outdated_buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let version = buffer.read(cx).version();
+                        let task = project.code_lens(&buffer, cx);
+                        async move { (buffer, version, task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let results = futures::future::join_all(tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                for (buffer, version, lenses) in results {
+                    if let Some(lenses) = lenses.log_err() {
+                        update_buffer_code_lens(editor, buffer, version, lenses, cx);
+                    }
+                }
+                resolve_visible_code_lens(editor, cx);
+            })
+            .log_err();
+    }));
+
+    None
+}
+
+fn update_buffer_code_lens(
+    editor: &mut Editor,
+    buffer: Model<Buffer>,
+    version: clock::Global,
+    lenses: Vec<CodeLens>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let buffer_id = buffer.read(cx).remote_id();
+    remove_buffer_code_lens(editor, buffer_id, cx);
+
+    let buffer_snapshot = buffer.read(cx).snapshot();
+    let mut lenses_by_row = BTreeMap::<u32, Vec<CodeLens>>::new();
+    for lens in lenses {
+        let row = lens.range.start.to_point(&buffer_snapshot).row;
+        lenses_by_row.entry(row).or_default().push(lens);
+    }
+
+    let multi_buffer = editor.buffer.read(cx);
+    let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+    let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+    let mut blocks = Vec::new();
+    for (row, lenses) in lenses_by_row {
+        // Blocks are aligned with the indentation of their line.
+        let indent = buffer_snapshot.indent_size_for_line(row);
+        let position = buffer_snapshot.anchor_before(Point::new(row, indent.len));
+        for (excerpt_id, excerpt_range) in &excerpts {
+            let context = &excerpt_range.context;
+            if context.start.cmp(&position, &buffer_snapshot).is_gt()
+                || context.end.cmp(&position, &buffer_snapshot).is_lt()
+            {
+                continue;
+            }
+            if let Some(multi_buffer_position) =
+                multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, position)
+            {
+                blocks.push((multi_buffer_position, position, lenses.clone()));
+            }
+        }
+    }
+
+    let view = cx.view().downgrade();
+    let block_ids = editor.insert_blocks(
+        blocks.iter().map(|(position, _, lenses)| BlockProperties {
+            position: *position,
+            height: 1,
+            style: BlockStyle::Fixed,
+            render: render_code_lens(view.clone(), buffer.clone(), lenses.clone()),
+            disposition: BlockDisposition::Above,
+        }),
+        None,
+        cx,
+    );
+    let blocks = block_ids
+        .into_iter()
+        .zip(blocks)
+        .map(|(id, (_, position, lenses))| CodeLensBlock {
+            id,
+            position,
+            resolved: lenses.iter().all(|lens| lens.lsp_lens.command.is_some()),
+            lenses,
+        })
+        .collect();
+    editor.code_lens.insert(
+        buffer_id,
+        BufferCodeLens {
+            buffer,
+            version: Some(version),
+            blocks,
+        },
+    );
+    cx.notify();
+}
+
+fn remove_buffer_code_lens(editor: &mut Editor, buffer_id: BufferId, cx: &mut ViewContext<Editor>) {
+    let Some(code_lens) = editor.code_lens.remove(&buffer_id) else {
+        return;
+    };
+    let block_ids = code_lens
+        .blocks
+        .iter()
+        .map(|block| block.id)
+        .collect::<HashSet<_>>();
+    for block_id in &block_ids {
+        editor.code_lens_resolve_tasks.remove(block_id);
+    }
+    if !block_ids.is_empty() {
+        editor.remove_blocks(block_ids, None, cx);
+    }
+}
+
+/// Resolves the commands of the lenses that are scrolled into view, which servers are allowed
+/// to leave out of their responses.
+pub(super) fn resolve_visible_code_lens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.code_lens.is_empty() {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let multi_buffer = editor.buffer.read(cx);
+    let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+    let visible_start = editor
+        .scroll_manager
+        .anchor()
+        .anchor
+        .to_point(&multi_buffer_snapshot);
+    let visible_end = multi_buffer_snapshot.clip_point(
+        visible_start + Point::new(editor.visible_line_count().unwrap_or(0.).ceil() as u32, 0),
+        Bias::Left,
+    );
+    let visible_ranges = multi_buffer.range_to_buffer_ranges(visible_start..visible_end, cx);
+
+    let mut blocks_to_resolve = Vec::new();
+    for (buffer, visible_range, _) in visible_ranges {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let Some(code_lens) = editor.code_lens.get_mut(&buffer_snapshot.remote_id()) else {
+            continue;
+        };
+        for block in &mut code_lens.blocks {
+            if block.resolved {
+                continue;
+            }
+            let offset = block.position.to_offset(&buffer_snapshot);
+            if visible_range.start <= offset && offset <= visible_range.end {
+                block.resolved = true;
+                blocks_to_resolve.push((code_lens.buffer.clone(), block.id, block.lenses.clone()));
+            }
+        }
+    }
+
+    for (buffer, block_id, lenses) in blocks_to_resolve {
+        let tasks = project.update(cx, |project, cx| {
+            lenses
+                .into_iter()
+                .map(|lens| project.resolve_code_lens(buffer.clone(), lens, cx))
+                .collect::<Vec<_>>()
+        });
+        let task = cx.spawn(|editor, mut cx| async move {
+            let resolved_lenses = futures::future::join_all(tasks).await;
+            editor
+                .update(&mut cx, |editor, cx| {
+                    editor.code_lens_resolve_tasks.remove(&block_id);
+                    let Some(block) = editor
+                        .code_lens
+                        .get_mut(&buffer.read(cx).remote_id())
+                        .and_then(|code_lens| {
+                            code_lens
+                                .blocks
+                                .iter_mut()
+                                .find(|block| block.id == block_id)
+                        })
+                    else {
+                        return;
+                    };
+                    for (lens, resolved_lens) in block.lenses.iter_mut().zip(resolved_lenses) {
+                        if let Some(resolved_lens) = resolved_lens.log_err() {
+                            *lens = resolved_lens;
+                        }
+                    }
+                    let render =
+                        render_code_lens(cx.view().downgrade(), buffer, block.lenses.clone());
+                    editor.replace_blocks(HashMap::from_iter([(block_id, render)]), None, cx);
+                })
+                .ok();
+        });
+        editor.code_lens_resolve_tasks.insert(block_id, task);
+    }
+}
+
+fn render_code_lens(
+    editor: WeakView<Editor>,
+    buffer: Model<Buffer>,
+    lenses: Vec<CodeLens>,
+) -> RenderBlock {
+    Box::new(move |cx| {
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            // Lenses are only displayed once they have a command.
+            .children(
+                lenses
+                    .iter()
+                    .filter_map(|lens| Some((lens, lens.lsp_lens.command.as_ref()?)))
+                    .enumerate()
+                    .map(|(ix, (lens, command))| {
+                        let title = command.title.clone();
+                        let editor = editor.clone();
+                        let buffer = buffer.clone();
+                        let lens = lens.clone();
+                        h_flex()
+                            .gap_1()
+                            .when(ix > 0, |this| {
+                                this.child(
+                                    Label::new("|").size(LabelSize::Small).color(Color::Muted),
+                                )
+                            })
+                            .child(
+                                Button::new(ix, title)
+                                    .label_size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .size(ButtonSize::None)
+                                    .style(ButtonStyle::Transparent)
+                                    .on_click(move |_, cx| {
+                                        cx.stop_propagation();
+                                        editor
+                                            .update(cx, |editor, cx| {
+                                                editor.execute_code_lens(
+                                                    buffer.clone(),
+                                                    lens.clone(),
+                                                    cx,
+                                                )
+                                            })
+                                            .ok();
+                                    }),
+                            )
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
+impl Editor {
+    fn execute_code_lens(
+        &mut self,
+        buffer: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<()> {
+        let workspace = self.workspace()?.downgrade();
+        let project = self.project.clone()?;
+        let title = lens.lsp_lens.command.as_ref()?.title.clone();
+        let execute_code_lens = project.update(cx, |project, cx| {
+            project.execute_code_lens(buffer, lens, cx)
+        });
+        cx.spawn(|editor, cx| async move {
+            let project_transaction = execute_code_lens.await?;
+            Self::open_project_transaction(&editor, workspace, project_transaction, title, cx).await
+        })
+        .detach_and_log_err(cx);
+        Some(())
+    }
+}
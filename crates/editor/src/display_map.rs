@@ -21,6 +21,7 @@ mod block_map;
 mod crease_map;
 mod fold_map;
 mod inlay_map;
+mod lsp_folding_ranges;
 mod tab_map;
 mod wrap_map;
 
@@ -47,6 +48,7 @@ use language::{
     Subscription as BufferSubscription,
 };
 use lsp::DiagnosticSeverity;
+pub use lsp_folding_ranges::{LspFoldingRange, LspFoldingRanges};
 use multi_buffer::{
     Anchor, AnchorRangeExt, MultiBuffer, MultiBufferPoint, MultiBufferRow, MultiBufferSnapshot,
     ToOffset, ToPoint,
@@ -82,6 +84,7 @@ pub trait ToDisplayPoint {
 
 type TextHighlights = TreeMap<Option<TypeId>, Arc<(HighlightStyle, Vec<Range<Anchor>>)>>;
 type InlayHighlights = TreeMap<TypeId, TreeMap<InlayId, (HighlightStyle, InlayHighlight)>>;
+type SemanticTokenHighlights = Arc<Vec<(Range<Anchor>, HighlightStyle)>>;
 
 /// Decides how text in a [`MultiBuffer`] should be displayed in a buffer, handling inlay hints,
 /// folding, hard tabs, soft wrapping, custom blocks (like diagnostics), and highlighting.
@@ -105,8 +108,12 @@ pub struct DisplayMap {
     text_highlights: TextHighlights,
     /// Regions of inlays that should be highlighted.
     inlay_highlights: InlayHighlights,
+    /// Regions of text styled according to the semantic tokens of a language server.
+    semantic_token_highlights: SemanticTokenHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     crease_map: CreaseMap,
+    /// Foldable ranges reported by language servers.
+    lsp_folding_ranges: LspFoldingRanges,
     fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
     pub(crate) masked: bool,
@@ -153,9 +160,11 @@ impl DisplayMap {
             wrap_map,
             block_map,
             crease_map,
+            lsp_folding_ranges: Default::default(),
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
+            semantic_token_highlights: Default::default(),
             clip_at_line_ends: false,
             masked: false,
         }
@@ -181,8 +190,10 @@ impl DisplayMap {
             wrap_snapshot,
             block_snapshot,
             crease_snapshot: self.crease_map.snapshot(),
+            lsp_folding_ranges: self.lsp_folding_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
+            semantic_token_highlights: self.semantic_token_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
             masked: self.masked,
             fold_placeholder: self.fold_placeholder.clone(),
@@ -268,6 +279,10 @@ impl DisplayMap {
         self.crease_map.remove(crease_ids, &snapshot)
     }
 
+    pub fn set_lsp_folding_ranges(&mut self, folding_ranges: LspFoldingRanges) {
+        self.lsp_folding_ranges = folding_ranges;
+    }
+
     pub fn insert_blocks(
         &mut self,
         blocks: impl IntoIterator<Item = BlockProperties<Anchor>>,
@@ -370,6 +385,14 @@ impl DisplayMap {
         }
     }
 
+    /// Replaces the semantic token highlights, which must be sorted and non-overlapping.
+    pub(crate) fn set_semantic_token_highlights(
+        &mut self,
+        highlights: Vec<(Range<Anchor>, HighlightStyle)>,
+    ) {
+        self.semantic_token_highlights = Arc::new(highlights);
+    }
+
     pub fn text_highlights(&self, type_id: TypeId) -> Option<(HighlightStyle, &[Range<Anchor>])> {
         let highlights = self.text_highlights.get(&Some(type_id))?;
         Some((highlights.0, &highlights.1))
@@ -445,6 +468,7 @@ impl DisplayMap {
 pub(crate) struct Highlights<'a> {
     pub text_highlights: Option<&'a TextHighlights>,
     pub inlay_highlights: Option<&'a InlayHighlights>,
+    pub semantic_token_highlights: Option<&'a [(Range<Anchor>, HighlightStyle)]>,
     pub styles: HighlightStyles,
 }
 
@@ -466,12 +490,14 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub crease_snapshot: CreaseSnapshot,
+    pub lsp_folding_ranges: LspFoldingRanges,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
     block_snapshot: BlockSnapshot,
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
+    semantic_token_highlights: SemanticTokenHighlights,
     clip_at_line_ends: bool,
     masked: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
@@ -656,6 +682,7 @@ impl DisplaySnapshot {
             Highlights {
                 text_highlights: Some(&self.text_highlights),
                 inlay_highlights: Some(&self.inlay_highlights),
+                semantic_token_highlights: Some(&self.semantic_token_highlights),
                 styles: highlight_styles,
             },
         )
@@ -951,6 +978,21 @@ impl DisplaySnapshot {
             .unwrap_or(false)
     }
 
+    /// Whether a fold can start on the given row, according to the language server's folding
+    /// ranges when it provides them, and to indentation otherwise.
+    pub fn starts_fold(&self, buffer_row: MultiBufferRow) -> bool {
+        if self
+            .lsp_folding_ranges
+            .covers_row(buffer_row, &self.buffer_snapshot)
+        {
+            self.lsp_folding_ranges
+                .query_row(buffer_row, &self.buffer_snapshot)
+                .is_some()
+        } else {
+            self.starts_indent(buffer_row)
+        }
+    }
+
     pub fn foldable_range(
         &self,
         buffer_row: MultiBufferRow,
@@ -964,6 +1006,20 @@ impl DisplaySnapshot {
                 crease.range.to_point(&self.buffer_snapshot),
                 crease.placeholder.clone(),
             ))
+        } else if self
+            .lsp_folding_ranges
+            .covers_row(buffer_row, &self.buffer_snapshot)
+        {
+            let folding_range = self
+                .lsp_folding_ranges
+                .query_row(buffer_row, &self.buffer_snapshot)?;
+            if self.is_line_folded(buffer_row) {
+                return None;
+            }
+            Some((
+                folding_range.range.to_point(&self.buffer_snapshot),
+                self.fold_placeholder.clone(),
+            ))
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
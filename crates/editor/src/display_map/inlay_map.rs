@@ -1,6 +1,6 @@
 use crate::{HighlightStyles, InlayId};
 use collections::{BTreeMap, BTreeSet};
-use gpui::HighlightStyle;
+use gpui::{HighlightStyle, Hsla};
 use language::{Chunk, Edit, Point, TextSummary};
 use multi_buffer::{
     Anchor, MultiBufferChunks, MultiBufferRow, MultiBufferRows, MultiBufferSnapshot, ToOffset,
@@ -44,6 +44,8 @@ pub(crate) struct Inlay {
     pub(crate) id: InlayId,
     pub position: Anchor,
     pub text: text::Rope,
+    /// The color of the inlay's text, overriding the style of its kind.
+    pub color: Option<Hsla>,
 }
 
 impl Inlay {
@@ -59,6 +61,7 @@ impl Inlay {
             id: InlayId::Hint(id),
             position,
             text: text.into(),
+            color: None,
         }
     }
 
@@ -67,6 +70,25 @@ impl Inlay {
             id: InlayId::Suggestion(id),
             position,
             text: text.into(),
+            color: None,
+        }
+    }
+
+    pub fn color(id: usize, position: Anchor, color: Hsla) -> Self {
+        Self {
+            id: InlayId::Color(id),
+            position,
+            text: "■ ".into(),
+            color: Some(color),
+        }
+    }
+
+    pub fn replacement<T: Into<Rope>>(id: usize, position: Anchor, text: T, color: Hsla) -> Self {
+        Self {
+            id: InlayId::Replacement(id),
+            position,
+            text: text.into(),
+            color: Some(color),
         }
     }
 }
@@ -191,10 +213,18 @@ pub struct InlayBufferRows<'a> {
 struct HighlightEndpoint {
     offset: InlayOffset,
     is_start: bool,
-    tag: Option<TypeId>,
+    tag: HighlightTag,
     style: HighlightStyle,
 }
 
+/// Identifies an active highlight. Highlights are layered in the order of their tags,
+/// so semantic tokens are applied first and every other highlight takes precedence.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+enum HighlightTag {
+    SemanticToken(usize),
+    Text(Option<TypeId>),
+}
+
 impl PartialOrd for HighlightEndpoint {
     fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
         Some(self.cmp(other))
@@ -219,7 +249,7 @@ pub struct InlayChunks<'a> {
     max_output_offset: InlayOffset,
     highlight_styles: HighlightStyles,
     highlight_endpoints: Peekable<vec::IntoIter<HighlightEndpoint>>,
-    active_highlights: BTreeMap<Option<TypeId>, HighlightStyle>,
+    active_highlights: BTreeMap<HighlightTag, HighlightStyle>,
     highlights: Highlights<'a>,
     snapshot: &'a InlaySnapshot,
 }
@@ -312,6 +342,12 @@ impl<'a> Iterator for InlayChunks<'a> {
                 let mut highlight_style = match inlay.id {
                     InlayId::Suggestion(_) => self.highlight_styles.suggestion,
                     InlayId::Hint(_) => self.highlight_styles.inlay_hint,
+                    InlayId::Color(_) | InlayId::Replacement(_) => {
+                        inlay.color.map(|color| HighlightStyle {
+                            color: Some(color),
+                            ..Default::default()
+                        })
+                    }
                 };
                 let next_inlay_highlight_endpoint;
                 let offset_in_inlay = self.output_offset - self.transforms.start().0;
@@ -672,6 +708,7 @@ impl InlayMap {
                     id: inlay_id,
                     position: snapshot.buffer.anchor_at(position, bias),
                     text: text.into(),
+                    color: None,
                 });
             } else {
                 to_remove.push(
@@ -1029,6 +1066,15 @@ impl InlaySnapshot {
         cursor.seek(&range.start, Bias::Right, &());
 
         let mut highlight_endpoints = Vec::new();
+        if let Some(semantic_token_highlights) = highlights.semantic_token_highlights {
+            if !semantic_token_highlights.is_empty() {
+                self.apply_semantic_token_highlights(
+                    &range,
+                    semantic_token_highlights,
+                    &mut highlight_endpoints,
+                );
+            }
+        }
         if let Some(text_highlights) = highlights.text_highlights {
             if !text_highlights.is_empty() {
                 self.apply_text_highlights(
@@ -1102,13 +1148,13 @@ impl InlaySnapshot {
                     highlight_endpoints.push(HighlightEndpoint {
                         offset: self.to_inlay_offset(range.start.to_offset(&self.buffer)),
                         is_start: true,
-                        tag: *tag,
+                        tag: HighlightTag::Text(*tag),
                         style,
                     });
                     highlight_endpoints.push(HighlightEndpoint {
                         offset: self.to_inlay_offset(range.end.to_offset(&self.buffer)),
                         is_start: false,
-                        tag: *tag,
+                        tag: HighlightTag::Text(*tag),
                         style,
                     });
                 }
@@ -1118,6 +1164,38 @@ impl InlaySnapshot {
         }
     }
 
+    fn apply_semantic_token_highlights(
+        &self,
+        range: &Range<InlayOffset>,
+        semantic_token_highlights: &[(Range<Anchor>, HighlightStyle)],
+        highlight_endpoints: &mut Vec<HighlightEndpoint>,
+    ) {
+        let range_start = self.buffer.anchor_after(self.to_buffer_offset(range.start));
+        let range_end = self.buffer.anchor_before(self.to_buffer_offset(range.end));
+        let start_ix = semantic_token_highlights
+            .partition_point(|(probe, _)| probe.end.cmp(&range_start, &self.buffer).is_le());
+        for (ix, (token_range, style)) in
+            semantic_token_highlights.iter().enumerate().skip(start_ix)
+        {
+            if token_range.start.cmp(&range_end, &self.buffer).is_ge() {
+                break;
+            }
+
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: self.to_inlay_offset(token_range.start.to_offset(&self.buffer)),
+                is_start: true,
+                tag: HighlightTag::SemanticToken(ix),
+                style: *style,
+            });
+            highlight_endpoints.push(HighlightEndpoint {
+                offset: self.to_inlay_offset(token_range.end.to_offset(&self.buffer)),
+                is_start: false,
+                tag: HighlightTag::SemanticToken(ix),
+                style: *style,
+            });
+        }
+    }
+
     #[cfg(test)]
     pub fn text(&self) -> String {
         self.chunks(Default::default()..self.len(), false, Highlights::default())
@@ -1278,6 +1356,7 @@ mod tests {
                 id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                 position: buffer.read(cx).snapshot(cx).anchor_after(3),
                 text: "|123|".into(),
+                color: None,
             }],
         );
         assert_eq!(inlay_snapshot.text(), "abc|123|defghi");
@@ -1355,11 +1434,13 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(3),
                     text: "|123|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Suggestion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_after(3),
                     text: "|456|".into(),
+                    color: None,
                 },
             ],
         );
@@ -1566,16 +1647,19 @@ mod tests {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(0),
                     text: "|123|\n".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Hint(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(4),
                     text: "|456|".into(),
+                    color: None,
                 },
                 Inlay {
                     id: InlayId::Suggestion(post_inc(&mut next_inlay_id)),
                     position: buffer.read(cx).snapshot(cx).anchor_before(7),
                     text: "\n|567|\n".into(),
+                    color: None,
                 },
             ],
         );
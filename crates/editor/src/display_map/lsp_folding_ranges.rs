@@ -0,0 +1,67 @@
+use collections::HashSet;
+use multi_buffer::{Anchor, MultiBufferRow, MultiBufferSnapshot, ToPoint};
+use std::{ops::Range, sync::Arc};
+use text::BufferId;
+
+/// A foldable region reported by a language server.
+#[derive(Clone, Debug)]
+pub struct LspFoldingRange {
+    pub range: Range<Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// The folding ranges reported by language servers, which supersede indentation based fold
+/// range suggestions in the buffers they were computed for.
+#[derive(Clone, Default)]
+pub struct LspFoldingRanges {
+    buffers: Arc<HashSet<BufferId>>,
+    /// Sorted by start, with the outermost range first when several start at the same position.
+    ranges: Arc<[LspFoldingRange]>,
+}
+
+impl LspFoldingRanges {
+    pub fn new(
+        buffers: HashSet<BufferId>,
+        mut ranges: Vec<LspFoldingRange>,
+        snapshot: &MultiBufferSnapshot,
+    ) -> Self {
+        ranges.sort_by(|a, b| {
+            a.range
+                .start
+                .cmp(&b.range.start, snapshot)
+                .then_with(|| b.range.end.cmp(&a.range.end, snapshot))
+        });
+        Self {
+            buffers: Arc::new(buffers),
+            ranges: ranges.into(),
+        }
+    }
+
+    /// Whether the folding ranges of the row's buffer are provided by a language server.
+    pub fn covers_row(&self, row: MultiBufferRow, snapshot: &MultiBufferSnapshot) -> bool {
+        !self.buffers.is_empty()
+            && snapshot
+                .buffer_line_for_row(row)
+                .map_or(false, |(buffer, _)| {
+                    self.buffers.contains(&buffer.remote_id())
+                })
+    }
+
+    /// Returns the outermost folding range starting on the specified buffer row.
+    pub fn query_row<'a>(
+        &'a self,
+        row: MultiBufferRow,
+        snapshot: &MultiBufferSnapshot,
+    ) -> Option<&'a LspFoldingRange> {
+        let ix = self
+            .ranges
+            .partition_point(|range| range.range.start.to_point(snapshot).row < row.0);
+        self.ranges
+            .get(ix)
+            .filter(|range| range.range.start.to_point(snapshot).row == row.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LspFoldingRange> {
+        self.ranges.iter()
+    }
+}
@@ -0,0 +1,261 @@
+use std::{mem, ops::Range, time::Duration};
+
+use gpui::{Hsla, Model, Pixels, Point, Rgba, ViewContext};
+use language::{Bias, Buffer};
+use multi_buffer::Anchor;
+use project::{ColorPresentation, DocumentColor};
+use settings::Settings;
+use ui::ContextMenu;
+use util::{post_inc, ResultExt};
+
+use crate::{
+    display_map::Inlay, mouse_context_menu::MouseContextMenu, Editor, EditorMode, EditorSettings,
+    InlayId, PickColor, PointForPosition,
+};
+
+pub(super) const DOCUMENT_COLORS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// A color reported by a language server, displayed as a swatch in front of its text.
+#[derive(Debug)]
+pub(super) struct ColorSwatch {
+    inlay_id: InlayId,
+    /// The range of the color's text in the multibuffer.
+    range: Range<Anchor>,
+    buffer: Model<Buffer>,
+    color: DocumentColor,
+}
+
+/// Requests the colors of the editor's buffers from their language servers, and displays a
+/// swatch in front of each of them.
+pub(super) fn refresh_document_colors(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    if !EditorSettings::get_global(cx).document_colors {
+        editor.document_colors_task = None;
+        let swatches = mem::take(&mut editor.color_swatches);
+        if !swatches.is_empty() {
+            let to_remove = swatches.iter().map(|swatch| swatch.inlay_id).collect();
+            editor.splice_inlays(to_remove, Vec::new(), cx);
+        }
+        return None;
+    }
+    let project = editor.project.clone()?;
+    let buffers = editor.buffer.read(cx).all_buffers();
+
+    editor.document_colors_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(DOCUMENT_COLORS_DEBOUNCE_TIMEOUT)
+            .await;
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let task = project.document_colors(&buffer, cx);
+                        async move { (buffer, task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let results = futures::future::join_all(tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multi_buffer = editor.buffer.read(cx);
+                let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+                let mut swatches = Vec::new();
+                let mut to_insert = Vec::new();
+                for (buffer, colors) in results {
+                    let Some(colors) = colors.log_err() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+                    for color in colors {
+                        for (excerpt_id, excerpt_range) in &excerpts {
+                            let context = &excerpt_range.context;
+                            if context
+                                .start
+                                .cmp(&color.range.start, &buffer_snapshot)
+                                .is_gt()
+                                || context.end.cmp(&color.range.end, &buffer_snapshot).is_lt()
+                            {
+                                continue;
+                            }
+                            let (Some(start), Some(end)) = (
+                                multi_buffer_snapshot
+                                    .anchor_in_excerpt(*excerpt_id, color.range.start),
+                                multi_buffer_snapshot
+                                    .anchor_in_excerpt(*excerpt_id, color.range.end),
+                            ) else {
+                                continue;
+                            };
+                            let inlay_id = post_inc(&mut editor.next_inlay_id);
+                            to_insert.push(Inlay::color(inlay_id, start, swatch_color(&color)));
+                            swatches.push(ColorSwatch {
+                                inlay_id: InlayId::Color(inlay_id),
+                                range: start..end,
+                                buffer: buffer.clone(),
+                                color: color.clone(),
+                            });
+                        }
+                    }
+                }
+
+                let to_remove = mem::replace(&mut editor.color_swatches, swatches)
+                    .into_iter()
+                    .map(|swatch| swatch.inlay_id)
+                    .collect();
+                editor.splice_inlays(to_remove, to_insert, cx);
+            })
+            .log_err();
+    }));
+
+    None
+}
+
+fn swatch_color(color: &DocumentColor) -> Hsla {
+    Rgba {
+        r: color.color.red,
+        g: color.color.green,
+        b: color.color.blue,
+        a: color.color.alpha,
+    }
+    .into()
+}
+
+impl Editor {
+    pub fn pick_color(&mut self, _: &PickColor, cx: &mut ViewContext<Self>) {
+        let snapshot = self.snapshot(cx);
+        let cursor = self.selections.newest_anchor().head();
+        let Some(ix) = self.color_swatches.iter().position(|swatch| {
+            swatch
+                .range
+                .start
+                .cmp(&cursor, &snapshot.buffer_snapshot)
+                .is_le()
+                && swatch
+                    .range
+                    .end
+                    .cmp(&cursor, &snapshot.buffer_snapshot)
+                    .is_ge()
+        }) else {
+            return;
+        };
+        let Some(position) = self.to_pixel_point(cursor, &snapshot, cx) else {
+            return;
+        };
+        self.show_color_picker(ix, position, cx);
+    }
+
+    /// Opens the color picker when a color swatch is clicked, returning whether one was.
+    pub(crate) fn click_color_swatch(
+        &mut self,
+        point_for_position: &PointForPosition,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        // Swatches are inlays, which have no valid position in the buffer.
+        if self.color_swatches.is_empty() || point_for_position.as_valid().is_some() {
+            return false;
+        }
+        let snapshot = self.snapshot(cx);
+        let buffer_snapshot = &snapshot.buffer_snapshot;
+        let previous_valid = buffer_snapshot.anchor_at(
+            point_for_position.previous_valid.to_point(&snapshot),
+            Bias::Left,
+        );
+        let next_valid = buffer_snapshot.anchor_at(
+            point_for_position.next_valid.to_point(&snapshot),
+            Bias::Right,
+        );
+        let Some(ix) = self.color_swatches.iter().position(|swatch| {
+            swatch
+                .range
+                .start
+                .cmp(&previous_valid, buffer_snapshot)
+                .is_ge()
+                && swatch.range.start.cmp(&next_valid, buffer_snapshot).is_le()
+        }) else {
+            return false;
+        };
+        self.show_color_picker(ix, position, cx);
+        true
+    }
+
+    /// Shows the ways the language server proposes to write the color of a swatch in, replacing
+    /// the color's text with the picked one.
+    fn show_color_picker(
+        &mut self,
+        ix: usize,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let swatch = &self.color_swatches[ix];
+        let source = swatch.range.start;
+        let buffer = swatch.buffer.clone();
+        let presentations = project.update(cx, |project, cx| {
+            project.color_presentations(&buffer, swatch.color.clone(), cx)
+        });
+
+        cx.spawn(|editor, mut cx| async move {
+            let presentations = presentations.await?;
+            if presentations.is_empty() {
+                return Ok(());
+            }
+            editor.update(&mut cx, |editor, cx| {
+                let view = cx.view().downgrade();
+                let context_menu = ContextMenu::build(cx, |menu, _| {
+                    let mut menu = presentations.into_iter().fold(
+                        menu.header("Color"),
+                        |menu, presentation| {
+                            let view = view.clone();
+                            let buffer = buffer.clone();
+                            menu.entry(presentation.label.clone(), None, move |cx| {
+                                view.update(cx, |editor, cx| {
+                                    editor.apply_color_presentation(&buffer, &presentation, cx)
+                                })
+                                .ok();
+                            })
+                        },
+                    );
+                    // Preselect the first presentation, so it can be picked from the keyboard.
+                    menu.select_first();
+                    menu
+                });
+                editor.mouse_context_menu =
+                    MouseContextMenu::pinned_to_editor(editor, source, position, context_menu, cx);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Writes a color the way a presentation proposes, in a single undoable transaction.
+    ///
+    /// The edits are applied to the buffer itself, as additional ones may lie outside of
+    /// the excerpts shown in the editor.
+    pub(crate) fn apply_color_presentation(
+        &self,
+        buffer: &Model<Buffer>,
+        presentation: &ColorPresentation,
+        cx: &mut ViewContext<Self>,
+    ) {
+        buffer.update(cx, |buffer, cx| {
+            buffer.finalize_last_transaction();
+            buffer.start_transaction();
+            buffer.edit(presentation.edits.iter().cloned(), None, cx);
+            buffer.end_transaction(cx);
+        });
+    }
+}
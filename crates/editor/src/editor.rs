@@ -15,11 +15,15 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod breakpoints;
+mod code_lens;
 mod debounced_delay;
 pub mod display_map;
+mod document_colors;
 mod editor_settings;
 mod editor_settings_controls;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -30,12 +34,16 @@ mod inlay_hint_cache;
 mod inline_completion_provider;
 pub mod items;
 mod linked_editing_ranges;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod replacement_preview;
 mod rust_analyzer_ext;
 pub mod scroll;
 mod selections_collection;
+mod semantic_tokens;
+mod sticky_scroll;
 pub mod tasks;
 
 #[cfg(test)]
@@ -243,6 +251,8 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     Suggestion(usize),
     Hint(usize),
+    Color(usize),
+    Replacement(usize),
 }
 
 impl InlayId {
@@ -250,6 +260,8 @@ impl InlayId {
         match self {
             Self::Suggestion(id) => *id,
             Self::Hint(id) => *id,
+            Self::Color(id) => *id,
+            Self::Replacement(id) => *id,
         }
     }
 }
@@ -277,9 +289,25 @@ pub fn init(cx: &mut AppContext) {
     workspace::register_serializable_item::<Editor>(cx);
 
     cx.observe_new_views(
-        |workspace: &mut Workspace, _cx: &mut ViewContext<Workspace>| {
+        |workspace: &mut Workspace, cx: &mut ViewContext<Workspace>| {
             workspace.register_action(Editor::new_file);
             workspace.register_action(Editor::new_file_in_direction);
+
+            // Edits that language servers make in response to file operations (e.g. updating
+            // the imports of a renamed file) are opened for review, so that they can be undone.
+            cx.subscribe(workspace.project(), |workspace, project, event, cx| {
+                if let project::Event::WorkspaceEditApplied(transaction) = event {
+                    let replica_id = project.read(cx).replica_id();
+                    Editor::open_project_transaction_in_workspace(
+                        workspace,
+                        transaction.clone(),
+                        replica_id,
+                        "File Operation Edits".to_string(),
+                        cx,
+                    );
+                }
+            })
+            .detach();
         },
     )
     .detach();
@@ -506,6 +534,17 @@ pub struct Editor {
     code_actions_task: Option<Task<()>>,
     document_highlights_task: Option<Task<()>>,
     linked_editing_range_task: Option<Task<Option<()>>>,
+    semantic_tokens_task: Option<Task<()>>,
+    folding_ranges_task: Option<Task<()>>,
+    code_lens_task: Option<Task<()>>,
+    code_lens: HashMap<BufferId, code_lens::BufferCodeLens>,
+    code_lens_resolve_tasks: HashMap<CustomBlockId, Task<()>>,
+    document_colors_task: Option<Task<()>>,
+    color_swatches: Vec<document_colors::ColorSwatch>,
+    replacement_previews: Vec<InlayId>,
+    merge_conflicts_task: Option<Task<()>>,
+    merge_conflicts: Vec<merge_conflicts::MergeConflict>,
+    merge_conflict_blocks: HashSet<CustomBlockId>,
     linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
     pending_rename: Option<RenameState>,
     searchable: bool,
@@ -1728,6 +1767,14 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::RefreshSemanticTokens = event {
+                        semantic_tokens::refresh_semantic_tokens(editor, cx);
+                    } else if let project::Event::LanguageServerAdded(_)
+                    | project::Event::LanguageServerRemoved(_) = event
+                    {
+                        folding_ranges::refresh_folding_ranges(editor, cx);
+                        code_lens::refresh_code_lens(editor, true, cx);
+                        document_colors::refresh_document_colors(editor, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1748,6 +1795,12 @@ impl Editor {
                 project_subscriptions.push(cx.observe(&task_inventory, |editor, _, cx| {
                     editor.tasks_update_task = Some(editor.refresh_runnables(cx));
                 }));
+                let dap_store = project.read(cx).dap_store().clone();
+                project_subscriptions.push(cx.subscribe(&dap_store, |_, _, event, cx| {
+                    if let project::dap_store::DapStoreEvent::BreakpointsChanged(_) = event {
+                        cx.notify();
+                    }
+                }));
             }
         }
 
@@ -1824,6 +1877,17 @@ impl Editor {
             code_actions_task: Default::default(),
             document_highlights_task: Default::default(),
             linked_editing_range_task: Default::default(),
+            semantic_tokens_task: None,
+            folding_ranges_task: None,
+            code_lens_task: None,
+            code_lens: HashMap::default(),
+            code_lens_resolve_tasks: HashMap::default(),
+            document_colors_task: None,
+            color_swatches: Vec::new(),
+            replacement_previews: Vec::new(),
+            merge_conflicts_task: None,
+            merge_conflicts: Vec::new(),
+            merge_conflict_blocks: HashSet::default(),
             pending_rename: Default::default(),
             searchable: true,
             cursor_shape: Default::default(),
@@ -1897,6 +1961,11 @@ impl Editor {
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        semantic_tokens::refresh_semantic_tokens(&mut this, cx);
+        folding_ranges::refresh_folding_ranges(&mut this, cx);
+        code_lens::refresh_code_lens(&mut this, false, cx);
+        document_colors::refresh_document_colors(&mut this, cx);
+        merge_conflicts::refresh_merge_conflicts(&mut this, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -4593,18 +4662,11 @@ impl Editor {
     ) -> Result<()> {
         let replica_id = this.update(&mut cx, |this, cx| this.replica_id(cx))?;
 
-        let mut entries = transaction.0.into_iter().collect::<Vec<_>>();
-        cx.update(|cx| {
-            entries.sort_unstable_by_key(|(buffer, _)| {
-                buffer.read(cx).file().map(|f| f.path().clone())
-            });
-        })?;
-
         // If the project transaction's edits are all contained within this editor, then
         // avoid opening a new editor to display them.
 
-        if let Some((buffer, transaction)) = entries.first() {
-            if entries.len() == 1 {
+        if let Some((buffer, buffer_transaction)) = transaction.0.iter().next() {
+            if transaction.0.len() == 1 {
                 let excerpt = this.update(&mut cx, |editor, cx| {
                     editor
                         .buffer()
@@ -4616,7 +4678,7 @@ impl Editor {
                         let all_edits_within_excerpt = buffer.read_with(&cx, |buffer, _| {
                             let excerpt_range = excerpt_range.to_offset(buffer);
                             buffer
-                                .edited_ranges_for_transaction::<usize>(transaction)
+                                .edited_ranges_for_transaction::<usize>(buffer_transaction)
                                 .all(|range| {
                                     excerpt_range.start <= range.start
                                         && excerpt_range.end >= range.end
@@ -4633,6 +4695,32 @@ impl Editor {
             return Ok(());
         }
 
+        workspace.update(&mut cx, |workspace, cx| {
+            Self::open_project_transaction_in_workspace(
+                workspace,
+                transaction,
+                replica_id,
+                title,
+                cx,
+            )
+        })?;
+
+        Ok(())
+    }
+
+    /// Opens a multibuffer containing the edits of the given project transaction, with the
+    /// edited ranges highlighted.
+    fn open_project_transaction_in_workspace(
+        workspace: &mut Workspace,
+        transaction: ProjectTransaction,
+        replica_id: ReplicaId,
+        title: String,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let mut entries = transaction.0.into_iter().collect::<Vec<_>>();
+        entries
+            .sort_unstable_by_key(|(buffer, _)| buffer.read(cx).file().map(|f| f.path().clone()));
+
         let mut ranges_to_highlight = Vec::new();
         let excerpt_buffer = cx.new_model(|cx| {
             let mut multibuffer =
@@ -4652,23 +4740,19 @@ impl Editor {
             }
             multibuffer.push_transaction(entries.iter().map(|(b, t)| (b, t)), cx);
             multibuffer
-        })?;
-
-        workspace.update(&mut cx, |workspace, cx| {
-            let project = workspace.project().clone();
-            let editor =
-                cx.new_view(|cx| Editor::for_multibuffer(excerpt_buffer, Some(project), true, cx));
-            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
-            editor.update(cx, |editor, cx| {
-                editor.highlight_background::<Self>(
-                    &ranges_to_highlight,
-                    |theme| theme.editor_highlighted_line_background,
-                    cx,
-                );
-            });
-        })?;
+        });
 
-        Ok(())
+        let project = workspace.project().clone();
+        let editor =
+            cx.new_view(|cx| Editor::for_multibuffer(excerpt_buffer, Some(project), true, cx));
+        workspace.add_item_to_active_pane(Box::new(editor.clone()), None, true, cx);
+        editor.update(cx, |editor, cx| {
+            editor.highlight_background::<Self>(
+                &ranges_to_highlight,
+                |theme| theme.editor_highlighted_line_background,
+                cx,
+            );
+        });
     }
 
     fn refresh_code_actions(&mut self, cx: &mut ViewContext<Self>) -> Option<()> {
@@ -9073,6 +9157,9 @@ impl Editor {
                     cx.open_url(&url);
                     Task::ready(Ok(None))
                 }
+                HoverLink::DocumentLink(buffer, link) => {
+                    self.compute_document_link_target(buffer, link, cx)
+                }
             };
             cx.spawn(|editor, mut cx| async move {
                 let target = target_task.await.context("target resolution task")?;
@@ -9172,6 +9259,7 @@ impl Editor {
                                 }),
                                 HoverLink::InlayHint(_, _) => None,
                                 HoverLink::Url(_) => None,
+                                HoverLink::DocumentLink(..) => None,
                             })
                             .unwrap_or(tab_kind.to_string());
                         let location_tasks = definitions
@@ -9182,6 +9270,9 @@ impl Editor {
                                     editor.compute_target_location(lsp_location, server_id, cx)
                                 }
                                 HoverLink::Url(_) => Task::ready(Ok(None)),
+                                HoverLink::DocumentLink(buffer, link) => {
+                                    editor.compute_document_link_target(buffer, link, cx)
+                                }
                             })
                             .collect::<Vec<_>>();
                         (title, location_tasks, editor.workspace().clone())
@@ -10041,6 +10132,31 @@ impl Editor {
         self.unfold_ranges(std::iter::once(intersection_range), true, autoscroll, cx)
     }
 
+    pub fn fold_all_comments(&mut self, _: &FoldAllComments, cx: &mut ViewContext<Self>) {
+        self.fold_lsp_folding_ranges(lsp::FoldingRangeKind::Comment, cx);
+    }
+
+    pub fn fold_all_imports(&mut self, _: &FoldAllImports, cx: &mut ViewContext<Self>) {
+        self.fold_lsp_folding_ranges(lsp::FoldingRangeKind::Imports, cx);
+    }
+
+    /// Folds all the regions of the given kind reported by the language servers.
+    fn fold_lsp_folding_ranges(&mut self, kind: lsp::FoldingRangeKind, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_ranges = display_map
+            .lsp_folding_ranges
+            .iter()
+            .filter(|folding_range| folding_range.kind.as_ref() == Some(&kind))
+            .map(|folding_range| {
+                (
+                    folding_range.range.to_point(&display_map.buffer_snapshot),
+                    display_map.fold_placeholder.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
     pub fn fold_selected_ranges(&mut self, _: &FoldSelectedRanges, cx: &mut ViewContext<Self>) {
         let selections = self.selections.all::<Point>(cx);
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
@@ -11239,9 +11355,14 @@ impl Editor {
                     }
                 }
 
+                merge_conflicts::refresh_merge_conflicts(self, cx);
                 let Some(project) = &self.project else { return };
                 let telemetry = project.read(cx).client().telemetry().clone();
                 refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, false, cx);
+                document_colors::refresh_document_colors(self, cx);
                 telemetry.log_edit_event("editor");
             }
             multi_buffer::Event::ExcerptsAdded {
@@ -11256,6 +11377,11 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, false, cx);
+                document_colors::refresh_document_colors(self, cx);
+                merge_conflicts::refresh_merge_conflicts(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
@@ -11274,6 +11400,10 @@ impl Editor {
             }
             multi_buffer::Event::LanguageChanged(buffer_id) => {
                 linked_editing_ranges::refresh_linked_ranges(self, cx);
+                semantic_tokens::refresh_semantic_tokens(self, cx);
+                folding_ranges::refresh_folding_ranges(self, cx);
+                code_lens::refresh_code_lens(self, true, cx);
+                document_colors::refresh_document_colors(self, cx);
                 cx.emit(EditorEvent::Reparsed(*buffer_id));
                 cx.notify();
             }
@@ -11308,6 +11438,9 @@ impl Editor {
     fn settings_changed(&mut self, cx: &mut ViewContext<Self>) {
         self.tasks_update_task = Some(self.refresh_runnables(cx));
         self.refresh_inline_completion(true, cx);
+        semantic_tokens::refresh_semantic_tokens(self, cx);
+        code_lens::refresh_code_lens(self, false, cx);
+        document_colors::refresh_document_colors(self, cx);
         self.refresh_inlay_hints(
             InlayHintRefreshReason::SettingsChange(inlay_hint_settings(
                 self.selections.newest_anchor().head(),
@@ -12318,7 +12451,7 @@ impl EditorSnapshot {
                 cx,
             ))
         } else if folded
-            || (self.starts_indent(buffer_row) && (row_contains_cursor || self.gutter_hovered))
+            || (self.starts_fold(buffer_row) && (row_contains_cursor || self.gutter_hovered))
         {
             Some(
                 Disclosure::new(("indent-fold-indicator", buffer_row.0), !folded)
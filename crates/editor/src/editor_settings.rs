@@ -12,9 +12,13 @@ pub struct EditorSettings {
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
     pub use_on_type_format: bool,
+    pub code_lens: bool,
+    pub document_colors: bool,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
     pub gutter: Gutter,
+    pub sticky_scroll: StickyScroll,
+    pub minimap: Minimap,
     pub scroll_beyond_last_line: ScrollBeyondLastLine,
     pub vertical_scroll_margin: f32,
     pub scroll_sensitivity: f32,
@@ -110,6 +114,45 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScroll {
+    pub enabled: bool,
+    pub max_depth: usize,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: ShowMinimap,
+    pub side: MinimapSide,
+    pub width: f32,
+}
+
+/// When to show the minimap in the editor.
+///
+/// Default: never
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowMinimap {
+    /// Always show the minimap, next to the text.
+    Always,
+    /// Show the minimap over the text while the mouse is over it.
+    Hover,
+    /// Never show the minimap.
+    Never,
+}
+
+/// Which side of the editor to show the minimap on.
+///
+/// Default: right
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinimapSide {
+    /// Show the minimap between the gutter and the text.
+    Left,
+    /// Show the minimap at the right edge of the editor, under the scrollbar.
+    Right,
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -190,12 +233,26 @@ pub struct EditorSettingsContent {
     ///
     /// Default: true
     pub use_on_type_format: Option<bool>,
+    /// Whether to show the code lenses provided by language servers above the
+    /// symbols they refer to.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
+    /// Whether to show swatches next to the colors reported by language
+    /// servers, which can be clicked to write the color in another format.
+    ///
+    /// Default: true
+    pub document_colors: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Sticky scroll related settings
+    pub sticky_scroll: Option<StickyScrollContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
     /// Whether the editor will scroll beyond the last line.
     ///
     /// Default: one_page
@@ -325,6 +382,36 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Sticky scroll related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct StickyScrollContent {
+    /// Whether to pin the scopes enclosing the first visible line to the top of the editor.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of nested scopes to pin.
+    ///
+    /// Default: 5
+    pub max_depth: Option<usize>,
+}
+
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// When to show the minimap in the editor.
+    ///
+    /// Default: never
+    pub show: Option<ShowMinimap>,
+    /// Which side of the editor to show the minimap on.
+    ///
+    /// Default: right
+    pub side: Option<MinimapSide>,
+    /// The width of the minimap in pixels.
+    ///
+    /// Default: 100
+    pub width: Option<f32>,
+}
+
 impl EditorSettings {
     pub fn jupyter_enabled(cx: &AppContext) -> bool {
         EditorSettings::get_global(cx).jupyter.enabled
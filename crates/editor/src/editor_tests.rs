@@ -1,8 +1,9 @@
 use super::*;
 use crate::{
+    editor_settings::{Minimap, MinimapSide, ShowMinimap},
     scroll::scroll_amount::ScrollAmount,
     test::{
-        assert_text_with_selections, build_editor, editor_hunks,
+        assert_text_with_selections, build_editor, build_editor_with_project, editor_hunks,
         editor_lsp_test_context::EditorLspTestContext, editor_test_context::EditorTestContext,
         expanded_hunks, expanded_hunks_background_highlights, select_ranges,
     },
@@ -1081,6 +1082,587 @@ fn test_fold_action_multiple_line_breaks(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+fn test_fold_lsp_folding_ranges(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let view = cx.add_window(|cx| {
+        let buffer = MultiBuffer::build_simple(
+            &"
+                // A
+                // B
+                // C
+                fn a() {
+                    1
+                }
+            "
+            .unindent(),
+            cx,
+        );
+        build_editor(buffer.clone(), cx)
+    });
+
+    _ = view.update(cx, |view, cx| {
+        let buffer_id = view
+            .buffer
+            .read(cx)
+            .as_singleton()
+            .unwrap()
+            .read(cx)
+            .remote_id();
+        let snapshot = view.buffer.read(cx).snapshot(cx);
+        let folding_ranges = crate::display_map::LspFoldingRanges::new(
+            HashSet::from_iter([buffer_id]),
+            vec![
+                crate::display_map::LspFoldingRange {
+                    range: snapshot.anchor_after(Point::new(0, 4))
+                        ..snapshot.anchor_before(Point::new(2, 4)),
+                    kind: Some(lsp::FoldingRangeKind::Comment),
+                },
+                crate::display_map::LspFoldingRange {
+                    range: snapshot.anchor_after(Point::new(3, 8))
+                        ..snapshot.anchor_before(Point::new(4, 5)),
+                    kind: None,
+                },
+            ],
+            &snapshot,
+        );
+        view.display_map
+            .update(cx, |map, _| map.set_lsp_folding_ranges(folding_ranges));
+
+        view.fold_all_comments(&FoldAllComments, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                // A⋯
+                fn a() {
+                    1
+                }
+            "
+            .unindent(),
+        );
+
+        view.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(4, 0)..Point::new(4, 0)]);
+        });
+        view.fold(&Fold, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                // A⋯
+                fn a() {⋯
+                }
+            "
+            .unindent(),
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_sticky_headers(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(
+            r#"
+            (impl_item "impl" @context type: (_) @name) @item
+            (function_item "fn" @context name: (_) @name) @item
+            "#,
+        )
+        .unwrap(),
+    );
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state(indoc! {"
+        ˇimpl Foo {
+            fn a() {
+                1;
+                2;
+                3;
+            }
+            fn b() {}
+        }
+    "});
+    cx.run_until_parked();
+
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        let headers = |scroll_top, max_depth| {
+            crate::sticky_scroll::sticky_headers(&snapshot, DisplayRow(scroll_top), max_depth, None)
+                .into_iter()
+                .map(|header| header.text)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(headers(0, 5), Vec::<String>::new());
+        // The first line of `a` is covered by the header of the impl, so `a` is pinned below it.
+        assert_eq!(headers(1, 5), ["impl Foo", "fn a"]);
+        assert_eq!(headers(2, 5), ["impl Foo", "fn a"]);
+        assert_eq!(headers(2, 1), ["impl Foo"]);
+        assert_eq!(headers(5, 5), ["impl Foo"]);
+        assert_eq!(headers(7, 5), ["impl Foo"]);
+        assert_eq!(headers(8, 5), Vec::<String>::new());
+    });
+}
+
+#[test]
+fn test_minimap_scroll() {
+    let row_height = px(4.);
+
+    // A buffer that fits in the minimap doesn't scroll it, and the viewport moves by a whole row
+    // when the editor is scrolled by one.
+    assert_eq!(
+        element::minimap_scroll(0., 50., 80., 100., row_height),
+        (0., row_height)
+    );
+    assert_eq!(
+        element::minimap_scroll(50., 50., 80., 100., row_height),
+        (0., row_height)
+    );
+
+    // Otherwise the minimap is scrolled along with the editor, showing its first and last rows
+    // when the editor is scrolled to the top and the bottom. Its viewport moves by less than a
+    // row, as the minimap scrolls in the meantime.
+    assert_eq!(
+        element::minimap_scroll(0., 100., 150., 100., row_height),
+        (0., px(2.))
+    );
+    assert_eq!(
+        element::minimap_scroll(50., 100., 150., 100., row_height),
+        (25., px(2.))
+    );
+    assert_eq!(
+        element::minimap_scroll(100., 100., 150., 100., row_height),
+        (50., px(2.))
+    );
+    // Scrolling beyond the last row doesn't scroll the minimap past its last rows.
+    assert_eq!(
+        element::minimap_scroll(120., 100., 150., 100., row_height),
+        (50., px(2.))
+    );
+
+    // The viewport moves by at least a pixel, even in a very long buffer.
+    assert_eq!(
+        element::minimap_scroll(0., 10000., 10100., 100., row_height).1,
+        px(1.)
+    );
+    assert_eq!(
+        element::minimap_scroll(0., 0., 10., 100., row_height),
+        (0., row_height)
+    );
+}
+
+#[test]
+fn test_minimap_and_text_bounds() {
+    let editor_bounds = Bounds {
+        origin: point(px(10.), px(20.)),
+        size: size(px(800.), px(600.)),
+    };
+    let gutter_dimensions = GutterDimensions {
+        width: px(50.),
+        margin: px(5.),
+        ..Default::default()
+    };
+    let bounds = |x: f32, width: f32| Bounds {
+        origin: point(px(x), px(20.)),
+        size: size(px(width), px(600.)),
+    };
+    let minimap = |show, side, width| Minimap { show, side, width };
+
+    // On the left, the minimap is shown between the gutter and the text, which starts after it.
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Always, MinimapSide::Left, 100.),
+            EditorMode::Full,
+        ),
+        (bounds(160., 650.), Some(bounds(60., 100.)))
+    );
+
+    // On the right, the text starts right after the gutter, and the minimap is shown at the
+    // right edge of the editor.
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Always, MinimapSide::Right, 100.),
+            EditorMode::Full,
+        ),
+        (bounds(60., 650.), Some(bounds(710., 100.)))
+    );
+
+    // A minimap shown on hover is painted over the text, which isn't moved or narrowed for it on
+    // either side.
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Hover, MinimapSide::Left, 100.),
+            EditorMode::Full,
+        ),
+        (bounds(60., 750.), Some(bounds(60., 100.)))
+    );
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Hover, MinimapSide::Right, 100.),
+            EditorMode::Full,
+        ),
+        (bounds(60., 750.), Some(bounds(710., 100.)))
+    );
+
+    // The minimap takes at most half of the width next to the gutter.
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Always, MinimapSide::Left, 1000.),
+            EditorMode::Full,
+        ),
+        (bounds(435., 375.), Some(bounds(60., 375.)))
+    );
+
+    // No minimap is shown when it's disabled, or in editors that aren't full.
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Never, MinimapSide::Left, 100.),
+            EditorMode::Full,
+        ),
+        (bounds(60., 750.), None)
+    );
+    assert_eq!(
+        element::text_and_minimap_bounds(
+            editor_bounds,
+            gutter_dimensions,
+            minimap(ShowMinimap::Always, MinimapSide::Left, 100.),
+            EditorMode::AutoHeight { max_lines: 10 },
+        ),
+        (bounds(60., 750.), None)
+    );
+}
+
+#[gpui::test]
+async fn test_semantic_token_highlights(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.semantic_tokens = Some(true)
+    });
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<theme::ThemeSettings>(cx, |settings| {
+                settings.theme_overrides = Some(
+                    serde_json::from_value(json!({
+                        "syntax": { "function": { "color": "#ff0000ff" } }
+                    }))
+                    .unwrap(),
+                );
+            });
+        });
+    });
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            semantic_tokens_provider: Some(
+                lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                    lsp::SemanticTokensOptions {
+                        legend: lsp::SemanticTokensLegend {
+                            token_types: vec![
+                                lsp::SemanticTokenType::VARIABLE,
+                                lsp::SemanticTokenType::FUNCTION,
+                            ],
+                            token_modifiers: Vec::new(),
+                        },
+                        full: Some(lsp::SemanticTokensFullOptions::Bool(true)),
+                        ..Default::default()
+                    },
+                ),
+            ),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    let mut requests =
+        cx.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(|_, _, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: None,
+                    data: vec![lsp::SemanticToken {
+                        delta_line: 0,
+                        delta_start: 15,
+                        length: 3,
+                        token_type: 1,
+                        token_modifiers_bitset: 0,
+                    }],
+                },
+            )))
+        });
+    cx.set_state("fn main() { a.foo(); }ˇ");
+    cx.executor()
+        .advance_clock(crate::semantic_tokens::SEMANTIC_TOKENS_DEBOUNCE_TIMEOUT);
+    requests.next().await;
+    cx.run_until_parked();
+
+    // Only the range of the token is styled as a function.
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        let highlighted_text = snapshot
+            .display_snapshot
+            .chunks(
+                DisplayRow(0)..DisplayRow(1),
+                true,
+                crate::display_map::HighlightStyles::default(),
+            )
+            .filter(|chunk| {
+                chunk
+                    .highlight_style
+                    .and_then(|style| style.color)
+                    .is_some()
+            })
+            .map(|chunk| chunk.text)
+            .collect::<String>();
+        assert_eq!(highlighted_text, "foo");
+    });
+}
+
+#[gpui::test]
+async fn test_color_presentation_picker(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    let red = lsp::Color {
+        red: 1.,
+        green: 0.,
+        blue: 0.,
+        alpha: 1.,
+    };
+    cx.set_state(r##"const RED: &str = "#ffˇ0000";"##);
+    let color_range = cx.lsp_range(r##"const RED: &str = "«#ff0000»";"##);
+    let mut color_requests =
+        cx.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _, _| async move {
+            Ok(vec![lsp::ColorInformation {
+                range: color_range,
+                color: red,
+            }])
+        });
+    cx.handle_request::<lsp::request::ColorPresentationRequest, _, _>(|_, _, _| async move {
+        Ok(vec![
+            lsp::ColorPresentation {
+                label: "#f00".into(),
+                text_edit: None,
+                additional_text_edits: None,
+            },
+            lsp::ColorPresentation {
+                label: "rgb(255, 0, 0)".into(),
+                text_edit: None,
+                additional_text_edits: None,
+            },
+        ])
+    });
+
+    cx.executor()
+        .advance_clock(crate::document_colors::DOCUMENT_COLORS_DEBOUNCE_TIMEOUT);
+    color_requests.next().await;
+    cx.run_until_parked();
+
+    // The first presentation is preselected, and picking it rewrites the color.
+    cx.update_editor(|editor, cx| editor.pick_color(&PickColor, cx));
+    cx.run_until_parked();
+    let context_menu = cx.update_editor(|editor, _| {
+        editor
+            .mouse_context_menu
+            .as_ref()
+            .expect("color picker should be open")
+            .context_menu
+            .clone()
+    });
+    cx.update(|cx| context_menu.update(cx, |menu, cx| menu.confirm(&Default::default(), cx)));
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert!(editor.mouse_context_menu.is_none());
+        assert_eq!(editor.text(cx), r##"const RED: &str = "#f00";"##);
+        editor.undo(&Undo, cx);
+        assert_eq!(editor.text(cx), r##"const RED: &str = "#ff0000";"##);
+    });
+}
+
+#[gpui::test]
+async fn test_code_lens_blocks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            code_lens_provider: Some(lsp::CodeLensOptions {
+                resolve_provider: None,
+            }),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    let mut requests =
+        cx.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _, _| async move {
+            let lens = |title: &str| lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 7), lsp::Position::new(2, 11)),
+                command: Some(lsp::Command {
+                    title: title.into(),
+                    command: "_the/command".into(),
+                    arguments: None,
+                }),
+                data: None,
+            };
+            Ok(Some(vec![lens("Run Test"), lens("Debug")]))
+        });
+    cx.set_state(indoc! {"
+        mod tests {
+            #[test]
+            fn ˇtest() {}
+        }
+    "});
+    cx.executor()
+        .advance_clock(crate::code_lens::CODE_LENS_DEBOUNCE_TIMEOUT);
+    requests.next().await;
+    cx.run_until_parked();
+
+    // Both lenses of the line are displayed in a single block above it.
+    cx.update_editor(|editor, cx| {
+        let blocks = editor
+            .code_lens
+            .values()
+            .flat_map(|code_lens| &code_lens.blocks)
+            .collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].lenses.len(), 2);
+        assert_eq!(
+            editor.display_text(cx),
+            "mod tests {\n    #[test]\n\n    fn test() {}\n}\n"
+        );
+    });
+
+    cx.update(|cx| {
+        cx.update_global::<SettingsStore, _>(|settings, cx| {
+            settings.update_user_settings::<EditorSettings>(cx, |settings| {
+                settings.code_lens = Some(false);
+            });
+        });
+    });
+    cx.update_editor(|editor, cx| {
+        assert!(editor.code_lens.is_empty());
+        assert_eq!(
+            editor.display_text(cx),
+            "mod tests {\n    #[test]\n    fn test() {}\n}\n"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_merge_conflicts(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        fn main() {
+        <<<<<<< HEAD
+            ˇfirst_ours();
+        =======
+            first_theirs();
+        >>>>>>> feature
+            common();
+        <<<<<<< HEAD
+            second_ours();
+        ||||||| base
+            second_base();
+        =======
+            second_theirs();
+        >>>>>>> feature
+        }
+    "});
+    cx.executor()
+        .advance_clock(crate::merge_conflicts::MERGE_CONFLICTS_DEBOUNCE_TIMEOUT);
+    cx.run_until_parked();
+
+    // Each conflict gets a block with its buttons above it.
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.merge_conflicts.len(), 2);
+        assert_eq!(editor.merge_conflict_blocks.len(), 2);
+        assert!(editor
+            .display_text(cx)
+            .starts_with("fn main() {\n\n<<<<<<< HEAD\n"));
+    });
+
+    // Only the conflict containing the selection is resolved.
+    cx.update_editor(|editor, cx| editor.accept_theirs(&AcceptTheirs, cx));
+    cx.update_editor(|editor, cx| {
+        assert_eq!(editor.merge_conflicts.len(), 1);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                fn main() {
+                    first_theirs();
+                    common();
+                <<<<<<< HEAD
+                    second_ours();
+                ||||||| base
+                    second_base();
+                =======
+                    second_theirs();
+                >>>>>>> feature
+                }
+            "}
+        );
+    });
+
+    cx.update_editor(|editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(4, 0)..Point::new(4, 0)])
+        });
+        editor.accept_both(&AcceptBoth, cx);
+    });
+    cx.executor()
+        .advance_clock(crate::merge_conflicts::MERGE_CONFLICTS_DEBOUNCE_TIMEOUT);
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| {
+        assert!(editor.merge_conflicts.is_empty());
+        assert!(editor.merge_conflict_blocks.is_empty());
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                fn main() {
+                    first_theirs();
+                    common();
+                    second_ours();
+                    second_theirs();
+                }
+            "}
+        );
+    });
+
+    // Undoing the resolution brings the conflict back.
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    cx.executor()
+        .advance_clock(crate::merge_conflicts::MERGE_CONFLICTS_DEBOUNCE_TIMEOUT);
+    cx.run_until_parked();
+    cx.update_editor(|editor, _| assert_eq!(editor.merge_conflicts.len(), 1));
+}
+
 #[gpui::test]
 fn test_move_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -10800,6 +11382,68 @@ async fn test_toggle_hunk_diff(executor: BackgroundExecutor, cx: &mut gpui::Test
     });
 }
 
+#[gpui::test]
+async fn test_stage_and_unstage_hunks(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let committed_text = "one\ntwo\nthree\nfour\n".to_string();
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "main.rs": "one\nTWO\nthree\nfour\n",
+        }),
+    )
+    .await;
+    fs.set_committed_contents_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("main.rs"), committed_text.clone())],
+    );
+    fs.set_index_for_repo(
+        Path::new("/dir/.git"),
+        &[(Path::new("main.rs"), committed_text.clone())],
+    );
+    let index_text = |fs: &FakeFs| {
+        let mut text = None;
+        fs.with_git_state(Path::new("/dir/.git"), false, |state| {
+            text = state.index_contents.get(Path::new("main.rs")).cloned();
+        });
+        text
+    };
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) =
+        cx.add_window_view(|cx| build_editor_with_project(project.clone(), buffer, cx));
+    editor.update(cx, |editor, cx| {
+        editor.edit([(Point::new(3, 0)..Point::new(3, 4), "FOUR")], cx);
+        editor.select_all(&SelectAll, cx);
+    });
+    cx.executor().run_until_parked();
+
+    // Only the saved contents of the hunks are staged.
+    editor.update(cx, |editor, cx| {
+        editor.stage_selected_hunks(&StageSelectedHunks, cx)
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(index_text(&fs).as_deref(), Some("one\nTWO\nthree\nfour\n"));
+
+    editor.update(cx, |editor, cx| {
+        editor.unstage_selected_hunks(&UnstageSelectedHunks, cx)
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(index_text(&fs), Some(committed_text));
+}
+
 #[gpui::test]
 async fn test_toggled_diff_base_change(
     executor: BackgroundExecutor,
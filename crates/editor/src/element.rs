@@ -4,8 +4,8 @@ use crate::{
         Block, BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ScrollBeyondLastLine,
-        ShowScrollbar,
+        CurrentLineHighlight, DoubleClickInMultibuffer, Minimap, MinimapSide, MultiCursorModifier,
+        ScrollBeyondLastLine, ShowMinimap, ShowScrollbar,
     },
     git::{
         blame::{CommitDetails, GitBlame},
@@ -19,7 +19,7 @@ use crate::{
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MenuPosition, MouseContextMenu},
     scroll::scroll_amount::ScrollAmount,
-    BlockId, CodeActionsMenu, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
+    sticky_scroll, BlockId, CodeActionsMenu, CursorShape, CustomBlockId, DisplayPoint, DisplayRow,
     DocumentHighlightRead, DocumentHighlightWrite, Editor, EditorMode, EditorSettings,
     EditorSnapshot, EditorStyle, ExpandExcerpts, FocusedBlock, GutterDimensions, HalfPageDown,
     HalfPageUp, HoveredCursor, HoveredHunk, LineDown, LineUp, OpenExcerpts, PageDown, PageUp,
@@ -37,8 +37,8 @@ use gpui::{
     EntityId, FontId, GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Length,
     ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad,
     ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size,
-    StatefulInteractiveElement, Style, Styled, TextRun, TextStyle, TextStyleRefinement, View,
-    ViewContext, WeakView, WindowContext,
+    StatefulInteractiveElement, Style, Styled, StyledText, TextRun, TextStyle, TextStyleRefinement,
+    View, ViewContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
 use language::language_settings::{
@@ -317,11 +317,14 @@ impl EditorElement {
                 .detach_and_log_err(cx);
         });
         register_action(view, cx, Editor::open_url);
+        register_action(view, cx, Editor::pick_color);
         register_action(view, cx, Editor::fold);
         register_action(view, cx, Editor::fold_at);
         register_action(view, cx, Editor::unfold_lines);
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
+        register_action(view, cx, Editor::fold_all_comments);
+        register_action(view, cx, Editor::fold_all_imports);
         register_action(view, cx, Editor::show_completions);
         register_action(view, cx, Editor::toggle_code_actions);
         register_action(view, cx, Editor::open_excerpts);
@@ -331,6 +334,7 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_indent_guides);
         register_action(view, cx, Editor::toggle_inlay_hints);
+        register_action(view, cx, Editor::toggle_breakpoint);
         register_action(view, cx, hover_popover::hover);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
@@ -401,6 +405,11 @@ impl EditorElement {
         register_action(view, cx, Editor::accept_partial_inline_completion);
         register_action(view, cx, Editor::accept_inline_completion);
         register_action(view, cx, Editor::revert_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_hunks);
+        register_action(view, cx, Editor::unstage_selected_hunks);
+        register_action(view, cx, Editor::accept_ours);
+        register_action(view, cx, Editor::accept_theirs);
+        register_action(view, cx, Editor::accept_both);
         register_action(view, cx, Editor::open_active_item_in_terminal)
     }
 
@@ -511,6 +520,13 @@ impl EditorElement {
 
         let point_for_position =
             position_map.point_for_position(text_hitbox.bounds, event.position);
+        if click_count == 1
+            && !modifiers.modified()
+            && editor.click_color_swatch(&point_for_position, event.position, cx)
+        {
+            cx.stop_propagation();
+            return;
+        }
         let position = point_for_position.previous_valid;
         if modifiers.shift && modifiers.alt {
             editor.select(
@@ -1170,6 +1186,234 @@ impl EditorElement {
         })
     }
 
+    /// Lays out a scaled-down view of the rows around the viewport, along with the selections,
+    /// highlights, diagnostics and git hunks in them.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        rows_per_page: f32,
+        max_scroll_top: f32,
+        line_height: Pixels,
+        em_advance: Pixels,
+        cx: &mut WindowContext,
+    ) -> MinimapLayout {
+        let settings = EditorSettings::get_global(cx).minimap;
+
+        // If a drag took place after we started dragging the minimap,
+        // cancel the minimap drag.
+        if cx.has_active_drag() {
+            self.editor.update(cx, |editor, cx| {
+                editor.scroll_manager.set_is_dragging_minimap(false, cx);
+            });
+        }
+
+        let row_height = line_height * MinimapLayout::SCALE;
+        let column_width = em_advance * MinimapLayout::SCALE;
+        let max_row = snapshot.max_point().row();
+
+        let (first_row, scroll_row_height) = minimap_scroll(
+            scroll_position.y,
+            max_scroll_top,
+            max_row.next_row().as_f32(),
+            bounds.size.height / row_height,
+            row_height,
+        );
+
+        let start_row = DisplayRow(first_row as u32);
+        let end_row = DisplayRow(cmp::min(
+            (first_row + bounds.size.height / row_height).ceil() as u32,
+            max_row.next_row().0,
+        ));
+        let row_bounds = |row: DisplayRow, columns: Range<u32>| {
+            let top = bounds.top() + (row.as_f32() - first_row) * row_height;
+            Bounds::from_corners(
+                point(
+                    bounds.left() + (columns.start as f32 * column_width).min(bounds.size.width),
+                    top,
+                ),
+                point(
+                    bounds.left() + (columns.end as f32 * column_width).min(bounds.size.width),
+                    top + row_height,
+                ),
+            )
+        };
+        let push_range_quads =
+            |quads: &mut Vec<PaintQuad>, range: Range<DisplayPoint>, color: Hsla| {
+                if range.is_empty() {
+                    if (start_row..end_row).contains(&range.start.row()) {
+                        quads.push(fill(row_bounds(range.start.row(), 0..u32::MAX), color));
+                    }
+                    return;
+                }
+                let rows = cmp::max(range.start.row(), start_row)
+                    ..cmp::min(range.end.row().next_row(), end_row);
+                for row in rows.iter_rows() {
+                    let start_column = if row == range.start.row() {
+                        range.start.column()
+                    } else {
+                        0
+                    };
+                    let end_column = if row == range.end.row() {
+                        range.end.column()
+                    } else {
+                        u32::MAX
+                    };
+                    if start_column < end_column {
+                        quads.push(fill(row_bounds(row, start_column..end_column), color));
+                    }
+                }
+            };
+
+        // Every word is drawn as a block of its syntax color.
+        let mut words: Vec<(DisplayRow, Range<u32>, Hsla)> = Vec::new();
+        let mut row = start_row;
+        let mut column = 0;
+        for chunk in snapshot.highlighted_chunks(start_row..end_row, true, &self.style) {
+            let color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(self.style.text.color);
+            for (ix, line) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    row.0 += 1;
+                    column = 0;
+                }
+                let mut word_start = None;
+                for (offset, ch) in line.char_indices().chain([(line.len(), ' ')]) {
+                    let offset = column + offset as u32;
+                    match (ch.is_whitespace(), word_start) {
+                        (true, Some(start)) => {
+                            word_start = None;
+                            // Adjacent chunks of the same color are drawn as one block.
+                            match words.last_mut() {
+                                Some(last)
+                                    if last.0 == row && last.1.end == start && last.2 == color =>
+                                {
+                                    last.1.end = offset;
+                                }
+                                _ => words.push((row, start..offset, color)),
+                            }
+                        }
+                        (false, None) => word_start = Some(offset),
+                        _ => {}
+                    }
+                }
+                column += line.len() as u32;
+            }
+        }
+        let text_quads = words
+            .into_iter()
+            .map(|(row, columns, color)| {
+                let mut bounds = row_bounds(row, columns);
+                bounds.size.height = row_height * 0.75;
+                fill(bounds, color)
+            })
+            .collect();
+
+        let start_point = DisplayPoint::new(start_row, 0).to_point(&snapshot.display_snapshot);
+        let end_point = if end_row > max_row {
+            snapshot.buffer_snapshot.max_point()
+        } else {
+            DisplayPoint::new(end_row, 0).to_point(&snapshot.display_snapshot)
+        };
+        let theme = cx.theme().clone();
+        let mut overlay_quads = Vec::new();
+
+        for hunk in snapshot.buffer_snapshot.git_diff_hunks_in_range(
+            MultiBufferRow(start_point.row)..MultiBufferRow(end_point.row + 1),
+        ) {
+            let start_display_row = MultiBufferPoint::new(hunk.associated_range.start.0, 0)
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            let mut end_display_row = MultiBufferPoint::new(hunk.associated_range.end.0, 0)
+                .to_display_point(&snapshot.display_snapshot)
+                .row();
+            if end_display_row != start_display_row {
+                end_display_row.0 -= 1;
+            }
+            let color = match hunk_status(&hunk) {
+                DiffHunkStatus::Added => theme.status().created,
+                DiffHunkStatus::Modified => theme.status().modified,
+                DiffHunkStatus::Removed => theme.status().deleted,
+            };
+            let top = row_bounds(start_display_row, 0..0).top();
+            let bottom = row_bounds(end_display_row, 0..0).bottom();
+            overlay_quads.push(fill(
+                Bounds::from_corners(
+                    point(bounds.left(), top),
+                    point(bounds.left() + MinimapLayout::GIT_MARKER_WIDTH, bottom),
+                ),
+                color,
+            ));
+        }
+
+        let start_anchor = snapshot.buffer_snapshot.anchor_before(start_point);
+        let end_anchor = snapshot.buffer_snapshot.anchor_after(end_point);
+        let editor = self.editor.read(cx);
+        for (range, color) in editor.background_highlights_in_range(
+            start_anchor..end_anchor,
+            &snapshot.display_snapshot,
+            theme.colors(),
+        ) {
+            push_range_quads(&mut overlay_quads, range, color);
+        }
+
+        let diagnostics = snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<_, Point>(start_point..end_point, false)
+            // Paint the most severe diagnostics last.
+            .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+        for diagnostic in diagnostics {
+            let mut color = match diagnostic.diagnostic.severity {
+                DiagnosticSeverity::ERROR => theme.status().error,
+                DiagnosticSeverity::WARNING => theme.status().warning,
+                DiagnosticSeverity::INFORMATION => theme.status().info,
+                _ => theme.status().hint,
+            };
+            color.fade_out(0.5);
+            let start = diagnostic
+                .range
+                .start
+                .to_display_point(&snapshot.display_snapshot);
+            let end = diagnostic
+                .range
+                .end
+                .to_display_point(&snapshot.display_snapshot);
+            push_range_quads(&mut overlay_quads, start..end, color);
+        }
+
+        let selection_color = theme.players().local().selection;
+        for selection in editor.selections.disjoint_anchors().iter() {
+            let range = selection.start.to_display_point(snapshot)
+                ..selection.end.to_display_point(snapshot);
+            push_range_quads(&mut overlay_quads, range, selection_color);
+        }
+
+        let viewport_bounds = Bounds {
+            origin: point(
+                bounds.left(),
+                bounds.top() + (scroll_position.y - first_row) * row_height,
+            ),
+            size: size(bounds.size.width, rows_per_page * row_height),
+        };
+
+        MinimapLayout {
+            hitbox: cx.insert_hitbox(bounds, true),
+            side: settings.side,
+            show_on_hover: settings.show == ShowMinimap::Hover,
+            first_row,
+            row_height,
+            scroll_row_height,
+            rows_per_page,
+            text_quads,
+            overlay_quads,
+            viewport_bounds,
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_gutter_fold_toggles(
         &self,
@@ -1562,6 +1806,7 @@ impl EditorElement {
         gutter_dimensions: &GutterDimensions,
         gutter_hitbox: &Hitbox,
         rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        breakpoint_rows: &[DisplayRow],
         snapshot: &EditorSnapshot,
         cx: &mut WindowContext,
     ) -> Vec<AnyElement> {
@@ -1591,6 +1836,9 @@ impl EditorElement {
                         return None;
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    if breakpoint_rows.contains(&display_row) {
+                        return None;
+                    }
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
@@ -1614,6 +1862,39 @@ impl EditorElement {
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
+    fn layout_breakpoint_indicators(
+        &self,
+        breakpoint_rows: &[DisplayRow],
+        visible_rows: Range<DisplayRow>,
+        line_height: Pixels,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        rows_with_hunk_bounds: &HashMap<DisplayRow, Bounds<Pixels>>,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            breakpoint_rows
+                .iter()
+                .filter(|row| visible_rows.contains(row))
+                .map(|row| {
+                    let button = editor.render_breakpoint_indicator(*row, cx);
+                    prepaint_gutter_button(
+                        button,
+                        *row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        rows_with_hunk_bounds,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_code_actions_indicator(
         &self,
@@ -2581,6 +2862,72 @@ impl EditorElement {
         }
     }
 
+    /// Pins the scopes enclosing the first visible rows to the top of the editor, each of them
+    /// indented like its first line.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        start_row: DisplayRow,
+        hitbox: &Hitbox,
+        text_hitbox: &Hitbox,
+        gutter_dimensions: &GutterDimensions,
+        line_height: Pixels,
+        em_advance: Pixels,
+        cx: &mut WindowContext,
+    ) -> Option<AnyElement> {
+        if snapshot.mode != EditorMode::Full {
+            return None;
+        }
+        let settings = EditorSettings::get_global(cx).sticky_scroll;
+        if !settings.enabled {
+            return None;
+        }
+        let headers = sticky_scroll::sticky_headers(
+            snapshot,
+            start_row,
+            settings.max_depth,
+            Some(&self.style.syntax),
+        );
+        if headers.is_empty() {
+            return None;
+        }
+
+        let text_style = self.style.text.clone();
+        let hover_background = cx.theme().colors().editor_active_line_background;
+        let mut element = v_flex()
+            .id("sticky-headers")
+            .occlude()
+            .w(text_hitbox.bounds.right() - hitbox.origin.x - EditorElement::SCROLLBAR_WIDTH)
+            .bg(cx.theme().colors().editor_background)
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .font(text_style.font())
+            .children(headers.into_iter().enumerate().map(|(ix, header)| {
+                let position = header.range.start;
+                let indent = position.to_display_point(snapshot).column() as f32 * em_advance;
+                let editor = self.editor.clone();
+                h_flex()
+                    .id(("sticky-header", ix))
+                    .h(line_height)
+                    .pl(text_hitbox.origin.x - hitbox.origin.x + gutter_dimensions.margin + indent)
+                    .cursor_pointer()
+                    .hover(move |style| style.bg(hover_background))
+                    .child(
+                        StyledText::new(header.text)
+                            .with_highlights(&text_style, header.highlight_ranges),
+                    )
+                    .on_click(move |_, cx| {
+                        editor.update(cx, |editor, cx| {
+                            editor.jump_to_sticky_header(position, cx);
+                        });
+                    })
+            }))
+            .into_any();
+        element.prepaint_as_root(hitbox.origin, AvailableSpace::min_size(), cx);
+        Some(element)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_context_menu(
         &self,
@@ -3279,6 +3626,9 @@ impl EditorElement {
             for test_indicator in layout.test_indicators.iter_mut() {
                 test_indicator.paint(cx);
             }
+            for breakpoint_indicator in layout.breakpoint_indicators.iter_mut() {
+                breakpoint_indicator.paint(cx);
+            }
             for close_indicator in layout.close_indicators.iter_mut() {
                 close_indicator.paint(cx);
             }
@@ -3621,6 +3971,133 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let Some(minimap_layout) = layout.minimap_layout.as_ref() else {
+            return;
+        };
+
+        let is_dragging = self.editor.read(cx).scroll_manager.is_dragging_minimap();
+        let visible =
+            !minimap_layout.show_on_hover || is_dragging || minimap_layout.hitbox.is_hovered(cx);
+        if visible {
+            let colors = cx.theme().colors();
+            let (background, border, viewport) = (
+                colors.editor_background,
+                colors.border_variant,
+                colors.scrollbar_thumb_background,
+            );
+            cx.paint_layer(minimap_layout.hitbox.bounds, |cx| {
+                let border_widths = match minimap_layout.side {
+                    MinimapSide::Left => Edges {
+                        right: MinimapLayout::BORDER_WIDTH,
+                        ..Default::default()
+                    },
+                    MinimapSide::Right => Edges {
+                        left: MinimapLayout::BORDER_WIDTH,
+                        ..Default::default()
+                    },
+                };
+                cx.paint_quad(quad(
+                    minimap_layout.hitbox.bounds,
+                    Corners::default(),
+                    background,
+                    border_widths,
+                    border,
+                ));
+                for text_quad in &minimap_layout.text_quads {
+                    cx.paint_quad(text_quad.clone());
+                }
+                for overlay_quad in &minimap_layout.overlay_quads {
+                    cx.paint_quad(overlay_quad.clone());
+                }
+                cx.paint_quad(fill(minimap_layout.viewport_bounds, viewport));
+            });
+        }
+
+        cx.set_cursor_style(CursorStyle::Arrow, &minimap_layout.hitbox);
+
+        let show_on_hover = minimap_layout.show_on_hover;
+        let scroll_row_height = minimap_layout.scroll_row_height;
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = minimap_layout.hitbox.clone();
+            let mut mouse_position = cx.mouse_position();
+            move |event: &MouseMoveEvent, phase, cx| {
+                if phase == DispatchPhase::Capture {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    if event.pressed_button == Some(MouseButton::Left)
+                        && editor.scroll_manager.is_dragging_minimap()
+                    {
+                        let mut position = editor.scroll_position(cx);
+                        position.y += (event.position.y - mouse_position.y) / scroll_row_height;
+                        if position.y < 0.0 {
+                            position.y = 0.0;
+                        }
+                        editor.set_scroll_position(position, cx);
+                        cx.stop_propagation();
+                    } else {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        if show_on_hover && visible != hitbox.is_hovered(cx) {
+                            cx.notify();
+                        }
+                    }
+                    mouse_position = event.position;
+                })
+            }
+        });
+
+        if is_dragging {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        } else {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                let hitbox = minimap_layout.hitbox.clone();
+                let viewport_bounds = minimap_layout.viewport_bounds;
+                let first_row = minimap_layout.first_row;
+                let row_height = minimap_layout.row_height;
+                let rows_per_page = minimap_layout.rows_per_page;
+                move |event: &MouseDownEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture
+                        || event.button != MouseButton::Left
+                        || !hitbox.is_hovered(cx)
+                    {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(true, cx);
+
+                        // Clicking outside of the viewport centers it on the clicked row.
+                        let y = event.position.y;
+                        if y < viewport_bounds.top() || viewport_bounds.bottom() < y {
+                            let center_row = first_row + (y - hitbox.top()) / row_height;
+                            let mut position = editor.scroll_position(cx);
+                            position.y = (center_row - rows_per_page / 2.).max(0.);
+                            editor.set_scroll_position(position, cx);
+                        }
+
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -3873,6 +4350,12 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_headers(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if let Some(sticky_headers) = layout.sticky_headers.as_mut() {
+            sticky_headers.paint(cx);
+        }
+    }
+
     fn paint_mouse_context_menu(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         if let Some(mouse_context_menu) = layout.mouse_context_menu.as_mut() {
             mouse_context_menu.paint(cx);
@@ -4945,7 +5428,13 @@ impl Element for EditorElement {
                         self.max_line_number_width(&snapshot, cx),
                         cx,
                     );
-                    let text_width = bounds.size.width - gutter_dimensions.width;
+                    let (text_bounds, minimap_bounds) = text_and_minimap_bounds(
+                        bounds,
+                        gutter_dimensions,
+                        EditorSettings::get_global(cx).minimap,
+                        snapshot.mode,
+                    );
+                    let text_width = text_bounds.size.width;
 
                     let right_margin = if snapshot.mode == EditorMode::Full {
                         EditorElement::SCROLLBAR_WIDTH
@@ -4994,13 +5483,7 @@ impl Element for EditorElement {
                     let hitbox = cx.insert_hitbox(bounds, false);
                     let gutter_hitbox =
                         cx.insert_hitbox(gutter_bounds(bounds, gutter_dimensions), false);
-                    let text_hitbox = cx.insert_hitbox(
-                        Bounds {
-                            origin: gutter_hitbox.upper_right(),
-                            size: size(text_width, bounds.size.height),
-                        },
-                        false,
-                    );
+                    let text_hitbox = cx.insert_hitbox(text_bounds, false);
                     // Offset the content_bounds from the text_bounds by the gutter margin (which
                     // is roughly half a character wide) to make hit testing work more like how we want.
                     let content_origin =
@@ -5306,6 +5789,19 @@ impl Element for EditorElement {
                         );
                     });
 
+                    let sticky_headers = cx.with_element_namespace("sticky_headers", |cx| {
+                        self.layout_sticky_headers(
+                            &snapshot,
+                            start_row,
+                            &hitbox,
+                            &text_hitbox,
+                            &gutter_dimensions,
+                            line_height,
+                            em_advance,
+                            cx,
+                        )
+                    });
+
                     let cursors = self.collect_cursors(&snapshot, cx);
                     let visible_row_range = start_row..end_row;
                     let non_visible_cursors = cursors
@@ -5327,6 +5823,19 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap_layout = minimap_bounds.map(|minimap_bounds| {
+                        self.layout_minimap(
+                            &snapshot,
+                            minimap_bounds,
+                            scroll_position,
+                            height_in_lines,
+                            max_scroll_top,
+                            line_height,
+                            em_advance,
+                            cx,
+                        )
+                    });
+
                     let scrollbar_layout = self.layout_scrollbar(
                         &snapshot,
                         bounds,
@@ -5375,6 +5884,8 @@ impl Element for EditorElement {
                                 rows_with_hunk_bounds
                             },
                         );
+                    let breakpoint_rows =
+                        self.editor.read(cx).breakpoint_display_rows(&snapshot, cx);
                     let mut _context_menu_visible = false;
                     let mut code_actions_indicator = None;
                     if let Some(newest_selection_head) = newest_selection_head {
@@ -5402,6 +5913,7 @@ impl Element for EditorElement {
                                     newest_selection_point.to_display_point(&snapshot).row();
                                 if !expanded_add_hunks_by_rows
                                     .contains_key(&newest_selection_display_row)
+                                    && !breakpoint_rows.contains(&newest_selection_display_row)
                                 {
                                     let buffer = snapshot.buffer_snapshot.buffer_line_for_row(
                                         MultiBufferRow(newest_selection_point.row),
@@ -5440,6 +5952,7 @@ impl Element for EditorElement {
                             &gutter_dimensions,
                             &gutter_hitbox,
                             &rows_with_hunk_bounds,
+                            &breakpoint_rows,
                             &snapshot,
                             cx,
                         )
@@ -5447,6 +5960,17 @@ impl Element for EditorElement {
                         Vec::new()
                     };
 
+                    let breakpoint_indicators = self.layout_breakpoint_indicators(
+                        &breakpoint_rows,
+                        start_row..end_row,
+                        line_height,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        &rows_with_hunk_bounds,
+                        cx,
+                    );
+
                     let close_indicators = self.layout_hunk_diff_close_indicators(
                         line_height,
                         scroll_pixel_position,
@@ -5553,6 +6077,7 @@ impl Element for EditorElement {
                         display_hunks,
                         content_origin,
                         scrollbar_layout,
+                        minimap_layout,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -5563,11 +6088,13 @@ impl Element for EditorElement {
                         blamed_display_rows,
                         inline_blame,
                         blocks,
+                        sticky_headers,
                         cursors,
                         visible_cursors,
                         selections,
                         mouse_context_menu,
                         test_indicators,
+                        breakpoint_indicators,
                         close_indicators,
                         code_actions_indicator,
                         gutter_fold_toggles,
@@ -5656,6 +6183,11 @@ impl Element for EditorElement {
                         });
                     }
 
+                    cx.with_element_namespace("sticky_headers", |cx| {
+                        self.paint_sticky_headers(layout, cx);
+                    });
+
+                    self.paint_minimap(layout, cx);
                     self.paint_scrollbar(layout, cx);
                     self.paint_mouse_context_menu(layout, cx);
                 });
@@ -5664,6 +6196,75 @@ impl Element for EditorElement {
     }
 }
 
+/// Splits the editor's width right of the gutter between the text and the minimap, returning the
+/// bounds of the text along with those of the minimap, if one is shown.
+pub(super) fn text_and_minimap_bounds(
+    editor_bounds: Bounds<Pixels>,
+    gutter_dimensions: GutterDimensions,
+    minimap: Minimap,
+    mode: EditorMode,
+) -> (Bounds<Pixels>, Option<Bounds<Pixels>>) {
+    let gutter_right = editor_bounds.left() + gutter_dimensions.width;
+    let minimap_width = if mode == EditorMode::Full && minimap.show != ShowMinimap::Never {
+        px(minimap.width.max(0.)).min((editor_bounds.size.width - gutter_dimensions.width) / 2.)
+    } else {
+        Pixels::ZERO
+    };
+    // A minimap shown on hover is painted over the text, rather than next to it.
+    let reserved_minimap_width = if minimap.show == ShowMinimap::Always {
+        minimap_width
+    } else {
+        Pixels::ZERO
+    };
+
+    let (text_left, minimap_left) = match minimap.side {
+        MinimapSide::Left => (gutter_right + reserved_minimap_width, gutter_right),
+        MinimapSide::Right => (gutter_right, editor_bounds.right() - minimap_width),
+    };
+    let text_bounds = Bounds {
+        origin: point(text_left, editor_bounds.top()),
+        size: size(
+            editor_bounds.size.width - gutter_dimensions.width - reserved_minimap_width,
+            editor_bounds.size.height,
+        ),
+    };
+    let minimap_bounds = (minimap_width > Pixels::ZERO).then(|| Bounds {
+        origin: point(minimap_left, editor_bounds.top()),
+        size: size(minimap_width, editor_bounds.size.height),
+    });
+    (text_bounds, minimap_bounds)
+}
+
+/// Returns the fractional row shown at the top of the minimap for the editor's scroll position,
+/// along with how far the minimap's viewport moves when the editor is scrolled by one row.
+///
+/// When the whole buffer doesn't fit, the minimap scrolls along with the editor, so that its top
+/// and bottom are shown when the editor is scrolled to the top or the bottom.
+pub(super) fn minimap_scroll(
+    scroll_top: f32,
+    max_scroll_top: f32,
+    row_count: f32,
+    minimap_row_count: f32,
+    row_height: Pixels,
+) -> (f32, Pixels) {
+    let scroll_range = (row_count - minimap_row_count).max(0.);
+    let first_row_for_scroll_top = |scroll_top: f32| {
+        if max_scroll_top > 0. {
+            (scroll_top / max_scroll_top).min(1.) * scroll_range
+        } else {
+            0.
+        }
+    };
+    let first_row = first_row_for_scroll_top(scroll_top);
+    let scroll_row_height = if max_scroll_top > 0. {
+        row_height * (max_scroll_top - first_row_for_scroll_top(max_scroll_top)) / max_scroll_top
+    } else {
+        row_height
+    }
+    .max(px(1.));
+    (first_row, scroll_row_height)
+}
+
 pub(super) fn gutter_bounds(
     editor_bounds: Bounds<Pixels>,
     gutter_dimensions: GutterDimensions,
@@ -5690,6 +6291,7 @@ pub struct EditorLayout {
     gutter_dimensions: GutterDimensions,
     content_origin: gpui::Point<Pixels>,
     scrollbar_layout: Option<ScrollbarLayout>,
+    minimap_layout: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -5702,6 +6304,7 @@ pub struct EditorLayout {
     blamed_display_rows: Option<Vec<AnyElement>>,
     inline_blame: Option<AnyElement>,
     blocks: Vec<BlockLayout>,
+    sticky_headers: Option<AnyElement>,
     highlighted_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     highlighted_gutter_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     redacted_ranges: Vec<Range<DisplayPoint>>,
@@ -5710,6 +6313,7 @@ pub struct EditorLayout {
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    breakpoint_indicators: Vec<AnyElement>,
     close_indicators: Vec<AnyElement>,
     gutter_fold_toggles: Vec<Option<AnyElement>>,
     crease_trailers: Vec<Option<CreaseTrailerLayout>>,
@@ -5739,6 +6343,28 @@ struct ScrollbarLayout {
     thumb_height: Pixels,
 }
 
+/// A scaled-down view of the rows around the viewport, scrolled along with the editor.
+struct MinimapLayout {
+    hitbox: Hitbox,
+    side: MinimapSide,
+    show_on_hover: bool,
+    /// The fractional display row shown at the top of the minimap.
+    first_row: f32,
+    row_height: Pixels,
+    /// How far the viewport moves in the minimap when the editor is scrolled by one row.
+    scroll_row_height: Pixels,
+    rows_per_page: f32,
+    text_quads: Vec<PaintQuad>,
+    overlay_quads: Vec<PaintQuad>,
+    viewport_bounds: Bounds<Pixels>,
+}
+
+impl MinimapLayout {
+    const SCALE: f32 = 0.15;
+    const BORDER_WIDTH: Pixels = px(1.0);
+    const GIT_MARKER_WIDTH: Pixels = px(2.0);
+}
+
 impl ScrollbarLayout {
     const BORDER_WIDTH: Pixels = px(1.0);
     const LINE_MARKER_HEIGHT: Pixels = px(2.0);
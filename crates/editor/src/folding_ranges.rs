@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use collections::HashSet;
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{
+    display_map::{LspFoldingRange, LspFoldingRanges},
+    Editor, EditorMode,
+};
+
+const FOLDING_RANGES_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Requests the folding ranges of the editor's buffers from their language servers.
+///
+/// Buffers whose language server does not provide folding ranges keep using the
+/// indentation based ones.
+pub(super) fn refresh_folding_ranges(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+    let buffers = editor.buffer.read(cx).all_buffers();
+
+    editor.folding_ranges_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(FOLDING_RANGES_DEBOUNCE_TIMEOUT)
+            .await;
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let task = project.folding_ranges(&buffer, cx);
+                        async move { (buffer, task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let results = futures::future::join_all(tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multi_buffer = editor.buffer.read(cx);
+                let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+                let mut covered_buffers = HashSet::default();
+                let mut folding_ranges = Vec::new();
+                for (buffer, ranges) in results {
+                    let Some(ranges) = ranges.log_err().flatten() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    covered_buffers.insert(buffer_snapshot.remote_id());
+
+                    let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+                    for folding_range in ranges {
+                        let range = folding_range.range;
+                        for (excerpt_id, excerpt_range) in &excerpts {
+                            let context = &excerpt_range.context;
+                            if context.start.cmp(&range.start, &buffer_snapshot).is_gt()
+                                || context.end.cmp(&range.end, &buffer_snapshot).is_lt()
+                            {
+                                continue;
+                            }
+                            if let Some((start, end)) = multi_buffer_snapshot
+                                .anchor_in_excerpt(*excerpt_id, range.start)
+                                .zip(
+                                    multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, range.end),
+                                )
+                            {
+                                folding_ranges.push(LspFoldingRange {
+                                    range: start..end,
+                                    kind: folding_range.kind.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+
+                let folding_ranges =
+                    LspFoldingRanges::new(covered_buffers, folding_ranges, &multi_buffer_snapshot);
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_lsp_folding_ranges(folding_ranges)
+                });
+                cx.notify();
+            })
+            .log_err();
+    }));
+
+    None
+}
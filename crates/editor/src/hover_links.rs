@@ -5,12 +5,12 @@ use crate::{
     PointForPosition, SelectPhase,
 };
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
-use language::{Bias, ToOffset};
+use language::{Bias, Location, PointUtf16, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
-    HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip, LocationLink,
-    ResolveState,
+    DocumentLink, HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip,
+    LocationLink, Project, ResolveState,
 };
 use std::ops::Range;
 use theme::ActiveTheme as _;
@@ -65,6 +65,8 @@ pub enum HoverLink {
     Url(String),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
+    /// A link reported by a language server, whose target is resolved once it is followed.
+    DocumentLink(Model<language::Buffer>, DocumentLink),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -109,6 +111,50 @@ pub fn exclude_link_to_position(
 }
 
 impl Editor {
+    /// Resolves the target of a document link, opening the buffer it points to, or the URL
+    /// when it is not a file.
+    pub(crate) fn compute_document_link_target(
+        &self,
+        buffer: Model<language::Buffer>,
+        link: DocumentLink,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<Option<Location>>> {
+        let Some(project) = self.project.clone() else {
+            return Task::ready(Ok(None));
+        };
+
+        cx.spawn(move |_, mut cx| async move {
+            let link = project
+                .update(&mut cx, |project, cx| {
+                    project.resolve_document_link(buffer, link, cx)
+                })?
+                .await?;
+            let Some(target) = link.lsp_link.target else {
+                return Ok(None);
+            };
+            if target.scheme() != "file" {
+                cx.update(|cx| cx.open_url(target.as_str()))?;
+                return Ok(None);
+            }
+
+            let path = target
+                .to_file_path()
+                .map_err(|()| anyhow::anyhow!("invalid document link target {target}"))?;
+            let target_buffer = project
+                .update(&mut cx, |project, cx| project.open_local_buffer(path, cx))?
+                .await?;
+            let target_point = document_link_target_point(&target).unwrap_or_default();
+            let target_anchor = target_buffer.update(&mut cx, |target_buffer, _| {
+                let target_point = target_buffer.clip_point_utf16(target_point, Bias::Left);
+                target_buffer.anchor_before(target_point)
+            })?;
+            Ok(Some(Location {
+                buffer: target_buffer,
+                range: target_anchor..target_anchor,
+            }))
+        })
+    }
+
     pub(crate) fn update_hovered_link(
         &mut self,
         point_for_position: PointForPosition,
@@ -486,7 +532,7 @@ pub fn show_link_definition(
         || hovered_link_state
             .links
             .first()
-            .is_some_and(|d| matches!(d, HoverLink::Url(_)));
+            .is_some_and(|d| matches!(d, HoverLink::Url(_) | HoverLink::DocumentLink(..)));
 
     if same_kind {
         if is_cached && (&hovered_link_state.last_trigger_point == &trigger_point)
@@ -521,6 +567,16 @@ pub fn show_link_definition(
                             (range, vec![HoverLink::Url(url)])
                         })
                         .ok()
+                    } else if let Some((link_range, link)) =
+                        find_document_link(project.as_ref(), &buffer, buffer_position, &mut cx)
+                            .await
+                    {
+                        let range = maybe!({
+                            let start = snapshot.anchor_in_excerpt(excerpt_id, link_range.start)?;
+                            let end = snapshot.anchor_in_excerpt(excerpt_id, link_range.end)?;
+                            Some(RangeInEditor::Text(start..end))
+                        });
+                        Some((range, vec![link]))
                     } else if let Some(project) = project {
                         // query the LSP for definition info
                         project
@@ -624,6 +680,45 @@ pub fn show_link_definition(
     editor.hovered_link_state = Some(hovered_link_state);
 }
 
+/// Finds the link reported by the language server at the given position.
+async fn find_document_link(
+    project: Option<&Model<Project>>,
+    buffer: &Model<language::Buffer>,
+    position: text::Anchor,
+    cx: &mut AsyncWindowContext,
+) -> Option<(Range<text::Anchor>, HoverLink)> {
+    let links = project?
+        .update(cx, |project, cx| project.document_links(buffer, cx))
+        .ok()?
+        .await
+        .log_err()?;
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot()).ok()?;
+    let link = links.into_iter().find(|link| {
+        link.range.start.cmp(&position, &snapshot).is_le()
+            && link.range.end.cmp(&position, &snapshot).is_ge()
+    })?;
+    Some((
+        link.range.clone(),
+        HoverLink::DocumentLink(buffer.clone(), link),
+    ))
+}
+
+/// The position a link to a file points to, written in the fragment of its target as
+/// `L<line>` or `L<line>,<column>`, both being one-based.
+fn document_link_target_point(target: &lsp::Url) -> Option<PointUtf16> {
+    let position = target.fragment()?.strip_prefix('L')?;
+    let (row, column) = match position.split_once([',', ':']) {
+        Some((row, column)) => (row, Some(column)),
+        None => (position, None),
+    };
+    let row = row.parse::<u32>().ok()?.checked_sub(1)?;
+    let column = match column {
+        Some(column) => column.parse::<u32>().ok()?.saturating_sub(1),
+        None => 0,
+    };
+    Some(PointUtf16::new(row, column))
+}
+
 pub(crate) fn find_url(
     buffer: &Model<language::Buffer>,
     position: text::Anchor,
@@ -1188,6 +1283,82 @@ mod tests {
             "});
     }
 
+    #[gpui::test]
+    async fn test_document_links(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+
+        cx.set_state(indoc! {"
+            fn main() {
+                // see line 3ˇ
+                let a = 1;
+            }
+        "});
+        let link_range = cx.lsp_range(indoc! {"
+            fn main() {
+                // see «line 3»
+                let a = 1;
+            }
+        "});
+        let mut link_requests = cx.handle_request::<lsp::request::DocumentLinkRequest, _, _>(
+            move |_, _, _| async move {
+                Ok(Some(vec![lsp::DocumentLink {
+                    range: link_range,
+                    target: None,
+                    tooltip: None,
+                    data: Some(serde_json::json!(3)),
+                }]))
+            },
+        );
+        let mut resolve_requests = cx.handle_request::<lsp::request::DocumentLinkResolve, _, _>(
+            move |url, mut link, _| async move {
+                let mut target = url.clone();
+                target.set_fragment(Some("L3,9"));
+                link.target = Some(target);
+                Ok(link)
+            },
+        );
+
+        let screen_coord = cx.pixel_position(indoc! {"
+            fn main() {
+                // see liˇne 3
+                let a = 1;
+            }
+        "});
+        cx.simulate_mouse_move(screen_coord, None, Modifiers::secondary_key());
+        link_requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_text_highlights::<HoveredLinkState>(indoc! {"
+            fn main() {
+                // see «line 3ˇ»
+                let a = 1;
+            }
+        "});
+
+        // The target of the link is only resolved once it is followed, and points into the
+        // file at the position written in its fragment.
+        assert!(resolve_requests.try_next().is_err());
+        cx.simulate_click(screen_coord, Modifiers::secondary_key());
+        resolve_requests.next().await;
+        cx.run_until_parked();
+        cx.assert_editor_state(indoc! {"
+            fn main() {
+                // see line 3
+                let ˇa = 1;
+            }
+        "});
+    }
+
     #[gpui::test]
     async fn test_urls(cx: &mut gpui::TestAppContext) {
         init_test(cx, |_| {});
@@ -4,19 +4,23 @@ use std::{
 };
 
 use collections::{hash_map, HashMap, HashSet};
-use git::diff::{DiffHunk, DiffHunkStatus};
+use git::{
+    diff::{DiffHunk, DiffHunkStatus},
+    repository::RepoPath,
+};
 use gpui::{Action, AppContext, CursorStyle, Hsla, Model, MouseButton, Subscription, Task, View};
-use language::Buffer;
+use language::{Bias, Buffer, BufferSnapshot, Rope, ToOffset as _};
 use multi_buffer::{
     Anchor, AnchorRangeExt, ExcerptRange, MultiBuffer, MultiBufferRow, MultiBufferSnapshot, ToPoint,
 };
+use project::{GitOperation, ProjectEntryId, WorktreeId};
 use settings::SettingsStore;
 use text::{BufferId, Point};
 use ui::{
     div, h_flex, rems, v_flex, ActiveTheme, Context as _, ContextMenu, InteractiveElement,
     IntoElement, ParentElement, Pixels, Styled, ViewContext, VisualContext,
 };
-use util::{debug_panic, RangeExt};
+use util::{debug_panic, RangeExt, ResultExt};
 
 use crate::{
     editor_settings::CurrentLineHighlight,
@@ -25,9 +29,76 @@ use crate::{
     mouse_context_menu::MouseContextMenu,
     BlockDisposition, BlockProperties, BlockStyle, CustomBlockId, DiffRowHighlight, Editor,
     EditorElement, EditorSnapshot, ExpandAllHunkDiffs, RangeToAnchorExt, RevertSelectedHunks,
-    ToDisplayPoint, ToggleHunkDiff,
+    StageSelectedHunks, ToDisplayPoint, ToggleHunkDiff, UnstageSelectedHunks,
 };
 
+/// Translates an offset in the current text of a buffer to its saved text. Offsets within
+/// unsaved edits are moved to the start or the end of the saved text they replaced.
+fn saved_offset(unsaved_edits: &[text::Edit<usize>], offset: usize, bias: Bias) -> usize {
+    let mut delta = 0;
+    for edit in unsaved_edits {
+        if offset < edit.new.start || (offset == edit.new.start && bias == Bias::Right) {
+            break;
+        }
+        if offset < edit.new.end {
+            return match bias {
+                Bias::Left => edit.old.start,
+                Bias::Right => edit.old.end,
+            };
+        }
+        delta = edit.old.end as isize - edit.new.end as isize;
+    }
+    (offset as isize + delta) as usize
+}
+
+/// Maps rows of a buffer to the rows of its diff base, expanding them to the whole hunks they
+/// touch.
+fn diff_base_rows(buffer: &BufferSnapshot, diff_base: &Rope, rows: Range<u32>) -> Range<u32> {
+    let mut start = None;
+    let mut delta = 0_i64;
+    for hunk in buffer.git_diff_hunks_in_row_range(0..u32::MAX) {
+        let hunk_rows = hunk.associated_range.clone();
+        // Added lines have an empty range at the start of the diff base.
+        let base_rows = if hunk.diff_base_byte_range.is_empty() {
+            let base_start = (hunk_rows.start as i64 + delta) as u32;
+            base_start..base_start
+        } else {
+            let start = diff_base.offset_to_point(hunk.diff_base_byte_range.start);
+            let end = diff_base.offset_to_point(hunk.diff_base_byte_range.end);
+            start.row..end.row + (end.column > 0) as u32
+        };
+
+        if start.is_none() && rows.start < hunk_rows.end.max(hunk_rows.start + 1) {
+            start = Some(if rows.start < hunk_rows.start {
+                (rows.start as i64 + delta) as u32
+            } else {
+                base_rows.start
+            });
+        }
+        if rows.end <= hunk_rows.start {
+            return start.unwrap_or(base_rows.start)..(rows.end as i64 + delta) as u32;
+        }
+        if rows.end <= hunk_rows.end {
+            return start.unwrap_or(base_rows.start)..base_rows.end;
+        }
+        delta = base_rows.end as i64 - hunk_rows.end as i64;
+    }
+    let start = start.unwrap_or_else(|| (rows.start as i64 + delta) as u32);
+    start..(rows.end as i64 + delta) as u32
+}
+
+/// Returns the repository containing the given file, along with the file's path within it.
+fn repository_path(
+    file: &project::File,
+    cx: &AppContext,
+) -> Option<(WorktreeId, ProjectEntryId, RepoPath)> {
+    let worktree = file.worktree.read(cx);
+    let snapshot = worktree.snapshot();
+    let repo_entry = snapshot.repository_for_path(&file.path)?;
+    let repo_path = repo_entry.relativize(&snapshot, &file.path).log_err()?;
+    Some((worktree.id(), repo_entry.work_directory_id(), repo_path))
+}
+
 #[derive(Debug, Clone)]
 pub(super) struct HoveredHunk {
     pub multi_buffer_range: Range<Anchor>,
@@ -139,6 +210,20 @@ impl Editor {
                             }
                         }
                     })
+                    .entry("Stage Hunk", Some(StageSelectedHunks.boxed_clone()), {
+                        let editor = editor_handle.clone();
+                        let hunk = hovered_hunk.clone();
+                        move |cx| {
+                            editor.update(cx, |editor, cx| {
+                                let multi_buffer_snapshot = editor.buffer().read(cx).snapshot(cx);
+                                if let Some(hunk) =
+                                    crate::hunk_diff::to_diff_hunk(&hunk, &multi_buffer_snapshot)
+                                {
+                                    editor.stage_hunks(vec![hunk], cx);
+                                }
+                            });
+                        }
+                    })
                     .entry("Revert File", None, {
                         let editor = editor_handle.clone();
                         move |cx| {
@@ -171,6 +256,141 @@ impl Editor {
         )
     }
 
+    pub fn stage_selected_hunks(&mut self, _: &StageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let multi_buffer_snapshot = self.buffer().read(cx).snapshot(cx);
+        let hunks =
+            hunks_for_selections(&multi_buffer_snapshot, &self.selections.disjoint_anchors());
+        self.stage_hunks(hunks, cx);
+    }
+
+    /// Adds the saved contents of the given hunks to the git index, leaving the rest of each
+    /// file's unstaged changes as they are.
+    pub(super) fn stage_hunks(
+        &mut self,
+        hunks: Vec<DiffHunk<MultiBufferRow>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let mut hunks_by_buffer = HashMap::<BufferId, Vec<DiffHunk<MultiBufferRow>>>::default();
+        for hunk in hunks {
+            hunks_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push(hunk);
+        }
+
+        for (buffer_id, mut hunks) in hunks_by_buffer {
+            let Some(buffer) = self.buffer().read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            let buffer = buffer.read(cx);
+            let Some(file) = project::File::from_dyn(buffer.file()) else {
+                continue;
+            };
+            let Some(diff_base) = buffer.diff_base() else {
+                continue;
+            };
+
+            // Stage what is on disk, not the unsaved edits of the buffer.
+            let saved_version = buffer.saved_version();
+            let saved_text = buffer.rope_for_version(saved_version);
+            let unsaved_edits = buffer
+                .edits_since::<usize>(saved_version)
+                .collect::<Vec<_>>();
+
+            // Replace the hunks back to front, so that earlier diff base offsets stay valid.
+            hunks.sort_by_key(|hunk| hunk.diff_base_byte_range.start);
+            hunks.dedup_by_key(|hunk| hunk.diff_base_byte_range.clone());
+            let mut index_text = diff_base.to_string();
+            for hunk in hunks.iter().rev() {
+                let range = hunk.buffer_range.to_offset(buffer);
+                let saved_range = saved_offset(&unsaved_edits, range.start, Bias::Left)
+                    ..saved_offset(&unsaved_edits, range.end, Bias::Right);
+                let new_text = saved_text.chunks_in_range(saved_range).collect::<String>();
+                index_text.replace_range(hunk.diff_base_byte_range.clone(), &new_text);
+            }
+
+            let Some((worktree_id, work_directory_id, repo_path)) = repository_path(file, cx)
+            else {
+                continue;
+            };
+            project
+                .read(cx)
+                .run_git_operation(
+                    worktree_id,
+                    work_directory_id,
+                    GitOperation::SetIndexText {
+                        repo_path,
+                        text: Some(index_text),
+                    },
+                    cx,
+                )
+                .detach_and_log_err(cx);
+        }
+    }
+
+    pub fn unstage_selected_hunks(&mut self, _: &UnstageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let multi_buffer = self.buffer().read(cx);
+        let mut rows_by_buffer = HashMap::<BufferId, (Model<Buffer>, Vec<Range<u32>>)>::default();
+        for selection in self.selections.disjoint_anchors().iter() {
+            let range = selection.start..selection.end;
+            for (buffer, range, _) in multi_buffer.range_to_buffer_ranges(range, cx) {
+                let buffer_snapshot = buffer.read(cx).snapshot();
+                let start = buffer_snapshot.offset_to_point(range.start).row;
+                let end = buffer_snapshot.offset_to_point(range.end).row + 1;
+                rows_by_buffer
+                    .entry(buffer_snapshot.remote_id())
+                    .or_insert_with(|| (buffer, Vec::new()))
+                    .1
+                    .push(start..end);
+            }
+        }
+        for (buffer, rows) in rows_by_buffer.into_values() {
+            self.unstage_rows(&buffer, rows, cx);
+        }
+    }
+
+    /// Resets the staged changes touching the given rows of a buffer to their committed
+    /// contents, leaving the buffer and its other staged changes as they are.
+    pub(super) fn unstage_rows(
+        &mut self,
+        buffer: &Model<Buffer>,
+        rows: Vec<Range<u32>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let buffer = buffer.read(cx);
+        let Some(file) = project::File::from_dyn(buffer.file()) else {
+            return;
+        };
+        let Some(diff_base) = buffer.diff_base() else {
+            return;
+        };
+        let Some((worktree_id, work_directory_id, repo_path)) = repository_path(file, cx) else {
+            return;
+        };
+
+        // Staged changes are in the index, which is what the buffer is compared to.
+        let buffer_snapshot = buffer.snapshot();
+        let rows = rows
+            .into_iter()
+            .map(|rows| diff_base_rows(&buffer_snapshot, diff_base, rows))
+            .collect();
+        project
+            .read(cx)
+            .run_git_operation(
+                worktree_id,
+                work_directory_id,
+                GitOperation::UnstageRows { repo_path, rows },
+                cx,
+            )
+            .detach_and_log_err(cx);
+    }
+
     pub(super) fn toggle_hovered_hunk(
         &mut self,
         hovered_hunk: &HoveredHunk,
@@ -1227,17 +1227,8 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
-        let text: Cow<_> = if text.len() == 1 {
-            text.first().cloned().unwrap().into()
-        } else {
-            let joined_chunks = text.join("");
-            joined_chunks.into()
-        };
-
-        if let Some(replacement) = query.replacement_for(&text) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if let Some(replacement) = replacement_for_match(&snapshot, identifier, query) {
             self.transact(cx, |this, cx| {
                 this.edit([(identifier.clone(), Arc::from(&*replacement))], cx);
             });
@@ -1249,19 +1240,10 @@ impl SearchableItem for Editor {
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
         let mut edits = vec![];
         for m in matches {
-            let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
-            let text: Cow<_> = if text.len() == 1 {
-                text.first().cloned().unwrap().into()
-            } else {
-                let joined_chunks = text.join("");
-                joined_chunks.into()
-            };
-
-            if let Some(replacement) = query.replacement_for(&text) {
+            if let Some(replacement) = replacement_for_match(&snapshot, m, query) {
                 edits.push((m.clone(), Arc::from(&*replacement)));
             }
         }
@@ -1458,6 +1440,34 @@ pub fn entry_git_aware_label_color(
     }
 }
 
+/// Returns the text that replacing the given match of the query would insert, with regex capture
+/// groups and structural placeholders expanded.
+pub fn replacement_for_match(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    if query.is_structural() {
+        return structural_replacement(snapshot, range, query);
+    }
+    let text = snapshot.text_for_range(range.clone()).collect::<String>();
+    query
+        .replacement_for(&text)
+        .map(|replacement| replacement.into_owned())
+}
+
+/// Structural replacements can reuse the captured subtrees of a match, so they are expanded
+/// against the syntax tree of the buffer containing it rather than against its text.
+fn structural_replacement(
+    snapshot: &MultiBufferSnapshot,
+    range: &Range<Anchor>,
+    query: &SearchQuery,
+) -> Option<String> {
+    let range = range.to_offset(snapshot);
+    let excerpt = snapshot.excerpt_containing(range.clone())?;
+    query.structural_replacement_for(excerpt.buffer(), excerpt.map_range_to_buffer(range))
+}
+
 fn path_for_buffer<'a>(
     buffer: &Model<MultiBuffer>,
     height: usize,
@@ -0,0 +1,363 @@
+use std::{mem, ops::Range, time::Duration};
+
+use git::conflict::{parse_conflicts, ConflictRegion};
+use gpui::{Hsla, WeakView};
+use language::Point;
+use multi_buffer::{Anchor, MultiBufferSnapshot, ToPoint};
+use ui::{prelude::*, ViewContext};
+use util::{maybe, ResultExt};
+
+use crate::{
+    display_map::{BlockDisposition, BlockProperties, BlockStyle, RenderBlock},
+    AcceptBoth, AcceptOurs, AcceptTheirs, Editor, EditorMode,
+};
+
+pub(super) const MERGE_CONFLICTS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A conflict between conflict markers in one of the editor's buffers.
+#[derive(Clone, Debug)]
+pub(crate) struct MergeConflict {
+    range: Range<Anchor>,
+    ours: Range<Anchor>,
+    base: Option<Range<Anchor>>,
+    theirs: Range<Anchor>,
+}
+
+impl MergeConflict {
+    /// The ranges of the lines of the `<<<<<<<`, `|||||||`, `=======` and `>>>>>>>` markers.
+    fn marker_lines(&self) -> Vec<Range<Anchor>> {
+        let mut marker_lines = vec![self.range.start..self.ours.start];
+        match &self.base {
+            Some(base) => {
+                marker_lines.push(self.ours.end..base.start);
+                marker_lines.push(base.end..self.theirs.start);
+            }
+            None => marker_lines.push(self.ours.end..self.theirs.start),
+        }
+        marker_lines.push(self.theirs.end..self.range.end);
+        marker_lines
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+enum ConflictMarkersHighlight {}
+enum ConflictOursHighlight {}
+enum ConflictBaseHighlight {}
+enum ConflictTheirsHighlight {}
+
+/// Looks for conflict markers in the editor's buffers, highlights the sections of each conflict
+/// and displays buttons to resolve it in a block above it.
+pub(super) fn refresh_merge_conflicts(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .map(|buffer| (buffer.clone(), buffer.read(cx).snapshot()))
+        .collect::<Vec<_>>();
+
+    editor.merge_conflicts_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(MERGE_CONFLICTS_DEBOUNCE_TIMEOUT)
+            .await;
+        let conflicts = cx
+            .background_executor()
+            .spawn(async move {
+                buffers
+                    .into_iter()
+                    .filter_map(|(buffer, snapshot)| {
+                        let conflicts = parse_conflicts(&snapshot.text());
+                        (!conflicts.is_empty()).then_some((buffer, snapshot, conflicts))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multi_buffer = editor.buffer.read(cx);
+                let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+                let mut merge_conflicts = Vec::new();
+                for (buffer, buffer_snapshot, conflicts) in conflicts {
+                    let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+                    for conflict in conflicts {
+                        let start = buffer_snapshot.anchor_after(conflict.range.start);
+                        let end = buffer_snapshot.anchor_before(conflict.range.end);
+                        let Some((excerpt_id, _)) = excerpts.iter().find(|(_, excerpt_range)| {
+                            let context = &excerpt_range.context;
+                            context.start.cmp(&start, &buffer_snapshot).is_le()
+                                && context.end.cmp(&end, &buffer_snapshot).is_ge()
+                        }) else {
+                            continue;
+                        };
+                        let to_multi_buffer_range = |range: Range<usize>| {
+                            let start = buffer_snapshot.anchor_after(range.start);
+                            let end = buffer_snapshot.anchor_before(range.end);
+                            Some(
+                                multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, start)?
+                                    ..multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, end)?,
+                            )
+                        };
+                        let ConflictRegion {
+                            range,
+                            ours,
+                            base,
+                            theirs,
+                        } = conflict;
+                        merge_conflicts.extend(maybe!({
+                            Some(MergeConflict {
+                                range: to_multi_buffer_range(range)?,
+                                ours: to_multi_buffer_range(ours)?,
+                                base: match base {
+                                    Some(base) => Some(to_multi_buffer_range(base)?),
+                                    None => None,
+                                },
+                                theirs: to_multi_buffer_range(theirs)?,
+                            })
+                        }));
+                    }
+                }
+                merge_conflicts
+                    .sort_by(|a, b| a.range.start.cmp(&b.range.start, &multi_buffer_snapshot));
+                editor.set_merge_conflicts(merge_conflicts, cx);
+            })
+            .log_err();
+    }));
+
+    None
+}
+
+fn section_color(color: Hsla) -> Hsla {
+    let mut color = color;
+    color.fade_out(0.8);
+    color
+}
+
+fn render_conflict_buttons(editor: WeakView<Editor>, range: Range<Anchor>) -> RenderBlock {
+    Box::new(move |cx| {
+        let resolutions = [
+            (ConflictResolution::Ours, "Accept Ours"),
+            (ConflictResolution::Theirs, "Accept Theirs"),
+            (ConflictResolution::Both, "Accept Both"),
+        ];
+        h_flex()
+            .id(cx.block_id)
+            .h(cx.line_height)
+            .pl(cx.anchor_x)
+            .gap_1()
+            .children(
+                resolutions
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, (resolution, label))| {
+                        let editor = editor.clone();
+                        let range = range.clone();
+                        h_flex()
+                            .gap_1()
+                            .when(ix > 0, |this| {
+                                this.child(
+                                    Label::new("|").size(LabelSize::Small).color(Color::Muted),
+                                )
+                            })
+                            .child(
+                                Button::new(ix, label)
+                                    .label_size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .size(ButtonSize::None)
+                                    .style(ButtonStyle::Transparent)
+                                    .on_click(move |_, cx| {
+                                        cx.stop_propagation();
+                                        editor
+                                            .update(cx, |editor, cx| {
+                                                editor.resolve_merge_conflicts(
+                                                    &[range.clone()],
+                                                    resolution,
+                                                    cx,
+                                                )
+                                            })
+                                            .ok();
+                                    }),
+                            )
+                    }),
+            )
+            .into_any_element()
+    })
+}
+
+/// The rows of a range of whole lines, which ends at the start of the line after it.
+fn line_rows(
+    range: &Range<Anchor>,
+    snapshot: &MultiBufferSnapshot,
+) -> Option<std::ops::RangeInclusive<Anchor>> {
+    let start = range.start.to_point(snapshot);
+    let end = range.end.to_point(snapshot);
+    if end <= start {
+        return None;
+    }
+    let last_row = if end.column == 0 {
+        end.row - 1
+    } else {
+        end.row
+    };
+    Some(snapshot.anchor_after(start)..=snapshot.anchor_before(Point::new(last_row, 0)))
+}
+
+impl Editor {
+    pub fn accept_ours(&mut self, _: &AcceptOurs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_merge_conflicts(ConflictResolution::Ours, cx);
+    }
+
+    pub fn accept_theirs(&mut self, _: &AcceptTheirs, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_merge_conflicts(ConflictResolution::Theirs, cx);
+    }
+
+    pub fn accept_both(&mut self, _: &AcceptBoth, cx: &mut ViewContext<Self>) {
+        self.resolve_selected_merge_conflicts(ConflictResolution::Both, cx);
+    }
+
+    fn resolve_selected_merge_conflicts(
+        &mut self,
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.disjoint_anchor_ranges();
+        let ranges = self
+            .merge_conflicts
+            .iter()
+            .filter(|conflict| {
+                selections.iter().any(|selection| {
+                    selection.start.cmp(&conflict.range.end, &snapshot).is_le()
+                        && selection.end.cmp(&conflict.range.start, &snapshot).is_ge()
+                })
+            })
+            .map(|conflict| conflict.range.clone())
+            .collect::<Vec<_>>();
+        self.resolve_merge_conflicts(&ranges, resolution, cx);
+    }
+
+    /// Replaces the conflicts with the given ranges by the sections chosen by the resolution.
+    fn resolve_merge_conflicts(
+        &mut self,
+        ranges: &[Range<Anchor>],
+        resolution: ConflictResolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if ranges.is_empty() || self.read_only(cx) {
+            return;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let (resolved, remaining): (Vec<_>, Vec<_>) = mem::take(&mut self.merge_conflicts)
+            .into_iter()
+            .partition(|conflict| {
+                ranges.iter().any(|range| {
+                    range.start.cmp(&conflict.range.start, &snapshot).is_eq()
+                        && range.end.cmp(&conflict.range.end, &snapshot).is_eq()
+                })
+            });
+        let edits = resolved
+            .iter()
+            .map(|conflict| {
+                let ours = snapshot.text_for_range(conflict.ours.clone());
+                let theirs = snapshot.text_for_range(conflict.theirs.clone());
+                let text = match resolution {
+                    ConflictResolution::Ours => ours.collect::<String>(),
+                    ConflictResolution::Theirs => theirs.collect::<String>(),
+                    ConflictResolution::Both => ours.chain(theirs).collect::<String>(),
+                };
+                (conflict.range.clone(), text)
+            })
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            self.merge_conflicts = remaining;
+            return;
+        }
+
+        self.transact(cx, |editor, cx| {
+            editor
+                .buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        });
+        // Resolved conflicts disappear right away rather than after the next parse.
+        self.set_merge_conflicts(remaining, cx);
+    }
+
+    fn set_merge_conflicts(
+        &mut self,
+        merge_conflicts: Vec<MergeConflict>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        self.clear_row_highlights::<ConflictMarkersHighlight>();
+        self.clear_row_highlights::<ConflictOursHighlight>();
+        self.clear_row_highlights::<ConflictBaseHighlight>();
+        self.clear_row_highlights::<ConflictTheirsHighlight>();
+        let old_blocks = mem::take(&mut self.merge_conflict_blocks);
+        if !old_blocks.is_empty() {
+            self.remove_blocks(old_blocks, None, cx);
+        }
+
+        let status = cx.theme().status();
+        let markers_color = section_color(status.git().conflict);
+        let ours_color = section_color(status.git().created);
+        let base_color = section_color(status.git().modified);
+        let theirs_color = section_color(status.info);
+        for conflict in &merge_conflicts {
+            for marker_line in conflict.marker_lines() {
+                if let Some(rows) = line_rows(&marker_line, &snapshot) {
+                    self.highlight_rows::<ConflictMarkersHighlight>(
+                        rows,
+                        Some(markers_color),
+                        false,
+                        cx,
+                    );
+                }
+            }
+            if let Some(rows) = line_rows(&conflict.ours, &snapshot) {
+                self.highlight_rows::<ConflictOursHighlight>(rows, Some(ours_color), false, cx);
+            }
+            if let Some(rows) = conflict
+                .base
+                .as_ref()
+                .and_then(|base| line_rows(base, &snapshot))
+            {
+                self.highlight_rows::<ConflictBaseHighlight>(rows, Some(base_color), false, cx);
+            }
+            if let Some(rows) = line_rows(&conflict.theirs, &snapshot) {
+                self.highlight_rows::<ConflictTheirsHighlight>(rows, Some(theirs_color), false, cx);
+            }
+        }
+
+        if !self.read_only(cx) {
+            let view = cx.view().downgrade();
+            self.merge_conflict_blocks = self
+                .insert_blocks(
+                    merge_conflicts.iter().map(|conflict| BlockProperties {
+                        position: conflict.range.start,
+                        height: 1,
+                        style: BlockStyle::Fixed,
+                        render: render_conflict_buttons(view.clone(), conflict.range.clone()),
+                        disposition: BlockDisposition::Above,
+                    }),
+                    None,
+                    cx,
+                )
+                .into_iter()
+                .collect();
+        }
+        self.merge_conflicts = merge_conflicts;
+        cx.notify();
+    }
+}
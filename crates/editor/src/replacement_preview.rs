@@ -0,0 +1,60 @@
+use std::{mem, ops::Range};
+
+use gpui::{HighlightStyle, StrikethroughStyle, ViewContext};
+use multi_buffer::Anchor;
+use theme::ActiveTheme;
+use util::post_inc;
+
+use crate::{display_map::Inlay, Editor};
+
+/// Marks the text that a previewed replacement would remove.
+enum ReplacementPreviewHighlight {}
+
+impl Editor {
+    /// Shows what replacing each range with its new text would look like, without editing the
+    /// buffer: the replaced text is struck through and followed by the text replacing it.
+    ///
+    /// Replaces the previews shown by an earlier call.
+    pub fn set_replacement_previews(
+        &mut self,
+        previews: Vec<(Range<Anchor>, String)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let status = cx.theme().status();
+        let (deleted, created) = (status.deleted, status.created);
+
+        let mut ranges = Vec::with_capacity(previews.len());
+        let mut to_insert = Vec::with_capacity(previews.len());
+        for (range, new_text) in previews {
+            if !new_text.is_empty() {
+                let id = post_inc(&mut self.next_inlay_id);
+                to_insert.push(Inlay::replacement(id, range.end, new_text, created));
+            }
+            ranges.push(range);
+        }
+        let inserted = to_insert.iter().map(|inlay| inlay.id).collect();
+        let to_remove = mem::replace(&mut self.replacement_previews, inserted);
+        self.splice_inlays(to_remove, to_insert, cx);
+
+        if ranges.is_empty() {
+            self.clear_highlights::<ReplacementPreviewHighlight>(cx);
+        } else {
+            self.highlight_text::<ReplacementPreviewHighlight>(
+                ranges,
+                HighlightStyle {
+                    color: Some(deleted),
+                    strikethrough: Some(StrikethroughStyle {
+                        thickness: 1.0.into(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                cx,
+            );
+        }
+    }
+
+    pub fn clear_replacement_previews(&mut self, cx: &mut ViewContext<Self>) {
+        self.set_replacement_previews(Vec::new(), cx);
+    }
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use collections::HashMap;
+use gpui::HighlightStyle;
+use language::{language_settings::language_settings, HighlightMap};
+use text::{Bias, Unclipped};
+use theme::{ActiveTheme, SyntaxTheme};
+use ui::ViewContext;
+use util::ResultExt;
+
+use crate::{Editor, EditorMode};
+
+pub(crate) const SEMANTIC_TOKENS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(150);
+
+pub(super) fn refresh_semantic_tokens(
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<()> {
+    if editor.mode != EditorMode::Full {
+        return None;
+    }
+    let project = editor.project.clone()?;
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx).semantic_tokens
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        editor.semantic_tokens_task = None;
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.set_semantic_token_highlights(Vec::new())
+        });
+        return None;
+    }
+
+    editor.semantic_tokens_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor()
+            .timer(SEMANTIC_TOKENS_DEBOUNCE_TIMEOUT)
+            .await;
+        let Some(tasks) = project
+            .update(&mut cx, |project, cx| {
+                buffers
+                    .into_iter()
+                    .map(|buffer| {
+                        let task = project.semantic_tokens(&buffer, cx);
+                        async move { (buffer, task.await) }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .log_err()
+        else {
+            return;
+        };
+        let results = futures::future::join_all(tasks).await;
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                let syntax_theme = cx.theme().syntax().clone();
+                let multi_buffer = editor.buffer.read(cx);
+                let multi_buffer_snapshot = multi_buffer.snapshot(cx);
+                let mut styles = HashMap::<String, Option<HighlightStyle>>::default();
+                let mut highlights = Vec::new();
+                for (buffer, tokens) in results {
+                    let Some(tokens) = tokens.log_err() else {
+                        continue;
+                    };
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    // The buffer changed while the tokens were being computed, another
+                    // refresh is already scheduled for the new version.
+                    if tokens.version != *buffer_snapshot.version() {
+                        continue;
+                    }
+
+                    let excerpts = multi_buffer.excerpts_for_buffer(&buffer, cx);
+                    for token in tokens.tokens() {
+                        let capture_name =
+                            semantic_token_capture_name(token.token_type, &token.modifiers);
+                        let Some(style) = *styles
+                            .entry(capture_name)
+                            .or_insert_with_key(|name| style_for_capture(name, &syntax_theme))
+                        else {
+                            continue;
+                        };
+
+                        let start = buffer_snapshot
+                            .clip_point_utf16(Unclipped(token.range.start), Bias::Left);
+                        let end = buffer_snapshot
+                            .clip_point_utf16(Unclipped(token.range.end), Bias::Right);
+                        if start >= end {
+                            continue;
+                        }
+                        let start = buffer_snapshot.anchor_after(start);
+                        let end = buffer_snapshot.anchor_before(end);
+                        for (excerpt_id, excerpt_range) in &excerpts {
+                            let context = &excerpt_range.context;
+                            if context.start.cmp(&start, &buffer_snapshot).is_gt()
+                                || context.end.cmp(&end, &buffer_snapshot).is_lt()
+                            {
+                                continue;
+                            }
+                            if let Some((start, end)) = multi_buffer_snapshot
+                                .anchor_in_excerpt(*excerpt_id, start)
+                                .zip(multi_buffer_snapshot.anchor_in_excerpt(*excerpt_id, end))
+                            {
+                                highlights.push((start..end, style));
+                            }
+                        }
+                    }
+                }
+
+                highlights.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &multi_buffer_snapshot));
+                editor.display_map.update(cx, |display_map, _| {
+                    display_map.set_semantic_token_highlights(highlights)
+                });
+                cx.notify();
+            })
+            .log_err();
+    }));
+
+    None
+}
+
+fn style_for_capture(capture_name: &str, syntax_theme: &SyntaxTheme) -> Option<HighlightStyle> {
+    HighlightMap::new(&[capture_name], syntax_theme)
+        .get(0)
+        .style(syntax_theme)
+}
+
+/// Maps a semantic token onto the capture names used by the syntax themes, so that
+/// semantic highlighting is consistent with the tree-sitter one.
+///
+/// The raw token type and modifiers are appended, which lets themes target them directly
+/// (e.g. `variable.mutable`).
+fn semantic_token_capture_name(token_type: &str, modifiers: &[&str]) -> String {
+    let base = match token_type {
+        "type" | "class" | "struct" | "interface" | "typeParameter" => "type",
+        "enum" => "enum",
+        "enumMember" => "variant",
+        "parameter" => "variable.parameter",
+        "variable" => "variable",
+        "property" => "property",
+        "function" => "function",
+        "method" => "function.method",
+        "macro" => "function.special",
+        "keyword" | "modifier" => "keyword",
+        "comment" => "comment",
+        "string" => "string",
+        "regexp" => "string.regex",
+        "number" => "number",
+        "operator" => "operator",
+        "decorator" => "attribute",
+        other => other,
+    };
+
+    let mut capture_name = base.to_string();
+    if !base.split('.').any(|part| part == token_type) {
+        capture_name.push('.');
+        capture_name.push_str(token_type);
+    }
+    for modifier in modifiers {
+        capture_name.push('.');
+        capture_name.push_str(match *modifier {
+            "documentation" => "doc",
+            modifier => modifier,
+        });
+    }
+    capture_name
+}
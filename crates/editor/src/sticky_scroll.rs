@@ -0,0 +1,68 @@
+use gpui::ViewContext;
+use language::OutlineItem;
+use multi_buffer::Anchor;
+use sum_tree::Bias;
+use theme::SyntaxTheme;
+
+use crate::{
+    display_map::{Block, ToDisplayPoint},
+    scroll::Autoscroll,
+    DisplayPoint, DisplayRow, Editor, EditorSnapshot,
+};
+
+/// Returns the outline items to pin at the top of the editor when its first visible row is
+/// `scroll_top`, outermost first.
+///
+/// The header of the item at index `ix` covers the display row `scroll_top + ix`, so an item is
+/// pinned once its first line is scrolled under the headers above it, and for as long as its
+/// last line isn't. Headers stop at excerpt headers, as a scope never spans two excerpts.
+pub(crate) fn sticky_headers(
+    snapshot: &EditorSnapshot,
+    scroll_top: DisplayRow,
+    max_depth: usize,
+    theme: Option<&SyntaxTheme>,
+) -> Vec<OutlineItem<Anchor>> {
+    let max_row = snapshot.max_point().row();
+    let mut headers = Vec::new();
+    while headers.len() < max_depth {
+        let row = DisplayRow(scroll_top.0 + headers.len() as u32);
+        if row > max_row
+            || snapshot
+                .blocks_in_range(scroll_top..row.next_row())
+                .any(|(_, block)| matches!(block, Block::ExcerptHeader { .. }))
+        {
+            break;
+        }
+
+        let position =
+            DisplayPoint::new(row, snapshot.line_len(row)).to_offset(snapshot, Bias::Left);
+        let Some((_, items)) = snapshot.buffer_snapshot.symbols_containing(position, theme) else {
+            break;
+        };
+        let mut enclosing = items
+            .into_iter()
+            .filter(|item| {
+                item.range.start.to_display_point(snapshot).row() < row
+                    && item.range.end.to_display_point(snapshot).row() >= row
+            })
+            .collect::<Vec<_>>();
+        if enclosing.len() <= headers.len() {
+            break;
+        }
+        enclosing.truncate(headers.len() + 1);
+        headers = enclosing;
+    }
+    headers
+}
+
+impl Editor {
+    /// Moves the cursor to the start of a scope shown in a sticky header, scrolling its first
+    /// line back to the top of the editor.
+    pub(crate) fn jump_to_sticky_header(&mut self, position: Anchor, cx: &mut ViewContext<Self>) {
+        self.unfold_ranges([position..position], true, false, cx);
+        self.change_selections(Some(Autoscroll::top_relative(0)), cx, |selections| {
+            selections.select_anchor_ranges([position..position]);
+        });
+        cx.focus(&self.focus_handle);
+    }
+}
@@ -0,0 +1,75 @@
+use editor::Editor;
+use gpui::{
+    div, Action, AnchorCorner, IntoElement, ParentElement, Render, Subscription, View, ViewContext,
+};
+use language::Encoding;
+use ui::{Button, ButtonCommon, ContextMenu, FluentBuilder, LabelSize, PopoverMenu, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::{ReopenWithEncoding, SaveWithEncoding};
+
+#[derive(Default)]
+pub struct ActiveBufferEncoding {
+    active_encoding: Option<Encoding>,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl ActiveBufferEncoding {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_encoding(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        self.active_encoding = None;
+
+        let editor = editor.read(cx);
+        if let Some((_, buffer, _)) = editor.active_excerpt(cx) {
+            let buffer = buffer.read(cx);
+            if buffer.file().is_some() {
+                self.active_encoding = Some(buffer.encoding());
+            }
+        }
+
+        cx.notify();
+    }
+}
+
+impl Render for ActiveBufferEncoding {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.active_encoding, |el, active_encoding| {
+            el.child(
+                PopoverMenu::new("change-encoding")
+                    .menu(|cx| {
+                        Some(ContextMenu::build(cx, |menu, _| {
+                            menu.action("Reopen with Encoding", ReopenWithEncoding.boxed_clone())
+                                .action("Save with Encoding", SaveWithEncoding.boxed_clone())
+                        }))
+                    })
+                    .anchor(AnchorCorner::BottomRight)
+                    .trigger(
+                        Button::new("change-encoding-button", active_encoding.to_string())
+                            .label_size(LabelSize::Small)
+                            .tooltip(|cx| Tooltip::text("Select Encoding", cx)),
+                    ),
+            )
+        })
+    }
+}
+
+impl StatusItemView for ActiveBufferEncoding {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_encoding));
+            self.update_encoding(editor, cx);
+        } else {
+            self.active_encoding = None;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use dap::{
+    adapters::{DebugAdapter, DebugAdapterName},
+    DebugAdapterBinary,
+};
+use futures::FutureExt;
+use language::LspAdapterDelegate;
+use task::DebugAdapterConfig;
+use wasmtime_wasi::WasiView as _;
+
+use crate::wasm_host::{WasmExtension, WasmHost};
+
+/// A debug adapter started with the command provided by an extension.
+pub struct ExtensionDebugAdapter {
+    pub(crate) extension: WasmExtension,
+    pub(crate) host: Arc<WasmHost>,
+    pub(crate) debug_adapter_id: Arc<str>,
+}
+
+#[async_trait(?Send)]
+impl DebugAdapter for ExtensionDebugAdapter {
+    fn name(&self) -> DebugAdapterName {
+        DebugAdapterName(self.debug_adapter_id.clone())
+    }
+
+    async fn get_binary(
+        &self,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _: &DebugAdapterConfig,
+    ) -> Result<DebugAdapterBinary> {
+        let debug_adapter_id = self.debug_adapter_id.clone();
+        let command = self
+            .extension
+            .call(|extension, store| {
+                async move {
+                    let resource = store.data_mut().table().push(delegate)?;
+                    let command = extension
+                        .call_debug_adapter_command(store, &debug_adapter_id, resource)
+                        .await?
+                        .map_err(|e| anyhow!("{}", e))?;
+                    anyhow::Ok(command)
+                }
+                .boxed()
+            })
+            .await?;
+
+        Ok(DebugAdapterBinary {
+            command: self
+                .host
+                .path_from_extension(&self.extension.manifest.id, command.command.as_ref()),
+            arguments: command.args.into_iter().map(|arg| arg.into()).collect(),
+            env: Some(command.env.into_iter().collect()),
+            connection: None,
+        })
+    }
+}
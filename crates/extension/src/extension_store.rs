@@ -1,4 +1,5 @@
 pub mod extension_builder;
+mod extension_debug_adapter;
 mod extension_indexed_docs_provider;
 mod extension_lsp_adapter;
 mod extension_manifest;
@@ -9,6 +10,7 @@ mod wasm_host;
 #[cfg(test)]
 mod extension_store_test;
 
+use crate::extension_debug_adapter::ExtensionDebugAdapter;
 use crate::extension_indexed_docs_provider::ExtensionIndexedDocsProvider;
 use crate::extension_manifest::SchemaVersion;
 use crate::extension_slash_command::ExtensionSlashCommand;
@@ -19,6 +21,7 @@ use async_compression::futures::bufread::GzipDecoder;
 use async_tar::Archive;
 use client::{telemetry::Telemetry, Client, ExtensionMetadata, GetExtensionsResponse};
 use collections::{btree_map, BTreeMap, HashSet};
+use dap::adapters::{DapRegistry, DebugAdapterName};
 use extension_builder::{CompileExtensionOptions, ExtensionBuilder};
 use fs::{Fs, RemoveOptions};
 use futures::{
@@ -117,6 +120,7 @@ pub struct ExtensionStore {
     slash_command_registry: Arc<SlashCommandRegistry>,
     indexed_docs_registry: Arc<IndexedDocsRegistry>,
     snippet_registry: Arc<SnippetRegistry>,
+    dap_registry: Arc<DapRegistry>,
     modified_extensions: HashSet<Arc<str>>,
     wasm_host: Arc<WasmHost>,
     wasm_extensions: Vec<(Arc<ExtensionManifest>, WasmExtension)>,
@@ -196,6 +200,7 @@ pub fn init(
             SlashCommandRegistry::global(cx),
             IndexedDocsRegistry::global(cx),
             SnippetRegistry::global(cx),
+            DapRegistry::default_global(cx),
             cx,
         )
     });
@@ -231,6 +236,7 @@ impl ExtensionStore {
         slash_command_registry: Arc<SlashCommandRegistry>,
         indexed_docs_registry: Arc<IndexedDocsRegistry>,
         snippet_registry: Arc<SnippetRegistry>,
+        dap_registry: Arc<DapRegistry>,
         cx: &mut ModelContext<Self>,
     ) -> Self {
         let work_dir = extensions_dir.join("work");
@@ -269,6 +275,7 @@ impl ExtensionStore {
             slash_command_registry,
             indexed_docs_registry,
             snippet_registry,
+            dap_registry,
             reload_tx,
             tasks: Vec::new(),
         };
@@ -1040,6 +1047,10 @@ impl ExtensionStore {
                         .remove_lsp_adapter(&language, language_server_name);
                 }
             }
+            for debug_adapter_id in extension.manifest.debug_adapters.keys() {
+                self.dap_registry
+                    .remove_adapter(&DebugAdapterName(debug_adapter_id.clone()));
+            }
         }
 
         self.wasm_extensions
@@ -1235,6 +1246,15 @@ impl ExtensionStore {
                             },
                         ));
                     }
+
+                    for debug_adapter_id in manifest.debug_adapters.keys() {
+                        this.dap_registry
+                            .add_adapter(Arc::new(ExtensionDebugAdapter {
+                                extension: wasm_extension.clone(),
+                                host: this.wasm_host.clone(),
+                                debug_adapter_id: debug_adapter_id.clone(),
+                            }));
+                    }
                 }
 
                 this.wasm_extensions.extend(wasm_extensions);
@@ -1277,10 +1297,15 @@ impl ExtensionStore {
             }
 
             if let Ok(index_json) = serde_json::to_string_pretty(&index) {
-                fs.save(&index_path, &index_json.as_str().into(), Default::default())
-                    .await
-                    .context("failed to save extension index")
-                    .log_err();
+                fs.save(
+                    &index_path,
+                    &index_json.as_str().into(),
+                    Default::default(),
+                    Default::default(),
+                )
+                .await
+                .context("failed to save extension index")
+                .log_err();
             }
 
             log::info!("rebuilt extension index in {:?}", start_time.elapsed());
@@ -8,6 +8,7 @@ use crate::{
 use assistant_slash_command::SlashCommandRegistry;
 use async_compression::futures::bufread::GzipEncoder;
 use collections::BTreeMap;
+use dap::adapters::DapRegistry;
 use fs::{FakeFs, Fs, RealFs};
 use futures::{io::BufReader, AsyncReadExt, StreamExt};
 use gpui::{Context, SemanticVersion, TestAppContext};
@@ -161,6 +162,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        debug_adapters: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -187,6 +189,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
                         indexed_docs_providers: BTreeMap::default(),
+                        debug_adapters: BTreeMap::default(),
                         snippets: None,
                     }),
                     dev: false,
@@ -262,6 +265,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
     let slash_command_registry = SlashCommandRegistry::new();
     let indexed_docs_registry = Arc::new(IndexedDocsRegistry::new(cx.executor()));
     let snippet_registry = Arc::new(SnippetRegistry::new());
+    let dap_registry = Arc::new(DapRegistry::default());
     let node_runtime = FakeNodeRuntime::new();
 
     let store = cx.new_model(|cx| {
@@ -277,6 +281,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
             slash_command_registry.clone(),
             indexed_docs_registry.clone(),
             snippet_registry.clone(),
+            dap_registry.clone(),
             cx,
         )
     });
@@ -350,6 +355,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 language_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
                 indexed_docs_providers: BTreeMap::default(),
+                debug_adapters: BTreeMap::default(),
                 snippets: None,
             }),
             dev: false,
@@ -404,6 +410,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
             slash_command_registry,
             indexed_docs_registry,
             snippet_registry,
+            dap_registry,
             cx,
         )
     });
@@ -486,6 +493,7 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
     let slash_command_registry = SlashCommandRegistry::new();
     let indexed_docs_registry = Arc::new(IndexedDocsRegistry::new(cx.executor()));
     let snippet_registry = Arc::new(SnippetRegistry::new());
+    let dap_registry = Arc::new(DapRegistry::default());
     let node_runtime = FakeNodeRuntime::new();
 
     let mut status_updates = language_registry.language_server_binary_statuses();
@@ -578,6 +586,7 @@ async fn test_extension_store_with_test_extension(cx: &mut TestAppContext) {
             slash_command_registry,
             indexed_docs_registry,
             snippet_registry,
+            dap_registry,
             cx,
         )
     });
@@ -307,6 +307,23 @@ impl Extension {
             }
         }
     }
+
+    pub async fn call_debug_adapter_command(
+        &self,
+        store: &mut Store<WasmState>,
+        debug_adapter_id: &str,
+        resource: Resource<Arc<dyn LspAdapterDelegate>>,
+    ) -> Result<Result<Command, String>> {
+        match self {
+            Extension::V007(ext) => {
+                ext.call_debug_adapter_command(store, debug_adapter_id, resource)
+                    .await
+            }
+            Extension::V001(_) | Extension::V004(_) | Extension::V006(_) => Err(anyhow!(
+                "`debug_adapter_command` not available prior to v0.0.7"
+            )),
+        }
+    }
 }
 
 trait ToWasmtimeResult<T> {
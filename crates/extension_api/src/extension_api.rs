@@ -138,6 +138,15 @@ pub trait Extension: Send + Sync {
     ) -> Result<(), String> {
         Err("`index_docs` not implemented".to_string())
     }
+
+    /// Returns the command used to start the specified debug adapter.
+    fn debug_adapter_command(
+        &mut self,
+        _debug_adapter_id: &str,
+        _worktree: &Worktree,
+    ) -> Result<Command> {
+        Err("`debug_adapter_command` not implemented".to_string())
+    }
 }
 
 /// Registers the provided type as a Zed extension.
@@ -268,6 +277,13 @@ impl wit::Guest for Component {
     ) -> Result<(), String> {
         extension().index_docs(provider, package, database)
     }
+
+    fn debug_adapter_command(
+        debug_adapter_id: String,
+        worktree: &Worktree,
+    ) -> Result<wit::Command> {
+        extension().debug_adapter_command(&debug_adapter_id, worktree)
+    }
 }
 
 /// The ID of a language server.
@@ -7,6 +7,7 @@ mod open_path_prompt;
 use collections::{BTreeSet, HashMap};
 use editor::{scroll::Autoscroll, Bias, Editor};
 use fuzzy::{CharBag, PathMatch, PathMatchCandidate};
+use git::repository::GitFileStatus;
 use gpui::{
     actions, rems, Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle,
     FocusableView, Model, Modifiers, ModifiersChangedEvent, ParentElement, Render, Styled, Task,
@@ -626,6 +627,23 @@ impl FileFinderDelegate {
         )
     }
 
+    /// Whether the matched file has unresolved merge conflicts.
+    fn is_conflicted(&self, path_match: &Match, cx: &AppContext) -> bool {
+        let project_path = match path_match {
+            Match::History(found_path, _) => found_path.project.clone(),
+            Match::Search(path_match) => ProjectPath {
+                worktree_id: WorktreeId::from_usize(path_match.0.worktree_id),
+                path: path_match.0.path.clone(),
+            },
+        };
+        self.project
+            .read(cx)
+            .entry_for_path(&project_path, cx)
+            .map_or(false, |entry| {
+                entry.git_status == Some(GitFileStatus::Conflict)
+            })
+    }
+
     fn labels_for_path_match(
         &self,
         path_match: &PathMatch,
@@ -965,6 +983,7 @@ impl PickerDelegate for FileFinderDelegate {
         };
         let (file_name, file_name_positions, full_path, full_path_positions) =
             self.labels_for_match(path_match, cx, ix);
+        let is_conflicted = self.is_conflicted(path_match, cx);
 
         Some(
             ListItem::new(ix)
@@ -976,12 +995,22 @@ impl PickerDelegate for FileFinderDelegate {
                     h_flex()
                         .gap_2()
                         .py_px()
-                        .child(HighlightedLabel::new(file_name, file_name_positions))
+                        .child(
+                            HighlightedLabel::new(file_name, file_name_positions)
+                                .when(is_conflicted, |label| label.color(Color::Conflict)),
+                        )
                         .child(
                             HighlightedLabel::new(full_path, full_path_positions)
                                 .size(LabelSize::Small)
                                 .color(Color::Muted),
-                        ),
+                        )
+                        .when(is_conflicted, |this| {
+                            this.child(
+                                Icon::new(IconName::ExclamationTriangle)
+                                    .size(IconSize::Small)
+                                    .color(Color::Conflict),
+                            )
+                        }),
                 ),
         )
     }
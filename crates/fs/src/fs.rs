@@ -1,4 +1,4 @@
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use git::GitHostingProviderRegistry;
 
 #[cfg(target_os = "linux")]
@@ -23,7 +23,7 @@ use std::{
     time::{Duration, SystemTime},
 };
 use tempfile::{NamedTempFile, TempDir};
-use text::LineEnding;
+use text::{Encoding, LineEnding};
 use util::ResultExt;
 
 #[cfg(any(test, feature = "test-support"))]
@@ -72,8 +72,31 @@ pub trait Fs: Send + Sync {
         Ok(String::from_utf8(self.load_bytes(path).await?)?)
     }
     async fn load_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+    /// Loads a text file, decoding it with the given encoding, or with the one detected from its
+    /// contents if there is none. Returns the text along with the encoding it was decoded with.
+    async fn load_with_encoding(
+        &self,
+        path: &Path,
+        encoding: Option<Encoding>,
+    ) -> Result<(String, Encoding)> {
+        let bytes = self.load_bytes(path).await?;
+        let encoding = match encoding {
+            Some(encoding) => encoding,
+            None => Encoding::detect(&bytes)
+                .ok_or_else(|| anyhow!("{path:?} is a binary file, not a text file"))?,
+        };
+        encoding
+            .decode(bytes)
+            .with_context(|| format!("decoding {path:?} as {encoding}"))
+    }
     async fn atomic_write(&self, path: PathBuf, text: String) -> Result<()>;
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()>;
+    async fn save(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()>;
     async fn canonicalize(&self, path: &Path) -> Result<PathBuf>;
     async fn is_file(&self, path: &Path) -> bool;
     async fn is_dir(&self, path: &Path) -> bool;
@@ -363,15 +386,34 @@ impl Fs for RealFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
+        // Encode the text before creating the file, so that it isn't truncated when the text
+        // can't be represented in the encoding.
+        let encoded = if encoding == Encoding::UTF8 {
+            None
+        } else {
+            let text = chunks(text, line_ending).collect::<String>();
+            Some(encoding.encode(&text)?.into_owned())
+        };
+
         let buffer_size = text.summary().len.min(10 * 1024);
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
         let file = smol::fs::File::create(path).await?;
         let mut writer = smol::io::BufWriter::with_capacity(buffer_size, file);
-        for chunk in chunks(text, line_ending) {
-            writer.write_all(chunk.as_bytes()).await?;
+        if let Some(encoded) = encoded {
+            writer.write_all(&encoded).await?;
+        } else {
+            for chunk in chunks(text, line_ending) {
+                writer.write_all(chunk.as_bytes()).await?;
+            }
         }
         writer.flush().await?;
         Ok(())
@@ -1005,6 +1047,17 @@ impl FakeFs {
         });
     }
 
+    pub fn set_committed_contents_for_repo(&self, dot_git: &Path, contents: &[(&Path, String)]) {
+        self.with_git_state(dot_git, true, |state| {
+            state.committed_contents.clear();
+            state.committed_contents.extend(
+                contents
+                    .iter()
+                    .map(|(path, content)| (path.to_path_buf(), content.clone())),
+            );
+        });
+    }
+
     pub fn set_blame_for_repo(&self, dot_git: &Path, blames: Vec<(&Path, git::blame::Blame)>) {
         self.with_git_state(dot_git, true, |state| {
             state.blames.clear();
@@ -1467,14 +1520,21 @@ impl Fs for FakeFs {
         Ok(())
     }
 
-    async fn save(&self, path: &Path, text: &Rope, line_ending: LineEnding) -> Result<()> {
+    async fn save(
+        &self,
+        path: &Path,
+        text: &Rope,
+        line_ending: LineEnding,
+        encoding: Encoding,
+    ) -> Result<()> {
         self.simulate_random_delay().await;
         let path = normalize_path(path);
         let content = chunks(text, line_ending).collect::<String>();
+        let content = encoding.encode(&content)?.into_owned();
         if let Some(path) = path.parent() {
             self.create_dir(path).await?;
         }
-        self.write_file_internal(path, content.into_bytes())?;
+        self.write_file_internal(path, content)?;
         Ok(())
     }
 
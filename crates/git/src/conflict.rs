@@ -0,0 +1,170 @@
+use std::ops::Range;
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// A conflict left in a file by a merge, rebase or cherry-pick.
+///
+/// All ranges are byte offsets into the parsed text. Sections span whole lines and exclude the
+/// marker lines around them, so that replacing `range` with the text of a section resolves the
+/// conflict.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictRegion {
+    /// The whole conflict, from the start of its `<<<<<<<` line to the end of its `>>>>>>>` line.
+    pub range: Range<usize>,
+    /// The current version, between `<<<<<<<` and `|||||||` or `=======`.
+    pub ours: Range<usize>,
+    /// The common ancestor, only present with the `diff3` and `zdiff3` conflict styles.
+    pub base: Option<Range<usize>>,
+    /// The incoming version, between `=======` and `>>>>>>>`.
+    pub theirs: Range<usize>,
+}
+
+enum ParseState {
+    Outside,
+    Ours {
+        start: usize,
+        ours_start: usize,
+    },
+    Base {
+        start: usize,
+        ours: Range<usize>,
+        base_start: usize,
+    },
+    Theirs {
+        start: usize,
+        ours: Range<usize>,
+        base: Option<Range<usize>>,
+        theirs_start: usize,
+    },
+}
+
+/// Finds the conflict regions delimited by conflict markers in the given text.
+/// Markers that don't form a complete conflict are ignored.
+pub fn parse_conflicts(text: &str) -> Vec<ConflictRegion> {
+    let mut conflicts = Vec::new();
+    let mut state = ParseState::Outside;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        state = match state {
+            _ if is_marker(line, OURS_MARKER) => ParseState::Ours {
+                start: line_start,
+                ours_start: line_end,
+            },
+            ParseState::Ours { start, ours_start } if is_marker(line, BASE_MARKER) => {
+                ParseState::Base {
+                    start,
+                    ours: ours_start..line_start,
+                    base_start: line_end,
+                }
+            }
+            ParseState::Ours { start, ours_start } if is_marker(line, SEPARATOR_MARKER) => {
+                ParseState::Theirs {
+                    start,
+                    ours: ours_start..line_start,
+                    base: None,
+                    theirs_start: line_end,
+                }
+            }
+            ParseState::Base {
+                start,
+                ours,
+                base_start,
+            } if is_marker(line, SEPARATOR_MARKER) => ParseState::Theirs {
+                start,
+                ours,
+                base: Some(base_start..line_start),
+                theirs_start: line_end,
+            },
+            ParseState::Theirs {
+                start,
+                ours,
+                base,
+                theirs_start,
+            } if is_marker(line, THEIRS_MARKER) => {
+                conflicts.push(ConflictRegion {
+                    range: start..line_end,
+                    ours,
+                    base,
+                    theirs: theirs_start..line_start,
+                });
+                ParseState::Outside
+            }
+            state => state,
+        };
+        line_start = line_end;
+    }
+    conflicts
+}
+
+/// Whether the line is the given marker, optionally followed by a space and a label.
+fn is_marker(line: &str, marker: &str) -> bool {
+    line.strip_prefix(marker).map_or(false, |rest| {
+        rest.is_empty() || rest.starts_with(|c: char| c.is_whitespace())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = concat!(
+            "fn main() {\n",
+            "<<<<<<< HEAD\n",
+            "    println!(\"ours\");\n",
+            "=======\n",
+            "    println!(\"theirs\");\n",
+            ">>>>>>> feature\n",
+            "}\n",
+            "<<<<<<< HEAD\n",
+            "a\n",
+            "||||||| base\n",
+            "b\n",
+            "=======\n",
+            ">>>>>>> feature",
+        );
+        let conflicts = parse_conflicts(text);
+        assert_eq!(conflicts.len(), 2);
+
+        let first = &conflicts[0];
+        assert!(text[first.range.clone()].starts_with("<<<<<<< HEAD\n"));
+        assert!(text[first.range.clone()].ends_with(">>>>>>> feature\n"));
+        assert_eq!(&text[first.ours.clone()], "    println!(\"ours\");\n");
+        assert_eq!(first.base, None);
+        assert_eq!(&text[first.theirs.clone()], "    println!(\"theirs\");\n");
+
+        let second = &conflicts[1];
+        assert_eq!(second.range.end, text.len());
+        assert_eq!(&text[second.ours.clone()], "a\n");
+        assert_eq!(second.base.clone().map(|base| &text[base]), Some("b\n"));
+        assert_eq!(&text[second.theirs.clone()], "");
+    }
+
+    #[test]
+    fn test_parse_incomplete_conflicts() {
+        // A separator outside of a conflict, a conflict without its end marker and a
+        // marker-like line that is part of the content.
+        let text = concat!(
+            "=======\n",
+            "<<<<<<< HEAD\n",
+            "a\n",
+            "<<<<<<<< not a marker\n",
+            "<<<<<<< HEAD\n",
+            "b\n",
+            "=======\n",
+            "c\n",
+            ">>>>>>> feature\n",
+            "<<<<<<< HEAD\n",
+            "d\n",
+        );
+        let conflicts = parse_conflicts(text);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(&text[conflicts[0].ours.clone()], "b\n");
+        assert_eq!(&text[conflicts[0].theirs.clone()], "c\n");
+    }
+}
@@ -285,6 +285,47 @@ impl BufferDiff {
     }
 }
 
+/// Reverts the changes from `base` to `text` that touch any of the given rows of `text`,
+/// e.g. to unstage some of the changes of a file by reverting its index text to the committed
+/// one. Changes are reverted as a whole, and removed lines touch the row following them.
+pub fn revert_rows(base: &str, text: &str, rows: &[Range<u32>]) -> String {
+    let Some(patch) = BufferDiff::diff(base, text) else {
+        return text.to_string();
+    };
+    let base_lines = base.split_inclusive('\n').collect::<Vec<_>>();
+    let text_lines = text.split_inclusive('\n').collect::<Vec<_>>();
+
+    let mut result = String::with_capacity(text.len());
+    let mut text_row = 0;
+    for hunk_index in 0..patch.num_hunks() {
+        let Ok((hunk, _)) = patch.hunk(hunk_index) else {
+            continue;
+        };
+        // Line numbers are one-based, except for empty sides, which give the line before them.
+        let old_start = hunk.old_start() - (hunk.old_lines() > 0) as u32;
+        let new_start = hunk.new_start() - (hunk.new_lines() > 0) as u32;
+        let old_rows = old_start as usize..(old_start + hunk.old_lines()) as usize;
+        let new_rows = new_start..new_start + hunk.new_lines();
+        let touched_rows = new_rows.start..new_rows.end.max(new_rows.start + 1);
+        if !rows
+            .iter()
+            .any(|row| row.start < touched_rows.end && touched_rows.start < row.end)
+        {
+            continue;
+        }
+
+        result.extend(
+            text_lines[text_row..new_rows.start as usize]
+                .iter()
+                .copied(),
+        );
+        result.extend(base_lines[old_rows].iter().copied());
+        text_row = new_rows.end as usize;
+    }
+    result.extend(text_lines[text_row..].iter().copied());
+    result
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -421,4 +462,40 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn test_revert_rows() {
+        let base = "
+            one
+            two
+            three
+            four
+            five
+        "
+        .unindent();
+        let text = "
+            one
+            TWO
+            three
+            five
+            six
+        "
+        .unindent();
+
+        assert_eq!(revert_rows(&base, &text, &[]), text);
+        assert_eq!(
+            revert_rows(&base, &text, &[1..2]),
+            "one\ntwo\nthree\nfive\nsix\n"
+        );
+        // Removed lines touch the row following them.
+        assert_eq!(
+            revert_rows(&base, &text, &[3..4]),
+            "one\nTWO\nthree\nfour\nfive\nsix\n"
+        );
+        assert_eq!(
+            revert_rows(&base, &text, &[4..5]),
+            "one\nTWO\nthree\nfive\n"
+        );
+        assert_eq!(revert_rows(&base, &text, &[0..5]), base);
+    }
 }
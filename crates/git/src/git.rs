@@ -13,8 +13,11 @@ pub use crate::hosting_provider::*;
 
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 pub mod diff;
+pub mod log;
 pub mod repository;
+pub mod stash;
 pub mod status;
 
 lazy_static! {
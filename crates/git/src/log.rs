@@ -0,0 +1,186 @@
+use crate::{repository::RepoPath, Oid};
+use anyhow::{anyhow, Context as _, Result};
+use std::{
+    ops::Range,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+const COMMIT_START: char = '\u{1e}';
+const COMMIT_END: char = '\u{1f}';
+const FIELD_SEPARATOR: char = '\0';
+const LOG_FORMAT: &str = "--format=%x1e%H%x00%P%x00%an%x00%ae%x00%at%x00%B%x1f";
+
+/// A commit as listed in the history of a repository.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitEntry {
+    pub sha: Oid,
+    pub parents: Vec<Oid>,
+    pub author_name: String,
+    pub author_email: String,
+    /// The author time, in seconds since the Unix epoch.
+    pub timestamp: i64,
+    pub message: String,
+}
+
+impl CommitEntry {
+    /// The first line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+/// Which part of the history of a repository to list, newest commits first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogQuery {
+    /// Only list the commits that changed this path, following its renames.
+    pub path: Option<RepoPath>,
+    /// Only list the commits that changed these zero-based rows of `path`.
+    pub rows: Option<Range<u32>>,
+    pub skip: usize,
+    pub limit: usize,
+}
+
+/// A page of commits, along with the remote that can be used to link to them.
+#[derive(Clone, Debug, Default)]
+pub struct GitLog {
+    pub commits: Vec<CommitEntry>,
+    pub remote_url: Option<String>,
+}
+
+/// The contents of a file before and after a commit, or at a revision and in the working tree.
+/// Either side is `None` if the file was created or deleted in between.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitFileDiff {
+    pub path: RepoPath,
+    pub old_text: Option<String>,
+    pub new_text: Option<String>,
+}
+
+pub(crate) fn run_git_log(
+    git_binary: &Path,
+    working_directory: &Path,
+    query: &LogQuery,
+) -> Result<Vec<CommitEntry>> {
+    let mut command = Command::new(git_binary);
+    command
+        .current_dir(working_directory)
+        .args(["--no-optional-locks", "log", LOG_FORMAT])
+        .arg(format!("--skip={}", query.skip))
+        .arg(format!("--max-count={}", query.limit));
+    match (&query.path, &query.rows) {
+        (Some(path), Some(rows)) => {
+            // `-L` takes one-based, inclusive line numbers and always prints patches,
+            // which the parser skips.
+            command.arg(format!(
+                "-L{},{}:{}",
+                rows.start + 1,
+                rows.end.max(rows.start + 1),
+                path.to_string_lossy()
+            ));
+        }
+        (Some(path), None) => {
+            command.arg("--follow").arg("--").arg(path.as_os_str());
+        }
+        (None, _) => {}
+    }
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git log process: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // A repository without commits has no history rather than a broken one.
+        if stderr.contains("does not have any commits yet") {
+            return Ok(Vec::new());
+        }
+        return Err(anyhow!("git log process failed: {}", stderr.trim()));
+    }
+
+    parse_git_log(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_git_log(output: &str) -> Result<Vec<CommitEntry>> {
+    let mut commits = Vec::new();
+    let mut remaining = output;
+    while let Some(start) = remaining.find(COMMIT_START) {
+        let record = &remaining[start + COMMIT_START.len_utf8()..];
+        let end = record
+            .find(COMMIT_END)
+            .context("unterminated commit in git log output")?;
+        commits.push(parse_commit(&record[..end])?);
+        remaining = &record[end + COMMIT_END.len_utf8()..];
+    }
+    Ok(commits)
+}
+
+fn parse_commit(record: &str) -> Result<CommitEntry> {
+    let mut fields = record.splitn(6, FIELD_SEPARATOR);
+    let mut next_field = |name: &str| {
+        fields
+            .next()
+            .with_context(|| format!("missing {name} in git log output"))
+    };
+    let sha = next_field("sha")?.parse()?;
+    let parents = next_field("parents")?
+        .split_whitespace()
+        .map(str::parse)
+        .collect::<Result<Vec<Oid>>>()?;
+    let author_name = next_field("author name")?.to_string();
+    let author_email = next_field("author email")?.to_string();
+    let timestamp = next_field("timestamp")?
+        .parse()
+        .context("invalid commit timestamp")?;
+    let message = next_field("message")?.trim().to_string();
+    Ok(CommitEntry {
+        sha,
+        parents,
+        author_name,
+        author_email,
+        timestamp,
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_log() {
+        let output = concat!(
+            "\u{1e}1111111111111111111111111111111111111111\0",
+            "2222222222222222222222222222222222222222 3333333333333333333333333333333333333333\0",
+            "Jane Doe\0jane@example.com\01700000000\0Merge branch 'feature'\n\nDetails\n\u{1f}\n",
+            "diff --git a/a.txt b/a.txt\n",
+            "\u{1e}2222222222222222222222222222222222222222\0\0",
+            "John Doe\0john@example.com\01600000000\0Initial commit\n\u{1f}\n",
+        );
+        let commits = parse_git_log(output).unwrap();
+        assert_eq!(commits.len(), 2);
+
+        assert_eq!(
+            commits[0].sha.to_string(),
+            "1111111111111111111111111111111111111111"
+        );
+        assert_eq!(commits[0].parents.len(), 2);
+        assert_eq!(commits[0].author_name, "Jane Doe");
+        assert_eq!(commits[0].author_email, "jane@example.com");
+        assert_eq!(commits[0].timestamp, 1700000000);
+        assert_eq!(commits[0].message, "Merge branch 'feature'\n\nDetails");
+        assert_eq!(commits[0].subject(), "Merge branch 'feature'");
+
+        assert!(commits[1].parents.is_empty());
+        assert_eq!(commits[1].subject(), "Initial commit");
+    }
+}
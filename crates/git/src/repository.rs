@@ -1,14 +1,22 @@
 use crate::GitHostingProviderRegistry;
-use crate::{blame::Blame, status::GitStatus};
-use anyhow::{Context, Result};
-use collections::HashMap;
+use crate::{
+    blame::Blame,
+    log::{run_git_log, CommitEntry, CommitFileDiff, LogQuery},
+    stash::{run_git_stash_list, StashEntry},
+    status::{GitChangeKind, GitStagingEntry, GitStagingStatus, GitStatus},
+    Oid,
+};
+use anyhow::{anyhow, Context, Result};
+use collections::{HashMap, HashSet};
 use git2::BranchType;
 use parking_lot::Mutex;
 use rope::Rope;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
+    ops::Range,
     path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
     sync::Arc,
 };
 use sum_tree::MapSeekTarget;
@@ -29,6 +37,9 @@ pub trait GitRepository: Send + Sync {
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads the contents of a path in the HEAD commit.
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -47,6 +58,67 @@ pub trait GitRepository: Send + Sync {
     fn create_branch(&self, _: &str) -> Result<()>;
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Returns the staged, unstaged and untracked changes of the whole repository.
+    fn staging_status(&self) -> Result<GitStagingStatus>;
+
+    /// Adds the working tree contents of the given paths to the index, including deletions.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Resets the index entries of the given paths to their HEAD contents.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Replaces the index contents of a path, removing it from the index when `content` is `None`.
+    /// Used to stage or unstage individual hunks.
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()>;
+
+    /// Resets the staged changes of a path that touch the given rows of its index text to their
+    /// HEAD contents, leaving its other staged changes as they are.
+    fn unstage_rows(&self, path: &RepoPath, rows: &[Range<u32>]) -> Result<()> {
+        let index_text = self
+            .load_index_text(path)
+            .with_context(|| format!("{path:?} is not in the index"))?;
+        let committed_text = self.load_committed_text(path).unwrap_or_default();
+        let index_text = crate::diff::revert_rows(&committed_text, &index_text, rows);
+        self.set_index_text(path, Some(index_text))
+    }
+
+    /// Commits the staged changes with the given message.
+    fn commit(&self, message: &str) -> Result<()>;
+
+    /// Lists the commits of the repository's history, newest first.
+    fn log(&self, query: &LogQuery) -> Result<Vec<CommitEntry>>;
+
+    /// Returns the files changed by a commit, compared to its first parent.
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>>;
+
+    /// Returns the files that differ between a revision and the working tree, or between the
+    /// merge base of the revision and HEAD and the working tree when `merge_base` is true.
+    fn revision_diff(&self, revision: &str, merge_base: bool) -> Result<Vec<CommitFileDiff>>;
+
+    /// Fetches from the default remote. The environment is passed to git, so that it can
+    /// prompt for credentials through an askpass program.
+    fn fetch(&self, env: &HashMap<String, String>) -> Result<()>;
+
+    /// Pulls the current branch from its upstream, as configured for the repository.
+    fn pull(&self, env: &HashMap<String, String>) -> Result<()>;
+
+    /// Pushes the current branch to its upstream, or to a branch of the same name on `origin`
+    /// that becomes its upstream if it doesn't have one yet.
+    fn push(&self, env: &HashMap<String, String>) -> Result<()>;
+
+    /// Lists the entries of the stash, most recent first.
+    fn stash_list(&self) -> Result<Vec<StashEntry>>;
+
+    /// Stashes all changes, including untracked files, with an optional message.
+    fn stash_save(&self, message: Option<&str>) -> Result<()>;
+
+    /// Applies the stash entry with the given index to the working tree, removing it from the
+    /// stash when `pop` is true.
+    fn stash_apply(&self, index: usize, pop: bool) -> Result<()>;
+
+    /// Removes the stash entry with the given index without applying it.
+    fn stash_drop(&self, index: usize) -> Result<()>;
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -109,6 +181,26 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_committed_text(&self, relative_file_path: &Path) -> Option<String> {
+        fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<String>> {
+            let tree = repo.head()?.peel_to_tree()?;
+            let entry = match tree.get_path(relative_file_path) {
+                Ok(entry) if entry.filemode() as u32 != GIT_MODE_SYMLINK => entry,
+                _ => return Ok(None),
+            };
+            let content = repo.find_blob(entry.id())?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(&self.repository.lock(), relative_file_path) {
+            Ok(value) => value,
+            Err(err) => {
+                log::error!("Error loading committed text: {:?}", err);
+                None
+            }
+        }
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -203,6 +295,300 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn staging_status(&self) -> Result<GitStagingStatus> {
+        GitStagingStatus::new(&self.git_binary_path, &self.working_directory()?)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        run_git_command(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            ["add", "--all", "--"],
+            paths,
+        )
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let working_directory = self.working_directory()?;
+        if self.head_sha().is_some() {
+            run_git_command(
+                &self.git_binary_path,
+                &working_directory,
+                ["reset", "--quiet", "--"],
+                paths,
+            )
+        } else {
+            // Without a HEAD commit there is nothing to reset to, so drop the entries instead.
+            run_git_command(
+                &self.git_binary_path,
+                &working_directory,
+                ["rm", "--cached", "-r", "--quiet", "--"],
+                paths,
+            )
+        }
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        check_path_to_repo_path_errors(path)?;
+        let repo = self.repository.lock();
+        let mut index = repo.index()?;
+        if let Some(content) = content {
+            const STAGE_NORMAL: i32 = 0;
+            let mode = index
+                .get_path(path, STAGE_NORMAL)
+                .map_or(0o100644, |entry| entry.mode);
+            let entry = git2::IndexEntry {
+                ctime: git2::IndexTime::new(0, 0),
+                mtime: git2::IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode,
+                uid: 0,
+                gid: 0,
+                file_size: content.len() as u32,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: path.to_string_lossy().into_owned().into_bytes(),
+            };
+            index.add_frombuffer(&entry, content.as_bytes())?;
+        } else {
+            index.remove_path(path)?;
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        run_git_command(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            ["commit", "--quiet", "--message", message],
+            &[],
+        )
+    }
+
+    fn log(&self, query: &LogQuery) -> Result<Vec<CommitEntry>> {
+        run_git_log(&self.git_binary_path, &self.working_directory()?, query)
+    }
+
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>> {
+        let repo = self.repository.lock();
+        let commit = repo.find_commit(sha.0)?;
+        let new_tree = commit.tree()?;
+        let old_tree = match commit.parents().next() {
+            Some(parent) => Some(parent.tree()?),
+            None => None,
+        };
+        let mut diff = repo.diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?;
+        diff.find_similar(None)?;
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            let (Ok(old_text), Ok(new_text)) = (
+                load_blob_text(&repo, delta.old_file().id()),
+                load_blob_text(&repo, delta.new_file().id()),
+            ) else {
+                continue;
+            };
+            files.push(CommitFileDiff {
+                path: RepoPath::new(path.to_path_buf()),
+                old_text,
+                new_text,
+            });
+        }
+        Ok(files)
+    }
+
+    fn revision_diff(&self, revision: &str, merge_base: bool) -> Result<Vec<CommitFileDiff>> {
+        let repo = self.repository.lock();
+        let workdir = repo
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        let mut commit = repo
+            .revparse_single(revision)
+            .and_then(|object| object.peel_to_commit())
+            .with_context(|| format!("unknown revision {revision:?}"))?;
+        if merge_base {
+            let head = repo.head()?.peel_to_commit()?;
+            let base = repo
+                .merge_base(head.id(), commit.id())
+                .with_context(|| format!("no merge base between HEAD and {revision:?}"))?;
+            commit = repo.find_commit(base)?;
+        }
+        let tree = commit.tree()?;
+        let mut diff = repo.diff_tree_to_workdir_with_index(Some(&tree), None)?;
+        diff.find_similar(None)?;
+
+        let mut files = Vec::new();
+        for delta in diff.deltas() {
+            let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+                continue;
+            };
+            let Ok(old_text) = load_blob_text(&repo, delta.old_file().id()) else {
+                continue;
+            };
+            let new_text = match delta.status() {
+                git2::Delta::Deleted => None,
+                _ => match std::fs::read(workdir.join(path)).map(String::from_utf8) {
+                    Ok(Ok(text)) => Some(text),
+                    Ok(Err(_)) => continue,
+                    Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(error) => return Err(error.into()),
+                },
+            };
+            files.push(CommitFileDiff {
+                path: RepoPath::new(path.to_path_buf()),
+                old_text,
+                new_text,
+            });
+        }
+        Ok(files)
+    }
+
+    fn fetch(&self, env: &HashMap<String, String>) -> Result<()> {
+        self.run_remote_git_command(&["fetch"], env)
+    }
+
+    fn pull(&self, env: &HashMap<String, String>) -> Result<()> {
+        self.run_remote_git_command(&["pull"], env)
+    }
+
+    fn push(&self, env: &HashMap<String, String>) -> Result<()> {
+        let has_upstream = {
+            let repo = self.repository.lock();
+            let head = repo.head()?;
+            if !head.is_branch() {
+                return Err(anyhow!("cannot push a detached HEAD"));
+            }
+            let branch_name = head.shorthand().context("invalid branch name")?;
+            repo.find_branch(branch_name, BranchType::Local)?
+                .upstream()
+                .is_ok()
+        };
+        if has_upstream {
+            self.run_remote_git_command(&["push"], env)
+        } else {
+            self.run_remote_git_command(&["push", "--set-upstream", "origin", "HEAD"], env)
+        }
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        run_git_stash_list(&self.git_binary_path, &self.working_directory()?)
+    }
+
+    fn stash_save(&self, message: Option<&str>) -> Result<()> {
+        let mut args = vec!["stash", "push", "--include-untracked"];
+        if let Some(message) = message {
+            args.extend(["--message", message]);
+        }
+        run_git_command(&self.git_binary_path, &self.working_directory()?, args, &[])
+    }
+
+    fn stash_apply(&self, index: usize, pop: bool) -> Result<()> {
+        let subcommand = if pop { "pop" } else { "apply" };
+        let stash = format!("stash@{{{index}}}");
+        run_git_command(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            ["stash", subcommand, &stash],
+            &[],
+        )
+    }
+
+    fn stash_drop(&self, index: usize) -> Result<()> {
+        let stash = format!("stash@{{{index}}}");
+        run_git_command(
+            &self.git_binary_path,
+            &self.working_directory()?,
+            ["stash", "drop", &stash],
+            &[],
+        )
+    }
+}
+
+/// Loads the text of a blob, or `None` for the zero id of a missing file.
+/// Binary blobs are an error, since there is no text to show for them.
+fn load_blob_text(repo: &git2::Repository, id: git2::Oid) -> Result<Option<String>> {
+    if id.is_zero() {
+        return Ok(None);
+    }
+    let blob = repo.find_blob(id)?;
+    if blob.is_binary() {
+        return Err(anyhow!("binary file"));
+    }
+    Ok(Some(String::from_utf8_lossy(blob.content()).into_owned()))
+}
+
+impl RealGitRepository {
+    fn working_directory(&self) -> Result<PathBuf> {
+        self.repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")
+            .map(Path::to_path_buf)
+    }
+
+    /// Runs a git command that talks to a remote. Git must never prompt on the terminal
+    /// Zed was started from, so credentials can only be asked for through an askpass program
+    /// set in `env`.
+    fn run_remote_git_command(&self, args: &[&str], env: &HashMap<String, String>) -> Result<()> {
+        let mut command = git_command(&self.git_binary_path, &self.working_directory()?);
+        command.args(args).envs(env).env("GIT_TERMINAL_PROMPT", "0");
+        run_command(command)
+    }
+}
+
+fn run_git_command<'a>(
+    git_binary: &Path,
+    working_directory: &Path,
+    args: impl IntoIterator<Item = &'a str>,
+    paths: &[RepoPath],
+) -> Result<()> {
+    let mut command = git_command(git_binary, working_directory);
+    command
+        .args(args)
+        .args(paths.iter().map(|path| path.as_os_str()));
+    run_command(command)
+}
+
+fn git_command(git_binary: &Path, working_directory: &Path) -> Command {
+    let mut command = Command::new(git_binary);
+    command
+        .current_dir(working_directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+    }
+
+    command
+}
+
+fn run_command(mut command: Command) -> Result<()> {
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git process: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git process failed: {}", stderr.trim()));
+    }
+    Ok(())
 }
 
 #[derive(Debug, Clone, Default)]
@@ -213,9 +599,22 @@ pub struct FakeGitRepository {
 #[derive(Debug, Clone, Default)]
 pub struct FakeGitRepositoryState {
     pub index_contents: HashMap<PathBuf, String>,
+    /// The contents of paths in the HEAD commit.
+    pub committed_contents: HashMap<PathBuf, String>,
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
+    /// Paths whose `worktree_statuses` entry has been staged.
+    pub staged_paths: HashSet<RepoPath>,
+    pub commit_messages: Vec<String>,
+    /// The history returned by `log`, newest commit first.
+    pub log: Vec<CommitEntry>,
+    pub commit_diffs: HashMap<Oid, Vec<CommitFileDiff>>,
+    /// The changes returned by `revision_diff`, by revision.
+    pub revision_diffs: HashMap<String, Vec<CommitFileDiff>>,
     pub branch_name: Option<String>,
+    /// The remote operations that were run, such as `"push"`, oldest first.
+    pub remote_operations: Vec<&'static str>,
+    pub stashes: Vec<StashEntry>,
 }
 
 impl FakeGitRepository {
@@ -232,6 +631,11 @@ impl GitRepository for FakeGitRepository {
         state.index_contents.get(path).cloned()
     }
 
+    fn load_committed_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.committed_contents.get(path).cloned()
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -288,6 +692,176 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn staging_status(&self) -> Result<GitStagingStatus> {
+        let state = self.state.lock();
+        let mut entries = state
+            .worktree_statuses
+            .iter()
+            .map(|(repo_path, status)| {
+                let kind = match status {
+                    GitFileStatus::Added => GitChangeKind::Added,
+                    GitFileStatus::Modified => GitChangeKind::Modified,
+                    GitFileStatus::Conflict => GitChangeKind::Conflict,
+                };
+                let (index_status, worktree_status) =
+                    if kind == GitChangeKind::Conflict || state.staged_paths.contains(repo_path) {
+                        (Some(kind), None)
+                    } else if kind == GitChangeKind::Added {
+                        (None, Some(GitChangeKind::Untracked))
+                    } else {
+                        (None, Some(kind))
+                    };
+                GitStagingEntry {
+                    repo_path: repo_path.clone(),
+                    index_status,
+                    worktree_status,
+                }
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.repo_path.cmp(&b.repo_path));
+        Ok(GitStagingStatus {
+            entries: entries.into(),
+        })
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            if state.worktree_statuses.contains_key(path) {
+                state.staged_paths.insert(path.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for path in paths {
+            state.staged_paths.remove(path);
+        }
+        Ok(())
+    }
+
+    fn set_index_text(&self, path: &RepoPath, content: Option<String>) -> Result<()> {
+        let mut state = self.state.lock();
+        if let Some(content) = content {
+            state.index_contents.insert(path.to_path_buf(), content);
+        } else {
+            state.index_contents.remove(path.as_path());
+        }
+        Ok(())
+    }
+
+    fn commit(&self, message: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.staged_paths.is_empty() {
+            anyhow::bail!("nothing to commit");
+        }
+        let staged_paths = std::mem::take(&mut state.staged_paths);
+        state
+            .worktree_statuses
+            .retain(|path, _| !staged_paths.contains(path));
+        state.commit_messages.push(message.to_owned());
+        Ok(())
+    }
+
+    fn log(&self, query: &LogQuery) -> Result<Vec<CommitEntry>> {
+        let state = self.state.lock();
+        Ok(state
+            .log
+            .iter()
+            .filter(|commit| {
+                query.path.as_ref().map_or(true, |path| {
+                    state
+                        .commit_diffs
+                        .get(&commit.sha)
+                        .map_or(false, |files| files.iter().any(|file| &file.path == path))
+                })
+            })
+            .skip(query.skip)
+            .take(query.limit)
+            .cloned()
+            .collect())
+    }
+
+    fn commit_diff(&self, sha: Oid) -> Result<Vec<CommitFileDiff>> {
+        let state = self.state.lock();
+        state
+            .commit_diffs
+            .get(&sha)
+            .cloned()
+            .with_context(|| format!("no such commit {sha}"))
+    }
+
+    fn revision_diff(&self, revision: &str, _merge_base: bool) -> Result<Vec<CommitFileDiff>> {
+        let state = self.state.lock();
+        state
+            .revision_diffs
+            .get(revision)
+            .cloned()
+            .with_context(|| format!("unknown revision {revision:?}"))
+    }
+
+    fn fetch(&self, _env: &HashMap<String, String>) -> Result<()> {
+        self.state.lock().remote_operations.push("fetch");
+        Ok(())
+    }
+
+    fn pull(&self, _env: &HashMap<String, String>) -> Result<()> {
+        self.state.lock().remote_operations.push("pull");
+        Ok(())
+    }
+
+    fn push(&self, _env: &HashMap<String, String>) -> Result<()> {
+        self.state.lock().remote_operations.push("push");
+        Ok(())
+    }
+
+    fn stash_list(&self) -> Result<Vec<StashEntry>> {
+        Ok(self.state.lock().stashes.clone())
+    }
+
+    fn stash_save(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.worktree_statuses.is_empty() {
+            anyhow::bail!("no local changes to save");
+        }
+        state.worktree_statuses.clear();
+        state.staged_paths.clear();
+        let message = match message {
+            Some(message) => format!(
+                "On {}: {message}",
+                state.branch_name.as_deref().unwrap_or("HEAD")
+            ),
+            None => format!("WIP on {}", state.branch_name.as_deref().unwrap_or("HEAD")),
+        };
+        state.stashes.insert(0, StashEntry { index: 0, message });
+        renumber_stashes(&mut state.stashes);
+        Ok(())
+    }
+
+    fn stash_apply(&self, index: usize, pop: bool) -> Result<()> {
+        let mut state = self.state.lock();
+        if index >= state.stashes.len() {
+            anyhow::bail!("stash@{{{index}}} is not a valid reference");
+        }
+        if pop {
+            state.stashes.remove(index);
+            renumber_stashes(&mut state.stashes);
+        }
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize) -> Result<()> {
+        self.stash_apply(index, true)
+    }
+}
+
+fn renumber_stashes(stashes: &mut [StashEntry]) {
+    for (index, stash) in stashes.iter_mut().enumerate() {
+        stash.index = index;
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -0,0 +1,95 @@
+use anyhow::{anyhow, Context as _, Result};
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+const FIELD_SEPARATOR: char = '\0';
+const STASH_FORMAT: &str = "--format=%gd%x00%gs";
+
+/// An entry of the stash of a repository, as listed by `git stash list`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StashEntry {
+    /// The position of the entry in the stash, where 0 is the most recently stashed one.
+    pub index: usize,
+    /// The description of the entry, such as `WIP on main: 1234567 Subject`.
+    pub message: String,
+}
+
+pub(crate) fn run_git_stash_list(
+    git_binary: &Path,
+    working_directory: &Path,
+) -> Result<Vec<StashEntry>> {
+    let mut command = Command::new(git_binary);
+    command
+        .current_dir(working_directory)
+        .args(["--no-optional-locks", "stash", "list", STASH_FORMAT])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git stash process: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git stash process failed: {}", stderr.trim()));
+    }
+
+    parse_stash_list(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_stash_list(output: &str) -> Result<Vec<StashEntry>> {
+    output
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (selector, message) = line
+                .split_once(FIELD_SEPARATOR)
+                .context("missing stash message in git stash output")?;
+            let index = selector
+                .strip_prefix("stash@{")
+                .and_then(|selector| selector.strip_suffix('}'))
+                .and_then(|index| index.parse().ok())
+                .with_context(|| format!("invalid stash selector {selector:?}"))?;
+            Ok(StashEntry {
+                index,
+                message: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stash_list() {
+        let output = concat!(
+            "stash@{0}\0On main: before rebase\n",
+            "stash@{1}\0WIP on feature: 1234567 Add the thing\n",
+        );
+        assert_eq!(
+            parse_stash_list(output).unwrap(),
+            [
+                StashEntry {
+                    index: 0,
+                    message: "On main: before rebase".into(),
+                },
+                StashEntry {
+                    index: 1,
+                    message: "WIP on feature: 1234567 Add the thing".into(),
+                },
+            ]
+        );
+        assert_eq!(parse_stash_list("").unwrap(), []);
+        assert!(parse_stash_list("stash@{x}\0message\n").is_err());
+    }
+}
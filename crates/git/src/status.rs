@@ -1,5 +1,6 @@
 use crate::repository::{GitFileStatus, RepoPath};
 use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
 use std::{
     path::{Path, PathBuf},
     process::{Command, Stdio},
@@ -57,7 +58,11 @@ impl GitStatus {
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut entries = stdout
+        Ok(Self::parse(&stdout))
+    }
+
+    fn parse(porcelain_output: &str) -> Self {
+        let mut entries = porcelain_output
             .split('\0')
             .filter_map(|entry| {
                 if entry.is_char_boundary(3) {
@@ -68,6 +73,10 @@ impl GitStatus {
                         match status {
                             "A" | "??" => GitFileStatus::Added,
                             "M" => GitFileStatus::Modified,
+                            // Unmerged paths, as listed in git-status(1).
+                            "DD" | "AU" | "UD" | "UA" | "DU" | "AA" | "UU" => {
+                                GitFileStatus::Conflict
+                            }
                             _ => return None,
                         },
                     ))
@@ -77,9 +86,9 @@ impl GitStatus {
             })
             .collect::<Vec<_>>();
         entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
-        Ok(Self {
+        Self {
             entries: entries.into(),
-        })
+        }
     }
 
     pub fn get(&self, path: &Path) -> Option<GitFileStatus> {
@@ -97,3 +106,187 @@ impl Default for GitStatus {
         }
     }
 }
+
+/// How a file differs from HEAD in the index, or from the index in the working tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GitChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Conflict,
+    /// Only ever reported for the working tree, for files git does not track yet.
+    Untracked,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitStagingEntry {
+    pub repo_path: RepoPath,
+    /// The change that would be committed, if any.
+    pub index_status: Option<GitChangeKind>,
+    /// The change that is not staged yet, if any.
+    pub worktree_status: Option<GitChangeKind>,
+}
+
+impl GitStagingEntry {
+    pub fn is_staged(&self) -> bool {
+        self.index_status.is_some()
+    }
+
+    pub fn is_untracked(&self) -> bool {
+        self.worktree_status == Some(GitChangeKind::Untracked)
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        self.index_status == Some(GitChangeKind::Conflict)
+    }
+}
+
+/// The staged and unstaged changes of a whole repository, as shown in a git panel.
+#[derive(Clone, Debug)]
+pub struct GitStagingStatus {
+    pub entries: Arc<[GitStagingEntry]>,
+}
+
+impl GitStagingStatus {
+    pub(crate) fn new(git_binary: &Path, working_directory: &Path) -> Result<Self> {
+        let mut child = Command::new(git_binary);
+        child
+            .current_dir(working_directory)
+            .args([
+                "--no-optional-locks",
+                "status",
+                "--porcelain=v1",
+                "--untracked-files=all",
+                "--no-renames",
+                "-z",
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            child.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+        }
+
+        let output = child
+            .output()
+            .map_err(|e| anyhow!("Failed to start git status process: {}", e))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("git status process failed: {}", stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(Self::parse(&stdout))
+    }
+
+    fn parse(porcelain_output: &str) -> Self {
+        let mut entries = porcelain_output
+            .split('\0')
+            .filter_map(|entry| {
+                if !entry.is_char_boundary(3) || entry.len() <= 3 {
+                    return None;
+                }
+                let (status, path) = entry.split_at(3);
+                let mut status = status.chars();
+                let index = status.next()?;
+                let worktree = status.next()?;
+                let (index_status, worktree_status) = match (index, worktree) {
+                    ('?', '?') => (None, Some(GitChangeKind::Untracked)),
+                    ('!', '!') => return None,
+                    ('U', _) | (_, 'U') | ('A', 'A') | ('D', 'D') => {
+                        (Some(GitChangeKind::Conflict), None)
+                    }
+                    (index, worktree) => (change_kind(index), change_kind(worktree)),
+                };
+                Some(GitStagingEntry {
+                    repo_path: RepoPath(PathBuf::from(path)),
+                    index_status,
+                    worktree_status,
+                })
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.repo_path.cmp(&b.repo_path));
+        Self {
+            entries: entries.into(),
+        }
+    }
+}
+
+impl Default for GitStagingStatus {
+    fn default() -> Self {
+        Self {
+            entries: Arc::new([]),
+        }
+    }
+}
+
+fn change_kind(status: char) -> Option<GitChangeKind> {
+    match status {
+        'A' | 'C' => Some(GitChangeKind::Added),
+        'M' | 'T' | 'R' => Some(GitChangeKind::Modified),
+        'D' => Some(GitChangeKind::Deleted),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status_with_conflicts() {
+        let status = GitStatus::parse("UU a.txt\0 M b.txt\0AA c.txt\0?? d.txt\0DU e.txt\0");
+        assert_eq!(
+            status
+                .entries
+                .iter()
+                .map(|(path, status)| (path.to_str().unwrap(), *status))
+                .collect::<Vec<_>>(),
+            [
+                ("a.txt", GitFileStatus::Conflict),
+                ("b.txt", GitFileStatus::Modified),
+                ("c.txt", GitFileStatus::Conflict),
+                ("d.txt", GitFileStatus::Added),
+                ("e.txt", GitFileStatus::Conflict),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_staging_status() {
+        let status = GitStagingStatus::parse(
+            "M  a.txt\0 M b.txt\0MM c.txt\0?? d.txt\0D  e.txt\0UU f.txt\0A  g.txt\0",
+        );
+        let entries = status
+            .entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.repo_path.to_str().unwrap(),
+                    entry.index_status,
+                    entry.worktree_status,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                ("a.txt", Some(GitChangeKind::Modified), None),
+                ("b.txt", None, Some(GitChangeKind::Modified)),
+                (
+                    "c.txt",
+                    Some(GitChangeKind::Modified),
+                    Some(GitChangeKind::Modified)
+                ),
+                ("d.txt", None, Some(GitChangeKind::Untracked)),
+                ("e.txt", Some(GitChangeKind::Deleted), None),
+                ("f.txt", Some(GitChangeKind::Conflict), None),
+                ("g.txt", Some(GitChangeKind::Added), None),
+            ]
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use std::{
+    any::Any,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Context as _, Result};
+use editor::{actions::ExpandAllHunkDiffs, Editor, MultiBuffer};
+use git::log::CommitFileDiff;
+use gpui::{AppContext, AsyncWindowContext, Model, WeakView};
+use language::{Buffer, BufferId, Capability, Point, TextBuffer};
+use project::{Project, WorktreeId};
+use workspace::Workspace;
+
+const DIFF_CONTEXT_LINES: u32 = 3;
+
+/// Opens the given file changes in a read-only multibuffer, with an excerpt around each changed
+/// hunk that can be expanded to show more context, and the hunks' old contents shown inline.
+pub(crate) async fn open_file_diffs(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    worktree_id: WorktreeId,
+    repository_name: PathBuf,
+    title: String,
+    files: Vec<CommitFileDiff>,
+    cx: &mut AsyncWindowContext,
+) -> Result<()> {
+    let (languages, worktree_entity_id) = project.read_with(cx, |project, cx| {
+        let worktree = project
+            .worktree_for_id(worktree_id, cx)
+            .context("worktree was removed")?;
+        anyhow::Ok((
+            project.languages().clone(),
+            worktree.entity_id().as_u64() as usize,
+        ))
+    })??;
+
+    let mut buffers = Vec::with_capacity(files.len());
+    for file in files {
+        let language = languages.language_for_file_path(&file.path).await.ok();
+        let diff_file = Arc::new(DiffFile {
+            path: Arc::from(file.path.as_ref()),
+            full_path: repository_name.join(&file.path),
+            worktree_id: worktree_entity_id,
+            remote_worktree_id: worktree_id.to_proto(),
+            is_deleted: file.new_text.is_none(),
+        });
+        let buffer = cx.new_model(|cx| {
+            let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
+            let text_buffer = TextBuffer::new(0, buffer_id, file.new_text.unwrap_or_default());
+            let mut buffer = Buffer::build(
+                text_buffer,
+                Some(file.old_text.unwrap_or_default()),
+                Some(diff_file),
+                Capability::ReadOnly,
+            );
+            buffer.set_language_registry(languages.clone());
+            buffer.set_language(language, cx);
+            buffer
+        })?;
+        let diff_recalc = buffer.update(cx, |buffer, cx| buffer.git_diff_recalc(cx))?;
+        if let Some(diff_recalc) = diff_recalc {
+            diff_recalc.await;
+        }
+        buffers.push(buffer);
+    }
+
+    workspace.update(cx, |workspace, cx| {
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(0, Capability::ReadOnly).with_title(title);
+            for buffer in buffers {
+                let snapshot = buffer.read(cx).snapshot();
+                let max_point = snapshot.max_point();
+                let ranges = snapshot
+                    .git_diff_hunks_in_row_range(0..max_point.row + 1)
+                    .map(|hunk| {
+                        let start = Point::new(hunk.associated_range.start, 0);
+                        let end = Point::new(hunk.associated_range.end, 0);
+                        start.min(max_point)..end.min(max_point)
+                    })
+                    .collect::<Vec<_>>();
+                multibuffer.push_excerpts_with_context_lines(
+                    buffer,
+                    ranges,
+                    DIFF_CONTEXT_LINES,
+                    cx,
+                );
+            }
+            multibuffer
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+            editor.set_read_only(true);
+            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+            editor
+        });
+        workspace.add_item_to_active_pane(Box::new(editor), None, true, cx);
+    })
+}
+
+/// A buffer's file for the contents of a path shown in a diff, such as its contents at some
+/// commit, which isn't backed by a worktree.
+struct DiffFile {
+    path: Arc<Path>,
+    full_path: PathBuf,
+    worktree_id: usize,
+    remote_worktree_id: u64,
+    is_deleted: bool,
+}
+
+impl language::File for DiffFile {
+    fn as_local(&self) -> Option<&dyn language::LocalFile> {
+        None
+    }
+
+    fn mtime(&self) -> Option<std::time::SystemTime> {
+        None
+    }
+
+    fn path(&self) -> &Arc<Path> {
+        &self.path
+    }
+
+    fn full_path(&self, _: &AppContext) -> PathBuf {
+        self.full_path.clone()
+    }
+
+    fn file_name<'a>(&'a self, _: &'a AppContext) -> &'a OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    fn worktree_id(&self) -> usize {
+        self.worktree_id
+    }
+
+    fn is_deleted(&self) -> bool {
+        self.is_deleted
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn to_proto(&self, _: &AppContext) -> rpc::proto::File {
+        rpc::proto::File {
+            worktree_id: self.remote_worktree_id,
+            entry_id: None,
+            path: self.path.to_string_lossy().into(),
+            mtime: None,
+            is_deleted: self.is_deleted,
+        }
+    }
+
+    fn is_private(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,561 @@
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use editor::Editor;
+use git::{
+    log::{CommitEntry, LogQuery},
+    parse_git_remote_url,
+    repository::RepoPath,
+    BuildCommitPermalinkParams, GitHostingProviderRegistry, Oid,
+};
+use gpui::{
+    uniform_list, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Task,
+    UniformListScrollHandle, WeakView,
+};
+use language::Point;
+use project::{Project, ProjectEntryId, WorktreeId};
+use time::{OffsetDateTime, UtcOffset};
+use ui::{prelude::*, ListItem, Tooltip};
+use workspace::{
+    item::{Item, ItemEvent},
+    Workspace,
+};
+
+use crate::{file_diff::open_file_diffs, ViewFileHistory, ViewHistory, ViewLineHistory};
+
+const PAGE_SIZE: usize = 200;
+
+/// Which part of a repository's history a [`GitLogView`] lists.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HistoryScope {
+    Repository,
+    File(RepoPath),
+    Lines { path: RepoPath, rows: Range<u32> },
+}
+
+impl HistoryScope {
+    fn query(&self, skip: usize) -> LogQuery {
+        let (path, rows) = match self {
+            Self::Repository => (None, None),
+            Self::File(path) => (Some(path.clone()), None),
+            Self::Lines { path, rows } => (Some(path.clone()), Some(rows.clone())),
+        };
+        LogQuery {
+            path,
+            rows,
+            skip,
+            limit: PAGE_SIZE,
+        }
+    }
+}
+
+struct LogRow {
+    commit: CommitEntry,
+    graph: SharedString,
+}
+
+/// A commit log of one of the project's repositories, listing the whole history, the history
+/// of a single file or the history of some lines in it.
+pub struct GitLogView {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    focus_handle: FocusHandle,
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    repository_name: SharedString,
+    scope: HistoryScope,
+    rows: Vec<LogRow>,
+    graph: LogGraph,
+    remote_url: Option<String>,
+    selected_row: Option<usize>,
+    has_more: bool,
+    load_task: Option<Task<()>>,
+    diff_task: Task<()>,
+    error: Option<SharedString>,
+    local_offset: UtcOffset,
+    scroll_handle: UniformListScrollHandle,
+}
+
+impl GitLogView {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ViewHistory, cx| {
+            Self::deploy(workspace, false, false, cx);
+        });
+        workspace.register_action(|workspace, _: &ViewFileHistory, cx| {
+            Self::deploy(workspace, true, false, cx);
+        });
+        workspace.register_action(|workspace, _: &ViewLineHistory, cx| {
+            Self::deploy(workspace, true, true, cx);
+        });
+    }
+
+    /// Opens the history of the active editor's repository, narrowed down to its file or
+    /// to the rows of its newest selection.
+    fn deploy(
+        workspace: &mut Workspace,
+        for_file: bool,
+        for_selection: bool,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let active_file = workspace.active_item_as::<Editor>(cx).and_then(|editor| {
+            let editor = editor.read(cx);
+            let buffer = editor.buffer().read(cx).as_singleton()?;
+            let file = project::File::from_dyn(buffer.read(cx).file())?;
+            let selection = editor.selections.newest::<Point>(cx);
+            let mut rows = selection.start.row..selection.end.row + 1;
+            // A selection ending at the start of a line doesn't include that line.
+            if selection.end.column == 0 && selection.end.row > selection.start.row {
+                rows.end -= 1;
+            }
+            Some((file.worktree.clone(), file.path.clone(), rows))
+        });
+
+        let (worktree, path, rows) = match active_file {
+            Some(active_file) => active_file,
+            None if for_file => return,
+            None => match project.read(cx).visible_worktrees(cx).next() {
+                Some(worktree) => (worktree, Arc::from(Path::new("")), 0..0),
+                None => return,
+            },
+        };
+
+        let worktree = worktree.read(cx);
+        let snapshot = worktree.snapshot();
+        let repository = match snapshot.repository_for_path(&path) {
+            Some(repository) => repository,
+            None if for_file => return,
+            None => match snapshot.repositories().next() {
+                Some((_, repository)) => repository.clone(),
+                None => return,
+            },
+        };
+        let scope = if for_file {
+            let Some(repo_path) = repository.relativize(&snapshot, &path).ok() else {
+                return;
+            };
+            if for_selection {
+                HistoryScope::Lines {
+                    path: repo_path,
+                    rows,
+                }
+            } else {
+                HistoryScope::File(repo_path)
+            }
+        } else {
+            HistoryScope::Repository
+        };
+        let repository_name = snapshot
+            .repositories()
+            .find(|(_, entry)| entry.work_directory_id() == repository.work_directory_id())
+            .map(|(work_directory, _)| Path::new(worktree.root_name()).join(work_directory))
+            .unwrap_or_else(|| PathBuf::from(worktree.root_name()));
+
+        let workspace_handle = workspace.weak_handle();
+        let worktree_id = worktree.id();
+        let view = cx.new_view(|cx| {
+            Self::new(
+                workspace_handle,
+                project,
+                worktree_id,
+                repository.work_directory_id(),
+                repository_name.to_string_lossy().into_owned().into(),
+                scope,
+                cx,
+            )
+        });
+        workspace.add_item_to_active_pane(Box::new(view), None, true, cx);
+    }
+
+    fn new(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        repository_name: SharedString,
+        scope: HistoryScope,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let local_offset = chrono::Local::now().offset().local_minus_utc();
+        let mut this = Self {
+            workspace,
+            project,
+            focus_handle: cx.focus_handle(),
+            worktree_id,
+            work_directory_id,
+            repository_name,
+            scope,
+            rows: Vec::new(),
+            graph: LogGraph::default(),
+            remote_url: None,
+            selected_row: None,
+            has_more: true,
+            load_task: None,
+            diff_task: Task::ready(()),
+            error: None,
+            local_offset: UtcOffset::from_whole_seconds(local_offset).unwrap_or(UtcOffset::UTC),
+            scroll_handle: UniformListScrollHandle::new(),
+        };
+        this.load_more(cx);
+        this
+    }
+
+    fn load_more(&mut self, cx: &mut ViewContext<Self>) {
+        if self.load_task.is_some() || !self.has_more {
+            return;
+        }
+        let log = self.project.read(cx).git_log(
+            self.worktree_id,
+            self.work_directory_id,
+            self.scope.query(self.rows.len()),
+            cx,
+        );
+        self.load_task = Some(cx.spawn(|this, mut cx| async move {
+            let result = log.await;
+            this.update(&mut cx, |this, cx| {
+                this.load_task = None;
+                match result {
+                    Ok(log) => {
+                        this.has_more = log.commits.len() == PAGE_SIZE;
+                        this.remote_url = log.remote_url;
+                        // Filtered histories skip the commits in between, so only the
+                        // repository log has parents that line up into a graph.
+                        let show_graph = this.scope == HistoryScope::Repository;
+                        for commit in log.commits {
+                            let graph = if show_graph {
+                                this.graph.push(&commit)
+                            } else {
+                                SharedString::default()
+                            };
+                            this.rows.push(LogRow { commit, graph });
+                        }
+                        this.error = None;
+                    }
+                    Err(error) => {
+                        this.has_more = false;
+                        this.error = Some(format!("{error:#}").into());
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    fn commit_permalink(&self, sha: Oid, cx: &AppContext) -> Option<String> {
+        let remote_url = self.remote_url.as_deref()?;
+        let (provider, remote) =
+            parse_git_remote_url(GitHostingProviderRegistry::default_global(cx), remote_url)?;
+        let permalink = provider.build_commit_permalink(
+            &remote,
+            BuildCommitPermalinkParams {
+                sha: &sha.to_string(),
+            },
+        );
+        Some(permalink.to_string())
+    }
+
+    /// Opens the changes of the commit in the given row in a read-only multibuffer.
+    fn open_commit(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(row) = self.rows.get(ix) else {
+            return;
+        };
+        self.selected_row = Some(ix);
+        cx.notify();
+
+        let commit = row.commit.clone();
+        let diff = self.project.read(cx).git_commit_diff(
+            self.worktree_id,
+            self.work_directory_id,
+            commit.sha,
+            cx,
+        );
+        let workspace = self.workspace.clone();
+        let project = self.project.clone();
+        let worktree_id = self.worktree_id;
+        let repository_name = PathBuf::from(self.repository_name.as_ref());
+        self.diff_task = cx.spawn(|this, mut cx| async move {
+            let result = async {
+                let files = diff.await?;
+                let title = format!("{} {}", commit.sha.display_short(), commit.subject());
+                open_file_diffs(
+                    workspace,
+                    project,
+                    worktree_id,
+                    repository_name,
+                    title,
+                    files,
+                    &mut cx,
+                )
+                .await
+            }
+            .await;
+
+            this.update(&mut cx, |this, cx| {
+                this.error = result
+                    .with_context(|| format!("opening commit {}", commit.sha.display_short()))
+                    .err()
+                    .map(|error| format!("{error:#}").into());
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn title(&self) -> String {
+        match &self.scope {
+            HistoryScope::Repository => format!("History of {}", self.repository_name),
+            HistoryScope::File(path) => format!("History of {}", path.display()),
+            HistoryScope::Lines { path, rows } => format!(
+                "History of {}:{}-{}",
+                path.display(),
+                rows.start + 1,
+                rows.end.max(rows.start + 1)
+            ),
+        }
+    }
+
+    fn format_timestamp(&self, timestamp: i64) -> String {
+        match OffsetDateTime::from_unix_timestamp(timestamp) {
+            Ok(timestamp) => time_format::format_localized_timestamp(
+                timestamp,
+                OffsetDateTime::now_utc(),
+                self.local_offset,
+                time_format::TimestampFormat::Relative,
+            ),
+            Err(_) => "Unknown date".to_string(),
+        }
+    }
+
+    fn render_row(&self, ix: usize, cx: &mut ViewContext<Self>) -> ListItem {
+        let row = &self.rows[ix];
+        let commit = &row.commit;
+        let permalink = self.commit_permalink(commit.sha, cx);
+        ListItem::new(("git-log-row", ix))
+            .selected(self.selected_row == Some(ix))
+            .on_click(cx.listener(move |this, _, cx| this.open_commit(ix, cx)))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .when(!row.graph.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .font_buffer(cx)
+                                .text_color(Color::Accent.color(cx))
+                                .child(row.graph.clone()),
+                        )
+                    })
+                    .child(
+                        Label::new(commit.sha.display_short())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(Label::new(commit.subject().to_string()).single_line())
+                    .child(
+                        Label::new(commit.author_name.clone())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        Label::new(self.format_timestamp(commit.timestamp))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .end_slot::<IconButton>(permalink.map(|permalink| {
+                IconButton::new(("git-log-permalink", ix), IconName::ExternalLink)
+                    .icon_size(IconSize::XSmall)
+                    .tooltip(|cx| Tooltip::text("Open Commit on Remote", cx))
+                    .on_click(move |_, cx| cx.open_url(&permalink))
+            }))
+    }
+}
+
+impl EventEmitter<()> for GitLogView {}
+
+impl FocusableView for GitLogView {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for GitLogView {
+    type Event = ();
+
+    fn to_item_events(_: &Self::Event, _: impl FnMut(ItemEvent)) {}
+
+    fn tab_content_text(&self, _: &WindowContext) -> Option<SharedString> {
+        Some(self.title().into())
+    }
+
+    fn tab_icon(&self, _: &WindowContext) -> Option<Icon> {
+        Some(Icon::new(IconName::FileGit))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl Render for GitLogView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let row_count = self.rows.len();
+        let footer = if self.load_task.is_some() {
+            Some(
+                h_flex()
+                    .p_2()
+                    .justify_center()
+                    .child(Label::new("Loading…").color(Color::Muted)),
+            )
+        } else if self.has_more && row_count > 0 {
+            Some(
+                h_flex().p_2().justify_center().child(
+                    Button::new("git-log-load-more", "Load More")
+                        .on_click(cx.listener(|this, _, cx| this.load_more(cx))),
+                ),
+            )
+        } else if row_count == 0 && self.error.is_none() {
+            Some(
+                h_flex()
+                    .p_2()
+                    .justify_center()
+                    .child(Label::new("No commits").color(Color::Muted)),
+            )
+        } else {
+            None
+        };
+
+        v_flex()
+            .id("git-log-view")
+            .size_full()
+            .track_focus(&self.focus_handle)
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(self.title())),
+            )
+            .children(self.error.clone().map(|error| {
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .child(Label::new(error).size(LabelSize::Small).color(Color::Error))
+            }))
+            .child(
+                uniform_list(
+                    cx.view().clone(),
+                    "git-log",
+                    row_count,
+                    |this, range, cx| range.map(|ix| this.render_row(ix, cx)).collect(),
+                )
+                .flex_1()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+            .children(footer)
+    }
+}
+
+/// Lays out the commits of a log, newest first, into lanes, one per branch of history
+/// that is still waiting for its next commit.
+#[derive(Default)]
+struct LogGraph {
+    lanes: Vec<Option<Oid>>,
+}
+
+impl LogGraph {
+    /// Places the next commit of the log and returns its row of the graph.
+    fn push(&mut self, commit: &CommitEntry) -> SharedString {
+        let column = self
+            .lanes
+            .iter()
+            .position(|lane| *lane == Some(commit.sha))
+            .or_else(|| self.lanes.iter().position(Option::is_none))
+            .unwrap_or_else(|| {
+                self.lanes.push(None);
+                self.lanes.len() - 1
+            });
+
+        let mut row = String::new();
+        for (ix, lane) in self.lanes.iter().enumerate() {
+            if ix > 0 {
+                row.push(' ');
+            }
+            row.push(if ix == column {
+                if commit.parents.len() > 1 {
+                    '○'
+                } else {
+                    '●'
+                }
+            } else if lane.is_some() {
+                '│'
+            } else {
+                ' '
+            });
+        }
+
+        // Other branches that were waiting for this commit join its lane.
+        for lane in &mut self.lanes {
+            if *lane == Some(commit.sha) {
+                *lane = None;
+            }
+        }
+        let mut parents = commit.parents.iter();
+        self.lanes[column] = parents.next().copied();
+        for parent in parents {
+            if self.lanes.contains(&Some(*parent)) {
+                continue;
+            }
+            match self.lanes.iter().position(Option::is_none) {
+                Some(ix) => self.lanes[ix] = Some(*parent),
+                None => self.lanes.push(Some(*parent)),
+            }
+        }
+        while self.lanes.last() == Some(&None) {
+            self.lanes.pop();
+        }
+
+        row.trim_end().to_string().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, parents: &[&str]) -> CommitEntry {
+        let oid = |sha: &str| sha.repeat(40).parse::<Oid>().unwrap();
+        CommitEntry {
+            sha: oid(sha),
+            parents: parents.iter().map(|parent| oid(parent)).collect(),
+            author_name: String::new(),
+            author_email: String::new(),
+            timestamp: 0,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_log_graph() {
+        let mut graph = LogGraph::default();
+        let rows = [
+            commit("e", &["d", "c"]),
+            commit("d", &["b"]),
+            commit("c", &["b"]),
+            commit("b", &["a"]),
+            commit("a", &[]),
+        ]
+        .iter()
+        .map(|commit| graph.push(commit).to_string())
+        .collect::<Vec<_>>();
+        assert_eq!(rows, ["○", "● │", "│ ●", "●", "●"]);
+        assert!(graph.lanes.is_empty());
+    }
+}
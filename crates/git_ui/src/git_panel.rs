@@ -0,0 +1,788 @@
+use std::{path::Path, sync::Arc, time::Duration};
+
+use anyhow::Context as _;
+use db::kvp::KEY_VALUE_STORE;
+use editor::Editor;
+use git::{
+    repository::RepoPath,
+    status::{GitChangeKind, GitStagingEntry, GitStagingStatus},
+};
+use gpui::{
+    Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView, KeyContext,
+    Model, Stateful, Subscription, Task, View, WeakView,
+};
+use project::{
+    Fs, GitOperation, GitRemoteOperation, Project, ProjectEntryId, ProjectPath, WorktreeId,
+};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+use crate::{
+    git_panel_settings::{GitPanelDockPosition, GitPanelSettings},
+    remote_operations::run_git_remote_operation,
+    stash_list::StashListModal,
+    Commit, Fetch, Pull, Push, StageAll, StashChanges, ToggleFocus, UnstageAll, ViewStashes,
+};
+
+const GIT_PANEL_KEY: &str = "GitPanel";
+const UPDATE_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// A git repository of one of the project's worktrees, with its changes as of the last update.
+struct Repository {
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    name: SharedString,
+    branch: Option<Arc<str>>,
+    entries: Arc<[GitStagingEntry]>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Section {
+    Staged,
+    Unstaged,
+    Untracked,
+}
+
+impl Section {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Staged => "Staged Changes",
+            Self::Unstaged => "Changes",
+            Self::Untracked => "Untracked Files",
+        }
+    }
+
+    fn id(&self) -> &'static str {
+        match self {
+            Self::Staged => "git-staged-entry",
+            Self::Unstaged => "git-unstaged-entry",
+            Self::Untracked => "git-untracked-entry",
+        }
+    }
+
+    fn contains(&self, entry: &GitStagingEntry) -> bool {
+        match self {
+            Self::Staged => entry.is_staged(),
+            Self::Unstaged => entry.worktree_status.is_some() && !entry.is_untracked(),
+            Self::Untracked => entry.is_untracked(),
+        }
+    }
+
+    fn status(&self, entry: &GitStagingEntry) -> Option<GitChangeKind> {
+        match self {
+            Self::Staged => entry.index_status,
+            Self::Unstaged | Self::Untracked => entry.worktree_status,
+        }
+    }
+}
+
+pub struct GitPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    active: bool,
+    focus_handle: FocusHandle,
+    pending_serialization: Task<Option<()>>,
+    repositories: Vec<Repository>,
+    selected_repository: Option<(WorktreeId, ProjectEntryId)>,
+    commit_editor: View<Editor>,
+    pending_operations: usize,
+    error: Option<SharedString>,
+    update_task: Task<()>,
+    _subscription: Subscription,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGitPanel {
+    width: Option<Pixels>,
+    active: Option<bool>,
+}
+
+impl GitPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(GIT_PANEL_KEY) })
+            .await
+            .context("loading git panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedGitPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.active = serialized_panel.active.unwrap_or(false);
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let workspace_handle = workspace.weak_handle();
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        cx.new_view(|cx| {
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(6, cx);
+                editor.set_placeholder_text("Commit message", cx);
+                editor
+            });
+            let subscription = cx.subscribe(&project, |panel, _, event, cx| match event {
+                project::Event::WorktreeAdded
+                | project::Event::WorktreeRemoved(_)
+                | project::Event::WorktreeUpdatedEntries(_, _)
+                | project::Event::WorktreeUpdatedGitRepositories => panel.schedule_update(cx),
+                _ => {}
+            });
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                width: None,
+                active: false,
+                focus_handle: cx.focus_handle(),
+                pending_serialization: Task::ready(None),
+                repositories: Vec::new(),
+                selected_repository: None,
+                commit_editor,
+                pending_operations: 0,
+                error: None,
+                update_task: Task::ready(()),
+                _subscription: subscription,
+            };
+            this.schedule_update(cx);
+            this
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let active = Some(self.active);
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        GIT_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedGitPanel { width, active })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self, _: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("GitPanel");
+        dispatch_context
+    }
+
+    /// Refetches the changes of every repository in the project, coalescing bursts of
+    /// worktree events into a single update.
+    fn schedule_update(&mut self, cx: &mut ViewContext<Self>) {
+        self.update_task = cx.spawn(|panel, mut cx| async move {
+            cx.background_executor().timer(UPDATE_DEBOUNCE).await;
+            let Ok(repositories) = panel.update(&mut cx, |panel, cx| panel.fetch_repositories(cx))
+            else {
+                return;
+            };
+
+            let mut updated_repositories = Vec::with_capacity(repositories.len());
+            for (mut repository, status) in repositories {
+                if let Some(status) = status.await.log_err() {
+                    repository.entries = status.entries;
+                }
+                updated_repositories.push(repository);
+            }
+
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.repositories = updated_repositories;
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+
+    fn fetch_repositories(
+        &self,
+        cx: &AppContext,
+    ) -> Vec<(Repository, Task<anyhow::Result<GitStagingStatus>>)> {
+        let project = self.project.read(cx);
+        let mut repositories = Vec::new();
+        for worktree in project.visible_worktrees(cx) {
+            let worktree = worktree.read(cx);
+            let snapshot = worktree.snapshot();
+            for (work_directory, repository_entry) in snapshot.repositories() {
+                let name = Path::new(worktree.root_name()).join(work_directory);
+                let work_directory_id = repository_entry.work_directory_id();
+                let status = project.git_staging_status(worktree.id(), work_directory_id, cx);
+                repositories.push((
+                    Repository {
+                        worktree_id: worktree.id(),
+                        work_directory_id,
+                        name: name.to_string_lossy().into_owned().into(),
+                        branch: repository_entry.branch(),
+                        entries: Arc::new([]),
+                    },
+                    status,
+                ));
+            }
+        }
+        repositories
+    }
+
+    fn selected_repository(&self) -> Option<&Repository> {
+        self.selected_repository
+            .and_then(|(worktree_id, work_directory_id)| {
+                self.repositories.iter().find(|repository| {
+                    repository.worktree_id == worktree_id
+                        && repository.work_directory_id == work_directory_id
+                })
+            })
+            .or_else(|| self.repositories.first())
+    }
+
+    fn select_repository(
+        &mut self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.selected_repository = Some((worktree_id, work_directory_id));
+        self.error = None;
+        cx.notify();
+    }
+
+    fn run_operation(&mut self, operation: GitOperation, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.selected_repository() else {
+            return;
+        };
+        let uses_message = matches!(
+            operation,
+            GitOperation::Commit { .. } | GitOperation::StashSave { message: Some(_) }
+        );
+        let task = self.project.read(cx).run_git_operation(
+            repository.worktree_id,
+            repository.work_directory_id,
+            operation,
+            cx,
+        );
+        self.pending_operations += 1;
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(|panel, mut cx| async move {
+            let result = task.await;
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.pending_operations -= 1;
+                    match result {
+                        Ok(()) => {
+                            if uses_message {
+                                panel
+                                    .commit_editor
+                                    .update(cx, |editor, cx| editor.clear(cx));
+                            }
+                        }
+                        Err(error) => panel.error = Some(error.to_string().into()),
+                    }
+                    panel.schedule_update(cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn run_remote_operation(&mut self, operation: GitRemoteOperation, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.selected_repository() else {
+            return;
+        };
+        let task = run_git_remote_operation(
+            self.workspace.clone(),
+            self.project.clone(),
+            repository.worktree_id,
+            repository.work_directory_id,
+            operation,
+            cx,
+        );
+        self.pending_operations += 1;
+        self.error = None;
+        cx.notify();
+
+        cx.spawn(|panel, mut cx| async move {
+            let result = task.await;
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.pending_operations -= 1;
+                    if let Err(error) = result {
+                        panel.error =
+                            Some(format!("Failed to {}: {error}", operation.name()).into());
+                    }
+                    panel.schedule_update(cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+
+    fn fetch(&mut self, _: &Fetch, cx: &mut ViewContext<Self>) {
+        self.run_remote_operation(GitRemoteOperation::Fetch, cx);
+    }
+
+    fn pull(&mut self, _: &Pull, cx: &mut ViewContext<Self>) {
+        self.run_remote_operation(GitRemoteOperation::Pull, cx);
+    }
+
+    fn push(&mut self, _: &Push, cx: &mut ViewContext<Self>) {
+        self.run_remote_operation(GitRemoteOperation::Push, cx);
+    }
+
+    /// Stashes the changes of the selected repository, using the commit message, if any,
+    /// as the stash message.
+    fn stash_changes(&mut self, _: &StashChanges, cx: &mut ViewContext<Self>) {
+        let message = self.commit_editor.read(cx).text(cx);
+        let message = message.trim();
+        let message = (!message.is_empty()).then(|| message.to_string());
+        self.run_operation(GitOperation::StashSave { message }, cx);
+    }
+
+    fn view_stashes(&mut self, _: &ViewStashes, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.selected_repository() else {
+            return;
+        };
+        let worktree_id = repository.worktree_id;
+        let work_directory_id = repository.work_directory_id;
+        self.workspace
+            .update(cx, |workspace, cx| {
+                StashListModal::open(workspace, worktree_id, work_directory_id, cx);
+            })
+            .ok();
+    }
+
+    fn section_paths(&self, section: Section) -> Vec<RepoPath> {
+        self.selected_repository()
+            .map(|repository| {
+                repository
+                    .entries
+                    .iter()
+                    .filter(|entry| section.contains(entry))
+                    .map(|entry| entry.repo_path.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let mut paths = self.section_paths(Section::Unstaged);
+        paths.extend(self.section_paths(Section::Untracked));
+        if !paths.is_empty() {
+            self.run_operation(GitOperation::Stage(paths), cx);
+        }
+    }
+
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let paths = self.section_paths(Section::Staged);
+        if !paths.is_empty() {
+            self.run_operation(GitOperation::Unstage(paths), cx);
+        }
+    }
+
+    fn commit(&mut self, _: &Commit, cx: &mut ViewContext<Self>) {
+        let has_staged_changes = self.selected_repository().map_or(false, |repository| {
+            repository.entries.iter().any(|entry| entry.is_staged())
+        });
+        if !has_staged_changes {
+            self.error = Some("There are no staged changes to commit".into());
+            cx.notify();
+            return;
+        }
+        let message = self.commit_editor.read(cx).text(cx);
+        let message = message.trim();
+        if message.is_empty() {
+            self.error = Some("Enter a commit message".into());
+            cx.notify();
+            return;
+        }
+        self.run_operation(
+            GitOperation::Commit {
+                message: message.to_string(),
+            },
+            cx,
+        );
+    }
+
+    fn open_entry(&mut self, repo_path: &RepoPath, cx: &mut ViewContext<Self>) {
+        let Some(repository) = self.selected_repository() else {
+            return;
+        };
+        let worktree_id = repository.worktree_id;
+        let work_directory_id = repository.work_directory_id;
+        let Some(path) = self
+            .project
+            .read(cx)
+            .worktree_for_id(worktree_id, cx)
+            .and_then(|worktree| {
+                let snapshot = worktree.read(cx).snapshot();
+                let (_, repository_entry) = snapshot
+                    .repositories()
+                    .find(|(_, entry)| entry.work_directory_id() == work_directory_id)?;
+                repository_entry.unrelativize(&snapshot, repo_path)
+            })
+        else {
+            return;
+        };
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .open_path(ProjectPath { worktree_id, path }, None, true, cx)
+                    .detach_and_log_err(cx);
+            })
+            .ok();
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> Div {
+        let selected = self
+            .selected_repository()
+            .map(|repository| (repository.worktree_id, repository.work_directory_id));
+        h_flex()
+            .px_2()
+            .py_1()
+            .gap_1()
+            .flex_wrap()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .children(
+                self.repositories
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, repository)| {
+                        let worktree_id = repository.worktree_id;
+                        let work_directory_id = repository.work_directory_id;
+                        let label = match &repository.branch {
+                            Some(branch) => format!("{} ({branch})", repository.name),
+                            None => repository.name.to_string(),
+                        };
+                        Button::new(("git-repository", ix), label)
+                            .label_size(LabelSize::Small)
+                            .selected(selected == Some((worktree_id, work_directory_id)))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.select_repository(worktree_id, work_directory_id, cx)
+                            }))
+                    }),
+            )
+    }
+
+    fn render_section(
+        &self,
+        repository: &Repository,
+        section: Section,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<Div> {
+        let entries = repository
+            .entries
+            .iter()
+            .filter(|entry| section.contains(entry))
+            .collect::<Vec<_>>();
+        if entries.is_empty() {
+            return None;
+        }
+
+        let section_button = match section {
+            Section::Staged => IconButton::new("git-unstage-all", IconName::Dash)
+                .tooltip(|cx| Tooltip::for_action("Unstage All", &UnstageAll, cx)),
+            Section::Unstaged | Section::Untracked => {
+                IconButton::new(("git-stage-all", section as usize), IconName::Plus)
+                    .tooltip(|cx| Tooltip::text("Stage All", cx))
+            }
+        };
+        let header = h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                Label::new(format!("{} ({})", section.title(), entries.len()))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(
+                section_button
+                    .icon_size(IconSize::XSmall)
+                    .on_click(cx.listener(move |this, _, cx| {
+                        let paths = this.section_paths(section);
+                        let operation = match section {
+                            Section::Staged => GitOperation::Unstage(paths),
+                            Section::Unstaged | Section::Untracked => GitOperation::Stage(paths),
+                        };
+                        this.run_operation(operation, cx);
+                    })),
+            );
+
+        let rows = entries.into_iter().enumerate().map(|(ix, entry)| {
+            let repo_path = entry.repo_path.clone();
+            let (status_label, status_color) = match section.status(entry) {
+                Some(GitChangeKind::Added) => ("A", Color::Created),
+                Some(GitChangeKind::Modified) => ("M", Color::Modified),
+                Some(GitChangeKind::Deleted) => ("D", Color::Deleted),
+                Some(GitChangeKind::Conflict) => ("!", Color::Conflict),
+                Some(GitChangeKind::Untracked) => ("U", Color::Created),
+                None => ("", Color::Muted),
+            };
+            let (button_icon, button_tooltip) = match section {
+                Section::Staged => (IconName::Dash, "Unstage"),
+                Section::Unstaged | Section::Untracked => (IconName::Plus, "Stage"),
+            };
+            let open_path = repo_path.clone();
+            ListItem::new((section.id(), ix))
+                .on_click(cx.listener(move |this, _, cx| this.open_entry(&open_path, cx)))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(
+                            Label::new(status_label)
+                                .size(LabelSize::Small)
+                                .color(status_color),
+                        )
+                        .child(
+                            Label::new(repo_path.to_string_lossy().into_owned())
+                                .size(LabelSize::Small)
+                                .single_line(),
+                        ),
+                )
+                .end_slot(
+                    IconButton::new("git-entry-button", button_icon)
+                        .icon_size(IconSize::XSmall)
+                        .tooltip(move |cx| Tooltip::text(button_tooltip, cx))
+                        .on_click(cx.listener(move |this, _, cx| {
+                            let paths = vec![repo_path.clone()];
+                            let operation = match section {
+                                Section::Staged => GitOperation::Unstage(paths),
+                                Section::Unstaged | Section::Untracked => {
+                                    GitOperation::Stage(paths)
+                                }
+                            };
+                            this.run_operation(operation, cx);
+                        })),
+                )
+        });
+
+        Some(v_flex().child(header).children(rows))
+    }
+
+    fn render_entries(&self, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        let list = v_flex().id("git-entries").flex_1().overflow_y_scroll();
+        let Some(repository) = self.selected_repository() else {
+            return list.child(
+                h_flex()
+                    .p_2()
+                    .justify_center()
+                    .child(Label::new("No git repositories in the project").color(Color::Muted)),
+            );
+        };
+        if repository.entries.is_empty() {
+            return list.child(
+                h_flex()
+                    .p_2()
+                    .justify_center()
+                    .child(Label::new("No changes").color(Color::Muted)),
+            );
+        }
+        list.children(self.render_section(repository, Section::Staged, cx))
+            .children(self.render_section(repository, Section::Unstaged, cx))
+            .children(self.render_section(repository, Section::Untracked, cx))
+    }
+
+    fn render_remote_buttons(&self, cx: &mut ViewContext<Self>) -> Div {
+        let disabled = self.pending_operations > 0 || self.selected_repository().is_none();
+        h_flex()
+            .gap_0p5()
+            .child(
+                IconButton::new("git-fetch", IconName::ArrowCircle)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(|cx| Tooltip::for_action("Fetch", &Fetch, cx))
+                    .on_click(cx.listener(|this, _, cx| this.fetch(&Fetch, cx))),
+            )
+            .child(
+                IconButton::new("git-pull", IconName::ArrowDown)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(|cx| Tooltip::for_action("Pull", &Pull, cx))
+                    .on_click(cx.listener(|this, _, cx| this.pull(&Pull, cx))),
+            )
+            .child(
+                IconButton::new("git-push", IconName::ArrowUp)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(|cx| Tooltip::for_action("Push", &Push, cx))
+                    .on_click(cx.listener(|this, _, cx| this.push(&Push, cx))),
+            )
+            .child(
+                IconButton::new("git-stash", IconName::Save)
+                    .icon_size(IconSize::Small)
+                    .disabled(disabled)
+                    .tooltip(|cx| Tooltip::for_action("Stash Changes", &StashChanges, cx))
+                    .on_click(cx.listener(|this, _, cx| this.stash_changes(&StashChanges, cx))),
+            )
+            .child(
+                IconButton::new("git-stashes", IconName::ListTree)
+                    .icon_size(IconSize::Small)
+                    .disabled(self.selected_repository().is_none())
+                    .tooltip(|cx| Tooltip::for_action("View Stashes", &ViewStashes, cx))
+                    .on_click(cx.listener(|this, _, cx| this.view_stashes(&ViewStashes, cx))),
+            )
+    }
+
+    fn render_commit_area(&self, cx: &mut ViewContext<Self>) -> Div {
+        let can_commit = self.pending_operations == 0
+            && self.selected_repository().map_or(false, |repository| {
+                repository.entries.iter().any(|entry| entry.is_staged())
+            });
+        v_flex()
+            .p_2()
+            .gap_1()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .children(
+                self.error
+                    .clone()
+                    .map(|error| Label::new(error).size(LabelSize::Small).color(Color::Error)),
+            )
+            .child(
+                div()
+                    .px_1()
+                    .py_1()
+                    .border_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .rounded_md()
+                    .child(self.commit_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(self.render_remote_buttons(cx))
+                    .child(
+                        Button::new("git-commit", "Commit")
+                            .label_size(LabelSize::Small)
+                            .disabled(!can_commit)
+                            .tooltip(|cx| Tooltip::for_action("Commit Staged Changes", &Commit, cx))
+                            .on_click(cx.listener(|this, _, cx| this.commit(&Commit, cx))),
+                    ),
+            )
+    }
+}
+
+impl Panel for GitPanel {
+    fn persistent_name() -> &'static str {
+        "Git Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match GitPanelSettings::get_global(cx).dock {
+            GitPanelDockPosition::Left => DockPosition::Left,
+            GitPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<GitPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => GitPanelDockPosition::Left,
+                    DockPosition::Right => GitPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| GitPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        GitPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::FileGit)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Git Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _: &WindowContext) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        self.active = active;
+        self.serialize(cx);
+    }
+}
+
+impl FocusableView for GitPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for GitPanel {}
+
+impl Render for GitPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .id("git-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .on_action(cx.listener(Self::stage_all))
+            .on_action(cx.listener(Self::unstage_all))
+            .on_action(cx.listener(Self::commit))
+            .on_action(cx.listener(Self::fetch))
+            .on_action(cx.listener(Self::pull))
+            .on_action(cx.listener(Self::push))
+            .on_action(cx.listener(Self::stash_changes))
+            .on_action(cx.listener(Self::view_stashes))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx))
+            .child(self.render_entries(cx))
+            .child(self.render_commit_area(cx))
+    }
+}
@@ -0,0 +1,82 @@
+mod file_diff;
+mod git_log_view;
+mod git_panel;
+mod git_panel_settings;
+mod remote_operations;
+mod revision_diff;
+mod stash_list;
+
+use std::{path::Path, sync::Arc};
+
+use git_panel_settings::GitPanelSettings;
+use gpui::{actions, AppContext, Model};
+use project::{RepositoryEntry, Worktree};
+use settings::Settings;
+use workspace::Workspace;
+
+pub use git_log_view::GitLogView;
+pub use git_panel::GitPanel;
+pub use remote_operations::AskPassModal;
+pub use revision_diff::RevisionDiffModal;
+pub use stash_list::StashListModal;
+
+actions!(git_panel, [ToggleFocus, StageAll, UnstageAll, Commit]);
+actions!(
+    git,
+    [
+        ViewHistory,
+        ViewFileHistory,
+        ViewLineHistory,
+        DiffAgainstRevision,
+        Fetch,
+        Pull,
+        Push,
+        StashChanges,
+        ViewStashes
+    ]
+);
+
+pub fn init_settings(cx: &mut AppContext) {
+    GitPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<GitPanel>(cx);
+        });
+        GitLogView::register(workspace, cx);
+        RevisionDiffModal::register(workspace, cx);
+        remote_operations::register(workspace, cx);
+        StashListModal::register(workspace, cx);
+    })
+    .detach();
+}
+
+/// The repository of the active editor's file, or the first repository of the project
+/// if there is none.
+fn active_repository(
+    workspace: &Workspace,
+    cx: &AppContext,
+) -> Option<(Model<Worktree>, RepositoryEntry)> {
+    let project = workspace.project().read(cx);
+    let active_file = workspace
+        .active_item_as::<editor::Editor>(cx)
+        .and_then(|editor| {
+            let buffer = editor.read(cx).buffer().read(cx).as_singleton()?;
+            let file = project::File::from_dyn(buffer.read(cx).file())?;
+            Some((file.worktree.clone(), file.path.clone()))
+        });
+    let (worktree, path) = active_file.or_else(|| {
+        let worktree = project.visible_worktrees(cx).next()?;
+        Some((worktree, Arc::from(Path::new(""))))
+    })?;
+
+    let snapshot = worktree.read(cx).snapshot();
+    let repository = snapshot
+        .repository_for_path(&path)
+        .or_else(|| Some(snapshot.repositories().next()?.1.clone()))?;
+    Some((worktree, repository))
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use askpass::{AskPassDelegate, AskPassPrompt};
+use editor::Editor;
+use futures::StreamExt as _;
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Task, View, WeakView,
+};
+use project::{GitOperation, GitRemoteOperation, Project, ProjectEntryId, WorktreeId};
+use ui::prelude::*;
+use workspace::{notifications::NotificationId, ModalView, Toast, Workspace};
+
+use crate::{active_repository, Fetch, Pull, Push, StashChanges};
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace, _: &Fetch, cx| {
+        run_for_active_repository(workspace, GitRemoteOperation::Fetch, cx);
+    });
+    workspace.register_action(|workspace, _: &Pull, cx| {
+        run_for_active_repository(workspace, GitRemoteOperation::Pull, cx);
+    });
+    workspace.register_action(|workspace, _: &Push, cx| {
+        run_for_active_repository(workspace, GitRemoteOperation::Push, cx);
+    });
+    workspace.register_action(|workspace, _: &StashChanges, cx| {
+        stash_active_repository(workspace, cx);
+    });
+}
+
+fn run_for_active_repository(
+    workspace: &mut Workspace,
+    operation: GitRemoteOperation,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some((worktree, repository)) = active_repository(workspace, cx) else {
+        return;
+    };
+    let task = run_git_remote_operation(
+        workspace.weak_handle(),
+        workspace.project().clone(),
+        worktree.read(cx).id(),
+        repository.work_directory_id(),
+        operation,
+        cx,
+    );
+    cx.spawn(|workspace, mut cx| async move {
+        if let Err(error) = task.await {
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    show_error(
+                        workspace,
+                        &format!("Failed to {}", operation.name()),
+                        error,
+                        cx,
+                    )
+                })
+                .ok();
+        }
+    })
+    .detach();
+}
+
+fn stash_active_repository(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some((worktree, repository)) = active_repository(workspace, cx) else {
+        return;
+    };
+    let task = workspace.project().read(cx).run_git_operation(
+        worktree.read(cx).id(),
+        repository.work_directory_id(),
+        GitOperation::StashSave { message: None },
+        cx,
+    );
+    cx.spawn(|workspace, mut cx| async move {
+        if let Err(error) = task.await {
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    show_error(workspace, "Failed to stash changes", error, cx)
+                })
+                .ok();
+        }
+    })
+    .detach();
+}
+
+pub(crate) fn show_error(
+    workspace: &mut Workspace,
+    title: &str,
+    error: anyhow::Error,
+    cx: &mut ViewContext<Workspace>,
+) {
+    struct GitOperationError;
+
+    let id = NotificationId::unique::<GitOperationError>();
+    workspace.show_toast(Toast::new(id, format!("{title}: {error:#}")), cx);
+}
+
+/// Fetches, pulls or pushes a repository of the project, asking for credentials
+/// in an [`AskPassModal`] whenever git or ssh need them.
+pub(crate) fn run_git_remote_operation(
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    operation: GitRemoteOperation,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    let (askpass, mut prompts) = AskPassDelegate::new();
+    // The prompts end once the operation is done and the delegate has been dropped.
+    cx.spawn(|mut cx| async move {
+        while let Some(prompt) = prompts.next().await {
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    workspace.toggle_modal(cx, |cx| AskPassModal::new(prompt, cx));
+                })
+                .ok();
+        }
+    })
+    .detach();
+    project.update(cx, |project, cx| {
+        project.run_git_remote_operation(
+            worktree_id,
+            work_directory_id,
+            operation,
+            Some(askpass),
+            cx,
+        )
+    })
+}
+
+/// A modal asking for a password or passphrase needed by a git operation.
+/// Dismissing it cancels the operation.
+pub struct AskPassModal {
+    prompt: SharedString,
+    editor: View<Editor>,
+    pending_prompt: Option<AskPassPrompt>,
+}
+
+impl AskPassModal {
+    fn new(prompt: AskPassPrompt, cx: &mut ViewContext<Self>) -> Self {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_masked(!prompt.prompt.contains("yes/no"), cx);
+            editor
+        });
+        Self {
+            prompt: prompt.prompt.trim().to_string().into(),
+            editor,
+            pending_prompt: Some(prompt),
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some(prompt) = self.pending_prompt.take() {
+            prompt.respond(self.editor.read(cx).text(cx));
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+}
+
+impl ModalView for AskPassModal {}
+
+impl EventEmitter<DismissEvent> for AskPassModal {}
+
+impl FocusableView for AskPassModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for AskPassModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("AskPassModal")
+            .elevation_3(cx)
+            .w(rems(34.))
+            .p_4()
+            .gap_2()
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(Label::new(self.prompt.clone()))
+            .child(self.editor.clone())
+    }
+}
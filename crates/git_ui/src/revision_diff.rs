@@ -0,0 +1,312 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::anyhow;
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::repository::Branch;
+use gpui::{
+    AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Subscription, Task, View, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, ProjectEntryId, WorktreeId};
+use ui::{prelude::*, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::NotificationId, ModalView, Toast, Workspace};
+
+use crate::{active_repository, file_diff::open_file_diffs, DiffAgainstRevision};
+
+/// A modal for picking the branch or revision to compare the working tree of a repository with.
+pub struct RevisionDiffModal {
+    picker: View<Picker<RevisionDiffDelegate>>,
+    _subscription: Subscription,
+}
+
+impl RevisionDiffModal {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &DiffAgainstRevision, cx| {
+            Self::toggle(workspace, cx);
+        });
+    }
+
+    /// Opens the modal for the repository of the active editor's file, or the first repository
+    /// of the project if there is none.
+    fn toggle(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+        let project = workspace.project().clone();
+        let Some((worktree, repository)) = active_repository(workspace, cx) else {
+            return;
+        };
+
+        let worktree = worktree.read(cx);
+        let snapshot = worktree.snapshot();
+        let repository_name = snapshot
+            .repositories()
+            .find(|(_, entry)| entry.work_directory_id() == repository.work_directory_id())
+            .map(|(work_directory, _)| Path::new(worktree.root_name()).join(work_directory))
+            .unwrap_or_else(|| PathBuf::from(worktree.root_name()));
+        // Branches are only listed for local repositories, but any revision can be typed in.
+        let branches = worktree
+            .as_local()
+            .and_then(|worktree| worktree.get_local_repo(&repository))
+            .and_then(|repository| repository.repo().branches().log_err())
+            .unwrap_or_default();
+
+        let delegate = RevisionDiffDelegate {
+            workspace: workspace.weak_handle(),
+            project,
+            worktree_id: worktree.id(),
+            work_directory_id: repository.work_directory_id(),
+            repository_name,
+            branches,
+            matches: Vec::new(),
+            typed_revision: None,
+            selected_index: 0,
+        };
+        workspace.toggle_modal(cx, |cx| {
+            let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+            let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+            Self {
+                picker,
+                _subscription,
+            }
+        });
+    }
+}
+
+impl ModalView for RevisionDiffModal {}
+
+impl EventEmitter<DismissEvent> for RevisionDiffModal {}
+
+impl FocusableView for RevisionDiffModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for RevisionDiffModal {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct RevisionDiffDelegate {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    repository_name: PathBuf,
+    branches: Vec<Branch>,
+    matches: Vec<StringMatch>,
+    /// The query, offered before the matching branches when it isn't a branch name itself,
+    /// so that commits, tags and expressions like `HEAD~3` can be compared with too.
+    typed_revision: Option<String>,
+    selected_index: usize,
+}
+
+impl RevisionDiffDelegate {
+    fn revision(&self, ix: usize) -> Option<String> {
+        match &self.typed_revision {
+            Some(revision) if ix == 0 => Some(revision.clone()),
+            Some(_) => Some(self.matches.get(ix - 1)?.string.clone()),
+            None => Some(self.matches.get(ix)?.string.clone()),
+        }
+    }
+}
+
+impl PickerDelegate for RevisionDiffDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Compare with branch or revision...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.typed_revision.is_some() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let mut branches = self.branches.clone();
+        branches.sort_unstable_by(|lhs, rhs| {
+            rhs.is_head
+                .cmp(&lhs.is_head)
+                .then(rhs.unix_timestamp.cmp(&lhs.unix_timestamp))
+        });
+        let candidates = branches
+            .iter()
+            .enumerate()
+            .map(|(id, branch)| StringMatchCandidate {
+                id,
+                char_bag: branch.name.chars().collect(),
+                string: branch.name.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let query = query.trim().to_string();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    let is_branch_name = branches.iter().any(|branch| *branch.name == query);
+                    delegate.typed_revision =
+                        (!query.is_empty() && !is_branch_name).then_some(query);
+                    delegate.matches = matches;
+                    delegate.selected_index = 0;
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(revision) = self.revision(self.selected_index) else {
+            return;
+        };
+        let merge_base = secondary;
+        let diff = self.project.read(cx).git_revision_diff(
+            self.worktree_id,
+            self.work_directory_id,
+            revision.clone(),
+            merge_base,
+            cx,
+        );
+        let title = if merge_base {
+            format!("Changes since merge base with {revision}")
+        } else {
+            format!("Changes since {revision}")
+        };
+        let workspace = self.workspace.clone();
+        let project = self.project.clone();
+        let worktree_id = self.worktree_id;
+        let repository_name = self.repository_name.clone();
+        cx.spawn(|picker, mut cx| async move {
+            let result = async {
+                let files = diff.await?;
+                if files.is_empty() {
+                    return Err(anyhow!("no changes compared to {revision}"));
+                }
+                open_file_diffs(
+                    workspace.clone(),
+                    project,
+                    worktree_id,
+                    repository_name,
+                    title,
+                    files,
+                    &mut cx,
+                )
+                .await
+            }
+            .await;
+
+            if let Err(error) = result {
+                struct RevisionDiffError;
+
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        let id = NotificationId::unique::<RevisionDiffError>();
+                        let message = format!("Failed to compare with {revision}: {error:#}");
+                        workspace.show_toast(Toast::new(id, message), cx);
+                    })
+                    .ok();
+            }
+            picker.update(&mut cx, |_, cx| cx.emit(DismissEvent)).ok();
+        })
+        .detach();
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let (icon, label) = match &self.typed_revision {
+            Some(revision) if ix == 0 => (
+                IconName::Hash,
+                HighlightedLabel::new(revision.clone(), Vec::new()),
+            ),
+            typed_revision => {
+                let hit = &self.matches[ix - typed_revision.is_some() as usize];
+                (
+                    IconName::FileGit,
+                    HighlightedLabel::new(hit.string.clone(), hit.positions.clone()),
+                )
+            }
+        };
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(Icon::new(icon).color(Color::Muted))
+                .child(label),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.match_count() == 0 {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("compare-with-merge-base", "Compare with Merge Base")
+                        .label_size(LabelSize::Small)
+                        .when_some(
+                            KeyBinding::for_action(&menu::SecondaryConfirm, cx),
+                            |this, keybind| this.key_binding(keybind),
+                        )
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::SecondaryConfirm))),
+                )
+                .child(
+                    Button::new("compare", "Compare")
+                        .label_size(LabelSize::Small)
+                        .when_some(
+                            KeyBinding::for_action(&menu::Confirm, cx),
+                            |this, keybind| this.key_binding(keybind),
+                        )
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::Confirm))),
+                )
+                .into_any_element(),
+        )
+    }
+}
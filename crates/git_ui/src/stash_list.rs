@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate};
+use git::stash::StashEntry;
+use gpui::{
+    AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Subscription, Task, View, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{GitOperation, Project, ProjectEntryId, WorktreeId};
+use ui::{prelude::*, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ModalView, Workspace};
+
+use crate::{active_repository, remote_operations::show_error, ViewStashes};
+
+/// A modal listing the stash of a repository, to apply, pop or drop its entries.
+pub struct StashListModal {
+    picker: View<Picker<StashListDelegate>>,
+    _subscription: Subscription,
+}
+
+impl StashListModal {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ViewStashes, cx| {
+            let Some((worktree, repository)) = active_repository(workspace, cx) else {
+                return;
+            };
+            let worktree_id = worktree.read(cx).id();
+            Self::open(workspace, worktree_id, repository.work_directory_id(), cx);
+        });
+    }
+
+    /// Lists the stash of the given repository, and opens the modal once it's loaded.
+    pub(crate) fn open(
+        workspace: &mut Workspace,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let stashes = project
+            .read(cx)
+            .git_stashes(worktree_id, work_directory_id, cx);
+        cx.spawn(|workspace, mut cx| async move {
+            let stashes = stashes.await;
+            workspace.update(&mut cx, |workspace, cx| {
+                let stashes = match stashes {
+                    Ok(stashes) => stashes,
+                    Err(error) => {
+                        show_error(workspace, "Failed to list stashes", error, cx);
+                        return;
+                    }
+                };
+                let delegate = StashListDelegate {
+                    workspace: workspace.weak_handle(),
+                    project,
+                    worktree_id,
+                    work_directory_id,
+                    stashes,
+                    matches: Vec::new(),
+                    selected_index: 0,
+                };
+                workspace.toggle_modal(cx, |cx| {
+                    let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+                    let _subscription = cx.subscribe(&picker, |_, _, _, cx| cx.emit(DismissEvent));
+                    Self {
+                        picker,
+                        _subscription,
+                    }
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl ModalView for StashListModal {}
+
+impl EventEmitter<DismissEvent> for StashListModal {}
+
+impl FocusableView for StashListModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for StashListModal {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+pub struct StashListDelegate {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    worktree_id: WorktreeId,
+    work_directory_id: ProjectEntryId,
+    stashes: Vec<StashEntry>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl StashListDelegate {
+    fn selected_stash(&self) -> Option<&StashEntry> {
+        let hit = self.matches.get(self.selected_index)?;
+        self.stashes.get(hit.candidate_id)
+    }
+
+    fn run_operation(&self, operation: GitOperation, cx: &mut ViewContext<Picker<Self>>) {
+        let title = match &operation {
+            GitOperation::StashDrop { .. } => "Failed to drop stash",
+            _ => "Failed to apply stash",
+        };
+        let is_drop = matches!(operation, GitOperation::StashDrop { .. });
+        let task = self.project.read(cx).run_git_operation(
+            self.worktree_id,
+            self.work_directory_id,
+            operation,
+            cx,
+        );
+        let workspace = self.workspace.clone();
+        cx.spawn(|picker, mut cx| async move {
+            let result = task.await;
+            if let Err(error) = result {
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        show_error(workspace, title, error, cx)
+                    })
+                    .ok();
+                picker.update(&mut cx, |_, cx| cx.emit(DismissEvent)).ok();
+                return;
+            }
+
+            // Dropping keeps the modal open, with the stash listed again.
+            if is_drop {
+                let stashes = picker
+                    .update(&mut cx, |picker, cx| {
+                        let delegate = &picker.delegate;
+                        delegate.project.read(cx).git_stashes(
+                            delegate.worktree_id,
+                            delegate.work_directory_id,
+                            cx,
+                        )
+                    })?
+                    .await?;
+                picker.update(&mut cx, |picker, cx| {
+                    picker.delegate.stashes = stashes;
+                    picker.refresh(cx);
+                })?;
+            } else {
+                picker.update(&mut cx, |_, cx| cx.emit(DismissEvent))?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn drop_selected(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(stash) = self.selected_stash() {
+            let index = stash.index;
+            self.run_operation(GitOperation::StashDrop { index }, cx);
+        }
+    }
+}
+
+impl PickerDelegate for StashListDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        "Search stashes...".into()
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No stashes".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let candidates = self
+            .stashes
+            .iter()
+            .enumerate()
+            .map(|(id, stash)| StringMatchCandidate {
+                id,
+                char_bag: stash.message.chars().collect(),
+                string: stash.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        let query = query.trim().to_string();
+        cx.spawn(move |picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                fuzzy::match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    10000,
+                    &Default::default(),
+                    cx.background_executor().clone(),
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, _| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(stash) = self.selected_stash() {
+            let index = stash.index;
+            self.run_operation(
+                GitOperation::StashApply {
+                    index,
+                    pop: secondary,
+                },
+                cx,
+            );
+        }
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let hit = self.matches.get(ix)?;
+        let stash = self.stashes.get(hit.candidate_id)?;
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Label::new(format!("stash@{{{}}}", stash.index))
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(HighlightedLabel::new(
+                    hit.string.clone(),
+                    hit.positions.clone(),
+                )),
+        )
+    }
+
+    fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
+        if self.match_count() == 0 {
+            return None;
+        }
+
+        Some(
+            h_flex()
+                .w_full()
+                .p_2()
+                .gap_2()
+                .justify_end()
+                .border_t_1()
+                .border_color(cx.theme().colors().border_variant)
+                .child(
+                    Button::new("drop-stash", "Drop")
+                        .label_size(LabelSize::Small)
+                        .on_click(cx.listener(|picker, _, cx| picker.delegate.drop_selected(cx))),
+                )
+                .child(
+                    Button::new("pop-stash", "Pop")
+                        .label_size(LabelSize::Small)
+                        .when_some(
+                            KeyBinding::for_action(&menu::SecondaryConfirm, cx),
+                            |this, keybind| this.key_binding(keybind),
+                        )
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::SecondaryConfirm))),
+                )
+                .child(
+                    Button::new("apply-stash", "Apply")
+                        .label_size(LabelSize::Small)
+                        .when_some(
+                            KeyBinding::for_action(&menu::Confirm, cx),
+                            |this, keybind| this.key_binding(keybind),
+                        )
+                        .on_click(|_, cx| cx.dispatch_action(Box::new(menu::Confirm))),
+                )
+                .into_any_element(),
+        )
+    }
+}
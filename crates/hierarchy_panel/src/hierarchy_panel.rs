@@ -0,0 +1,851 @@
+mod hierarchy_panel_settings;
+
+use std::{ops::Range, sync::Arc};
+
+use anyhow::Context;
+use collections::HashMap;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Editor};
+use gpui::{
+    actions, div, px, uniform_list, Action, AppContext, AsyncWindowContext, ClickEvent, Div,
+    ElementId, EventEmitter, FocusHandle, FocusableView, InteractiveElement, IntoElement,
+    KeyContext, Model, MouseButton, ParentElement, Pixels, Render, Stateful, Styled, Task,
+    UniformListScrollHandle, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use hierarchy_panel_settings::{HierarchyPanelDockPosition, HierarchyPanelSettings};
+use language::{Anchor, Buffer, ToPoint};
+use menu::{SelectFirst, SelectLast, SelectNext, SelectPrev};
+use project::{CallHierarchyItem, Fs, Location, Project, TypeHierarchyItem};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use util::{ResultExt, TryFutureExt};
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    ui::{
+        h_flex, v_flex, ActiveTheme, ButtonCommon, Clickable, Color, FluentBuilder, IconButton,
+        IconName, IconSize, Label, LabelCommon, LabelSize, ListItem, Selectable, StyledTypography,
+        Tooltip,
+    },
+    Workspace,
+};
+
+actions!(
+    hierarchy_panel,
+    [
+        ShowIncomingCalls,
+        ShowOutgoingCalls,
+        ShowSupertypes,
+        ShowSubtypes,
+        ToggleDirection,
+        ExpandSelectedEntry,
+        CollapseSelectedEntry,
+        Open,
+        OpenCallSites,
+        ToggleFocus,
+    ]
+);
+
+const HIERARCHY_PANEL_KEY: &str = "HierarchyPanel";
+
+/// Which hierarchy is shown in the panel, and in which direction it is walked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HierarchyKind {
+    IncomingCalls,
+    OutgoingCalls,
+    Supertypes,
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn title(&self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming Calls",
+            Self::OutgoingCalls => "Outgoing Calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn opposite(&self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+
+    fn is_call_hierarchy(&self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+}
+
+#[derive(Clone)]
+enum EntryItem {
+    Call(CallHierarchyItem),
+    Type(TypeHierarchyItem),
+}
+
+impl EntryItem {
+    fn name(&self) -> &str {
+        match self {
+            Self::Call(item) => &item.lsp_item.name,
+            Self::Type(item) => &item.lsp_item.name,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            Self::Call(item) => item.lsp_item.detail.as_deref(),
+            Self::Type(item) => item.lsp_item.detail.as_deref(),
+        }
+    }
+
+    fn location(&self) -> &Location {
+        match self {
+            Self::Call(item) => &item.location,
+            Self::Type(item) => &item.location,
+        }
+    }
+
+    fn selection_range(&self) -> &Range<Anchor> {
+        match self {
+            Self::Call(item) => &item.selection_range,
+            Self::Type(item) => &item.selection_range,
+        }
+    }
+}
+
+type EntryId = usize;
+
+struct Entry {
+    item: EntryItem,
+    /// Where the calls between this entry and its parent happen, empty for the roots
+    /// and for type hierarchies.
+    call_sites: Vec<Location>,
+    parent: Option<EntryId>,
+    depth: usize,
+    /// `None` until the calls of the entry are fetched.
+    children: Option<Vec<EntryId>>,
+    expanded: bool,
+}
+
+pub struct HierarchyPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    active: bool,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    pending_serialization: Task<Option<()>>,
+    kind: HierarchyKind,
+    entries: Vec<Entry>,
+    roots: Vec<EntryId>,
+    visible_entries: Vec<EntryId>,
+    selected_entry: Option<EntryId>,
+    loading: bool,
+    prepare_task: Task<()>,
+    fetch_tasks: HashMap<EntryId, Task<()>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedHierarchyPanel {
+    width: Option<Pixels>,
+    active: Option<bool>,
+}
+
+pub fn init_settings(cx: &mut AppContext) {
+    HierarchyPanelSettings::register(cx);
+}
+
+pub fn init(cx: &mut AppContext) {
+    init_settings(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<HierarchyPanel>(cx);
+        });
+        workspace.register_action(|workspace, _: &ShowIncomingCalls, cx| {
+            show_hierarchy(workspace, HierarchyKind::IncomingCalls, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowOutgoingCalls, cx| {
+            show_hierarchy(workspace, HierarchyKind::OutgoingCalls, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSupertypes, cx| {
+            show_hierarchy(workspace, HierarchyKind::Supertypes, cx);
+        });
+        workspace.register_action(|workspace, _: &ShowSubtypes, cx| {
+            show_hierarchy(workspace, HierarchyKind::Subtypes, cx);
+        });
+    })
+    .detach();
+}
+
+fn show_hierarchy(workspace: &mut Workspace, kind: HierarchyKind, cx: &mut ViewContext<Workspace>) {
+    let Some(editor) = workspace
+        .active_item(cx)
+        .and_then(|item| item.act_as::<Editor>(cx))
+    else {
+        return;
+    };
+    let Some((buffer, position)) = editor.update(cx, |editor, cx| {
+        let head = editor.selections.newest_anchor().head();
+        editor.buffer().read(cx).text_anchor_for_position(head, cx)
+    }) else {
+        return;
+    };
+    if let Some(panel) = workspace.focus_panel::<HierarchyPanel>(cx) {
+        panel.update(cx, |panel, cx| {
+            panel.show_hierarchy(buffer, position, kind, cx)
+        });
+    }
+}
+
+impl HierarchyPanel {
+    pub async fn load(
+        workspace: WeakView<Workspace>,
+        mut cx: AsyncWindowContext,
+    ) -> anyhow::Result<View<Self>> {
+        let serialized_panel = cx
+            .background_executor()
+            .spawn(async move { KEY_VALUE_STORE.read_kvp(HIERARCHY_PANEL_KEY) })
+            .await
+            .context("loading hierarchy panel")
+            .log_err()
+            .flatten()
+            .map(|panel| serde_json::from_str::<SerializedHierarchyPanel>(&panel))
+            .transpose()
+            .log_err()
+            .flatten();
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let panel = Self::new(workspace, cx);
+            if let Some(serialized_panel) = serialized_panel {
+                panel.update(cx, |panel, cx| {
+                    panel.width = serialized_panel.width.map(|px| px.round());
+                    panel.active = serialized_panel.active.unwrap_or(false);
+                    cx.notify();
+                });
+            }
+            panel
+        })
+    }
+
+    fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let workspace_handle = workspace.weak_handle();
+        let project = workspace.project().clone();
+        let fs = workspace.app_state().fs.clone();
+        cx.new_view(|cx| Self {
+            workspace: workspace_handle,
+            project,
+            fs,
+            width: None,
+            active: false,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            pending_serialization: Task::ready(None),
+            kind: HierarchyKind::IncomingCalls,
+            entries: Vec::new(),
+            roots: Vec::new(),
+            visible_entries: Vec::new(),
+            selected_entry: None,
+            loading: false,
+            prepare_task: Task::ready(()),
+            fetch_tasks: HashMap::default(),
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let active = Some(self.active);
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedHierarchyPanel { width, active })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn dispatch_context(&self, _: &ViewContext<Self>) -> KeyContext {
+        let mut dispatch_context = KeyContext::new_with_defaults();
+        dispatch_context.add("HierarchyPanel");
+        dispatch_context.add("menu");
+        dispatch_context
+    }
+
+    /// Resolves the symbol at the given position and shows its hierarchy in the panel.
+    pub fn show_hierarchy(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: Anchor,
+        kind: HierarchyKind,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.kind = kind;
+        self.loading = true;
+        self.clear(cx);
+
+        let items = self.prepare_items(&buffer, position, cx);
+        self.prepare_task = cx.spawn(|panel, mut cx| async move {
+            let items = items.await.log_err().unwrap_or_default();
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.loading = false;
+                    panel.set_roots(items, cx);
+                })
+                .ok();
+        });
+        cx.notify();
+    }
+
+    fn prepare_items(
+        &self,
+        buffer: &Model<Buffer>,
+        position: Anchor,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<Vec<EntryItem>>> {
+        if self.kind.is_call_hierarchy() {
+            let items = self.project.update(cx, |project, cx| {
+                project.prepare_call_hierarchy(buffer, position, cx)
+            });
+            cx.spawn(
+                |_, _| async move { Ok(items.await?.into_iter().map(EntryItem::Call).collect()) },
+            )
+        } else {
+            let items = self.project.update(cx, |project, cx| {
+                project.prepare_type_hierarchy(buffer, position, cx)
+            });
+            cx.spawn(
+                |_, _| async move { Ok(items.await?.into_iter().map(EntryItem::Type).collect()) },
+            )
+        }
+    }
+
+    fn clear(&mut self, cx: &mut ViewContext<Self>) {
+        self.entries.clear();
+        self.roots.clear();
+        self.visible_entries.clear();
+        self.selected_entry = None;
+        self.fetch_tasks.clear();
+        cx.notify();
+    }
+
+    fn set_roots(&mut self, items: Vec<EntryItem>, cx: &mut ViewContext<Self>) {
+        self.clear(cx);
+        for item in items {
+            let id = self.entries.len();
+            self.entries.push(Entry {
+                item,
+                call_sites: Vec::new(),
+                parent: None,
+                depth: 0,
+                children: None,
+                expanded: true,
+            });
+            self.roots.push(id);
+            self.fetch_children(id, cx);
+        }
+        self.selected_entry = self.roots.first().copied();
+        self.update_visible_entries(cx);
+    }
+
+    fn fetch_children(&mut self, id: EntryId, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(id) else {
+            return;
+        };
+        if entry.children.is_some() || self.fetch_tasks.contains_key(&id) {
+            return;
+        }
+
+        let item = entry.item.clone();
+        let children = self.children_of(&item, cx);
+        let task = cx.spawn(|panel, mut cx| async move {
+            let children = children.await.log_err().unwrap_or_default();
+            panel
+                .update(&mut cx, |panel, cx| {
+                    panel.fetch_tasks.remove(&id);
+                    panel.insert_children(id, children, cx);
+                })
+                .ok();
+        });
+        self.fetch_tasks.insert(id, task);
+    }
+
+    /// Fetches the neighbours of the item in the current direction, along with the
+    /// call sites that connect them to the item.
+    fn children_of(
+        &self,
+        item: &EntryItem,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<anyhow::Result<Vec<(EntryItem, Vec<Location>)>>> {
+        let kind = self.kind;
+        match item {
+            EntryItem::Call(item) => {
+                let calls = self.project.update(cx, |project, cx| match kind {
+                    HierarchyKind::OutgoingCalls => project.outgoing_calls(item, cx),
+                    _ => project.incoming_calls(item, cx),
+                });
+                cx.spawn(|_, _| async move {
+                    Ok(calls
+                        .await?
+                        .into_iter()
+                        .map(|call| (EntryItem::Call(call.item), call.call_sites))
+                        .collect())
+                })
+            }
+            EntryItem::Type(item) => {
+                let types = self.project.update(cx, |project, cx| match kind {
+                    HierarchyKind::Subtypes => project.subtypes(item, cx),
+                    _ => project.supertypes(item, cx),
+                });
+                cx.spawn(|_, _| async move {
+                    Ok(types
+                        .await?
+                        .into_iter()
+                        .map(|item| (EntryItem::Type(item), Vec::new()))
+                        .collect())
+                })
+            }
+        }
+    }
+
+    fn insert_children(
+        &mut self,
+        parent: EntryId,
+        items: Vec<(EntryItem, Vec<Location>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(depth) = self.entries.get(parent).map(|entry| entry.depth + 1) else {
+            return;
+        };
+        let mut children = Vec::with_capacity(items.len());
+        for (item, call_sites) in items {
+            children.push(self.entries.len());
+            self.entries.push(Entry {
+                item,
+                call_sites,
+                parent: Some(parent),
+                depth,
+                children: None,
+                expanded: false,
+            });
+        }
+        self.entries[parent].children = Some(children);
+        self.update_visible_entries(cx);
+    }
+
+    fn update_visible_entries(&mut self, cx: &mut ViewContext<Self>) {
+        let mut visible_entries = Vec::new();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(id) = stack.pop() {
+            visible_entries.push(id);
+            let entry = &self.entries[id];
+            if entry.expanded {
+                if let Some(children) = &entry.children {
+                    stack.extend(children.iter().rev().copied());
+                }
+            }
+        }
+        self.visible_entries = visible_entries;
+        cx.notify();
+    }
+
+    fn toggle_direction(&mut self, _: &ToggleDirection, cx: &mut ViewContext<Self>) {
+        self.kind = self.kind.opposite();
+        let roots = self
+            .roots
+            .iter()
+            .map(|&id| self.entries[id].item.clone())
+            .collect();
+        self.set_roots(roots, cx);
+    }
+
+    fn toggle_expanded(&mut self, id: EntryId, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get_mut(id) else {
+            return;
+        };
+        entry.expanded = !entry.expanded;
+        if entry.expanded {
+            self.fetch_children(id, cx);
+        }
+        self.update_visible_entries(cx);
+    }
+
+    fn expand_selected_entry(&mut self, _: &ExpandSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(id) = self.selected_entry else {
+            return;
+        };
+        if self.entries[id].expanded {
+            self.select_next(&SelectNext, cx);
+        } else {
+            self.toggle_expanded(id, cx);
+        }
+    }
+
+    fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
+        let Some(id) = self.selected_entry else {
+            return;
+        };
+        let entry = &self.entries[id];
+        if entry.expanded {
+            self.toggle_expanded(id, cx);
+        } else if let Some(parent) = entry.parent {
+            self.selected_entry = Some(parent);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn select_next(&mut self, _: &SelectNext, cx: &mut ViewContext<Self>) {
+        let next_ix = match self.selected_ix() {
+            Some(ix) => ix + 1,
+            None => 0,
+        };
+        if let Some(&id) = self.visible_entries.get(next_ix) {
+            self.selected_entry = Some(id);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
+        let prev_ix = match self.selected_ix() {
+            Some(ix) => ix.saturating_sub(1),
+            None => 0,
+        };
+        if let Some(&id) = self.visible_entries.get(prev_ix) {
+            self.selected_entry = Some(id);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        if let Some(&id) = self.visible_entries.first() {
+            self.selected_entry = Some(id);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn select_last(&mut self, _: &SelectLast, cx: &mut ViewContext<Self>) {
+        if let Some(&id) = self.visible_entries.last() {
+            self.selected_entry = Some(id);
+            self.autoscroll(cx);
+            cx.notify();
+        }
+    }
+
+    fn selected_ix(&self) -> Option<usize> {
+        let selected_entry = self.selected_entry?;
+        self.visible_entries
+            .iter()
+            .position(|&id| id == selected_entry)
+    }
+
+    fn autoscroll(&mut self, _: &mut ViewContext<Self>) {
+        if let Some(ix) = self.selected_ix() {
+            self.scroll_handle.scroll_to_item(ix);
+        }
+    }
+
+    fn open(&mut self, _: &Open, cx: &mut ViewContext<Self>) {
+        if let Some(id) = self.selected_entry {
+            self.open_entry(id, cx);
+        }
+    }
+
+    /// Navigates to the first call site of the entry, or to the symbol itself when there are none.
+    fn open_entry(&mut self, id: EntryId, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.entries.get(id) else {
+            return;
+        };
+        let target = entry
+            .call_sites
+            .first()
+            .cloned()
+            .unwrap_or_else(|| Location {
+                buffer: entry.item.location().buffer.clone(),
+                range: entry.item.selection_range().clone(),
+            });
+        self.workspace
+            .update(cx, |workspace, cx| {
+                let pane = workspace.active_pane().clone();
+                let editor = workspace.open_project_item::<Editor>(
+                    pane,
+                    target.buffer.clone(),
+                    true,
+                    true,
+                    cx,
+                );
+                editor.update(cx, |editor, cx| {
+                    let position = target.range.start.to_point(target.buffer.read(cx));
+                    editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                        s.select_ranges([position..position])
+                    });
+                });
+            })
+            .ok();
+    }
+
+    /// Opens all call sites of the selected entry in a multibuffer, or the entry itself
+    /// when it has none.
+    fn open_call_sites(&mut self, _: &OpenCallSites, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.selected_entry.and_then(|id| self.entries.get(id)) else {
+            return;
+        };
+        let locations = if entry.call_sites.is_empty() {
+            vec![entry.item.location().clone()]
+        } else {
+            entry.call_sites.clone()
+        };
+        let title = format!(
+            "{} of `{}`",
+            self.kind.title(),
+            self.root_name(entry).unwrap_or(entry.item.name())
+        );
+        let replica_id = self.project.read(cx).replica_id();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                Editor::open_locations_in_multibuffer(
+                    workspace, locations, replica_id, title, false, cx,
+                );
+            })
+            .ok();
+    }
+
+    fn root_name<'a>(&'a self, mut entry: &'a Entry) -> Option<&'a str> {
+        while let Some(parent) = entry.parent {
+            entry = self.entries.get(parent)?;
+        }
+        Some(entry.item.name())
+    }
+
+    fn render_entry(&self, id: EntryId, cx: &mut ViewContext<Self>) -> Stateful<Div> {
+        let settings = HierarchyPanelSettings::get_global(cx);
+        let entry = &self.entries[id];
+        let is_selected = self.selected_entry == Some(id);
+        let is_expandable = entry
+            .children
+            .as_ref()
+            .map_or(true, |children| !children.is_empty());
+
+        let buffer = entry.item.location().buffer.read(cx);
+        let row = entry.item.selection_range().start.to_point(buffer).row;
+        let path = buffer
+            .file()
+            .map(|file| file.path().to_string_lossy().into_owned())
+            .unwrap_or_else(|| "untitled".to_owned());
+        let detail = match entry.item.detail() {
+            Some(detail) if !detail.is_empty() => format!("{detail} · {path}:{}", row + 1),
+            _ => format!("{path}:{}", row + 1),
+        };
+
+        let item_id = ElementId::from(id);
+        div()
+            .text_ui(cx)
+            .id(item_id.clone())
+            .child(
+                ListItem::new(item_id)
+                    .indent_level(entry.depth)
+                    .indent_step_size(px(settings.indent_size))
+                    .selected(is_selected)
+                    .toggle(is_expandable.then_some(entry.expanded))
+                    .on_toggle(cx.listener(move |panel, _, cx| panel.toggle_expanded(id, cx)))
+                    .child(
+                        h_flex()
+                            .h_6()
+                            .gap_2()
+                            .child(Label::new(entry.item.name().to_owned()).single_line())
+                            .child(
+                                Label::new(detail)
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted)
+                                    .single_line(),
+                            ),
+                    )
+                    .when(entry.call_sites.len() > 1, |list_item| {
+                        list_item.end_slot(
+                            Label::new(entry.call_sites.len().to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    })
+                    .on_click(cx.listener(move |panel, event: &ClickEvent, cx| {
+                        if event.down.button == MouseButton::Right || event.down.first_mouse {
+                            return;
+                        }
+                        panel.selected_entry = Some(id);
+                        if event.down.click_count > 1 {
+                            panel.toggle_expanded(id, cx);
+                        } else {
+                            panel.open_entry(id, cx);
+                        }
+                        cx.notify();
+                    })),
+            )
+            .border_1()
+            .border_r_2()
+            .rounded_none()
+            .when(
+                is_selected && self.focus_handle.contains_focused(cx),
+                |div| div.border_color(Color::Selected.color(cx)),
+            )
+    }
+
+    fn render_header(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let (icon, tooltip) = match self.kind {
+            HierarchyKind::IncomingCalls => (IconName::ArrowDownFromLine, "Show Outgoing Calls"),
+            HierarchyKind::OutgoingCalls => (IconName::ArrowUpFromLine, "Show Incoming Calls"),
+            HierarchyKind::Supertypes => (IconName::ArrowDownFromLine, "Show Subtypes"),
+            HierarchyKind::Subtypes => (IconName::ArrowUpFromLine, "Show Supertypes"),
+        };
+        h_flex()
+            .px_2()
+            .py_1()
+            .justify_between()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(Label::new(self.kind.title()).size(LabelSize::Small))
+            .child(
+                IconButton::new("toggle-hierarchy-direction", icon)
+                    .icon_size(IconSize::Small)
+                    .tooltip(move |cx| Tooltip::for_action(tooltip, &ToggleDirection, cx))
+                    .on_click(
+                        cx.listener(|panel, _, cx| panel.toggle_direction(&ToggleDirection, cx)),
+                    ),
+            )
+    }
+}
+
+impl Panel for HierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "Hierarchy Panel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        match HierarchyPanelSettings::get_global(cx).dock {
+            HierarchyPanelDockPosition::Left => DockPosition::Left,
+            HierarchyPanelDockPosition::Right => DockPosition::Right,
+        }
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<HierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings, _| {
+                let dock = match position {
+                    DockPosition::Left | DockPosition::Bottom => HierarchyPanelDockPosition::Left,
+                    DockPosition::Right => HierarchyPanelDockPosition::Right,
+                };
+                settings.dock = Some(dock);
+            },
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| HierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        HierarchyPanelSettings::get_global(cx)
+            .button
+            .then(|| IconName::Code)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+
+    fn starts_open(&self, _: &WindowContext) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
+        self.active = active;
+        self.serialize(cx);
+    }
+}
+
+impl FocusableView for HierarchyPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for HierarchyPanel {}
+
+impl Render for HierarchyPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let panel = v_flex()
+            .id("hierarchy-panel")
+            .size_full()
+            .key_context(self.dispatch_context(cx))
+            .on_action(cx.listener(Self::open))
+            .on_action(cx.listener(Self::open_call_sites))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_prev))
+            .on_action(cx.listener(Self::select_first))
+            .on_action(cx.listener(Self::select_last))
+            .on_action(cx.listener(Self::expand_selected_entry))
+            .on_action(cx.listener(Self::collapse_selected_entry))
+            .on_action(cx.listener(Self::toggle_direction))
+            .track_focus(&self.focus_handle)
+            .child(self.render_header(cx));
+
+        if self.visible_entries.is_empty() {
+            let message = match (self.loading, self.kind.is_call_hierarchy()) {
+                (true, true) => "Loading call hierarchy",
+                (true, false) => "Loading type hierarchy",
+                (false, true) => "No call hierarchy for the symbol under the cursor",
+                (false, false) => "No type hierarchy for the symbol under the cursor",
+            };
+            panel.child(
+                v_flex()
+                    .justify_center()
+                    .size_full()
+                    .child(h_flex().justify_center().child(Label::new(message))),
+            )
+        } else {
+            panel.child(
+                uniform_list(
+                    cx.view().clone(),
+                    "hierarchy-entries",
+                    self.visible_entries.len(),
+                    |panel, range, cx| {
+                        panel.visible_entries[range]
+                            .to_vec()
+                            .into_iter()
+                            .map(|id| panel.render_entry(id, cx))
+                            .collect()
+                    },
+                )
+                .size_full()
+                .track_scroll(self.scroll_handle.clone()),
+            )
+        }
+    }
+}
@@ -53,9 +53,9 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection, SelectionGoal,
-    Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16,
-    Transaction, TransactionId, Unclipped,
+    Encoding, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection,
+    SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint,
+    ToPointUtf16, Transaction, TransactionId, Unclipped,
 };
 use theme::SyntaxTheme;
 #[cfg(any(test, feature = "test-support"))]
@@ -91,6 +91,9 @@ pub struct Buffer {
     diff_base: Option<Rope>,
     git_diff: git::diff::BufferDiff,
     file: Option<Arc<dyn File>>,
+    /// The encoding that the file was decoded with when this buffer was
+    /// loaded, and that it will be encoded with when saved.
+    encoding: Encoding,
     /// The mtime of the file when this buffer was last loaded from
     /// or saved to disk.
     saved_mtime: Option<SystemTime>,
@@ -386,8 +389,11 @@ pub trait LocalFile: File {
     /// Returns the absolute path of this file.
     fn abs_path(&self, cx: &AppContext) -> PathBuf;
 
-    /// Loads the file's contents from disk.
-    fn load(&self, cx: &AppContext) -> Task<Result<String>>;
+    /// Loads the file's contents from disk, decoding them with the given encoding.
+    ///
+    /// Returns the contents along with the encoding, which records whether the file
+    /// starts with a byte order mark.
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<(String, Encoding)>>;
 
     /// Returns true if the file should not be shared with collaborators.
     fn is_private(&self, _: &AppContext) -> bool {
@@ -610,6 +616,9 @@ impl Buffer {
             rpc::proto::LineEnding::from_i32(message.line_ending)
                 .ok_or_else(|| anyhow!("missing line_ending"))?,
         ));
+        if let Some(encoding) = message.encoding {
+            this.encoding = proto::deserialize_encoding(encoding)?;
+        }
         this.saved_version = proto::deserialize_version(&message.saved_version);
         this.saved_mtime = message.saved_mtime.map(|time| time.into());
         Ok(this)
@@ -625,6 +634,7 @@ impl Buffer {
             line_ending: proto::serialize_line_ending(self.line_ending()) as i32,
             saved_version: proto::serialize_version(&self.saved_version),
             saved_mtime: self.saved_mtime.map(|time| time.into()),
+            encoding: Some(proto::serialize_encoding(self.encoding)),
         }
     }
 
@@ -681,6 +691,12 @@ impl Buffer {
         self
     }
 
+    /// Assign the encoding that the buffer's file was decoded with, returning the buffer.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
     /// Returns the [Capability] of this buffer.
     pub fn capability(&self) -> Capability {
         self.capability
@@ -701,6 +717,7 @@ impl Buffer {
         let saved_mtime = file.as_ref().and_then(|file| file.mtime());
 
         Self {
+            encoding: Encoding::default(),
             saved_mtime,
             saved_version: buffer.version(),
             reload_task: None,
@@ -782,6 +799,19 @@ impl Buffer {
         self.saved_mtime
     }
 
+    /// The encoding that the buffer's file is decoded and encoded with.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Assign the encoding that the buffer will be encoded with the next time it is saved.
+    pub fn set_encoding(&mut self, encoding: Encoding, cx: &mut ModelContext<Self>) {
+        if self.encoding != encoding {
+            self.encoding = encoding;
+            cx.notify();
+        }
+    }
+
     /// Assign a language to the buffer.
     pub fn set_language(&mut self, language: Option<Arc<Language>>, cx: &mut ModelContext<Self>) {
         self.non_text_state_update_count += 1;
@@ -829,19 +859,35 @@ impl Buffer {
     pub fn reload(
         &mut self,
         cx: &mut ModelContext<Self>,
-    ) -> oneshot::Receiver<Option<Transaction>> {
+    ) -> oneshot::Receiver<Result<Option<Transaction>>> {
+        self.reload_with_encoding(self.encoding, cx)
+    }
+
+    /// Reloads the contents of the buffer from disk, decoding them with the given encoding,
+    /// which the buffer is then saved with.
+    pub fn reload_with_encoding(
+        &mut self,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
+    ) -> oneshot::Receiver<Result<Option<Transaction>>> {
         let (tx, rx) = futures::channel::oneshot::channel();
         let prev_version = self.text.version();
         self.reload_task = Some(cx.spawn(|this, mut cx| async move {
             let Some((new_mtime, new_text)) = this.update(&mut cx, |this, cx| {
                 let file = this.file.as_ref()?.as_local()?;
-                Some((file.mtime(), file.load(cx)))
+                Some((file.mtime(), file.load(encoding, cx)))
             })?
             else {
                 return Ok(());
             };
 
-            let new_text = new_text.await?;
+            let (new_text, encoding) = match new_text.await {
+                Ok(loaded) => loaded,
+                Err(error) => {
+                    tx.send(Err(error)).ok();
+                    return Ok(());
+                }
+            };
             let diff = this
                 .update(&mut cx, |this, cx| this.diff(new_text.clone(), cx))?
                 .await;
@@ -849,8 +895,9 @@ impl Buffer {
                 if this.version() == diff.base_version {
                     this.finalize_last_transaction();
                     this.apply_diff(diff, cx);
-                    tx.send(this.finalize_last_transaction().cloned()).ok();
+                    tx.send(Ok(this.finalize_last_transaction().cloned())).ok();
                     this.has_conflict = false;
+                    this.encoding = encoding;
                     this.did_reload(this.version(), this.line_ending(), new_mtime, cx);
                 } else {
                     if !diff.edits.is_empty()
@@ -125,6 +125,8 @@ pub struct LanguageSettings {
     pub code_actions_on_format: HashMap<String, bool>,
     /// Whether to perform linked edits
     pub linked_edits: bool,
+    /// Whether to highlight code using semantic tokens from language servers
+    pub semantic_tokens: bool,
     /// Task configuration for this language.
     pub tasks: LanguageTaskConfig,
 }
@@ -347,6 +349,11 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: true
     pub linked_edits: Option<bool>,
+    /// Whether to use semantic tokens reported by language servers to highlight code,
+    /// on top of the tree-sitter based syntax highlighting.
+    ///
+    /// Default: false
+    pub semantic_tokens: Option<bool>,
     /// Task configuration for this language.
     ///
     /// Default: {}
@@ -1042,6 +1049,7 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.code_actions_on_format.clone(),
     );
     merge(&mut settings.linked_edits, src.linked_edits);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
     merge(&mut settings.tasks, src.tasks.clone());
 
     merge(
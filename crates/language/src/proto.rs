@@ -27,6 +27,21 @@ pub fn serialize_line_ending(message: text::LineEnding) -> proto::LineEnding {
     }
 }
 
+/// Deserializes a [`text::Encoding`] from the RPC representation.
+pub fn deserialize_encoding(message: proto::Encoding) -> Result<text::Encoding> {
+    let encoding = text::Encoding::from_name(&message.name)
+        .ok_or_else(|| anyhow!("unsupported encoding {:?}", message.name))?;
+    Ok(encoding.with_bom(message.bom))
+}
+
+/// Serializes a [`text::Encoding`] to be sent over RPC.
+pub fn serialize_encoding(encoding: text::Encoding) -> proto::Encoding {
+    proto::Encoding {
+        name: encoding.name().to_string(),
+        bom: encoding.has_bom(),
+    }
+}
+
 /// Serializes a [`crate::Operation`] to be sent over RPC.
 pub fn serialize_operation(operation: &crate::Operation) -> proto::Operation {
     proto::Operation {
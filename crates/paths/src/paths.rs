@@ -230,6 +230,13 @@ pub fn remote_servers_dir() -> &'static PathBuf {
     REMOTE_SERVERS_DIR.get_or_init(|| support_dir().join("remote_servers"))
 }
 
+/// Returns the path to the directory in which a remote server keeps the sockets and logs of its
+/// running instances, on the machine that it runs on.
+pub fn remote_server_state_dir() -> &'static PathBuf {
+    static REMOTE_SERVER_STATE_DIR: OnceLock<PathBuf> = OnceLock::new();
+    REMOTE_SERVER_STATE_DIR.get_or_init(|| support_dir().join("remote_server_state"))
+}
+
 /// Returns the relative path to a `.zed` folder within a project.
 pub fn local_settings_folder_relative_path() -> &'static Path {
     Path::new(".zed")
@@ -7,12 +7,16 @@ use collections::{hash_map, HashMap};
 use futures::{channel::oneshot, stream::FuturesUnordered, StreamExt as _};
 use git::blame::Blame;
 use gpui::{
-    AppContext, AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel,
+    AppContext, AsyncAppContext, Context as _, EntityId, EventEmitter, Model, ModelContext, Task,
+    WeakModel,
 };
 use http_client::Url;
 use language::{
-    proto::{deserialize_line_ending, deserialize_version, serialize_version, split_operations},
-    Buffer, Capability, Event as BufferEvent, File as _, Language, Operation,
+    proto::{
+        deserialize_encoding, deserialize_line_ending, deserialize_version, serialize_encoding,
+        serialize_version, split_operations,
+    },
+    Buffer, Capability, Encoding, Event as BufferEvent, File as _, Language, Operation,
 };
 use rpc::{
     proto::{self, AnyProtoClient, EnvelopedMessage, PeerId},
@@ -294,10 +298,10 @@ impl BufferStore {
         worktree: Model<Worktree>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Model<Buffer>>> {
+        let reservation = cx.reserve_model();
+        let buffer_id = self.local_buffer_id(reservation.entity_id());
         let load_buffer = worktree.update(cx, |worktree, cx| {
             let load_file = worktree.load_file(path.as_ref(), cx);
-            let reservation = cx.reserve_model();
-            let buffer_id = BufferId::from(reservation.entity_id().as_non_zero_u64());
             cx.spawn(move |_, mut cx| async move {
                 let loaded = load_file.await?;
                 let text_buffer = cx
@@ -311,6 +315,7 @@ impl BufferStore {
                         Some(loaded.file),
                         Capability::ReadWrite,
                     )
+                    .with_encoding(loaded.encoding)
                 })
             })
         });
@@ -318,23 +323,26 @@ impl BufferStore {
         cx.spawn(move |this, mut cx| async move {
             let buffer = match load_buffer.await {
                 Ok(buffer) => Ok(buffer),
-                Err(error) if is_not_found_error(&error) => cx.new_model(|cx| {
-                    let buffer_id = BufferId::from(cx.entity_id().as_non_zero_u64());
+                Err(error) if is_not_found_error(&error) => this.update(&mut cx, |this, cx| {
+                    let reservation = cx.reserve_model();
+                    let buffer_id = this.local_buffer_id(reservation.entity_id());
                     let text_buffer = text::Buffer::new(0, buffer_id, "".into());
-                    Buffer::build(
-                        text_buffer,
-                        None,
-                        Some(Arc::new(File {
-                            worktree,
-                            path,
-                            mtime: None,
-                            entry_id: None,
-                            is_local: true,
-                            is_deleted: false,
-                            is_private: false,
-                        })),
-                        Capability::ReadWrite,
-                    )
+                    cx.insert_model(reservation, |_| {
+                        Buffer::build(
+                            text_buffer,
+                            None,
+                            Some(Arc::new(File {
+                                worktree,
+                                path,
+                                mtime: None,
+                                entry_id: None,
+                                is_local: true,
+                                is_deleted: false,
+                                is_private: false,
+                            })),
+                            Capability::ReadWrite,
+                        )
+                    })
                 }),
                 Err(e) => Err(e),
             }?;
@@ -398,14 +406,138 @@ impl BufferStore {
         language: Option<Arc<Language>>,
         cx: &mut ModelContext<Self>,
     ) -> Model<Buffer> {
-        let buffer = cx.new_model(|cx| {
-            Buffer::local(text, cx)
-                .with_language(language.unwrap_or_else(|| language::PLAIN_TEXT.clone()), cx)
+        let reservation = cx.reserve_model();
+        let buffer_id = self.local_buffer_id(reservation.entity_id());
+        let buffer = cx.insert_model(reservation, |cx| {
+            Buffer::build(
+                text::Buffer::new(0, buffer_id, text.into()),
+                None,
+                None,
+                Capability::ReadWrite,
+            )
+            .with_language(language.unwrap_or_else(|| language::PLAIN_TEXT.clone()), cx)
         });
         self.add_buffer(buffer.clone(), cx).log_err();
         buffer
     }
 
+    /// Returns the id of a new local buffer, which is derived from the id of its model.
+    ///
+    /// The buffers restored for the client of an SSH project keep the ids that they were given
+    /// by the server that it was previously connected to, which may coincide with the id derived
+    /// from a new buffer's model. Those ids are skipped.
+    fn local_buffer_id(&self, entity_id: EntityId) -> BufferId {
+        let mut id = entity_id.as_non_zero_u64();
+        while self.opened_buffers.contains_key(&BufferId::from(id)) {
+            id = id.saturating_add(1 << 32);
+        }
+        BufferId::from(id)
+    }
+
+    /// Opens a buffer that the client of an SSH project kept open while the server that it was
+    /// opened on wasn't running, applying the edits that were made to it in the meantime.
+    ///
+    /// The buffer is rebuilt from the client's history, which its edits are relative to, so its
+    /// file is as the client last loaded or saved it until it's reconciled with the disk.
+    pub fn restore_buffer(
+        &mut self,
+        mut state: proto::BufferState,
+        operations: Vec<Operation>,
+        cx: &mut ModelContext<Self>,
+    ) -> Result<Model<Buffer>> {
+        let buffer_id = BufferId::new(state.id)?;
+        if self.opened_buffers.contains_key(&buffer_id) {
+            return Err(anyhow!("buffer {buffer_id} is already open"));
+        }
+
+        let file = state
+            .file
+            .take()
+            .map(|file| -> Result<Arc<dyn language::File>> {
+                let worktree_id = WorktreeId::from_proto(file.worktree_id);
+                let worktree = self
+                    .worktree_store
+                    .read(cx)
+                    .worktree_for_id(worktree_id, cx)
+                    .ok_or_else(|| anyhow!("no worktree found for id {}", file.worktree_id))?;
+                Ok(Arc::new(File {
+                    worktree,
+                    path: Path::new(&file.path).into(),
+                    mtime: file.mtime.map(|time| time.into()),
+                    entry_id: None,
+                    is_local: true,
+                    is_deleted: file.is_deleted,
+                    is_private: false,
+                }))
+            })
+            .transpose()?;
+        let buffer = Buffer::from_proto(0, Capability::ReadWrite, state, file)?;
+        let buffer = cx.new_model(|_| buffer);
+        buffer.update(cx, |buffer, cx| buffer.apply_ops(operations, cx))?;
+        self.add_buffer(buffer.clone(), cx)?;
+        Ok(buffer)
+    }
+
+    /// Brings a restored buffer up to date with its file on disk, which may have changed since
+    /// the client last loaded or saved it. The buffer is reloaded if the file has changed and the
+    /// client has no unsaved edits, and has a conflict if the client does.
+    pub fn reconcile_restored_buffer(
+        &mut self,
+        buffer: Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(file) = File::from_dyn(buffer.read(cx).file()).cloned() else {
+            return Task::ready(Ok(()));
+        };
+
+        let load_file = file
+            .worktree
+            .update(cx, |worktree, cx| worktree.load_file(&file.path, cx));
+        cx.spawn(move |this, mut cx| async move {
+            let (new_file, diff_base) = match load_file.await {
+                Ok(loaded) => (loaded.file, loaded.diff_base),
+                Err(error) if is_not_found_error(&error) => (
+                    Arc::new(File {
+                        entry_id: None,
+                        is_deleted: true,
+                        ..file
+                    }),
+                    None,
+                ),
+                Err(error) => return Err(error),
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let buffer_id = buffer.update(cx, |buffer, cx| {
+                    buffer.set_diff_base(diff_base.clone(), cx);
+                    buffer.file_updated(new_file.clone(), cx);
+                    buffer.remote_id().to_proto()
+                });
+                this.buffer_changed_file(buffer.clone(), cx);
+
+                // The client doesn't know the entry of the buffer's file in the new worktree yet.
+                if let Some(project_id) = this.remote_id {
+                    cx.emit(BufferStoreEvent::MessageToReplicas(Box::new(
+                        proto::UpdateBufferFile {
+                            project_id,
+                            buffer_id,
+                            file: Some(new_file.to_proto(cx)),
+                        }
+                        .into_envelope(0, None, None),
+                    )));
+                    cx.emit(BufferStoreEvent::MessageToReplicas(Box::new(
+                        proto::UpdateDiffBase {
+                            project_id,
+                            buffer_id,
+                            diff_base,
+                        }
+                        .into_envelope(0, None, None),
+                    )));
+                }
+            })
+        })
+    }
+
     pub fn save_buffer(
         &mut self,
         buffer: Model<Buffer>,
@@ -465,6 +597,7 @@ impl BufferStore {
         let buffer = buffer_handle.read(cx);
         let text = buffer.as_rope().clone();
         let line_ending = buffer.line_ending();
+        let encoding = buffer.encoding();
         let version = buffer.version();
         let buffer_id = buffer.remote_id();
         if buffer.file().is_some_and(|file| !file.is_created()) {
@@ -472,7 +605,7 @@ impl BufferStore {
         }
 
         let save = worktree.update(cx, |worktree, cx| {
-            worktree.write_file(path.as_ref(), text, line_ending, cx)
+            worktree.write_file(path.as_ref(), text, line_ending, encoding, cx)
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -496,6 +629,7 @@ impl BufferStore {
                             buffer_id: buffer_id.to_proto(),
                             version: serialize_version(&version),
                             mtime: mtime.map(|time| time.into()),
+                            encoding: Some(serialize_encoding(encoding)),
                         }
                         .into_envelope(0, None, None),
                     )));
@@ -520,6 +654,7 @@ impl BufferStore {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id().into();
         let version = buffer.version();
+        let encoding = buffer.encoding();
         let rpc = tree.client();
         let project_id = tree.project_id();
         cx.spawn(move |_, mut cx| async move {
@@ -529,6 +664,7 @@ impl BufferStore {
                     buffer_id,
                     new_path,
                     version: serialize_version(&version),
+                    encoding: Some(serialize_encoding(encoding)),
                 })
                 .await?;
             let version = deserialize_version(&response.version);
@@ -542,6 +678,46 @@ impl BufferStore {
         })
     }
 
+    /// Reloads a buffer's file, decoding it with the given encoding instead of the one that the
+    /// buffer was opened with. The buffer is then saved with that encoding.
+    pub fn reopen_buffer_with_encoding(
+        &self,
+        buffer: Model<Buffer>,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(file) = File::from_dyn(buffer.read(cx).file()) else {
+            return Task::ready(Err(anyhow!("buffer doesn't have a file")));
+        };
+        if buffer.read(cx).is_dirty() {
+            return Task::ready(Err(anyhow!(
+                "save or discard the buffer's changes before reopening it"
+            )));
+        }
+
+        match file.worktree.read(cx) {
+            Worktree::Local(_) => {
+                let reload =
+                    buffer.update(cx, |buffer, cx| buffer.reload_with_encoding(encoding, cx));
+                cx.background_executor().spawn(async move {
+                    reload.await??;
+                    Ok(())
+                })
+            }
+            Worktree::Remote(tree) => {
+                let request = tree.client().request(proto::ReopenBuffer {
+                    project_id: tree.project_id(),
+                    buffer_id: buffer.read(cx).remote_id().into(),
+                    encoding: Some(serialize_encoding(encoding)),
+                });
+                cx.background_executor().spawn(async move {
+                    request.await?;
+                    Ok(())
+                })
+            }
+        }
+    }
+
     pub fn blame_buffer(
         &self,
         buffer: &Model<Buffer>,
@@ -1008,7 +1184,11 @@ impl BufferStore {
                         buffer.update(cx, |buffer, cx| buffer.apply_ops(ops, cx))?;
                     }
                     OpenBuffer::Operations(operations) => operations.extend_from_slice(&ops),
-                    OpenBuffer::Weak(_) => {}
+                    OpenBuffer::Weak(buffer) => {
+                        if let Some(buffer) = buffer.upgrade() {
+                            buffer.update(cx, |buffer, cx| buffer.apply_ops(ops, cx))?;
+                        }
+                    }
                 },
                 hash_map::Entry::Vacant(e) => {
                     e.insert(OpenBuffer::Operations(ops));
@@ -1095,7 +1275,11 @@ impl BufferStore {
                     }
                 } else if chunk.is_last {
                     self.loading_remote_buffers_by_id.remove(&buffer_id);
-                    self.add_buffer(buffer, cx)?;
+                    // The headless server of an SSH project can't tell which of the buffers it
+                    // has sent are still open, so it may send one again. The open one is kept.
+                    if self.get(buffer_id).is_none() {
+                        self.add_buffer(buffer, cx)?;
+                    }
                 }
             }
         }
@@ -1176,7 +1360,12 @@ impl BufferStore {
                 buffer.wait_for_version(deserialize_version(&envelope.payload.version))
             })?
             .await?;
-        let buffer_id = buffer.update(&mut cx, |buffer, _| buffer.remote_id())?;
+        let buffer_id = buffer.update(&mut cx, |buffer, cx| {
+            if let Some(encoding) = envelope.payload.encoding {
+                buffer.set_encoding(deserialize_encoding(encoding)?, cx);
+            }
+            anyhow::Ok(buffer.remote_id())
+        })??;
 
         if let Some(new_path) = envelope.payload.new_path {
             let new_path = ProjectPath::from_proto(new_path);
@@ -1194,6 +1383,7 @@ impl BufferStore {
             buffer_id: buffer_id.into(),
             version: serialize_version(buffer.saved_version()),
             mtime: buffer.saved_mtime().map(|time| time.into()),
+            encoding: Some(serialize_encoding(buffer.encoding())),
         })
     }
 
@@ -1205,9 +1395,17 @@ impl BufferStore {
         let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
         let version = deserialize_version(&envelope.payload.version);
         let mtime = envelope.payload.mtime.map(|time| time.into());
+        let encoding = envelope
+            .payload
+            .encoding
+            .map(deserialize_encoding)
+            .transpose()?;
         this.update(&mut cx, |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_save(version, mtime, cx);
                 });
             }
@@ -1226,15 +1424,43 @@ impl BufferStore {
             proto::LineEnding::from_i32(envelope.payload.line_ending)
                 .ok_or_else(|| anyhow!("missing line ending"))?,
         );
+        let encoding = envelope
+            .payload
+            .encoding
+            .map(deserialize_encoding)
+            .transpose()?;
         this.update(&mut cx, |this, cx| {
             if let Some(buffer) = this.get_possibly_incomplete(buffer_id) {
                 buffer.update(cx, |buffer, cx| {
+                    if let Some(encoding) = encoding {
+                        buffer.set_encoding(encoding, cx);
+                    }
                     buffer.did_reload(version, line_ending, mtime, cx);
                 });
             }
         })
     }
 
+    pub async fn handle_reopen_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ReopenBuffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let encoding = deserialize_encoding(
+            envelope
+                .payload
+                .encoding
+                .ok_or_else(|| anyhow!("missing encoding"))?,
+        )?;
+        this.update(&mut cx, |this, cx| {
+            let buffer = this.get_existing(buffer_id)?;
+            anyhow::Ok(this.reopen_buffer_with_encoding(buffer, encoding, cx))
+        })??
+        .await?;
+        Ok(proto::Ack {})
+    }
+
     pub async fn handle_blame_buffer(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::BlameBuffer>,
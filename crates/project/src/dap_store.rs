@@ -0,0 +1,612 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context as _, Result};
+use collections::{BTreeMap, HashMap};
+use dap::{
+    adapters::{DebugAdapter, DebugTaskDefinition},
+    events::{Continued, Exited, Initialized, Output, Stopped, Terminated, Thread as ThreadEvent},
+    requests::{
+        Attach, ConfigurationDone, Continue, Evaluate, Launch, Next, Pause, Scopes, SetBreakpoints,
+        StackTrace, StepIn, StepOut, Threads, Variables,
+    },
+    ContinueArguments, DebugAdapterClient, DebugAdapterClientId, EvaluateArguments,
+    EvaluateArgumentsContext, EvaluateResponse, OutputEventBody, PauseArguments, Scope,
+    ScopesArguments, SetBreakpointsArguments, Source, SourceBreakpoint, StackFrame,
+    StackTraceArguments, StepArguments, Thread, Variable, VariablesArguments,
+};
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+    Future, FutureExt as _,
+};
+use gpui::{AsyncAppContext, Context as _, EventEmitter, Model, ModelContext, Task, WeakModel};
+use language::{Buffer, LspAdapterDelegate};
+use task::DebugRequestType;
+use text::{Anchor, BufferId, BufferSnapshot, ToPoint as _};
+use util::{post_inc, ResultExt as _, TryFutureExt as _};
+
+/// The breakpoints of the project and the debug sessions running in it.
+#[derive(Default)]
+pub struct DapStore {
+    next_client_id: usize,
+    sessions: BTreeMap<DebugAdapterClientId, Model<DebugSession>>,
+    breakpoints: HashMap<BufferId, BufferBreakpoints>,
+    session_subscriptions: HashMap<DebugAdapterClientId, gpui::Subscription>,
+}
+
+struct BufferBreakpoints {
+    buffer: Model<Buffer>,
+    positions: Vec<Anchor>,
+}
+
+pub enum DapStoreEvent {
+    SessionStarted(Model<DebugSession>),
+    SessionEnded(DebugAdapterClientId),
+    BreakpointsChanged(BufferId),
+}
+
+impl EventEmitter<DapStoreEvent> for DapStore {}
+
+impl DapStore {
+    pub fn sessions(&self) -> impl Iterator<Item = &Model<DebugSession>> {
+        self.sessions.values()
+    }
+
+    pub fn session(&self, id: DebugAdapterClientId) -> Option<&Model<DebugSession>> {
+        self.sessions.get(&id)
+    }
+
+    /// Adds a breakpoint at the given row of the buffer, or removes the one already there.
+    pub fn toggle_breakpoint(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let snapshot = buffer.read(cx).text_snapshot();
+        let buffer_id = snapshot.remote_id();
+        let breakpoints = self
+            .breakpoints
+            .entry(buffer_id)
+            .or_insert_with(|| BufferBreakpoints {
+                buffer: buffer.clone(),
+                positions: Vec::new(),
+            });
+        let previous_len = breakpoints.positions.len();
+        breakpoints
+            .positions
+            .retain(|position| position.to_point(&snapshot).row != row);
+        if breakpoints.positions.len() == previous_len {
+            let position = snapshot.anchor_after(text::Point::new(row, 0));
+            breakpoints.positions.push(position);
+        }
+        if breakpoints.positions.is_empty() {
+            self.breakpoints.remove(&buffer_id);
+        }
+
+        cx.emit(DapStoreEvent::BreakpointsChanged(buffer_id));
+        self.send_breakpoints(buffer_id, cx);
+    }
+
+    /// Returns the rows of the buffer with a breakpoint, in ascending order.
+    pub fn breakpoint_rows(&self, buffer_id: BufferId, snapshot: &BufferSnapshot) -> Vec<u32> {
+        let Some(breakpoints) = self.breakpoints.get(&buffer_id) else {
+            return Vec::new();
+        };
+        let mut rows = breakpoints
+            .positions
+            .iter()
+            .map(|position| position.to_point(snapshot).row)
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Returns the breakpoints of a buffer in the form debug adapters expect them,
+    /// unless the buffer has no file on disk.
+    fn source_breakpoints(
+        &self,
+        buffer_id: BufferId,
+        cx: &ModelContext<Self>,
+    ) -> Option<(Source, Vec<SourceBreakpoint>)> {
+        let buffer = self.breakpoints.get(&buffer_id)?.buffer.read(cx);
+        let abs_path = buffer.file()?.as_local()?.abs_path(cx);
+        let breakpoints = self
+            .breakpoint_rows(buffer_id, &buffer.text_snapshot())
+            .into_iter()
+            .map(|row| SourceBreakpoint {
+                line: row as u64 + 1,
+                ..Default::default()
+            })
+            .collect();
+        let source = Source {
+            name: abs_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            path: Some(abs_path.to_string_lossy().into_owned()),
+            source_reference: None,
+        };
+        Some((source, breakpoints))
+    }
+
+    /// Sends the breakpoints of a buffer to the debug sessions past their startup,
+    /// which send every breakpoint themselves.
+    fn send_breakpoints(&self, buffer_id: BufferId, cx: &mut ModelContext<Self>) {
+        let Some((source, breakpoints)) = self.source_breakpoints(buffer_id, cx) else {
+            return;
+        };
+        for session in self.sessions.values() {
+            let session = session.read(cx);
+            if session.status == DebugSessionStatus::Starting {
+                continue;
+            }
+            let request = session
+                .client
+                .request::<SetBreakpoints>(SetBreakpointsArguments {
+                    source: source.clone(),
+                    breakpoints: Some(breakpoints.clone()),
+                    source_modified: None,
+                });
+            cx.background_executor().spawn(request.log_err()).detach();
+        }
+    }
+
+    /// Starts a debug adapter and asks it to debug the given task.
+    pub fn start_session(
+        &mut self,
+        definition: DebugTaskDefinition,
+        adapter: Arc<dyn DebugAdapter>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        working_dir: PathBuf,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<DebugSession>>> {
+        let id = DebugAdapterClientId(post_inc(&mut self.next_client_id));
+        let request_args = adapter.request_args(&definition);
+        cx.spawn(move |this, mut cx| async move {
+            let client = Self::start_client(
+                id,
+                adapter.as_ref(),
+                delegate,
+                &definition,
+                &working_dir,
+                &cx,
+            )
+            .await
+            .with_context(|| format!("failed to start {} debug adapter", adapter.name()))?;
+            let client = Arc::new(client);
+
+            let (initialized_tx, initialized_rx) = oneshot::channel();
+            let mut initialized_tx = Some(initialized_tx);
+            let _initialized = client.on_event::<Initialized, _>(move |_, _| {
+                if let Some(initialized_tx) = initialized_tx.take() {
+                    initialized_tx.send(()).ok();
+                }
+            });
+
+            let session = this.update(&mut cx, |this, cx| {
+                let session =
+                    cx.new_model(|cx| DebugSession::new(client.clone(), definition.label, cx));
+                this.add_session(session.clone(), cx);
+                session
+            })?;
+
+            let result = Self::initialize_session(
+                this.clone(),
+                &client,
+                &definition.config.request,
+                request_args,
+                initialized_rx,
+                &mut cx,
+            )
+            .await;
+            match result {
+                Ok(()) => {
+                    session.update(&mut cx, |session, cx| {
+                        if session.status == DebugSessionStatus::Starting {
+                            session.set_status(DebugSessionStatus::Running, cx);
+                        }
+                        session.refresh_threads(cx);
+                    })?;
+                    Ok(session)
+                }
+                Err(error) => {
+                    this.update(&mut cx, |this, cx| this.remove_session(id, cx))?;
+                    Err(error)
+                }
+            }
+        })
+    }
+
+    async fn start_client(
+        id: DebugAdapterClientId,
+        adapter: &dyn DebugAdapter,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        definition: &DebugTaskDefinition,
+        working_dir: &Path,
+        cx: &AsyncAppContext,
+    ) -> Result<DebugAdapterClient> {
+        let name = adapter.name().0;
+
+        #[cfg(any(test, feature = "test-support"))]
+        if let Some(fake) = adapter.as_fake() {
+            let (client, fake_adapter) =
+                dap::FakeDebugAdapter::new(id, name, fake.capabilities.clone(), cx.clone());
+            (fake.initializer)(&fake_adapter);
+            fake.fakes_tx.unbounded_send(fake_adapter).ok();
+            return Ok(client);
+        }
+
+        let binary = adapter.get_binary(delegate, &definition.config).await?;
+        DebugAdapterClient::start(id, name, &binary, working_dir, cx.clone()).await
+    }
+
+    /// Runs the startup sequence of a debug session: initializes the adapter, asks it to launch
+    /// (or attach to) the debuggee, and configures the breakpoints once the adapter is ready for them.
+    async fn initialize_session(
+        this: WeakModel<Self>,
+        client: &DebugAdapterClient,
+        request: &DebugRequestType,
+        request_args: serde_json::Value,
+        initialized_rx: oneshot::Receiver<()>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<()> {
+        let capabilities = client.initialize().await?;
+
+        let launch = match request {
+            DebugRequestType::Launch => client.request::<Launch>(request_args).boxed_local(),
+            DebugRequestType::Attach { .. } => client.request::<Attach>(request_args).boxed_local(),
+        };
+        // Some adapters only respond to `launch` once configured, others only send the
+        // `initialized` event after responding.
+        let launch = match future::select(launch, initialized_rx).await {
+            Either::Left((result, initialized_rx)) => {
+                result?;
+                initialized_rx
+                    .await
+                    .map_err(|_| anyhow!("debug adapter stopped before initializing"))?;
+                None
+            }
+            Either::Right((_, launch)) => Some(launch),
+        };
+
+        let breakpoints = this.update(cx, |this, cx| {
+            this.breakpoints
+                .keys()
+                .filter_map(|buffer_id| this.source_breakpoints(*buffer_id, cx))
+                .collect::<Vec<_>>()
+        })?;
+        for (source, breakpoints) in breakpoints {
+            client
+                .request::<SetBreakpoints>(SetBreakpointsArguments {
+                    source,
+                    breakpoints: Some(breakpoints),
+                    source_modified: None,
+                })
+                .await
+                .log_err();
+        }
+        if capabilities.supports_configuration_done_request == Some(true) {
+            client.request::<ConfigurationDone>(()).await?;
+        }
+
+        if let Some(launch) = launch {
+            launch.await?;
+        }
+        Ok(())
+    }
+
+    fn add_session(&mut self, session: Model<DebugSession>, cx: &mut ModelContext<Self>) {
+        let id = session.read(cx).id();
+        let subscription = cx.subscribe(&session, move |this, _, event, cx| {
+            if let DebugSessionEvent::Terminated = event {
+                this.remove_session(id, cx);
+            }
+        });
+        self.session_subscriptions.insert(id, subscription);
+        self.sessions.insert(id, session.clone());
+        cx.emit(DapStoreEvent::SessionStarted(session));
+    }
+
+    fn remove_session(&mut self, id: DebugAdapterClientId, cx: &mut ModelContext<Self>) {
+        self.session_subscriptions.remove(&id);
+        if let Some(session) = self.sessions.remove(&id) {
+            if let Some(shutdown) = session.read(cx).client.shutdown() {
+                cx.background_executor().spawn(shutdown).detach();
+            }
+            cx.emit(DapStoreEvent::SessionEnded(id));
+        }
+    }
+
+    /// Ends a debug session, terminating its debuggee.
+    pub fn stop_session(&mut self, id: DebugAdapterClientId, cx: &mut ModelContext<Self>) {
+        self.remove_session(id, cx);
+    }
+
+    /// Ends every debug session.
+    pub fn shutdown_sessions(&mut self, cx: &mut ModelContext<Self>) -> impl Future<Output = ()> {
+        let shutdowns = self
+            .sessions
+            .values()
+            .filter_map(|session| session.read(cx).client.shutdown())
+            .collect::<Vec<_>>();
+        self.sessions.clear();
+        self.session_subscriptions.clear();
+        future::join_all(shutdowns).map(|_| ())
+    }
+}
+
+/// The state of the debuggee of a [`DebugSession`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugSessionStatus {
+    Starting,
+    Running,
+    Stopped {
+        thread_id: Option<u64>,
+        reason: String,
+    },
+    Exited,
+}
+
+pub enum DebugSessionEvent {
+    StatusChanged,
+    ThreadsChanged,
+    Output,
+    Terminated,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepKind {
+    Over,
+    In,
+    Out,
+}
+
+/// A debuggee, driven through a debug adapter.
+pub struct DebugSession {
+    client: Arc<DebugAdapterClient>,
+    label: String,
+    status: DebugSessionStatus,
+    threads: Vec<Thread>,
+    output: Vec<OutputEventBody>,
+}
+
+impl EventEmitter<DebugSessionEvent> for DebugSession {}
+
+impl DebugSession {
+    fn new(client: Arc<DebugAdapterClient>, label: String, cx: &mut ModelContext<Self>) -> Self {
+        // The handlers only hold a weak handle on the session, and go away with its client.
+        let this = cx.weak_model();
+        client
+            .on_event::<Stopped, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| {
+                        this.set_status(
+                            DebugSessionStatus::Stopped {
+                                thread_id: body.thread_id,
+                                reason: body.reason,
+                            },
+                            cx,
+                        );
+                        this.refresh_threads(cx);
+                    })
+                    .ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<Continued, _>({
+                let this = this.clone();
+                move |_, mut cx| {
+                    this.update(&mut cx, |this, cx| {
+                        this.set_status(DebugSessionStatus::Running, cx);
+                    })
+                    .ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<ThreadEvent, _>({
+                let this = this.clone();
+                move |_, mut cx| {
+                    this.update(&mut cx, |this, cx| this.refresh_threads(cx))
+                        .ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<Output, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| this.push_output(body, cx))
+                        .ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<Exited, _>({
+                let this = this.clone();
+                move |body, mut cx| {
+                    this.update(&mut cx, |this, cx| {
+                        this.push_output(
+                            OutputEventBody {
+                                category: Some("console".into()),
+                                output: format!("Process exited with code {}\n", body.exit_code),
+                            },
+                            cx,
+                        );
+                        this.set_status(DebugSessionStatus::Exited, cx);
+                    })
+                    .ok();
+                }
+            })
+            .detach();
+        client
+            .on_event::<Terminated, _>(move |_, mut cx| {
+                this.update(&mut cx, |this, cx| {
+                    this.set_status(DebugSessionStatus::Exited, cx);
+                    cx.emit(DebugSessionEvent::Terminated);
+                })
+                .ok();
+            })
+            .detach();
+
+        Self {
+            client,
+            label,
+            status: DebugSessionStatus::Starting,
+            threads: Vec::new(),
+            output: Vec::new(),
+        }
+    }
+
+    pub fn id(&self) -> DebugAdapterClientId {
+        self.client.id()
+    }
+
+    pub fn adapter_name(&self) -> &str {
+        self.client.name()
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    pub fn status(&self) -> &DebugSessionStatus {
+        &self.status
+    }
+
+    pub fn threads(&self) -> &[Thread] {
+        &self.threads
+    }
+
+    pub fn output(&self) -> &[OutputEventBody] {
+        &self.output
+    }
+
+    fn set_status(&mut self, status: DebugSessionStatus, cx: &mut ModelContext<Self>) {
+        if self.status != status {
+            self.status = status;
+            cx.emit(DebugSessionEvent::StatusChanged);
+            cx.notify();
+        }
+    }
+
+    /// Appends to the output of the session, as shown in its console.
+    pub fn push_output(&mut self, output: OutputEventBody, cx: &mut ModelContext<Self>) {
+        if output.category.as_deref() == Some("telemetry") {
+            return;
+        }
+        self.output.push(output);
+        cx.emit(DebugSessionEvent::Output);
+        cx.notify();
+    }
+
+    fn refresh_threads(&mut self, cx: &mut ModelContext<Self>) {
+        let threads = self.client.request::<Threads>(());
+        cx.spawn(|this, mut cx| async move {
+            let threads = threads.await?;
+            this.update(&mut cx, |this, cx| {
+                this.threads = threads.threads;
+                cx.emit(DebugSessionEvent::ThreadsChanged);
+                cx.notify();
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// The thread to resume, step or inspect: the one that stopped last, or the first one.
+    pub fn current_thread_id(&self) -> Option<u64> {
+        match &self.status {
+            DebugSessionStatus::Stopped {
+                thread_id: Some(thread_id),
+                ..
+            } => Some(*thread_id),
+            _ => self.threads.first().map(|thread| thread.id),
+        }
+    }
+
+    pub fn resume(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(thread_id) = self.current_thread_id() else {
+            return Task::ready(Err(anyhow!("no thread to continue")));
+        };
+        let request = self.client.request::<Continue>(ContinueArguments {
+            thread_id,
+            single_thread: None,
+        });
+        self.set_status(DebugSessionStatus::Running, cx);
+        cx.background_executor()
+            .spawn(async move { request.await.map(|_| ()) })
+    }
+
+    pub fn step(&mut self, kind: StepKind, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(thread_id) = self.current_thread_id() else {
+            return Task::ready(Err(anyhow!("no thread to step")));
+        };
+        let arguments = StepArguments {
+            thread_id,
+            single_thread: None,
+        };
+        let request = match kind {
+            StepKind::Over => self.client.request::<Next>(arguments).boxed(),
+            StepKind::In => self.client.request::<StepIn>(arguments).boxed(),
+            StepKind::Out => self.client.request::<StepOut>(arguments).boxed(),
+        };
+        self.set_status(DebugSessionStatus::Running, cx);
+        cx.background_executor().spawn(request)
+    }
+
+    pub fn pause(&mut self, cx: &mut ModelContext<Self>) -> Task<Result<()>> {
+        let Some(thread_id) = self.current_thread_id() else {
+            return Task::ready(Err(anyhow!("no thread to pause")));
+        };
+        cx.background_executor()
+            .spawn(self.client.request::<Pause>(PauseArguments { thread_id }))
+    }
+
+    pub fn stack_frames(
+        &self,
+        thread_id: u64,
+    ) -> impl 'static + Future<Output = Result<Vec<StackFrame>>> {
+        let request = self.client.request::<StackTrace>(StackTraceArguments {
+            thread_id,
+            start_frame: None,
+            levels: None,
+        });
+        async move { Ok(request.await?.stack_frames) }
+    }
+
+    pub fn scopes(&self, frame_id: u64) -> impl 'static + Future<Output = Result<Vec<Scope>>> {
+        let request = self.client.request::<Scopes>(ScopesArguments { frame_id });
+        async move { Ok(request.await?.scopes) }
+    }
+
+    pub fn variables(
+        &self,
+        variables_reference: u64,
+    ) -> impl 'static + Future<Output = Result<Vec<Variable>>> {
+        let request = self.client.request::<Variables>(VariablesArguments {
+            variables_reference,
+            start: None,
+            count: None,
+        });
+        async move { Ok(request.await?.variables) }
+    }
+
+    pub fn evaluate(
+        &self,
+        expression: String,
+        frame_id: Option<u64>,
+        context: EvaluateArgumentsContext,
+    ) -> impl 'static + Future<Output = Result<EvaluateResponse>> {
+        self.client.request::<Evaluate>(EvaluateArguments {
+            expression,
+            frame_id,
+            context: Some(context),
+        })
+    }
+}
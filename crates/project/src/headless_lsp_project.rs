@@ -0,0 +1,220 @@
+use crate::{
+    buffer_store::BufferStore, lsp_command::*, lsp_ext_command, search::SearchQuery,
+    worktree_store::WorktreeStore, Project, SearchResult,
+};
+use client::{Client, UserStore};
+use clock::RealSystemClock;
+use fs::Fs;
+use gpui::{Context as _, Model, ModelContext};
+use http_client::HttpClientWithUrl;
+use language::{Buffer, BufferId, LanguageRegistry};
+use node_runtime::NodeRuntime;
+use remote::SshSession;
+use rpc::proto::PeerId;
+use smol::channel::Receiver;
+use std::sync::Arc;
+use worktree::Worktree;
+
+/// A local [`Project`] that runs the language servers of an SSH project on its remote machine,
+/// for the worktrees and buffers of the project's headless server.
+///
+/// The project keeps its worktrees and buffers in the headless server's stores. Its [`Client`]
+/// and [`UserStore`] are never connected, as the project is only ever shared with the SSH
+/// client, through the server's session. It handles the requests of the client's
+/// [`LspCommand`](crate::lsp_command::LspCommand)s, and sends the diagnostics and statuses of
+/// its language servers back to the client. The client's tasks are resolved by it too, as the
+/// language context providers of task variables may need to inspect the remote worktrees.
+pub struct HeadlessLspProject {
+    project: Model<Project>,
+}
+
+impl HeadlessLspProject {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        session: Arc<SshSession>,
+        worktree_store: Model<WorktreeStore>,
+        buffer_store: Model<BufferStore>,
+        http: Arc<HttpClientWithUrl>,
+        node: Arc<dyn NodeRuntime>,
+        languages: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let client = Client::new(Arc::new(RealSystemClock), http, cx);
+        let user_store = cx.new_model(|cx| UserStore::new(client.clone(), cx));
+        let project = cx.new_model(|cx| {
+            let mut project = Project::new_local(
+                worktree_store,
+                buffer_store,
+                client,
+                node,
+                user_store,
+                languages,
+                fs,
+                cx,
+            );
+            project.downstream_ssh_session = Some(session.clone().into());
+            project
+        });
+
+        let handle = project.downgrade();
+        session.add_request_handler(handle.clone(), Project::handle_multi_lsp_query);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetCodeActions>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetCompletions>,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetHover>);
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetDefinition>);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetTypeDefinition>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetDocumentHighlights>,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetReferences>);
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<PrepareRename>);
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<PerformRename>);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<lsp_ext_command::ExpandMacro>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<LinkedEditingRange>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<PrepareCallHierarchy>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetIncomingCalls>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetOutgoingCalls>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<PrepareTypeHierarchy>,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetSupertypes>);
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetSubtypes>);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetFoldingRanges>,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_lsp_command::<GetCodeLens>);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetDocumentLinks>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetDocumentColors>,
+        );
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_lsp_command::<GetColorPresentations>,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_inlay_hints);
+        session.add_request_handler(handle.clone(), Project::handle_resolve_inlay_hint);
+        session.add_request_handler(handle.clone(), Project::handle_get_semantic_tokens);
+        session.add_request_handler(handle.clone(), Project::handle_resolve_code_lens);
+        session.add_request_handler(handle.clone(), Project::handle_resolve_document_link);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_resolve_completion_documentation,
+        );
+        session.add_request_handler(handle.clone(), Project::handle_get_project_symbols);
+        session.add_request_handler(handle.clone(), Project::handle_open_buffer_for_symbol);
+        session.add_request_handler(handle.clone(), Project::handle_restart_language_servers);
+
+        // Requests that edit buffers on the remote machine, whose transactions are sent back to
+        // the client.
+        session.add_request_handler(handle.clone(), Project::handle_apply_code_action);
+        session.add_request_handler(handle.clone(), Project::handle_execute_code_lens);
+        session.add_request_handler(handle.clone(), Project::handle_format_buffers);
+        session.add_request_handler(handle.clone(), Project::handle_on_type_formatting);
+        session.add_request_handler(
+            handle.clone(),
+            Project::handle_apply_additional_edits_for_completion,
+        );
+
+        // Tasks are resolved with the paths, shell environment and language context providers
+        // of the remote machine.
+        session.add_request_handler(handle.clone(), Project::handle_task_context_for_location);
+        session.add_request_handler(handle, Project::handle_task_templates);
+
+        Self { project }
+    }
+
+    /// Adds a worktree, so that language servers are started for its files and told about
+    /// changes to them.
+    pub fn add_worktree(&mut self, worktree: &Model<Worktree>, cx: &mut ModelContext<Self>) {
+        self.project
+            .update(cx, |project, cx| project.add_worktree(worktree, cx));
+    }
+
+    /// Searches the worktrees, and the buffers opened in them.
+    pub fn search(
+        &mut self,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<SearchResult> {
+        self.project
+            .update(cx, |project, cx| project.search(query, cx))
+    }
+
+    /// Sends a buffer that the client has opened or found in a search to it, unless the client
+    /// already has it.
+    pub fn create_buffer_for_peer(
+        &mut self,
+        buffer: &Model<Buffer>,
+        peer_id: PeerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.project.update(cx, |project, cx| {
+            project.create_buffer_for_peer(buffer, peer_id, cx);
+        });
+    }
+
+    /// Records that the client has closed a buffer, so that it's sent again the next time the
+    /// client needs it.
+    pub fn forget_shared_buffer(
+        &mut self,
+        buffer_id: BufferId,
+        peer_id: PeerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.project.update(cx, |project, _| {
+            if let Some(shared_buffers) = project.shared_buffers.get_mut(&peer_id) {
+                shared_buffers.remove(&buffer_id);
+            }
+        });
+    }
+
+    /// Records that the client already has a buffer, which it restored on this server, so that
+    /// it isn't sent to the client again.
+    pub fn mark_buffer_as_shared(
+        &mut self,
+        buffer: &Model<Buffer>,
+        peer_id: PeerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.project.update(cx, |project, cx| {
+            let buffer_id = buffer.read(cx).remote_id();
+            project
+                .shared_buffers
+                .entry(peer_id)
+                .or_default()
+                .insert(buffer_id);
+        });
+    }
+}
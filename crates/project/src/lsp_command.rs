@@ -1,9 +1,12 @@
+mod semantic_tokens;
 mod signature_help;
 
 use crate::{
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
-    InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, Project, ProjectTransaction, ResolveState,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, ColorPresentation, CoreCompletion,
+    DocumentColor, DocumentHighlight, DocumentLink, FoldingRange, HierarchyItem, Hover, HoverBlock,
+    HoverBlockKind, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip,
+    InlayHintTooltip, Location, LocationLink, MarkupContent, Project, ProjectTransaction,
+    ResolveState, TypeHierarchyItem,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -25,10 +28,13 @@ use lsp::{
     LanguageServer, LanguageServerId, LinkedEditingRangeServerCapabilities, OneOf,
     ServerCapabilities,
 };
+use serde::{de::DeserializeOwned, Serialize};
 use signature_help::{lsp_to_proto_signature, proto_to_lsp_signature};
 use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
-use text::{BufferId, LineEnding};
+use text::{BufferId, LineEnding, Point};
+use util::ResultExt;
 
+pub use semantic_tokens::{SemanticToken, SemanticTokens};
 pub use signature_help::{
     SignatureHelp, SIGNATURE_HELP_HIGHLIGHT_CURRENT, SIGNATURE_HELP_HIGHLIGHT_OVERLOAD,
 };
@@ -169,6 +175,48 @@ pub(crate) struct LinkedEditingRange {
     pub position: Anchor,
 }
 
+pub(crate) struct GetSemanticTokensFull;
+
+pub(crate) struct GetSemanticTokensDelta {
+    pub previous: Arc<SemanticTokens>,
+}
+
+pub(crate) struct GetFoldingRanges;
+
+pub(crate) struct GetCodeLens;
+
+pub(crate) struct GetDocumentLinks;
+
+pub(crate) struct GetDocumentColors;
+
+pub(crate) struct GetColorPresentations {
+    pub color: DocumentColor,
+}
+
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetIncomingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct GetOutgoingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetSupertypes {
+    pub item: TypeHierarchyItem,
+}
+
+pub(crate) struct GetSubtypes {
+    pub item: TypeHierarchyItem,
+}
+
 #[async_trait(?Send)]
 impl LspCommand for PrepareRename {
     type Response = Option<Range<Anchor>>;
@@ -2892,3 +2940,1744 @@ impl LspCommand for LinkedEditingRange {
         BufferId::new(message.buffer_id)
     }
 }
+
+fn semantic_tokens_options(
+    capabilities: &ServerCapabilities,
+) -> Option<&lsp::SemanticTokensOptions> {
+    match capabilities.semantic_tokens_provider.as_ref()? {
+        lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => Some(options),
+        lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            Some(&options.semantic_tokens_options)
+        }
+    }
+}
+
+fn semantic_tokens_legend(
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncAppContext,
+) -> Result<Arc<lsp::SemanticTokensLegend>> {
+    let (_, language_server) = language_server_for_buffer(project, buffer, server_id, cx)?;
+    let legend = semantic_tokens_options(&language_server.capabilities())
+        .map(|options| options.legend.clone())
+        .context("language server does not provide semantic tokens")?;
+    Ok(Arc::new(legend))
+}
+
+fn semantic_tokens_to_proto(tokens: SemanticTokens) -> proto::GetSemanticTokensResponse {
+    proto::GetSemanticTokensResponse {
+        result_id: tokens.result_id,
+        data: tokens.data,
+        token_types: tokens
+            .legend
+            .token_types
+            .iter()
+            .map(|token_type| token_type.as_str().to_string())
+            .collect(),
+        token_modifiers: tokens
+            .legend
+            .token_modifiers
+            .iter()
+            .map(|modifier| modifier.as_str().to_string())
+            .collect(),
+        version: serialize_version(&tokens.version),
+    }
+}
+
+async fn semantic_tokens_from_proto(
+    message: proto::GetSemanticTokensResponse,
+    buffer: Model<Buffer>,
+    mut cx: AsyncAppContext,
+) -> Result<SemanticTokens> {
+    let version = deserialize_version(&message.version);
+    buffer
+        .update(&mut cx, |buffer, _| {
+            buffer.wait_for_version(version.clone())
+        })?
+        .await?;
+    Ok(SemanticTokens {
+        result_id: message.result_id,
+        data: message.data,
+        legend: Arc::new(lsp::SemanticTokensLegend {
+            token_types: message
+                .token_types
+                .into_iter()
+                .map(lsp::SemanticTokenType::from)
+                .collect(),
+            token_modifiers: message
+                .token_modifiers
+                .into_iter()
+                .map(lsp::SemanticTokenModifier::from)
+                .collect(),
+        }),
+        version,
+    })
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokensFull {
+    type Response = SemanticTokens;
+    type LspRequest = lsp::request::SemanticTokensFullRequest;
+    type ProtoRequest = proto::GetSemanticTokens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        semantic_tokens_options(&capabilities.server_capabilities).map_or(false, |options| {
+            !matches!(
+                options.full,
+                None | Some(lsp::SemanticTokensFullOptions::Bool(false))
+            )
+        })
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SemanticTokensParams {
+        lsp::SemanticTokensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensResult>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<SemanticTokens> {
+        let legend = semantic_tokens_legend(&project, &buffer, server_id, &mut cx)?;
+        let mut tokens = match message {
+            Some(lsp::SemanticTokensResult::Tokens(tokens)) => {
+                SemanticTokens::new(tokens.result_id, tokens.data, legend)
+            }
+            Some(lsp::SemanticTokensResult::Partial(partial)) => {
+                SemanticTokens::new(None, partial.data, legend)
+            }
+            None => SemanticTokens::new(None, Vec::new(), legend),
+        };
+        tokens.version = buffer.update(&mut cx, |buffer, _| buffer.version())?;
+        Ok(tokens)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokens {
+        proto::GetSemanticTokens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSemanticTokens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: SemanticTokens,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSemanticTokensResponse {
+        semantic_tokens_to_proto(response)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSemanticTokensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<SemanticTokens> {
+        semantic_tokens_from_proto(message, buffer, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetSemanticTokensDelta {
+    pub fn is_supported(capabilities: &ServerCapabilities) -> bool {
+        semantic_tokens_options(capabilities).map_or(false, |options| {
+            matches!(
+                options.full,
+                Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) })
+            )
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSemanticTokensDelta {
+    type Response = SemanticTokens;
+    type LspRequest = lsp::request::SemanticTokensFullDeltaRequest;
+    type ProtoRequest = proto::GetSemanticTokens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        self.previous.result_id.is_some() && Self::is_supported(&capabilities.server_capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::SemanticTokensDeltaParams {
+        lsp::SemanticTokensDeltaParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            previous_result_id: self.previous.result_id.clone().unwrap_or_default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::SemanticTokensFullDeltaResult>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<SemanticTokens> {
+        let mut tokens = match message {
+            Some(lsp::SemanticTokensFullDeltaResult::Tokens(tokens)) => {
+                let legend = semantic_tokens_legend(&project, &buffer, server_id, &mut cx)?;
+                SemanticTokens::new(tokens.result_id, tokens.data, legend)
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                let mut tokens = SemanticTokens::clone(&self.previous);
+                tokens.result_id = delta.result_id;
+                tokens.apply_edits(delta.edits);
+                tokens
+            }
+            Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                let mut tokens = SemanticTokens::clone(&self.previous);
+                tokens.result_id = None;
+                tokens.apply_edits(edits);
+                tokens
+            }
+            None => {
+                let legend = semantic_tokens_legend(&project, &buffer, server_id, &mut cx)?;
+                SemanticTokens::new(None, Vec::new(), legend)
+            }
+        };
+        tokens.version = buffer.update(&mut cx, |buffer, _| buffer.version())?;
+        Ok(tokens)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSemanticTokens {
+        GetSemanticTokensFull.to_proto(project_id, buffer)
+    }
+
+    async fn from_proto(
+        _: proto::GetSemanticTokens,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        Err(anyhow!(
+            "semantic token deltas are computed by the host and cannot be requested remotely"
+        ))
+    }
+
+    fn response_to_proto(
+        response: SemanticTokens,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetSemanticTokensResponse {
+        semantic_tokens_to_proto(response)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetSemanticTokensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<SemanticTokens> {
+        semantic_tokens_from_proto(message, buffer, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSemanticTokens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Option<Vec<FoldingRange>>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(has_support)) => *has_support,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Option<Vec<FoldingRange>>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut lsp_ranges = lsp_ranges.unwrap_or_default();
+            lsp_ranges.sort_unstable_by_key(|range| (range.start_line, Reverse(range.end_line)));
+            Some(
+                lsp_ranges
+                    .into_iter()
+                    .filter_map(|lsp_range| {
+                        // Folds always span whole lines, the first one staying visible.
+                        let start_row = lsp_range.start_line.min(max_row);
+                        let end_row = lsp_range.end_line.min(max_row);
+                        if start_row >= end_row {
+                            return None;
+                        }
+                        let start = Point::new(start_row, buffer.line_len(start_row));
+                        let end = Point::new(end_row, buffer.line_len(end_row));
+                        Some(FoldingRange {
+                            range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                            kind: lsp_range.kind,
+                        })
+                    })
+                    .collect(),
+            )
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        response: Option<Vec<FoldingRange>>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            supported: response.is_some(),
+            ranges: response
+                .unwrap_or_default()
+                .into_iter()
+                .map(|range| proto::FoldingRange {
+                    start: Some(serialize_anchor(&range.range.start)),
+                    end: Some(serialize_anchor(&range.range.end)),
+                    kind: range.kind.map(|kind| {
+                        match kind {
+                            lsp::FoldingRangeKind::Comment => proto::folding_range::Kind::Comment,
+                            lsp::FoldingRangeKind::Imports => proto::folding_range::Kind::Imports,
+                            lsp::FoldingRangeKind::Region => proto::folding_range::Kind::Region,
+                        }
+                        .into()
+                    }),
+                })
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Option<Vec<FoldingRange>>> {
+        if !message.supported {
+            return Ok(None);
+        }
+
+        let mut ranges = Vec::new();
+        for range in message.ranges {
+            let start = range
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range start"))?;
+            let end = range
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing folding range end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            let kind = range
+                .kind
+                .and_then(proto::folding_range::Kind::from_i32)
+                .map(|kind| match kind {
+                    proto::folding_range::Kind::Comment => lsp::FoldingRangeKind::Comment,
+                    proto::folding_range::Kind::Imports => lsp::FoldingRangeKind::Imports,
+                    proto::folding_range::Kind::Region => lsp::FoldingRangeKind::Region,
+                });
+            ranges.push(FoldingRange {
+                range: start..end,
+                kind,
+            });
+        }
+        Ok(Some(ranges))
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .code_lens_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_lenses: Option<Vec<lsp::CodeLens>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        // Servers are allowed to compute the commands of the lenses lazily, so the lenses are
+        // only resolved once they are displayed.
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_lenses
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| CodeLens {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_lens.range),
+                    lsp_lens,
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        lenses: Vec<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses.iter().map(Project::serialize_code_lens).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(Project::deserialize_code_lens)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        capabilities
+            .server_capabilities
+            .document_link_provider
+            .is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_links: Option<Vec<lsp::DocumentLink>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        let (_, language_server) =
+            language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        // Servers are allowed to compute the targets of links lazily, in which case they are
+        // resolved once the link is clicked.
+        let can_resolve = language_server
+            .capabilities()
+            .document_link_provider
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false);
+
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_links
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|lsp_link| lsp_link.target.is_some() || can_resolve)
+                .map(|lsp_link| DocumentLink {
+                    server_id,
+                    range: anchor_range_from_lsp(buffer, lsp_link.range),
+                    lsp_link,
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links.iter().map(Project::serialize_document_link).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(Project::deserialize_document_link)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentColors {
+    type Response = Vec<DocumentColor>;
+    type LspRequest = lsp::request::DocumentColor;
+    type ProtoRequest = proto::GetDocumentColors;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.color_provider {
+            Some(lsp::ColorProviderCapability::Simple(has_support)) => *has_support,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentColorParams {
+        lsp::DocumentColorParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        colors: Vec<lsp::ColorInformation>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer.update(&mut cx, |buffer, _| {
+            colors
+                .into_iter()
+                .map(|color| DocumentColor {
+                    range: anchor_range_from_lsp(buffer, color.range),
+                    color: color.color,
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentColors {
+        proto::GetDocumentColors {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentColors,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        colors: Vec<DocumentColor>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentColorsResponse {
+        proto::GetDocumentColorsResponse {
+            colors: colors
+                .iter()
+                .map(Project::serialize_document_color)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentColorsResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .colors
+            .into_iter()
+            .map(Project::deserialize_document_color)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentColors) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetColorPresentations {
+    type Response = Vec<ColorPresentation>;
+    type LspRequest = lsp::request::ColorPresentationRequest;
+    type ProtoRequest = proto::GetColorPresentations;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        GetDocumentColors.check_capabilities(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        buffer: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::ColorPresentationParams {
+        let range = self.color.range.start.to_point_utf16(buffer)
+            ..self.color.range.end.to_point_utf16(buffer);
+        lsp::ColorPresentationParams {
+            text_document: lsp::TextDocumentIdentifier {
+                uri: lsp::Url::from_file_path(path).unwrap(),
+            },
+            color: self.color.color,
+            range: range_to_lsp(range),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        presentations: Vec<lsp::ColorPresentation>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer.update(&mut cx, |buffer, _| {
+            presentations
+                .into_iter()
+                .map(|presentation| {
+                    // Without an explicit edit, the label replaces the color's text.
+                    let edit = match presentation.text_edit {
+                        Some(edit) => (anchor_range_from_lsp(buffer, edit.range), edit.new_text),
+                        None => (self.color.range.clone(), presentation.label.clone()),
+                    };
+                    let additional_edits = presentation
+                        .additional_text_edits
+                        .into_iter()
+                        .flatten()
+                        .map(|edit| (anchor_range_from_lsp(buffer, edit.range), edit.new_text));
+                    ColorPresentation {
+                        label: presentation.label,
+                        edits: Some(edit).into_iter().chain(additional_edits).collect(),
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetColorPresentations {
+        proto::GetColorPresentations {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            color: Some(Project::serialize_document_color(&self.color)),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetColorPresentations,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let color = message
+            .color
+            .ok_or_else(|| anyhow!("invalid color"))
+            .and_then(Project::deserialize_document_color)?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self { color })
+    }
+
+    fn response_to_proto(
+        presentations: Vec<ColorPresentation>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetColorPresentationsResponse {
+        proto::GetColorPresentationsResponse {
+            presentations: presentations
+                .iter()
+                .map(Project::serialize_color_presentation)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetColorPresentationsResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<ColorPresentation>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .presentations
+            .into_iter()
+            .map(Project::deserialize_color_presentation)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetColorPresentations) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: AdapterServerCapabilities) -> bool {
+        match &capabilities.server_capabilities.call_hierarchy_provider {
+            Some(lsp::CallHierarchyServerCapability::Simple(has_support)) => *has_support,
+            Some(lsp::CallHierarchyServerCapability::Options(_)) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::CallHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let mut result = Vec::new();
+        for lsp_item in items.unwrap_or_default() {
+            result.push(
+                hierarchy_item_from_lsp(lsp_item, &project, &buffer, server_id, &mut cx).await?,
+            );
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: response
+                .iter()
+                .map(|item| hierarchy_item_to_proto(item, project, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(hierarchy_item_from_proto(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding incoming calls...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        calls: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let mut result = Vec::new();
+        for call in calls.unwrap_or_default() {
+            let item =
+                hierarchy_item_from_lsp(call.from, &project, &buffer, server_id, &mut cx).await?;
+            // Incoming call sites are reported relative to the caller.
+            let call_sites =
+                locations_from_lsp_ranges(&item.location.buffer, call.from_ranges, &mut cx)?;
+            result.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_hierarchy_item(&self.item, buffer.remote_id())),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_hierarchy_item(item, buffer, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetCallHierarchyCallsResponse {
+        call_hierarchy_calls_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCallHierarchyCallsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding outgoing calls...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        calls: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        let mut result = Vec::new();
+        for call in calls.unwrap_or_default() {
+            let item =
+                hierarchy_item_from_lsp(call.to, &project, &buffer, server_id, &mut cx).await?;
+            // Outgoing call sites are reported relative to the item the calls were requested for.
+            let call_sites =
+                locations_from_lsp_ranges(&self.item.location.buffer, call.from_ranges, &mut cx)?;
+            result.push(CallHierarchyCall { item, call_sites });
+        }
+        Ok(result)
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_hierarchy_item(&self.item, buffer.remote_id())),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_hierarchy_item(item, buffer, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<CallHierarchyCall>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetCallHierarchyCallsResponse {
+        call_hierarchy_calls_to_proto(response, project, peer_id, cx)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCallHierarchyCallsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        cx: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        call_hierarchy_calls_from_proto(message, project, cx).await
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, &project, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::PrepareTypeHierarchyResponse {
+        proto::PrepareTypeHierarchyResponse {
+            items: response
+                .iter()
+                .map(|item| hierarchy_item_to_proto(item, project, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareTypeHierarchyResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(hierarchy_item_from_proto(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding supertypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, &project, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_hierarchy_item(&self.item, buffer.remote_id())),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_hierarchy_item(item, buffer, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetTypeHierarchyItemsResponse {
+        proto::GetTypeHierarchyItemsResponse {
+            items: response
+                .iter()
+                .map(|item| hierarchy_item_to_proto(item, project, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetTypeHierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(hierarchy_item_from_proto(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn status(&self) -> Option<String> {
+        Some("Finding subtypes...".to_owned())
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        items: Option<Vec<lsp::TypeHierarchyItem>>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_lsp(items, &project, &buffer, server_id, &mut cx).await
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(serialize_hierarchy_item(&self.item, buffer.remote_id())),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: deserialize_hierarchy_item(item, buffer, &mut cx).await?,
+        })
+    }
+
+    fn response_to_proto(
+        response: Vec<TypeHierarchyItem>,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetTypeHierarchyItemsResponse {
+        proto::GetTypeHierarchyItemsResponse {
+            items: response
+                .iter()
+                .map(|item| hierarchy_item_to_proto(item, project, peer_id, cx))
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetTypeHierarchyItemsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        let mut items = Vec::new();
+        for item in message.items {
+            items.push(hierarchy_item_from_proto(item, &project, &mut cx).await?);
+        }
+        Ok(items)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+async fn type_hierarchy_items_from_lsp(
+    items: Option<Vec<lsp::TypeHierarchyItem>>,
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncAppContext,
+) -> Result<Vec<TypeHierarchyItem>> {
+    let mut result = Vec::new();
+    for lsp_item in items.unwrap_or_default() {
+        result.push(hierarchy_item_from_lsp(lsp_item, project, buffer, server_id, cx).await?);
+    }
+    Ok(result)
+}
+
+/// The parts of call and type hierarchy items that are needed to resolve them into buffers.
+trait LspHierarchyItem: Serialize + DeserializeOwned {
+    fn uri(&self) -> &lsp::Url;
+    fn range(&self) -> lsp::Range;
+    fn selection_range(&self) -> lsp::Range;
+}
+
+impl LspHierarchyItem for lsp::CallHierarchyItem {
+    fn uri(&self) -> &lsp::Url {
+        &self.uri
+    }
+
+    fn range(&self) -> lsp::Range {
+        self.range
+    }
+
+    fn selection_range(&self) -> lsp::Range {
+        self.selection_range
+    }
+}
+
+impl LspHierarchyItem for lsp::TypeHierarchyItem {
+    fn uri(&self) -> &lsp::Url {
+        &self.uri
+    }
+
+    fn range(&self) -> lsp::Range {
+        self.range
+    }
+
+    fn selection_range(&self) -> lsp::Range {
+        self.selection_range
+    }
+}
+
+async fn hierarchy_item_from_lsp<T: LspHierarchyItem>(
+    lsp_item: T,
+    project: &Model<Project>,
+    buffer: &Model<Buffer>,
+    server_id: LanguageServerId,
+    cx: &mut AsyncAppContext,
+) -> Result<HierarchyItem<T>> {
+    let (lsp_adapter, language_server) =
+        language_server_for_buffer(project, buffer, server_id, cx)?;
+    let target_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer_via_lsp(
+                lsp_item.uri().clone(),
+                language_server.server_id(),
+                lsp_adapter.name.clone(),
+                cx,
+            )
+        })?
+        .await?;
+    target_buffer.clone().update(cx, |target_buffer, _| {
+        let range = anchor_range_from_lsp(target_buffer, lsp_item.range());
+        let selection_range = anchor_range_from_lsp(target_buffer, lsp_item.selection_range());
+        HierarchyItem {
+            server_id,
+            location: Location {
+                buffer: target_buffer,
+                range,
+            },
+            selection_range,
+            lsp_item,
+        }
+    })
+}
+
+fn anchor_range_from_lsp(buffer: &Buffer, range: lsp::Range) -> Range<Anchor> {
+    let start = buffer.clip_point_utf16(point_from_lsp(range.start), Bias::Left);
+    let end = buffer.clip_point_utf16(point_from_lsp(range.end), Bias::Left);
+    buffer.anchor_after(start)..buffer.anchor_before(end)
+}
+
+fn locations_from_lsp_ranges(
+    buffer: &Model<Buffer>,
+    ranges: Vec<lsp::Range>,
+    cx: &mut AsyncAppContext,
+) -> Result<Vec<Location>> {
+    buffer.update(cx, |snapshot, _| {
+        ranges
+            .into_iter()
+            .map(|range| Location {
+                buffer: buffer.clone(),
+                range: anchor_range_from_lsp(snapshot, range),
+            })
+            .collect()
+    })
+}
+
+fn serialize_hierarchy_item<T: LspHierarchyItem>(
+    item: &HierarchyItem<T>,
+    buffer_id: BufferId,
+) -> proto::HierarchyItem {
+    proto::HierarchyItem {
+        server_id: item.server_id.0 as u64,
+        location: Some(proto::Location {
+            buffer_id: buffer_id.into(),
+            start: Some(serialize_anchor(&item.location.range.start)),
+            end: Some(serialize_anchor(&item.location.range.end)),
+        }),
+        selection_start: Some(serialize_anchor(&item.selection_range.start)),
+        selection_end: Some(serialize_anchor(&item.selection_range.end)),
+        lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+    }
+}
+
+async fn deserialize_hierarchy_item<T: LspHierarchyItem>(
+    item: proto::HierarchyItem,
+    buffer: Model<Buffer>,
+    cx: &mut AsyncAppContext,
+) -> Result<HierarchyItem<T>> {
+    let location = item.location.ok_or_else(|| anyhow!("missing location"))?;
+    let start = location
+        .start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing start"))?;
+    let end = location
+        .end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing end"))?;
+    let selection_start = item
+        .selection_start
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing selection start"))?;
+    let selection_end = item
+        .selection_end
+        .and_then(deserialize_anchor)
+        .ok_or_else(|| anyhow!("missing selection end"))?;
+    buffer
+        .update(cx, |buffer, _| {
+            buffer.wait_for_anchors([start, end, selection_start, selection_end])
+        })?
+        .await?;
+    Ok(HierarchyItem {
+        server_id: LanguageServerId(item.server_id as usize),
+        location: Location {
+            buffer,
+            range: start..end,
+        },
+        selection_range: selection_start..selection_end,
+        lsp_item: serde_json::from_slice(&item.lsp_item)?,
+    })
+}
+
+fn hierarchy_item_to_proto<T: LspHierarchyItem>(
+    item: &HierarchyItem<T>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::HierarchyItem {
+    let buffer_id = project.create_buffer_for_peer(&item.location.buffer, peer_id, cx);
+    serialize_hierarchy_item(item, buffer_id)
+}
+
+async fn hierarchy_item_from_proto<T: LspHierarchyItem>(
+    item: proto::HierarchyItem,
+    project: &Model<Project>,
+    cx: &mut AsyncAppContext,
+) -> Result<HierarchyItem<T>> {
+    let buffer_id = item
+        .location
+        .as_ref()
+        .ok_or_else(|| anyhow!("missing location"))?
+        .buffer_id;
+    let buffer_id = BufferId::new(buffer_id)?;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.wait_for_remote_buffer(buffer_id, cx)
+        })?
+        .await?;
+    deserialize_hierarchy_item(item, buffer, cx).await
+}
+
+fn call_hierarchy_calls_to_proto(
+    calls: Vec<CallHierarchyCall>,
+    project: &mut Project,
+    peer_id: PeerId,
+    cx: &mut AppContext,
+) -> proto::GetCallHierarchyCallsResponse {
+    proto::GetCallHierarchyCallsResponse {
+        calls: calls
+            .into_iter()
+            .map(|call| proto::CallHierarchyCall {
+                item: Some(hierarchy_item_to_proto(&call.item, project, peer_id, cx)),
+                call_sites: call
+                    .call_sites
+                    .into_iter()
+                    .map(|location| {
+                        let buffer_id =
+                            project.create_buffer_for_peer(&location.buffer, peer_id, cx);
+                        proto::Location {
+                            start: Some(serialize_anchor(&location.range.start)),
+                            end: Some(serialize_anchor(&location.range.end)),
+                            buffer_id: buffer_id.into(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+async fn call_hierarchy_calls_from_proto(
+    message: proto::GetCallHierarchyCallsResponse,
+    project: Model<Project>,
+    mut cx: AsyncAppContext,
+) -> Result<Vec<CallHierarchyCall>> {
+    let mut calls = Vec::new();
+    for call in message.calls {
+        let item = call.item.ok_or_else(|| anyhow!("missing item"))?;
+        let item = hierarchy_item_from_proto(item, &project, &mut cx).await?;
+        let mut call_sites = Vec::new();
+        for location in call.call_sites {
+            let buffer_id = BufferId::new(location.buffer_id)?;
+            let buffer = project
+                .update(&mut cx, |project, cx| {
+                    project.wait_for_remote_buffer(buffer_id, cx)
+                })?
+                .await?;
+            let start = location
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing call site start"))?;
+            let end = location
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing call site end"))?;
+            buffer
+                .update(&mut cx, |buffer, _| buffer.wait_for_anchors([start, end]))?
+                .await?;
+            call_sites.push(Location {
+                buffer,
+                range: start..end,
+            });
+        }
+        calls.push(CallHierarchyCall { item, call_sites });
+    }
+    Ok(calls)
+}
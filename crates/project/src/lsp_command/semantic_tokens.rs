@@ -0,0 +1,199 @@
+use std::{ops::Range, sync::Arc};
+
+use clock::Global;
+use language::PointUtf16;
+
+/// Semantic tokens reported by a language server for a single buffer.
+///
+/// The tokens are kept in the relative encoding used by the LSP, so that
+/// `textDocument/semanticTokens/full/delta` responses can be applied on top of them.
+#[derive(Clone, Debug, Default)]
+pub struct SemanticTokens {
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+    pub legend: Arc<lsp::SemanticTokensLegend>,
+    /// The buffer version these tokens were computed for.
+    pub version: Global,
+}
+
+/// A single decoded semantic token.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemanticToken<'a> {
+    pub range: Range<PointUtf16>,
+    pub token_type: &'a str,
+    pub modifiers: Vec<&'a str>,
+}
+
+impl SemanticTokens {
+    pub(super) fn new(
+        result_id: Option<String>,
+        tokens: Vec<lsp::SemanticToken>,
+        legend: Arc<lsp::SemanticTokensLegend>,
+    ) -> Self {
+        Self {
+            result_id,
+            data: flatten_tokens(tokens),
+            legend,
+            version: Global::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.len() < 5
+    }
+
+    /// Applies the edits of a `textDocument/semanticTokens/full/delta` response.
+    ///
+    /// Edit offsets refer to the flat `u32` array of the previous response, so they are
+    /// applied back to front to keep the remaining offsets valid.
+    pub(super) fn apply_edits(&mut self, mut edits: Vec<lsp::SemanticTokensEdit>) {
+        edits.sort_by_key(|edit| edit.start);
+        for edit in edits.into_iter().rev() {
+            let start = (edit.start as usize).min(self.data.len());
+            let end = (start + edit.delete_count as usize).min(self.data.len());
+            self.data
+                .splice(start..end, flatten_tokens(edit.data.unwrap_or_default()));
+        }
+    }
+
+    /// Decodes the tokens into absolute ranges, skipping the ones the legend cannot describe.
+    pub fn tokens(&self) -> impl Iterator<Item = SemanticToken<'_>> {
+        let mut row = 0;
+        let mut column = 0;
+        self.data.chunks_exact(5).filter_map(move |token| {
+            let &[delta_line, delta_start, length, token_type, modifiers_bitset] = token else {
+                return None;
+            };
+            if delta_line > 0 {
+                row += delta_line;
+                column = delta_start;
+            } else {
+                column += delta_start;
+            }
+
+            let token_type = self.legend.token_types.get(token_type as usize)?;
+            let modifiers = self
+                .legend
+                .token_modifiers
+                .iter()
+                .enumerate()
+                .filter(|(ix, _)| *ix < 32 && modifiers_bitset & (1 << ix) != 0)
+                .map(|(_, modifier)| modifier.as_str())
+                .collect();
+            Some(SemanticToken {
+                range: PointUtf16::new(row, column)..PointUtf16::new(row, column + length),
+                token_type: token_type.as_str(),
+                modifiers,
+            })
+        })
+    }
+}
+
+fn flatten_tokens(tokens: Vec<lsp::SemanticToken>) -> Vec<u32> {
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legend() -> Arc<lsp::SemanticTokensLegend> {
+        Arc::new(lsp::SemanticTokensLegend {
+            token_types: vec![
+                lsp::SemanticTokenType::FUNCTION,
+                lsp::SemanticTokenType::VARIABLE,
+            ],
+            token_modifiers: vec![
+                lsp::SemanticTokenModifier::DECLARATION,
+                lsp::SemanticTokenModifier::READONLY,
+            ],
+        })
+    }
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        token_type: u32,
+    ) -> lsp::SemanticToken {
+        lsp::SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type,
+            token_modifiers_bitset: 0,
+        }
+    }
+
+    #[test]
+    fn test_decoding_semantic_tokens() {
+        let mut tokens = SemanticTokens::new(
+            None,
+            vec![token(1, 4, 3, 0), token(0, 5, 2, 1), token(2, 1, 1, 7)],
+            legend(),
+        );
+        tokens.data[4] = 0b11;
+
+        let decoded = tokens.tokens().collect::<Vec<_>>();
+        assert_eq!(
+            decoded,
+            vec![
+                SemanticToken {
+                    range: PointUtf16::new(1, 4)..PointUtf16::new(1, 7),
+                    token_type: "function",
+                    modifiers: vec!["declaration", "readonly"],
+                },
+                SemanticToken {
+                    range: PointUtf16::new(1, 9)..PointUtf16::new(1, 11),
+                    token_type: "variable",
+                    modifiers: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_applying_semantic_token_edits() {
+        let mut tokens = SemanticTokens::new(
+            Some("1".into()),
+            vec![token(0, 0, 3, 0), token(1, 0, 3, 0), token(1, 0, 3, 0)],
+            legend(),
+        );
+
+        tokens.apply_edits(vec![
+            lsp::SemanticTokensEdit {
+                start: 10,
+                delete_count: 5,
+                data: None,
+            },
+            lsp::SemanticTokensEdit {
+                start: 0,
+                delete_count: 0,
+                data: Some(vec![token(0, 0, 1, 1)]),
+            },
+        ]);
+
+        assert_eq!(
+            tokens
+                .tokens()
+                .map(|token| (token.range, token.token_type))
+                .collect::<Vec<_>>(),
+            vec![
+                (PointUtf16::new(0, 0)..PointUtf16::new(0, 1), "variable"),
+                (PointUtf16::new(0, 0)..PointUtf16::new(0, 3), "function"),
+                (PointUtf16::new(1, 0)..PointUtf16::new(1, 3), "function"),
+            ]
+        );
+    }
+}
@@ -415,6 +415,7 @@ async fn save_prettier_server_file(fs: &dyn Fs) -> anyhow::Result<()> {
         &prettier_wrapper_path,
         &text::Rope::from(prettier::PRETTIER_SERVER_JS),
         text::LineEnding::Unix,
+        Default::default(),
     )
     .await
     .with_context(|| {
@@ -1,6 +1,8 @@
 pub mod buffer_store;
 pub mod connection_manager;
+pub mod dap_store;
 pub mod debounced_delay;
+pub mod headless_lsp_project;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
@@ -16,6 +18,7 @@ pub mod search_history;
 mod yarn;
 
 use anyhow::{anyhow, bail, Context as _, Result};
+use askpass::AskPassDelegate;
 use async_trait::async_trait;
 use buffer_store::{BufferStore, BufferStoreEvent};
 use client::{
@@ -24,6 +27,8 @@ use client::{
 };
 use clock::ReplicaId;
 use collections::{btree_map, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use dap::adapters::{DapRegistry, DebugTaskDefinition};
+use dap_store::{DapStore, DebugSession};
 use debounced_delay::DebouncedDelay;
 use futures::{
     channel::mpsc::{self, UnboundedReceiver},
@@ -33,7 +38,14 @@ use futures::{
     AsyncWriteExt, Future, FutureExt, StreamExt,
 };
 use fuzzy::CharBag;
-use git::{blame::Blame, repository::GitRepository};
+use git::{
+    blame::Blame,
+    log::{CommitFileDiff, GitLog, LogQuery},
+    repository::GitRepository,
+    stash::StashEntry,
+    status::GitStagingStatus,
+    Oid,
+};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, BorrowAppContext, Context, Entity,
@@ -48,14 +60,14 @@ use language::{
     },
     markdown, point_to_lsp, prepare_completion_documentation,
     proto::{
-        deserialize_anchor, deserialize_version, serialize_anchor, serialize_line_ending,
-        serialize_version, split_operations,
+        deserialize_anchor, deserialize_version, serialize_anchor, serialize_encoding,
+        serialize_line_ending, serialize_version, split_operations,
     },
     range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
-    ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
+    ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation, Encoding,
     Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName, LocalFile,
-    LspAdapterDelegate, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot, ToOffset,
-    ToPointUtf16, Transaction, Unclipped,
+    LspAdapterDelegate, ParseStatus, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot,
+    ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use log::error;
 use lsp::{
@@ -76,7 +88,10 @@ use prettier_support::{DefaultPrettier, PrettierInstance};
 use project_settings::{DirenvSettings, LspSettings, ProjectSettings};
 use rand::prelude::*;
 use remote::SshSession;
-use rpc::{proto::AddWorktree, ErrorCode};
+use rpc::{
+    proto::{AddWorktree, AnyProtoClient},
+    ErrorCode,
+};
 use search::SearchQuery;
 use search_history::SearchHistory;
 use serde::Serialize;
@@ -110,7 +125,8 @@ use std::{
 };
 use task::{
     static_source::{StaticSource, TrackedFile},
-    HideStrategy, RevealStrategy, Shell, TaskContext, TaskTemplate, TaskVariables, VariableName,
+    HideStrategy, ResolvedTask, RevealStrategy, Shell, TaskContext, TaskTemplate, TaskVariables,
+    VariableName,
 };
 use terminals::Terminals;
 use text::{Anchor, BufferId, LineEnding};
@@ -120,6 +136,7 @@ use util::{
     NumericPrefixWithSuffix, ResultExt, TryFutureExt as _,
 };
 use worktree::{CreatedEntry, Snapshot, Traversal};
+pub use worktree_store::{GitOperation, GitRemoteOperation};
 use worktree_store::{WorktreeStore, WorktreeStoreEvent};
 use yarn::YarnPathStore;
 
@@ -127,6 +144,7 @@ pub use fs::*;
 pub use language::Location;
 #[cfg(any(test, feature = "test-support"))]
 pub use prettier::FORMAT_SUFFIX as TEST_PRETTIER_FORMAT_SUFFIX;
+pub use remote::{SshConnectionStatus, SshPortForward, SshPortForwardKind};
 pub use task_inventory::{
     BasicContextProvider, ContextProviderWithTasks, Inventory, TaskSourceKind,
 };
@@ -200,17 +218,27 @@ pub struct Project {
     user_store: Model<UserStore>,
     fs: Arc<dyn Fs>,
     ssh_session: Option<Arc<SshSession>>,
+    /// On the headless server of an SSH project, the session with the client that this
+    /// project's language servers are shared with.
+    downstream_ssh_session: Option<AnyProtoClient>,
     client_state: ProjectClientState,
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
     worktree_store: Model<WorktreeStore>,
     buffer_store: Model<BufferStore>,
+    dap_store: Model<DapStore>,
     _subscriptions: Vec<gpui::Subscription>,
     shared_buffers: HashMap<proto::PeerId, HashSet<BufferId>>,
     #[allow(clippy::type_complexity)]
     loading_worktrees:
         HashMap<Arc<Path>, Shared<Task<Result<Model<Worktree>, Arc<anyhow::Error>>>>>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, CachedSemanticTokens>,
+    document_links: HashMap<BufferId, CachedDocumentLinks>,
+    /// The result ids of the last diagnostics pulled from each language server, by document.
+    diagnostic_result_ids: HashMap<LanguageServerId, HashMap<lsp::Url, String>>,
+    pull_diagnostics_tasks: HashMap<BufferId, Task<()>>,
+    workspace_diagnostics_tasks: HashMap<LanguageServerId, Task<()>>,
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DebouncedDelay<Self>,
@@ -230,6 +258,12 @@ pub struct Project {
     snippets: Model<SnippetProvider>,
     yarn: Model<YarnPathStore>,
     cached_shell_environments: HashMap<WorktreeId, HashMap<String, String>>,
+    pending_git_remote_operations: BTreeMap<usize, GitRemoteOperation>,
+    next_git_remote_operation_id: usize,
+    /// The searches running on the headless server of an SSH project, which sends their
+    /// results back to the client.
+    ssh_searches: HashMap<u64, smol::channel::Sender<SearchResult>>,
+    next_ssh_search_id: u64,
 }
 
 pub enum LanguageServerToQuery {
@@ -237,6 +271,17 @@ pub enum LanguageServerToQuery {
     Other(LanguageServerId),
 }
 
+struct CachedSemanticTokens {
+    /// The server that produced the tokens, `None` for tokens received from the host.
+    server_id: Option<LanguageServerId>,
+    tokens: Arc<SemanticTokens>,
+}
+
+struct CachedDocumentLinks {
+    version: clock::Global,
+    links: Vec<DocumentLink>,
+}
+
 struct LspBufferSnapshot {
     version: i32,
     snapshot: TextBufferSnapshot,
@@ -346,8 +391,13 @@ pub enum Event {
     Reshared,
     Rejoined,
     RefreshInlayHints,
+    RefreshSemanticTokens,
     RevealInProjectPanel(ProjectEntryId),
     SnippetEdit(BufferId, Vec<(lsp::Range, Snippet)>),
+    WorkspaceEditApplied(ProjectTransaction),
+    /// A port that a server run on the remote host of an SSH project announced listening on
+    /// was forwarded.
+    SshPortForwarded(SshPortForward),
 }
 
 pub enum LanguageServerState {
@@ -467,6 +517,75 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer this code lens is displayed for.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server, always having a command.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+/// A link to a resource, found by a language server in the text of a buffer.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    /// The id of the language server that produced this link.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the link is displayed for.
+    pub range: Range<Anchor>,
+    /// The raw link provided by the language server, whose target may be missing until it is
+    /// resolved with [`Project::resolve_document_link`].
+    pub lsp_link: lsp::DocumentLink,
+}
+
+/// A color found by a language server in the text of a buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentColor {
+    /// The range of the buffer the color is written in.
+    pub range: Range<Anchor>,
+    pub color: lsp::Color,
+}
+
+/// One of the ways to write a color, as proposed by a language server.
+#[derive(Clone, Debug)]
+pub struct ColorPresentation {
+    /// The color written this way, e.g. `#ff0000` or `rgb(255, 0, 0)`.
+    pub label: String,
+    /// The edits writing the color this way in the buffer.
+    pub edits: Vec<(Range<Anchor>, String)>,
+}
+
+/// A symbol in a call or type hierarchy, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct HierarchyItem<T> {
+    /// The id of the language server that produced this item.
+    pub server_id: LanguageServerId,
+    /// The range enclosing the whole symbol, e.g. a function including its body.
+    pub location: Location,
+    /// The range to select when navigating to the symbol, e.g. the function name.
+    pub selection_range: Range<Anchor>,
+    /// The raw item provided by the language server, sent back when resolving
+    /// its neighbours in the hierarchy.
+    pub lsp_item: T,
+}
+
+/// A function or method in a call hierarchy.
+pub type CallHierarchyItem = HierarchyItem<lsp::CallHierarchyItem>;
+
+/// A type in a type hierarchy.
+pub type TypeHierarchyItem = HierarchyItem<lsp::TypeHierarchyItem>;
+
+/// A call to or from a [`CallHierarchyItem`].
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The ranges of the call expressions.
+    pub call_sites: Vec<Location>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -526,6 +645,15 @@ pub struct DocumentHighlight {
     pub kind: DocumentHighlightKind,
 }
 
+/// A foldable region of a buffer, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// Starts at the end of the first line of the region, so that the line stays visible
+    /// when folded.
+    pub range: Range<language::Anchor>,
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Symbol {
     pub language_server_name: LanguageServerName,
@@ -575,9 +703,20 @@ impl Hover {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
+impl PartialEq for ProjectTransaction {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len()
+            && self.0.iter().all(|(buffer, transaction)| {
+                other.0.get(buffer).map_or(false, |other_transaction| {
+                    other_transaction.id == transaction.id
+                })
+            })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
     Save,
@@ -603,6 +742,127 @@ impl FormatTrigger {
     }
 }
 
+/// An operation on the files of a local worktree that language servers may be interested in, as
+/// described by the `workspace.fileOperations` server capability.
+#[derive(Debug)]
+enum FileOperation {
+    Create {
+        path: PathBuf,
+        is_dir: bool,
+    },
+    Rename {
+        old_path: PathBuf,
+        new_path: PathBuf,
+        is_dir: bool,
+    },
+    Delete {
+        path: PathBuf,
+        is_dir: bool,
+    },
+}
+
+impl FileOperation {
+    fn create(worktree: &Worktree, path: &Path, is_dir: bool) -> Option<Self> {
+        Some(Self::Create {
+            path: worktree.absolutize(path).ok()?,
+            is_dir,
+        })
+    }
+
+    fn rename(worktree: &Worktree, entry_id: ProjectEntryId, new_path: &Path) -> Option<Self> {
+        let entry = worktree.entry_for_id(entry_id)?;
+        Some(Self::Rename {
+            old_path: worktree.absolutize(&entry.path).ok()?,
+            new_path: worktree.absolutize(new_path).ok()?,
+            is_dir: entry.is_dir(),
+        })
+    }
+
+    fn delete(worktree: &Worktree, entry_id: ProjectEntryId) -> Option<Self> {
+        let entry = worktree.entry_for_id(entry_id)?;
+        Some(Self::Delete {
+            path: worktree.absolutize(&entry.path).ok()?,
+            is_dir: entry.is_dir(),
+        })
+    }
+
+    /// Whether the operation matches one of the filters a server registered for it. Renames are
+    /// matched against the path the entry is renamed from.
+    fn matches(&self, options: Option<&lsp::FileOperationRegistrationOptions>) -> bool {
+        let Some(options) = options else {
+            return false;
+        };
+        let (path, is_dir) = match self {
+            Self::Create { path, is_dir } | Self::Delete { path, is_dir } => (path, *is_dir),
+            Self::Rename {
+                old_path, is_dir, ..
+            } => (old_path, *is_dir),
+        };
+        options.filters.iter().any(|filter| {
+            if filter
+                .scheme
+                .as_deref()
+                .map_or(false, |scheme| scheme != "file")
+            {
+                return false;
+            }
+            match &filter.pattern.matches {
+                Some(lsp::FileOperationPatternKind::File) if is_dir => return false,
+                Some(lsp::FileOperationPatternKind::Folder) if !is_dir => return false,
+                _ => {}
+            }
+            let ignore_case = filter
+                .pattern
+                .options
+                .as_ref()
+                .and_then(|options| options.ignore_case)
+                .unwrap_or(false);
+            globset::GlobBuilder::new(&filter.pattern.glob)
+                .case_insensitive(ignore_case)
+                .literal_separator(true)
+                .build()
+                .map_or(false, |glob| glob.compile_matcher().is_match(path))
+        })
+    }
+
+    fn create_params(&self) -> Option<lsp::CreateFilesParams> {
+        let Self::Create { path, .. } = self else {
+            return None;
+        };
+        Some(lsp::CreateFilesParams {
+            files: vec![lsp::FileCreate {
+                uri: lsp::Url::from_file_path(path).ok()?.to_string(),
+            }],
+        })
+    }
+
+    fn rename_params(&self) -> Option<lsp::RenameFilesParams> {
+        let Self::Rename {
+            old_path, new_path, ..
+        } = self
+        else {
+            return None;
+        };
+        Some(lsp::RenameFilesParams {
+            files: vec![lsp::FileRename {
+                old_uri: lsp::Url::from_file_path(old_path).ok()?.to_string(),
+                new_uri: lsp::Url::from_file_path(new_path).ok()?.to_string(),
+            }],
+        })
+    }
+
+    fn delete_params(&self) -> Option<lsp::DeleteFilesParams> {
+        let Self::Delete { path, .. } = self else {
+            return None;
+        };
+        Some(lsp::DeleteFilesParams {
+            files: vec![lsp::FileDelete {
+                uri: lsp::Url::from_file_path(path).ok()?.to_string(),
+            }],
+        })
+    }
+}
+
 #[derive(Clone)]
 pub enum DirectoryLister {
     Project(Model<Project>),
@@ -731,18 +991,49 @@ impl Project {
         client.add_model_request_handler(Self::handle_task_context_for_location);
         client.add_model_request_handler(Self::handle_task_templates);
         client.add_model_request_handler(Self::handle_lsp_command::<LinkedEditingRange>);
-
-        client.add_model_request_handler(WorktreeStore::handle_create_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_rename_project_entry);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentColors>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetColorPresentations>);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_execute_code_lens);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
+        client.add_model_request_handler(Self::handle_refresh_semantic_tokens);
+
+        client.add_model_request_handler(Self::handle_create_project_entry);
+        client.add_model_request_handler(Self::handle_rename_project_entry);
         client.add_model_request_handler(WorktreeStore::handle_copy_project_entry);
-        client.add_model_request_handler(WorktreeStore::handle_delete_project_entry);
+        client.add_model_request_handler(Self::handle_delete_project_entry);
         client.add_model_request_handler(WorktreeStore::handle_expand_project_entry);
+        client.add_model_request_handler(WorktreeStore::handle_get_git_staging_status);
+        client.add_model_request_handler(WorktreeStore::handle_stage_git_paths);
+        client.add_model_request_handler(WorktreeStore::handle_unstage_git_paths);
+        client.add_model_request_handler(WorktreeStore::handle_set_git_index_text);
+        client.add_model_request_handler(WorktreeStore::handle_unstage_git_rows);
+        client.add_model_request_handler(WorktreeStore::handle_commit_git_changes);
+        client.add_model_request_handler(WorktreeStore::handle_stash_git_changes);
+        client.add_model_request_handler(WorktreeStore::handle_apply_git_stash);
+        client.add_model_request_handler(WorktreeStore::handle_drop_git_stash);
+        client.add_model_request_handler(WorktreeStore::handle_get_git_stashes);
+        client.add_model_request_handler(WorktreeStore::handle_run_git_remote_operation);
+        client.add_model_request_handler(WorktreeStore::handle_get_git_log);
+        client.add_model_request_handler(WorktreeStore::handle_get_git_commit_diff);
+        client.add_model_request_handler(WorktreeStore::handle_get_git_revision_diff);
 
         client.add_model_message_handler(BufferStore::handle_buffer_reloaded);
         client.add_model_message_handler(BufferStore::handle_buffer_saved);
         client.add_model_message_handler(BufferStore::handle_update_buffer_file);
         client.add_model_message_handler(BufferStore::handle_update_diff_base);
         client.add_model_request_handler(BufferStore::handle_save_buffer);
+        client.add_model_request_handler(BufferStore::handle_reopen_buffer);
         client.add_model_request_handler(BufferStore::handle_blame_buffer);
     }
 
@@ -755,84 +1046,122 @@ impl Project {
         cx: &mut AppContext,
     ) -> Model<Self> {
         cx.new_model(|cx: &mut ModelContext<Self>| {
-            let (tx, rx) = mpsc::unbounded();
-            cx.spawn(move |this, cx| Self::send_buffer_ordered_messages(this, rx, cx))
-                .detach();
-            let tasks = Inventory::new(cx);
-            let global_snippets_dir = paths::config_dir().join("snippets");
-            let snippets =
-                SnippetProvider::new(fs.clone(), BTreeSet::from_iter([global_snippets_dir]), cx);
-
             let worktree_store = cx.new_model(|_| WorktreeStore::new(false));
-            cx.subscribe(&worktree_store, Self::on_worktree_store_event)
-                .detach();
-
             let buffer_store =
                 cx.new_model(|cx| BufferStore::new(worktree_store.clone(), None, cx));
-            cx.subscribe(&buffer_store, Self::on_buffer_store_event)
-                .detach();
-
-            let yarn = YarnPathStore::new(fs.clone(), cx);
-
-            Self {
-                buffer_ordered_messages_tx: tx,
-                collaborators: Default::default(),
+            Self::new_local(
                 worktree_store,
                 buffer_store,
-                shared_buffers: Default::default(),
-                loading_worktrees: Default::default(),
-                buffer_snapshots: Default::default(),
-                join_project_response_message_id: 0,
-                client_state: ProjectClientState::Local,
-                client_subscriptions: Vec::new(),
-                _subscriptions: vec![
-                    cx.observe_global::<SettingsStore>(Self::on_settings_changed),
-                    cx.on_release(Self::release),
-                    cx.on_app_quit(Self::shutdown_language_servers),
-                ],
-                _maintain_buffer_languages: Self::maintain_buffer_languages(languages.clone(), cx),
-                _maintain_workspace_config: Self::maintain_workspace_config(cx),
-                active_entry: None,
-                yarn,
-                snippets,
-                languages,
                 client,
+                node,
                 user_store,
+                languages,
                 fs,
-                ssh_session: None,
-                next_entry_id: Default::default(),
-                next_diagnostic_group_id: Default::default(),
-                diagnostics: Default::default(),
-                diagnostic_summaries: Default::default(),
-                supplementary_language_servers: HashMap::default(),
-                language_servers: Default::default(),
-                language_server_ids: HashMap::default(),
-                language_server_statuses: Default::default(),
-                last_formatting_failure: None,
-                last_workspace_edits_by_language_server: Default::default(),
-                language_server_watched_paths: HashMap::default(),
-                language_server_watcher_registrations: HashMap::default(),
-                buffers_being_formatted: Default::default(),
-                buffers_needing_diff: Default::default(),
-                git_diff_debouncer: DebouncedDelay::new(),
-                nonce: StdRng::from_entropy().gen(),
-                terminals: Terminals {
-                    local_handles: Vec::new(),
-                },
-                current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
-                node: Some(node),
-                default_prettier: DefaultPrettier::default(),
-                prettiers_per_worktree: HashMap::default(),
-                prettier_instances: HashMap::default(),
-                tasks,
-                hosted_project_id: None,
-                dev_server_project_id: None,
-                search_history: Self::new_search_history(),
-                cached_shell_environments: HashMap::default(),
-            }
+                cx,
+            )
         })
     }
 
+    /// Creates a local project whose worktrees and buffers are kept in the given stores, which
+    /// may be shared with another model.
+    #[allow(clippy::too_many_arguments)]
+    fn new_local(
+        worktree_store: Model<WorktreeStore>,
+        buffer_store: Model<BufferStore>,
+        client: Arc<Client>,
+        node: Arc<dyn NodeRuntime>,
+        user_store: Model<UserStore>,
+        languages: Arc<LanguageRegistry>,
+        fs: Arc<dyn Fs>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
+        let (tx, rx) = mpsc::unbounded();
+        cx.spawn(move |this, cx| Self::send_buffer_ordered_messages(this, rx, cx))
+            .detach();
+        let tasks = Inventory::new(cx);
+        let global_snippets_dir = paths::config_dir().join("snippets");
+        let snippets =
+            SnippetProvider::new(fs.clone(), BTreeSet::from_iter([global_snippets_dir]), cx);
+
+        cx.subscribe(&worktree_store, Self::on_worktree_store_event)
+            .detach();
+        cx.subscribe(&buffer_store, Self::on_buffer_store_event)
+            .detach();
+
+        let dap_store = cx.new_model(|_| DapStore::default());
+
+        let yarn = YarnPathStore::new(fs.clone(), cx);
+
+        Self {
+            buffer_ordered_messages_tx: tx,
+            collaborators: Default::default(),
+            worktree_store,
+            buffer_store,
+            dap_store,
+            shared_buffers: Default::default(),
+            loading_worktrees: Default::default(),
+            buffer_snapshots: Default::default(),
+            semantic_tokens: Default::default(),
+            document_links: Default::default(),
+            diagnostic_result_ids: Default::default(),
+            pull_diagnostics_tasks: Default::default(),
+            workspace_diagnostics_tasks: Default::default(),
+            join_project_response_message_id: 0,
+            client_state: ProjectClientState::Local,
+            client_subscriptions: Vec::new(),
+            _subscriptions: vec![
+                cx.observe_global::<SettingsStore>(Self::on_settings_changed),
+                cx.on_release(Self::release),
+                cx.on_app_quit(Self::shutdown_language_servers),
+                cx.on_app_quit(Self::shutdown_debug_sessions),
+            ],
+            _maintain_buffer_languages: Self::maintain_buffer_languages(languages.clone(), cx),
+            _maintain_workspace_config: Self::maintain_workspace_config(cx),
+            active_entry: None,
+            yarn,
+            snippets,
+            languages,
+            client,
+            user_store,
+            fs,
+            ssh_session: None,
+            downstream_ssh_session: None,
+            pending_git_remote_operations: Default::default(),
+            next_git_remote_operation_id: 0,
+            ssh_searches: Default::default(),
+            next_ssh_search_id: 0,
+            next_entry_id: Default::default(),
+            next_diagnostic_group_id: Default::default(),
+            diagnostics: Default::default(),
+            diagnostic_summaries: Default::default(),
+            supplementary_language_servers: HashMap::default(),
+            language_servers: Default::default(),
+            language_server_ids: HashMap::default(),
+            language_server_statuses: Default::default(),
+            last_formatting_failure: None,
+            last_workspace_edits_by_language_server: Default::default(),
+            language_server_watched_paths: HashMap::default(),
+            language_server_watcher_registrations: HashMap::default(),
+            buffers_being_formatted: Default::default(),
+            buffers_needing_diff: Default::default(),
+            git_diff_debouncer: DebouncedDelay::new(),
+            nonce: StdRng::from_entropy().gen(),
+            terminals: Terminals {
+                local_handles: Vec::new(),
+            },
+            current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+            node: Some(node),
+            default_prettier: DefaultPrettier::default(),
+            prettiers_per_worktree: HashMap::default(),
+            prettier_instances: HashMap::default(),
+            tasks,
+            hosted_project_id: None,
+            dev_server_project_id: None,
+            search_history: Self::new_search_history(),
+            cached_shell_environments: HashMap::default(),
+        }
+    }
+
     pub fn ssh(
         ssh: Arc<SshSession>,
         client: Arc<Client>,
@@ -845,17 +1174,127 @@ impl Project {
         let this = Self::local(client, node, user_store, languages, fs, cx);
         this.update(cx, |this, cx| {
             let buffer_store = this.buffer_store.downgrade();
+            let worktree_store = this.worktree_store.downgrade();
 
             ssh.add_message_handler(cx.weak_model(), Self::handle_update_worktree);
             ssh.add_message_handler(cx.weak_model(), Self::handle_create_buffer_for_peer);
             ssh.add_message_handler(buffer_store.clone(), BufferStore::handle_update_buffer_file);
             ssh.add_message_handler(buffer_store.clone(), BufferStore::handle_update_diff_base);
-
+            ssh.add_request_handler(buffer_store.clone(), BufferStore::handle_update_buffer);
+            ssh.add_request_handler(worktree_store, WorktreeStore::handle_ask_pass);
+            ssh.add_message_handler(cx.weak_model(), Self::handle_start_language_server);
+            ssh.add_message_handler(cx.weak_model(), Self::handle_update_language_server);
+            ssh.add_message_handler(cx.weak_model(), Self::handle_update_diagnostic_summary);
+            ssh.add_request_handler(cx.weak_model(), Self::handle_search_project_results);
+            ssh.add_request_handler(cx.weak_model(), Self::handle_refresh_inlay_hints);
+            ssh.add_request_handler(cx.weak_model(), Self::handle_refresh_semantic_tokens);
+
+            let status = ssh.status();
             this.ssh_session = Some(ssh);
+            cx.spawn(|this, cx| Self::maintain_ssh_connection(this, status, cx))
+                .detach_and_log_err(cx);
         });
         this
     }
 
+    /// Follows the status of the SSH connection. After a reconnection the server carries on
+    /// with the same session, whose unacknowledged messages are sent again, so the buffers stay
+    /// in sync. If the server had to be restarted, it is given the project's worktrees and
+    /// buffers again.
+    async fn maintain_ssh_connection(
+        this: WeakModel<Self>,
+        mut statuses: watch::Receiver<SshConnectionStatus>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let mut server_restarts = 0;
+        while let Some(status) = statuses.next().await {
+            let restored = this.update(&mut cx, |this, cx| {
+                cx.notify();
+                let ssh = this.ssh_session.clone()?;
+                if status != SshConnectionStatus::Connected
+                    || ssh.server_restarts() == server_restarts
+                {
+                    return None;
+                }
+                server_restarts = ssh.server_restarts();
+                Some(this.restore_ssh_project(ssh, cx))
+            })?;
+            if let Some(restored) = restored {
+                restored.await.log_err();
+            }
+        }
+        Ok(())
+    }
+
+    /// Recreates the worktrees and buffers of the project on a restarted server. The worktrees
+    /// and buffers keep their ids, and the buffers keep their unsaved changes.
+    fn restore_ssh_project(
+        &mut self,
+        ssh: Arc<SshSession>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let worktrees = self
+            .worktrees(cx)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|worktree| {
+                worktree.update(cx, |worktree, _| {
+                    // The restarted server sends the entries of the worktree from scratch.
+                    if let Some(worktree) = worktree.as_remote_mut() {
+                        worktree.reset_snapshot();
+                    }
+                    (
+                        worktree.id(),
+                        worktree.abs_path().to_string_lossy().to_string(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(|this, mut cx| async move {
+            for (worktree_id, path) in worktrees {
+                let response = ssh
+                    .request(AddWorktree {
+                        path,
+                        worktree_id: Some(worktree_id.to_proto()),
+                    })
+                    .await?;
+                if response.worktree_id != worktree_id.to_proto() {
+                    return Err(anyhow!(
+                        "worktree {worktree_id} was restored with another id"
+                    ));
+                }
+            }
+
+            // The server reconciles the buffers with their files, which may have changed while
+            // it wasn't running, and loads their diff bases itself.
+            let requests = this.update(&mut cx, |this, cx| {
+                this.buffer_store
+                    .read(cx)
+                    .buffers()
+                    .map(|buffer| {
+                        let buffer = buffer.read(cx);
+                        let mut state = buffer.to_proto(cx);
+                        state.diff_base = None;
+                        (state, buffer.serialize_ops(None, cx))
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+
+            for (state, operations) in requests {
+                let operations = operations.await;
+                ssh.request(proto::RestoreBuffer {
+                    project_id: 0,
+                    state: Some(state),
+                    operations,
+                })
+                .await
+                .log_err();
+            }
+            Ok(())
+        })
+    }
+
     pub async fn remote(
         remote_id: u64,
         client: Arc<Client>,
@@ -950,6 +1389,7 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 buffer_store: buffer_store.clone(),
                 worktree_store,
+                dap_store: cx.new_model(|_| DapStore::default()),
                 shared_buffers: Default::default(),
                 loading_worktrees: Default::default(),
                 active_entry: None,
@@ -963,6 +1403,11 @@ impl Project {
                 yarn,
                 fs,
                 ssh_session: None,
+                downstream_ssh_session: None,
+                pending_git_remote_operations: Default::default(),
+                next_git_remote_operation_id: 0,
+                ssh_searches: Default::default(),
+                next_ssh_search_id: 0,
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
                 diagnostic_summaries: Default::default(),
@@ -1007,6 +1452,11 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                document_links: Default::default(),
+                diagnostic_result_ids: Default::default(),
+                pull_diagnostics_tasks: Default::default(),
+                workspace_diagnostics_tasks: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
@@ -1130,6 +1580,11 @@ impl Project {
         }
     }
 
+    fn shutdown_debug_sessions(&mut self, cx: &mut ModelContext<Self>) -> impl Future<Output = ()> {
+        self.dap_store
+            .update(cx, |dap_store, cx| dap_store.shutdown_sessions(cx))
+    }
+
     #[cfg(any(test, feature = "test-support"))]
     pub async fn example(
         root_paths: impl IntoIterator<Item = &Path>,
@@ -1415,6 +1870,52 @@ impl Project {
         cx.notify();
     }
 
+    pub fn dap_store(&self) -> &Model<DapStore> {
+        &self.dap_store
+    }
+
+    /// Starts debugging a task which has a debug configuration, with the debug adapter it names.
+    pub fn start_debug_session(
+        &mut self,
+        task: &ResolvedTask,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Model<DebugSession>>> {
+        if !self.is_local() || self.is_ssh() {
+            return Task::ready(Err(anyhow!(
+                "debugging is only supported in local projects"
+            )));
+        }
+        let Some(definition) = DebugTaskDefinition::from_resolved_task(task) else {
+            return Task::ready(Err(anyhow!(
+                "task {:?} has no debug configuration",
+                task.display_label()
+            )));
+        };
+        let Some(adapter) = DapRegistry::default_global(cx).adapter(&definition.config.adapter)
+        else {
+            return Task::ready(Err(anyhow!(
+                "unknown debug adapter {:?}",
+                definition.config.adapter
+            )));
+        };
+        let worktree = definition
+            .cwd
+            .as_deref()
+            .and_then(|cwd| Some(self.find_worktree(cwd, cx)?.0))
+            .or_else(|| self.visible_worktrees(cx).next());
+        let Some(worktree) = worktree else {
+            return Task::ready(Err(anyhow!("no worktree to debug the task in")));
+        };
+        let working_dir = definition
+            .cwd
+            .clone()
+            .unwrap_or_else(|| worktree.read(cx).abs_path().to_path_buf());
+        let delegate = ProjectLspAdapterDelegate::new(self, &worktree, cx);
+        self.dap_store.update(cx, |dap_store, cx| {
+            dap_store.start_session(definition, adapter, delegate, working_dir, cx)
+        })
+    }
+
     pub fn task_inventory(&self) -> &Model<Inventory> {
         &self.tasks
     }
@@ -1533,9 +2034,18 @@ impl Project {
                 "No worktree for path {project_path:?}"
             ))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.create_entry(project_path.path, is_directory, cx)
-        })
+        let operation = FileOperation::create(worktree.read(cx), &project_path.path, is_directory);
+        self.perform_file_operation(
+            &worktree,
+            operation,
+            true,
+            move |_, cx| {
+                worktree.update(cx, |worktree, cx| {
+                    worktree.create_entry(project_path.path, is_directory, cx)
+                })
+            },
+            cx,
+        )
     }
 
     pub fn copy_entry(
@@ -1561,9 +2071,19 @@ impl Project {
         let Some(worktree) = self.worktree_for_entry(entry_id, cx) else {
             return Task::ready(Err(anyhow!(format!("No worktree for entry {entry_id:?}"))));
         };
-        worktree.update(cx, |worktree, cx| {
-            worktree.rename_entry(entry_id, new_path, cx)
-        })
+        let new_path = new_path.into();
+        let operation = FileOperation::rename(worktree.read(cx), entry_id, &new_path);
+        self.perform_file_operation(
+            &worktree,
+            operation,
+            true,
+            move |_, cx| {
+                worktree.update(cx, |worktree, cx| {
+                    worktree.rename_entry(entry_id, new_path, cx)
+                })
+            },
+            cx,
+        )
     }
 
     pub fn delete_entry(
@@ -1573,15 +2093,172 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_entry(entry_id, cx)?;
-        worktree.update(cx, |worktree, cx| {
-            worktree.delete_entry(entry_id, trash, cx)
-        })
-    }
-
-    pub fn expand_entry(
-        &mut self,
-        worktree_id: WorktreeId,
-        entry_id: ProjectEntryId,
+        let Some(operation) = FileOperation::delete(worktree.read(cx), entry_id) else {
+            return worktree.update(cx, |worktree, cx| {
+                worktree.delete_entry(entry_id, trash, cx)
+            });
+        };
+        Some(self.perform_file_operation(
+            &worktree,
+            Some(operation),
+            true,
+            move |_, cx| {
+                worktree
+                    .update(cx, |worktree, cx| {
+                        worktree.delete_entry(entry_id, trash, cx)
+                    })
+                    .unwrap_or_else(|| Task::ready(Err(anyhow!("no such entry {entry_id:?}"))))
+            },
+            cx,
+        ))
+    }
+
+    /// Performs an operation on the files of a worktree, giving the worktree's language servers a
+    /// chance to update the project beforehand (e.g. to fix imports of a renamed file), and
+    /// notifying them once the operation succeeded.
+    ///
+    /// The edits of the language servers are only announced for operations started locally, as
+    /// the ones of collaborators are theirs to review.
+    fn perform_file_operation<T: 'static>(
+        &mut self,
+        worktree: &Model<Worktree>,
+        operation: Option<FileOperation>,
+        started_locally: bool,
+        perform: impl FnOnce(&mut Self, &mut ModelContext<Self>) -> Task<Result<T>> + 'static,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<T>> {
+        let worktree_id = worktree.read(cx).id();
+        let Some(operation) = operation.filter(|_| worktree.read(cx).is_local()) else {
+            return perform(self, cx);
+        };
+        let will_perform = self.will_perform_file_operation(worktree_id, &operation, cx);
+        cx.spawn(move |this, mut cx| async move {
+            let transaction = will_perform.await;
+            if started_locally && !transaction.0.is_empty() {
+                this.update(&mut cx, |_, cx| {
+                    cx.emit(Event::WorkspaceEditApplied(transaction))
+                })?;
+            }
+            let result = this.update(&mut cx, perform)?.await?;
+            this.update(&mut cx, |this, _| {
+                this.did_perform_file_operation(worktree_id, &operation)
+            })?;
+            Ok(result)
+        })
+    }
+
+    fn will_perform_file_operation(
+        &self,
+        worktree_id: WorktreeId,
+        operation: &FileOperation,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<ProjectTransaction> {
+        let requests = self
+            .language_servers_for_worktree(worktree_id)
+            .filter_map(|(adapter, _, server)| {
+                let file_operations = server.capabilities().workspace?.file_operations?;
+                let request = match operation {
+                    FileOperation::Create { .. } => {
+                        if !operation.matches(file_operations.will_create.as_ref()) {
+                            return None;
+                        }
+                        server
+                            .request::<lsp::request::WillCreateFiles>(operation.create_params()?)
+                            .boxed_local()
+                    }
+                    FileOperation::Rename { .. } => {
+                        if !operation.matches(file_operations.will_rename.as_ref()) {
+                            return None;
+                        }
+                        server
+                            .request::<lsp::request::WillRenameFiles>(operation.rename_params()?)
+                            .boxed_local()
+                    }
+                    FileOperation::Delete { .. } => {
+                        if !operation.matches(file_operations.will_delete.as_ref()) {
+                            return None;
+                        }
+                        server
+                            .request::<lsp::request::WillDeleteFiles>(operation.delete_params()?)
+                            .boxed_local()
+                    }
+                };
+                Some((adapter.clone(), server.clone(), request))
+            })
+            .collect::<Vec<_>>();
+
+        cx.spawn(move |this, mut cx| async move {
+            let mut project_transaction = ProjectTransaction::default();
+            let Some(this) = this.upgrade() else {
+                return project_transaction;
+            };
+            for (adapter, server, request) in requests {
+                let Some(Some(edit)) = request.await.log_err() else {
+                    continue;
+                };
+                if let Some(transaction) = Self::deserialize_workspace_edit(
+                    this.clone(),
+                    edit,
+                    true,
+                    adapter,
+                    server,
+                    &mut cx,
+                )
+                .await
+                .log_err()
+                {
+                    project_transaction.0.extend(transaction.0);
+                }
+            }
+            project_transaction
+        })
+    }
+
+    fn did_perform_file_operation(&self, worktree_id: WorktreeId, operation: &FileOperation) {
+        for (_, _, server) in self.language_servers_for_worktree(worktree_id) {
+            let Some(file_operations) = server
+                .capabilities()
+                .workspace
+                .and_then(|workspace| workspace.file_operations)
+            else {
+                continue;
+            };
+            match operation {
+                FileOperation::Create { .. } => {
+                    if operation.matches(file_operations.did_create.as_ref()) {
+                        if let Some(params) = operation.create_params() {
+                            server
+                                .notify::<lsp::notification::DidCreateFiles>(params)
+                                .log_err();
+                        }
+                    }
+                }
+                FileOperation::Rename { .. } => {
+                    if operation.matches(file_operations.did_rename.as_ref()) {
+                        if let Some(params) = operation.rename_params() {
+                            server
+                                .notify::<lsp::notification::DidRenameFiles>(params)
+                                .log_err();
+                        }
+                    }
+                }
+                FileOperation::Delete { .. } => {
+                    if operation.matches(file_operations.did_delete.as_ref()) {
+                        if let Some(params) = operation.delete_params() {
+                            server
+                                .notify::<lsp::notification::DidDeleteFiles>(params)
+                                .log_err();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn expand_entry(
+        &mut self,
+        worktree_id: WorktreeId,
+        entry_id: ProjectEntryId,
         cx: &mut ModelContext<Self>,
     ) -> Option<Task<Result<()>>> {
         let worktree = self.worktree_for_id(worktree_id, cx)?;
@@ -1915,9 +2592,96 @@ impl Project {
     }
 
     pub fn is_ssh(&self) -> bool {
-        match &self.client_state {
-            ProjectClientState::Local | ProjectClientState::Shared { .. } => true,
-            ProjectClientState::Remote { .. } => false,
+        self.ssh_session.is_some()
+    }
+
+    pub fn ssh_connection_status(&self) -> Option<SshConnectionStatus> {
+        let ssh_session = self.ssh_session.as_ref()?;
+        let status = *ssh_session.status().borrow();
+        Some(status)
+    }
+
+    pub fn ssh_connection_string(&self) -> Option<String> {
+        self.ssh_session.as_ref()?.connection_string()
+    }
+
+    /// The ports that are forwarded over the SSH connection of the project.
+    pub fn ssh_port_forwards(&self) -> Vec<SshPortForward> {
+        self.ssh_session
+            .as_ref()
+            .map(|ssh_session| ssh_session.port_forwards())
+            .unwrap_or_default()
+    }
+
+    pub fn forward_ssh_port(
+        &mut self,
+        forward: SshPortForward,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(ssh_session) = self.ssh_session.clone() else {
+            return Task::ready(Err(anyhow!("not an ssh project")));
+        };
+        cx.spawn(|this, mut cx| async move {
+            ssh_session.forward_port(forward).await?;
+            this.update(&mut cx, |_, cx| cx.notify())?;
+            Ok(())
+        })
+    }
+
+    pub fn cancel_ssh_port_forward(
+        &mut self,
+        forward: SshPortForward,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(ssh_session) = self.ssh_session.clone() else {
+            return Task::ready(Err(anyhow!("not an ssh project")));
+        };
+        cx.spawn(|this, mut cx| async move {
+            let result = ssh_session.cancel_port_forward(forward).await;
+            this.update(&mut cx, |_, cx| cx.notify())?;
+            result
+        })
+    }
+
+    /// Forwards a port that a server run on the remote host announced listening on to the same
+    /// local port, unless it is forwarded already.
+    fn forward_announced_ssh_port(&mut self, port: u16, cx: &mut ModelContext<Self>) {
+        let is_forwarded = self.ssh_port_forwards().iter().any(|forward| {
+            forward.kind == SshPortForwardKind::Local && forward.remote_port == port
+        });
+        if is_forwarded {
+            return;
+        }
+        let forward = SshPortForward::local(port, port);
+        let task = self.forward_ssh_port(forward, cx);
+        cx.spawn(|this, mut cx| async move {
+            task.await?;
+            this.update(&mut cx, |_, cx| cx.emit(Event::SshPortForwarded(forward)))
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// The client that requests for this project's language servers are sent to, along with
+    /// the id of the project on the other end, when those servers run elsewhere: on the host of
+    /// a project that has been joined, or on the remote machine of an SSH project.
+    fn lsp_upstream_client(&self) -> Option<(AnyProtoClient, u64)> {
+        if let Some(ssh_session) = &self.ssh_session {
+            Some((ssh_session.clone().into(), 0))
+        } else if self.is_remote() {
+            Some((self.client.clone().into(), self.remote_id()?))
+        } else {
+            None
+        }
+    }
+
+    /// The client that updates from this project's language servers, such as diagnostics, are
+    /// sent to, along with the id of the project on the other end: the collaborators of a
+    /// shared project, or the client of an SSH project whose headless server this is.
+    fn lsp_downstream_client(&self) -> Option<(AnyProtoClient, u64)> {
+        if let Some(downstream_ssh_session) = &self.downstream_ssh_session {
+            Some((downstream_ssh_session.clone(), 0))
+        } else {
+            Some((self.client.clone().into(), self.remote_id()?))
         }
     }
 
@@ -2129,6 +2893,18 @@ impl Project {
         })
     }
 
+    /// Reloads a buffer's file with the given encoding, which the buffer is then saved with.
+    pub fn reopen_buffer_with_encoding(
+        &mut self,
+        buffer: Model<Buffer>,
+        encoding: Encoding,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.buffer_store.update(cx, |buffer_store, cx| {
+            buffer_store.reopen_buffer_with_encoding(buffer, encoding, cx)
+        })
+    }
+
     pub fn get_open_buffer(
         &mut self,
         path: &ProjectPath,
@@ -2155,6 +2931,16 @@ impl Project {
         self.detect_language_for_buffer(buffer, cx);
         self.register_buffer_with_language_servers(buffer, cx);
         cx.observe_release(buffer, |this, buffer, cx| {
+            this.semantic_tokens.remove(&buffer.remote_id());
+            this.document_links.remove(&buffer.remote_id());
+            // The server only sends a buffer once, so it must be told when the buffer is gone.
+            if let Some(ssh) = &this.ssh_session {
+                ssh.send(proto::CloseBuffer {
+                    project_id: 0,
+                    buffer_id: buffer.remote_id().to_proto(),
+                })
+                .log_err();
+            }
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
                     let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
@@ -2259,6 +3045,8 @@ impl Project {
                         .insert(server.server_id(), vec![snapshot]);
                 }
             }
+
+            self.pull_buffer_diagnostics(buffer_handle, cx);
         }
     }
 
@@ -2286,6 +3074,9 @@ impl Project {
             }
 
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            self.document_links.remove(&buffer.remote_id());
+            self.pull_diagnostics_tasks.remove(&buffer.remote_id());
             let file_url = lsp::Url::from_file_path(old_path).unwrap();
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
@@ -2380,8 +3171,10 @@ impl Project {
                         .await?;
 
                         this.update(&mut cx, |this, _| {
-                            if let Some(project_id) = this.remote_id() {
-                                this.client
+                            if let Some((downstream_client, project_id)) =
+                                this.lsp_downstream_client()
+                            {
+                                downstream_client
                                     .send(proto::UpdateLanguageServer {
                                         project_id,
                                         language_server_id: language_server_id.0 as u64,
@@ -2426,7 +3219,13 @@ impl Project {
                 self.register_buffer_with_language_servers(&buffer, cx);
             }
             BufferStoreEvent::MessageToReplicas(message) => {
-                self.client.send_dynamic(message.as_ref().clone()).log_err();
+                if let Some(downstream_ssh_session) = &self.downstream_ssh_session {
+                    downstream_ssh_session
+                        .send_dynamic(message.as_ref().clone())
+                        .log_err();
+                } else {
+                    self.client.send_dynamic(message.as_ref().clone()).log_err();
+                }
             }
         }
     }
@@ -2456,10 +3255,16 @@ impl Project {
         ) {
             self.request_buffer_diff_recalculation(&buffer, cx);
         }
+        if matches!(event, BufferEvent::Edited { .. }) {
+            self.pull_buffer_diagnostics(&buffer, cx);
+        }
 
         let buffer_id = buffer.read(cx).remote_id();
         match event {
             BufferEvent::Operation(operation) => {
+                // The client of an SSH project makes its own edits, so its headless server only
+                // sends back the operations that originate there, such as diagnostics.
+                let is_edit = matches!(operation, language::Operation::Buffer(_));
                 let operation = language::proto::serialize_operation(operation);
 
                 if let Some(ssh) = &self.ssh_session {
@@ -2469,6 +3274,16 @@ impl Project {
                         operations: vec![operation.clone()],
                     })
                     .ok();
+                } else if let Some(downstream_ssh_session) =
+                    self.downstream_ssh_session.as_ref().filter(|_| !is_edit)
+                {
+                    downstream_ssh_session
+                        .send(proto::UpdateBuffer {
+                            project_id: 0,
+                            buffer_id: buffer_id.to_proto(),
+                            operations: vec![operation.clone()],
+                        })
+                        .ok();
                 }
 
                 self.enqueue_buffer_ordered_message(BufferOrderedMessage::Operation {
@@ -2489,6 +3304,7 @@ impl Project {
                                 version: serialize_version(&buffer.version()),
                                 mtime: buffer.saved_mtime().map(|t| t.into()),
                                 line_ending: serialize_line_ending(buffer.line_ending()) as i32,
+                                encoding: Some(serialize_encoding(buffer.encoding())),
                             })
                             .log_err();
                     }
@@ -2618,6 +3434,7 @@ impl Project {
 
                 for language_server_id in self.language_server_ids_for_buffer(buffer.read(cx), cx) {
                     self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
+                    self.pull_workspace_diagnostics(language_server_id, cx);
                 }
             }
 
@@ -3505,8 +4322,42 @@ impl Project {
                     async move {
                         this.update(&mut cx, |project, cx| {
                             cx.emit(Event::RefreshInlayHints);
-                            project.remote_id().map(|project_id| {
-                                project.client.send(proto::RefreshInlayHints { project_id })
+                            project.lsp_downstream_client().map(|(client, project_id)| {
+                                client.send(proto::RefreshInlayHints { project_id })
+                            })
+                        })?
+                        .transpose()?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = project.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.pull_diagnostics_for_language_server(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
+        language_server
+            .on_request::<lsp::request::SemanticTokensRefresh, _, _>({
+                let this = project.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.invalidate_semantic_tokens(Some(server_id), cx);
+                            project.lsp_downstream_client().map(|(client, project_id)| {
+                                client.send(proto::RefreshSemanticTokens { project_id })
                             })
                         })?
                         .transpose()?;
@@ -3707,8 +4558,8 @@ impl Project {
 
         cx.emit(Event::LanguageServerAdded(server_id));
 
-        if let Some(project_id) = self.remote_id() {
-            self.client.send(proto::StartLanguageServer {
+        if let Some((downstream_client, project_id)) = self.lsp_downstream_client() {
+            downstream_client.send(proto::StartLanguageServer {
                 project_id,
                 server: Some(proto::LanguageServer {
                     id: server_id.0 as u64,
@@ -3786,6 +4637,7 @@ impl Project {
             }
             anyhow::Ok(())
         })?;
+        self.pull_diagnostics_for_language_server(server_id, cx);
 
         cx.notify();
         Ok(())
@@ -3822,14 +4674,14 @@ impl Project {
                 }
             });
 
-            let project_id = self.remote_id();
+            let downstream_client = self.lsp_downstream_client();
             for (worktree_id, summaries) in self.diagnostic_summaries.iter_mut() {
                 summaries.retain(|path, summaries_by_server_id| {
                     if summaries_by_server_id.remove(&server_id).is_some() {
-                        if let Some(project_id) = project_id {
-                            self.client
+                        if let Some((downstream_client, project_id)) = &downstream_client {
+                            downstream_client
                                 .send(proto::UpdateDiagnosticSummary {
-                                    project_id,
+                                    project_id: *project_id,
                                     worktree_id: worktree_id.to_proto(),
                                     summary: Some(proto::DiagnosticSummary {
                                         path: path.to_string_lossy().to_string(),
@@ -3862,6 +4714,10 @@ impl Project {
 
             self.language_server_watched_paths.remove(&server_id);
             self.language_server_statuses.remove(&server_id);
+            self.diagnostic_result_ids.remove(&server_id);
+            self.workspace_diagnostics_tasks.remove(&server_id);
+            self.invalidate_semantic_tokens(Some(server_id), cx);
+            self.document_links.clear();
             cx.notify();
 
             let server_state = self.language_servers.remove(&server_id);
@@ -3936,9 +4792,9 @@ impl Project {
         buffers: impl IntoIterator<Item = Model<Buffer>>,
         cx: &mut ModelContext<Self>,
     ) {
-        if self.is_remote() {
-            let request = self.client.request(proto::RestartLanguageServers {
-                project_id: self.remote_id().unwrap(),
+        if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request = upstream_client.request(proto::RestartLanguageServers {
+                project_id,
                 buffer_ids: buffers
                     .into_iter()
                     .map(|b| b.read(cx).remote_id().to_proto())
@@ -4490,50 +5346,299 @@ impl Project {
         self.last_formatting_failure.as_deref()
     }
 
-    pub fn update_diagnostics(
+    /// Pulls the diagnostics of an open buffer from its language servers supporting the
+    /// pull model, debouncing the requests while the buffer is being edited.
+    fn pull_buffer_diagnostics(
         &mut self,
-        language_server_id: LanguageServerId,
-        mut params: lsp::PublishDiagnosticsParams,
-        disk_based_sources: &[String],
+        buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
-    ) -> Result<()> {
-        let abs_path = params
-            .uri
-            .to_file_path()
-            .map_err(|_| anyhow!("URI is not a file"))?;
-        let mut diagnostics = Vec::default();
-        let mut primary_diagnostic_group_ids = HashMap::default();
-        let mut sources_by_group_id = HashMap::default();
-        let mut supporting_diagnostics = HashMap::default();
+    ) {
+        const PULL_DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(250);
 
-        // Ensure that primary diagnostics are always the most severe
-        params.diagnostics.sort_by_key(|item| item.severity);
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(abs_path) =
+            File::from_dyn(buffer.file()).and_then(|file| Some(file.as_local()?.abs_path(cx)))
+        else {
+            return;
+        };
+        let Some(uri) = lsp::Url::from_file_path(abs_path).log_err() else {
+            return;
+        };
+        let servers = self
+            .language_servers_for_buffer(buffer, cx)
+            .filter_map(|(adapter, server)| {
+                let options = diagnostic_options(&server.capabilities())?.clone();
+                Some((adapter.clone(), server.clone(), options.identifier))
+            })
+            .collect::<Vec<_>>();
+        if servers.is_empty() {
+            return;
+        }
 
-        for diagnostic in &params.diagnostics {
-            let source = diagnostic.source.as_ref();
-            let code = diagnostic.code.as_ref().map(|code| match code {
-                lsp::NumberOrString::Number(code) => code.to_string(),
-                lsp::NumberOrString::String(code) => code.clone(),
-            });
-            let range = range_from_lsp(diagnostic.range);
-            let is_supporting = diagnostic
-                .related_information
-                .as_ref()
-                .map_or(false, |infos| {
-                    infos.iter().any(|info| {
-                        primary_diagnostic_group_ids.contains_key(&(
-                            source,
-                            code.clone(),
-                            range_from_lsp(info.location.range),
-                        ))
-                    })
-                });
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(PULL_DIAGNOSTICS_DEBOUNCE)
+                .await;
+            for (adapter, server, identifier) in servers {
+                let server_id = server.server_id();
+                let Ok((version, previous_result_id)) = this.update(&mut cx, |this, _| {
+                    let version = this
+                        .buffer_snapshots
+                        .get(&buffer_id)
+                        .and_then(|snapshots| snapshots.get(&server_id)?.last())
+                        .map(|snapshot| snapshot.version);
+                    (version, this.previous_diagnostic_result_id(server_id, &uri))
+                }) else {
+                    return;
+                };
+                let report = server
+                    .request::<lsp::request::DocumentDiagnosticRequest>(
+                        lsp::DocumentDiagnosticParams {
+                            text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                            identifier,
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await;
+                let Some(report) = report.log_err() else {
+                    continue;
+                };
 
-            let is_unnecessary = diagnostic.tags.as_ref().map_or(false, |tags| {
-                tags.iter().any(|tag| *tag == DiagnosticTag::UNNECESSARY)
-            });
+                let (report, related_documents) = match report {
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Full(report),
+                    ) => (
+                        Some(lsp::DocumentDiagnosticReportKind::Full(
+                            report.full_document_diagnostic_report,
+                        )),
+                        report.related_documents,
+                    ),
+                    lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Unchanged(report),
+                    ) => (
+                        Some(lsp::DocumentDiagnosticReportKind::Unchanged(
+                            report.unchanged_document_diagnostic_report,
+                        )),
+                        report.related_documents,
+                    ),
+                    lsp::DocumentDiagnosticReportResult::Partial(report) => {
+                        (None, report.related_documents)
+                    }
+                };
+                this.update(&mut cx, |this, cx| {
+                    if let Some(report) = report {
+                        this.update_pulled_diagnostics(
+                            server_id,
+                            &adapter,
+                            uri.clone(),
+                            version,
+                            report,
+                            cx,
+                        );
+                    }
+                    for (uri, report) in related_documents.into_iter().flatten() {
+                        this.update_pulled_diagnostics(server_id, &adapter, uri, None, report, cx);
+                    }
+                })
+                .ok();
+            }
+        });
+        self.pull_diagnostics_tasks.insert(buffer_id, task);
+    }
 
-            if is_supporting {
+    /// Pulls the diagnostics of the whole workspace from a language server, if it supports it.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(LanguageServerState::Running {
+            adapter, server, ..
+        }) = self.language_servers.get(&server_id)
+        else {
+            return;
+        };
+        let Some(options) = diagnostic_options(&server.capabilities())
+            .filter(|options| options.workspace_diagnostics)
+            .cloned()
+        else {
+            return;
+        };
+        let adapter = adapter.clone();
+        let server = server.clone();
+        let previous_result_ids = self
+            .diagnostic_result_ids
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .map(|(uri, value)| lsp::PreviousResultId {
+                uri: uri.clone(),
+                value: value.clone(),
+            })
+            .collect();
+
+        let task = cx.spawn(move |this, mut cx| async move {
+            let report = server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(
+                    lsp::WorkspaceDiagnosticParams {
+                        identifier: options.identifier,
+                        previous_result_ids,
+                        work_done_progress_params: Default::default(),
+                        partial_result_params: Default::default(),
+                    },
+                )
+                .await;
+            let Some(report) = report.log_err() else {
+                return;
+            };
+            let items = match report {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(report) => report.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    let version = version.and_then(|version| i32::try_from(version).ok());
+                    this.update_pulled_diagnostics(server_id, &adapter, uri, version, report, cx);
+                }
+            })
+            .ok();
+        });
+        self.workspace_diagnostics_tasks.insert(server_id, task);
+    }
+
+    /// Pulls the diagnostics of every open buffer handled by the language server, and of its
+    /// workspace, e.g. after the server started or requested a refresh.
+    fn pull_diagnostics_for_language_server(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffers = self
+            .buffer_store
+            .read(cx)
+            .buffers()
+            .filter(|buffer| {
+                self.language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_buffer_diagnostics(&buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn previous_diagnostic_result_id(
+        &self,
+        server_id: LanguageServerId,
+        uri: &lsp::Url,
+    ) -> Option<String> {
+        self.diagnostic_result_ids
+            .get(&server_id)?
+            .get(uri)
+            .cloned()
+    }
+
+    fn update_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        adapter: &CachedLspAdapter,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let result_ids = self.diagnostic_result_ids.entry(server_id).or_default();
+        match report {
+            // The diagnostics reported previously are still valid.
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                result_ids.insert(uri, report.result_id);
+            }
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                match report.result_id {
+                    Some(result_id) => result_ids.insert(uri.clone(), result_id),
+                    None => result_ids.remove(&uri),
+                };
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+        }
+    }
+
+    pub fn update_diagnostics(
+        &mut self,
+        language_server_id: LanguageServerId,
+        mut params: lsp::PublishDiagnosticsParams,
+        disk_based_sources: &[String],
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let abs_path = params
+            .uri
+            .to_file_path()
+            .map_err(|_| anyhow!("URI is not a file"))?;
+        let mut diagnostics = Vec::default();
+        let mut primary_diagnostic_group_ids = HashMap::default();
+        let mut sources_by_group_id = HashMap::default();
+        let mut supporting_diagnostics = HashMap::default();
+
+        // Ensure that primary diagnostics are always the most severe
+        params.diagnostics.sort_by_key(|item| item.severity);
+
+        for diagnostic in &params.diagnostics {
+            let source = diagnostic.source.as_ref();
+            let code = diagnostic.code.as_ref().map(|code| match code {
+                lsp::NumberOrString::Number(code) => code.to_string(),
+                lsp::NumberOrString::String(code) => code.clone(),
+            });
+            let range = range_from_lsp(diagnostic.range);
+            let is_supporting = diagnostic
+                .related_information
+                .as_ref()
+                .map_or(false, |infos| {
+                    infos.iter().any(|info| {
+                        primary_diagnostic_group_ids.contains_key(&(
+                            source,
+                            code.clone(),
+                            range_from_lsp(info.location.range),
+                        ))
+                    })
+                });
+
+            let is_unnecessary = diagnostic.tags.as_ref().map_or(false, |tags| {
+                tags.iter().any(|tag| *tag == DiagnosticTag::UNNECESSARY)
+            });
+
+            if is_supporting {
                 supporting_diagnostics.insert(
                     (source, code.clone(), range),
                     (diagnostic.severity, is_unnecessary),
@@ -4693,8 +5798,8 @@ impl Project {
         }
 
         if !old_summary.is_empty() || !new_summary.is_empty() {
-            if let Some(project_id) = self.remote_id() {
-                self.client
+            if let Some((downstream_client, project_id)) = self.lsp_downstream_client() {
+                downstream_client
                     .send(proto::UpdateDiagnosticSummary {
                         project_id,
                         worktree_id: worktree_id.to_proto(),
@@ -4835,7 +5940,7 @@ impl Project {
             for buffer in local_buffers {
                 let transaction = buffer
                     .update(&mut cx, |buffer, cx| buffer.reload(cx))?
-                    .await?;
+                    .await??;
                 buffer.update(&mut cx, |buffer, cx| {
                     if let Some(transaction) = transaction {
                         if !push_to_history {
@@ -4857,7 +5962,25 @@ impl Project {
         trigger: FormatTrigger,
         cx: &mut ModelContext<Project>,
     ) -> Task<anyhow::Result<ProjectTransaction>> {
-        if self.is_local() {
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            cx.spawn(move |this, mut cx| async move {
+                let response = client
+                    .request(proto::FormatBuffers {
+                        project_id,
+                        trigger: trigger as i32,
+                        buffer_ids: buffers
+                            .iter()
+                            .map(|buffer| {
+                                buffer.update(&mut cx, |buffer, _| buffer.remote_id().into())
+                            })
+                            .collect::<Result<_>>()?,
+                    })
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+                Self::deserialize_project_transaction(this, response, push_to_history, cx).await
+            })
+        } else if self.is_local() {
             let buffers_with_paths = buffers
                 .into_iter()
                 .map(|buffer_handle| {
@@ -4888,29 +6011,7 @@ impl Project {
                 result
             })
         } else {
-            let remote_id = self.remote_id();
-            let client = self.client.clone();
-            cx.spawn(move |this, mut cx| async move {
-                if let Some(project_id) = remote_id {
-                    let response = client
-                        .request(proto::FormatBuffers {
-                            project_id,
-                            trigger: trigger as i32,
-                            buffer_ids: buffers
-                                .iter()
-                                .map(|buffer| {
-                                    buffer.update(&mut cx, |buffer, _| buffer.remote_id().into())
-                                })
-                                .collect::<Result<_>>()?,
-                        })
-                        .await?
-                        .transaction
-                        .ok_or_else(|| anyhow!("missing transaction"))?;
-                    Self::deserialize_project_transaction(this, response, push_to_history, cx).await
-                } else {
-                    Ok(ProjectTransaction::default())
-                }
-            })
+            Task::ready(Ok(ProjectTransaction::default()))
         }
     }
 
@@ -5534,6 +6635,88 @@ impl Project {
         self.references_impl(buffer, position, cx)
     }
 
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn incoming_calls(
+        &self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls { item: item.clone() },
+            cx,
+        )
+    }
+
+    pub fn outgoing_calls(
+        &self,
+        item: &CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls { item: item.clone() },
+            cx,
+        )
+    }
+
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    pub fn supertypes(
+        &self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes { item: item.clone() },
+            cx,
+        )
+    }
+
+    pub fn subtypes(
+        &self,
+        item: &TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            item.location.buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes { item: item.clone() },
+            cx,
+        )
+    }
+
     fn document_highlights_impl(
         &self,
         buffer: &Model<Buffer>,
@@ -5561,7 +6744,7 @@ impl Project {
     pub fn symbols(&self, query: &str, cx: &mut ModelContext<Self>) -> Task<Result<Vec<Symbol>>> {
         let language_registry = self.languages.clone();
 
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let mut requests = Vec::new();
             for ((worktree_id, _), server_id) in self.language_server_ids.iter() {
                 let Some(worktree_handle) = self.worktree_for_id(*worktree_id, cx) else {
@@ -5685,8 +6868,8 @@ impl Project {
 
                 Ok(symbols)
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let request = self.client.request(proto::GetProjectSymbols {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request = upstream_client.request(proto::GetProjectSymbols {
                 project_id,
                 query: query.to_string(),
             });
@@ -5718,7 +6901,7 @@ impl Project {
         symbol: &Symbol,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Model<Buffer>>> {
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let language_server_id = if let Some(id) = self.language_server_ids.get(&(
                 symbol.source_worktree_id,
                 symbol.language_server_name.clone(),
@@ -5752,8 +6935,8 @@ impl Project {
                 symbol.language_server_name.clone(),
                 cx,
             )
-        } else if let Some(project_id) = self.remote_id() {
-            let request = self.client.request(proto::OpenBufferForSymbol {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request = upstream_client.request(proto::OpenBufferForSymbol {
                 project_id,
                 symbol: Some(serialize_symbol(symbol)),
             });
@@ -5777,7 +6960,7 @@ impl Project {
         cx: &mut ModelContext<Self>,
     ) -> Task<Vec<SignatureHelp>> {
         let position = position.to_point_utf16(buffer.read(cx));
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let all_actions_task = self.request_multiple_lsp_locally(
                 buffer,
                 Some(position),
@@ -5792,8 +6975,8 @@ impl Project {
                     .filter(|help| !help.markdown.is_empty())
                     .collect::<Vec<_>>()
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let request_task = self.client().request(proto::MultiLspQuery {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request_task = upstream_client.request(proto::MultiLspQuery {
                 buffer_id: buffer.read(cx).remote_id().into(),
                 version: serialize_version(&buffer.read(cx).version()),
                 project_id,
@@ -5851,7 +7034,7 @@ impl Project {
         position: PointUtf16,
         cx: &mut ModelContext<Self>,
     ) -> Task<Vec<Hover>> {
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let all_actions_task = self.request_multiple_lsp_locally(
                 &buffer,
                 Some(position),
@@ -5865,8 +7048,8 @@ impl Project {
                     .filter_map(|hover| remove_empty_hover_blocks(hover?))
                     .collect::<Vec<Hover>>()
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let request_task = self.client().request(proto::MultiLspQuery {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request_task = upstream_client.request(proto::MultiLspQuery {
                 buffer_id: buffer.read(cx).remote_id().into(),
                 version: serialize_version(&buffer.read(cx).version()),
                 project_id,
@@ -5991,6 +7174,237 @@ impl Project {
         self.linked_edit_impl(buffer, position, cx)
     }
 
+    /// Returns the semantic tokens of the buffer's primary language server.
+    ///
+    /// Tokens are cached per buffer version: unchanged buffers are served from the cache,
+    /// and edited ones are updated with a delta request when the server supports it.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Arc<SemanticTokens>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        let cached = self.semantic_tokens.get(&buffer_id);
+        if let Some(cached) = cached {
+            if cached.tokens.version == version {
+                return Task::ready(Ok(cached.tokens.clone()));
+            }
+        }
+
+        // The language servers of SSH projects run on the remote machine, which keeps track of
+        // the results to send deltas against.
+        let is_local = self.is_local() && !self.is_ssh();
+        let (server_id, request) = if is_local {
+            let Some((_, language_server)) =
+                self.primary_language_server_for_buffer(buffer.read(cx), cx)
+            else {
+                return Task::ready(Ok(Default::default()));
+            };
+            let server_id = language_server.server_id();
+            let previous = cached
+                .filter(|cached| {
+                    cached.server_id == Some(server_id) && cached.tokens.result_id.is_some()
+                })
+                .map(|cached| cached.tokens.clone())
+                .filter(|_| GetSemanticTokensDelta::is_supported(&language_server.capabilities()));
+            let server = LanguageServerToQuery::Other(server_id);
+            let request = match previous {
+                Some(previous) => self.request_lsp(
+                    buffer.clone(),
+                    server,
+                    GetSemanticTokensDelta { previous },
+                    cx,
+                ),
+                None => self.request_lsp(buffer.clone(), server, GetSemanticTokensFull, cx),
+            };
+            (Some(server_id), request)
+        } else {
+            let request = self.request_lsp(
+                buffer.clone(),
+                LanguageServerToQuery::Primary,
+                GetSemanticTokensFull,
+                cx,
+            );
+            (None, request)
+        };
+
+        cx.spawn(move |this, mut cx| async move {
+            let mut tokens = request.await?;
+            if is_local {
+                tokens.version = version;
+            }
+            let tokens = Arc::new(tokens);
+            this.update(&mut cx, |this, _| {
+                this.semantic_tokens.insert(
+                    buffer_id,
+                    CachedSemanticTokens {
+                        server_id,
+                        tokens: tokens.clone(),
+                    },
+                );
+            })?;
+            Ok(tokens)
+        })
+    }
+
+    /// Returns the folding ranges of the buffer's primary language server, or `None` if
+    /// there is no server providing them and folding should be based on indentation.
+    pub fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Vec<FoldingRange>>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Returns the code lenses the buffer's primary language server displays. Their commands
+    /// may be missing until they are resolved with [`Project::resolve_code_lens`].
+    pub fn code_lens(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CodeLens>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetCodeLens,
+            cx,
+        )
+    }
+
+    /// Returns the links of the buffer's primary language server, cached per buffer version.
+    ///
+    /// The targets of the links may be missing until they are resolved with
+    /// [`Project::resolve_document_link`].
+    pub fn document_links(
+        &mut self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentLink>>> {
+        let buffer_id = buffer.read(cx).remote_id();
+        let version = buffer.read(cx).version();
+        if let Some(cached) = self.document_links.get(&buffer_id) {
+            if cached.version == version {
+                return Task::ready(Ok(cached.links.clone()));
+            }
+        }
+
+        let request = self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentLinks,
+            cx,
+        );
+        cx.spawn(move |this, mut cx| async move {
+            let links = request.await?;
+            this.update(&mut cx, |this, _| {
+                this.document_links.insert(
+                    buffer_id,
+                    CachedDocumentLinks {
+                        version,
+                        links: links.clone(),
+                    },
+                );
+            })?;
+            Ok(links)
+        })
+    }
+
+    /// Computes the target of a document link that the language server left out of the
+    /// response.
+    pub fn resolve_document_link(
+        &self,
+        buffer_handle: Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        if link.lsp_link.target.is_some() {
+            return Task::ready(Ok(link));
+        }
+
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                link: Some(Self::serialize_document_link(&link)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("document link resolve proto request")?;
+                match response.link {
+                    Some(link) => Self::deserialize_document_link(link),
+                    None => Ok(link),
+                }
+            })
+        } else if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer, link.server_id, cx)
+            else {
+                return Task::ready(Ok(link));
+            };
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
+                    .await
+                    .context("document link resolve LSP request")?;
+                Ok(DocumentLink { lsp_link, ..link })
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    pub fn document_colors(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<DocumentColor>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetDocumentColors,
+            cx,
+        )
+    }
+
+    pub fn color_presentations(
+        &self,
+        buffer: &Model<Buffer>,
+        color: DocumentColor,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<ColorPresentation>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetColorPresentations { color },
+            cx,
+        )
+    }
+
+    fn invalidate_semantic_tokens(
+        &mut self,
+        server_id: Option<LanguageServerId>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        match server_id {
+            Some(server_id) => self
+                .semantic_tokens
+                .retain(|_, cached| cached.server_id != Some(server_id)),
+            None => self.semantic_tokens.clear(),
+        }
+        cx.emit(Event::RefreshSemanticTokens);
+    }
+
     #[inline(never)]
     fn completions_impl(
         &self,
@@ -6001,7 +7415,7 @@ impl Project {
     ) -> Task<Result<Vec<Completion>>> {
         let language_registry = self.languages.clone();
 
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let snapshot = buffer.read(cx).snapshot();
             let offset = position.to_offset(&snapshot);
             let scope = snapshot.language_scope_at(offset);
@@ -6056,9 +7470,10 @@ impl Project {
 
                 Ok(completions)
             })
-        } else if let Some(project_id) = self.remote_id() {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
             let task = self.send_lsp_proto_request(
                 buffer.clone(),
+                upstream_client,
                 project_id,
                 GetCompletions { position, context },
                 cx,
@@ -6108,21 +7523,15 @@ impl Project {
         completions: Arc<RwLock<Box<[Completion]>>>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<bool>> {
-        let client = self.client();
         let language_registry = self.languages().clone();
-
-        let is_remote = self.is_remote();
-        let project_id = self.remote_id();
+        let upstream_client = self.lsp_upstream_client();
 
         let buffer_id = buffer.read(cx).remote_id();
         let buffer_snapshot = buffer.read(cx).snapshot();
 
         cx.spawn(move |this, mut cx| async move {
             let mut did_resolve = false;
-            if is_remote {
-                let project_id =
-                    project_id.ok_or_else(|| anyhow!("Remote project without remote_id"))?;
-
+            if let Some((client, project_id)) = upstream_client {
                 for completion_index in completion_indices {
                     let (server_id, completion) = {
                         let completions_guard = completions.read();
@@ -6267,7 +7676,7 @@ impl Project {
         completions: Arc<RwLock<Box<[Completion]>>>,
         completion_index: usize,
         completion: lsp::CompletionItem,
-        client: Arc<Client>,
+        client: AnyProtoClient,
         language_registry: Arc<LanguageRegistry>,
     ) {
         let request = proto::ResolveCompletionDocumentation {
@@ -6324,7 +7733,39 @@ impl Project {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
 
-        if self.is_local() {
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            cx.spawn(move |_, mut cx| async move {
+                let response = client
+                    .request(proto::ApplyCompletionAdditionalEdits {
+                        project_id,
+                        buffer_id: buffer_id.into(),
+                        completion: Some(Self::serialize_completion(&CoreCompletion {
+                            old_range: completion.old_range,
+                            new_text: completion.new_text,
+                            server_id: completion.server_id,
+                            lsp_completion: completion.lsp_completion,
+                        })),
+                    })
+                    .await?;
+
+                if let Some(transaction) = response.transaction {
+                    let transaction = language::proto::deserialize_transaction(transaction)?;
+                    buffer_handle
+                        .update(&mut cx, |buffer, _| {
+                            buffer.wait_for_edits(transaction.edit_ids.iter().copied())
+                        })?
+                        .await?;
+                    if push_to_history {
+                        buffer_handle.update(&mut cx, |buffer, _| {
+                            buffer.push_transaction(transaction.clone(), Instant::now());
+                        })?;
+                    }
+                    Ok(Some(transaction))
+                } else {
+                    Ok(None)
+                }
+            })
+        } else if self.is_local() {
             let server_id = completion.server_id;
             let lang_server = match self.language_server_for_buffer(buffer, server_id, cx) {
                 Some((_, server)) => server.clone(),
@@ -6392,39 +7833,6 @@ impl Project {
                     Ok(None)
                 }
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let client = self.client.clone();
-            cx.spawn(move |_, mut cx| async move {
-                let response = client
-                    .request(proto::ApplyCompletionAdditionalEdits {
-                        project_id,
-                        buffer_id: buffer_id.into(),
-                        completion: Some(Self::serialize_completion(&CoreCompletion {
-                            old_range: completion.old_range,
-                            new_text: completion.new_text,
-                            server_id: completion.server_id,
-                            lsp_completion: completion.lsp_completion,
-                        })),
-                    })
-                    .await?;
-
-                if let Some(transaction) = response.transaction {
-                    let transaction = language::proto::deserialize_transaction(transaction)?;
-                    buffer_handle
-                        .update(&mut cx, |buffer, _| {
-                            buffer.wait_for_edits(transaction.edit_ids.iter().copied())
-                        })?
-                        .await?;
-                    if push_to_history {
-                        buffer_handle.update(&mut cx, |buffer, _| {
-                            buffer.push_transaction(transaction.clone(), Instant::now());
-                        })?;
-                    }
-                    Ok(Some(transaction))
-                } else {
-                    Ok(None)
-                }
-            })
         } else {
             Task::ready(Err(anyhow!("project does not have a remote id")))
         }
@@ -6436,7 +7844,7 @@ impl Project {
         range: Range<Anchor>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Vec<CodeAction>> {
-        if self.is_local() {
+        if self.is_local() && !self.is_ssh() {
             let all_actions_task = self.request_multiple_lsp_locally(
                 &buffer_handle,
                 Some(range.start),
@@ -6447,8 +7855,8 @@ impl Project {
                 cx,
             );
             cx.spawn(|_, _| async move { all_actions_task.await.into_iter().flatten().collect() })
-        } else if let Some(project_id) = self.remote_id() {
-            let request_task = self.client().request(proto::MultiLspQuery {
+        } else if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            let request_task = upstream_client.request(proto::MultiLspQuery {
                 buffer_id: buffer_handle.read(cx).remote_id().into(),
                 version: serialize_version(&buffer_handle.read(cx).version()),
                 project_id,
@@ -6527,7 +7935,21 @@ impl Project {
         push_to_history: bool,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<ProjectTransaction>> {
-        if self.is_local() {
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            let request = proto::ApplyCodeAction {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                action: Some(Self::serialize_code_action(&action)),
+            };
+            cx.spawn(move |this, cx| async move {
+                let response = client
+                    .request(request)
+                    .await?
+                    .transaction
+                    .ok_or_else(|| anyhow!("missing transaction"))?;
+                Self::deserialize_project_transaction(this, response, push_to_history, cx).await
+            })
+        } else if self.is_local() {
             let buffer = buffer_handle.read(cx);
             let (lsp_adapter, lang_server) = if let Some((adapter, server)) =
                 self.language_server_for_buffer(buffer, action.server_id, cx)
@@ -6582,12 +8004,84 @@ impl Project {
 
                 Ok(ProjectTransaction::default())
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let client = self.client.clone();
-            let request = proto::ApplyCodeAction {
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Computes the command of a code lens that the language server left out of the response.
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.lsp_lens.command.is_some() {
+            return Task::ready(Ok(lens));
+        }
+
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            let request = proto::ResolveCodeLens {
                 project_id,
                 buffer_id: buffer_handle.read(cx).remote_id().into(),
-                action: Some(Self::serialize_code_action(&action)),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("code lens resolve proto request")?;
+                match response.lens {
+                    Some(lens) => Self::deserialize_code_lens(lens),
+                    None => Ok(lens),
+                }
+            })
+        } else if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(lens));
+            };
+            let can_resolve = lang_server
+                .capabilities()
+                .code_lens_provider
+                .and_then(|options| options.resolve_provider)
+                .unwrap_or(false);
+            if !can_resolve {
+                return Task::ready(Ok(lens));
+            }
+
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(CodeLens { lsp_lens, ..lens })
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Runs the command of a code lens via `workspace/executeCommand`, returning the edits
+    /// the language server applied while executing it.
+    pub fn execute_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(command) = lens.lsp_lens.command.clone() else {
+            return Task::ready(Ok(ProjectTransaction::default()));
+        };
+
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            let request = proto::ExecuteCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
             };
             cx.spawn(move |this, cx| async move {
                 let response = client
@@ -6595,7 +8089,47 @@ impl Project {
                     .await?
                     .transaction
                     .ok_or_else(|| anyhow!("missing transaction"))?;
-                Self::deserialize_project_transaction(this, response, push_to_history, cx).await
+                Self::deserialize_project_transaction(this, response, true, cx).await
+            })
+        } else if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(Default::default()));
+            };
+            // Lenses may carry commands meant for the client, which the server cannot execute.
+            let is_supported = lang_server
+                .capabilities()
+                .execute_command_provider
+                .as_ref()
+                .map_or(false, |options| options.commands.contains(&command.command));
+            if !is_supported {
+                return Task::ready(Err(anyhow!(
+                    "language server does not support the command {:?}",
+                    command.command
+                )));
+            }
+            let lang_server = lang_server.clone();
+            cx.spawn(move |this, mut cx| async move {
+                this.update(&mut cx, |this, _| {
+                    this.last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id());
+                })?;
+
+                lang_server
+                    .request::<lsp::request::ExecuteCommand>(lsp::ExecuteCommandParams {
+                        command: command.command,
+                        arguments: command.arguments.unwrap_or_default(),
+                        ..Default::default()
+                    })
+                    .await?;
+
+                this.update(&mut cx, |this, _| {
+                    this.last_workspace_edits_by_language_server
+                        .remove(&lang_server.server_id())
+                        .unwrap_or_default()
+                })
             })
         } else {
             Task::ready(Err(anyhow!("project does not have a remote id")))
@@ -7035,24 +8569,7 @@ impl Project {
         let buffer_id = buffer.remote_id().into();
         let lsp_request = InlayHints { range };
 
-        if self.is_local() {
-            let lsp_request_task = self.request_lsp(
-                buffer_handle.clone(),
-                LanguageServerToQuery::Primary,
-                lsp_request,
-                cx,
-            );
-            cx.spawn(move |_, mut cx| async move {
-                buffer_handle
-                    .update(&mut cx, |buffer, _| {
-                        buffer.wait_for_edits(vec![range_start.timestamp, range_end.timestamp])
-                    })?
-                    .await
-                    .context("waiting for inlay hint request range edits")?;
-                lsp_request_task.await.context("inlay hints LSP request")
-            })
-        } else if let Some(project_id) = self.remote_id() {
-            let client = self.client.clone();
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
             let request = proto::InlayHints {
                 project_id,
                 buffer_id,
@@ -7075,6 +8592,22 @@ impl Project {
                 .await
                 .context("inlay hints proto response conversion")
             })
+        } else if self.is_local() {
+            let lsp_request_task = self.request_lsp(
+                buffer_handle.clone(),
+                LanguageServerToQuery::Primary,
+                lsp_request,
+                cx,
+            );
+            cx.spawn(move |_, mut cx| async move {
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_edits(vec![range_start.timestamp, range_end.timestamp])
+                    })?
+                    .await
+                    .context("waiting for inlay hint request range edits")?;
+                lsp_request_task.await.context("inlay hints LSP request")
+            })
         } else {
             Task::ready(Err(anyhow!("project does not have a remote id")))
         }
@@ -7087,7 +8620,25 @@ impl Project {
         server_id: LanguageServerId,
         cx: &mut ModelContext<Self>,
     ) -> Task<anyhow::Result<InlayHint>> {
-        if self.is_local() {
+        if let Some((client, project_id)) = self.lsp_upstream_client() {
+            let request = proto::ResolveInlayHint {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                language_server_id: server_id.0 as u64,
+                hint: Some(InlayHints::project_to_proto_hint(hint.clone())),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("inlay hints proto request")?;
+                match response.hint {
+                    Some(resolved_hint) => InlayHints::proto_to_project_hint(resolved_hint)
+                        .context("inlay hints proto resolve response conversion"),
+                    None => Ok(hint),
+                }
+            })
+        } else if self.is_local() {
             let buffer = buffer_handle.read(cx);
             let (_, lang_server) = if let Some((adapter, server)) =
                 self.language_server_for_buffer(buffer, server_id, cx)
@@ -7119,25 +8670,6 @@ impl Project {
                 .await?;
                 Ok(resolved_hint)
             })
-        } else if let Some(project_id) = self.remote_id() {
-            let client = self.client.clone();
-            let request = proto::ResolveInlayHint {
-                project_id,
-                buffer_id: buffer_handle.read(cx).remote_id().into(),
-                language_server_id: server_id.0 as u64,
-                hint: Some(InlayHints::project_to_proto_hint(hint.clone())),
-            };
-            cx.spawn(move |_, _| async move {
-                let response = client
-                    .request(request)
-                    .await
-                    .context("inlay hints proto request")?;
-                match response.hint {
-                    Some(resolved_hint) => InlayHints::proto_to_project_hint(resolved_hint)
-                        .context("inlay hints proto resolve response conversion"),
-                    None => Ok(hint),
-                }
-            })
         } else {
             Task::ready(Err(anyhow!("project does not have a remote id")))
         }
@@ -7145,11 +8677,13 @@ impl Project {
 
     #[allow(clippy::type_complexity)]
     pub fn search(
-        &self,
+        &mut self,
         query: SearchQuery,
         cx: &mut ModelContext<Self>,
     ) -> Receiver<SearchResult> {
-        if self.is_local() {
+        if let Some(ssh_session) = self.ssh_session.clone() {
+            self.search_ssh(ssh_session, query, cx)
+        } else if self.is_local() {
             self.search_local(query, cx)
         } else if let Some(project_id) = self.remote_id() {
             let (tx, rx) = smol::channel::unbounded();
@@ -7194,6 +8728,37 @@ impl Project {
         }
     }
 
+    /// Runs a search on the headless server of an SSH project, which sends the matches back in
+    /// batches as it finds them, rather than all at once.
+    fn search_ssh(
+        &mut self,
+        ssh_session: Arc<SshSession>,
+        query: SearchQuery,
+        cx: &mut ModelContext<Self>,
+    ) -> Receiver<SearchResult> {
+        let (tx, rx) = smol::channel::bounded(1024);
+        let search_id = post_inc(&mut self.next_ssh_search_id);
+        self.ssh_searches.insert(search_id, tx.clone());
+
+        let mut request = query.to_proto(0);
+        request.search_id = search_id;
+        let response = ssh_session.request(request);
+        cx.spawn(move |this, mut cx| async move {
+            let response = response.await;
+            this.update(&mut cx, |this, _| this.ssh_searches.remove(&search_id))?;
+            // The server stops searching once the results are no longer received.
+            if tx.is_closed() {
+                return Ok(());
+            }
+            if response?.limit_reached {
+                tx.send(SearchResult::LimitReached).await?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+        rx
+    }
+
     pub fn search_local(
         &self,
         query: SearchQuery,
@@ -7345,6 +8910,14 @@ impl Project {
 
                     chunk_results.push(cx.spawn(|cx| async move {
                         let buffer = buffer.await?;
+                        // Structural queries match the syntax tree, so it has to be up to date.
+                        if query.is_structural() {
+                            let mut parse_status =
+                                buffer.read_with(&cx, |buffer, _| buffer.parse_status())?;
+                            while *parse_status.borrow() != ParseStatus::Idle {
+                                parse_status.changed().await?;
+                            }
+                        }
                         let snapshot = buffer.read_with(&cx, |buffer, _| buffer.snapshot())?;
                         let ranges = cx
                             .background_executor()
@@ -7490,6 +9063,16 @@ impl Project {
         <R::LspRequest as lsp::request::Request>::Result: Send,
         <R::LspRequest as lsp::request::Request>::Params: Send,
     {
+        if let Some((upstream_client, project_id)) = self.lsp_upstream_client() {
+            return self.send_lsp_proto_request(
+                buffer_handle,
+                upstream_client,
+                project_id,
+                request,
+                cx,
+            );
+        }
+
         let buffer = buffer_handle.read(cx);
         if self.is_local() {
             let language_server = match server {
@@ -7573,8 +9156,6 @@ impl Project {
                         .await
                 });
             }
-        } else if let Some(project_id) = self.remote_id() {
-            return self.send_lsp_proto_request(buffer_handle, project_id, request, cx);
         }
 
         Task::ready(Ok(Default::default()))
@@ -7632,11 +9213,11 @@ impl Project {
     fn send_lsp_proto_request<R: LspCommand>(
         &self,
         buffer: Model<Buffer>,
+        rpc: AnyProtoClient,
         project_id: u64,
         request: R,
         cx: &mut ModelContext<'_, Project>,
     ) -> Task<anyhow::Result<<R as LspCommand>::Response>> {
-        let rpc = self.client.clone();
         let message = request.to_proto(project_id, buffer.read(cx));
         cx.spawn(move |this, mut cx| async move {
             // Ensure the project is still alive by the time the task
@@ -7785,7 +9366,12 @@ impl Project {
         let root_name = abs_path.file_name().unwrap().to_string_lossy().to_string();
         let path = abs_path.to_string_lossy().to_string();
         cx.spawn(|this, mut cx| async move {
-            let response = ssh.request(AddWorktree { path: path.clone() }).await?;
+            let response = ssh
+                .request(AddWorktree {
+                    path: path.clone(),
+                    worktree_id: None,
+                })
+                .await?;
             let worktree = cx.update(|cx| {
                 Worktree::remote(
                     0,
@@ -7931,6 +9517,8 @@ impl Project {
             self.last_workspace_edits_by_language_server
                 .remove(&server_id_to_remove);
             self.language_servers.remove(&server_id_to_remove);
+            self.invalidate_semantic_tokens(Some(server_id_to_remove), cx);
+            self.document_links.clear();
             cx.emit(Event::LanguageServerRemoved(server_id_to_remove));
         }
 
@@ -8432,6 +10020,142 @@ impl Project {
         self.buffer_store.read(cx).blame_buffer(buffer, version, cx)
     }
 
+    /// Returns the staged, unstaged and untracked files of a worktree's git repository.
+    pub fn git_staging_status(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        cx: &AppContext,
+    ) -> Task<Result<GitStagingStatus>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store
+            .read(cx)
+            .git_staging_status(&worktree, work_directory_id, cx)
+    }
+
+    /// Stages, unstages or commits changes in a worktree's git repository.
+    pub fn run_git_operation(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        operation: GitOperation,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store
+            .read(cx)
+            .run_git_operation(&worktree, work_directory_id, operation, cx)
+    }
+
+    /// Fetches, pulls or pushes a worktree's git repository. Prompts for credentials are sent to
+    /// `askpass`, also when the operation runs on the host of a remote project.
+    pub fn run_git_remote_operation(
+        &mut self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        operation: GitRemoteOperation,
+        askpass: Option<AskPassDelegate>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        let task = self.worktree_store.update(cx, |worktree_store, cx| {
+            worktree_store.run_git_remote_operation(
+                &worktree,
+                work_directory_id,
+                operation,
+                askpass,
+                cx,
+            )
+        });
+        let id = post_inc(&mut self.next_git_remote_operation_id);
+        self.pending_git_remote_operations.insert(id, operation);
+        cx.notify();
+        cx.spawn(|this, mut cx| async move {
+            let result = task.await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_git_remote_operations.remove(&id);
+                cx.notify();
+            })
+            .ok();
+            result
+        })
+    }
+
+    /// The fetches, pulls and pushes that are currently running, in the order they were started.
+    pub fn pending_git_remote_operations(&self) -> impl Iterator<Item = GitRemoteOperation> + '_ {
+        self.pending_git_remote_operations.values().copied()
+    }
+
+    pub fn git_stashes(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<StashEntry>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store
+            .read(cx)
+            .git_stashes(&worktree, work_directory_id, cx)
+    }
+
+    pub fn git_log(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        query: LogQuery,
+        cx: &AppContext,
+    ) -> Task<Result<GitLog>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store
+            .read(cx)
+            .git_log(&worktree, work_directory_id, query, cx)
+    }
+
+    pub fn git_commit_diff(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store
+            .read(cx)
+            .git_commit_diff(&worktree, work_directory_id, sha, cx)
+    }
+
+    pub fn git_revision_diff(
+        &self,
+        worktree_id: WorktreeId,
+        work_directory_id: ProjectEntryId,
+        revision: String,
+        merge_base: bool,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        let Some(worktree) = self.worktree_for_id(worktree_id, cx) else {
+            return Task::ready(Err(anyhow!("no worktree with id {worktree_id:?}")));
+        };
+        self.worktree_store.read(cx).git_revision_diff(
+            &worktree,
+            work_directory_id,
+            revision,
+            merge_base,
+            cx,
+        )
+    }
+
     // RPC message handlers
 
     async fn handle_multi_lsp_query(
@@ -8996,6 +10720,7 @@ impl Project {
                             line_ending: language::proto::serialize_line_ending(
                                 buffer.line_ending(),
                             ) as i32,
+                            encoding: Some(language::proto::serialize_encoding(buffer.encoding())),
                         })
                         .log_err();
 
@@ -9151,13 +10876,97 @@ impl Project {
             }
         }
 
-        Ok(proto::ResolveCompletionDocumentationResponse {
-            documentation,
-            documentation_is_markdown,
-            old_start,
-            old_end,
-            new_text,
-        })
+        Ok(proto::ResolveCompletionDocumentationResponse {
+            documentation,
+            documentation_is_markdown,
+            old_start,
+            old_end,
+            new_text,
+        })
+    }
+
+    async fn handle_create_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CreateProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+        let path = PathBuf::from(envelope.payload.path.clone());
+        let is_directory = envelope.payload.is_directory;
+        this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(worktree_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let operation = FileOperation::create(worktree.read(cx), &path, is_directory);
+            let worktree_store = this.worktree_store.clone();
+            anyhow::Ok(this.perform_file_operation(
+                &worktree,
+                operation,
+                false,
+                move |_, cx| {
+                    cx.spawn(|_, cx| {
+                        WorktreeStore::handle_create_project_entry(worktree_store, envelope, cx)
+                    })
+                },
+                cx,
+            ))
+        })??
+        .await
+    }
+
+    async fn handle_rename_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RenameProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        let new_path = PathBuf::from(envelope.payload.new_path.clone());
+        this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let operation = FileOperation::rename(worktree.read(cx), entry_id, &new_path);
+            let worktree_store = this.worktree_store.clone();
+            anyhow::Ok(this.perform_file_operation(
+                &worktree,
+                operation,
+                false,
+                move |_, cx| {
+                    cx.spawn(|_, cx| {
+                        WorktreeStore::handle_rename_project_entry(worktree_store, envelope, cx)
+                    })
+                },
+                cx,
+            ))
+        })??
+        .await
+    }
+
+    async fn handle_delete_project_entry(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DeleteProjectEntry>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ProjectEntryResponse> {
+        let entry_id = ProjectEntryId::from_proto(envelope.payload.entry_id);
+        this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_entry(entry_id, cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let operation = FileOperation::delete(worktree.read(cx), entry_id);
+            let worktree_store = this.worktree_store.clone();
+            anyhow::Ok(this.perform_file_operation(
+                &worktree,
+                operation,
+                false,
+                move |_, cx| {
+                    cx.spawn(|_, cx| {
+                        WorktreeStore::handle_delete_project_entry(worktree_store, envelope, cx)
+                    })
+                },
+                cx,
+            ))
+        })??
+        .await
     }
 
     async fn handle_apply_code_action(
@@ -9187,6 +10996,79 @@ impl Project {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let resolve_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_code_lens(buffer, lens, cx))
+        })??;
+
+        let lens = resolve_code_lens.await?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&lens)),
+        })
+    }
+
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = Self::deserialize_document_link(
+            envelope
+                .payload
+                .link
+                .ok_or_else(|| anyhow!("invalid document link"))?,
+        )?;
+        let resolve_document_link = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.resolve_document_link(buffer, link, cx))
+        })??;
+
+        let link = resolve_document_link.await?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(Self::serialize_document_link(&link)),
+        })
+    }
+
+    async fn handle_execute_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ExecuteCodeLens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ExecuteCodeLensResponse> {
+        let sender_id = envelope.original_sender_id()?;
+        let lens = Self::deserialize_code_lens(
+            envelope
+                .payload
+                .lens
+                .ok_or_else(|| anyhow!("invalid code lens"))?,
+        )?;
+        let execute_code_lens = this.update(&mut cx, |this, cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            let buffer = this.buffer_store.read(cx).get_existing(buffer_id)?;
+            anyhow::Ok(this.execute_code_lens(buffer, lens, cx))
+        })??;
+
+        let project_transaction = execute_code_lens.await?;
+        let project_transaction = this.update(&mut cx, |this, cx| {
+            this.serialize_project_transaction_for_peer(project_transaction, sender_id, cx)
+        })?;
+        Ok(proto::ExecuteCodeLensResponse {
+            transaction: Some(project_transaction),
+        })
+    }
+
     async fn handle_on_type_formatting(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OnTypeFormatting>,
@@ -9356,6 +11238,8 @@ impl Project {
             .await
             .context("receiving task templates")?
             .into_iter()
+            // Debug sessions can only be started on the host.
+            .filter(|(_, template)| template.debug.is_none())
             .map(|(kind, template)| {
                 let kind = Some(match kind {
                     TaskSourceKind::UserInput => proto::task_source_kind::Kind::UserInput(
@@ -9526,6 +11410,50 @@ impl Project {
         Ok(proto::Ack {})
     }
 
+    async fn handle_refresh_semantic_tokens(
+        this: Model<Self>,
+        _: TypedEnvelope<proto::RefreshSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            this.invalidate_semantic_tokens(None, cx);
+        })?;
+        Ok(proto::Ack {})
+    }
+
+    async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let sender_id = envelope.original_sender_id()?;
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.read(cx).get_existing(buffer_id)
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+
+        let tokens = this
+            .update(&mut cx, |project, cx| project.semantic_tokens(&buffer, cx))?
+            .await
+            .context("semantic tokens fetch")?;
+
+        this.update(&mut cx, |project, cx| {
+            GetSemanticTokensFull::response_to_proto(
+                SemanticTokens::clone(&tokens),
+                project,
+                sender_id,
+                &buffer.read(cx).version(),
+                cx,
+            )
+        })
+    }
+
     async fn handle_lsp_command<T: LspCommand>(
         this: Model<Self>,
         envelope: TypedEnvelope<T::ProtoRequest>,
@@ -9623,6 +11551,51 @@ impl Project {
         .await
     }
 
+    async fn handle_search_project_results(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SearchProjectResults>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let search_id = envelope.payload.search_id;
+        let results_tx = this
+            .update(&mut cx, |this, _| {
+                this.ssh_searches.get(&search_id).cloned()
+            })?
+            .ok_or_else(|| anyhow!("search {search_id} is not running"))?;
+
+        let mut ranges_by_buffer = Vec::<(BufferId, Vec<Range<Anchor>>)>::new();
+        for location in envelope.payload.locations {
+            let buffer_id = BufferId::new(location.buffer_id)?;
+            let start = location
+                .start
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing target start"))?;
+            let end = location
+                .end
+                .and_then(deserialize_anchor)
+                .ok_or_else(|| anyhow!("missing target end"))?;
+            match ranges_by_buffer.last_mut() {
+                Some((last_buffer_id, ranges)) if *last_buffer_id == buffer_id => {
+                    ranges.push(start..end)
+                }
+                _ => ranges_by_buffer.push((buffer_id, vec![start..end])),
+            }
+        }
+
+        for (buffer_id, ranges) in ranges_by_buffer {
+            let buffer = this
+                .update(&mut cx, |this, cx| {
+                    this.wait_for_remote_buffer(buffer_id, cx)
+                })?
+                .await?;
+            results_tx
+                .send(SearchResult::Buffer { buffer, ranges })
+                .await
+                .map_err(|_| anyhow!("search {search_id} was cancelled"))?;
+        }
+        Ok(proto::Ack {})
+    }
+
     async fn handle_open_buffer_for_symbol(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::OpenBufferForSymbol>,
@@ -9818,7 +11791,29 @@ impl Project {
         cx: &mut AppContext,
     ) -> BufferId {
         let buffer_id = buffer.read(cx).remote_id();
-        if let ProjectClientState::Shared { updates_tx, .. } = &self.client_state {
+        if let Some(downstream_ssh_session) = &self.downstream_ssh_session {
+            if self
+                .shared_buffers
+                .entry(peer_id)
+                .or_default()
+                .insert(buffer_id)
+            {
+                let buffer_store = self.buffer_store.clone();
+                let downstream_ssh_session = downstream_ssh_session.clone();
+                cx.spawn(|mut cx| async move {
+                    BufferStore::create_buffer_for_peer(
+                        buffer_store,
+                        peer_id,
+                        buffer_id,
+                        0,
+                        downstream_ssh_session,
+                        &mut cx,
+                    )
+                    .await
+                })
+                .detach_and_log_err(cx);
+            }
+        } else if let ProjectClientState::Shared { updates_tx, .. } = &self.client_state {
             updates_tx
                 .unbounded_send(LocalProjectUpdate::CreateBufferForPeer { peer_id, buffer_id })
                 .ok();
@@ -10059,6 +12054,128 @@ impl Project {
         })
     }
 
+    fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
+    fn serialize_document_link(link: &DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            server_id: link.server_id.0 as u64,
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+        }
+    }
+
+    fn deserialize_document_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_link = serde_json::from_slice(&link.lsp_link)?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId(link.server_id as usize),
+            range: start..end,
+            lsp_link,
+        })
+    }
+
+    fn serialize_document_color(color: &DocumentColor) -> proto::DocumentColor {
+        proto::DocumentColor {
+            start: Some(serialize_anchor(&color.range.start)),
+            end: Some(serialize_anchor(&color.range.end)),
+            red: color.color.red,
+            green: color.color.green,
+            blue: color.color.blue,
+            alpha: color.color.alpha,
+        }
+    }
+
+    fn deserialize_document_color(color: proto::DocumentColor) -> Result<DocumentColor> {
+        let start = color
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = color
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(DocumentColor {
+            range: start..end,
+            color: lsp::Color {
+                red: color.red,
+                green: color.green,
+                blue: color.blue,
+                alpha: color.alpha,
+            },
+        })
+    }
+
+    fn serialize_color_presentation(presentation: &ColorPresentation) -> proto::ColorPresentation {
+        proto::ColorPresentation {
+            label: presentation.label.clone(),
+            edits: presentation
+                .edits
+                .iter()
+                .map(|(range, new_text)| proto::TextEdit {
+                    start: Some(serialize_anchor(&range.start)),
+                    end: Some(serialize_anchor(&range.end)),
+                    new_text: new_text.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn deserialize_color_presentation(
+        presentation: proto::ColorPresentation,
+    ) -> Result<ColorPresentation> {
+        let edits = presentation
+            .edits
+            .into_iter()
+            .map(|edit| {
+                let start = edit
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid start"))?;
+                let end = edit
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("invalid end"))?;
+                Ok((start..end, edit.new_text))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ColorPresentation {
+            label: presentation.label,
+            edits,
+        })
+    }
+
     #[allow(clippy::type_complexity)]
     fn edits_from_lsp(
         &mut self,
@@ -10298,7 +12415,36 @@ impl Project {
         location: Location,
         cx: &mut ModelContext<'_, Project>,
     ) -> Task<Option<TaskContext>> {
-        if self.is_local() {
+        if let Some((upstream_client, project_id)) = self.task_upstream_client(cx) {
+            let task_context = upstream_client.request(proto::TaskContextForLocation {
+                project_id,
+                location: Some(proto::Location {
+                    buffer_id: location.buffer.read(cx).remote_id().into(),
+                    start: Some(serialize_anchor(&location.range.start)),
+                    end: Some(serialize_anchor(&location.range.end)),
+                }),
+            });
+            cx.background_executor().spawn(async move {
+                let task_context = task_context.await.log_err()?;
+                Some(TaskContext {
+                    project_env: task_context.project_env.into_iter().collect(),
+                    cwd: task_context.cwd.map(PathBuf::from),
+                    task_variables: task_context
+                        .task_variables
+                        .into_iter()
+                        .filter_map(
+                            |(variable_name, variable_value)| match variable_name.parse() {
+                                Ok(variable_name) => Some((variable_name, variable_value)),
+                                Err(()) => {
+                                    log::error!("Unknown variable name: {variable_name}");
+                                    None
+                                }
+                            },
+                        )
+                        .collect(),
+                })
+            })
+        } else if self.is_local() {
             let (worktree_id, cwd) = if let Some(worktree) = self.task_worktree(cx) {
                 (
                     Some(worktree.read(cx).id()),
@@ -10338,38 +12484,6 @@ impl Project {
                     task_variables,
                 })
             })
-        } else if let Some(project_id) = self
-            .remote_id()
-            .filter(|_| self.ssh_connection_string(cx).is_some())
-        {
-            let task_context = self.client().request(proto::TaskContextForLocation {
-                project_id,
-                location: Some(proto::Location {
-                    buffer_id: location.buffer.read(cx).remote_id().into(),
-                    start: Some(serialize_anchor(&location.range.start)),
-                    end: Some(serialize_anchor(&location.range.end)),
-                }),
-            });
-            cx.background_executor().spawn(async move {
-                let task_context = task_context.await.log_err()?;
-                Some(TaskContext {
-                    project_env: task_context.project_env.into_iter().collect(),
-                    cwd: task_context.cwd.map(PathBuf::from),
-                    task_variables: task_context
-                        .task_variables
-                        .into_iter()
-                        .filter_map(
-                            |(variable_name, variable_value)| match variable_name.parse() {
-                                Ok(variable_name) => Some((variable_name, variable_value)),
-                                Err(()) => {
-                                    log::error!("Unknown variable name: {variable_name}");
-                                    None
-                                }
-                            },
-                        )
-                        .collect(),
-                })
-            })
         } else {
             Task::ready(None)
         }
@@ -10425,6 +12539,7 @@ impl Project {
         location: Option<Location>,
         cx: &mut ModelContext<Self>,
     ) -> Task<Result<Vec<(TaskSourceKind, TaskTemplate)>>> {
+        let remote_templates = self.query_remote_task_templates(worktree, location.as_ref(), cx);
         if self.is_local() {
             let (file, language) = location
                 .map(|location| {
@@ -10435,33 +12550,55 @@ impl Project {
                     )
                 })
                 .unwrap_or_default();
-            Task::ready(Ok(self
+            let local_templates = self
                 .task_inventory()
                 .read(cx)
-                .list_tasks(file, language, worktree, cx)))
-        } else if let Some(project_id) = self
-            .remote_id()
-            .filter(|_| self.ssh_connection_string(cx).is_some())
-        {
-            let remote_templates =
-                self.query_remote_task_templates(project_id, worktree, location.as_ref(), cx);
+                .list_tasks(file, language, worktree, cx);
+            match remote_templates {
+                // The remote machine of an SSH project lists the tasks of its worktrees and
+                // languages, while the global tasks are listed here.
+                Some(remote_templates) => cx.background_executor().spawn(async move {
+                    let mut templates = remote_templates.await?;
+                    templates.extend(
+                        local_templates
+                            .into_iter()
+                            .filter(|(kind, _)| !matches!(kind, TaskSourceKind::Language { .. })),
+                    );
+                    Ok(templates)
+                }),
+                None => Task::ready(Ok(local_templates)),
+            }
+        } else if let Some(remote_templates) = remote_templates {
             cx.background_executor().spawn(remote_templates)
         } else {
             Task::ready(Ok(Vec::new()))
         }
     }
 
+    /// The client that tasks are resolved by, along with the id of the project on the other end,
+    /// when they run on another machine: the remote machine of an SSH project, or the dev server
+    /// of a project that has been joined over SSH.
+    fn task_upstream_client(&self, cx: &ModelContext<Self>) -> Option<(AnyProtoClient, u64)> {
+        if let Some(ssh_session) = &self.ssh_session {
+            Some((ssh_session.clone().into(), 0))
+        } else if self.ssh_connection_string(cx).is_some() {
+            Some((self.client.clone().into(), self.remote_id()?))
+        } else {
+            None
+        }
+    }
+
+    /// Lists the task templates of the machine that tasks run on, if it isn't this one.
     pub fn query_remote_task_templates(
         &self,
-        project_id: u64,
         worktree: Option<WorktreeId>,
         location: Option<&Location>,
-        cx: &AppContext,
-    ) -> Task<Result<Vec<(TaskSourceKind, TaskTemplate)>>> {
-        let client = self.client();
+        cx: &ModelContext<Self>,
+    ) -> Option<Task<Result<Vec<(TaskSourceKind, TaskTemplate)>>>> {
+        let (upstream_client, project_id) = self.task_upstream_client(cx)?;
         let location = location.map(|location| serialize_location(location, cx));
-        cx.spawn(|_| async move {
-            let response = client
+        Some(cx.background_executor().spawn(async move {
+            let response = upstream_client
                 .request(proto::TaskTemplates {
                     project_id,
                     worktree_id: worktree.map(|id| id.to_proto()),
@@ -10535,11 +12672,12 @@ impl Project {
                         hide,
                         shell,
                         tags: proto_template.tags,
+                        debug: None,
                     };
                     Some((task_source_kind, task_template))
                 })
                 .collect())
-        })
+        }))
     }
 
     fn task_worktree(&self, cx: &AppContext) -> Option<Model<Worktree>> {
@@ -11225,6 +13363,15 @@ impl Completion {
     }
 }
 
+fn diagnostic_options(capabilities: &lsp::ServerCapabilities) -> Option<&lsp::DiagnosticOptions> {
+    match capabilities.diagnostic_provider.as_ref()? {
+        lsp::DiagnosticServerCapabilities::Options(options) => Some(options),
+        lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+            Some(&options.diagnostic_options)
+        }
+    }
+}
+
 fn include_text(server: &lsp::LanguageServer) -> Option<bool> {
     match server.capabilities().text_document_sync.as_ref()? {
         lsp::TextDocumentSyncCapability::Kind(kind) => match kind {
@@ -5,7 +5,7 @@ use gpui::{AppContext, SemanticVersion, UpdateGlobal};
 use http_client::Url;
 use language::{
     language_settings::{AllLanguageSettings, LanguageSettingsContent},
-    tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
+    tree_sitter_rust, tree_sitter_typescript, Diagnostic, Encoding, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
 use lsp::NumberOrString;
@@ -2013,6 +2013,193 @@ async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppC
     });
 }
 
+#[gpui::test]
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "let a = 1;",
+            "b.rs": "let b = 2;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions {
+                        workspace_diagnostics: true,
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let previous_result_ids = Arc::new(Mutex::new(Vec::new()));
+    fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+        let previous_result_ids = previous_result_ids.clone();
+        move |params, _| {
+            previous_result_ids
+                .lock()
+                .push(params.previous_result_id.clone());
+            async move {
+                let report = if params.previous_result_id.is_some() {
+                    lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport {
+                                    result_id: "1".into(),
+                                },
+                        },
+                    )
+                } else {
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some("1".into()),
+                            items: vec![lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 4),
+                                    lsp::Position::new(0, 5),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                message: "error 1".into(),
+                                ..Default::default()
+                            }],
+                        },
+                    })
+                };
+                Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::WorkspaceDiagnosticRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.previous_result_ids,
+                vec![lsp::PreviousResultId {
+                    uri: Url::from_file_path("/dir/a.rs").unwrap(),
+                    value: "1".into(),
+                }]
+            );
+            Ok(lsp::WorkspaceDiagnosticReportResult::Report(
+                lsp::WorkspaceDiagnosticReport {
+                    items: vec![lsp::WorkspaceDocumentDiagnosticReport::Full(
+                        lsp::WorkspaceFullDocumentDiagnosticReport {
+                            uri: Url::from_file_path("/dir/b.rs").unwrap(),
+                            version: None,
+                            full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                                result_id: Some("2".into()),
+                                items: vec![lsp::Diagnostic {
+                                    range: lsp::Range::new(
+                                        lsp::Position::new(0, 4),
+                                        lsp::Position::new(0, 5),
+                                    ),
+                                    severity: Some(lsp::DiagnosticSeverity::WARNING),
+                                    message: "warning 1".into(),
+                                    ..Default::default()
+                                }],
+                            },
+                        },
+                    )],
+                },
+            ))
+        },
+    );
+
+    cx.executor().advance_clock(Duration::from_millis(250));
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("let ".to_string(), None),
+                ("a".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" = 1;".to_string(), None),
+            ]
+        );
+    });
+
+    // Edits pull the diagnostics again, which stay the same when the server reports them
+    // as unchanged.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    cx.executor().advance_clock(Duration::from_millis(250));
+    cx.executor().run_until_parked();
+    assert_eq!(*previous_result_ids.lock(), [None, Some("1".to_string())]);
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("\nlet ".to_string(), None),
+                ("a".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" = 1;".to_string(), None),
+            ]
+        );
+    });
+
+    // Refreshing pulls the diagnostics of the whole workspace.
+    fake_server
+        .server
+        .request::<lsp::request::WorkspaceDiagnosticRefresh>(())
+        .await
+        .unwrap();
+    cx.executor().advance_clock(Duration::from_millis(250));
+    cx.executor().run_until_parked();
+    project.update(cx, |project, cx| {
+        let mut summaries = project.diagnostic_summaries(false, cx).collect::<Vec<_>>();
+        summaries.sort_by_key(|(path, _, _)| path.path.clone());
+        assert_eq!(
+            summaries,
+            vec![
+                (
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new("a.rs")),
+                    },
+                    fake_server.server.server_id(),
+                    DiagnosticSummary {
+                        error_count: 1,
+                        warning_count: 0,
+                    }
+                ),
+                (
+                    ProjectPath {
+                        worktree_id,
+                        path: Arc::from(Path::new("b.rs")),
+                    },
+                    fake_server.server.server_id(),
+                    DiagnosticSummary {
+                        error_count: 0,
+                        warning_count: 1,
+                    }
+                ),
+            ]
+        );
+    });
+}
+
 #[gpui::test]
 async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2417,77 +2604,1025 @@ async fn test_definition(cx: &mut gpui::TestAppContext) {
         language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
 
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        let params = params.text_document_position_params;
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(params.position, lsp::Position::new(0, 22));
+
+        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+            lsp::Location::new(
+                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            ),
+        )))
+    });
+
+    let mut definitions = project
+        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+        .await
+        .unwrap();
+
+    // Assert no new language server started
+    cx.executor().run_until_parked();
+    assert!(fake_servers.try_next().is_err());
+
+    assert_eq!(definitions.len(), 1);
+    let definition = definitions.pop().unwrap();
+    cx.update(|cx| {
+        let target_buffer = definition.target.buffer.read(cx);
+        assert_eq!(
+            target_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/dir/a.rs"),
+        );
+        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+        assert_eq!(
+            list_worktrees(&project, cx),
+            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
+        );
+
+        drop(definition);
+    });
+    cx.update(|cx| {
+        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
+    });
+
+    fn list_worktrees<'a>(
+        project: &'a Model<Project>,
+        cx: &'a AppContext,
+    ) -> Vec<(&'a Path, bool)> {
+        project
+            .read(cx)
+            .worktrees(cx)
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (
+                    worktree.as_local().unwrap().abs_path().as_ref(),
+                    worktree.is_visible(),
+                )
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[gpui::test]
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() { b() }\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    fn lsp_item(name: &str, row: u32) -> lsp::CallHierarchyItem {
+        lsp::CallHierarchyItem {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 9)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, 3),
+                lsp::Position::new(row, 4),
+            ),
+            data: Some(json!({ "name": name })),
+        }
+    }
+
+    fake_server.handle_request::<lsp::request::CallHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 3)
+            );
+            Ok(Some(vec![lsp_item("b", 1)]))
+        },
+    );
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, Point::new(1, 3), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "b");
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            items[0].selection_range.to_point(buffer),
+            Point::new(1, 3)..Point::new(1, 4)
+        );
+    });
+
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        |params, _| async move {
+            // The item is sent back to the server unchanged.
+            assert_eq!(params.item.data, Some(json!({ "name": "b" })));
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("a", 0),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 9),
+                    lsp::Position::new(0, 10),
+                )],
+            }]))
+        },
+    );
+    let calls = project
+        .update(cx, |project, cx| project.incoming_calls(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].item.lsp_item.name, "a");
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            calls[0]
+                .call_sites
+                .iter()
+                .map(|location| location.range.to_point(buffer))
+                .collect::<Vec<_>>(),
+            vec![Point::new(0, 9)..Point::new(0, 10)]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "trait Shape {}\nstruct Circle;\nimpl Shape for Circle {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    fn lsp_item(
+        name: &str,
+        kind: lsp::SymbolKind,
+        row: u32,
+        column: u32,
+    ) -> lsp::TypeHierarchyItem {
+        lsp::TypeHierarchyItem {
+            name: name.to_string(),
+            kind,
+            tags: None,
+            detail: None,
+            uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+            range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 14)),
+            selection_range: lsp::Range::new(
+                lsp::Position::new(row, column),
+                lsp::Position::new(row, column + name.len() as u32),
+            ),
+            data: Some(json!({ "name": name })),
+        }
+    }
+
+    fake_server.handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 7)
+            );
+            Ok(Some(vec![lsp_item(
+                "Circle",
+                lsp::SymbolKind::STRUCT,
+                1,
+                7,
+            )]))
+        },
+    );
+    let items = project
+        .update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, Point::new(1, 7), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "Circle");
+
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        |params, _| async move {
+            // The item is sent back to the server unchanged.
+            assert_eq!(params.item.data, Some(json!({ "name": "Circle" })));
+            Ok(Some(vec![lsp_item(
+                "Shape",
+                lsp::SymbolKind::INTERFACE,
+                0,
+                6,
+            )]))
+        },
+    );
+    let supertypes = project
+        .update(cx, |project, cx| project.supertypes(&items[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].lsp_item.name, "Shape");
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            supertypes[0].selection_range.to_point(buffer),
+            Point::new(0, 6)..Point::new(0, 11)
+        );
+    });
+
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(
+        |params, _| async move {
+            assert_eq!(params.item.data, Some(json!({ "name": "Shape" })));
+            Ok(Some(vec![lsp_item(
+                "Circle",
+                lsp::SymbolKind::STRUCT,
+                1,
+                7,
+            )]))
+        },
+    );
+    let subtypes = project
+        .update(cx, |project, cx| project.subtypes(&supertypes[0], cx))
+        .await
+        .unwrap();
+    assert_eq!(subtypes.len(), 1);
+    assert_eq!(subtypes[0].lsp_item.name, "Circle");
+}
+
+#[gpui::test]
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "use a;\nuse b;\nfn c() {\n    d()\n}",
+            "b.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    language_registry.add(typescript_lang());
+    let mut fake_rust_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let mut fake_typescript_servers =
+        language_registry.register_fake_lsp_adapter("TypeScript", FakeLspAdapter::default());
+
+    let rust_buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let typescript_buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/b.ts", cx))
+        .await
+        .unwrap();
+    let fake_rust_server = fake_rust_servers.next().await.unwrap();
+    fake_typescript_servers.next().await.unwrap();
+
+    fake_rust_server.handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::FoldingRange {
+                start_line: 2,
+                end_line: 3,
+                ..Default::default()
+            },
+            lsp::FoldingRange {
+                start_line: 0,
+                end_line: 1,
+                kind: Some(lsp::FoldingRangeKind::Imports),
+                ..Default::default()
+            },
+            // Single line ranges cannot be folded.
+            lsp::FoldingRange {
+                start_line: 3,
+                end_line: 3,
+                ..Default::default()
+            },
+        ]))
+    });
+    let ranges = project
+        .update(cx, |project, cx| project.folding_ranges(&rust_buffer, cx))
+        .await
+        .unwrap()
+        .unwrap();
+    rust_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            ranges
+                .iter()
+                .map(|range| (range.range.to_point(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Point::new(0, 6)..Point::new(1, 6),
+                    Some(lsp::FoldingRangeKind::Imports)
+                ),
+                (Point::new(2, 8)..Point::new(3, 7), None),
+            ]
+        );
+    });
+
+    // Servers without folding range support fall back to indentation based folding.
+    let ranges = project
+        .update(cx, |project, cx| {
+            project.folding_ranges(&typescript_buffer, cx)
+        })
+        .await
+        .unwrap();
+    assert!(ranges.is_none());
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "fn a() {}\n\nfn b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                execute_command_provider: Some(lsp::ExecuteCommandOptions {
+                    commands: vec!["_the/run".into()],
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    fake_server.handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 4)),
+                command: Some(lsp::Command {
+                    title: "Run".into(),
+                    command: "_the/run".into(),
+                    arguments: Some(vec![json!("a")]),
+                }),
+                data: None,
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 3), lsp::Position::new(2, 4)),
+                command: None,
+                data: Some(json!({ "references": 1 })),
+            },
+            lsp::CodeLens {
+                range: lsp::Range::new(lsp::Position::new(2, 3), lsp::Position::new(2, 4)),
+                command: None,
+                data: None,
+            },
+        ]))
+    });
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|mut lens, _| async move {
+        if lens.data.is_some() {
+            lens.command = Some(lsp::Command {
+                title: "1 reference".into(),
+                command: "_the/references".into(),
+                arguments: None,
+            });
+        }
+        Ok(lens)
+    });
+
+    // Lenses are only resolved on demand.
+    let lenses = project
+        .update(cx, |project, cx| project.code_lens(&buffer, cx))
+        .await
+        .unwrap();
+    let titles = |lenses: &[CodeLens]| {
+        lenses
+            .iter()
+            .map(|lens| Some(lens.lsp_lens.command.as_ref()?.title.clone()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(titles(&lenses), [Some("Run".into()), None, None]);
+
+    let lenses = future::join_all(project.update(cx, |project, cx| {
+        lenses
+            .into_iter()
+            .map(|lens| project.resolve_code_lens(buffer.clone(), lens, cx))
+            .collect::<Vec<_>>()
+    }))
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>>>()
+    .unwrap();
+    assert_eq!(
+        titles(&lenses),
+        [Some("Run".into()), Some("1 reference".into()), None]
+    );
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            lenses[1].range.to_point(buffer),
+            Point::new(2, 3)..Point::new(2, 4)
+        );
+    });
+
+    // Clicking a lens executes its command on the language server.
+    let execute = project.update(cx, |project, cx| {
+        project.execute_code_lens(buffer.clone(), lenses[0].clone(), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "_the/run");
+            assert_eq!(params.arguments, vec![json!("a")]);
+            Ok(Some(json!(null)))
+        })
+        .next()
+        .await;
+    let transaction = execute.await.unwrap();
+    assert!(transaction.0.is_empty());
+
+    // Commands the server did not register are not sent to it.
+    let execute = project.update(cx, |project, cx| {
+        project.execute_code_lens(buffer.clone(), lenses[1].clone(), cx)
+    });
+    assert!(execute.await.is_err());
+}
+
+#[gpui::test]
+async fn test_document_links_and_colors(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "// see b.rs\nconst RED: &str = \"#ff0000\";",
+            "b.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let link_requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::DocumentLinkRequest, _, _>({
+        let link_requests = link_requests.clone();
+        move |_, _| {
+            link_requests.fetch_add(1, SeqCst);
+            async move {
+                Ok(Some(vec![
+                    lsp::DocumentLink {
+                        range: lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 11)),
+                        target: None,
+                        tooltip: None,
+                        data: Some(json!("b.rs")),
+                    },
+                    lsp::DocumentLink {
+                        range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+                        target: Some(lsp::Url::parse("https://zed.dev").unwrap()),
+                        tooltip: None,
+                        data: None,
+                    },
+                ]))
+            }
+        }
+    });
+    fake_server.handle_request::<lsp::request::DocumentLinkResolve, _, _>(
+        |mut link, _| async move {
+            if link.data.is_some() {
+                link.target = Some(lsp::Url::from_file_path("/dir/b.rs").unwrap());
+                link.tooltip = Some("Open b.rs".into());
+            }
+            Ok(link)
+        },
+    );
+
+    // Targets are left unresolved until the link is followed.
+    let links = project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            links
+                .iter()
+                .map(|link| (
+                    link.range.to_point(buffer),
+                    link.lsp_link.target.as_ref().map(|target| target.as_str())
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (Point::new(0, 7)..Point::new(0, 11), None),
+                (Point::new(0, 3)..Point::new(0, 6), Some("https://zed.dev/")),
+            ]
+        );
+    });
+
+    let link = project
+        .update(cx, |project, cx| {
+            project.resolve_document_link(buffer.clone(), links[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        link.lsp_link.target.as_ref().map(|target| target.path()),
+        Some("/dir/b.rs")
+    );
+    assert_eq!(link.lsp_link.tooltip.as_deref(), Some("Open b.rs"));
+
+    // Links are cached until the buffer is edited.
+    project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(link_requests.load(SeqCst), 1);
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    project
+        .update(cx, |project, cx| project.document_links(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(link_requests.load(SeqCst), 2);
+    buffer.update(cx, |buffer, cx| buffer.undo(cx));
+
+    let red = lsp::Color {
+        red: 1.,
+        green: 0.,
+        blue: 0.,
+        alpha: 1.,
+    };
+    fake_server.handle_request::<lsp::request::DocumentColor, _, _>(move |_, _| async move {
+        Ok(vec![lsp::ColorInformation {
+            range: lsp::Range::new(lsp::Position::new(1, 19), lsp::Position::new(1, 26)),
+            color: red,
+        }])
+    });
+    let colors = project
+        .update(cx, |project, cx| project.document_colors(&buffer, cx))
+        .await
+        .unwrap();
+    assert_eq!(colors.len(), 1);
+    assert_eq!(colors[0].color, red);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            colors[0].range.to_point(buffer),
+            Point::new(1, 19)..Point::new(1, 26)
+        );
+    });
+
+    // Presentations without an explicit edit replace the color's text with their label.
+    fake_server.handle_request::<lsp::request::ColorPresentationRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(
+                params.range,
+                lsp::Range::new(lsp::Position::new(1, 19), lsp::Position::new(1, 26))
+            );
+            Ok(vec![
+                lsp::ColorPresentation {
+                    label: "#f00".into(),
+                    text_edit: None,
+                    additional_text_edits: None,
+                },
+                lsp::ColorPresentation {
+                    label: "rgb(255, 0, 0)".into(),
+                    text_edit: Some(lsp::TextEdit {
+                        range: lsp::Range::new(
+                            lsp::Position::new(1, 18),
+                            lsp::Position::new(1, 27),
+                        ),
+                        new_text: "rgb(255, 0, 0)".into(),
+                    }),
+                    additional_text_edits: None,
+                },
+            ])
+        },
+    );
+    let presentations = project
+        .update(cx, |project, cx| {
+            project.color_presentations(&buffer, colors[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            presentations
+                .iter()
+                .map(|presentation| (
+                    presentation.label.as_str(),
+                    presentation
+                        .edits
+                        .iter()
+                        .map(|(range, new_text)| (range.to_point(buffer), new_text.as_str()))
+                        .collect::<Vec<_>>()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("#f00", vec![(Point::new(1, 19)..Point::new(1, 26), "#f00")]),
+                (
+                    "rgb(255, 0, 0)",
+                    vec![(Point::new(1, 18)..Point::new(1, 27), "rgb(255, 0, 0)")]
+                ),
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_debug_session(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        adapters::DapRegistry,
+        events::{Initialized, Stopped, Terminated},
+        requests::{ConfigurationDone, Launch, SetBreakpoints, StackTrace, Threads},
+        Capabilities, SetBreakpointsResponse, StackFrame, StackTraceResponse, StoppedEventBody,
+        Thread, ThreadsResponse,
+    };
+    use dap_store::DebugSessionStatus;
+    use task::{DebugAdapterConfig, DebugRequestType};
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "fn main() {\n    let a = 1;\n    let b = 2;\n}",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let launch_args = Arc::new(Mutex::new(Vec::new()));
+    let sent_breakpoints = Arc::new(Mutex::new(Vec::new()));
+    let mut fake_adapters = cx.update(|cx| {
+        let launch_args = launch_args.clone();
+        let sent_breakpoints = sent_breakpoints.clone();
+        DapRegistry::default_global(cx).register_fake_adapter(
+            "fake",
+            Capabilities {
+                supports_configuration_done_request: Some(true),
+                ..Default::default()
+            },
+            move |fake| {
+                let launch_args = launch_args.clone();
+                let fake_adapter = fake.clone();
+                fake.handle_request::<Launch, _, _>(move |arguments, _| {
+                    launch_args.lock().push(arguments);
+                    fake_adapter.send_event::<Initialized>(());
+                    async move { Ok(()) }
+                });
+                let sent_breakpoints = sent_breakpoints.clone();
+                fake.handle_request::<SetBreakpoints, _, _>(move |arguments, _| {
+                    let lines = arguments
+                        .breakpoints
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|breakpoint| breakpoint.line)
+                        .collect::<Vec<_>>();
+                    sent_breakpoints
+                        .lock()
+                        .push((arguments.source.path.unwrap(), lines));
+                    async move {
+                        Ok(SetBreakpointsResponse {
+                            breakpoints: Vec::new(),
+                        })
+                    }
+                });
+                fake.handle_request::<ConfigurationDone, _, _>(|_, _| async move { Ok(()) });
+                fake.handle_request::<Threads, _, _>(|_, _| async move {
+                    Ok(ThreadsResponse {
+                        threads: vec![Thread {
+                            id: 1,
+                            name: "main".into(),
+                        }],
+                    })
+                });
+            },
+        )
+    });
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_breakpoint(&buffer, 1, cx)
+    });
+
+    let task = TaskTemplate {
+        label: "debug main".into(),
+        command: "/dir/target/debug/main".into(),
+        debug: Some(DebugAdapterConfig {
+            adapter: "fake".into(),
+            request: DebugRequestType::Launch,
+            initialize_args: Some(json!({ "stopOnEntry": true })),
+        }),
+        ..Default::default()
+    }
+    .resolve_task("test", &TaskContext::default())
+    .unwrap();
+    let session = project
+        .update(cx, |project, cx| project.start_debug_session(&task, cx))
+        .await
+        .unwrap();
+    let fake_adapter = fake_adapters.next().await.unwrap();
+    cx.run_until_parked();
+
+    let launch_args = launch_args.lock().clone();
+    assert_eq!(launch_args.len(), 1);
+    assert_eq!(launch_args[0]["program"], json!("/dir/target/debug/main"));
+    assert_eq!(launch_args[0]["stopOnEntry"], json!(true));
+    assert_eq!(
+        sent_breakpoints.lock().drain(..).collect::<Vec<_>>(),
+        vec![("/dir/main.rs".to_string(), vec![2])]
+    );
+    session.read_with(cx, |session, _| {
+        assert_eq!(session.status(), &DebugSessionStatus::Running);
+        assert_eq!(session.threads().len(), 1);
+    });
+
+    // Breakpoints toggled during the session are sent to the adapter right away.
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_breakpoint(&buffer, 2, cx)
+    });
+    cx.run_until_parked();
+    assert_eq!(
+        sent_breakpoints.lock().drain(..).collect::<Vec<_>>(),
+        vec![("/dir/main.rs".to_string(), vec![2, 3])]
+    );
+
+    fake_adapter.send_event::<Stopped>(StoppedEventBody {
+        reason: "breakpoint".into(),
+        description: None,
+        thread_id: Some(1),
+        all_threads_stopped: Some(true),
+        text: None,
+    });
+    cx.run_until_parked();
+    session.read_with(cx, |session, _| {
+        assert_eq!(
+            session.status(),
+            &DebugSessionStatus::Stopped {
+                thread_id: Some(1),
+                reason: "breakpoint".into()
+            }
+        );
+        assert_eq!(session.current_thread_id(), Some(1));
+    });
+
+    fake_adapter.handle_request::<StackTrace, _, _>(|arguments, _| async move {
+        assert_eq!(arguments.thread_id, 1);
+        Ok(StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 1,
+                name: "main".into(),
+                source: None,
+                line: 2,
+                column: 5,
+                end_line: None,
+                end_column: None,
+                presentation_hint: None,
+            }],
+            total_frames: Some(1),
+        })
+    });
+    let frames = session
+        .read_with(cx, |session, _| session.stack_frames(1))
+        .await
+        .unwrap();
+    assert_eq!(frames[0].name, "main");
+
+    fake_adapter.send_event::<Terminated>(None);
+    cx.run_until_parked();
+    dap_store.read_with(cx, |dap_store, _| {
+        assert_eq!(dap_store.sessions().count(), 0);
+    });
+}
+
+#[gpui::test]
+async fn test_rename_entry_with_file_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "mod two;\nuse two::f;",
+            "two.rs": "pub fn f() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let rust_files = lsp::FileOperationRegistrationOptions {
+        filters: vec![lsp::FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: lsp::FileOperationPattern {
+                glob: "**/*.rs".into(),
+                matches: Some(lsp::FileOperationPatternKind::File),
+                options: None,
+            },
+        }],
+    };
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace: Some(lsp::WorkspaceServerCapabilities {
+                    file_operations: Some(lsp::WorkspaceFileOperationsServerCapabilities {
+                        will_rename: Some(rust_files.clone()),
+                        did_rename: Some(rust_files),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
         .await
         .unwrap();
+    let mut fake_server = fake_language_servers.next().await.unwrap();
 
-    let fake_server = fake_servers.next().await.unwrap();
-    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
-        let params = params.text_document_position_params;
+    let events = Arc::new(Mutex::new(Vec::new()));
+    cx.update(|cx| {
+        cx.subscribe(&project, {
+            let events = events.clone();
+            move |_, event, _| {
+                if let Event::WorkspaceEditApplied(transaction) = event {
+                    events.lock().push(transaction.clone());
+                }
+            }
+        })
+        .detach();
+    });
+
+    fake_server.handle_request::<lsp::request::WillRenameFiles, _, _>(|params, _| async move {
         assert_eq!(
-            params.text_document.uri.to_file_path().unwrap(),
-            Path::new("/dir/b.rs"),
+            params.files,
+            vec![lsp::FileRename {
+                old_uri: "file:///dir/two.rs".into(),
+                new_uri: "file:///dir/three.rs".into(),
+            }]
         );
-        assert_eq!(params.position, lsp::Position::new(0, 22));
-
-        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
-            lsp::Location::new(
-                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-            ),
-        )))
+        Ok(Some(lsp::WorkspaceEdit {
+            changes: Some(HashMap::from_iter([(
+                lsp::Url::from_file_path("/dir/one.rs").unwrap(),
+                vec![
+                    lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 7)),
+                        "three".into(),
+                    ),
+                    lsp::TextEdit::new(
+                        lsp::Range::new(lsp::Position::new(1, 4), lsp::Position::new(1, 7)),
+                        "three".into(),
+                    ),
+                ],
+            )])),
+            ..Default::default()
+        }))
     });
 
-    let mut definitions = project
-        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+    let entry_id = project.update(cx, |project, cx| {
+        let worktree = project.worktrees(cx).next().unwrap();
+        worktree.read(cx).entry_for_path("two.rs").unwrap().id
+    });
+    project
+        .update(cx, |project, cx| {
+            project.rename_entry(entry_id, Path::new("three.rs"), cx)
+        })
         .await
         .unwrap();
 
-    // Assert no new language server started
-    cx.executor().run_until_parked();
-    assert!(fake_servers.try_next().is_err());
-
-    assert_eq!(definitions.len(), 1);
-    let definition = definitions.pop().unwrap();
-    cx.update(|cx| {
-        let target_buffer = definition.target.buffer.read(cx);
-        assert_eq!(
-            target_buffer
-                .file()
-                .unwrap()
-                .as_local()
-                .unwrap()
-                .abs_path(cx),
-            Path::new("/dir/a.rs"),
-        );
-        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
-        assert_eq!(
-            list_worktrees(&project, cx),
-            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
-        );
-
-        drop(definition);
-    });
-    cx.update(|cx| {
-        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
-    });
+    // The language server is notified about the rename once it happened.
+    let params = fake_server
+        .receive_notification::<lsp::notification::DidRenameFiles>()
+        .await;
+    assert_eq!(
+        params.files,
+        vec![lsp::FileRename {
+            old_uri: "file:///dir/two.rs".into(),
+            new_uri: "file:///dir/three.rs".into(),
+        }]
+    );
 
-    fn list_worktrees<'a>(
-        project: &'a Model<Project>,
-        cx: &'a AppContext,
-    ) -> Vec<(&'a Path, bool)> {
-        project
-            .read(cx)
-            .worktrees(cx)
-            .map(|worktree| {
-                let worktree = worktree.read(cx);
-                (
-                    worktree.as_local().unwrap().abs_path().as_ref(),
-                    worktree.is_visible(),
-                )
-            })
-            .collect::<Vec<_>>()
-    }
+    // The edits requested by the language server are applied, and can be undone.
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "mod three;\nuse three::f;"
+    );
+    assert_eq!(events.lock().len(), 1);
+    buffer.update(cx, |buffer, cx| buffer.undo(cx));
+    assert_eq!(
+        buffer.read_with(cx, |buffer, _| buffer.text()),
+        "mod two;\nuse two::f;"
+    );
 }
 
 #[gpui::test]
@@ -2776,6 +3911,155 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "let a = b;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::VARIABLE,
+                                    lsp::SemanticTokenType::FUNCTION,
+                                ],
+                                token_modifiers: vec![lsp::SemanticTokenModifier::DECLARATION],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let tokens = project.update(cx, |project, cx| project.semantic_tokens(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(|_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 4,
+                            length: 1,
+                            token_type: 0,
+                            token_modifiers_bitset: 1,
+                        },
+                        lsp::SemanticToken {
+                            delta_line: 0,
+                            delta_start: 4,
+                            length: 1,
+                            token_type: 0,
+                            token_modifiers_bitset: 0,
+                        },
+                    ],
+                },
+            )))
+        })
+        .next()
+        .await;
+    let tokens = tokens.await.unwrap();
+    assert_eq!(tokens.result_id.as_deref(), Some("1"));
+    assert_eq!(
+        tokens
+            .tokens()
+            .map(|token| (token.range, token.token_type, token.modifiers))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                PointUtf16::new(0, 4)..PointUtf16::new(0, 5),
+                "variable",
+                vec!["declaration"]
+            ),
+            (
+                PointUtf16::new(0, 8)..PointUtf16::new(0, 9),
+                "variable",
+                vec![]
+            ),
+        ]
+    );
+
+    // Tokens are cached until the buffer changes.
+    let cached_tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    assert!(Arc::ptr_eq(&tokens, &cached_tokens));
+
+    // Once the buffer is edited, only the delta is requested from the server.
+    buffer.update(cx, |buffer, cx| buffer.set_text("let a = b();", cx));
+    let tokens = project.update(cx, |project, cx| project.semantic_tokens(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+            |params, _| async move {
+                assert_eq!(params.previous_result_id, "1");
+                Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                    lsp::SemanticTokensDelta {
+                        result_id: Some("2".into()),
+                        edits: vec![lsp::SemanticTokensEdit {
+                            start: 5,
+                            delete_count: 5,
+                            data: Some(vec![lsp::SemanticToken {
+                                delta_line: 0,
+                                delta_start: 4,
+                                length: 1,
+                                token_type: 1,
+                                token_modifiers_bitset: 0,
+                            }]),
+                        }],
+                    },
+                )))
+            },
+        )
+        .next()
+        .await;
+    let tokens = tokens.await.unwrap();
+    assert_eq!(tokens.result_id.as_deref(), Some("2"));
+    assert_eq!(
+        tokens
+            .tokens()
+            .map(|token| (token.range, token.token_type))
+            .collect::<Vec<_>>(),
+        vec![
+            (PointUtf16::new(0, 4)..PointUtf16::new(0, 5), "variable"),
+            (PointUtf16::new(0, 8)..PointUtf16::new(0, 9), "function"),
+        ]
+    );
+
+    // The tokens are evicted once the buffer is released.
+    drop(buffer);
+    cx.run_until_parked();
+    project.read_with(cx, |project, _| assert!(project.semantic_tokens.is_empty()));
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -2838,6 +4122,7 @@ async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext)
         "/dir/file1".as_ref(),
         &"the first contents".into(),
         Default::default(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -2849,6 +4134,7 @@ async fn test_file_changes_multiple_times_on_disk(cx: &mut gpui::TestAppContext)
         "/dir/file1".as_ref(),
         &"the second contents".into(),
         Default::default(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -2893,6 +4179,7 @@ async fn test_edit_buffer_while_it_reloads(cx: &mut gpui::TestAppContext) {
         "/dir/file1".as_ref(),
         &"the first contents".into(),
         Default::default(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -3431,6 +4718,7 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
         "/dir/the-file".as_ref(),
         &new_contents.into(),
         LineEnding::Unix,
+        Default::default(),
     )
     .await
     .unwrap();
@@ -3466,6 +4754,7 @@ async fn test_buffer_file_changes_on_disk(cx: &mut gpui::TestAppContext) {
         "/dir/the-file".as_ref(),
         &"\n\n\nAAAA\naaa\nBB\nbbbbb\n".into(),
         LineEnding::Unix,
+        Default::default(),
     )
     .await
     .unwrap();
@@ -3517,6 +4806,7 @@ async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
         "/dir/file1".as_ref(),
         &"aaa\nb\nc\n".into(),
         LineEnding::Windows,
+        Default::default(),
     )
     .await
     .unwrap();
@@ -3540,6 +4830,96 @@ async fn test_buffer_line_endings(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_buffer_encodings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file("/dir/latin1", b"caf\xe8\n".to_vec()).await;
+    fs.insert_file("/dir/utf16", b"\xFF\xFEh\0i\0\n\0".to_vec())
+        .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer1 = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/latin1", cx))
+        .await
+        .unwrap();
+    let buffer2 = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/utf16", cx))
+        .await
+        .unwrap();
+
+    let windows_1252 = Encoding::from_name("windows-1252").unwrap();
+    let utf16 = Encoding::from_name("UTF-16LE").unwrap().with_bom(true);
+    buffer1.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "caf\u{e8}\n");
+        assert_eq!(buffer.encoding(), windows_1252);
+    });
+    buffer2.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "hi\n");
+        assert_eq!(buffer.encoding(), utf16);
+    });
+
+    // Saving a buffer writes it in the encoding it was loaded with.
+    buffer1.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "\u{e0} la ")], None, cx)
+    });
+    buffer2.update(cx, |buffer, cx| buffer.edit([(2..2, "!")], None, cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer1.clone(), cx))
+        .await
+        .unwrap();
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer2.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load_bytes("/dir/latin1".as_ref()).await.unwrap(),
+        b"\xe0 la caf\xe8\n"
+    );
+    assert_eq!(
+        fs.load_bytes("/dir/utf16".as_ref()).await.unwrap(),
+        b"\xFF\xFEh\0i\0!\0\n\0"
+    );
+
+    // Reopening a file with a different encoding decodes it again.
+    let iso_8859_2 = Encoding::from_name("ISO-8859-2").unwrap();
+    project
+        .update(cx, |project, cx| {
+            project.reopen_buffer_with_encoding(buffer1.clone(), iso_8859_2, cx)
+        })
+        .await
+        .unwrap();
+    buffer1.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "\u{155} la ca\u{10d}\n");
+        assert_eq!(buffer.encoding(), iso_8859_2);
+        assert!(!buffer.is_dirty());
+    });
+
+    // Reopening fails, leaving the buffer as it was, when the file isn't valid in the encoding.
+    project
+        .update(cx, |project, cx| {
+            project.reopen_buffer_with_encoding(buffer1.clone(), Encoding::UTF8, cx)
+        })
+        .await
+        .unwrap_err();
+    buffer1.update(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "\u{155} la ca\u{10d}\n");
+        assert_eq!(buffer.encoding(), iso_8859_2);
+    });
+
+    // Text that can't be represented in the buffer's encoding isn't saved.
+    buffer1.update(cx, |buffer, cx| buffer.set_text("\u{65e5}\u{672c}\n", cx));
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer1.clone(), cx))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        fs.load_bytes("/dir/latin1".as_ref()).await.unwrap(),
+        b"\xe0 la caf\xe8\n"
+    );
+}
+
 #[gpui::test]
 async fn test_grouped_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4522,6 +5902,36 @@ async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "main.rs": "fn main() {\n    let a = foo().unwrap();\n    bar(a).unwrap();\n    // baz.unwrap()\n}\n",
+            "notes.txt": "foo().unwrap()",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("$A.unwrap()", false, Default::default(), Default::default())
+                .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([("dir/main.rs".to_string(), vec![24..38, 44..59])]),
+        "Only calls in the syntax tree should match, and not text in comments or plain text files"
+    );
+}
+
 #[test]
 fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("**/*.js"), "");
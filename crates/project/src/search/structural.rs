@@ -0,0 +1,459 @@
+//! Structural search, which matches syntax trees against code patterns with metavariables.
+//!
+//! A pattern is written in the language being searched, with metavariables in place of the
+//! parts that may vary: `$A.unwrap()` matches any call to `unwrap`, capturing its receiver as `A`.
+//! A metavariable used more than once must capture the same text every time, and `$_` matches
+//! any node without capturing it. `$$$ARGS` matches any number of sibling nodes, such as the
+//! arguments of a call in `foo($$$ARGS)`. The same metavariables can be used in a replacement,
+//! such as `$A?`, which reinserts the text of the nodes they captured.
+
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use language::{with_parser, BufferSnapshot, Grammar, GrammarId, Node, TextBufferSnapshot, Tree};
+use parking_lot::Mutex;
+use std::{ops::Range, sync::Arc};
+
+const PLACEHOLDER_PREFIX: &str = "zed_meta_";
+const MULTI_PLACEHOLDER_PREFIX: &str = "zed_metas_";
+const ANONYMOUS_NAME: &str = "_";
+
+/// A structural search pattern, which is parsed with the grammar of each buffer it's matched in.
+#[derive(Clone, Debug)]
+pub struct StructuralPattern {
+    /// The pattern, with its metavariables replaced by identifiers that parse in most languages.
+    source: String,
+    /// The words of the pattern outside of its metavariables, which every match must contain.
+    words: Vec<String>,
+    /// The pattern parsed with each grammar it has been matched with, or `None` for the
+    /// grammars it doesn't parse in, so that it's parsed once per language.
+    compiled_patterns: Arc<Mutex<HashMap<GrammarId, Option<Arc<CompiledPattern>>>>>,
+}
+
+/// A metavariable of a pattern, recognized by its placeholder in the parsed pattern.
+#[derive(Debug, PartialEq, Eq)]
+struct Metavariable<'a> {
+    name: &'a str,
+    multiple: bool,
+}
+
+/// A pattern parsed with the grammar of a language, ready to be matched against its trees.
+#[derive(Debug)]
+struct CompiledPattern {
+    source: String,
+    tree: Tree,
+}
+
+impl StructuralPattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let mut source = String::new();
+        let mut words = Vec::new();
+        let mut literal = String::new();
+        let mut rest = pattern;
+        while let Some(dollar_ix) = rest.find('$') {
+            literal.push_str(&rest[..dollar_ix]);
+            source.push_str(&rest[..dollar_ix]);
+            rest = &rest[dollar_ix..];
+            let (multiple, after_dollars) = match rest.strip_prefix("$$$") {
+                Some(after_dollars) => (true, after_dollars),
+                None => (false, &rest[1..]),
+            };
+            let name_len = after_dollars
+                .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+                .unwrap_or(after_dollars.len());
+            let name = &after_dollars[..name_len];
+            if name.is_empty() && !multiple {
+                return Err(anyhow!(
+                    "expected a metavariable name after `$`, such as `$A` or `$_`"
+                ));
+            }
+            let name = if name.is_empty() {
+                ANONYMOUS_NAME
+            } else {
+                name
+            };
+            source.push_str(if multiple {
+                MULTI_PLACEHOLDER_PREFIX
+            } else {
+                PLACEHOLDER_PREFIX
+            });
+            source.push_str(name);
+            // Keep the words on either side of the metavariable apart.
+            literal.push(' ');
+            rest = &after_dollars[name_len..];
+        }
+        literal.push_str(rest);
+        source.push_str(rest);
+
+        if source.trim().is_empty() {
+            return Err(anyhow!("empty structural search pattern"));
+        }
+        for word in literal.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+            if !word.is_empty() && !words.iter().any(|existing| existing == word) {
+                words.push(word.to_string());
+            }
+        }
+        Ok(Self {
+            source,
+            words,
+            compiled_patterns: Default::default(),
+        })
+    }
+
+    /// Whether the text contains every word that a match of this pattern must contain.
+    pub fn may_match(&self, text: &str) -> bool {
+        self.words.iter().all(|word| text.contains(word.as_str()))
+    }
+
+    /// Finds the non-overlapping matches of the pattern in every syntax layer of the buffer
+    /// whose language the pattern can be parsed in, within the given range of the buffer.
+    ///
+    /// This doesn't yield, as the syntax trees it walks can't be held across await points.
+    pub fn search(&self, buffer: &BufferSnapshot, range: Range<usize>) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        for layer in buffer.syntax_layers() {
+            let Some(pattern) = layer
+                .language
+                .grammar()
+                .and_then(|grammar| self.compiled(grammar))
+            else {
+                continue;
+            };
+            let pattern_node = pattern.root();
+
+            let mut cursor = layer.node().walk();
+            'nodes: loop {
+                let node = cursor.node();
+                let node_range = node.byte_range();
+                let overlaps_range = node_range.start < range.end && node_range.end > range.start;
+                let mut matched = false;
+                if overlaps_range
+                    && node_range.start >= range.start
+                    && node_range.end <= range.end
+                    && !node.is_extra()
+                {
+                    let mut captures = Captures::default();
+                    if pattern.match_node(pattern_node, node, buffer, &mut captures) {
+                        matches.push(node_range);
+                        matched = true;
+                    }
+                }
+
+                // Don't look for matches inside of a match, or outside of the range.
+                if !matched && overlaps_range && cursor.goto_first_child() {
+                    continue;
+                }
+                while !cursor.goto_next_sibling() {
+                    if !cursor.goto_parent() {
+                        break 'nodes;
+                    }
+                }
+            }
+        }
+
+        matches.sort_unstable_by_key(|range| (range.start, std::cmp::Reverse(range.end)));
+        matches.dedup_by(|next, previous| next.start < previous.end);
+        matches
+    }
+
+    /// Expands the metavariables of the replacement with the text they captured in the match
+    /// of this pattern at the given range of the buffer.
+    pub fn replacement(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+        replacement: &str,
+    ) -> Option<String> {
+        let layers = buffer
+            .syntax_layers()
+            .filter(|layer| {
+                let node_range = layer.node().byte_range();
+                node_range.start <= range.start && node_range.end >= range.end
+            })
+            .collect::<Vec<_>>();
+        // Injected layers come after the layers they're injected in.
+        for layer in layers.into_iter().rev() {
+            let Some(pattern) = layer
+                .language
+                .grammar()
+                .and_then(|grammar| self.compiled(grammar))
+            else {
+                continue;
+            };
+            let captures = pattern.captures_at(layer.node(), range.clone(), buffer);
+            if let Some(captures) = captures {
+                return Some(expand_replacement(replacement, &captures, buffer));
+            }
+        }
+        None
+    }
+
+    /// The pattern parsed with the given grammar, parsing it on the first use of the grammar.
+    fn compiled(&self, grammar: &Grammar) -> Option<Arc<CompiledPattern>> {
+        self.compiled_patterns
+            .lock()
+            .entry(grammar.id())
+            .or_insert_with(|| self.compile(grammar).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, grammar: &Grammar) -> Option<CompiledPattern> {
+        let tree = with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(&self.source, None)
+        })?;
+        let pattern = CompiledPattern {
+            source: self.source.clone(),
+            tree,
+        };
+        if contains_error(pattern.root()) {
+            None
+        } else {
+            Some(pattern)
+        }
+    }
+}
+
+impl CompiledPattern {
+    /// The node of the parsed pattern to match, which is the innermost one spanning all of it.
+    fn root(&self) -> Node<'_> {
+        let trimmed_start = self.source.len() - self.source.trim_start().len();
+        let trimmed_end = self.source.trim_end().len();
+        let mut node = self.tree.root_node();
+        'descend: loop {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                if child.start_byte() <= trimmed_start && child.end_byte() >= trimmed_end {
+                    node = child;
+                    continue 'descend;
+                }
+            }
+            return node;
+        }
+    }
+
+    /// Matches the pattern against the outermost node spanning exactly the given range that
+    /// matches it, returning what its metavariables captured.
+    fn captures_at(
+        &self,
+        root: Node,
+        range: Range<usize>,
+        buffer: &TextBufferSnapshot,
+    ) -> Option<Captures> {
+        let mut node = root.descendant_for_byte_range(range.start, range.end)?;
+        while let Some(parent) = node.parent().filter(|parent| parent.byte_range() == range) {
+            node = parent;
+        }
+        loop {
+            let mut captures = Captures::default();
+            if node.byte_range() == range
+                && self.match_node(self.root(), node, buffer, &mut captures)
+            {
+                return Some(captures);
+            }
+            node = node.child(0).filter(|child| child.byte_range() == range)?;
+        }
+    }
+
+    fn metavariable(&self, node: Node) -> Option<Metavariable<'_>> {
+        let text = self.source.get(node.byte_range())?;
+        if let Some(name) = text.strip_prefix(MULTI_PLACEHOLDER_PREFIX) {
+            Some(Metavariable {
+                name,
+                multiple: true,
+            })
+        } else {
+            text.strip_prefix(PLACEHOLDER_PREFIX)
+                .map(|name| Metavariable {
+                    name,
+                    multiple: false,
+                })
+        }
+    }
+
+    fn match_node(
+        &self,
+        pattern: Node,
+        node: Node,
+        buffer: &TextBufferSnapshot,
+        captures: &mut Captures,
+    ) -> bool {
+        if let Some(metavariable) = self.metavariable(pattern) {
+            if !metavariable.multiple {
+                return captures.bind(metavariable.name, node.byte_range(), buffer);
+            }
+        }
+        if pattern.kind_id() != node.kind_id() {
+            return false;
+        }
+
+        let pattern_children = children(pattern);
+        let node_children = children(node);
+        if pattern_children.is_empty() {
+            let pattern_text = &self.source[pattern.byte_range()];
+            return node_children.is_empty()
+                && node.byte_range().len() == pattern_text.len()
+                && buffer.contains_str_at(node.start_byte(), pattern_text);
+        }
+        self.match_children(&pattern_children, &node_children, buffer, captures)
+    }
+
+    fn match_children(
+        &self,
+        patterns: &[Node],
+        nodes: &[Node],
+        buffer: &TextBufferSnapshot,
+        captures: &mut Captures,
+    ) -> bool {
+        let Some((pattern, remaining_patterns)) = patterns.split_first() else {
+            return nodes.is_empty();
+        };
+
+        match self.metavariable(*pattern) {
+            Some(metavariable) if metavariable.multiple => {
+                for count in 0..=nodes.len() {
+                    let (matched, remaining_nodes) = nodes.split_at(count);
+                    let range = match (matched.first(), matched.last()) {
+                        (Some(first), Some(last)) => first.start_byte()..last.end_byte(),
+                        _ => {
+                            let offset = nodes
+                                .first()
+                                .map_or(pattern.start_byte(), |node| node.start_byte());
+                            offset..offset
+                        }
+                    };
+                    let mut attempt = captures.clone();
+                    if attempt.bind(metavariable.name, range, buffer)
+                        && self.match_children(
+                            remaining_patterns,
+                            remaining_nodes,
+                            buffer,
+                            &mut attempt,
+                        )
+                    {
+                        *captures = attempt;
+                        return true;
+                    }
+                }
+                false
+            }
+            _ => match nodes.split_first() {
+                Some((node, remaining_nodes)) => {
+                    self.match_node(*pattern, *node, buffer, captures)
+                        && self.match_children(
+                            remaining_patterns,
+                            remaining_nodes,
+                            buffer,
+                            captures,
+                        )
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// The text captured by the metavariables of a match, as ranges of the searched buffer.
+#[derive(Clone, Default)]
+struct Captures(HashMap<String, Range<usize>>);
+
+impl Captures {
+    /// Captures the range for the metavariable, or checks that it has the same text as what
+    /// the metavariable already captured.
+    fn bind(&mut self, name: &str, range: Range<usize>, buffer: &TextBufferSnapshot) -> bool {
+        if name == ANONYMOUS_NAME {
+            return true;
+        }
+        match self.0.get(name) {
+            Some(captured) => {
+                captured.len() == range.len()
+                    && buffer
+                        .bytes_in_range(captured.clone())
+                        .flatten()
+                        .eq(buffer.bytes_in_range(range).flatten())
+            }
+            None => {
+                self.0.insert(name.to_string(), range);
+                true
+            }
+        }
+    }
+}
+
+/// The children of a node that take part in matching, leaving out comments and the nodes
+/// inserted by the parser to recover from errors.
+fn children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing())
+        .collect()
+}
+
+fn contains_error(node: Node) -> bool {
+    node.is_error()
+        || (0..node.child_count()).any(|ix| node.child(ix).map_or(false, contains_error))
+}
+
+fn expand_replacement(
+    replacement: &str,
+    captures: &Captures,
+    buffer: &TextBufferSnapshot,
+) -> String {
+    let mut expanded = String::new();
+    let mut rest = replacement;
+    while let Some(dollar_ix) = rest.find('$') {
+        expanded.push_str(&rest[..dollar_ix]);
+        rest = &rest[dollar_ix..];
+        let after_dollars = rest.strip_prefix("$$$").unwrap_or(&rest[1..]);
+        let name_len = after_dollars
+            .find(|c: char| !(c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+            .unwrap_or(after_dollars.len());
+        let name = &after_dollars[..name_len];
+        match captures.0.get(name) {
+            Some(range) => {
+                expanded.extend(buffer.text_for_range(range.clone()));
+                rest = &after_dollars[name_len..];
+            }
+            None => {
+                expanded.push('$');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_words_and_placeholders() {
+        let pattern = StructuralPattern::new("$A.unwrap_or($$$ARGS)").unwrap();
+        assert_eq!(pattern.source, "zed_meta_A.unwrap_or(zed_metas_ARGS)");
+        assert_eq!(pattern.words, ["unwrap_or"]);
+        assert!(pattern.may_match("let x = y.unwrap_or(0);"));
+        assert!(!pattern.may_match("let x = y.unwrap();"));
+
+        let pattern = StructuralPattern::new("$_ + $$$").unwrap();
+        assert_eq!(pattern.source, "zed_meta__ + zed_metas__");
+        assert!(pattern.words.is_empty());
+
+        assert!(StructuralPattern::new("$a").is_err());
+        assert!(StructuralPattern::new("  ").is_err());
+    }
+
+    #[test]
+    fn test_expand_replacement() {
+        let buffer = text::Buffer::new(0, text::BufferId::new(1).unwrap(), "foo(bar)".into());
+        let buffer = buffer.snapshot();
+        let mut captures = Captures::default();
+        assert!(captures.bind("A", 0..3, &buffer));
+        assert!(captures.bind("ARGS", 4..7, &buffer));
+        assert!(!captures.bind("A", 4..7, &buffer));
+        assert_eq!(
+            expand_replacement("$A?($$$ARGS, $B) costs $5", &captures, &buffer),
+            "foo?(bar, $B) costs $5"
+        );
+    }
+}
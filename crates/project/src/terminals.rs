@@ -109,6 +109,7 @@ impl Project {
             }
         }
         let settings = TerminalSettings::get(settings_location, cx);
+        let forward_announced_ports = settings.forward_announced_ports;
 
         let (completion_tx, completion_rx) = bounded(1);
 
@@ -175,9 +176,15 @@ impl Project {
                         log::debug!("Connecting to a remote server: {ssh_command:?}");
                         env.entry("TERM".to_string())
                             .or_insert_with(|| "xterm-256color".to_string());
+                        let use_remote_shell = self.is_ssh() && spawn_task.shell == Shell::System;
+                        let (command, args) = if use_remote_shell {
+                            remote_shell_command(&spawn_task.command, &spawn_task.args)
+                        } else {
+                            (spawn_task.command, spawn_task.args)
+                        };
                         let (program, args) = wrap_for_ssh(
                             ssh_command,
-                            Some((&spawn_task.command, &spawn_task.args)),
+                            Some((&command, &args)),
                             path.as_deref(),
                             env,
                             python_venv_directory,
@@ -238,6 +245,17 @@ impl Project {
             if let Some(activate_command) = python_venv_activate_command {
                 self.activate_python_virtual_environment(activate_command, &terminal_handle, cx);
             }
+
+            // The servers run in the terminal on the remote host are made reachable locally.
+            if self.is_ssh() && forward_announced_ports {
+                terminal_handle.update(cx, |terminal, _| terminal.detect_announced_ports());
+                cx.subscribe(&terminal_handle, |project, _, event, cx| {
+                    if let terminal::Event::PortAnnounced(port) = event {
+                        project.forward_announced_ssh_port(*port, cx);
+                    }
+                })
+                .detach();
+            }
             terminal_handle
         });
 
@@ -354,6 +372,22 @@ pub fn wrap_for_ssh(
     (program, args)
 }
 
+/// Runs a task's command line with the shell of the remote user, the way tasks are run with
+/// the system shell locally. That shell is only known on the remote machine.
+fn remote_shell_command(command: &str, args: &[String]) -> (String, Vec<String>) {
+    let command_line = iter::once(command)
+        .chain(args.iter().map(String::as_str))
+        .join(" ");
+    (
+        "sh".to_string(),
+        vec![
+            "-c".to_string(),
+            r#"exec "${SHELL:-sh}" -i -c "$0""#.to_string(),
+            command_line,
+        ],
+    )
+}
+
 fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> anyhow::Result<()> {
     let mut env_paths = vec![new_path.to_path_buf()];
     if let Some(path) = env.get("PATH").or(env::var("PATH").ok().as_ref()) {
@@ -392,6 +426,21 @@ mod tests {
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
 
+    #[test]
+    fn test_remote_shell_command() {
+        let (program, args) =
+            super::remote_shell_command("cargo", &["test".to_string(), "$ZED_SYMBOL".to_string()]);
+        assert_eq!(program, "sh");
+        assert_eq!(
+            args,
+            [
+                "-c",
+                r#"exec "${SHELL:-sh}" -i -c "$0""#,
+                "cargo test $ZED_SYMBOL"
+            ]
+        );
+    }
+
     #[test]
     fn test_add_environment_path_with_empty_path() {
         let tmp_path = std::path::PathBuf::from("/tmp/new");
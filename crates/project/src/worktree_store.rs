@@ -1,17 +1,101 @@
+use std::{ops::Range, path::PathBuf, sync::Arc};
+
 use anyhow::{anyhow, Context as _, Result};
+use askpass::{AskPassDelegate, AskPassSession};
 use collections::HashMap;
-use gpui::{AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, WeakModel};
+use futures::StreamExt as _;
+use git::{
+    log::{CommitEntry, CommitFileDiff, GitLog, LogQuery},
+    repository::{GitRepository, RepoPath},
+    stash::StashEntry,
+    status::{GitChangeKind, GitStagingEntry, GitStagingStatus},
+    Oid,
+};
+use gpui::{
+    AppContext, AsyncAppContext, EntityId, EventEmitter, Model, ModelContext, Task, WeakModel,
+};
 use rpc::{
     proto::{self, AnyProtoClient},
     TypedEnvelope,
 };
 use text::ReplicaId;
+use util::{post_inc, ResultExt as _};
 use worktree::{ProjectEntryId, Worktree, WorktreeId};
 
 pub struct WorktreeStore {
     is_shared: bool,
     worktrees: Vec<WorktreeHandle>,
     worktrees_reordered: bool,
+    /// Where to send the prompts for credentials of the remote operations running on the host,
+    /// by the id sent along with them.
+    askpass_delegates: HashMap<u64, AskPassDelegate>,
+    next_askpass_id: u64,
+}
+
+/// A change to the index or history of a worktree's git repository.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitOperation {
+    Stage(Vec<RepoPath>),
+    Unstage(Vec<RepoPath>),
+    /// Replaces the index contents of a single path, e.g. to stage or unstage individual hunks.
+    SetIndexText {
+        repo_path: RepoPath,
+        text: Option<String>,
+    },
+    /// Unstages the changes of a single path that touch the given rows of its index text.
+    UnstageRows {
+        repo_path: RepoPath,
+        rows: Vec<Range<u32>>,
+    },
+    Commit {
+        message: String,
+    },
+    /// Stashes all changes, including untracked files.
+    StashSave {
+        message: Option<String>,
+    },
+    /// Applies a stash entry, and removes it from the stash when `pop` is true.
+    StashApply {
+        index: usize,
+        pop: bool,
+    },
+    StashDrop {
+        index: usize,
+    },
+}
+
+/// A git command that talks to the remote of a worktree's repository.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GitRemoteOperation {
+    Fetch,
+    Pull,
+    Push,
+}
+
+impl GitRemoteOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fetch => "fetch",
+            Self::Pull => "pull",
+            Self::Push => "push",
+        }
+    }
+
+    fn to_proto(self) -> proto::run_git_remote_operation::Kind {
+        match self {
+            Self::Fetch => proto::run_git_remote_operation::Kind::Fetch,
+            Self::Pull => proto::run_git_remote_operation::Kind::Pull,
+            Self::Push => proto::run_git_remote_operation::Kind::Push,
+        }
+    }
+
+    fn from_proto(kind: proto::run_git_remote_operation::Kind) -> Self {
+        match kind {
+            proto::run_git_remote_operation::Kind::Fetch => Self::Fetch,
+            proto::run_git_remote_operation::Kind::Pull => Self::Pull,
+            proto::run_git_remote_operation::Kind::Push => Self::Push,
+        }
+    }
 }
 
 pub enum WorktreeStoreEvent {
@@ -28,6 +112,8 @@ impl WorktreeStore {
             is_shared: retain_worktrees,
             worktrees: Vec::new(),
             worktrees_reordered: false,
+            askpass_delegates: HashMap::default(),
+            next_askpass_id: 0,
         }
     }
 
@@ -238,6 +324,390 @@ impl WorktreeStore {
         }
     }
 
+    /// Returns the staged and unstaged changes of the repository whose work directory is the
+    /// given entry, querying the host for remote worktrees.
+    pub fn git_staging_status(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        cx: &AppContext,
+    ) -> Task<Result<GitStagingStatus>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor()
+                    .spawn(async move { repo?.staging_status() })
+            }
+            Worktree::Remote(worktree) => {
+                let request = proto::GetGitStagingStatus {
+                    project_id: worktree.project_id(),
+                    worktree_id: worktree.id().to_proto(),
+                    work_directory_id: work_directory_id.to_proto(),
+                };
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    let response = client.request(request).await?;
+                    Ok(GitStagingStatus {
+                        entries: response
+                            .entries
+                            .into_iter()
+                            .map(deserialize_git_staging_entry)
+                            .collect(),
+                    })
+                })
+            }
+        }
+    }
+
+    /// Applies a [`GitOperation`] to the repository whose work directory is the given entry,
+    /// forwarding it to the host for remote worktrees.
+    pub fn run_git_operation(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        operation: GitOperation,
+        cx: &AppContext,
+    ) -> Task<Result<()>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor().spawn(async move {
+                    let repo = repo?;
+                    match operation {
+                        GitOperation::Stage(paths) => repo.stage_paths(&paths),
+                        GitOperation::Unstage(paths) => repo.unstage_paths(&paths),
+                        GitOperation::SetIndexText { repo_path, text } => {
+                            repo.set_index_text(&repo_path, text)
+                        }
+                        GitOperation::UnstageRows { repo_path, rows } => {
+                            repo.unstage_rows(&repo_path, &rows)
+                        }
+                        GitOperation::Commit { message } => repo.commit(&message),
+                        GitOperation::StashSave { message } => repo.stash_save(message.as_deref()),
+                        GitOperation::StashApply { index, pop } => repo.stash_apply(index, pop),
+                        GitOperation::StashDrop { index } => repo.stash_drop(index),
+                    }
+                })
+            }
+            Worktree::Remote(worktree) => {
+                let project_id = worktree.project_id();
+                let worktree_id = worktree.id().to_proto();
+                let work_directory_id = work_directory_id.to_proto();
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    match operation {
+                        GitOperation::Stage(paths) => {
+                            client
+                                .request(proto::StageGitPaths {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    repo_paths: serialize_repo_paths(&paths),
+                                })
+                                .await?;
+                        }
+                        GitOperation::Unstage(paths) => {
+                            client
+                                .request(proto::UnstageGitPaths {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    repo_paths: serialize_repo_paths(&paths),
+                                })
+                                .await?;
+                        }
+                        GitOperation::SetIndexText { repo_path, text } => {
+                            client
+                                .request(proto::SetGitIndexText {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    repo_path: repo_path.to_string_lossy().into_owned(),
+                                    text,
+                                })
+                                .await?;
+                        }
+                        GitOperation::UnstageRows { repo_path, rows } => {
+                            client
+                                .request(proto::UnstageGitRows {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    repo_path: repo_path.to_string_lossy().into_owned(),
+                                    rows: rows
+                                        .into_iter()
+                                        .map(|rows| proto::RowRange {
+                                            start: rows.start,
+                                            end: rows.end,
+                                        })
+                                        .collect(),
+                                })
+                                .await?;
+                        }
+                        GitOperation::Commit { message } => {
+                            client
+                                .request(proto::CommitGitChanges {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    message,
+                                })
+                                .await?;
+                        }
+                        GitOperation::StashSave { message } => {
+                            client
+                                .request(proto::StashGitChanges {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    message,
+                                })
+                                .await?;
+                        }
+                        GitOperation::StashApply { index, pop } => {
+                            client
+                                .request(proto::ApplyGitStash {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    index: index as u64,
+                                    pop,
+                                })
+                                .await?;
+                        }
+                        GitOperation::StashDrop { index } => {
+                            client
+                                .request(proto::DropGitStash {
+                                    project_id,
+                                    worktree_id,
+                                    work_directory_id,
+                                    index: index as u64,
+                                })
+                                .await?;
+                        }
+                    }
+                    Ok(())
+                })
+            }
+        }
+    }
+
+    /// Lists a page of the history of the repository whose work directory is the given entry,
+    /// querying the host for remote worktrees.
+    pub fn git_log(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        query: LogQuery,
+        cx: &AppContext,
+    ) -> Task<Result<GitLog>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor().spawn(async move {
+                    let repo = repo?;
+                    Ok(GitLog {
+                        commits: repo.log(&query)?,
+                        remote_url: repo.remote_url("origin"),
+                    })
+                })
+            }
+            Worktree::Remote(worktree) => {
+                let request = proto::GetGitLog {
+                    project_id: worktree.project_id(),
+                    worktree_id: worktree.id().to_proto(),
+                    work_directory_id: work_directory_id.to_proto(),
+                    path: query.path.map(|path| path.to_string_lossy().into_owned()),
+                    start_row: query.rows.as_ref().map(|rows| rows.start),
+                    end_row: query.rows.as_ref().map(|rows| rows.end),
+                    skip: query.skip as u64,
+                    limit: query.limit as u64,
+                };
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    let response = client.request(request).await?;
+                    Ok(GitLog {
+                        commits: response
+                            .commits
+                            .into_iter()
+                            .map(deserialize_git_commit)
+                            .collect::<Result<_>>()?,
+                        remote_url: response.remote_url,
+                    })
+                })
+            }
+        }
+    }
+
+    /// Returns the contents of the files changed by a commit, before and after it,
+    /// querying the host for remote worktrees.
+    pub fn git_commit_diff(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        sha: Oid,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor()
+                    .spawn(async move { repo?.commit_diff(sha) })
+            }
+            Worktree::Remote(worktree) => {
+                let request = proto::GetGitCommitDiff {
+                    project_id: worktree.project_id(),
+                    worktree_id: worktree.id().to_proto(),
+                    work_directory_id: work_directory_id.to_proto(),
+                    sha: sha.as_bytes().to_vec(),
+                };
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    let response = client.request(request).await?;
+                    Ok(response
+                        .files
+                        .into_iter()
+                        .map(deserialize_commit_file_diff)
+                        .collect())
+                })
+            }
+        }
+    }
+
+    /// Returns the contents of the files that differ between a revision, or its merge base
+    /// with HEAD, and the working tree, querying the host for remote worktrees.
+    pub fn git_revision_diff(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        revision: String,
+        merge_base: bool,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CommitFileDiff>>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor()
+                    .spawn(async move { repo?.revision_diff(&revision, merge_base) })
+            }
+            Worktree::Remote(worktree) => {
+                let request = proto::GetGitRevisionDiff {
+                    project_id: worktree.project_id(),
+                    worktree_id: worktree.id().to_proto(),
+                    work_directory_id: work_directory_id.to_proto(),
+                    revision,
+                    merge_base,
+                };
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    let response = client.request(request).await?;
+                    Ok(response
+                        .files
+                        .into_iter()
+                        .map(deserialize_commit_file_diff)
+                        .collect())
+                })
+            }
+        }
+    }
+
+    /// Runs a [`GitRemoteOperation`] in the repository whose work directory is the given entry,
+    /// forwarding it to the host for remote worktrees. Prompts for credentials are sent to
+    /// `askpass`, and the operation fails when git needs credentials and there is no delegate.
+    pub fn run_git_remote_operation(
+        &mut self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        operation: GitRemoteOperation,
+        askpass: Option<AskPassDelegate>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let (project_id, worktree_id, client) = match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                let executor = cx.background_executor().clone();
+                return cx.background_executor().spawn(async move {
+                    let repo = repo?;
+                    let askpass = match askpass {
+                        Some(delegate) => Some(AskPassSession::new(&executor, delegate).await?),
+                        None => None,
+                    };
+                    let env = askpass
+                        .as_ref()
+                        .map(AskPassSession::env)
+                        .unwrap_or_default();
+                    match operation {
+                        GitRemoteOperation::Fetch => repo.fetch(&env),
+                        GitRemoteOperation::Pull => repo.pull(&env),
+                        GitRemoteOperation::Push => repo.push(&env),
+                    }
+                });
+            }
+            Worktree::Remote(worktree) => (
+                worktree.project_id(),
+                worktree.id().to_proto(),
+                worktree.client(),
+            ),
+        };
+
+        let askpass_id = post_inc(&mut self.next_askpass_id);
+        if let Some(askpass) = askpass {
+            self.askpass_delegates.insert(askpass_id, askpass);
+        }
+        let request = proto::RunGitRemoteOperation {
+            project_id,
+            worktree_id,
+            work_directory_id: work_directory_id.to_proto(),
+            kind: operation.to_proto().into(),
+            askpass_id,
+        };
+        cx.spawn(|this, mut cx| async move {
+            let response = client.request(request).await;
+            this.update(&mut cx, |this, _| {
+                this.askpass_delegates.remove(&askpass_id)
+            })
+            .ok();
+            response?;
+            Ok(())
+        })
+    }
+
+    /// Lists the stash of the repository whose work directory is the given entry,
+    /// querying the host for remote worktrees.
+    pub fn git_stashes(
+        &self,
+        worktree: &Model<Worktree>,
+        work_directory_id: ProjectEntryId,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<StashEntry>>> {
+        match worktree.read(cx) {
+            Worktree::Local(worktree) => {
+                let repo = local_git_repository(worktree, work_directory_id);
+                cx.background_executor()
+                    .spawn(async move { repo?.stash_list() })
+            }
+            Worktree::Remote(worktree) => {
+                let request = proto::GetGitStashes {
+                    project_id: worktree.project_id(),
+                    worktree_id: worktree.id().to_proto(),
+                    work_directory_id: work_directory_id.to_proto(),
+                };
+                let client = worktree.client();
+                cx.background_executor().spawn(async move {
+                    let response = client.request(request).await?;
+                    Ok(response
+                        .stashes
+                        .into_iter()
+                        .map(|stash| StashEntry {
+                            index: stash.index as usize,
+                            message: stash.message,
+                        })
+                        .collect())
+                })
+            }
+        }
+    }
+
     pub async fn handle_create_project_entry(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::CreateProjectEntry>,
@@ -301,6 +771,378 @@ impl WorktreeStore {
             .ok_or_else(|| anyhow!("invalid request"))?;
         Worktree::handle_expand_entry(worktree, envelope.payload, cx).await
     }
+
+    pub async fn handle_get_git_staging_status(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGitStagingStatus>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGitStagingStatusResponse> {
+        let status = this
+            .update(&mut cx, |this, cx| {
+                let worktree_id = WorktreeId::from_proto(envelope.payload.worktree_id);
+                let worktree = this
+                    .worktree_for_id(worktree_id, cx)
+                    .ok_or_else(|| anyhow!("worktree not found"))?;
+                let work_directory_id =
+                    ProjectEntryId::from_proto(envelope.payload.work_directory_id);
+                anyhow::Ok(this.git_staging_status(&worktree, work_directory_id, cx))
+            })??
+            .await?;
+        Ok(proto::GetGitStagingStatusResponse {
+            entries: status
+                .entries
+                .iter()
+                .map(serialize_git_staging_entry)
+                .collect(),
+        })
+    }
+
+    pub async fn handle_stage_git_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::StageGitPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::Stage(deserialize_repo_paths(envelope.payload.repo_paths));
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_unstage_git_paths(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnstageGitPaths>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::Unstage(deserialize_repo_paths(envelope.payload.repo_paths));
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_set_git_index_text(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SetGitIndexText>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::SetIndexText {
+            repo_path: RepoPath::new(PathBuf::from(envelope.payload.repo_path)),
+            text: envelope.payload.text,
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_unstage_git_rows(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::UnstageGitRows>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::UnstageRows {
+            repo_path: RepoPath::new(PathBuf::from(envelope.payload.repo_path)),
+            rows: envelope
+                .payload
+                .rows
+                .into_iter()
+                .map(|rows| rows.start..rows.end)
+                .collect(),
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_commit_git_changes(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CommitGitChanges>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::Commit {
+            message: envelope.payload.message,
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_get_git_log(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGitLog>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGitLogResponse> {
+        let payload = envelope.payload;
+        let query = LogQuery {
+            path: payload.path.map(|path| RepoPath::new(PathBuf::from(path))),
+            rows: payload
+                .start_row
+                .zip(payload.end_row)
+                .map(|(start, end)| start..end),
+            skip: payload.skip as usize,
+            limit: payload.limit as usize,
+        };
+        let log = this
+            .update(&mut cx, |this, cx| {
+                let worktree = this
+                    .worktree_for_id(WorktreeId::from_proto(payload.worktree_id), cx)
+                    .ok_or_else(|| anyhow!("worktree not found"))?;
+                let work_directory_id = ProjectEntryId::from_proto(payload.work_directory_id);
+                anyhow::Ok(this.git_log(&worktree, work_directory_id, query, cx))
+            })??
+            .await?;
+        Ok(proto::GetGitLogResponse {
+            commits: log.commits.iter().map(serialize_git_commit).collect(),
+            remote_url: log.remote_url,
+        })
+    }
+
+    pub async fn handle_get_git_commit_diff(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGitCommitDiff>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGitCommitDiffResponse> {
+        let payload = envelope.payload;
+        let sha = Oid::from_bytes(&payload.sha)?;
+        let files = this
+            .update(&mut cx, |this, cx| {
+                let worktree = this
+                    .worktree_for_id(WorktreeId::from_proto(payload.worktree_id), cx)
+                    .ok_or_else(|| anyhow!("worktree not found"))?;
+                let work_directory_id = ProjectEntryId::from_proto(payload.work_directory_id);
+                anyhow::Ok(this.git_commit_diff(&worktree, work_directory_id, sha, cx))
+            })??
+            .await?;
+        Ok(proto::GetGitCommitDiffResponse {
+            files: files.into_iter().map(serialize_commit_file_diff).collect(),
+        })
+    }
+
+    pub async fn handle_get_git_revision_diff(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGitRevisionDiff>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGitRevisionDiffResponse> {
+        let payload = envelope.payload;
+        let files = this
+            .update(&mut cx, |this, cx| {
+                let worktree = this
+                    .worktree_for_id(WorktreeId::from_proto(payload.worktree_id), cx)
+                    .ok_or_else(|| anyhow!("worktree not found"))?;
+                let work_directory_id = ProjectEntryId::from_proto(payload.work_directory_id);
+                anyhow::Ok(this.git_revision_diff(
+                    &worktree,
+                    work_directory_id,
+                    payload.revision,
+                    payload.merge_base,
+                    cx,
+                ))
+            })??
+            .await?;
+        Ok(proto::GetGitRevisionDiffResponse {
+            files: files.into_iter().map(serialize_commit_file_diff).collect(),
+        })
+    }
+
+    pub async fn handle_stash_git_changes(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::StashGitChanges>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::StashSave {
+            message: envelope.payload.message,
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_apply_git_stash(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ApplyGitStash>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::StashApply {
+            index: envelope.payload.index as usize,
+            pop: envelope.payload.pop,
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_drop_git_stash(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::DropGitStash>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitOperation::StashDrop {
+            index: envelope.payload.index as usize,
+        };
+        Self::handle_git_operation(
+            this,
+            envelope.payload.worktree_id,
+            envelope.payload.work_directory_id,
+            operation,
+            cx,
+        )
+        .await
+    }
+
+    pub async fn handle_get_git_stashes(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetGitStashes>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetGitStashesResponse> {
+        let payload = envelope.payload;
+        let stashes = this
+            .update(&mut cx, |this, cx| {
+                let worktree = this
+                    .worktree_for_id(WorktreeId::from_proto(payload.worktree_id), cx)
+                    .ok_or_else(|| anyhow!("worktree not found"))?;
+                let work_directory_id = ProjectEntryId::from_proto(payload.work_directory_id);
+                anyhow::Ok(this.git_stashes(&worktree, work_directory_id, cx))
+            })??
+            .await?;
+        Ok(proto::GetGitStashesResponse {
+            stashes: stashes
+                .into_iter()
+                .map(|stash| proto::GitStashEntry {
+                    index: stash.index as u64,
+                    message: stash.message,
+                })
+                .collect(),
+        })
+    }
+
+    /// Runs a remote operation for a guest. Guests can't be prompted for credentials,
+    /// so it only succeeds when the host's git doesn't need to ask for them.
+    pub async fn handle_run_git_remote_operation(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RunGitRemoteOperation>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        Self::run_requested_git_remote_operation(this, envelope.payload, None, cx).await
+    }
+
+    /// Runs a remote operation requested by a peer, sending the prompts for credentials back
+    /// to it through `askpass_client` when given.
+    pub async fn run_requested_git_remote_operation(
+        this: Model<Self>,
+        request: proto::RunGitRemoteOperation,
+        askpass_client: Option<AnyProtoClient>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let operation = GitRemoteOperation::from_proto(request.kind());
+        let askpass = askpass_client.map(|client| {
+            let (delegate, mut prompts) = AskPassDelegate::new();
+            let project_id = request.project_id;
+            let askpass_id = request.askpass_id;
+            cx.background_executor()
+                .spawn(async move {
+                    while let Some(prompt) = prompts.next().await {
+                        let response = client
+                            .request(proto::AskPass {
+                                project_id,
+                                askpass_id,
+                                prompt: prompt.prompt.clone(),
+                            })
+                            .await;
+                        if let Some(response) = response.log_err() {
+                            prompt.respond(response.response);
+                        }
+                    }
+                })
+                .detach();
+            delegate
+        });
+        this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(WorktreeId::from_proto(request.worktree_id), cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let work_directory_id = ProjectEntryId::from_proto(request.work_directory_id);
+            anyhow::Ok(this.run_git_remote_operation(
+                &worktree,
+                work_directory_id,
+                operation,
+                askpass,
+                cx,
+            ))
+        })??
+        .await?;
+        Ok(proto::Ack {})
+    }
+
+    /// Answers a prompt for credentials of a remote operation running on the host.
+    pub async fn handle_ask_pass(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::AskPass>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::AskPassResponse> {
+        let delegate = this
+            .update(&mut cx, |this, _| {
+                this.askpass_delegates
+                    .get(&envelope.payload.askpass_id)
+                    .cloned()
+            })?
+            .context("no git operation is waiting for credentials")?;
+        let response = delegate
+            .ask(envelope.payload.prompt)
+            .await
+            .context("authentication was cancelled")?;
+        Ok(proto::AskPassResponse { response })
+    }
+
+    async fn handle_git_operation(
+        this: Model<Self>,
+        worktree_id: u64,
+        work_directory_id: u64,
+        operation: GitOperation,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        this.update(&mut cx, |this, cx| {
+            let worktree = this
+                .worktree_for_id(WorktreeId::from_proto(worktree_id), cx)
+                .ok_or_else(|| anyhow!("worktree not found"))?;
+            let work_directory_id = ProjectEntryId::from_proto(work_directory_id);
+            anyhow::Ok(this.run_git_operation(&worktree, work_directory_id, operation, cx))
+        })??
+        .await?;
+        Ok(proto::Ack {})
+    }
 }
 
 #[derive(Clone)]
@@ -317,3 +1159,125 @@ impl WorktreeHandle {
         }
     }
 }
+
+fn local_git_repository(
+    worktree: &worktree::LocalWorktree,
+    work_directory_id: ProjectEntryId,
+) -> Result<Arc<dyn GitRepository>> {
+    let snapshot = worktree.snapshot();
+    let repo_entry = snapshot
+        .repositories()
+        .map(|(_, repo_entry)| repo_entry)
+        .find(|repo_entry| repo_entry.work_directory_id() == work_directory_id)
+        .context("git repository not found")?;
+    let local_repo_entry = worktree
+        .get_local_repo(repo_entry)
+        .context("git repository not found")?;
+    Ok(local_repo_entry.repo().clone())
+}
+
+fn serialize_repo_paths(paths: &[RepoPath]) -> Vec<String> {
+    paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
+}
+
+fn deserialize_repo_paths(paths: Vec<String>) -> Vec<RepoPath> {
+    paths
+        .into_iter()
+        .map(|path| RepoPath::new(PathBuf::from(path)))
+        .collect()
+}
+
+fn serialize_git_staging_entry(entry: &GitStagingEntry) -> proto::GitStagingEntry {
+    proto::GitStagingEntry {
+        repo_path: entry.repo_path.to_string_lossy().into_owned(),
+        index_status: entry
+            .index_status
+            .map(|kind| serialize_git_change_kind(kind).into()),
+        worktree_status: entry
+            .worktree_status
+            .map(|kind| serialize_git_change_kind(kind).into()),
+    }
+}
+
+fn deserialize_git_staging_entry(entry: proto::GitStagingEntry) -> GitStagingEntry {
+    GitStagingEntry {
+        repo_path: RepoPath::new(PathBuf::from(entry.repo_path)),
+        index_status: entry
+            .index_status
+            .and_then(proto::GitChangeKind::from_i32)
+            .map(deserialize_git_change_kind),
+        worktree_status: entry
+            .worktree_status
+            .and_then(proto::GitChangeKind::from_i32)
+            .map(deserialize_git_change_kind),
+    }
+}
+
+fn serialize_git_change_kind(kind: GitChangeKind) -> proto::GitChangeKind {
+    match kind {
+        GitChangeKind::Added => proto::GitChangeKind::GitAdded,
+        GitChangeKind::Modified => proto::GitChangeKind::GitModified,
+        GitChangeKind::Deleted => proto::GitChangeKind::GitDeleted,
+        GitChangeKind::Conflict => proto::GitChangeKind::GitConflict,
+        GitChangeKind::Untracked => proto::GitChangeKind::GitUntracked,
+    }
+}
+
+fn deserialize_git_change_kind(kind: proto::GitChangeKind) -> GitChangeKind {
+    match kind {
+        proto::GitChangeKind::GitAdded => GitChangeKind::Added,
+        proto::GitChangeKind::GitModified => GitChangeKind::Modified,
+        proto::GitChangeKind::GitDeleted => GitChangeKind::Deleted,
+        proto::GitChangeKind::GitConflict => GitChangeKind::Conflict,
+        proto::GitChangeKind::GitUntracked => GitChangeKind::Untracked,
+    }
+}
+
+fn serialize_git_commit(commit: &CommitEntry) -> proto::GitCommit {
+    proto::GitCommit {
+        sha: commit.sha.as_bytes().to_vec(),
+        parents: commit
+            .parents
+            .iter()
+            .map(|parent| parent.as_bytes().to_vec())
+            .collect(),
+        author_name: commit.author_name.clone(),
+        author_email: commit.author_email.clone(),
+        timestamp: commit.timestamp,
+        message: commit.message.clone(),
+    }
+}
+
+fn deserialize_git_commit(commit: proto::GitCommit) -> Result<CommitEntry> {
+    Ok(CommitEntry {
+        sha: Oid::from_bytes(&commit.sha)?,
+        parents: commit
+            .parents
+            .iter()
+            .map(|parent| Oid::from_bytes(parent))
+            .collect::<Result<_>>()?,
+        author_name: commit.author_name,
+        author_email: commit.author_email,
+        timestamp: commit.timestamp,
+        message: commit.message,
+    })
+}
+
+fn serialize_commit_file_diff(file: CommitFileDiff) -> proto::GitCommitFileDiff {
+    proto::GitCommitFileDiff {
+        path: file.path.to_string_lossy().into_owned(),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
+
+fn deserialize_commit_file_diff(file: proto::GitCommitFileDiff) -> CommitFileDiff {
+    CommitFileDiff {
+        path: RepoPath::new(PathBuf::from(file.path)),
+        old_text: file.old_text,
+        new_text: file.new_text,
+    }
+}
@@ -136,6 +136,7 @@ actions!(
         Copy,
         CopyPath,
         CopyRelativePath,
+        CompareMarkedFiles,
         Duplicate,
         RevealInFileManager,
         Cut,
@@ -462,6 +463,7 @@ impl ProjectPanel {
             let worktree_id = worktree.id();
             let is_read_only = project.is_read_only();
             let is_remote = project.is_remote() && project.dev_server_project_id().is_none();
+            let can_compare = self.marked_files_to_compare(cx).is_some();
 
             let context_menu = ContextMenu::build(cx, |menu, cx| {
                 menu.context(self.focus_handle.clone()).map(|menu| {
@@ -516,6 +518,10 @@ impl ProjectPanel {
                             .separator()
                             .action("Copy Path", Box::new(CopyPath))
                             .action("Copy Relative Path", Box::new(CopyRelativePath))
+                            .when(can_compare, |menu| {
+                                menu.separator()
+                                    .action("Compare Selected Files", Box::new(CompareMarkedFiles))
+                            })
                             .separator()
                             .action("Rename", Box::new(Rename))
                             .when(!is_root, |menu| {
@@ -1373,6 +1379,30 @@ impl ProjectPanel {
         }
     }
 
+    fn compare_marked_files(&mut self, _: &CompareMarkedFiles, cx: &mut ViewContext<Self>) {
+        if let Some((old_path, new_path)) = self.marked_files_to_compare(cx) {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    diff_view::SplitDiffView::compare_project_paths(
+                        workspace, old_path, new_path, cx,
+                    );
+                })
+                .ok();
+        }
+    }
+
+    /// Returns the paths of the marked entries when exactly two files are marked.
+    fn marked_files_to_compare(&self, cx: &AppContext) -> Option<(ProjectPath, ProjectPath)> {
+        let project = self.project.read(cx);
+        let mut paths = self.marked_entries().into_iter().filter_map(|selection| {
+            let path = project.path_for_entry(selection.entry_id, cx)?;
+            project.entry_for_path(&path, cx)?.is_file().then_some(path)
+        });
+        let old_path = paths.next()?;
+        let new_path = paths.next()?;
+        paths.next().is_none().then_some((old_path, new_path))
+    }
+
     fn reveal_in_finder(&mut self, _: &RevealInFileManager, cx: &mut ViewContext<Self>) {
         if let Some((worktree, entry)) = self.selected_entry(cx) {
             cx.reveal_path(&worktree.abs_path().join(&entry.path));
@@ -2009,6 +2039,7 @@ impl ProjectPanel {
         let width = self.size(cx);
         let filename_text_color =
             entry_git_aware_label_color(details.git_status, details.is_ignored, is_marked);
+        let is_conflicted = kind.is_file() && details.git_status == Some(GitFileStatus::Conflict);
         let file_name = details.filename.clone();
         let mut icon = details.icon.clone();
         if settings.file_icons && show_editor && details.kind.is_file() {
@@ -2104,6 +2135,20 @@ impl ProjectPanel {
                     .indent_level(depth)
                     .indent_step_size(px(settings.indent_size))
                     .selected(is_marked || is_active)
+                    .when(is_conflicted, |this| {
+                        this.end_slot::<AnyElement>(
+                            div()
+                                .id("conflict_icon")
+                                .pr_3()
+                                .tooltip(|cx| Tooltip::text("Merge Conflict", cx))
+                                .child(
+                                    Icon::new(IconName::ExclamationTriangle)
+                                        .size(IconSize::Indicator)
+                                        .color(Color::Conflict),
+                                )
+                                .into_any_element(),
+                        )
+                    })
                     .when_some(canonical_path, |this, path| {
                         this.end_slot::<AnyElement>(
                             div()
@@ -2427,6 +2472,7 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::cancel))
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
+                .on_action(cx.listener(Self::compare_marked_files))
                 .on_action(cx.listener(Self::new_search_in_directory))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
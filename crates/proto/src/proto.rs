@@ -328,6 +328,7 @@ messages!(
     (SetChannelVisibility, Foreground),
     (SearchProject, Background),
     (SearchProjectResponse, Background),
+    (SearchProjectResults, Background),
     (SendChannelMessage, Background),
     (SendChannelMessageResponse, Background),
     (ShareProject, Foreground),
@@ -406,6 +407,59 @@ messages!(
     (SynchronizeContextsResponse, Foreground),
     (AddWorktree, Foreground),
     (AddWorktreeResponse, Foreground),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (RefreshSemanticTokens, Foreground),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetOutgoingCalls, Background),
+    (GetCallHierarchyCallsResponse, Background),
+    (PrepareTypeHierarchy, Background),
+    (PrepareTypeHierarchyResponse, Background),
+    (GetSupertypes, Background),
+    (GetSubtypes, Background),
+    (GetTypeHierarchyItemsResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (ExecuteCodeLens, Background),
+    (ExecuteCodeLensResponse, Background),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
+    (GetDocumentColors, Background),
+    (GetDocumentColorsResponse, Background),
+    (GetColorPresentations, Background),
+    (GetColorPresentationsResponse, Background),
+    (GetGitStagingStatus, Background),
+    (GetGitStagingStatusResponse, Background),
+    (StageGitPaths, Background),
+    (UnstageGitPaths, Background),
+    (SetGitIndexText, Background),
+    (UnstageGitRows, Background),
+    (CommitGitChanges, Background),
+    (GetGitLog, Background),
+    (GetGitLogResponse, Background),
+    (GetGitCommitDiff, Background),
+    (GetGitCommitDiffResponse, Background),
+    (GetGitRevisionDiff, Background),
+    (GetGitRevisionDiffResponse, Background),
+    (RunGitRemoteOperation, Background),
+    (AskPass, Foreground),
+    (AskPassResponse, Foreground),
+    (GetGitStashes, Background),
+    (GetGitStashesResponse, Background),
+    (StashGitChanges, Background),
+    (ApplyGitStash, Background),
+    (DropGitStash, Background),
+    (ReopenBuffer, Foreground),
+    (RestoreBuffer, Foreground),
+    (CloseBuffer, Foreground),
 );
 
 request_messages!(
@@ -496,6 +550,7 @@ request_messages!(
     (RespondToContactRequest, Ack),
     (SaveBuffer, BufferSaved),
     (SearchProject, SearchProjectResponse),
+    (SearchProjectResults, Ack),
     (SendChannelMessage, SendChannelMessageResponse),
     (SetChannelMemberRole, Ack),
     (SetChannelVisibility, Ack),
@@ -529,6 +584,39 @@ request_messages!(
     (CreateContext, CreateContextResponse),
     (SynchronizeContexts, SynchronizeContextsResponse),
     (AddWorktree, AddWorktreeResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (RefreshSemanticTokens, Ack),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
+    (GetIncomingCalls, GetCallHierarchyCallsResponse),
+    (GetOutgoingCalls, GetCallHierarchyCallsResponse),
+    (PrepareTypeHierarchy, PrepareTypeHierarchyResponse),
+    (GetSupertypes, GetTypeHierarchyItemsResponse),
+    (GetSubtypes, GetTypeHierarchyItemsResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (ExecuteCodeLens, ExecuteCodeLensResponse),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
+    (GetDocumentColors, GetDocumentColorsResponse),
+    (GetColorPresentations, GetColorPresentationsResponse),
+    (GetGitStagingStatus, GetGitStagingStatusResponse),
+    (StageGitPaths, Ack),
+    (UnstageGitPaths, Ack),
+    (SetGitIndexText, Ack),
+    (UnstageGitRows, Ack),
+    (CommitGitChanges, Ack),
+    (GetGitLog, GetGitLogResponse),
+    (GetGitCommitDiff, GetGitCommitDiffResponse),
+    (GetGitRevisionDiff, GetGitRevisionDiffResponse),
+    (RunGitRemoteOperation, Ack),
+    (AskPass, AskPassResponse),
+    (GetGitStashes, GetGitStashesResponse),
+    (StashGitChanges, Ack),
+    (ApplyGitStash, Ack),
+    (DropGitStash, Ack),
+    (ReopenBuffer, Ack),
+    (RestoreBuffer, Ack),
 );
 
 entity_messages!(
@@ -536,24 +624,45 @@ entity_messages!(
     AddProjectCollaborator,
     ApplyCodeAction,
     ApplyCompletionAdditionalEdits,
+    ApplyGitStash,
+    AskPass,
     BlameBuffer,
     BufferReloaded,
     BufferSaved,
+    CloseBuffer,
+    CommitGitChanges,
     CopyProjectEntry,
     CreateBufferForPeer,
     CreateProjectEntry,
     DeleteProjectEntry,
+    DropGitStash,
+    ExecuteCodeLens,
     ExpandProjectEntry,
     FormatBuffers,
     GetCodeActions,
+    GetCodeLens,
+    GetColorPresentations,
     GetCompletions,
     GetDefinition,
     GetImplementation,
+    GetDocumentColors,
     GetDocumentHighlights,
+    GetDocumentLinks,
+    GetFoldingRanges,
+    GetGitCommitDiff,
+    GetGitLog,
+    GetGitRevisionDiff,
+    GetGitStagingStatus,
+    GetGitStashes,
     GetHover,
+    GetIncomingCalls,
+    GetOutgoingCalls,
     GetProjectSymbols,
     GetReferences,
+    GetSemanticTokens,
     GetSignatureHelp,
+    GetSubtypes,
+    GetSupertypes,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
@@ -567,20 +676,34 @@ entity_messages!(
     OpenBufferByPath,
     OpenBufferForSymbol,
     PerformRename,
+    PrepareCallHierarchy,
     PrepareRename,
+    PrepareTypeHierarchy,
     RefreshInlayHints,
+    RefreshSemanticTokens,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
+    ReopenBuffer,
+    ResolveCodeLens,
     ResolveCompletionDocumentation,
+    ResolveDocumentLink,
     ResolveInlayHint,
+    RestoreBuffer,
+    RunGitRemoteOperation,
     SaveBuffer,
     SearchProject,
+    SearchProjectResults,
+    SetGitIndexText,
+    StageGitPaths,
+    StashGitChanges,
     StartLanguageServer,
     SynchronizeBuffers,
     TaskContextForLocation,
     TaskTemplates,
     UnshareProject,
+    UnstageGitPaths,
+    UnstageGitRows,
     UpdateBuffer,
     UpdateBufferFile,
     UpdateDiagnosticSummary,
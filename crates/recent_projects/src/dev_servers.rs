@@ -310,6 +310,7 @@ impl DevServerProjects {
             username,
             port,
             password: None,
+            port_forwards: Vec::new(),
         };
         let ssh_prompt = cx.new_view(|cx| SshPrompt::new(&connection_options, cx));
         let connection = connect_over_ssh(connection_options.clone(), ssh_prompt.clone(), cx)
@@ -1071,6 +1072,7 @@ impl DevServerProjects {
                     username: connection_options.username,
                     port: connection_options.port,
                     projects: vec![],
+                    port_forwards: vec![],
                 })
         });
     }
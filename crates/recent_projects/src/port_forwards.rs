@@ -0,0 +1,227 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{Project, SshPortForward};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+use crate::ForwardPort;
+
+/// Lists the ports that are forwarded over the SSH connection of a project, forwarding the one
+/// typed into the query, or stopping the one that is selected.
+pub struct PortForwards {
+    picker: View<Picker<PortForwardsDelegate>>,
+}
+
+impl PortForwards {
+    pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ForwardPort, cx| {
+            let project = workspace.project().clone();
+            if project.read(cx).is_ssh() {
+                workspace.toggle_modal(cx, |cx| Self::new(project, cx));
+            }
+        });
+    }
+
+    pub fn open(workspace: View<Workspace>, cx: &mut WindowContext) {
+        workspace.update(cx, |workspace, cx| {
+            let project = workspace.project().clone();
+            workspace.toggle_modal(cx, |cx| Self::new(project, cx))
+        })
+    }
+
+    fn new(project: Model<Project>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = PortForwardsDelegate::new(cx.view().downgrade(), project, cx);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+impl Render for PortForwards {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for PortForwards {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for PortForwards {}
+impl ModalView for PortForwards {}
+
+pub struct PortForwardsDelegate {
+    port_forwards: WeakView<PortForwards>,
+    project: Model<Project>,
+    forwards: Vec<SshPortForward>,
+    candidates: Vec<StringMatchCandidate>,
+    /// The forward typed into the query, which is listed before the existing ones.
+    new_forward: Option<SshPortForward>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PortForwardsDelegate {
+    fn new(
+        port_forwards: WeakView<PortForwards>,
+        project: Model<Project>,
+        cx: &AppContext,
+    ) -> Self {
+        let forwards = project.read(cx).ssh_port_forwards();
+        let candidates = forwards
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, forward)| {
+                StringMatchCandidate::new(candidate_id, forward.to_string())
+            })
+            .collect();
+
+        Self {
+            port_forwards,
+            project,
+            forwards,
+            candidates,
+            new_forward: None,
+            matches: Vec::new(),
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for PortForwardsDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Forward a port (3000, 8080:3000, R9000)...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No ports are forwarded".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.new_forward.iter().count() + self.matches.len()
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let forward_ix = match self.new_forward {
+            Some(forward) if self.selected_index == 0 => {
+                self.project
+                    .update(cx, |project, cx| project.forward_ssh_port(forward, cx))
+                    .detach_and_prompt_err("Failed to forward port", cx, |_, _| None);
+                None
+            }
+            Some(_) => self.selected_index.checked_sub(1),
+            None => Some(self.selected_index),
+        };
+        if let Some(mat) = forward_ix.and_then(|ix| self.matches.get(ix)) {
+            let forward = self.forwards[mat.candidate_id];
+            self.project
+                .update(cx, |project, cx| {
+                    project.cancel_ssh_port_forward(forward, cx)
+                })
+                .detach_and_prompt_err("Failed to stop forwarding port", cx, |_, _| None);
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.port_forwards
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.new_forward = query
+            .parse::<SshPortForward>()
+            .ok()
+            .filter(|forward| !self.forwards.contains(forward));
+
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+
+        let ix = match self.new_forward {
+            Some(forward) if ix == 0 => {
+                return Some(
+                    item.start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                        .child(Label::new(format!("Forward {forward}"))),
+                );
+            }
+            Some(_) => ix - 1,
+            None => ix,
+        };
+        let mat = &self.matches[ix];
+        Some(
+            item.child(HighlightedLabel::new(
+                mat.string.clone(),
+                mat.positions.clone(),
+            ))
+            .end_slot(
+                Label::new("Stop Forwarding")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            ),
+        )
+    }
+}
@@ -1,5 +1,6 @@
 mod dev_servers;
 pub mod disconnected_overlay;
+mod port_forwards;
 mod ssh_connections;
 mod ssh_remotes;
 pub use ssh_connections::open_ssh_project;
@@ -18,6 +19,7 @@ use picker::{
     highlighted_match_with_paths::{HighlightedMatchWithPaths, HighlightedText},
     Picker, PickerDelegate,
 };
+pub use port_forwards::PortForwards;
 use rpc::proto::DevServerStatus;
 use serde::Deserialize;
 use settings::Settings;
@@ -47,13 +49,14 @@ fn default_create_new_window() -> bool {
 }
 
 gpui::impl_actions!(projects, [OpenRecent]);
-gpui::actions!(projects, [OpenRemote]);
+gpui::actions!(projects, [OpenRemote, ForwardPort]);
 
 pub fn init(cx: &mut AppContext) {
     SshSettings::register(cx);
     cx.observe_new_views(RecentProjects::register).detach();
     cx.observe_new_views(DevServerProjects::register).detach();
     cx.observe_new_views(DisconnectedOverlay::register).detach();
+    cx.observe_new_views(PortForwards::register).detach();
 }
 
 pub struct RecentProjects {
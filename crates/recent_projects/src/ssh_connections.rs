@@ -1,4 +1,8 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use anyhow::Result;
 use auto_update::AutoUpdater;
@@ -11,7 +15,7 @@ use gpui::{
     Transformation, View,
 };
 use release_channel::{AppVersion, ReleaseChannel};
-use remote::{SshConnectionOptions, SshPlatform, SshSession};
+use remote::{SshConnectionOptions, SshPlatform, SshPortForward, SshSession};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -45,6 +49,9 @@ pub struct SshConnection {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub port: Option<u16>,
     pub projects: Vec<SshProject>,
+    /// The ports to forward once connected.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub port_forwards: Vec<SshPortForwardSetting>,
 }
 impl From<SshConnection> for SshConnectionOptions {
     fn from(val: SshConnection) -> Self {
@@ -53,6 +60,27 @@ impl From<SshConnection> for SshConnectionOptions {
             username: val.username,
             port: val.port,
             password: None,
+            port_forwards: val.port_forwards.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SshPortForwardSetting {
+    pub local_port: u16,
+    pub remote_port: u16,
+    /// Whether connections to the port on the remote host are forwarded to the local port,
+    /// instead of the other way around.
+    #[serde(default)]
+    pub reverse: bool,
+}
+
+impl From<SshPortForwardSetting> for SshPortForward {
+    fn from(val: SshPortForwardSetting) -> Self {
+        if val.reverse {
+            SshPortForward::remote(val.remote_port, val.local_port)
+        } else {
+            SshPortForward::local(val.local_port, val.remote_port)
         }
     }
 }
@@ -210,7 +238,9 @@ impl ModalView for SshConnectionModal {}
 pub struct SshClientDelegate {
     window: AnyWindowHandle,
     ui: View<SshPrompt>,
-    known_password: Option<String>,
+    /// The password of the connection, which is remembered once it has been typed, so that the
+    /// user isn't asked for it again when reconnecting after the connection prompt is gone.
+    known_password: Arc<Mutex<Option<String>>>,
 }
 
 impl remote::SshClientDelegate for SshClientDelegate {
@@ -220,17 +250,28 @@ impl remote::SshClientDelegate for SshClientDelegate {
         cx: &mut AsyncAppContext,
     ) -> oneshot::Receiver<Result<String>> {
         let (tx, rx) = oneshot::channel();
-        let mut known_password = self.known_password.clone();
-        if let Some(password) = known_password.take() {
+        let known_password = self.known_password.lock().unwrap().clone();
+        if let Some(password) = known_password {
             tx.send(Ok(password)).ok();
         } else {
+            let (prompt_tx, prompt_rx) = oneshot::channel();
             self.window
                 .update(cx, |_, cx| {
                     self.ui.update(cx, |modal, cx| {
-                        modal.set_prompt(prompt, tx, cx);
+                        modal.set_prompt(prompt, prompt_tx, cx);
                     })
                 })
                 .ok();
+            let known_password = self.known_password.clone();
+            cx.background_executor()
+                .spawn(async move {
+                    let password = prompt_rx.await.unwrap_or_else(|error| Err(error.into()));
+                    if let Ok(password) = &password {
+                        *known_password.lock().unwrap() = Some(password.clone());
+                    }
+                    tx.send(password).ok();
+                })
+                .detach();
         }
         rx
     }
@@ -327,7 +368,7 @@ pub fn connect_over_ssh(
     cx: &mut WindowContext,
 ) -> Task<Result<Arc<SshSession>>> {
     let window = cx.window_handle();
-    let known_password = connection_options.password.clone();
+    let known_password = Arc::new(Mutex::new(connection_options.password.clone()));
 
     cx.spawn(|mut cx| async move {
         remote::SshSession::client(
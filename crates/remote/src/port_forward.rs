@@ -0,0 +1,167 @@
+use anyhow::{anyhow, Context as _, Result};
+use std::{fmt, str::FromStr};
+
+/// The side of the SSH connection on which a forwarded port is listened on.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SshPortForwardKind {
+    /// Connections to the local port are forwarded to the port on the remote host (`ssh -L`).
+    Local,
+    /// Connections to the port on the remote host are forwarded to the local port (`ssh -R`).
+    Remote,
+}
+
+/// A port forwarded over the SSH connection of a session, between the loopback interfaces of
+/// the local machine and the remote host.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SshPortForward {
+    pub kind: SshPortForwardKind,
+    pub local_port: u16,
+    pub remote_port: u16,
+}
+
+impl SshPortForward {
+    pub fn local(local_port: u16, remote_port: u16) -> Self {
+        Self {
+            kind: SshPortForwardKind::Local,
+            local_port,
+            remote_port,
+        }
+    }
+
+    pub fn remote(remote_port: u16, local_port: u16) -> Self {
+        Self {
+            kind: SshPortForwardKind::Remote,
+            local_port,
+            remote_port,
+        }
+    }
+
+    /// The `ssh` arguments that set up the forward.
+    pub(crate) fn ssh_args(&self) -> [String; 2] {
+        match self.kind {
+            SshPortForwardKind::Local => [
+                "-L".to_string(),
+                format!("{}:localhost:{}", self.local_port, self.remote_port),
+            ],
+            SshPortForwardKind::Remote => [
+                "-R".to_string(),
+                format!("{}:localhost:{}", self.remote_port, self.local_port),
+            ],
+        }
+    }
+}
+
+/// Parses forwards written like `ssh`'s, as the port to listen on, optionally followed by the
+/// port to forward connections to when it differs: `3000`, `8080:3000`, and `R9000:9001` for a
+/// forward from the remote host.
+impl FromStr for SshPortForward {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (kind, ports) = if let Some(ports) = s.strip_prefix(['R', 'r']) {
+            (SshPortForwardKind::Remote, ports)
+        } else {
+            let ports = s.strip_prefix(['L', 'l']).unwrap_or(s);
+            (SshPortForwardKind::Local, ports)
+        };
+        let (listen_port, target_port) = match ports.split_once(':') {
+            Some((listen_port, target_port)) => {
+                (parse_port(listen_port)?, parse_port(target_port)?)
+            }
+            None => {
+                let port = parse_port(ports)?;
+                (port, port)
+            }
+        };
+        Ok(match kind {
+            SshPortForwardKind::Local => Self::local(listen_port, target_port),
+            SshPortForwardKind::Remote => Self::remote(listen_port, target_port),
+        })
+    }
+}
+
+fn parse_port(port: &str) -> Result<u16> {
+    let port = port
+        .trim()
+        .parse::<u16>()
+        .with_context(|| format!("invalid port {port:?}"))?;
+    if port == 0 {
+        return Err(anyhow!("invalid port 0"));
+    }
+    Ok(port)
+}
+
+impl fmt::Display for SshPortForward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SshPortForwardKind::Local => write!(
+                f,
+                "localhost:{} → remote:{}",
+                self.local_port, self.remote_port
+            ),
+            SshPortForwardKind::Remote => write!(
+                f,
+                "remote:{} → localhost:{}",
+                self.remote_port, self.local_port
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_port_forward() {
+        assert_eq!(
+            "3000".parse::<SshPortForward>().unwrap(),
+            SshPortForward::local(3000, 3000)
+        );
+        assert_eq!(
+            "8080:3000".parse::<SshPortForward>().unwrap(),
+            SshPortForward::local(8080, 3000)
+        );
+        assert_eq!(
+            "L8080:3000".parse::<SshPortForward>().unwrap(),
+            SshPortForward::local(8080, 3000)
+        );
+        assert_eq!(
+            " l 8080 : 3000 ".parse::<SshPortForward>().unwrap(),
+            SshPortForward::local(8080, 3000)
+        );
+        assert_eq!(
+            "R9000".parse::<SshPortForward>().unwrap(),
+            SshPortForward::remote(9000, 9000)
+        );
+        assert_eq!(
+            "r9000:9001".parse::<SshPortForward>().unwrap(),
+            SshPortForward::remote(9000, 9001)
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_port_forward() {
+        for spec in [
+            "",
+            "L",
+            "R:",
+            "0",
+            "8080:0",
+            "65536",
+            "-1",
+            "80:",
+            ":80",
+            "80:81:82",
+            "X80",
+            "localhost:80",
+            "LR80",
+        ] {
+            assert!(
+                spec.parse::<SshPortForward>().is_err(),
+                "{spec:?} was parsed as a port forward"
+            );
+        }
+    }
+}
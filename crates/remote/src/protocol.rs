@@ -7,6 +7,10 @@ use std::mem::size_of;
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct MessageId(pub u32);
 
+/// The exit code of a remote server proxy that was asked to reconnect to a server that is no
+/// longer running.
+pub const SERVER_NOT_RUNNING_EXIT_CODE: i32 = 90;
+
 pub type MessageLen = u32;
 pub const MESSAGE_LEN_SIZE: usize = size_of::<MessageLen>();
 
@@ -1,5 +1,9 @@
 pub mod json_log;
+mod port_forward;
 pub mod protocol;
 pub mod ssh_session;
 
-pub use ssh_session::{SshClientDelegate, SshConnectionOptions, SshPlatform, SshSession};
+pub use port_forward::{SshPortForward, SshPortForwardKind};
+pub use ssh_session::{
+    SshClientDelegate, SshConnectionOptions, SshConnectionStatus, SshPlatform, SshSession,
+};
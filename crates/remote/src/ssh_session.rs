@@ -1,7 +1,9 @@
 use crate::{
     json_log::LogRecord,
+    port_forward::SshPortForward,
     protocol::{
         message_len_from_buffer, read_message_with_len, write_message, MessageId, MESSAGE_LEN_SIZE,
+        SERVER_NOT_RUNNING_EXIT_CODE,
     },
 };
 use anyhow::{anyhow, Context as _, Result};
@@ -11,14 +13,17 @@ use futures::{
     future::{BoxFuture, LocalBoxFuture},
     select_biased, AsyncReadExt as _, AsyncWriteExt as _, Future, FutureExt as _, StreamExt as _,
 };
-use gpui::{AppContext, AsyncAppContext, Model, SemanticVersion, WeakModel};
+use gpui::{
+    AppContext, AsyncAppContext, BackgroundExecutor, Model, SemanticVersion, Task, WeakModel,
+};
 use parking_lot::Mutex;
+use postage::watch;
 use rpc::{
     proto::{
         self, build_typed_envelope, AnyTypedEnvelope, Envelope, EnvelopedMessage, PeerId,
         ProtoClient, RequestMessage,
     },
-    TypedEnvelope,
+    ErrorExt as _, RpcError, TypedEnvelope,
 };
 use smol::{
     fs,
@@ -26,15 +31,18 @@ use smol::{
 };
 use std::{
     any::TypeId,
+    collections::VecDeque,
     ffi::OsStr,
+    fmt, iter, mem,
     path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicU32, Ordering::SeqCst},
-        Arc,
+        atomic::{AtomicU32, AtomicUsize, Ordering::SeqCst},
+        Arc, Weak,
     },
-    time::Instant,
+    time::{Duration, Instant},
 };
 use tempfile::TempDir;
+use util::ResultExt as _;
 
 #[derive(Clone)]
 pub struct SshSocket {
@@ -42,12 +50,55 @@ pub struct SshSocket {
     socket_path: PathBuf,
 }
 
+/// How often the client checks that the remote server is still responding.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+/// How long the client waits for the remote server to respond to a heartbeat.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+/// The number of heartbeats in a row that can go unanswered before the connection is considered
+/// to be lost.
+const MAX_MISSED_HEARTBEATS: usize = 3;
+/// How long the client waits for the remote server to respond after starting a proxy to it.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(20);
+const INITIAL_RECONNECTION_DELAY: Duration = Duration::from_millis(500);
+const MAX_RECONNECTION_DELAY: Duration = Duration::from_secs(10);
+const MAX_RECONNECTION_ATTEMPTS: usize = 10;
+
+/// The state of the connection between the client of an SSH session and its remote server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SshConnectionStatus {
+    Connected,
+    /// The connection was lost, and the client is connecting to the remote server again. The
+    /// messages sent in the meantime are delivered once it has.
+    Reconnecting,
+    /// The client gave up on reconnecting to the remote server.
+    Disconnected,
+}
+
+/// A session between Zed and a remote server, which exchange messages through a proxy process
+/// that the client starts on the remote machine over SSH.
+///
+/// The remote server outlives the proxy, so when the connection is lost, the client connects to
+/// it again through a new proxy. Every message is kept until the other side acknowledges
+/// receiving it, and the ones that weren't are sent again after reconnecting, so neither side
+/// misses any. If the remote server is no longer running by then, a new one is started, and the
+/// session starts over with it.
 pub struct SshSession {
     next_message_id: AtomicU32,
+    /// The id of the last message received from the other side, or zero if none has been yet.
+    last_received_id: AtomicU32,
     response_channels: ResponseChannels,
-    outgoing_tx: mpsc::UnboundedSender<Envelope>,
-    spawn_process_tx: mpsc::UnboundedSender<SpawnRequest>,
-    client_socket: Option<SshSocket>,
+    outgoing: Mutex<Outgoing>,
+    incoming_task: Mutex<Option<Task<()>>>,
+    connection: Mutex<Option<Arc<dyn SshConnection>>>,
+    maintain_connection_task: Mutex<Option<Task<()>>>,
+    status: (
+        Mutex<watch::Sender<SshConnectionStatus>>,
+        watch::Receiver<SshConnectionStatus>,
+    ),
+    reconnections: AtomicUsize,
+    server_restarts: AtomicUsize,
+    /// The ports that are forwarded over the SSH connection, again after reconnecting.
+    port_forwards: Mutex<Vec<SshPortForward>>,
     message_handlers: Mutex<
         HashMap<
             TypeId,
@@ -64,18 +115,72 @@ pub struct SshSession {
     >,
 }
 
+struct Outgoing {
+    tx: mpsc::UnboundedSender<Envelope>,
+    /// The messages that the other side hasn't acknowledged receiving yet, in the order in which
+    /// they were sent.
+    unacknowledged: VecDeque<Envelope>,
+}
+
 struct SshClientState {
     socket: SshSocket,
-    _master_process: process::Child,
+    master_process: process::Child,
     _temp_dir: TempDir,
 }
 
+/// What the client needs to start a proxy to the remote server, again after reconnecting.
+struct ProxyParams {
+    connection_options: SshConnectionOptions,
+    /// Connects to the remote machine again, after the connection to it was lost.
+    connect: ConnectFn,
+    remote_binary_path: PathBuf,
+    /// Identifies the remote server, so that the proxies started after reconnecting connect to
+    /// the same one.
+    server_id: String,
+}
+
+/// A connection to the remote machine, over which the client starts proxies to the remote server
+/// and forwards ports.
+trait SshConnection: Send + Sync {
+    fn socket(&self) -> &SshSocket;
+
+    /// Starts a proxy to the remote server, like [`SshClientState::start_proxy`] does over SSH.
+    fn start_proxy(
+        &self,
+        params: &ProxyParams,
+        reconnect: bool,
+        executor: &BackgroundExecutor,
+    ) -> Result<(
+        mpsc::UnboundedReceiver<Envelope>,
+        mpsc::UnboundedSender<Envelope>,
+        ProxyExit,
+    )>;
+
+    fn forward_port(&self, forward: SshPortForward) -> LocalBoxFuture<'static, Result<()>>;
+
+    fn cancel_port_forward(&self, forward: SshPortForward) -> LocalBoxFuture<'static, Result<()>>;
+}
+
+/// A proxy that was asked to reconnect to the remote server found that it is no longer running.
+#[derive(Debug)]
+struct ServerNotRunning;
+
+impl fmt::Display for ServerNotRunning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the remote server is no longer running")
+    }
+}
+
+impl std::error::Error for ServerNotRunning {}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshConnectionOptions {
     pub host: String,
     pub username: Option<String>,
     pub port: Option<u16>,
     pub password: Option<String>,
+    /// The ports that are forwarded once connected.
+    pub port_forwards: Vec<SshPortForward>,
 }
 
 impl SshConnectionOptions {
@@ -115,11 +220,6 @@ impl SshConnectionOptions {
     }
 }
 
-struct SpawnRequest {
-    command: String,
-    process_tx: oneshot::Sender<process::Child>,
-}
-
 #[derive(Copy, Clone, Debug)]
 pub struct SshPlatform {
     pub os: &'static str,
@@ -143,13 +243,19 @@ pub trait SshClientDelegate {
 
 type ResponseChannels = Mutex<HashMap<MessageId, oneshot::Sender<(Envelope, oneshot::Sender<()>)>>>;
 
+type ProxyExit = Task<Result<Option<i32>>>;
+
+type ConnectFn =
+    Box<dyn Fn(AsyncAppContext) -> LocalBoxFuture<'static, Result<Arc<dyn SshConnection>>>>;
+
 impl SshSession {
     pub async fn client(
         connection_options: SshConnectionOptions,
         delegate: Arc<dyn SshClientDelegate>,
         cx: &mut AsyncAppContext,
     ) -> Result<Arc<Self>> {
-        let client_state = SshClientState::new(connection_options, delegate.clone(), cx).await?;
+        let client_state =
+            SshClientState::new(connection_options.clone(), delegate.clone(), cx).await?;
 
         let platform = client_state.query_platform().await?;
         let (local_binary_path, version) = delegate.get_server_binary(platform, cx).await??;
@@ -163,119 +269,55 @@ impl SshSession {
                 cx,
             )
             .await?;
+        run_cmd(
+            client_state
+                .socket
+                .ssh_command(&remote_binary_path)
+                .arg("version"),
+        )
+        .await?;
 
-        let (spawn_process_tx, mut spawn_process_rx) = mpsc::unbounded::<SpawnRequest>();
-        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<Envelope>();
-        let (incoming_tx, incoming_rx) = mpsc::unbounded::<Envelope>();
-
-        let socket = client_state.socket.clone();
-        run_cmd(socket.ssh_command(&remote_binary_path).arg("version")).await?;
-
-        let mut remote_server_child = socket
-            .ssh_command(&format!(
-                "RUST_LOG={} {:?} run",
-                std::env::var("RUST_LOG").unwrap_or(String::new()),
-                remote_binary_path,
-            ))
-            .spawn()
-            .context("failed to spawn remote server")?;
-        let mut child_stderr = remote_server_child.stderr.take().unwrap();
-        let mut child_stdout = remote_server_child.stdout.take().unwrap();
-        let mut child_stdin = remote_server_child.stdin.take().unwrap();
+        let params = ProxyParams {
+            connection_options: connection_options.clone(),
+            connect: Box::new(move |mut cx| {
+                let connection_options = connection_options.clone();
+                let delegate = delegate.clone();
+                async move {
+                    let client_state =
+                        SshClientState::new(connection_options, delegate, &mut cx).await?;
+                    Ok(Arc::new(client_state) as Arc<dyn SshConnection>)
+                }
+                .boxed_local()
+            }),
+            remote_binary_path,
+            server_id: uuid::Uuid::new_v4().to_string(),
+        };
+        Self::start(Arc::new(client_state), params, cx).await
+    }
 
+    /// Starts a new remote server through a proxy over the given connection, and keeps the
+    /// session connected to it.
+    async fn start(
+        connection: Arc<dyn SshConnection>,
+        params: ProxyParams,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Arc<Self>> {
         let executor = cx.background_executor().clone();
-        executor.clone().spawn(async move {
-            let mut stdin_buffer = Vec::new();
-            let mut stdout_buffer = Vec::new();
-            let mut stderr_buffer = Vec::new();
-            let mut stderr_offset = 0;
-
-            loop {
-                stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
-                stderr_buffer.resize(stderr_offset + 1024, 0);
-
-                select_biased! {
-                    outgoing = outgoing_rx.next().fuse() => {
-                        let Some(outgoing) = outgoing else {
-                            return anyhow::Ok(());
-                        };
-
-                        write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
-                    }
-
-                    request = spawn_process_rx.next().fuse() => {
-                        let Some(request) = request else {
-                            return Ok(());
-                        };
-
-                        log::info!("spawn process: {:?}", request.command);
-                        let child = client_state.socket
-                            .ssh_command(&request.command)
-                            .spawn()
-                            .context("failed to create channel")?;
-                        request.process_tx.send(child).ok();
-                    }
-
-                    result = child_stdout.read(&mut stdout_buffer).fuse() => {
-                        match result {
-                            Ok(len) => {
-                                if len == 0 {
-                                    child_stdin.close().await?;
-                                    let status = remote_server_child.status().await?;
-                                    if !status.success() {
-                                        log::info!("channel exited with status: {status:?}");
-                                    }
-                                    return Ok(());
-                                }
-
-                                if len < stdout_buffer.len() {
-                                    child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
-                                }
-
-                                let message_len = message_len_from_buffer(&stdout_buffer);
-                                match read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len).await {
-                                    Ok(envelope) => {
-                                        incoming_tx.unbounded_send(envelope).ok();
-                                    }
-                                    Err(error) => {
-                                        log::error!("error decoding message {error:?}");
-                                    }
-                                }
-                            }
-                            Err(error) => {
-                                Err(anyhow!("error reading stdout: {error:?}"))?;
-                            }
-                        }
-                    }
+        let (incoming_rx, outgoing_tx, mut proxy_exit) =
+            connection.start_proxy(&params, false, &executor)?;
+        let this = cx.update(|cx| Self::new(incoming_rx, outgoing_tx, Some(connection), cx))?;
+        this.wait_for_server(&mut proxy_exit, &executor).await?;
+        for forward in &params.connection_options.port_forwards {
+            this.forward_port(*forward).await.log_err();
+        }
 
-                    result = child_stderr.read(&mut stderr_buffer[stderr_offset..]).fuse() => {
-                        match result {
-                            Ok(len) => {
-                                stderr_offset += len;
-                                let mut start_ix = 0;
-                                while let Some(ix) = stderr_buffer[start_ix..stderr_offset].iter().position(|b| b == &b'\n') {
-                                    let line_ix = start_ix + ix;
-                                    let content = &stderr_buffer[start_ix..line_ix];
-                                    start_ix = line_ix + 1;
-                                    if let Ok(record) = serde_json::from_slice::<LogRecord>(&content) {
-                                        record.log(log::logger())
-                                    } else {
-                                        eprintln!("(remote) {}", String::from_utf8_lossy(content));
-                                    }
-                                }
-                                stderr_buffer.drain(0..start_ix);
-                                stderr_offset -= start_ix;
-                            }
-                            Err(error) => {
-                                Err(anyhow!("error reading stderr: {error:?}"))?;
-                            }
-                        }
-                    }
-                }
-            }
-        }).detach();
+        let maintain_connection = cx.spawn({
+            let this = Arc::downgrade(&this);
+            |cx| Self::maintain_connection(this, params, proxy_exit, cx)
+        });
+        *this.maintain_connection_task.lock() = Some(maintain_connection);
 
-        cx.update(|cx| Self::new(incoming_rx, outgoing_tx, spawn_process_tx, Some(socket), cx))
+        Ok(this)
     }
 
     pub fn server(
@@ -283,8 +325,7 @@ impl SshSession {
         outgoing_tx: mpsc::UnboundedSender<Envelope>,
         cx: &AppContext,
     ) -> Arc<SshSession> {
-        let (tx, _rx) = mpsc::unbounded();
-        Self::new(incoming_rx, outgoing_tx, tx, None, cx)
+        Self::new(incoming_rx, outgoing_tx, None, cx)
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -294,84 +335,372 @@ impl SshSession {
     ) -> (Arc<Self>, Arc<Self>) {
         let (server_to_client_tx, server_to_client_rx) = mpsc::unbounded();
         let (client_to_server_tx, client_to_server_rx) = mpsc::unbounded();
-        let (tx, _rx) = mpsc::unbounded();
         (
-            client_cx.update(|cx| {
-                Self::new(
-                    server_to_client_rx,
-                    client_to_server_tx,
-                    tx.clone(),
-                    None, // todo()
-                    cx,
-                )
-            }),
-            server_cx.update(|cx| {
-                Self::new(
-                    client_to_server_rx,
-                    server_to_client_tx,
-                    tx.clone(),
-                    None,
-                    cx,
-                )
-            }),
+            client_cx.update(|cx| Self::new(server_to_client_rx, client_to_server_tx, None, cx)),
+            server_cx.update(|cx| Self::new(client_to_server_rx, server_to_client_tx, None, cx)),
         )
     }
 
+    /// Drops the connection between two fake sessions, losing the messages that are on their way.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn fake_disconnect(client: &Arc<Self>, server: &Arc<Self>) {
+        for session in [client, server] {
+            session.incoming_task.lock().take();
+            session.outgoing.lock().tx = mpsc::unbounded().0;
+        }
+        client.set_status(SshConnectionStatus::Reconnecting);
+    }
+
+    /// Connects two fake sessions again after [`Self::fake_disconnect`].
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn fake_reconnect(
+        client: &Arc<Self>,
+        server: &Arc<Self>,
+        client_cx: &mut gpui::TestAppContext,
+        server_cx: &mut gpui::TestAppContext,
+    ) {
+        let (server_to_client_tx, server_to_client_rx) = mpsc::unbounded();
+        let (client_to_server_tx, client_to_server_rx) = mpsc::unbounded();
+        server_cx.update(|cx| server.reconnect(client_to_server_rx, server_to_client_tx, cx));
+        client_cx.update(|cx| client.reconnect(server_to_client_rx, client_to_server_tx, cx));
+        client.reconnections.fetch_add(1, SeqCst);
+        client.set_status(SshConnectionStatus::Connected);
+    }
+
+    /// Connects a fake client session to a new server session after [`Self::fake_disconnect`],
+    /// as if the previous remote server had stopped running in the meantime.
+    ///
+    /// The handlers of the new server session have to be added before the client is given a
+    /// chance to run.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn fake_restart(
+        client: &Arc<Self>,
+        client_cx: &mut gpui::TestAppContext,
+        server_cx: &mut gpui::TestAppContext,
+    ) -> Arc<Self> {
+        let (server_to_client_tx, server_to_client_rx) = mpsc::unbounded();
+        let (client_to_server_tx, client_to_server_rx) = mpsc::unbounded();
+        client.reset();
+        client_cx.update(|cx| client.reconnect(server_to_client_rx, client_to_server_tx, cx));
+        client.server_restarts.fetch_add(1, SeqCst);
+        client.set_status(SshConnectionStatus::Connected);
+        server_cx.update(|cx| Self::new(client_to_server_rx, server_to_client_tx, None, cx))
+    }
+
     fn new(
-        mut incoming_rx: mpsc::UnboundedReceiver<Envelope>,
+        incoming_rx: mpsc::UnboundedReceiver<Envelope>,
         outgoing_tx: mpsc::UnboundedSender<Envelope>,
-        spawn_process_tx: mpsc::UnboundedSender<SpawnRequest>,
-        client_socket: Option<SshSocket>,
+        connection: Option<Arc<dyn SshConnection>>,
         cx: &AppContext,
     ) -> Arc<SshSession> {
+        let (status_tx, status_rx) = watch::channel_with(SshConnectionStatus::Connected);
         let this = Arc::new(Self {
-            next_message_id: AtomicU32::new(0),
+            next_message_id: AtomicU32::new(1),
+            last_received_id: AtomicU32::new(0),
             response_channels: ResponseChannels::default(),
-            outgoing_tx,
-            spawn_process_tx,
-            client_socket,
+            outgoing: Mutex::new(Outgoing {
+                tx: outgoing_tx,
+                unacknowledged: VecDeque::new(),
+            }),
+            incoming_task: Mutex::new(None),
+            connection: Mutex::new(connection),
+            maintain_connection_task: Mutex::new(None),
+            status: (Mutex::new(status_tx), status_rx),
+            reconnections: AtomicUsize::new(0),
+            server_restarts: AtomicUsize::new(0),
+            port_forwards: Mutex::new(Vec::new()),
             message_handlers: Default::default(),
         });
+        this.start_handling_messages(incoming_rx, cx);
+        this
+    }
 
-        cx.spawn(|cx| {
-            let this = this.clone();
-            async move {
-                let peer_id = PeerId { owner_id: 0, id: 0 };
-                while let Some(incoming) = incoming_rx.next().await {
-                    if let Some(request_id) = incoming.responding_to {
-                        let request_id = MessageId(request_id);
-                        let sender = this.response_channels.lock().remove(&request_id);
-                        if let Some(sender) = sender {
-                            let (tx, rx) = oneshot::channel();
-                            if incoming.payload.is_some() {
-                                sender.send((incoming, tx)).ok();
-                            }
-                            rx.await.ok();
-                        }
-                    } else if let Some(envelope) =
-                        build_typed_envelope(peer_id, Instant::now(), incoming)
+    /// Handles the messages received over a connection, in place of the ones received over the
+    /// previous connection.
+    fn start_handling_messages(
+        self: &Arc<Self>,
+        mut incoming_rx: mpsc::UnboundedReceiver<Envelope>,
+        cx: &AppContext,
+    ) {
+        let this = Arc::downgrade(self);
+        let task = cx.spawn(|cx| async move {
+            let peer_id = PeerId { owner_id: 0, id: 0 };
+            while let Some(mut incoming) = incoming_rx.next().await {
+                let Some(this) = this.upgrade() else {
+                    break;
+                };
+
+                if let Some(ack_id) = incoming.ack_id {
+                    let mut outgoing = this.outgoing.lock();
+                    while outgoing
+                        .unacknowledged
+                        .front()
+                        .map_or(false, |envelope| envelope.id <= ack_id)
                     {
-                        log::debug!(
-                            "ssh message received. name:{}",
-                            envelope.payload_type_name()
-                        );
-                        let type_id = envelope.payload_type_id();
-                        let handler = this.message_handlers.lock().get(&type_id).cloned();
-                        if let Some(handler) = handler {
-                            if let Some(future) = handler(envelope, this.clone(), cx.clone()) {
-                                future.await.ok();
-                            } else {
-                                this.message_handlers.lock().remove(&type_id);
-                            }
+                        outgoing.unacknowledged.pop_front();
+                    }
+                }
+
+                // After reconnecting, the other side sends the messages that it doesn't know
+                // we've received again.
+                if incoming.id <= this.last_received_id.load(SeqCst) {
+                    continue;
+                }
+                this.last_received_id.store(incoming.id, SeqCst);
+
+                if let Some(request_id) = incoming.responding_to {
+                    let request_id = MessageId(request_id);
+                    let sender = this.response_channels.lock().remove(&request_id);
+                    if let Some(sender) = sender {
+                        let (tx, rx) = oneshot::channel();
+                        if incoming.payload.is_some() {
+                            sender.send((incoming, tx)).ok();
+                        }
+                        rx.await.ok();
+                    }
+                } else if let Some(proto::envelope::Payload::Ping(_)) = incoming.payload {
+                    this.send_envelope(
+                        proto::Ack {}.into_envelope(0, Some(incoming.id), None),
+                        None,
+                    );
+                } else {
+                    // Messages aren't forwarded through a server over ssh, so the handlers
+                    // that expect to know their original sender get the other end of the
+                    // session.
+                    incoming.original_sender_id.get_or_insert(peer_id);
+                    let Some(envelope) = build_typed_envelope(peer_id, Instant::now(), incoming)
+                    else {
+                        continue;
+                    };
+                    log::debug!(
+                        "ssh message received. name:{}",
+                        envelope.payload_type_name()
+                    );
+                    let type_id = envelope.payload_type_id();
+                    let handler = this.message_handlers.lock().get(&type_id).cloned();
+                    if let Some(handler) = handler {
+                        if let Some(future) = handler(envelope, this.clone(), cx.clone()) {
+                            // Handlers run concurrently, so that a slow request, such as one
+                            // waiting on a language server, doesn't hold up the messages
+                            // behind it.
+                            cx.spawn(|_| future).detach();
+                        } else {
+                            this.message_handlers.lock().remove(&type_id);
                         }
                     }
                 }
-                anyhow::Ok(())
             }
-        })
-        .detach();
+        });
+        *self.incoming_task.lock() = Some(task);
+    }
 
-        this
+    /// Continues the session over a new connection after the previous one was lost, sending the
+    /// messages that the other side hasn't acknowledged receiving again.
+    pub fn reconnect(
+        self: &Arc<Self>,
+        incoming_rx: mpsc::UnboundedReceiver<Envelope>,
+        outgoing_tx: mpsc::UnboundedSender<Envelope>,
+        cx: &AppContext,
+    ) {
+        self.start_handling_messages(incoming_rx, cx);
+        let ack_id = self.ack_id();
+        let mut outgoing = self.outgoing.lock();
+        for envelope in &outgoing.unacknowledged {
+            let mut envelope = envelope.clone();
+            envelope.ack_id = ack_id;
+            outgoing_tx.unbounded_send(envelope).ok();
+        }
+        outgoing.tx = outgoing_tx;
+    }
+
+    /// Forgets the messages exchanged with a remote server that is no longer running, before
+    /// connecting to the one started in its place. The requests that are still waiting for a
+    /// response fail.
+    fn reset(&self) {
+        let mut outgoing = self.outgoing.lock();
+        outgoing.unacknowledged.clear();
+        self.response_channels.lock().clear();
+        self.last_received_id.store(0, SeqCst);
+    }
+
+    pub fn status(&self) -> watch::Receiver<SshConnectionStatus> {
+        self.status.1.clone()
+    }
+
+    /// The number of times that the client connected to the remote server again after losing
+    /// the connection to it.
+    pub fn reconnections(&self) -> usize {
+        self.reconnections.load(SeqCst)
+    }
+
+    /// The number of times that the client started a new remote server, because the previous
+    /// one was no longer running when reconnecting to it.
+    pub fn server_restarts(&self) -> usize {
+        self.server_restarts.load(SeqCst)
+    }
+
+    fn set_status(&self, status: SshConnectionStatus) {
+        *self.status.0.lock().borrow_mut() = status;
+    }
+
+    /// Reconnects to the remote server whenever the connection to it is lost, either because the
+    /// proxy exited or because the remote server stopped responding to heartbeats.
+    async fn maintain_connection(
+        this: Weak<Self>,
+        params: ProxyParams,
+        mut proxy_exit: ProxyExit,
+        mut cx: AsyncAppContext,
+    ) {
+        loop {
+            let executor = cx.background_executor().clone();
+            select_biased! {
+                exit = (&mut proxy_exit).fuse() => {
+                    log::warn!("remote server proxy exited: {exit:?}");
+                }
+                _ = Self::missed_heartbeats(this.clone(), executor).fuse() => {
+                    log::warn!("remote server stopped responding to heartbeats");
+                }
+            }
+
+            let Some(session) = this.upgrade() else {
+                return;
+            };
+            session.set_status(SshConnectionStatus::Reconnecting);
+            drop(session);
+
+            match Self::reconnect_with_backoff(&this, &params, &mut cx).await {
+                Ok(exit) => proxy_exit = exit,
+                Err(error) => {
+                    log::error!(
+                        "failed to reconnect to {}: {error:?}",
+                        params.connection_options.connection_string()
+                    );
+                    if let Some(session) = this.upgrade() {
+                        // The forwards were closed along with the connection.
+                        session.port_forwards.lock().clear();
+                        session.set_status(SshConnectionStatus::Disconnected);
+                        session.response_channels.lock().clear();
+                    }
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Sends heartbeats to the remote server, returning once too many of them in a row have gone
+    /// unanswered.
+    async fn missed_heartbeats(this: Weak<Self>, executor: BackgroundExecutor) {
+        let mut missed_heartbeats = 0;
+        while missed_heartbeats < MAX_MISSED_HEARTBEATS {
+            executor.timer(HEARTBEAT_INTERVAL).await;
+            let Some(session) = this.upgrade() else {
+                return;
+            };
+            let ping = session.request(proto::Ping {});
+            drop(session);
+
+            select_biased! {
+                response = ping.fuse() => {
+                    if response.is_ok() {
+                        missed_heartbeats = 0;
+                    } else {
+                        missed_heartbeats += 1;
+                    }
+                }
+                _ = executor.timer(HEARTBEAT_TIMEOUT).fuse() => missed_heartbeats += 1,
+            }
+        }
+    }
+
+    async fn reconnect_with_backoff(
+        this: &Weak<Self>,
+        params: &ProxyParams,
+        cx: &mut AsyncAppContext,
+    ) -> Result<ProxyExit> {
+        let mut delays = reconnection_delays();
+        let mut attempt = 1;
+        loop {
+            match Self::try_reconnect(this, params, cx).await {
+                Ok(proxy_exit) => return Ok(proxy_exit),
+                Err(error) => {
+                    let Some(delay) = delays.next() else {
+                        return Err(error);
+                    };
+                    log::warn!("reconnection attempt {attempt} failed: {error:?}");
+                    cx.background_executor().timer(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    async fn try_reconnect(
+        this: &Weak<Self>,
+        params: &ProxyParams,
+        cx: &mut AsyncAppContext,
+    ) -> Result<ProxyExit> {
+        let connection = (params.connect)(cx.clone()).await?;
+        let this = this.upgrade().context("ssh session was dropped")?;
+        let executor = cx.background_executor().clone();
+
+        let (incoming_rx, outgoing_tx, mut proxy_exit) =
+            connection.start_proxy(params, true, &executor)?;
+        cx.update(|cx| this.resume(connection.clone(), incoming_rx, outgoing_tx, cx))?;
+        match this.wait_for_server(&mut proxy_exit, &executor).await {
+            Ok(()) => {
+                this.reconnections.fetch_add(1, SeqCst);
+            }
+            Err(error) if error.is::<ServerNotRunning>() => {
+                log::warn!("remote server is no longer running, starting a new one");
+                let (incoming_rx, outgoing_tx, new_proxy_exit) =
+                    connection.start_proxy(params, false, &executor)?;
+                proxy_exit = new_proxy_exit;
+                this.reset();
+                cx.update(|cx| this.resume(connection, incoming_rx, outgoing_tx, cx))?;
+                this.wait_for_server(&mut proxy_exit, &executor).await?;
+                this.server_restarts.fetch_add(1, SeqCst);
+            }
+            Err(error) => return Err(error),
+        }
+
+        // Forward the ports again over the new SSH connection.
+        let port_forwards = mem::take(&mut *this.port_forwards.lock());
+        for forward in port_forwards {
+            this.forward_port(forward).await.log_err();
+        }
+
+        this.set_status(SshConnectionStatus::Connected);
+        Ok(proxy_exit)
+    }
+
+    fn resume(
+        self: &Arc<Self>,
+        connection: Arc<dyn SshConnection>,
+        incoming_rx: mpsc::UnboundedReceiver<Envelope>,
+        outgoing_tx: mpsc::UnboundedSender<Envelope>,
+        cx: &AppContext,
+    ) {
+        *self.connection.lock() = Some(connection);
+        self.reconnect(incoming_rx, outgoing_tx, cx);
+    }
+
+    /// Waits for the remote server to respond through a proxy that was just started.
+    async fn wait_for_server(
+        &self,
+        proxy_exit: &mut ProxyExit,
+        executor: &BackgroundExecutor,
+    ) -> Result<()> {
+        let ping = self.request(proto::Ping {});
+        select_biased! {
+            response = ping.fuse() => response.map(drop),
+            exit = proxy_exit.fuse() => match exit? {
+                Some(SERVER_NOT_RUNNING_EXIT_CODE) => Err(ServerNotRunning.into()),
+                code => Err(anyhow!("remote server proxy exited with code {code:?}")),
+            },
+            _ = executor.timer(CONNECTION_TIMEOUT).fuse() => {
+                Err(anyhow!("timed out waiting for the remote server"))
+            }
+        }
     }
 
     pub fn request<T: RequestMessage>(
@@ -379,7 +708,7 @@ impl SshSession {
         payload: T,
     ) -> impl 'static + Future<Output = Result<T::Response>> {
         log::debug!("ssh request start. name:{}", T::NAME);
-        let response = self.request_dynamic(payload.into_envelope(0, None, None), "");
+        let response = self.request_dynamic(payload.into_envelope(0, None, None), T::NAME);
         async move {
             let response = response.await?;
             log::debug!("ssh request finish. name:{}", T::NAME);
@@ -394,37 +723,125 @@ impl SshSession {
 
     pub fn request_dynamic(
         &self,
-        mut envelope: proto::Envelope,
-        _request_type: &'static str,
+        envelope: proto::Envelope,
+        request_type: &'static str,
     ) -> impl 'static + Future<Output = Result<proto::Envelope>> {
-        envelope.id = self.next_message_id.fetch_add(1, SeqCst);
         let (tx, rx) = oneshot::channel();
-        self.response_channels
-            .lock()
-            .insert(MessageId(envelope.id), tx);
-        self.outgoing_tx.unbounded_send(envelope).ok();
-        async move { Ok(rx.await.context("connection lost")?.0) }
+        if *self.status.1.borrow() != SshConnectionStatus::Disconnected {
+            self.send_envelope(envelope, Some(tx));
+        }
+        async move {
+            let response = rx.await.context("connection lost")?.0;
+            if let Some(proto::envelope::Payload::Error(error)) = &response.payload {
+                return Err(RpcError::from_proto(error, request_type));
+            }
+            Ok(response)
+        }
     }
 
-    pub fn send_dynamic(&self, mut envelope: proto::Envelope) -> Result<()> {
+    /// Sends a message while the connection is lost too, as it is delivered after reconnecting.
+    pub fn send_dynamic(&self, envelope: proto::Envelope) -> Result<()> {
+        self.send_envelope(envelope, None);
+        Ok(())
+    }
+
+    /// Sends a message to the other side, keeping it until the other side acknowledges receiving
+    /// it.
+    fn send_envelope(
+        &self,
+        mut envelope: proto::Envelope,
+        response_tx: Option<oneshot::Sender<(Envelope, oneshot::Sender<()>)>>,
+    ) {
+        let mut outgoing = self.outgoing.lock();
         envelope.id = self.next_message_id.fetch_add(1, SeqCst);
-        self.outgoing_tx.unbounded_send(envelope)?;
+        envelope.ack_id = self.ack_id();
+        if let Some(response_tx) = response_tx {
+            self.response_channels
+                .lock()
+                .insert(MessageId(envelope.id), response_tx);
+        }
+        outgoing.unacknowledged.push_back(envelope.clone());
+        outgoing.tx.unbounded_send(envelope).ok();
+    }
+
+    fn ack_id(&self) -> Option<u32> {
+        Some(self.last_received_id.load(SeqCst)).filter(|id| *id != 0)
+    }
+
+    pub fn spawn_process(&self, command: String) -> Result<process::Child> {
+        let connection = self
+            .connection
+            .lock()
+            .clone()
+            .context("not connected over ssh")?;
+        log::info!("spawn process: {:?}", command);
+        connection
+            .socket()
+            .ssh_command(&command)
+            .spawn()
+            .context("failed to create channel")
+    }
+
+    /// Forwards a port over the SSH connection.
+    pub async fn forward_port(&self, forward: SshPortForward) -> Result<()> {
+        if self.port_forwards.lock().contains(&forward) {
+            return Ok(());
+        }
+        let connection = self
+            .connection
+            .lock()
+            .clone()
+            .context("not connected over ssh")?;
+        log::info!("forward port: {forward}");
+        connection
+            .forward_port(forward)
+            .await
+            .with_context(|| format!("failed to forward {forward}"))?;
+        self.port_forwards.lock().push(forward);
+        Ok(())
+    }
+
+    /// Stops forwarding a port that was forwarded with [`Self::forward_port`].
+    pub async fn cancel_port_forward(&self, forward: SshPortForward) -> Result<()> {
+        {
+            let mut port_forwards = self.port_forwards.lock();
+            let ix = port_forwards
+                .iter()
+                .position(|existing| *existing == forward)
+                .with_context(|| format!("{forward} is not forwarded"))?;
+            port_forwards.remove(ix);
+        }
+        let connection = self
+            .connection
+            .lock()
+            .clone()
+            .context("not connected over ssh")?;
+        log::info!("cancel port forward: {forward}");
+        connection
+            .cancel_port_forward(forward)
+            .await
+            .with_context(|| format!("failed to stop forwarding {forward}"))?;
         Ok(())
     }
 
-    pub async fn spawn_process(&self, command: String) -> process::Child {
-        let (process_tx, process_rx) = oneshot::channel();
-        self.spawn_process_tx
-            .unbounded_send(SpawnRequest {
-                command,
-                process_tx,
-            })
-            .ok();
-        process_rx.await.unwrap()
+    pub fn port_forwards(&self) -> Vec<SshPortForward> {
+        self.port_forwards.lock().clone()
+    }
+
+    /// The `user@host:port` string of the SSH connection, if this is the client's session.
+    pub fn connection_string(&self) -> Option<String> {
+        let connection = self.connection.lock();
+        Some(
+            connection
+                .as_ref()?
+                .socket()
+                .connection_options
+                .connection_string(),
+        )
     }
 
     pub fn ssh_args(&self) -> Vec<String> {
-        self.client_socket.as_ref().unwrap().ssh_args()
+        self.connection.lock().as_ref().unwrap().socket().ssh_args()
     }
 
     pub fn add_message_handler<M, E, H, F>(&self, entity: WeakModel<E>, handler: H)
@@ -462,11 +879,13 @@ impl SshSession {
                 Some(
                     handler(entity, *envelope, cx)
                         .then(move |result| async move {
-                            this.outgoing_tx.unbounded_send(result?.into_envelope(
-                                this.next_message_id.fetch_add(1, SeqCst),
-                                Some(request_id),
-                                None,
-                            ))?;
+                            let response = match result {
+                                Ok(response) => response.into_envelope(0, Some(request_id), None),
+                                Err(error) => {
+                                    error.to_proto().into_envelope(0, Some(request_id), None)
+                                }
+                            };
+                            this.send_envelope(response, None);
                             Ok(())
                         })
                         .boxed_local(),
@@ -506,9 +925,7 @@ impl SshClientState {
         delegate: Arc<dyn SshClientDelegate>,
         cx: &mut AsyncAppContext,
     ) -> Result<Self> {
-        use futures::{io::BufReader, AsyncBufReadExt as _};
-        use smol::{fs::unix::PermissionsExt as _, net::unix::UnixListener};
-        use util::ResultExt as _;
+        use askpass::{AskPassDelegate, AskPassSession};
 
         delegate.set_status(Some("connecting"), cx);
 
@@ -517,42 +934,22 @@ impl SshClientState {
             .prefix("zed-ssh-session")
             .tempdir()?;
 
-        // Create a domain socket listener to handle requests from the askpass program.
-        let askpass_socket = temp_dir.path().join("askpass.sock");
-        let listener =
-            UnixListener::bind(&askpass_socket).context("failed to create askpass socket")?;
-
+        let (askpass_delegate, mut askpass_prompts) = AskPassDelegate::new();
+        let askpass = AskPassSession::new(cx.background_executor(), askpass_delegate).await?;
         let askpass_task = cx.spawn(|mut cx| async move {
-            while let Ok((mut stream, _)) = listener.accept().await {
-                let mut buffer = Vec::new();
-                let mut reader = BufReader::new(&mut stream);
-                if reader.read_until(b'\0', &mut buffer).await.is_err() {
-                    buffer.clear();
-                }
-                let password_prompt = String::from_utf8_lossy(&buffer);
+            while let Some(prompt) = askpass_prompts.next().await {
                 if let Some(password) = delegate
-                    .ask_password(password_prompt.to_string(), &mut cx)
+                    .ask_password(prompt.prompt.clone(), &mut cx)
                     .await
                     .context("failed to get ssh password")
                     .and_then(|p| p)
                     .log_err()
                 {
-                    stream.write_all(password.as_bytes()).await.log_err();
+                    prompt.respond(password);
                 }
             }
         });
 
-        // Create an askpass script that communicates back to this process.
-        let askpass_script = format!(
-            "{shebang}\n{print_args} | nc -U {askpass_socket} 2> /dev/null \n",
-            askpass_socket = askpass_socket.display(),
-            print_args = "printf '%s\\0' \"$@\"",
-            shebang = "#!/bin/sh",
-        );
-        let askpass_script_path = temp_dir.path().join("askpass.sh");
-        fs::write(&askpass_script_path, askpass_script).await?;
-        fs::set_permissions(&askpass_script_path, std::fs::Permissions::from_mode(0o755)).await?;
-
         // Start the master SSH process, which does not do anything except for establish
         // the connection and keep it open, allowing other ssh commands to reuse it
         // via a control socket.
@@ -562,7 +959,7 @@ impl SshClientState {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .env("SSH_ASKPASS_REQUIRE", "force")
-            .env("SSH_ASKPASS", &askpass_script_path)
+            .env("SSH_ASKPASS", askpass.script_path())
             .args(["-N", "-o", "ControlMaster=yes", "-o"])
             .arg(format!("ControlPath={}", socket_path.display()))
             .arg(&url)
@@ -573,6 +970,7 @@ impl SshClientState {
         let stdout = master_process.stdout.as_mut().unwrap();
         let mut output = Vec::new();
         stdout.read_to_end(&mut output).await?;
+        drop(askpass);
         drop(askpass_task);
 
         if master_process.try_status()?.is_some() {
@@ -590,11 +988,138 @@ impl SshClientState {
                 connection_options,
                 socket_path,
             },
-            _master_process: master_process,
+            master_process,
             _temp_dir: temp_dir,
         })
     }
 
+    /// Starts a proxy on the remote machine that relays messages between the session and the
+    /// remote server. Unless `reconnect` is true, the proxy starts the remote server if it isn't
+    /// running.
+    ///
+    /// Returns the channels through which the session exchanges messages with the remote
+    /// server, along with a task that resolves to the exit code of the proxy once the
+    /// connection is lost.
+    fn start_proxy(
+        &self,
+        params: &ProxyParams,
+        reconnect: bool,
+        executor: &BackgroundExecutor,
+    ) -> Result<(
+        mpsc::UnboundedReceiver<Envelope>,
+        mpsc::UnboundedSender<Envelope>,
+        ProxyExit,
+    )> {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded::<Envelope>();
+        let (incoming_tx, incoming_rx) = mpsc::unbounded::<Envelope>();
+
+        let command = proxy_command(&params.remote_binary_path, &params.server_id, reconnect);
+        let mut proxy = self
+            .socket
+            .ssh_command(&command)
+            .kill_on_drop(true)
+            .spawn()
+            .context("failed to spawn remote server proxy")?;
+        let mut child_stderr = proxy.stderr.take().unwrap();
+        let mut child_stdout = proxy.stdout.take().unwrap();
+        let mut child_stdin = proxy.stdin.take().unwrap();
+
+        let proxy_exit = executor.spawn(async move {
+            let mut stdin_buffer = Vec::new();
+            let mut stdout_buffer = Vec::new();
+            let mut stderr_buffer = Vec::new();
+            let mut stderr_offset = 0;
+
+            loop {
+                stdout_buffer.resize(MESSAGE_LEN_SIZE, 0);
+                stderr_buffer.resize(stderr_offset + 1024, 0);
+
+                select_biased! {
+                    outgoing = outgoing_rx.next().fuse() => {
+                        let Some(outgoing) = outgoing else {
+                            // The session has moved on to another connection.
+                            proxy.kill().ok();
+                            return anyhow::Ok(None);
+                        };
+
+                        write_message(&mut child_stdin, &mut stdin_buffer, outgoing).await?;
+                    }
+
+                    result = child_stdout.read(&mut stdout_buffer).fuse() => {
+                        match result {
+                            Ok(len) => {
+                                if len == 0 {
+                                    child_stdin.close().await.ok();
+                                    let status = proxy.status().await?;
+                                    if !status.success() {
+                                        log::info!("proxy exited with status: {status:?}");
+                                    }
+                                    return Ok(status.code());
+                                }
+
+                                if len < stdout_buffer.len() {
+                                    child_stdout.read_exact(&mut stdout_buffer[len..]).await?;
+                                }
+
+                                let message_len = message_len_from_buffer(&stdout_buffer);
+                                match read_message_with_len(&mut child_stdout, &mut stdout_buffer, message_len).await {
+                                    Ok(envelope) => {
+                                        incoming_tx.unbounded_send(envelope).ok();
+                                    }
+                                    Err(error) => {
+                                        log::error!("error decoding message {error:?}");
+                                    }
+                                }
+                            }
+                            Err(error) => {
+                                Err(anyhow!("error reading stdout: {error:?}"))?;
+                            }
+                        }
+                    }
+
+                    result = child_stderr.read(&mut stderr_buffer[stderr_offset..]).fuse() => {
+                        match result {
+                            Ok(len) => {
+                                stderr_offset += len;
+                                let mut start_ix = 0;
+                                while let Some(ix) = stderr_buffer[start_ix..stderr_offset].iter().position(|b| b == &b'\n') {
+                                    let line_ix = start_ix + ix;
+                                    let content = &stderr_buffer[start_ix..line_ix];
+                                    start_ix = line_ix + 1;
+                                    if let Ok(record) = serde_json::from_slice::<LogRecord>(&content) {
+                                        record.log(log::logger())
+                                    } else {
+                                        eprintln!("(remote) {}", String::from_utf8_lossy(content));
+                                    }
+                                }
+                                stderr_buffer.drain(0..start_ix);
+                                stderr_offset -= start_ix;
+                            }
+                            Err(error) => {
+                                Err(anyhow!("error reading stderr: {error:?}"))?;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok((incoming_rx, outgoing_tx, proxy_exit))
+    }
+
+    /// Starts or stops forwarding a port through the master process of the connection.
+    fn port_forward_command(
+        &self,
+        command: &str,
+        forward: SshPortForward,
+    ) -> LocalBoxFuture<'static, Result<()>> {
+        let mut control_command = self.socket.control_command(command);
+        control_command
+            .args(forward.ssh_args())
+            .arg(self.socket.connection_options.ssh_url());
+        async move { run_cmd(&mut control_command).await.map(drop) }.boxed_local()
+    }
+
     async fn ensure_server_binary(
         &self,
         delegate: &Arc<dyn SshClientDelegate>,
@@ -713,6 +1238,40 @@ impl SshClientState {
     }
 }
 
+impl SshConnection for SshClientState {
+    fn socket(&self) -> &SshSocket {
+        &self.socket
+    }
+
+    fn start_proxy(
+        &self,
+        params: &ProxyParams,
+        reconnect: bool,
+        executor: &BackgroundExecutor,
+    ) -> Result<(
+        mpsc::UnboundedReceiver<Envelope>,
+        mpsc::UnboundedSender<Envelope>,
+        ProxyExit,
+    )> {
+        SshClientState::start_proxy(self, params, reconnect, executor)
+    }
+
+    fn forward_port(&self, forward: SshPortForward) -> LocalBoxFuture<'static, Result<()>> {
+        self.port_forward_command("forward", forward)
+    }
+
+    fn cancel_port_forward(&self, forward: SshPortForward) -> LocalBoxFuture<'static, Result<()>> {
+        self.port_forward_command("cancel", forward)
+    }
+}
+
+impl Drop for SshClientState {
+    fn drop(&mut self) {
+        // Closes the connection, along with the processes that were started through it.
+        self.master_process.kill().ok();
+    }
+}
+
 impl SshSocket {
     fn ssh_command<S: AsRef<OsStr>>(&self, program: S) -> process::Command {
         let mut command = process::Command::new("ssh");
@@ -722,6 +1281,13 @@ impl SshSocket {
         command
     }
 
+    /// A command that is sent to the master process of the connection, like `forward`.
+    fn control_command(&self, command: &str) -> process::Command {
+        let mut control_command = process::Command::new("ssh");
+        self.ssh_options(&mut control_command).args(["-O", command]);
+        control_command
+    }
+
     fn ssh_options<'a>(&self, command: &'a mut process::Command) -> &'a mut process::Command {
         command
             .stdin(Stdio::piped())
@@ -742,6 +1308,30 @@ impl SshSocket {
     }
 }
 
+/// The delays between the attempts to reconnect to the remote server, which double after each
+/// failed attempt, up to a maximum.
+fn reconnection_delays() -> impl Iterator<Item = Duration> {
+    iter::successors(Some(INITIAL_RECONNECTION_DELAY), |delay| {
+        Some((*delay * 2).min(MAX_RECONNECTION_DELAY))
+    })
+    .take(MAX_RECONNECTION_ATTEMPTS - 1)
+}
+
+/// The command that starts a proxy to the remote server with the given id on the remote machine.
+/// A proxy that reconnects exits instead of starting the remote server if it isn't running.
+fn proxy_command(remote_binary_path: &Path, server_id: &str, reconnect: bool) -> String {
+    let mut command = format!(
+        "RUST_LOG={} {:?} proxy {}",
+        std::env::var("RUST_LOG").unwrap_or(String::new()),
+        remote_binary_path,
+        server_id,
+    );
+    if reconnect {
+        command.push_str(" --reconnect");
+    }
+    command
+}
+
 async fn run_cmd(command: &mut process::Command) -> Result<String> {
     let output = command.output().await?;
     if output.status.success() {
@@ -753,3 +1343,404 @@ async fn run_cmd(command: &mut process::Command) -> Result<String> {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt as _;
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_missed_heartbeats(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let (client, server) = SshSession::fake(cx, server_cx);
+        let mut missed_heartbeats = cx.spawn({
+            let client = Arc::downgrade(&client);
+            |cx| SshSession::missed_heartbeats(client, cx.background_executor().clone())
+        });
+
+        // The heartbeats are answered while the remote server is connected.
+        advance_clock(cx, HEARTBEAT_INTERVAL * 10);
+        assert!((&mut missed_heartbeats).now_or_never().is_none());
+
+        // Once it stops responding, the connection is considered lost after enough heartbeats
+        // in a row have timed out.
+        SshSession::fake_disconnect(&client, &server);
+        let heartbeat_period = HEARTBEAT_INTERVAL + HEARTBEAT_TIMEOUT;
+        advance_clock(cx, heartbeat_period * (MAX_MISSED_HEARTBEATS as u32 - 1));
+        assert!((&mut missed_heartbeats).now_or_never().is_none());
+        advance_clock(cx, heartbeat_period);
+        assert!((&mut missed_heartbeats).now_or_never().is_some());
+    }
+
+    #[test]
+    fn test_reconnection_delays() {
+        let delays = reconnection_delays().collect::<Vec<_>>();
+        assert_eq!(delays.len(), MAX_RECONNECTION_ATTEMPTS - 1);
+        assert_eq!(
+            delays,
+            [500, 1000, 2000, 4000, 8000, 10000, 10000, 10000, 10000]
+                .into_iter()
+                .map(Duration::from_millis)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_proxy_command() {
+        let binary_path = Path::new("/home/user/.zed_server/zed-remote-server");
+        assert!(proxy_command(binary_path, "server-1", false)
+            .ends_with("\"/home/user/.zed_server/zed-remote-server\" proxy server-1"));
+        assert!(proxy_command(binary_path, "server-1", true)
+            .ends_with("\"/home/user/.zed_server/zed-remote-server\" proxy server-1 --reconnect"));
+    }
+
+    #[gpui::test]
+    async fn test_wait_for_server(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let (client, server) = SshSession::fake(cx, server_cx);
+        let executor = cx.executor();
+
+        let mut proxy_exit = executor.spawn(futures::future::pending());
+        client
+            .wait_for_server(&mut proxy_exit, &executor)
+            .await
+            .unwrap();
+
+        // A proxy that reconnects exits with a dedicated code if the remote server isn't running.
+        SshSession::fake_disconnect(&client, &server);
+        let mut proxy_exit = Task::ready(Ok(Some(SERVER_NOT_RUNNING_EXIT_CODE)));
+        let error = client
+            .wait_for_server(&mut proxy_exit, &executor)
+            .await
+            .unwrap_err();
+        assert!(error.is::<ServerNotRunning>());
+
+        let mut proxy_exit = Task::ready(Ok(Some(255)));
+        let error = client
+            .wait_for_server(&mut proxy_exit, &executor)
+            .await
+            .unwrap_err();
+        assert!(!error.is::<ServerNotRunning>());
+
+        let wait_for_server = cx.spawn({
+            let client = client.clone();
+            let executor = executor.clone();
+            |_| async move {
+                let mut proxy_exit = executor.spawn(futures::future::pending());
+                client.wait_for_server(&mut proxy_exit, &executor).await
+            }
+        });
+        cx.executor().run_until_parked();
+        advance_clock(cx, CONNECTION_TIMEOUT);
+        let error = wait_for_server.await.unwrap_err();
+        assert!(!error.is::<ServerNotRunning>());
+    }
+
+    #[gpui::test]
+    async fn test_reconnect(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let forward = SshPortForward::local(8080, 3000);
+        let machine = FakeRemoteMachine::new(vec![forward]);
+        let (client, server) = machine.start_session(cx, server_cx).await;
+        assert_eq!(machine.port_forwards(), [forward]);
+
+        // A request made while the connection is lost is sent again after a proxy that
+        // reconnects to the same remote server is started over a new connection.
+        machine.drop_connection(&server);
+        let request = client.request(proto::Ping {});
+        cx.executor().run_until_parked();
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Reconnecting);
+        assert_eq!(machine.connection_attempts(), 2);
+        assert!(machine.port_forwards().is_empty());
+
+        machine.accept_proxy(&server, server_cx);
+        request.await.unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Connected);
+        assert_eq!(client.reconnections(), 1);
+        assert_eq!(client.server_restarts(), 0);
+
+        // The ports are forwarded again over the new connection.
+        assert_eq!(machine.port_forwards(), [forward]);
+        assert_eq!(client.port_forwards(), [forward]);
+    }
+
+    #[gpui::test]
+    async fn test_server_restart(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let forward = SshPortForward::local(8080, 3000);
+        let machine = FakeRemoteMachine::new(vec![forward]);
+        let (client, server) = machine.start_session(cx, server_cx).await;
+
+        // When the remote server stopped running while the connection was lost, the proxy that
+        // reconnects exits, and a new remote server is started instead.
+        machine.drop_connection(&server);
+        machine.stop_server();
+        drop(server);
+        let request = client.request(proto::Ping {});
+        cx.executor().run_until_parked();
+        let _server = machine.start_server(server_cx);
+
+        // A request to the previous remote server fails, rather than being sent to the new one.
+        assert!(request.await.is_err());
+        cx.executor().run_until_parked();
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Connected);
+        assert_eq!(client.reconnections(), 0);
+        assert_eq!(client.server_restarts(), 1);
+        assert_eq!(machine.port_forwards(), [forward]);
+        client.request(proto::Ping {}).await.unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_reconnection_attempts(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+        let forward = SshPortForward::local(8080, 3000);
+        let machine = FakeRemoteMachine::new(vec![forward]);
+        let (client, server) = machine.start_session(cx, server_cx).await;
+
+        // The client retries connecting to an unreachable remote machine with a backoff, and
+        // gives up after too many attempts.
+        machine.drop_connection(&server);
+        machine.set_reachable(false);
+        let request = client.request(proto::Ping {});
+        cx.executor().run_until_parked();
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Reconnecting);
+        assert_eq!(machine.connection_attempts(), 2);
+
+        let mut delays = reconnection_delays();
+        advance_clock(cx, delays.next().unwrap());
+        assert_eq!(machine.connection_attempts(), 3);
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Reconnecting);
+
+        advance_clock(cx, delays.sum());
+        assert_eq!(machine.connection_attempts(), 1 + MAX_RECONNECTION_ATTEMPTS);
+        assert_eq!(*client.status().borrow(), SshConnectionStatus::Disconnected);
+        assert!(request.await.is_err());
+        assert!(client.port_forwards().is_empty());
+        assert!(client.request(proto::Ping {}).await.is_err());
+    }
+
+    /// A remote machine that the client's session connects to, whose proxies are connected to
+    /// fake remote servers by the tests.
+    struct FakeRemoteMachine {
+        port_forwards: Vec<SshPortForward>,
+        state: Arc<Mutex<FakeRemoteMachineState>>,
+    }
+
+    struct FakeRemoteMachineState {
+        reachable: bool,
+        server_running: bool,
+        connection_attempts: usize,
+        /// The ports forwarded over the latest connection to the machine.
+        port_forwards: Vec<SshPortForward>,
+        /// The proxies that were started, but aren't connected to a remote server yet.
+        pending_proxies: VecDeque<FakeProxy>,
+        /// Makes the proxy that is connected to the remote server exit with the given code.
+        connected_proxy_exit: Option<oneshot::Sender<Option<i32>>>,
+    }
+
+    struct FakeProxy {
+        reconnect: bool,
+        incoming_tx: mpsc::UnboundedSender<Envelope>,
+        outgoing_rx: mpsc::UnboundedReceiver<Envelope>,
+        exit_tx: oneshot::Sender<Option<i32>>,
+    }
+
+    struct FakeSshConnection {
+        socket: SshSocket,
+        state: Arc<Mutex<FakeRemoteMachineState>>,
+    }
+
+    impl FakeRemoteMachine {
+        fn new(port_forwards: Vec<SshPortForward>) -> Self {
+            Self {
+                port_forwards,
+                state: Arc::new(Mutex::new(FakeRemoteMachineState {
+                    reachable: true,
+                    server_running: false,
+                    connection_attempts: 0,
+                    port_forwards: Vec::new(),
+                    pending_proxies: VecDeque::new(),
+                    connected_proxy_exit: None,
+                })),
+            }
+        }
+
+        /// Starts a session and its remote server, as [`SshSession::client`] does.
+        async fn start_session(
+            &self,
+            cx: &mut TestAppContext,
+            server_cx: &mut TestAppContext,
+        ) -> (Arc<SshSession>, Arc<SshSession>) {
+            let connection = connect(&self.state).unwrap();
+            let params = self.proxy_params();
+            let client =
+                cx.spawn(
+                    |mut cx| async move { SshSession::start(connection, params, &mut cx).await },
+                );
+            cx.executor().run_until_parked();
+            let server = self.start_server(server_cx);
+            (client.await.unwrap(), server)
+        }
+
+        fn proxy_params(&self) -> ProxyParams {
+            let state = self.state.clone();
+            ProxyParams {
+                connection_options: connection_options(self.port_forwards.clone()),
+                connect: Box::new(move |_| futures::future::ready(connect(&state)).boxed_local()),
+                remote_binary_path: PathBuf::from("/home/user/.zed_server/zed-remote-server"),
+                server_id: "server-1".to_string(),
+            }
+        }
+
+        /// Starts a remote server for the proxy that was started to do so.
+        fn start_server(&self, server_cx: &mut TestAppContext) -> Arc<SshSession> {
+            let (reconnect, incoming_tx, outgoing_rx) = self.connect_pending_proxy();
+            assert!(!reconnect);
+            self.state.lock().server_running = true;
+            server_cx.update(|cx| SshSession::server(outgoing_rx, incoming_tx, cx))
+        }
+
+        /// Connects the proxy that was started to reconnect to the running remote server.
+        fn accept_proxy(&self, server: &Arc<SshSession>, server_cx: &mut TestAppContext) {
+            let (reconnect, incoming_tx, outgoing_rx) = self.connect_pending_proxy();
+            assert!(reconnect);
+            server_cx.update(|cx| server.reconnect(outgoing_rx, incoming_tx, cx));
+        }
+
+        /// Returns whether the oldest pending proxy reconnects, along with the channels through
+        /// which a remote server exchanges messages with the session over it.
+        fn connect_pending_proxy(
+            &self,
+        ) -> (
+            bool,
+            mpsc::UnboundedSender<Envelope>,
+            mpsc::UnboundedReceiver<Envelope>,
+        ) {
+            let mut state = self.state.lock();
+            let FakeProxy {
+                reconnect,
+                incoming_tx,
+                outgoing_rx,
+                exit_tx,
+            } = state
+                .pending_proxies
+                .pop_front()
+                .expect("no proxy was started");
+            state.connected_proxy_exit = Some(exit_tx);
+            (reconnect, incoming_tx, outgoing_rx)
+        }
+
+        /// Loses the connection to the remote machine, along with the messages on their way.
+        fn drop_connection(&self, server: &Arc<SshSession>) {
+            server.incoming_task.lock().take();
+            server.outgoing.lock().tx = mpsc::unbounded().0;
+            let exit_tx = self.state.lock().connected_proxy_exit.take();
+            // Like ssh, the proxy exits with 255 when the connection is lost.
+            exit_tx.unwrap().send(Some(255)).ok();
+        }
+
+        fn stop_server(&self) {
+            self.state.lock().server_running = false;
+        }
+
+        fn set_reachable(&self, reachable: bool) {
+            self.state.lock().reachable = reachable;
+        }
+
+        fn connection_attempts(&self) -> usize {
+            self.state.lock().connection_attempts
+        }
+
+        fn port_forwards(&self) -> Vec<SshPortForward> {
+            self.state.lock().port_forwards.clone()
+        }
+    }
+
+    fn connection_options(port_forwards: Vec<SshPortForward>) -> SshConnectionOptions {
+        SshConnectionOptions {
+            host: "example.com".to_string(),
+            username: None,
+            port: None,
+            password: None,
+            port_forwards,
+        }
+    }
+
+    fn connect(state: &Arc<Mutex<FakeRemoteMachineState>>) -> Result<Arc<dyn SshConnection>> {
+        let mut state_guard = state.lock();
+        state_guard.connection_attempts += 1;
+        if !state_guard.reachable {
+            return Err(anyhow!("connection refused"));
+        }
+        // Ports are forwarded per connection, so they're closed along with the previous one.
+        state_guard.port_forwards.clear();
+        Ok(Arc::new(FakeSshConnection {
+            socket: SshSocket {
+                connection_options: connection_options(Vec::new()),
+                socket_path: PathBuf::from("/tmp/ssh.sock"),
+            },
+            state: state.clone(),
+        }))
+    }
+
+    impl SshConnection for FakeSshConnection {
+        fn socket(&self) -> &SshSocket {
+            &self.socket
+        }
+
+        fn start_proxy(
+            &self,
+            _params: &ProxyParams,
+            reconnect: bool,
+            executor: &BackgroundExecutor,
+        ) -> Result<(
+            mpsc::UnboundedReceiver<Envelope>,
+            mpsc::UnboundedSender<Envelope>,
+            ProxyExit,
+        )> {
+            let mut state = self.state.lock();
+            if reconnect && !state.server_running {
+                return Ok((
+                    mpsc::unbounded().1,
+                    mpsc::unbounded().0,
+                    Task::ready(Ok(Some(SERVER_NOT_RUNNING_EXIT_CODE))),
+                ));
+            }
+
+            let (incoming_tx, incoming_rx) = mpsc::unbounded();
+            let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
+            let (exit_tx, exit_rx) = oneshot::channel();
+            state.pending_proxies.push_back(FakeProxy {
+                reconnect,
+                incoming_tx,
+                outgoing_rx,
+                exit_tx,
+            });
+            let proxy_exit = executor.spawn(async move { Ok(exit_rx.await.unwrap_or(None)) });
+            Ok((incoming_rx, outgoing_tx, proxy_exit))
+        }
+
+        fn forward_port(&self, forward: SshPortForward) -> LocalBoxFuture<'static, Result<()>> {
+            self.state.lock().port_forwards.push(forward);
+            futures::future::ready(Ok(())).boxed_local()
+        }
+
+        fn cancel_port_forward(
+            &self,
+            forward: SshPortForward,
+        ) -> LocalBoxFuture<'static, Result<()>> {
+            self.state
+                .lock()
+                .port_forwards
+                .retain(|existing| *existing != forward);
+            futures::future::ready(Ok(())).boxed_local()
+        }
+    }
+
+    fn advance_clock(cx: &mut TestAppContext, duration: Duration) {
+        let step = Duration::from_millis(100);
+        let mut elapsed = Duration::ZERO;
+        while elapsed < duration {
+            cx.executor().advance_clock(step);
+            cx.executor().run_until_parked();
+            elapsed += step;
+        }
+    }
+}
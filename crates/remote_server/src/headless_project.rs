@@ -1,23 +1,26 @@
-use anyhow::Result;
+use anyhow::{anyhow, Context as _, Result};
 use fs::Fs;
 use gpui::{AppContext, AsyncAppContext, Context, Model, ModelContext};
+use http_client::HttpClientWithUrl;
+use language::{proto::serialize_anchor, BufferId, LanguageRegistry};
+use node_runtime::NodeRuntime;
 use project::{
-    buffer_store::{BufferStore, BufferStoreEvent},
-    worktree_store::WorktreeStore,
-    ProjectPath, WorktreeId, WorktreeSettings,
+    buffer_store::BufferStore, headless_lsp_project::HeadlessLspProject, search::SearchQuery,
+    worktree_store::WorktreeStore, Project, ProjectPath, SearchResult, WorktreeId,
 };
 use remote::SshSession;
 use rpc::{
     proto::{self, AnyProtoClient, PeerId},
     TypedEnvelope,
 };
-use settings::{Settings as _, SettingsStore};
+use settings::SettingsStore;
 use smol::stream::StreamExt;
 use std::{
+    collections::HashSet,
+    iter,
     path::{Path, PathBuf},
     sync::{atomic::AtomicUsize, Arc},
 };
-use util::ResultExt as _;
 use worktree::Worktree;
 
 const PEER_ID: PeerId = PeerId { owner_id: 0, id: 0 };
@@ -28,31 +31,56 @@ pub struct HeadlessProject {
     pub session: AnyProtoClient,
     pub worktree_store: Model<WorktreeStore>,
     pub buffer_store: Model<BufferStore>,
+    pub lsp_project: Model<HeadlessLspProject>,
     pub next_entry_id: Arc<AtomicUsize>,
 }
 
 impl HeadlessProject {
     pub fn init(cx: &mut AppContext) {
         cx.set_global(SettingsStore::new(cx));
-        WorktreeSettings::register(cx);
+        client::init_settings(cx);
+        language::init(cx);
+        Project::init_settings(cx);
     }
 
-    pub fn new(session: Arc<SshSession>, fs: Arc<dyn Fs>, cx: &mut ModelContext<Self>) -> Self {
+    pub fn new(
+        session: Arc<SshSession>,
+        fs: Arc<dyn Fs>,
+        http: Arc<HttpClientWithUrl>,
+        node_runtime: Arc<dyn NodeRuntime>,
+        languages: Arc<LanguageRegistry>,
+        cx: &mut ModelContext<Self>,
+    ) -> Self {
         let this = cx.weak_model();
 
         let worktree_store = cx.new_model(|_| WorktreeStore::new(true));
         let buffer_store =
             cx.new_model(|cx| BufferStore::new(worktree_store.clone(), Some(PROJECT_ID), cx));
-        cx.subscribe(&buffer_store, Self::on_buffer_store_event)
-            .detach();
+        let lsp_project = cx.new_model(|cx| {
+            HeadlessLspProject::new(
+                session.clone(),
+                worktree_store.clone(),
+                buffer_store.clone(),
+                http,
+                node_runtime,
+                languages,
+                fs.clone(),
+                cx,
+            )
+        });
 
         session.add_request_handler(this.clone(), Self::handle_list_remote_directory);
         session.add_request_handler(this.clone(), Self::handle_add_worktree);
         session.add_request_handler(this.clone(), Self::handle_open_buffer_by_path);
+        session.add_request_handler(this.clone(), Self::handle_restore_buffer);
+        session.add_message_handler(this.clone(), Self::handle_close_buffer);
+        session.add_request_handler(this.clone(), Self::handle_search_project);
+        session.add_request_handler(this.clone(), Self::handle_run_git_remote_operation);
 
         session.add_request_handler(buffer_store.downgrade(), BufferStore::handle_blame_buffer);
         session.add_request_handler(buffer_store.downgrade(), BufferStore::handle_update_buffer);
         session.add_request_handler(buffer_store.downgrade(), BufferStore::handle_save_buffer);
+        session.add_request_handler(buffer_store.downgrade(), BufferStore::handle_reopen_buffer);
 
         session.add_request_handler(
             worktree_store.downgrade(),
@@ -74,12 +102,65 @@ impl HeadlessProject {
             worktree_store.downgrade(),
             WorktreeStore::handle_expand_project_entry,
         );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_get_git_staging_status,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_stage_git_paths,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_unstage_git_paths,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_set_git_index_text,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_unstage_git_rows,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_commit_git_changes,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_stash_git_changes,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_apply_git_stash,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_drop_git_stash,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_get_git_stashes,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_get_git_log,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_get_git_commit_diff,
+        );
+        session.add_request_handler(
+            worktree_store.downgrade(),
+            WorktreeStore::handle_get_git_revision_diff,
+        );
 
         HeadlessProject {
             session: session.into(),
             fs,
             worktree_store,
             buffer_store,
+            lsp_project,
             next_entry_id: Default::default(),
         }
     }
@@ -90,13 +171,36 @@ impl HeadlessProject {
         mut cx: AsyncAppContext,
     ) -> Result<proto::AddWorktreeResponse> {
         let path = shellexpand::tilde(&message.payload.path).to_string();
+        let requested_id = message.payload.worktree_id.map(WorktreeId::from_proto);
+        let used_ids = this.update(&mut cx, |this, cx| {
+            this.worktree_store
+                .read(cx)
+                .worktrees()
+                .map(|worktree| worktree.read(cx).id())
+                .collect::<HashSet<_>>()
+        })?;
+        if let Some(id) = requested_id.filter(|id| used_ids.contains(id)) {
+            return Err(anyhow!("worktree {id} already exists"));
+        }
+
+        // A worktree that the client restores keeps its id, which the ids derived for other
+        // worktrees skip.
         let worktree = this
             .update(&mut cx.clone(), |this, _| {
-                Worktree::local(
+                Worktree::local_with_id(
                     Path::new(&path),
                     true,
                     this.fs.clone(),
                     this.next_entry_id.clone(),
+                    move |derived_id| {
+                        requested_id.unwrap_or_else(|| {
+                            let mut id = derived_id.to_usize();
+                            while used_ids.contains(&WorktreeId::from_usize(id)) {
+                                id = id.saturating_add(1 << 32);
+                            }
+                            WorktreeId::from_usize(id)
+                        })
+                    },
                     &mut cx,
                 )
             })?
@@ -104,8 +208,8 @@ impl HeadlessProject {
 
         this.update(&mut cx, |this, cx| {
             let session = this.session.clone();
-            this.worktree_store.update(cx, |worktree_store, cx| {
-                worktree_store.add(&worktree, cx);
+            this.lsp_project.update(cx, |lsp_project, cx| {
+                lsp_project.add_worktree(&worktree, cx);
             });
             worktree.update(cx, |worktree, cx| {
                 worktree.observe_updates(0, cx, move |update| {
@@ -125,9 +229,8 @@ impl HeadlessProject {
         mut cx: AsyncAppContext,
     ) -> Result<proto::OpenBufferResponse> {
         let worktree_id = WorktreeId::from_proto(message.payload.worktree_id);
-        let (buffer_store, buffer, session) = this.update(&mut cx, |this, cx| {
-            let buffer_store = this.buffer_store.clone();
-            let buffer = this.buffer_store.update(cx, |buffer_store, cx| {
+        let buffer = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
                 buffer_store.open_buffer(
                     ProjectPath {
                         worktree_id,
@@ -135,31 +238,140 @@ impl HeadlessProject {
                     },
                     cx,
                 )
-            });
-            anyhow::Ok((buffer_store, buffer, this.session.clone()))
-        })??;
+            })
+        })?;
 
         let buffer = buffer.await?;
-        let buffer_id = buffer.read_with(&cx, |b, _| b.remote_id())?;
-
-        cx.spawn(|mut cx| async move {
-            BufferStore::create_buffer_for_peer(
-                buffer_store,
-                PEER_ID,
-                buffer_id,
-                PROJECT_ID,
-                session,
-                &mut cx,
-            )
-            .await
-        })
-        .detach();
+        let buffer_id = this.update(&mut cx, |this, cx| {
+            this.lsp_project.update(cx, |lsp_project, cx| {
+                lsp_project.create_buffer_for_peer(&buffer, PEER_ID, cx)
+            });
+            buffer.read(cx).remote_id()
+        })?;
 
         Ok(proto::OpenBufferResponse {
             buffer_id: buffer_id.to_proto(),
         })
     }
 
+    /// Recreates a buffer that the client had open on a previous server, which is no longer
+    /// running, along with the client's unsaved changes to it.
+    pub async fn handle_restore_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RestoreBuffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let state = envelope.payload.state.context("missing buffer state")?;
+        let operations = envelope
+            .payload
+            .operations
+            .into_iter()
+            .map(language::proto::deserialize_operation)
+            .collect::<Result<Vec<_>>>()?;
+        let reconcile = this.update(&mut cx, |this, cx| {
+            this.buffer_store.update(cx, |buffer_store, cx| {
+                let buffer = buffer_store.restore_buffer(state, operations, cx)?;
+                this.lsp_project.update(cx, |lsp_project, cx| {
+                    lsp_project.mark_buffer_as_shared(&buffer, PEER_ID, cx)
+                });
+                anyhow::Ok(buffer_store.reconcile_restored_buffer(buffer, cx))
+            })
+        })??;
+        reconcile.await?;
+        Ok(proto::Ack {})
+    }
+
+    pub async fn handle_close_buffer(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::CloseBuffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<()> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        this.update(&mut cx, |this, cx| {
+            this.lsp_project.update(cx, |lsp_project, cx| {
+                lsp_project.forget_shared_buffer(buffer_id, PEER_ID, cx)
+            })
+        })
+    }
+
+    /// Runs a project search, sending the matches back to the client as they are found.
+    ///
+    /// Each batch contains the matches found while the client was handling the previous one, so
+    /// that the client isn't sent more matches than it can take in.
+    pub async fn handle_search_project(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::SearchProject>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::SearchProjectResponse> {
+        let search_id = envelope.payload.search_id;
+        let query = SearchQuery::from_proto(envelope.payload)?;
+        let (session, results) = this.update(&mut cx, |this, cx| {
+            let results = this
+                .lsp_project
+                .update(cx, |lsp_project, cx| lsp_project.search(query, cx));
+            (this.session.clone(), results)
+        })?;
+
+        let mut limit_reached = false;
+        while let Ok(result) = results.recv().await {
+            let mut locations = Vec::new();
+            for result in iter::once(result).chain(iter::from_fn(|| results.try_recv().ok())) {
+                match result {
+                    SearchResult::Buffer { buffer, ranges } => {
+                        if ranges.is_empty() {
+                            continue;
+                        }
+                        let buffer_id = this.update(&mut cx, |this, cx| {
+                            this.lsp_project.update(cx, |lsp_project, cx| {
+                                lsp_project.create_buffer_for_peer(&buffer, PEER_ID, cx)
+                            });
+                            buffer.read(cx).remote_id().to_proto()
+                        })?;
+                        locations.extend(ranges.iter().map(|range| proto::Location {
+                            buffer_id,
+                            start: Some(serialize_anchor(&range.start)),
+                            end: Some(serialize_anchor(&range.end)),
+                        }));
+                    }
+                    SearchResult::LimitReached => limit_reached = true,
+                }
+            }
+
+            if !locations.is_empty() {
+                session
+                    .request(proto::SearchProjectResults {
+                        project_id: PROJECT_ID,
+                        search_id,
+                        locations,
+                    })
+                    .await?;
+            }
+        }
+
+        Ok(proto::SearchProjectResponse {
+            locations: Vec::new(),
+            limit_reached,
+        })
+    }
+
+    /// Runs a fetch, pull or push, sending the prompts for credentials back to the client.
+    pub async fn handle_run_git_remote_operation(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::RunGitRemoteOperation>,
+        cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        let (worktree_store, session) = this.update(&mut cx.clone(), |this, _| {
+            (this.worktree_store.clone(), this.session.clone())
+        })?;
+        WorktreeStore::run_requested_git_remote_operation(
+            worktree_store,
+            envelope.payload,
+            Some(session),
+            cx,
+        )
+        .await
+    }
+
     pub async fn handle_list_remote_directory(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::ListRemoteDirectory>,
@@ -177,20 +389,4 @@ impl HeadlessProject {
         }
         Ok(proto::ListRemoteDirectoryResponse { entries })
     }
-
-    pub fn on_buffer_store_event(
-        &mut self,
-        _: Model<BufferStore>,
-        event: &BufferStoreEvent,
-        _: &mut ModelContext<Self>,
-    ) {
-        match event {
-            BufferStoreEvent::MessageToReplicas(message) => {
-                self.session
-                    .send_dynamic(message.as_ref().clone())
-                    .log_err();
-            }
-            _ => {}
-        }
-    }
 }
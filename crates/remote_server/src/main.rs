@@ -1,22 +1,49 @@
 #![cfg_attr(target_os = "windows", allow(unused, dead_code))]
 
+use anyhow::{Context as _, Result};
+use client::ClientSettings;
 use fs::RealFs;
 use futures::channel::mpsc;
-use gpui::Context as _;
+use gpui::{BackgroundExecutor, Context as _, Task};
+use http_client::HttpClientWithUrl;
+use language::LanguageRegistry;
+use node_runtime::RealNodeRuntime;
 use remote::{
     json_log::LogRecord,
-    protocol::{read_message, write_message},
+    protocol::{read_message, write_message, SERVER_NOT_RUNNING_EXIT_CODE},
     SshSession,
 };
 use remote_server::HeadlessProject;
-use smol::{io::AsyncWriteExt, stream::StreamExt as _, Async};
+use rpc::proto::Envelope;
+use settings::Settings as _;
+#[cfg(not(windows))]
+use smol::net::unix::{UnixListener, UnixStream};
+use smol::{
+    io::{AsyncBufReadExt as _, AsyncSeekExt as _, AsyncWriteExt},
+    stream::StreamExt as _,
+    Async,
+};
 use std::{
     env,
-    io::{self, Write},
-    mem, process,
-    sync::Arc,
+    io::{self, SeekFrom, Write},
+    mem,
+    net::Shutdown,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicUsize, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
 };
 
+/// How long the server keeps running without a connection, during which the client can
+/// reconnect to it.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+/// How long a proxy waits for the server that it started to accept connections.
+const SERVER_START_TIMEOUT: Duration = Duration::from_secs(10);
+
 #[cfg(windows)]
 fn main() {
     unimplemented!()
@@ -33,60 +60,220 @@ fn main() {
         })
         .init();
 
-    let subcommand = std::env::args().nth(1);
-    match subcommand.as_deref() {
-        Some("run") => {}
-        Some("version") => {
+    let mut args = env::args().skip(1);
+    let subcommand = args.next();
+    let server_id = args.next();
+    match (subcommand.as_deref(), server_id) {
+        (Some("run"), Some(server_id)) => run(&server_id),
+        (Some("proxy"), Some(server_id)) => {
+            let reconnect = args.next().as_deref() == Some("--reconnect");
+            if let Err(error) = proxy(&server_id, reconnect) {
+                log::error!("{error:?}");
+                process::exit(1);
+            }
+        }
+        (Some("version"), _) => {
             println!("{}", env!("ZED_PKG_VERSION"));
-            return;
         }
         _ => {
-            eprintln!("usage: remote <run|version>");
+            eprintln!("usage: remote <run|proxy> <server-id> [--reconnect] | remote version");
             process::exit(1);
         }
     }
+}
+
+/// The directory in which the server with the given id keeps its socket and log.
+fn state_dir(server_id: &str) -> PathBuf {
+    paths::remote_server_state_dir().join(server_id)
+}
+
+/// Relays the messages between the client, over stdin and stdout, and the server with the given
+/// id, starting the server unless the client is reconnecting to it.
+#[cfg(not(windows))]
+fn proxy(server_id: &str, reconnect: bool) -> Result<()> {
+    let socket_path = state_dir(server_id).join("server.sock");
+    let log_path = state_dir(server_id).join("server.log");
+    smol::block_on(async {
+        let (stream, started) = match UnixStream::connect(&socket_path).await {
+            Ok(stream) => (stream, false),
+            Err(error) if reconnect => {
+                log::info!("failed to connect to the server: {error}");
+                process::exit(SERVER_NOT_RUNNING_EXIT_CODE);
+            }
+            Err(_) => {
+                spawn_server(server_id)?;
+                (connect_to_started_server(&socket_path).await?, true)
+            }
+        };
+
+        smol::spawn(async move {
+            if let Err(error) = forward_server_log(&log_path, started).await {
+                log::error!("failed to forward the server log: {error:?}");
+            }
+        })
+        .detach();
+
+        let stdin = Async::new(io::stdin())?;
+        let mut stdout = Async::new(io::stdout())?;
+        let mut server_writer = stream.clone();
+        smol::future::race(
+            smol::io::copy(stdin, &mut server_writer),
+            smol::io::copy(stream, &mut stdout),
+        )
+        .await?;
+        Ok(())
+    })
+}
+
+/// Starts the server in the background, detached from the proxy so that it outlives it.
+#[cfg(not(windows))]
+fn spawn_server(server_id: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt as _;
+
+    let state_dir = state_dir(server_id);
+    std::fs::create_dir_all(&state_dir)?;
+    let log_file = std::fs::File::create(state_dir.join("server.log"))?;
+    process::Command::new(env::current_exe()?)
+        .arg("run")
+        .arg(server_id)
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(log_file)
+        .process_group(0)
+        .spawn()
+        .context("failed to start the server")?;
+    Ok(())
+}
+
+/// Copies the lines that the server appends to its log to stderr, from which the client reads the
+/// logs of the proxy. The log of a server that was already running is forwarded from its end, as
+/// the client has received the earlier lines through a previous proxy.
+#[cfg(not(windows))]
+async fn forward_server_log(log_path: &Path, from_start: bool) -> Result<()> {
+    let poll_interval = Duration::from_millis(100);
+    let mut file = smol::fs::File::open(log_path).await?;
+    if !from_start {
+        file.seek(SeekFrom::End(0)).await?;
+    }
+    let mut reader = smol::io::BufReader::new(file);
+    let mut line = String::new();
+    loop {
+        if reader.read_line(&mut line).await? == 0 || !line.ends_with('\n') {
+            smol::Timer::after(poll_interval).await;
+            continue;
+        }
+        io::stderr().lock().write_all(line.as_bytes())?;
+        line.clear();
+    }
+}
+
+#[cfg(not(windows))]
+async fn connect_to_started_server(socket_path: &Path) -> Result<UnixStream> {
+    let retry_interval = Duration::from_millis(100);
+    let mut waited = Duration::ZERO;
+    loop {
+        match UnixStream::connect(socket_path).await {
+            Ok(stream) => return Ok(stream),
+            Err(error) if waited >= SERVER_START_TIMEOUT => {
+                return Err(error).context("failed to connect to the server");
+            }
+            Err(_) => {
+                smol::Timer::after(retry_interval).await;
+                waited += retry_interval;
+            }
+        }
+    }
+}
+
+/// Runs the server, which the client connects to through proxies. A proxy is started for each
+/// connection, so that the session carries on over the next one when a connection is lost. The
+/// server exits once it has gone without a connection for too long.
+#[cfg(not(windows))]
+fn run(server_id: &str) {
+    let socket_path = state_dir(server_id).join("server.sock");
 
     gpui::App::headless().run(move |cx| {
         HeadlessProject::init(cx);
 
-        let (incoming_tx, incoming_rx) = mpsc::unbounded();
-        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded();
+        std::fs::remove_file(&socket_path).ok();
+        let listener = match UnixListener::bind(&socket_path) {
+            Ok(listener) => listener,
+            Err(error) => {
+                log::error!("failed to listen on {socket_path:?}: {error}");
+                process::exit(1);
+            }
+        };
 
-        let mut stdin = Async::new(io::stdin()).unwrap();
-        let mut stdout = Async::new(io::stdout()).unwrap();
+        let http = Arc::new(HttpClientWithUrl::new(
+            ClientSettings::get_global(cx).server_url.clone(),
+            None,
+            None,
+        ));
+        let node_runtime = RealNodeRuntime::new(http.clone());
+        let mut languages =
+            LanguageRegistry::new(Task::ready(()), cx.background_executor().clone());
+        languages.set_language_server_download_dir(paths::languages_dir().clone());
+        let languages = Arc::new(languages);
+        languages::init(languages.clone(), node_runtime.clone(), cx);
 
+        // The messages sent before the first proxy connects are sent to it once it does.
+        let (_, incoming_rx) = mpsc::unbounded();
+        let (outgoing_tx, _) = mpsc::unbounded();
         let session = SshSession::server(incoming_rx, outgoing_tx, cx);
         let project = cx.new_model(|cx| {
             HeadlessProject::new(
                 session.clone(),
                 Arc::new(RealFs::new(Default::default(), None)),
+                http,
+                node_runtime,
+                languages,
                 cx,
             )
         });
 
-        cx.background_executor()
-            .spawn(async move {
-                let mut output_buffer = Vec::new();
-                while let Some(message) = outgoing_rx.next().await {
-                    write_message(&mut stdout, &mut output_buffer, message).await?;
-                    stdout.flush().await?;
+        let connections = Arc::new(AtomicUsize::new(0));
+        cx.spawn({
+            let connections = connections.clone();
+            |cx| async move {
+                let mut incoming = listener.incoming();
+                while let Some(stream) = incoming.next().await {
+                    let stream = stream?;
+                    log::info!("proxy connected");
+                    connections.fetch_add(1, SeqCst);
+                    let (incoming_tx, incoming_rx) = mpsc::unbounded();
+                    let (outgoing_tx, outgoing_rx) = mpsc::unbounded();
+                    handle_connection(
+                        stream,
+                        incoming_tx,
+                        outgoing_rx,
+                        connections.clone(),
+                        cx.background_executor(),
+                    );
+                    cx.update(|cx| session.reconnect(incoming_rx, outgoing_tx, cx))?;
                 }
                 anyhow::Ok(())
-            })
-            .detach();
+            }
+        })
+        .detach();
 
         cx.background_executor()
-            .spawn(async move {
-                let mut input_buffer = Vec::new();
-                loop {
-                    let message = match read_message(&mut stdin, &mut input_buffer).await {
-                        Ok(message) => message,
-                        Err(error) => {
-                            log::warn!("error reading message: {:?}", error);
+            .spawn({
+                let executor = cx.background_executor().clone();
+                async move {
+                    let mut idle_time = Duration::ZERO;
+                    loop {
+                        executor.timer(IDLE_CHECK_INTERVAL).await;
+                        if connections.load(SeqCst) > 0 {
+                            idle_time = Duration::ZERO;
+                            continue;
+                        }
+                        idle_time += IDLE_CHECK_INTERVAL;
+                        if idle_time >= IDLE_TIMEOUT {
+                            log::info!("no proxy connected for {IDLE_TIMEOUT:?}, exiting");
+                            std::fs::remove_file(&socket_path).ok();
                             process::exit(0);
                         }
-                    };
-                    incoming_tx.unbounded_send(message).ok();
+                    }
                 }
             })
             .detach();
@@ -94,3 +281,49 @@ fn main() {
         mem::forget(project);
     });
 }
+
+/// Relays the messages of the session over a connection from a proxy, until the proxy
+/// disconnects or the session moves on to a newer connection.
+#[cfg(not(windows))]
+fn handle_connection(
+    stream: UnixStream,
+    incoming_tx: mpsc::UnboundedSender<Envelope>,
+    mut outgoing_rx: mpsc::UnboundedReceiver<Envelope>,
+    connections: Arc<AtomicUsize>,
+    executor: &BackgroundExecutor,
+) {
+    let mut writer = stream.clone();
+    executor
+        .spawn(async move {
+            let mut output_buffer = Vec::new();
+            while let Some(message) = outgoing_rx.next().await {
+                write_message(&mut writer, &mut output_buffer, message).await?;
+                writer.flush().await?;
+            }
+            // Stop reading from a connection that has been replaced by a newer one.
+            writer.shutdown(Shutdown::Both)?;
+            anyhow::Ok(())
+        })
+        .detach();
+
+    let mut reader = stream;
+    executor
+        .spawn(async move {
+            let mut input_buffer = Vec::new();
+            loop {
+                match read_message(&mut reader, &mut input_buffer).await {
+                    Ok(message) => {
+                        if incoming_tx.unbounded_send(message).is_err() {
+                            break;
+                        }
+                    }
+                    Err(error) => {
+                        log::info!("proxy disconnected: {error:?}");
+                        break;
+                    }
+                }
+            }
+            connections.fetch_sub(1, SeqCst);
+        })
+        .detach();
+}
@@ -2,11 +2,19 @@ use crate::headless_project::HeadlessProject;
 use client::{Client, UserStore};
 use clock::FakeSystemClock;
 use fs::{FakeFs, Fs as _};
+use futures::StreamExt as _;
 use gpui::{Context, Model, TestAppContext};
 use http_client::FakeHttpClient;
-use language::LanguageRegistry;
+use language::{
+    Diagnostic, DiagnosticEntry, DiagnosticSeverity, FakeLspAdapter, Language, LanguageConfig,
+    LanguageMatcher, LanguageRegistry, OffsetRangeExt as _, PointUtf16,
+};
 use node_runtime::FakeNodeRuntime;
-use project::Project;
+use project::{
+    search::SearchQuery, DiagnosticSummary, FormatTrigger, Project, ProjectPath, SearchResult,
+    SshConnectionStatus, TaskSourceKind,
+};
+use rand::prelude::*;
 use remote::SshSession;
 use serde_json::json;
 use settings::SettingsStore;
@@ -45,9 +53,8 @@ async fn test_remote_editing(cx: &mut TestAppContext, server_cx: &mut TestAppCon
         &[(Path::new("src/lib.rs"), "fn one() -> usize { 0 }".into())],
     );
 
-    server_cx.update(HeadlessProject::init);
-    let _headless_project =
-        server_cx.new_model(|cx| HeadlessProject::new(server_ssh, fs.clone(), cx));
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
 
     let project = build_project(client_ssh, cx);
     let (worktree, _) = project
@@ -107,6 +114,7 @@ async fn test_remote_editing(cx: &mut TestAppContext, server_cx: &mut TestAppCon
         "/code/project1/src/main.rs".as_ref(),
         &"fn main() {}".into(),
         Default::default(),
+        Default::default(),
     )
     .await
     .unwrap();
@@ -150,6 +158,919 @@ async fn test_remote_editing(cx: &mut TestAppContext, server_cx: &mut TestAppCon
     });
 }
 
+#[gpui::test]
+async fn test_remote_lsp(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }\nfn two() -> usize { one() }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    // The language server only exists on the remote machine.
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    languages.add(rust_lang());
+    let mut fake_servers = languages.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                definition_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    // Opening a buffer starts the language server on the remote machine,
+    // and the client is told about it.
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+    project.read_with(cx, |project, _| {
+        assert_eq!(
+            project
+                .language_server_statuses()
+                .map(|(_, status)| status.name.clone())
+                .collect::<Vec<_>>(),
+            vec!["the-fake-language-server".to_string()]
+        );
+    });
+
+    // Diagnostics published by the language server are streamed to the client.
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: lsp::Url::from_file_path("/code/project1/src/lib.rs").unwrap(),
+        version: None,
+        diagnostics: vec![lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+            severity: Some(lsp::DiagnosticSeverity::WARNING),
+            message: "unused function".to_string(),
+            ..Default::default()
+        }],
+    });
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, usize>(0..buffer.len(), false)
+                .collect::<Vec<_>>(),
+            vec![DiagnosticEntry {
+                range: 3..6,
+                diagnostic: Diagnostic {
+                    severity: DiagnosticSeverity::WARNING,
+                    message: "unused function".to_string(),
+                    group_id: 0,
+                    is_primary: true,
+                    ..Default::default()
+                },
+            }]
+        );
+    });
+    project.read_with(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 0,
+                warning_count: 1,
+            }
+        );
+    });
+
+    // Requests made by the client are answered by the remote language server.
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+            lsp::Location::new(
+                params.text_document_position_params.text_document.uri,
+                lsp::Range::new(lsp::Position::new(0, 3), lsp::Position::new(0, 6)),
+            ),
+        )))
+    });
+    let definitions = project
+        .update(cx, |project, cx| {
+            project.definition(&buffer, PointUtf16::new(1, 21), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(definitions.len(), 1);
+    let target = definitions[0].target.clone();
+    assert_eq!(target.buffer, buffer);
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .text_for_range(target.range.start..target.range.end)
+                .collect::<String>(),
+            "one"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_remote_code_actions(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }\nfn two() -> usize { one() }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    languages.add(rust_lang());
+    let mut fake_servers = languages.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fake_server.handle_request::<lsp::request::CodeActionRequest, _, _>(|params, _| async move {
+        let uri = params.text_document.uri;
+        Ok(Some(vec![lsp::CodeActionOrCommand::CodeAction(
+            lsp::CodeAction {
+                title: "Rename to uno".to_string(),
+                edit: Some(lsp::WorkspaceEdit {
+                    changes: Some(
+                        [(
+                            uri,
+                            vec![
+                                lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(0, 3),
+                                        lsp::Position::new(0, 6),
+                                    ),
+                                    "uno".to_string(),
+                                ),
+                                lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(1, 20),
+                                        lsp::Position::new(1, 23),
+                                    ),
+                                    "uno".to_string(),
+                                ),
+                            ],
+                        )]
+                        .into_iter()
+                        .collect(),
+                    ),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        )]))
+    });
+
+    // The code actions of the remote language server are applied on the remote machine, and
+    // their edits are sent back to the client.
+    let actions = project
+        .update(cx, |project, cx| {
+            project.code_actions(&buffer, PointUtf16::new(0, 3)..PointUtf16::new(0, 6), cx)
+        })
+        .await;
+    assert_eq!(actions.len(), 1);
+    assert_eq!(actions[0].lsp_action.title, "Rename to uno");
+
+    let transaction = project
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer.clone(), actions[0].clone(), true, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(transaction.0.len(), 1);
+    assert!(transaction.0.contains_key(&buffer));
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(
+            buffer.text(),
+            "fn uno() -> usize { 1 }\nfn two() -> usize { uno() }"
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_remote_formatting(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize {1}"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    languages.add(rust_lang());
+    let mut fake_servers = languages.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_formatting_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fake_server.handle_request::<lsp::request::Formatting, _, _>(|_, _| async move {
+        Ok(Some(vec![
+            lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(0, 19), lsp::Position::new(0, 19)),
+                " ".to_string(),
+            ),
+            lsp::TextEdit::new(
+                lsp::Range::new(lsp::Position::new(0, 20), lsp::Position::new(0, 20)),
+                " ".to_string(),
+            ),
+        ]))
+    });
+
+    // The buffer is formatted on the remote machine, where the language server runs.
+    let transaction = project
+        .update(cx, |project, cx| {
+            project.format(
+                [buffer.clone()].into_iter().collect(),
+                true,
+                FormatTrigger::Manual,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+    assert!(transaction.0.contains_key(&buffer));
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn one() -> usize { 1 }");
+    });
+}
+
+#[gpui::test]
+async fn test_remote_symbols_and_restart(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "mod one;\nfn two() -> usize { one::one() }",
+                    "one.rs": "pub fn one() -> usize { 1 }",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    languages.add(rust_lang());
+    let mut fake_servers = languages.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    fake_server.handle_request::<lsp::request::WorkspaceSymbolRequest, _, _>(|_, _| async move {
+        #[allow(deprecated)]
+        Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![
+            lsp::SymbolInformation {
+                name: "one".to_string(),
+                kind: lsp::SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                location: lsp::Location::new(
+                    lsp::Url::from_file_path("/code/project1/src/one.rs").unwrap(),
+                    lsp::Range::new(lsp::Position::new(0, 7), lsp::Position::new(0, 10)),
+                ),
+                container_name: None,
+            },
+        ])))
+    });
+
+    // Project symbols are found by the remote language server, and the buffers they're in are
+    // opened on the remote machine, even when the client hasn't opened them yet.
+    let symbols = project
+        .update(cx, |project, cx| project.symbols("one", cx))
+        .await
+        .unwrap();
+    assert_eq!(symbols.len(), 1);
+    assert_eq!(symbols[0].name, "one");
+    assert_eq!(
+        symbols[0].path,
+        ProjectPath {
+            worktree_id,
+            path: Path::new("src/one.rs").into(),
+        }
+    );
+
+    let symbol_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer_for_symbol(&symbols[0], cx)
+        })
+        .await
+        .unwrap();
+    symbol_buffer.read_with(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "pub fn one() -> usize { 1 }");
+        assert_eq!(
+            buffer.file().unwrap().full_path(cx),
+            Path::new("project1/src/one.rs")
+        );
+    });
+
+    // Restarting the language servers of a buffer restarts them on the remote machine.
+    project.update(cx, |project, cx| {
+        project.restart_language_servers_for_buffers([buffer.clone()], cx);
+    });
+    cx.executor().run_until_parked();
+    let restarted_server = fake_servers.next().await.unwrap();
+    assert_ne!(
+        restarted_server.server.server_id(),
+        fake_server.server.server_id()
+    );
+}
+
+#[gpui::test]
+async fn test_remote_project_search(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "README.md": "# project 1",
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }",
+                    "main.rs": "fn main() { one(); }",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    // The user edits a buffer without saving it.
+    let lib_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    lib_buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "// one\n")], None, cx));
+    cx.executor().run_until_parked();
+
+    // The search runs on the remote machine, and matches the buffer's unsaved
+    // contents. The buffer that the user has open is reused for its matches.
+    let query = SearchQuery::text(
+        "one",
+        false,
+        true,
+        false,
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    let mut results = project.update(cx, |project, cx| project.search(query, cx));
+    let mut matches = Vec::new();
+    while let Some(result) = results.next().await {
+        if let SearchResult::Buffer { buffer, ranges } = result {
+            let (path, ranges) = buffer.read_with(cx, |buffer, _| {
+                let ranges = ranges
+                    .iter()
+                    .map(|range| range.to_offset(buffer))
+                    .collect::<Vec<_>>();
+                (buffer.file().unwrap().path().clone(), ranges)
+            });
+            matches.push((path, ranges, buffer));
+        }
+    }
+    matches.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        matches
+            .iter()
+            .map(|(path, ranges, _)| (path.as_ref(), ranges.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Path::new("src/lib.rs"), vec![3..6, 10..13]),
+            (Path::new("src/main.rs"), vec![12..15]),
+        ]
+    );
+    assert_eq!(matches[0].2, lib_buffer);
+}
+
+#[gpui::test]
+async fn test_remote_reopen_closed_buffer(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+
+    // The server keeps the buffer after the client closes it, and sends it again when the
+    // client needs it.
+    drop(buffer);
+    cx.executor().run_until_parked();
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.remote_id(), buffer_id);
+        assert_eq!(buffer.text(), "fn one() -> usize { 1 }");
+    });
+}
+
+#[gpui::test]
+async fn test_remote_task_templates(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                ".zed": {
+                    "tasks.json": r#"[{ "label": "build", "command": "cargo build" }]"#,
+                },
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+
+    let project = build_project(client_ssh, cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+
+    // The tasks file of the worktree is only on the remote machine, which lists its tasks.
+    let templates = project
+        .update(cx, |project, cx| {
+            project.task_templates(Some(worktree_id), None, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        templates
+            .iter()
+            .map(|(kind, template)| (kind.clone(), template.label.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(
+            TaskSourceKind::Worktree {
+                id: worktree_id,
+                abs_path: "/code/project1/.zed/tasks.json".into(),
+                id_base: "local_tasks_for_worktree".into(),
+            },
+            "build"
+        )]
+    );
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_random_reconnects(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+    mut rng: StdRng,
+) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let headless_project =
+        build_headless_project(server_ssh.clone(), fs.clone(), languages, server_cx);
+    let project = build_project(client_ssh.clone(), cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+
+    // The buffer stays editable while the connection is lost, and the edits reach the server
+    // once the client reconnects.
+    let mut connected = true;
+    for _ in 0..20 {
+        match rng.gen_range(0..100) {
+            0..=19 if connected => {
+                SshSession::fake_disconnect(&client_ssh, &server_ssh);
+                connected = false;
+            }
+            0..=39 if !connected => {
+                SshSession::fake_reconnect(&client_ssh, &server_ssh, cx, server_cx);
+                connected = true;
+            }
+            _ => {
+                buffer.update(cx, |buffer, cx| buffer.randomly_edit(&mut rng, 2, cx));
+            }
+        }
+        if rng.gen() {
+            cx.executor().run_until_parked();
+        }
+    }
+    if !connected {
+        SshSession::fake_reconnect(&client_ssh, &server_ssh, cx, server_cx);
+    }
+    cx.executor().run_until_parked();
+
+    let text = buffer.read_with(cx, |buffer, _| buffer.text());
+    headless_project.update(server_cx, |headless_project, cx| {
+        let server_buffer = headless_project
+            .buffer_store
+            .read(cx)
+            .get(buffer_id)
+            .unwrap();
+        assert_eq!(server_buffer.read(cx).text(), text);
+    });
+    assert_eq!(client_ssh.server_restarts(), 0);
+}
+
+#[gpui::test]
+async fn test_remote_restart(cx: &mut TestAppContext, server_cx: &mut TestAppContext) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }"
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let headless_project =
+        build_headless_project(server_ssh.clone(), fs.clone(), languages.clone(), server_cx);
+    let project = build_project(client_ssh.clone(), cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let buffer_id = buffer.read_with(cx, |buffer, _| buffer.remote_id());
+    buffer.update(cx, |buffer, cx| {
+        let ix = buffer.text().find('1').unwrap();
+        buffer.edit([(ix..ix + 1, "100")], None, cx);
+    });
+    cx.executor().run_until_parked();
+
+    // The remote server stops running while the connection is lost, so the client starts a
+    // new one, and gives it the project's worktrees and unsaved buffers.
+    SshSession::fake_disconnect(&client_ssh, &server_ssh);
+    drop(headless_project);
+    drop(server_ssh);
+    let server_ssh = SshSession::fake_restart(&client_ssh, cx, server_cx);
+    let headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+    cx.executor().run_until_parked();
+
+    let server_buffer = headless_project.update(server_cx, |headless_project, cx| {
+        headless_project
+            .buffer_store
+            .read(cx)
+            .get(buffer_id)
+            .unwrap()
+    });
+    server_buffer.read_with(server_cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn one() -> usize { 100 }");
+    });
+    project.read_with(cx, |project, cx| {
+        assert_eq!(project.worktrees(cx).count(), 1);
+        assert_eq!(
+            project.worktree_for_id(worktree_id, cx),
+            Some(worktree.clone())
+        );
+        assert_eq!(
+            project.ssh_connection_status(),
+            Some(SshConnectionStatus::Connected)
+        );
+    });
+    headless_project.read_with(server_cx, |headless_project, cx| {
+        let worktree_store = headless_project.worktree_store.read(cx);
+        assert!(worktree_store.worktree_for_id(worktree_id, cx).is_some());
+    });
+    worktree.read_with(cx, |worktree, _| {
+        assert!(worktree.entry_for_path("src/lib.rs").is_some());
+    });
+
+    // Later edits reach the new server.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "// restarted\n")], None, cx);
+    });
+    cx.executor().run_until_parked();
+    server_buffer.read_with(server_cx, |buffer, _| {
+        assert_eq!(buffer.text(), "// restarted\nfn one() -> usize { 100 }");
+    });
+
+    // The buffer is saved to the file on the remote machine.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(
+        fs.load("/code/project1/src/lib.rs".as_ref()).await.unwrap(),
+        "// restarted\nfn one() -> usize { 100 }"
+    );
+}
+
+#[gpui::test]
+async fn test_remote_restart_with_changed_files(
+    cx: &mut TestAppContext,
+    server_cx: &mut TestAppContext,
+) {
+    let (client_ssh, server_ssh) = SshSession::fake(cx, server_cx);
+    init_logger();
+
+    let fs = FakeFs::new(server_cx.executor());
+    fs.insert_tree(
+        "/code",
+        json!({
+            "project1": {
+                "src": {
+                    "lib.rs": "fn one() -> usize { 1 }",
+                    "main.rs": "fn main() { one(); }",
+                }
+            },
+        }),
+    )
+    .await;
+
+    let languages = Arc::new(LanguageRegistry::test(server_cx.executor()));
+    let headless_project =
+        build_headless_project(server_ssh.clone(), fs.clone(), languages.clone(), server_cx);
+    let project = build_project(client_ssh.clone(), cx);
+    let (worktree, _) = project
+        .update(cx, |project, cx| {
+            project.find_or_create_worktree("/code/project1", true, cx)
+        })
+        .await
+        .unwrap();
+    let worktree_id = worktree.read_with(cx, |worktree, _| worktree.id());
+    let lib_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/lib.rs")), cx)
+        })
+        .await
+        .unwrap();
+    let main_buffer = project
+        .update(cx, |project, cx| {
+            project.open_buffer((worktree_id, Path::new("src/main.rs")), cx)
+        })
+        .await
+        .unwrap();
+    main_buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "// edited\n")], None, cx)
+    });
+    cx.executor().run_until_parked();
+
+    // Both files change while no server is running.
+    SshSession::fake_disconnect(&client_ssh, &server_ssh);
+    drop(headless_project);
+    drop(server_ssh);
+    fs.insert_file(
+        "/code/project1/src/lib.rs",
+        b"fn one() -> usize { 2 }".to_vec(),
+    )
+    .await;
+    fs.insert_file("/code/project1/src/main.rs", b"fn main() {}".to_vec())
+        .await;
+    let server_ssh = SshSession::fake_restart(&client_ssh, cx, server_cx);
+    let _headless_project = build_headless_project(server_ssh, fs.clone(), languages, server_cx);
+    cx.executor().run_until_parked();
+
+    // The buffer without unsaved changes is reloaded, and the other one has a conflict.
+    lib_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "fn one() -> usize { 2 }");
+        assert!(!buffer.is_dirty());
+    });
+    main_buffer.read_with(cx, |buffer, _| {
+        assert_eq!(buffer.text(), "// edited\nfn main() { one(); }");
+        assert!(buffer.has_conflict());
+    });
+}
+
+fn rust_lang() -> Arc<Language> {
+    Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        None,
+    ))
+}
+
+fn build_headless_project(
+    ssh: Arc<SshSession>,
+    fs: Arc<FakeFs>,
+    languages: Arc<LanguageRegistry>,
+    cx: &mut TestAppContext,
+) -> Model<HeadlessProject> {
+    cx.update(HeadlessProject::init);
+    let http = FakeHttpClient::with_404_response();
+    let node = FakeNodeRuntime::new();
+    cx.new_model(|cx| HeadlessProject::new(ssh, fs, http, node, languages, cx))
+}
+
 fn build_project(ssh: Arc<SshSession>, cx: &mut TestAppContext) -> Model<Project> {
     cx.update(|cx| {
         let settings_store = SettingsStore::test(cx);
@@ -1,12 +1,12 @@
 use crate::{
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
     SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
-    ToggleReplace, ToggleWholeWord,
+    ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use collections::{HashMap, HashSet};
 use editor::{
     actions::SelectAll,
-    items::active_match_index,
+    items::{active_match_index, replacement_for_match},
     scroll::{Autoscroll, Axis},
     Anchor, Editor, EditorElement, EditorEvent, EditorSettings, EditorStyle, MultiBuffer,
     MAX_TAB_TITLE_LEN,
@@ -17,6 +17,7 @@ use gpui::{
     ModelContext, ParentElement, Point, Render, SharedString, Styled, Subscription, Task,
     TextStyle, UpdateGlobal, View, ViewContext, VisualContext, WeakModel, WindowContext,
 };
+use language::TransactionId;
 use menu::Confirm;
 use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath};
 use settings::Settings;
@@ -45,7 +46,15 @@ const MAX_INPUT_WIDTH_REMS: f32 = 30.;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        ToggleReplacePreview,
+        ToggleMatchExcluded,
+        ToggleFileExcluded
+    ]
 );
 
 #[derive(Default)]
@@ -71,9 +80,22 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleReplacePreview, cx| search_bar.toggle_replace_preview(cx),
+        );
+        register_workspace_action(workspace, move |search_bar, _: &ToggleMatchExcluded, cx| {
+            search_bar.toggle_match_excluded(cx)
+        });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleFileExcluded, cx| {
+            search_bar.toggle_file_excluded(cx)
+        });
         register_workspace_action(
             workspace,
             move |search_bar, action: &SelectPrevMatch, cx| {
@@ -118,6 +140,10 @@ struct ProjectSearch {
     excerpts: Model<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
+    /// Indices into `match_ranges` of the matches left out of replacements.
+    excluded_matches: HashSet<usize>,
+    /// The matches excluded by each replacement, so that undoing it includes them again.
+    replaced_matches: Vec<(TransactionId, Vec<usize>)>,
     active_query: Option<SearchQuery>,
     last_search_query_text: Option<String>,
     search_id: usize,
@@ -147,6 +173,7 @@ pub struct ProjectSearchView {
     excluded_files_editor: View<Editor>,
     filters_enabled: bool,
     replace_enabled: bool,
+    replace_preview_enabled: bool,
     _subscriptions: Vec<Subscription>,
 }
 
@@ -171,6 +198,8 @@ impl ProjectSearch {
             excerpts: cx.new_model(|_| MultiBuffer::new(replica_id, capability)),
             pending_search: Default::default(),
             match_ranges: Default::default(),
+            excluded_matches: Default::default(),
+            replaced_matches: Default::default(),
             active_query: None,
             last_search_query_text: None,
             search_id: 0,
@@ -188,6 +217,8 @@ impl ProjectSearch {
                 .update(cx, |excerpts, cx| cx.new_model(|cx| excerpts.clone(cx))),
             pending_search: Default::default(),
             match_ranges: self.match_ranges.clone(),
+            excluded_matches: self.excluded_matches.clone(),
+            replaced_matches: self.replaced_matches.clone(),
             active_query: self.active_query.clone(),
             last_search_query_text: self.last_search_query_text.clone(),
             search_id: self.search_id,
@@ -208,11 +239,15 @@ impl ProjectSearch {
         self.search_id += 1;
         self.active_query = Some(query);
         self.match_ranges.clear();
+        self.excluded_matches.clear();
+        self.replaced_matches.clear();
         self.pending_search = Some(cx.spawn(|this, mut cx| async move {
             let mut matches = search;
             let this = this.upgrade()?;
             this.update(&mut cx, |this, cx| {
                 this.match_ranges.clear();
+                this.excluded_matches.clear();
+                this.replaced_matches.clear();
                 this.excerpts.update(cx, |this, cx| this.clear(cx));
                 this.no_results = Some(true);
                 this.limit_reached = false;
@@ -262,6 +297,81 @@ impl ProjectSearch {
         }));
         cx.notify();
     }
+
+    fn included_match_ranges(&self) -> impl Iterator<Item = &Range<Anchor>> {
+        self.match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| !self.excluded_matches.contains(ix))
+            .map(|(_, range)| range)
+    }
+
+    /// Excludes the replaced matches, as their text no longer matches and must not be replaced
+    /// again, unless the transaction that replaced them is undone.
+    fn matches_replaced(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        match_indices: Vec<usize>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.excluded_matches.extend(match_indices.iter().copied());
+        if let Some(transaction_id) = transaction_id {
+            self.replaced_matches.push((transaction_id, match_indices));
+        }
+        cx.notify();
+    }
+
+    fn replacement_undone(&mut self, transaction_id: TransactionId, cx: &mut ModelContext<Self>) {
+        let mut undone = false;
+        let excluded_matches = &mut self.excluded_matches;
+        self.replaced_matches
+            .retain(|(replacement_id, match_indices)| {
+                if *replacement_id != transaction_id {
+                    return true;
+                }
+                for ix in match_indices {
+                    excluded_matches.remove(ix);
+                }
+                undone = true;
+                false
+            });
+        if undone {
+            cx.notify();
+        }
+    }
+
+    fn toggle_match_excluded(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        if !self.excluded_matches.remove(&ix) {
+            self.excluded_matches.insert(ix);
+        }
+        cx.notify();
+    }
+
+    /// Excludes every match in the file of the given match, or includes them all again if they
+    /// are all excluded already.
+    fn toggle_file_excluded(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        let Some(buffer_id) = self.match_ranges.get(ix).map(|range| range.start.buffer_id) else {
+            return;
+        };
+        let file_matches = self
+            .match_ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, range)| range.start.buffer_id == buffer_id)
+            .map(|(ix, _)| ix)
+            .collect::<Vec<_>>();
+        if file_matches
+            .iter()
+            .all(|ix| self.excluded_matches.contains(ix))
+        {
+            for ix in file_matches {
+                self.excluded_matches.remove(&ix);
+            }
+        } else {
+            self.excluded_matches.extend(file_matches);
+        }
+        cx.notify();
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -522,6 +632,14 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut ViewContext<Self>) {
         self.search_options.toggle(option);
+        // A query is either a regex or a syntax tree pattern, never both.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.model.read(cx).project.downgrade(),
@@ -530,6 +648,27 @@ impl ProjectSearchView {
         });
     }
 
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        self.replace_preview_enabled = !self.replace_preview_enabled;
+        self.refresh_replacement_previews(cx);
+        cx.notify();
+    }
+
+    fn toggle_match_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(active_index) = self.active_match_index {
+            self.model.update(cx, |model, cx| {
+                model.toggle_match_excluded(active_index, cx)
+            });
+        }
+    }
+
+    fn toggle_file_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(active_index) = self.active_match_index {
+            self.model
+                .update(cx, |model, cx| model.toggle_file_excluded(active_index, cx));
+        }
+    }
+
     fn replace_next(&mut self, _: &ReplaceNext, cx: &mut ViewContext<Self>) {
         if self.model.read(cx).match_ranges.is_empty() {
             return;
@@ -537,6 +676,10 @@ impl ProjectSearchView {
         let Some(active_index) = self.active_match_index else {
             return;
         };
+        if self.model.read(cx).excluded_matches.contains(&active_index) {
+            self.select_match(Direction::Next, cx);
+            return;
+        }
 
         let query = self.model.read(cx).active_query.clone();
         if let Some(query) = query {
@@ -544,8 +687,11 @@ impl ProjectSearchView {
 
             // TODO: Do we need the clone here?
             let mat = self.model.read(cx).match_ranges[active_index].clone();
-            self.results_editor.update(cx, |editor, cx| {
-                editor.replace(&mat, &query, cx);
+            let transaction_id = self.results_editor.update(cx, |editor, cx| {
+                editor.transact(cx, |editor, cx| editor.replace(&mat, &query, cx))
+            });
+            self.model.update(cx, |model, cx| {
+                model.matches_replaced(transaction_id, vec![active_index], cx)
             });
             self.select_match(Direction::Next, cx)
         }
@@ -563,19 +709,60 @@ impl ProjectSearchView {
         };
         let query = query.clone().with_replacement(self.replacement(cx));
 
-        let match_ranges = self
-            .model
-            .update(cx, |model, _| mem::take(&mut model.match_ranges));
+        let model = self.model.read(cx);
+        let (match_indices, match_ranges): (Vec<_>, Vec<_>) = model
+            .match_ranges
+            .iter()
+            .cloned()
+            .enumerate()
+            .filter(|(ix, _)| !model.excluded_matches.contains(ix))
+            .unzip();
         if match_ranges.is_empty() {
             return;
         }
 
-        self.results_editor.update(cx, |editor, cx| {
-            editor.replace_all(&mut match_ranges.iter(), &query, cx);
+        // All buffers are edited in a single transaction of the results' multibuffer, so the
+        // whole replacement can be undone at once.
+        let transaction_id = self.results_editor.update(cx, |editor, cx| {
+            editor.transact(cx, |editor, cx| {
+                editor.replace_all(&mut match_ranges.iter(), &query, cx)
+            })
         });
+        self.model.update(cx, |model, cx| {
+            model.matches_replaced(transaction_id, match_indices, cx)
+        });
+    }
+
+    fn refresh_replacement_previews(&mut self, cx: &mut ViewContext<Self>) {
+        let query = self
+            .model
+            .read(cx)
+            .active_query
+            .clone()
+            .filter(|_| self.replace_enabled && self.replace_preview_enabled);
+        let Some(query) = query else {
+            self.results_editor
+                .update(cx, |editor, cx| editor.clear_replacement_previews(cx));
+            return;
+        };
+        let query = query.with_replacement(self.replacement(cx));
+        let match_ranges = self
+            .model
+            .read(cx)
+            .included_match_ranges()
+            .cloned()
+            .collect::<Vec<_>>();
 
-        self.model.update(cx, |model, _cx| {
-            model.match_ranges = match_ranges;
+        self.results_editor.update(cx, |editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let previews = match_ranges
+                .into_iter()
+                .filter_map(|range| {
+                    let replacement = replacement_for_match(&snapshot, &range, &query)?;
+                    Some((range, replacement))
+                })
+                .collect();
+            editor.set_replacement_previews(previews, cx);
         });
     }
 
@@ -629,6 +816,14 @@ impl ProjectSearchView {
             }
             editor
         });
+        subscriptions.push(cx.subscribe(
+            &replacement_editor,
+            |this, _, event: &EditorEvent, cx| {
+                if matches!(event, EditorEvent::BufferEdited) {
+                    this.refresh_replacement_previews(cx);
+                }
+            },
+        ));
         let results_editor = cx.new_view(|cx| {
             let mut editor = Editor::for_multibuffer(excerpts, Some(project.clone()), true, cx);
             editor.set_searchable(false);
@@ -638,8 +833,14 @@ impl ProjectSearchView {
 
         subscriptions.push(
             cx.subscribe(&results_editor, |this, _, event: &EditorEvent, cx| {
-                if matches!(event, editor::EditorEvent::SelectionsChanged { .. }) {
-                    this.update_match_index(cx);
+                match event {
+                    EditorEvent::SelectionsChanged { .. } => this.update_match_index(cx),
+                    EditorEvent::TransactionUndone { transaction_id } => {
+                        let transaction_id = *transaction_id;
+                        this.model
+                            .update(cx, |model, cx| model.replacement_undone(transaction_id, cx));
+                    }
+                    _ => {}
                 }
                 // Reraise editor events for workspace item activation purposes
                 cx.emit(ViewEvent::EditorEvent(event.clone()));
@@ -698,6 +899,7 @@ impl ProjectSearchView {
             excluded_files_editor,
             filters_enabled,
             replace_enabled: false,
+            replace_preview_enabled: false,
             _subscriptions: subscriptions,
         };
         this.model_changed(cx);
@@ -875,7 +1077,31 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(_e) => {
+                    let should_mark_error = self.panels_with_errors.insert(InputPanel::Query);
+                    if should_mark_error {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -999,6 +1225,12 @@ impl ProjectSearchView {
 
     fn model_changed(&mut self, cx: &mut ViewContext<Self>) {
         let match_ranges = self.model.read(cx).match_ranges.clone();
+        let included_match_ranges = self
+            .model
+            .read(cx)
+            .included_match_ranges()
+            .cloned()
+            .collect::<Vec<_>>();
         if match_ranges.is_empty() {
             self.active_match_index = None;
         } else {
@@ -1017,7 +1249,7 @@ impl ProjectSearchView {
                     editor.scroll(Point::default(), Some(Axis::Vertical), cx);
                 }
                 editor.highlight_background::<Self>(
-                    &match_ranges,
+                    &included_match_ranges,
                     |theme| theme.search_match_background,
                     cx,
                 );
@@ -1026,6 +1258,7 @@ impl ProjectSearchView {
                 self.focus_results_editor(cx);
             }
         }
+        self.refresh_replacement_previews(cx);
 
         cx.emit(ViewEvent::UpdateTab);
         cx.notify();
@@ -1076,6 +1309,14 @@ impl ProjectSearchView {
                     .key_binding(KeyBinding::for_action(&ToggleRegex, cx))
                     .on_click(|_event, cx| cx.dispatch_action(ToggleRegex.boxed_clone())),
             )
+            .child(
+                Button::new("structural", "Match syntax tree patterns")
+                    .icon(IconName::ListTree)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .key_binding(KeyBinding::for_action(&ToggleStructural, cx))
+                    .on_click(|_event, cx| cx.dispatch_action(ToggleStructural.boxed_clone())),
+            )
             .child(
                 Button::new("match-case", "Match case")
                     .icon(IconName::CaseSensitive)
@@ -1217,11 +1458,30 @@ impl ProjectSearchBar {
                     this.query_editor.focus_handle(cx)
                 };
                 cx.focus(&editor_to_focus);
+                this.refresh_replacement_previews(cx);
                 cx.notify();
             });
         }
     }
 
+    fn toggle_replace_preview(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |search, cx| search.toggle_replace_preview(cx));
+        }
+    }
+
+    fn toggle_match_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |search, cx| search.toggle_match_excluded(cx));
+        }
+    }
+
+    fn toggle_file_excluded(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(search) = &self.active_project_search {
+            search.update(cx, |search, cx| search.toggle_file_excluded(cx));
+        }
+    }
+
     fn toggle_filters(&mut self, cx: &mut ViewContext<Self>) -> bool {
         if let Some(search_view) = self.active_project_search.as_ref() {
             search_view.update(cx, |search_view, cx| {
@@ -1398,6 +1658,12 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
@@ -1536,6 +1802,44 @@ impl Render for ProjectSearchBar {
                         }))
                         .tooltip(|cx| Tooltip::for_action("Replace all matches", &ReplaceAll, cx)),
                 )
+                .child(
+                    IconButton::new("project-search-replace-preview", IconName::Eye)
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_replace_preview(cx);
+                        }))
+                        .selected(search.replace_preview_enabled)
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Toggle replace preview", &ToggleReplacePreview, cx)
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-exclude-match", IconName::Dash)
+                        .disabled(search.active_match_index.is_none())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_match_excluded(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Exclude match from replacement",
+                                &ToggleMatchExcluded,
+                                cx,
+                            )
+                        }),
+                )
+                .child(
+                    IconButton::new("project-search-exclude-file", IconName::File)
+                        .disabled(search.active_match_index.is_none())
+                        .on_click(cx.listener(|this, _, cx| {
+                            this.toggle_file_excluded(cx);
+                        }))
+                        .tooltip(|cx| {
+                            Tooltip::for_action(
+                                "Exclude file from replacement",
+                                &ToggleFileExcluded,
+                                cx,
+                            )
+                        }),
+                )
             });
             h_flex()
                 .pr(rems(5.5))
@@ -1855,6 +2159,134 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_replace_all_skips_excluded_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "TWO", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.replace_enabled = true;
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("2", cx));
+
+                // Exclude the second match in three.rs, then every match in two.rs.
+                search_view.select_match(Direction::Next, cx);
+                search_view.toggle_match_excluded(cx);
+                search_view.select_match(Direction::Next, cx);
+                assert_eq!(search_view.active_match_index, Some(2));
+                search_view.toggle_file_excluded(cx);
+                assert_eq!(
+                    search_view.model.read(cx).excluded_matches,
+                    HashSet::from_iter([1, 2])
+                );
+
+                search_view.replace_all(&ReplaceAll, cx);
+                assert_eq!(
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx)),
+                    "\n\n\nconst THREE: usize = one::ONE + 2::TWO;\n\n\n\n\nconst TWO: usize = one::ONE + one::ONE;\n"
+                );
+
+                assert_eq!(
+                    search_view.model.read(cx).excluded_matches,
+                    HashSet::from_iter([0, 1, 2])
+                );
+
+                // Undoing the replacement includes the replaced match again, but keeps the
+                // matches that were excluded by hand.
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.undo(&editor::actions::Undo, cx);
+                    assert_eq!(
+                        editor.display_text(cx),
+                        "\n\n\nconst THREE: usize = one::ONE + two::TWO;\n\n\n\n\nconst TWO: usize = one::ONE + one::ONE;\n"
+                    );
+                });
+                assert_eq!(
+                    search_view.model.read(cx).excluded_matches,
+                    HashSet::from_iter([1, 2])
+                );
+            })
+            .unwrap();
+    }
+
+    #[gpui::test]
+    async fn test_undo_replace_all_in_every_buffer(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+                "four.rs": "const FOUR: usize = one::ONE + three::THREE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+
+        perform_search(search_view, "ONE", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.replace_enabled = true;
+                search_view
+                    .replacement_editor
+                    .update(cx, |editor, cx| editor.set_text("1", cx));
+                search_view.select_match(Direction::Next, cx);
+
+                let buffers = search.read(cx).excerpts.read(cx).all_buffers();
+                assert_eq!(buffers.len(), 4);
+                let buffer_texts = |cx: &AppContext| {
+                    let mut texts = buffers
+                        .iter()
+                        .map(|buffer| buffer.read(cx).text())
+                        .collect::<Vec<_>>();
+                    texts.sort();
+                    texts
+                };
+                let original_texts = buffer_texts(cx);
+
+                search_view.replace_all(&ReplaceAll, cx);
+                assert_eq!(
+                    buffer_texts(cx),
+                    [
+                        "const 1: usize = 1;",
+                        "const FOUR: usize = 1::1 + three::THREE;",
+                        "const THREE: usize = 1::1 + two::TWO;",
+                        "const TWO: usize = 1::1 + 1::1;",
+                    ]
+                );
+
+                search_view.results_editor.update(cx, |editor, cx| {
+                    editor.undo(&editor::actions::Undo, cx);
+                });
+                assert_eq!(buffer_texts(cx), original_texts);
+                assert!(search_view.model.read(cx).excluded_matches.is_empty());
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
@@ -0,0 +1,32 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Turns a [`crate::TaskTemplate`] into a debug session: instead of spawning the task's command in a terminal,
+/// Zed starts the given debug adapter and asks it to launch the command (or to attach to a running process).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DebugAdapterConfig {
+    /// Name of the debug adapter to start, either one built into Zed (`lldb`, `python`, `go`) or one provided by an extension.
+    pub adapter: String,
+    /// Whether to launch the task's command under the debugger or to attach to an already running process.
+    #[serde(default)]
+    pub request: DebugRequestType,
+    /// Arguments to pass to the debug adapter's `launch` or `attach` request as is,
+    /// overriding the ones Zed derives from the task.
+    #[serde(default)]
+    pub initialize_args: Option<serde_json::Value>,
+}
+
+/// The way a debug adapter starts debugging a program.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugRequestType {
+    /// Launch the task's command under the debugger.
+    #[default]
+    Launch,
+    /// Attach to an already running process.
+    Attach {
+        /// Id of the process to attach to.
+        process_id: u32,
+    },
+}
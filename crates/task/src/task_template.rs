@@ -8,8 +8,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, Shell, SpawnInTerminal, TaskContext, TaskId, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    DebugAdapterConfig, DebugRequestType, ResolvedTask, Shell, SpawnInTerminal, TaskContext,
+    TaskId, VariableName, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -57,6 +57,9 @@ pub struct TaskTemplate {
     /// Which shell to use when spawning the task.
     #[serde(default)]
     pub shell: Shell,
+    /// Debug the task's command with a debug adapter, rather than spawning it in a terminal.
+    #[serde(default)]
+    pub debug: Option<DebugAdapterConfig>,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -107,7 +110,15 @@ impl TaskTemplate {
     /// Every [`ResolvedTask`] gets a [`TaskId`], based on the `id_base` (to avoid collision with various task sources),
     /// and hashes of its template and [`TaskContext`], see [`ResolvedTask`] fields' documentation for more details.
     pub fn resolve_task(&self, id_base: &str, cx: &TaskContext) -> Option<ResolvedTask> {
-        if self.label.trim().is_empty() || self.command.trim().is_empty() {
+        // Attaching a debugger to a running process is the only way to run a task without a command.
+        let is_attach = matches!(
+            self.debug,
+            Some(DebugAdapterConfig {
+                request: DebugRequestType::Attach { .. },
+                ..
+            })
+        );
+        if self.label.trim().is_empty() || (self.command.trim().is_empty() && !is_attach) {
             return None;
         }
 
@@ -221,7 +232,12 @@ impl TaskTemplate {
                     },
                 ),
                 command,
-                args: self.args.clone(),
+                // Debug adapters pass the arguments to the program as is, without a shell to substitute them.
+                args: if self.debug.is_some() {
+                    args_with_substitutions
+                } else {
+                    self.args.clone()
+                },
                 env,
                 use_new_terminal: self.use_new_terminal,
                 allow_concurrent_runs: self.allow_concurrent_runs,
@@ -804,4 +820,60 @@ mod tests {
             "overwritten"
         );
     }
+
+    #[test]
+    fn test_debug_task_resolution() {
+        let context = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([(
+                VariableName::File,
+                "/dir/main.py".to_string(),
+            )]),
+            project_env: HashMap::default(),
+        };
+
+        let launch = TaskTemplate {
+            label: "debug file".to_string(),
+            command: "python".to_string(),
+            args: vec![VariableName::File.template_value()],
+            debug: Some(DebugAdapterConfig {
+                adapter: "python".to_string(),
+                request: DebugRequestType::Launch,
+                initialize_args: None,
+            }),
+            ..TaskTemplate::default()
+        };
+        let resolved = launch
+            .resolve_task(TEST_ID_BASE, &context)
+            .unwrap()
+            .resolved
+            .unwrap();
+        assert_eq!(
+            resolved.args,
+            vec!["/dir/main.py".to_string()],
+            "debug adapters should get substituted arguments"
+        );
+
+        let attach = TaskTemplate {
+            label: "attach".to_string(),
+            debug: Some(DebugAdapterConfig {
+                adapter: "lldb".to_string(),
+                request: DebugRequestType::Attach { process_id: 42 },
+                initialize_args: None,
+            }),
+            ..TaskTemplate::default()
+        };
+        assert!(
+            attach.resolve_task(TEST_ID_BASE, &context).is_some(),
+            "attaching to a process should not need a command"
+        );
+        let launch_without_command = TaskTemplate {
+            command: String::new(),
+            ..launch
+        };
+        assert_eq!(
+            launch_without_command.resolve_task(TEST_ID_BASE, &context),
+            None
+        );
+    }
 }
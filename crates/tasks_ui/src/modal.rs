@@ -225,21 +225,11 @@ impl PickerDelegate for TasksModalDelegate {
                                     if project.is_remote() && ssh_connection_string.is_none() {
                                         Task::ready((Vec::new(), Vec::new()))
                                     } else {
-                                        let remote_templates = if project.is_local() {
-                                            None
-                                        } else {
-                                            project
-                                                .remote_id()
-                                                .filter(|_| ssh_connection_string.is_some())
-                                                .map(|project_id| {
-                                                    project.query_remote_task_templates(
-                                                        project_id,
-                                                        worktree,
-                                                        location.as_ref(),
-                                                        cx,
-                                                    )
-                                                })
-                                        };
+                                        let remote_templates = project.query_remote_task_templates(
+                                            worktree,
+                                            location.as_ref(),
+                                            cx,
+                                        );
                                         project
                                             .task_inventory()
                                             .read(cx)
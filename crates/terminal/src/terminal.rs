@@ -33,7 +33,7 @@ use mappings::mouse::{
     scroll_report,
 };
 
-use collections::{HashMap, VecDeque};
+use collections::{HashMap, HashSet, VecDeque};
 use futures::StreamExt;
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
@@ -91,6 +91,8 @@ const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
 const DEBUG_LINE_HEIGHT: Pixels = px(5.);
+/// Matches the addresses that servers announce listening on, like `http://localhost:3000`.
+const ANNOUNCED_ADDRESS_REGEX: &str = r"(localhost|127\.0\.0\.1|0\.0\.0\.0|\[::1?\]):[0-9]{2,5}";
 
 ///Upward flowing events, for changing the title and such
 #[derive(Clone, Debug)]
@@ -104,6 +106,9 @@ pub enum Event {
     SelectionsChanged,
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
+    /// The output shows a server listening on the port, for the first time. Only emitted once
+    /// [`Terminal::detect_announced_ports`] has been called.
+    PortAnnounced(u16),
 }
 
 #[derive(Clone, Debug)]
@@ -449,6 +454,10 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            scrolling_history,
+            announced_address_regex: None,
+            announced_ports: HashSet::default(),
+            announced_ports_scanned_line: 0,
         };
 
         Ok(TerminalBuilder {
@@ -602,6 +611,12 @@ pub struct Terminal {
     hovered_word: bool,
     url_regex: RegexSearch,
     word_regex: RegexSearch,
+    scrolling_history: usize,
+    announced_address_regex: Option<RegexSearch>,
+    announced_ports: HashSet<u16>,
+    /// The line that the output has been scanned for announced ports up to, counted from the top
+    /// of the scrollback.
+    announced_ports_scanned_line: usize,
     task: Option<TaskState>,
 }
 
@@ -676,6 +691,7 @@ impl Terminal {
             }
             AlacTermEvent::Wakeup => {
                 cx.emit(Event::Wakeup);
+                self.find_announced_ports(cx);
 
                 if self.pty_info.has_changed() {
                     cx.emit(Event::TitleChanged);
@@ -691,6 +707,48 @@ impl Terminal {
         }
     }
 
+    /// Starts looking for the ports that servers announce listening on in the output, such as
+    /// the ones of the processes run on a remote host that may need to be forwarded.
+    pub fn detect_announced_ports(&mut self) {
+        if self.announced_address_regex.is_none() {
+            self.announced_address_regex = RegexSearch::new(ANNOUNCED_ADDRESS_REGEX).ok();
+        }
+    }
+
+    fn find_announced_ports(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(regex) = self.announced_address_regex.as_mut() else {
+            return;
+        };
+        let term = self.term.lock();
+        let history_size = term.grid().history_size();
+        let cursor_line = term.grid().cursor.point.line;
+
+        // Only the lines output since the last scan are scanned, from the one that the cursor was
+        // on, which may have been written to since. Once the scrollback is full, the lines that
+        // scrolled into it can't be told apart from the earlier ones, so the screen is scanned.
+        let start_line = if history_size >= self.scrolling_history {
+            Line(0)
+        } else {
+            Line(self.announced_ports_scanned_line as i32 - history_size as i32)
+        };
+        let start_line = start_line.max(term.topmost_line()).min(cursor_line);
+        let start = term.line_search_left(AlacPoint::new(start_line, Column(0)));
+        let end = term.line_search_right(AlacPoint::new(cursor_line, Column(0)));
+        let ports = RegexIter::new(start, end, AlacDirection::Right, &term, regex)
+            .filter_map(|address| {
+                announced_port(&term.bounds_to_string(*address.start(), *address.end()))
+            })
+            .collect::<Vec<_>>();
+        self.announced_ports_scanned_line = history_size + cursor_line.0.max(0) as usize;
+        drop(term);
+
+        for port in ports {
+            if self.announced_ports.insert(port) {
+                cx.emit(Event::PortAnnounced(port));
+            }
+        }
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -1676,6 +1734,12 @@ pub fn visible_regex_match_iter<'a, T>(
         .take_while(move |rm| rm.start().line <= viewport_end)
 }
 
+/// The port of an address matched by [`ANNOUNCED_ADDRESS_REGEX`].
+fn announced_port(address: &str) -> Option<u16> {
+    let (_, port) = address.rsplit_once(':')?;
+    port.parse().ok().filter(|port| *port != 0)
+}
+
 fn make_selection(range: &RangeInclusive<AlacPoint>) -> Selection {
     let mut selection = Selection::new(SelectionType::Simple, *range.start(), AlacDirection::Left);
     selection.update(*range.end(), AlacDirection::Right);
@@ -1793,9 +1857,19 @@ mod tests {
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        announced_port, content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent,
+        TerminalSize,
     };
 
+    #[test]
+    fn test_announced_port() {
+        assert_eq!(announced_port("localhost:3000"), Some(3000));
+        assert_eq!(announced_port("0.0.0.0:8080"), Some(8080));
+        assert_eq!(announced_port("[::]:5173"), Some(5173));
+        assert_eq!(announced_port("127.0.0.1:99999"), None);
+        assert_eq!(announced_port("localhost:00"), None);
+    }
+
     #[test]
     fn test_rgb_for_index() {
         // Test every possible value in the color cube.
@@ -36,6 +36,7 @@ pub struct TerminalSettings {
     pub alternate_scroll: AlternateScroll,
     pub option_as_meta: bool,
     pub copy_on_select: bool,
+    pub forward_announced_ports: bool,
     pub button: bool,
     pub dock: TerminalDockPosition,
     pub default_width: Pixels,
@@ -148,6 +149,12 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: false
     pub copy_on_select: Option<bool>,
+    /// Whether the ports that servers run in the terminal on the remote host of an SSH project
+    /// announce listening on, like `http://localhost:3000`, are forwarded to the same local
+    /// ports. Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: false
+    pub forward_announced_ports: Option<bool>,
     /// Whether to show the terminal button in the status bar.
     ///
     /// Default: true
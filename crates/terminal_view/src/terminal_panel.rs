@@ -383,6 +383,72 @@ impl TerminalPanel {
     }
 
     fn spawn_task(&mut self, spawn_in_terminal: &SpawnInTerminal, cx: &mut ViewContext<Self>) {
+        let is_ssh = self.workspace.upgrade().map_or(false, |workspace| {
+            workspace.read(cx).project().read(cx).is_ssh()
+        });
+        // The system shell of an SSH project's remote machine is only known once the task is
+        // started there.
+        let spawn_task = if is_ssh && spawn_in_terminal.shell == Shell::System {
+            spawn_in_terminal.clone()
+        } else {
+            let Some(spawn_task) = Self::wrap_in_shell(spawn_in_terminal) else {
+                return;
+            };
+            spawn_task
+        };
+
+        let allow_concurrent_runs = spawn_in_terminal.allow_concurrent_runs;
+        let use_new_terminal = spawn_in_terminal.use_new_terminal;
+
+        if allow_concurrent_runs && use_new_terminal {
+            self.spawn_in_new_terminal(spawn_task, cx)
+                .detach_and_log_err(cx);
+            return;
+        }
+
+        let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
+        if terminals_for_task.is_empty() {
+            self.spawn_in_new_terminal(spawn_task, cx)
+                .detach_and_log_err(cx);
+            return;
+        }
+        let (existing_item_index, existing_terminal) = terminals_for_task
+            .last()
+            .expect("covered no terminals case above")
+            .clone();
+        if allow_concurrent_runs {
+            debug_assert!(
+                !use_new_terminal,
+                "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
+            );
+            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+        } else {
+            self.deferred_tasks.insert(
+                spawn_in_terminal.id.clone(),
+                cx.spawn(|terminal_panel, mut cx| async move {
+                    wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
+                    terminal_panel
+                        .update(&mut cx, |terminal_panel, cx| {
+                            if use_new_terminal {
+                                terminal_panel
+                                    .spawn_in_new_terminal(spawn_task, cx)
+                                    .detach_and_log_err(cx);
+                            } else {
+                                terminal_panel.replace_terminal(
+                                    spawn_task,
+                                    existing_item_index,
+                                    existing_terminal,
+                                    cx,
+                                );
+                            }
+                        })
+                        .ok();
+                }),
+            );
+        }
+    }
+
+    fn wrap_in_shell(spawn_in_terminal: &SpawnInTerminal) -> Option<SpawnInTerminal> {
         let mut spawn_task = spawn_in_terminal.clone();
         // Set up shell args unconditionally, as tasks are always spawned inside of a shell.
         let Some((shell, mut user_args)) = (match spawn_in_terminal.shell.clone() {
@@ -390,7 +456,7 @@ impl TerminalPanel {
             Shell::Program(shell) => Some((shell, Vec::new())),
             Shell::WithArguments { program, args } => Some((program, args)),
         }) else {
-            return;
+            return None;
         };
         #[cfg(target_os = "windows")]
         let windows_shell_type = to_windows_shell_type(&shell);
@@ -447,57 +513,7 @@ impl TerminalPanel {
             }
         }
         spawn_task.args = user_args;
-        let spawn_task = spawn_task;
-
-        let allow_concurrent_runs = spawn_in_terminal.allow_concurrent_runs;
-        let use_new_terminal = spawn_in_terminal.use_new_terminal;
-
-        if allow_concurrent_runs && use_new_terminal {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
-        }
-
-        let terminals_for_task = self.terminals_for_task(&spawn_in_terminal.full_label, cx);
-        if terminals_for_task.is_empty() {
-            self.spawn_in_new_terminal(spawn_task, cx)
-                .detach_and_log_err(cx);
-            return;
-        }
-        let (existing_item_index, existing_terminal) = terminals_for_task
-            .last()
-            .expect("covered no terminals case above")
-            .clone();
-        if allow_concurrent_runs {
-            debug_assert!(
-                !use_new_terminal,
-                "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
-            );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
-        } else {
-            self.deferred_tasks.insert(
-                spawn_in_terminal.id.clone(),
-                cx.spawn(|terminal_panel, mut cx| async move {
-                    wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
-                                terminal_panel
-                                    .spawn_in_new_terminal(spawn_task, cx)
-                                    .detach_and_log_err(cx);
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
-                }),
-            );
-        }
+        Some(spawn_task)
     }
 
     pub fn spawn_in_new_terminal(
@@ -736,6 +736,8 @@ fn subscribe_for_terminal_events(
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
+            // Handled by the project, which forwards the ports announced on remote hosts.
+            Event::PortAnnounced(_) => {}
         });
     vec![terminal_subscription, terminal_events_subscription]
 }
@@ -1214,6 +1216,8 @@ impl SearchableItem for TerminalView {
                 .unwrap()),
             ),
             SearchQuery::Regex { .. } => regex_search_for_query(&query),
+            // Terminal output has no syntax tree to match.
+            SearchQuery::Structural { .. } => None,
         };
 
         if let Some(s) = searcher {
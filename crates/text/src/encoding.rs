@@ -0,0 +1,233 @@
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, fmt};
+
+/// The encodings that files can be reopened or saved with, other than UTF-8.
+const LEGACY_ENCODINGS: &[&encoding_rs::Encoding] = &[
+    encoding_rs::WINDOWS_1252,
+    encoding_rs::ISO_8859_2,
+    encoding_rs::ISO_8859_15,
+    encoding_rs::WINDOWS_1250,
+    encoding_rs::WINDOWS_1251,
+    encoding_rs::KOI8_R,
+    encoding_rs::WINDOWS_1253,
+    encoding_rs::WINDOWS_1254,
+    encoding_rs::WINDOWS_1255,
+    encoding_rs::WINDOWS_1256,
+    encoding_rs::WINDOWS_1257,
+    encoding_rs::WINDOWS_1258,
+    encoding_rs::WINDOWS_874,
+    encoding_rs::SHIFT_JIS,
+    encoding_rs::EUC_JP,
+    encoding_rs::ISO_2022_JP,
+    encoding_rs::GBK,
+    encoding_rs::GB18030,
+    encoding_rs::BIG5,
+    encoding_rs::EUC_KR,
+];
+
+/// The character encoding of a file, along with whether it starts with a byte order mark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Encoding {
+    encoding: &'static encoding_rs::Encoding,
+    bom: bool,
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())?;
+        if self.bom {
+            f.write_str(" with BOM")?;
+        }
+        Ok(())
+    }
+}
+
+impl Encoding {
+    pub const UTF8: Self = Self {
+        encoding: encoding_rs::UTF_8,
+        bom: false,
+    };
+
+    /// Returns the encoding with the given name or label, such as `"UTF-16LE"` or `"latin1"`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let encoding = encoding_rs::Encoding::for_label(name.as_bytes())?;
+        let is_supported = encoding == encoding_rs::UTF_8
+            || Self::is_utf16(encoding)
+            || LEGACY_ENCODINGS.contains(&encoding);
+        is_supported.then_some(Self {
+            encoding,
+            bom: false,
+        })
+    }
+
+    /// Returns every encoding that files can be reopened or saved with.
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::UTF8,
+            Self::UTF8.with_bom(true),
+            Self {
+                encoding: encoding_rs::UTF_16LE,
+                bom: true,
+            },
+            Self {
+                encoding: encoding_rs::UTF_16BE,
+                bom: true,
+            },
+        ]
+        .into_iter()
+        .chain(LEGACY_ENCODINGS.iter().map(|encoding| Self {
+            encoding,
+            bom: false,
+        }))
+    }
+
+    /// The canonical name of the encoding, not including whether it has a byte order mark.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Returns this encoding with or without a byte order mark. Only Unicode encodings have one.
+    pub fn with_bom(self, bom: bool) -> Self {
+        Self {
+            encoding: self.encoding,
+            bom: bom && !self.bom_bytes().is_empty(),
+        }
+    }
+
+    fn is_utf16(encoding: &encoding_rs::Encoding) -> bool {
+        encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == encoding_rs::UTF_8 {
+            b"\xEF\xBB\xBF"
+        } else if self.encoding == encoding_rs::UTF_16LE {
+            b"\xFF\xFE"
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            b"\xFE\xFF"
+        } else {
+            b""
+        }
+    }
+
+    /// Guesses the encoding of a file's contents, returning `None` if they look like binary data
+    /// rather than text.
+    ///
+    /// A byte order mark is trusted when there is one. Otherwise, UTF-16 is recognized by the
+    /// zero bytes in its ASCII characters, and contents that are neither UTF-16 nor UTF-8 are
+    /// assumed to be Windows-1252, which is a superset of Latin-1 that can decode any byte.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if let Some((encoding, _)) = encoding_rs::Encoding::for_bom(bytes) {
+            return Some(Self {
+                encoding,
+                bom: true,
+            });
+        }
+
+        if let Some(encoding) = detect_utf16(bytes) {
+            Some(Self {
+                encoding,
+                bom: false,
+            })
+        } else if std::str::from_utf8(bytes).is_ok() {
+            Some(Self::UTF8)
+        } else if bytes.contains(&0) {
+            None
+        } else {
+            Some(Self {
+                encoding: encoding_rs::WINDOWS_1252,
+                bom: false,
+            })
+        }
+    }
+
+    /// Decodes a file's contents, skipping the encoding's byte order mark if they start with one.
+    ///
+    /// Returns the text, along with this encoding recording whether there was a byte order mark.
+    /// Fails rather than replacing the bytes that aren't valid in this encoding, so that saving
+    /// the text can't corrupt the file.
+    pub fn decode(self, mut bytes: Vec<u8>) -> Result<(String, Self)> {
+        let bom = self.bom_bytes();
+        let has_bom = !bom.is_empty() && bytes.starts_with(bom);
+        if has_bom {
+            bytes.drain(..bom.len());
+        }
+        let encoding = self.with_bom(has_bom);
+
+        let text = if self.encoding == encoding_rs::UTF_8 {
+            String::from_utf8(bytes).map_err(|_| anyhow!("the file is not valid UTF-8"))?
+        } else {
+            self.encoding
+                .decode_without_bom_handling_and_without_replacement(&bytes)
+                .ok_or_else(|| anyhow!("the file is not valid {}", self.name()))?
+                .into_owned()
+        };
+        Ok((text, encoding))
+    }
+
+    /// Encodes text, prefixed with a byte order mark if this encoding has one.
+    ///
+    /// Fails if the text contains characters that this encoding can't represent.
+    pub fn encode<'a>(&self, text: &'a str) -> Result<Cow<'a, [u8]>> {
+        let content: Cow<'a, [u8]> = if self.encoding == encoding_rs::UTF_16LE {
+            text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+        } else if self.encoding == encoding_rs::UTF_16BE {
+            text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+        } else {
+            let (content, _, has_unmappable_characters) = self.encoding.encode(text);
+            if has_unmappable_characters {
+                return Err(anyhow!(
+                    "the text contains characters that can't be encoded in {}",
+                    self.name()
+                ));
+            }
+            content
+        };
+
+        if self.bom {
+            let mut bytes = self.bom_bytes().to_vec();
+            bytes.extend_from_slice(&content);
+            Ok(Cow::Owned(bytes))
+        } else {
+            Ok(content)
+        }
+    }
+}
+
+/// Recognizes UTF-16 without a byte order mark, in which mostly-ASCII text has a zero in every
+/// other byte.
+fn detect_utf16(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let sample = &bytes[..bytes.len().min(1024) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+
+    let (mut even_zeros, mut odd_zeros) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_zeros += (pair[0] == 0) as usize;
+        odd_zeros += (pair[1] == 0) as usize;
+    }
+
+    let pairs = sample.len() / 2;
+    let encoding = if odd_zeros * 2 > pairs && even_zeros * 10 < pairs {
+        encoding_rs::UTF_16LE
+    } else if even_zeros * 2 > pairs && odd_zeros * 10 < pairs {
+        encoding_rs::UTF_16BE
+    } else {
+        return None;
+    };
+    encoding
+        .decode_without_bom_handling_and_without_replacement(bytes)
+        .is_some()
+        .then_some(encoding)
+}
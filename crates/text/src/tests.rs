@@ -189,6 +189,48 @@ fn test_line_endings() {
     buffer.check_invariants();
 }
 
+#[test]
+fn test_encodings() {
+    let utf8 = "héllo wörld\n".as_bytes().to_vec();
+    assert_eq!(Encoding::detect(&utf8), Some(Encoding::UTF8));
+
+    // Byte order marks are skipped when decoding and restored when encoding.
+    let utf8_with_bom = [b"\xEF\xBB\xBF".as_slice(), &utf8].concat();
+    let encoding = Encoding::detect(&utf8_with_bom).unwrap();
+    assert_eq!(encoding, Encoding::UTF8.with_bom(true));
+    let (text, encoding) = encoding.decode(utf8_with_bom.clone()).unwrap();
+    assert_eq!(text, "héllo wörld\n");
+    assert_eq!(encoding.encode(&text).unwrap().as_ref(), utf8_with_bom);
+
+    // UTF-16 without a byte order mark is recognized by its zero bytes.
+    let utf16 = "hello\nworld\n"
+        .encode_utf16()
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+    let encoding = Encoding::detect(&utf16).unwrap();
+    assert_eq!(encoding, Encoding::from_name("UTF-16LE").unwrap());
+    let (text, encoding) = encoding.decode(utf16.clone()).unwrap();
+    assert_eq!(text, "hello\nworld\n");
+    assert_eq!(encoding.encode(&text).unwrap().as_ref(), utf16);
+
+    // Other files fall back to Windows-1252, which can decode and re-encode any byte.
+    let latin1 = b"caf\xE9 \x80\n".to_vec();
+    let encoding = Encoding::detect(&latin1).unwrap();
+    assert_eq!(encoding.name(), "windows-1252");
+    let (text, encoding) = encoding.decode(latin1.clone()).unwrap();
+    assert_eq!(text, "café €\n");
+    assert_eq!(encoding.encode(&text).unwrap().as_ref(), latin1);
+
+    let shift_jis = Encoding::from_name("shift_jis").unwrap();
+    let (text, _) = shift_jis.decode(b"\x82\xA0\n".to_vec()).unwrap();
+    assert_eq!(text, "あ\n");
+    assert_eq!(shift_jis.encode(&text).unwrap().as_ref(), b"\x82\xA0\n");
+    assert!(shift_jis.decode(b"\x82\n".to_vec()).is_err());
+    assert!(shift_jis.encode("é").is_err());
+
+    assert_eq!(Encoding::detect(b"\x00\x01\x02\xFF\xFE\x00\x80"), None);
+}
+
 #[test]
 fn test_line_len() {
     let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "".into());
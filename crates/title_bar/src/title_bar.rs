@@ -17,7 +17,7 @@ use gpui::{
     Interactivity, IntoElement, Model, MouseButton, ParentElement, Render, Stateful,
     StatefulInteractiveElement, Styled, Subscription, ViewContext, VisualContext, WeakView,
 };
-use project::{Project, RepositoryEntry};
+use project::{Project, RepositoryEntry, SshConnectionStatus};
 use recent_projects::RecentProjects;
 use rpc::proto::{self, DevServerStatus};
 use smallvec::SmallVec;
@@ -285,6 +285,39 @@ impl TitleBar {
             );
         }
 
+        if let Some(status) = self.project.read(cx).ssh_connection_status() {
+            let connection_string = self
+                .project
+                .read(cx)
+                .ssh_connection_string()
+                .unwrap_or_else(|| "SSH".into());
+            let (color, tooltip) = match status {
+                SshConnectionStatus::Connected => (Color::Created, "Connected over SSH"),
+                SshConnectionStatus::Reconnecting => {
+                    (Color::Warning, "Connection lost, reconnecting over SSH")
+                }
+                SshConnectionStatus::Disconnected => {
+                    (Color::Error, "Disconnected, could not reconnect over SSH")
+                }
+            };
+            return Some(
+                ButtonLike::new("ssh_server_trigger")
+                    .child(Indicator::dot().color(color))
+                    .child(
+                        Label::new(connection_string)
+                            .size(LabelSize::Small)
+                            .line_height_style(LineHeightStyle::UiLabel),
+                    )
+                    .tooltip(move |cx| Tooltip::text(tooltip, cx))
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(workspace) = this.workspace.upgrade() {
+                            recent_projects::PortForwards::open(workspace, cx)
+                        }
+                    }))
+                    .into_any_element(),
+            );
+        }
+
         if self.project.read(cx).is_disconnected() {
             return Some(
                 Button::new("disconnected", "Disconnected")
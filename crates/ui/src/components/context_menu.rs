@@ -200,8 +200,13 @@ impl ContextMenu {
         cx.emit(DismissEvent);
     }
 
-    fn select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+    pub fn select_first(&mut self) -> Option<usize> {
         self.selected_index = self.items.iter().position(|item| item.is_selectable());
+        self.selected_index
+    }
+
+    fn handle_select_first(&mut self, _: &SelectFirst, cx: &mut ViewContext<Self>) {
+        self.select_first();
         cx.notify();
     }
 
@@ -231,7 +236,7 @@ impl ContextMenu {
                 }
             }
         } else {
-            self.select_first(&Default::default(), cx);
+            self.handle_select_first(&Default::default(), cx);
         }
     }
 
@@ -317,7 +322,7 @@ impl Render for ContextMenu {
                     .track_focus(&self.focus_handle)
                     .on_mouse_down_out(cx.listener(|this, _, cx| this.cancel(&menu::Cancel, cx)))
                     .key_context("menu")
-                    .on_action(cx.listener(ContextMenu::select_first))
+                    .on_action(cx.listener(ContextMenu::handle_select_first))
                     .on_action(cx.listener(ContextMenu::handle_select_last))
                     .on_action(cx.listener(ContextMenu::select_next))
                     .on_action(cx.listener(ContextMenu::select_prev))
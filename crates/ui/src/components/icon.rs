@@ -153,6 +153,12 @@ pub enum IconName {
     Copy,
     CountdownTimer,
     Dash,
+    Debug,
+    DebugBreakpoint,
+    DebugPause,
+    DebugStepInto,
+    DebugStepOut,
+    DebugStepOver,
     Delete,
     Disconnected,
     Download,
@@ -313,6 +319,12 @@ impl IconName {
             IconName::Copy => "icons/copy.svg",
             IconName::CountdownTimer => "icons/countdown_timer.svg",
             IconName::Dash => "icons/dash.svg",
+            IconName::Debug => "icons/debug.svg",
+            IconName::DebugBreakpoint => "icons/debug_breakpoint.svg",
+            IconName::DebugPause => "icons/debug_pause.svg",
+            IconName::DebugStepInto => "icons/debug_step_into.svg",
+            IconName::DebugStepOut => "icons/debug_step_out.svg",
+            IconName::DebugStepOver => "icons/debug_step_over.svg",
             IconName::Delete => "icons/delete.svg",
             IconName::Disconnected => "icons/disconnected.svg",
             IconName::Download => "icons/download.svg",
@@ -2,7 +2,7 @@ use project::TaskSourceKind;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::ViewContext;
 
-use crate::Workspace;
+use crate::{notifications::NotifyTaskExt as _, Workspace};
 
 pub fn schedule_task(
     workspace: &Workspace,
@@ -33,14 +33,28 @@ pub fn schedule_resolved_task(
     cx: &mut ViewContext<'_, Workspace>,
 ) {
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
-        if !omit_history {
+        // Tasks with a debug configuration run under their debug adapter rather than in a terminal.
+        let is_debug_task = resolved_task.original_task().debug.is_some();
+        if is_debug_task || !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
+        }
+        if is_debug_task {
+            workspace
+                .project()
+                .update(cx, |project, cx| {
+                    project.start_debug_session(&resolved_task, cx)
+                })
+                .detach_and_notify_err(cx);
+        }
+        if !omit_history {
             workspace.project().update(cx, |project, cx| {
                 project.task_inventory().update(cx, |inventory, _| {
                     inventory.task_scheduled(task_source_kind, resolved_task);
                 })
             });
         }
-        cx.emit(crate::Event::SpawnTask(Box::new(spawn_in_terminal)));
+        if !is_debug_task {
+            cx.emit(crate::Event::SpawnTask(Box::new(spawn_in_terminal)));
+        }
     }
 }
@@ -828,6 +828,26 @@ impl Workspace {
                     )
                 }
 
+                project::Event::SshPortForwarded(forward) => {
+                    struct SshPortForwarded;
+
+                    let url = format!("http://localhost:{}", forward.local_port);
+                    this.show_notification(
+                        NotificationId::identified::<SshPortForwarded>(forward.local_port as usize),
+                        cx,
+                        |cx| {
+                            cx.new_view(|_| {
+                                MessageNotification::new(format!(
+                                    "Port {} of the remote host is forwarded to localhost:{}",
+                                    forward.remote_port, forward.local_port
+                                ))
+                                .with_click_message("Open in Browser")
+                                .on_click(move |cx| cx.open_url(&url))
+                            })
+                        },
+                    )
+                }
+
                 project::Event::LanguageServerPrompt(request) => {
                     struct LanguageServerPrompt;
 
@@ -5320,8 +5340,13 @@ pub fn create_and_open_local_file(
         let fs = workspace.update(&mut cx, |workspace, _| workspace.app_state().fs.clone())?;
         if !fs.is_file(path).await {
             fs.create_file(path, Default::default()).await?;
-            fs.save(path, &default_content(), Default::default())
-                .await?;
+            fs.save(
+                path,
+                &default_content(),
+                Default::default(),
+                Default::default(),
+            )
+            .await?;
         }
 
         let mut items = workspace
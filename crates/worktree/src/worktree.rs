@@ -57,7 +57,7 @@ use std::{
     time::{Duration, Instant, SystemTime},
 };
 use sum_tree::{Bias, Edit, SeekTarget, SumTree, TreeMap, TreeSet};
-use text::{LineEnding, Rope};
+use text::{Encoding, LineEnding, Rope};
 use util::{paths::home_dir, ResultExt};
 pub use worktree_settings::WorktreeSettings;
 
@@ -103,6 +103,7 @@ pub enum CreatedEntry {
 pub struct LoadedFile {
     pub file: Arc<File>,
     pub text: String,
+    pub encoding: Encoding,
     pub diff_base: Option<String>,
 }
 
@@ -231,6 +232,17 @@ impl RepositoryEntry {
             relativize_path(path)
         }
     }
+
+    /// The inverse of [`Self::relativize`]: returns the worktree path of a path relative to the
+    /// repository root, or `None` if that path lies outside of the worktree.
+    pub fn unrelativize(&self, worktree: &Snapshot, repo_path: &RepoPath) -> Option<Arc<Path>> {
+        let work_directory = worktree.entry_for_id(self.work_directory.0)?;
+        let path = match &self.location_in_repo {
+            Some(location_in_repo) => repo_path.strip_prefix(location_in_repo).ok()?,
+            None => repo_path.as_path(),
+        };
+        Some(work_directory.path.join(path).into())
+    }
 }
 
 impl From<&RepositoryEntry> for proto::RepositoryEntry {
@@ -368,6 +380,20 @@ impl Worktree {
         fs: Arc<dyn Fs>,
         next_entry_id: Arc<AtomicUsize>,
         cx: &mut AsyncAppContext,
+    ) -> Result<Model<Self>> {
+        Self::local_with_id(path, visible, fs, next_entry_id, |id| id, cx).await
+    }
+
+    /// Creates a local worktree whose id is given by `worktree_id`, from the id that would be
+    /// derived from the worktree's model. This lets a worktree keep an id that it was given
+    /// before, and other worktrees avoid it.
+    pub async fn local_with_id(
+        path: impl Into<Arc<Path>>,
+        visible: bool,
+        fs: Arc<dyn Fs>,
+        next_entry_id: Arc<AtomicUsize>,
+        worktree_id: impl FnOnce(WorktreeId) -> WorktreeId,
+        cx: &mut AsyncAppContext,
     ) -> Result<Model<Self>> {
         let abs_path = path.into();
         let metadata = fs
@@ -383,9 +409,9 @@ impl Worktree {
         });
 
         cx.new_model(move |cx: &mut ModelContext<Worktree>| {
-            let worktree_id = cx.handle().entity_id().as_u64();
+            let handle_id = cx.handle().entity_id().as_u64();
             let settings_location = Some(SettingsLocation {
-                worktree_id: worktree_id as usize,
+                worktree_id: handle_id as usize,
                 path: Path::new(EMPTY_PATH),
             });
 
@@ -405,7 +431,7 @@ impl Worktree {
                 ignores_by_parent_abs_path: Default::default(),
                 git_repositories: Default::default(),
                 snapshot: Snapshot::new(
-                    cx.entity_id().as_u64(),
+                    worktree_id(WorktreeId::from_usize(handle_id as usize)).to_proto(),
                     abs_path
                         .file_name()
                         .map_or(String::new(), |f| f.to_string_lossy().to_string()),
@@ -678,10 +704,11 @@ impl Worktree {
         path: &Path,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         match self {
-            Worktree::Local(this) => this.write_file(path, text, line_ending, cx),
+            Worktree::Local(this) => this.write_file(path, text, line_ending, encoding, cx),
             Worktree::Remote(_) => {
                 Task::ready(Err(anyhow!("remote worktree can't yet write files")))
             }
@@ -1204,7 +1231,7 @@ impl LocalWorktree {
 
         cx.spawn(|this, mut cx| async move {
             let abs_path = abs_path?;
-            let text = fs.load(&abs_path).await?;
+            let (text, encoding) = fs.load_with_encoding(&abs_path, None).await?;
             let mut index_task = None;
             let snapshot = this.update(&mut cx, |this, _| this.as_local().unwrap().snapshot())?;
             if let Some(repo) = snapshot.repository_for_path(&path) {
@@ -1255,6 +1282,7 @@ impl LocalWorktree {
             Ok(LoadedFile {
                 file,
                 text,
+                encoding,
                 diff_base,
             })
         })
@@ -1293,9 +1321,14 @@ impl LocalWorktree {
                     .await
                     .with_context(|| format!("creating directory {task_abs_path:?}"))
             } else {
-                fs.save(&task_abs_path, &Rope::default(), LineEnding::default())
-                    .await
-                    .with_context(|| format!("creating file {task_abs_path:?}"))
+                fs.save(
+                    &task_abs_path,
+                    &Rope::default(),
+                    LineEnding::default(),
+                    Default::default(),
+                )
+                .await
+                .with_context(|| format!("creating file {task_abs_path:?}"))
             }
         });
 
@@ -1342,6 +1375,7 @@ impl LocalWorktree {
         path: impl Into<Arc<Path>>,
         text: Rope,
         line_ending: LineEnding,
+        encoding: Encoding,
         cx: &mut ModelContext<Worktree>,
     ) -> Task<Result<Arc<File>>> {
         let path = path.into();
@@ -1354,7 +1388,7 @@ impl LocalWorktree {
         let write = cx.background_executor().spawn({
             let fs = fs.clone();
             let abs_path = abs_path.clone();
-            async move { fs.save(&abs_path, &text, line_ending).await }
+            async move { fs.save(&abs_path, &text, line_ending, encoding).await }
         });
 
         cx.spawn(move |this, mut cx| async move {
@@ -1701,7 +1735,7 @@ impl LocalWorktree {
             .unbounded_send((self.snapshot(), Arc::default(), Arc::default()))
             .ok();
 
-        let worktree_id = cx.entity_id().as_u64();
+        let worktree_id = self.id().to_proto();
         let _maintain_remote_snapshot = cx.background_executor().spawn(async move {
             let mut is_first = true;
             while let Some((snapshot, entry_changes, repo_changes)) = snapshots_rx.next().await {
@@ -1760,6 +1794,21 @@ impl RemoteWorktree {
         self.disconnected = true;
     }
 
+    /// Discards the entries of the worktree, for a host that sends them again from its first
+    /// scan, as one that has restarted does. The worktree keeps its id.
+    pub fn reset_snapshot(&mut self) {
+        let snapshot = Snapshot::new(
+            self.snapshot.id.to_proto(),
+            self.snapshot.root_name.clone(),
+            self.snapshot.abs_path.clone(),
+        );
+        let mut background_snapshot = self.background_snapshot.lock();
+        background_snapshot.0 = snapshot.clone();
+        background_snapshot.1.clear();
+        self.snapshot = snapshot;
+        self.snapshot_subscriptions.clear();
+    }
+
     pub fn update_from_remote(&mut self, update: proto::UpdateWorktree) {
         if let Some(updates_tx) = &self.updates_tx {
             updates_tx
@@ -3072,12 +3121,12 @@ impl language::LocalFile for File {
         }
     }
 
-    fn load(&self, cx: &AppContext) -> Task<Result<String>> {
+    fn load(&self, encoding: Encoding, cx: &AppContext) -> Task<Result<(String, Encoding)>> {
         let worktree = self.worktree.read(cx).as_local().unwrap();
         let abs_path = worktree.absolutize(&self.path);
         let fs = worktree.fs.clone();
         cx.background_executor()
-            .spawn(async move { fs.load(&abs_path?).await })
+            .spawn(async move { fs.load_with_encoding(&abs_path?, Some(encoding)).await })
     }
 }
 
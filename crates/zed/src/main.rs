@@ -221,6 +221,7 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
     editor::init(cx);
     image_viewer::init(cx);
     diagnostics::init(cx);
+    diff_view::init(cx);
 
     audio::init(Assets, cx);
     workspace::init(app_state.clone(), cx);
@@ -234,6 +235,9 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
     project_symbols::init(cx);
     project_panel::init(Assets, cx);
     outline_panel::init(Assets, cx);
+    hierarchy_panel::init(cx);
+    debugger_ui::init(cx);
+    git_ui::init(cx);
     tasks_ui::init(cx);
     channel::init(&app_state.client.clone(), app_state.user_store.clone(), cx);
     search::init(cx);
@@ -241,6 +245,7 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
     terminal_view::init(cx);
     journal::init(app_state.clone(), cx);
     language_selector::init(cx);
+    encoding_selector::init(cx);
     theme_selector::init(cx);
     language_tools::init(cx);
     call::init(app_state.client.clone(), app_state.user_store.clone(), cx);
@@ -19,7 +19,10 @@ pub use open_listener::*;
 
 use anyhow::Context as _;
 use assets::Assets;
+use debugger_ui::DebugPanel;
 use futures::{channel::mpsc, select_biased, StreamExt};
+use git_ui::GitPanel;
+use hierarchy_panel::HierarchyPanel;
 use outline_panel::OutlinePanel;
 use project::TaskSourceKind;
 use project_panel::ProjectPanel;
@@ -192,6 +195,8 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             activity_indicator::ActivityIndicator::new(workspace, app_state.languages.clone(), cx);
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
+        let active_buffer_encoding =
+            cx.new_view(|_| encoding_selector::ActiveBufferEncoding::new());
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
         let cursor_position =
             cx.new_view(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
@@ -199,6 +204,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
             status_bar.add_right_item(inline_completion_button, cx);
+            status_bar.add_right_item(active_buffer_encoding, cx);
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
@@ -244,7 +250,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
 
             let project_panel = ProjectPanel::load(workspace_handle.clone(), cx.clone());
             let outline_panel = OutlinePanel::load(workspace_handle.clone(), cx.clone());
+            let hierarchy_panel = HierarchyPanel::load(workspace_handle.clone(), cx.clone());
+            let git_panel = GitPanel::load(workspace_handle.clone(), cx.clone());
             let terminal_panel = TerminalPanel::load(workspace_handle.clone(), cx.clone());
+            let debug_panel = DebugPanel::load(workspace_handle.clone(), cx.clone());
             let channels_panel =
                 collab_ui::collab_panel::CollabPanel::load(workspace_handle.clone(), cx.clone());
             let chat_panel =
@@ -257,7 +266,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             let (
                 project_panel,
                 outline_panel,
+                hierarchy_panel,
+                git_panel,
                 terminal_panel,
+                debug_panel,
                 assistant_panel,
                 channels_panel,
                 chat_panel,
@@ -265,7 +277,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
             ) = futures::try_join!(
                 project_panel,
                 outline_panel,
+                hierarchy_panel,
+                git_panel,
                 terminal_panel,
+                debug_panel,
                 assistant_panel,
                 channels_panel,
                 chat_panel,
@@ -276,7 +291,10 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 workspace.add_panel(assistant_panel, cx);
                 workspace.add_panel(project_panel, cx);
                 workspace.add_panel(outline_panel, cx);
+                workspace.add_panel(hierarchy_panel, cx);
+                workspace.add_panel(git_panel, cx);
                 workspace.add_panel(terminal_panel, cx);
+                workspace.add_panel(debug_panel, cx);
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
                 workspace.add_panel(notification_panel, cx);
@@ -3056,6 +3074,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3075,6 +3094,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3127,6 +3147,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3152,6 +3173,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3196,6 +3218,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3214,6 +3237,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3261,6 +3285,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3286,6 +3311,7 @@ mod tests {
                 "#
                 .into(),
                 Default::default(),
+                Default::default(),
             )
             .await
             .unwrap();
@@ -3462,6 +3488,10 @@ mod tests {
             collab_ui::init(&app_state, cx);
             project_panel::init((), cx);
             outline_panel::init((), cx);
+            hierarchy_panel::init(cx);
+            debugger_ui::init(cx);
+            git_ui::init(cx);
+            diff_view::init(cx);
             terminal_view::init(cx);
             copilot::copilot_chat::init(
                 app_state.fs.clone(),
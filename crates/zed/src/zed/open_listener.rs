@@ -78,6 +78,7 @@ impl OpenRequest {
             password,
             host,
             port,
+            port_forwards: Vec::new(),
         };
         if let Some(ssh_connection) = &self.ssh_connection {
             if *ssh_connection != connection {